@@ -122,11 +122,19 @@ fn main() -> Result {
                 let ftl = include_str!("../assets/locales/en-US/main.ftl");
                 app.shell.localization =
                     peak_ui::prelude::Localization::new("en-US", vec![ftl.to_string()]);
+                let restore = app.restore_conversations();
+                let models = app.restore_models();
+                let tools = app.connect_tools();
                 (
                     app,
-                    Task::perform(async {}, |_| {
-                        peak_ui::reference::Message::ApplyNativeVibrancy
-                    }),
+                    Task::batch([
+                        Task::perform(async {}, |_| {
+                            peak_ui::reference::Message::ApplyNativeVibrancy
+                        }),
+                        restore,
+                        models,
+                        tools,
+                    ]),
                 )
             },
             reference::App::update,
@@ -229,7 +237,10 @@ pub fn run() {
             app.shell.localization =
                 peak_ui::prelude::Localization::new("en-US", vec![ftl.to_string()]);
 
-            (app, Task::none())
+            let restore = app.restore_conversations();
+            let models = app.restore_models();
+            let tools = app.connect_tools();
+            (app, Task::batch([restore, models, tools]))
         },
         reference::App::update,
         reference::App::view,
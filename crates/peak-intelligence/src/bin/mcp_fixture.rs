@@ -0,0 +1,113 @@
+//! Minimal stdio MCP server used to exercise `mcp::client` end to end.
+//!
+//! Offers `echo`, `add` and `fail`, and serves them through the same
+//! `mcp::Server` as the main binary.
+use anyhow::{bail, Result};
+use async_trait::async_trait;
+use peak_intelligence::mcp::{JsonRpcRequest, JsonRpcResponse, Server, PARSE_ERROR};
+use peak_intelligence::tools::registry::{IntelligenceTool, ToolRegistry};
+use serde_json::{json, Value};
+use std::sync::Arc;
+use tokio::io::{self, AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::sync::mpsc;
+
+struct Echo;
+
+#[async_trait]
+impl IntelligenceTool for Echo {
+    fn name(&self) -> &str {
+        "echo"
+    }
+    fn description(&self) -> &str {
+        "Returns its arguments"
+    }
+    fn input_schema(&self) -> Value {
+        json!({ "type": "object" })
+    }
+    async fn execute(&self, args: Value) -> Result<Value> {
+        Ok(args)
+    }
+}
+
+struct Add;
+
+#[async_trait]
+impl IntelligenceTool for Add {
+    fn name(&self) -> &str {
+        "add"
+    }
+    fn description(&self) -> &str {
+        "Adds two integers"
+    }
+    fn input_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": { "a": { "type": "integer" }, "b": { "type": "integer" } },
+            "required": ["a", "b"]
+        })
+    }
+    async fn execute(&self, args: Value) -> Result<Value> {
+        match (args["a"].as_i64(), args["b"].as_i64()) {
+            (Some(a), Some(b)) => Ok(json!(a + b)),
+            _ => bail!("'a' and 'b' must be integers"),
+        }
+    }
+}
+
+struct Fail;
+
+#[async_trait]
+impl IntelligenceTool for Fail {
+    fn name(&self) -> &str {
+        "fail"
+    }
+    fn description(&self) -> &str {
+        "Always fails"
+    }
+    fn input_schema(&self) -> Value {
+        json!({ "type": "object" })
+    }
+    async fn execute(&self, _args: Value) -> Result<Value> {
+        bail!("fixture failure")
+    }
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let mut registry = ToolRegistry::new();
+    registry.register(Echo);
+    registry.register(Add);
+    registry.register(Fail);
+
+    let server = Arc::new(Server::new(registry));
+    let (tx, mut rx) = mpsc::channel::<String>(100);
+
+    tokio::spawn(async move {
+        while let Some(msg) = rx.recv().await {
+            let mut stdout = io::stdout();
+            let _ = stdout.write_all(format!("{}\n", msg).as_bytes()).await;
+            let _ = stdout.flush().await;
+        }
+    });
+
+    let mut lines = BufReader::new(io::stdin()).lines();
+
+    while let Some(line) = lines.next_line().await? {
+        let response = match serde_json::from_str::<JsonRpcRequest>(&line) {
+            Ok(request) => {
+                let (server, tx) = (server.clone(), tx.clone());
+                tokio::spawn(async move {
+                    if let Some(response) = server.handle(request).await {
+                        let _ = tx.send(json!(response).to_string()).await;
+                    }
+                });
+                continue;
+            }
+            Err(_) => JsonRpcResponse::error(None, PARSE_ERROR, "Parse error".into()),
+        };
+
+        tx.send(json!(response).to_string()).await?;
+    }
+
+    Ok(())
+}
@@ -275,6 +275,7 @@ impl Assistant {
                         Reply {
                             reasoning: reasoning.clone(),
                             content: content.trim().to_owned(),
+                            citations: Vec::new(),
                             last_token: if let Token::Talking(token) = &token {
                                 Some(token.clone())
                             } else {
@@ -289,6 +290,7 @@ impl Assistant {
             Ok(Reply {
                 reasoning: reasoning.clone(),
                 content: content.trim().to_owned(),
+                citations: Vec::new(),
                 last_token: None,
             })
         })
@@ -460,9 +462,20 @@ impl Message {
 pub struct Reply {
     pub reasoning: Option<Reasoning>,
     pub content: String,
+    /// Memory records the reply refers to, in the order they were cited.
+    pub citations: Vec<Citation>,
     pub last_token: Option<String>,
 }
 
+/// A memory record a [`Reply`] refers to with an inline marker like `[2]`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Citation {
+    pub index: usize,
+    pub record_id: String,
+    pub collection: String,
+    pub excerpt: String,
+}
+
 #[derive(Debug, Clone)]
 pub struct Reasoning {
     pub content: String,
@@ -1,9 +1,8 @@
 #![allow(dead_code)]
 mod schema;
+mod storage;
 
 use crate::brain::assistant::{self, Assistant, Reply, Token};
-#[cfg(feature = "native")]
-use crate::brain::directory;
 use crate::brain::model;
 use crate::brain::plan::{self, Plan};
 use crate::brain::Error;
@@ -11,14 +10,11 @@ use crate::brain::Error;
 use serde::{Deserialize, Serialize};
 use sipper::{sipper, Sipper, Straw};
 #[cfg(feature = "native")]
-use tokio::fs;
-#[cfg(feature = "native")]
 use tokio::task;
 use uuid::Uuid;
 
 use std::fmt;
-use std::io;
-use std::path::PathBuf;
+use std::str::FromStr;
 
 #[derive(Clone)]
 pub struct Chat {
@@ -31,14 +27,14 @@ pub struct Chat {
 #[derive(Debug, Clone)]
 pub enum Item {
     User(String),
+    System(String),
     Reply(Reply),
     Plan(Plan),
 }
 
 impl Chat {
-    #[cfg(feature = "native")]
-    async fn path(id: &Id) -> Result<PathBuf, Error> {
-        Ok(storage_dir().await?.join(format!("{}.json", id.0.simple())))
+    fn file_name(id: &Id) -> String {
+        format!("{}.json", id.0.simple())
     }
 
     pub async fn list() -> Result<Vec<Entry>, Error> {
@@ -48,19 +44,11 @@ impl Chat {
     }
 
     pub async fn fetch(id: Id) -> Result<Self, Error> {
-        #[cfg(feature = "native")]
-        {
-            let json = fs::read_to_string(Self::path(&id).await?).await?;
+        let chat = Self::load(id).await?;
 
-            let _ = LastOpened::update(id).await;
+        let _ = LastOpened::update(id).await;
 
-            task::spawn_blocking(move || schema::decode(&json)).await?
-        }
-        #[cfg(not(feature = "native"))]
-        {
-            let _ = id;
-            Err(Error::WasmError("Storage not supported".to_string()))
-        }
+        Ok(chat)
     }
 
     pub async fn fetch_last_opened() -> Result<Self, Error> {
@@ -69,6 +57,12 @@ impl Chat {
         Self::fetch(id).await
     }
 
+    async fn load(id: Id) -> Result<Self, Error> {
+        let json = storage::read(&Self::file_name(&id)).await?;
+
+        blocking(move || schema::decode(&json)).await?
+    }
+
     pub async fn create(
         file: model::File,
         title: Option<String>,
@@ -80,81 +74,66 @@ impl Chat {
             file,
             title,
             history,
-        };
-
-        #[cfg(not(target_arch = "wasm32"))]
-        {
-            let chat = chat.save().await?;
-            LastOpened::update(chat.id).await?;
-
-            List::push(Entry {
-                id: chat.id,
-                file: chat.file.clone(),
-                title: chat.title.clone(),
-            })
-            .await?;
-            Ok(chat)
         }
+        .save()
+        .await?;
 
-        #[cfg(target_arch = "wasm32")]
-        {
-            Ok(chat)
-        }
+        LastOpened::update(chat.id).await?;
+
+        List::push(Entry {
+            id: chat.id,
+            file: chat.file.clone(),
+            title: chat.title.clone(),
+        })
+        .await?;
+
+        Ok(chat)
     }
 
     pub async fn save(self) -> Result<Self, Error> {
-        #[cfg(feature = "native")]
-        {
-            if let Ok(current) = Self::fetch(self.id).await {
-                if current.title != self.title {
-                    let mut list = List::fetch().await?;
-
-                    if let Some(entry) = list.entries.iter_mut().find(|entry| entry.id == self.id) {
-                        entry.title = self.title.clone();
-                    }
+        let mut list = List::fetch().await?;
 
-                    list.save().await?;
-                }
+        if let Some(entry) = list.entries.iter_mut().find(|entry| entry.id == self.id) {
+            if entry.title != self.title {
+                entry.title = self.title.clone();
+                list.save().await?;
             }
+        }
 
-            let (bytes, chat) = task::spawn_blocking(move || (schema::encode(&self), self)).await?;
+        let (json, chat) = blocking(move || (schema::encode(&self), self)).await?;
 
-            fs::write(Self::path(&chat.id).await?, bytes?).await?;
+        storage::write(&Self::file_name(&chat.id), json?).await?;
 
-            Ok(chat)
-        }
-        #[cfg(not(feature = "native"))]
-        {
-            Ok(self)
-        }
+        Ok(chat)
+    }
+
+    /// Changes the title of a stored chat without marking it as last opened.
+    pub async fn rename(id: Id, title: Option<String>) -> Result<Self, Error> {
+        let mut chat = Self::load(id).await?;
+        chat.title = title;
+
+        chat.save().await
     }
 
     pub async fn delete(id: Id) -> Result<(), Error> {
-        #[cfg(feature = "native")]
-        {
-            fs::remove_file(Self::path(&id).await?).await?;
-
-            let _ = List::remove(&id).await;
-
-            match LastOpened::fetch().await {
-                Ok(LastOpened(last_opened)) if id == last_opened => {
-                    let list = List::fetch().await.ok();
-
-                    match list.as_ref().and_then(|list| list.entries.first()) {
-                        Some(entry) => {
-                            LastOpened::update(entry.id).await?;
-                        }
-                        None => {
-                            LastOpened::delete().await?;
-                        }
+        storage::remove(&Self::file_name(&id)).await?;
+
+        let _ = List::remove(&id).await;
+
+        match LastOpened::fetch().await {
+            Ok(LastOpened(last_opened)) if id == last_opened => {
+                let list = List::fetch().await.ok();
+
+                match list.as_ref().and_then(|list| list.entries.first()) {
+                    Some(entry) => {
+                        LastOpened::update(entry.id).await?;
+                    }
+                    None => {
+                        LastOpened::delete().await?;
                     }
                 }
-                _ => {}
             }
-        }
-        #[cfg(not(feature = "native"))]
-        {
-            let _ = id;
+            _ => {}
         }
 
         Ok(())
@@ -228,24 +207,21 @@ fn reply<'a>(
     })
 }
 
+const TITLE_REQUEST: &str = "Give me a short title for our conversation so far, \
+                             without considering this interaction. \
+                             Just the title between quotes; don't say anything else.";
+
+const TITLE_MAX_LEN: usize = 80;
+
 pub fn title(assistant: &Assistant, items: &[Item]) -> impl Straw<String, String, Error> {
     let assistant = assistant.clone();
     let history = history(items);
 
     sipper(move |mut sender| async move {
-        let request = [assistant::Message::User(
-            "Give me a short title for our conversation so far, \
-                    without considering this interaction. \
-                    Just the title between quotes; don't say anything else."
-                .to_owned(),
-        )];
+        let request = [assistant::Message::User(TITLE_REQUEST.to_owned())];
 
         let mut title = String::new();
 
-        fn sanitize(title: &str) -> String {
-            title.trim().trim_matches('"').to_owned()
-        }
-
         let mut completion = assistant
             .clone()
             .complete(SYSTEM_PROMPT, history.clone(), request.to_vec())
@@ -256,19 +232,35 @@ pub fn title(assistant: &Assistant, items: &[Item]) -> impl Straw<String, String
                 title.push_str(&token);
             }
 
-            let is_too_long = title.len() > 80;
-
-            if is_too_long {
-                title.push_str("...");
-            }
-
-            sender.send(sanitize(&title)).await;
+            sender.send(sanitize_title(&title)).await;
         }
 
-        Ok(sanitize(&title))
+        Ok(sanitize_title(&title))
     })
 }
 
+/// The full conversation [`title`] sends, for clients that complete through
+/// a provider other than a local [`Assistant`]. Pass the reply through
+/// [`sanitize_title`].
+pub fn title_messages(items: &[Item]) -> Vec<assistant::Message> {
+    std::iter::once(assistant::Message::System(SYSTEM_PROMPT.to_owned()))
+        .chain(history(items))
+        .chain(std::iter::once(assistant::Message::User(
+            TITLE_REQUEST.to_owned(),
+        )))
+        .collect()
+}
+
+/// Strips the quotes the model wraps titles in and truncates long ones.
+pub fn sanitize_title(title: &str) -> String {
+    let title = title.trim().trim_matches('"').trim();
+
+    match title.char_indices().nth(TITLE_MAX_LEN) {
+        Some((end, _)) => format!("{}...", title[..end].trim_end()),
+        None => title.to_owned(),
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Id(Uuid);
 
@@ -290,6 +282,20 @@ impl Id {
     }
 }
 
+impl fmt::Display for Id {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl FromStr for Id {
+    type Err = uuid::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Uuid::parse_str(s).map(Self)
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Entry {
     pub id: Id,
@@ -303,29 +309,16 @@ struct List {
 }
 
 impl List {
-    async fn path() -> Result<PathBuf, io::Error> {
-        Ok(storage_dir().await?.join("list.json"))
-    }
+    const FILE: &'static str = "list.json";
 
     async fn fetch() -> Result<Self, Error> {
-        #[cfg(feature = "native")]
-        {
-            let path = Self::path().await?;
-            let bytes = fs::read(&path).await;
-
-            let Ok(bytes) = bytes else {
-                return Ok(List::default());
-            };
-
-            Ok(
-                task::spawn_blocking(move || serde_json::from_slice(&bytes).ok())
-                    .await?
-                    .unwrap_or_default(),
-            )
-        }
+        let Ok(json) = storage::read(Self::FILE).await else {
+            return Ok(List::default());
+        };
 
-        #[cfg(not(feature = "native"))]
-        Ok(List::default())
+        Ok(blocking(move || serde_json::from_str(&json).ok())
+            .await?
+            .unwrap_or_default())
     }
 
     async fn push(entry: Entry) -> Result<(), Error> {
@@ -335,7 +328,6 @@ impl List {
         list.save().await
     }
 
-    #[cfg(feature = "native")]
     async fn remove(id: &Id) -> Result<(), Error> {
         let mut list = List::fetch().await?;
         list.entries.retain(|entry| &entry.id != id);
@@ -344,14 +336,9 @@ impl List {
     }
 
     async fn save(self) -> Result<(), Error> {
-        #[cfg(feature = "native")]
-        {
-            let json = task::spawn_blocking(move || serde_json::to_vec_pretty(&self)).await?;
-
-            fs::write(Self::path().await?, json?).await?;
-        }
+        let json = blocking(move || serde_json::to_string_pretty(&self)).await?;
 
-        Ok(())
+        storage::write(Self::FILE, json?).await
     }
 }
 
@@ -359,64 +346,35 @@ impl List {
 struct LastOpened(Id);
 
 impl LastOpened {
-    async fn path() -> Result<PathBuf, io::Error> {
-        Ok(storage_dir().await?.join("last_opened.json"))
-    }
+    const FILE: &'static str = "last_opened.json";
 
     async fn fetch() -> Result<Self, Error> {
-        let path = Self::path().await?;
-        #[cfg(feature = "native")]
-        {
-            let bytes = fs::read(path).await?;
-            Ok(serde_json::from_slice(&bytes)?)
-        }
+        let json = storage::read(Self::FILE).await?;
 
-        #[cfg(not(feature = "native"))]
-        {
-            let _ = path;
-            Err(Error::WasmError("Storage not supported".to_string()))
-        }
+        Ok(serde_json::from_str(&json)?)
     }
 
     async fn update(id: Id) -> Result<(), Error> {
-        #[cfg(feature = "native")]
-        {
-            let json = serde_json::to_vec(&LastOpened(id))?;
-
-            fs::write(Self::path().await?, json).await?;
-        }
+        let json = serde_json::to_string(&LastOpened(id))?;
 
-        #[cfg(not(feature = "native"))]
-        let _ = id;
-
-        Ok(())
+        storage::write(Self::FILE, json).await
     }
 
-    #[cfg(feature = "native")]
     async fn delete() -> Result<(), Error> {
-        #[cfg(feature = "native")]
-        fs::remove_file(Self::path().await?).await?;
-
-        Ok(())
+        storage::remove(Self::FILE).await
     }
 }
 
-async fn storage_dir() -> Result<PathBuf, io::Error> {
+/// Runs encoding/decoding off the async executor where threads are available.
+async fn blocking<T: Send + 'static>(f: impl FnOnce() -> T + Send + 'static) -> Result<T, Error> {
     #[cfg(feature = "native")]
     {
-        let directory = directory::data().join("chats");
-
-        fs::create_dir_all(&directory).await?;
-
-        return Ok(directory);
+        Ok(task::spawn_blocking(f).await?)
     }
 
     #[cfg(not(feature = "native"))]
     {
-        Err(io::Error::new(
-            io::ErrorKind::Other,
-            "WASM storage not supported",
-        ))
+        Ok(f())
     }
 }
 
@@ -425,6 +383,7 @@ fn history(items: &[Item]) -> Vec<assistant::Message> {
         .iter()
         .flat_map(|item| match item {
             Item::User(query) => vec![assistant::Message::User(query.clone())],
+            Item::System(message) => vec![assistant::Message::System(message.clone())],
             Item::Reply(reply) => vec![assistant::Message::Assistant(reply.content.clone())],
             Item::Plan(plan) => plan
                 .answers()
@@ -433,3 +392,82 @@ fn history(items: &[Item]) -> Vec<assistant::Message> {
         })
         .collect()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sanitize_title_strips_quotes_and_truncates() {
+        assert_eq!(sanitize_title("  \"Rust lifetimes\"\n"), "Rust lifetimes");
+
+        let long = "a".repeat(TITLE_MAX_LEN + 10);
+        let title = sanitize_title(&long);
+        assert_eq!(title.chars().count(), TITLE_MAX_LEN + 3);
+        assert!(title.ends_with("..."));
+    }
+
+    #[test]
+    fn test_system_items_survive_encoding() {
+        let chat = Chat {
+            id: Id(Uuid::new_v4()),
+            file: model::File {
+                model: model::Id("remote/llama3".to_owned()),
+                name: "llama3".to_owned(),
+                size: None,
+            },
+            title: None,
+            history: vec![
+                Item::User("list my files".to_owned()),
+                Item::System("read_dir returned 3 entries".to_owned()),
+            ],
+        };
+
+        let decoded = schema::decode(&schema::encode(&chat).unwrap()).unwrap();
+
+        assert_eq!(decoded.id, chat.id);
+        assert!(matches!(
+            decoded.history.as_slice(),
+            [Item::User(_), Item::System(message)] if message == "read_dir returned 3 entries"
+        ));
+    }
+
+    #[test]
+    fn test_reply_citations_survive_encoding() {
+        let citation = assistant::Citation {
+            index: 1,
+            record_id: "note-1".to_owned(),
+            collection: "notes".to_owned(),
+            excerpt: "Peak ships on Fridays".to_owned(),
+        };
+        let chat = Chat {
+            id: Id(Uuid::new_v4()),
+            file: model::File {
+                model: model::Id("remote/llama3".to_owned()),
+                name: "llama3".to_owned(),
+                size: None,
+            },
+            title: None,
+            history: vec![Item::Reply(Reply {
+                content: "On Fridays [1].".to_owned(),
+                citations: vec![citation.clone()],
+                ..Reply::default()
+            })],
+        };
+
+        let decoded = schema::decode(&schema::encode(&chat).unwrap()).unwrap();
+
+        assert!(matches!(
+            decoded.history.as_slice(),
+            [Item::Reply(reply)] if reply.citations == [citation]
+        ));
+    }
+
+    #[test]
+    fn test_ids_round_trip_through_strings() {
+        let id = Id(Uuid::new_v4());
+
+        assert_eq!(id.to_string().parse::<Id>().unwrap(), id);
+        assert!("not-a-uuid".parse::<Id>().is_err());
+    }
+}
@@ -1,11 +1,12 @@
-use crate::brain::assistant::{Reasoning, Reply};
+use crate::brain::assistant::{Citation, Reasoning, Reply};
 use crate::brain::chat;
 use crate::brain::model;
 use crate::brain::plan;
+use crate::brain::readable;
 use crate::brain::web;
 use crate::brain::{Chat, Plan, Url};
 
-use decoder::decode::{duration, map, sequence, string};
+use decoder::decode::{duration, map, sequence, string, u64};
 use decoder::{Decoder, Error, Result, Value};
 
 pub fn chat(value: Value) -> Result<Chat> {
@@ -26,6 +27,7 @@ fn item(value: Value) -> Result<chat::Item> {
 
     let item = match type_.as_str() {
         "user" => chat::Item::User(item.required("message", string)?),
+        "system" => chat::Item::System(item.required("message", string)?),
         "reply" => chat::Item::Reply(reply(item.into_value())?),
         "plan" => chat::Item::Plan(plan(item.into_value())?),
         _ => {
@@ -42,10 +44,24 @@ fn reply(value: Value) -> Result<Reply> {
     Ok(Reply {
         reasoning: reply.optional("reasoning", reasoning)?,
         content: reply.required("content", string)?,
+        citations: reply
+            .optional("citations", sequence(citation))?
+            .unwrap_or_default(),
         last_token: None,
     })
 }
 
+fn citation(value: Value) -> Result<Citation> {
+    let mut citation = map(value)?;
+
+    Ok(Citation {
+        index: citation.required("index", u64)? as usize,
+        record_id: citation.required("record_id", string)?,
+        collection: citation.required("collection", string)?,
+        excerpt: citation.required("excerpt", string)?,
+    })
+}
+
 fn reasoning(value: Value) -> Result<Reasoning> {
     let mut reasoning = map(value)?;
 
@@ -118,6 +134,20 @@ fn web_summary(value: Value) -> Result<web::Summary> {
     Ok(web::Summary {
         url: summary.required("url", url)?,
         content: summary.required("content", string)?,
+        passages: summary
+            .optional("passages", sequence(passage))?
+            .unwrap_or_default(),
+    })
+}
+
+fn passage(value: Value) -> Result<readable::Chunk> {
+    let mut passage = map(value)?;
+
+    Ok(readable::Chunk {
+        index: passage.required("index", u64)? as usize,
+        start: passage.required("start", u64)? as usize,
+        end: passage.required("end", u64)? as usize,
+        text: passage.required("text", string)?,
     })
 }
 
@@ -1,10 +1,11 @@
-use crate::brain::assistant::{Reasoning, Reply};
+use crate::brain::assistant::{Citation, Reasoning, Reply};
 use crate::brain::chat;
 use crate::brain::plan;
+use crate::brain::readable;
 use crate::brain::web;
 use crate::brain::{Chat, Plan, Url};
 
-use decoder::encode::{duration, map, optional, sequence, string};
+use decoder::encode::{duration, map, optional, sequence, string, u64};
 use decoder::{Map, Value};
 use function::Binary;
 
@@ -21,6 +22,7 @@ pub fn chat(chat: Chat) -> Value {
 fn item(item: chat::Item) -> Map {
     let (type_, item) = match item {
         chat::Item::User(message) => ("user", map([("message", string(message))])),
+        chat::Item::System(message) => ("system", map([("message", string(message))])),
         chat::Item::Reply(reply_) => ("reply", reply(reply_)),
         chat::Item::Plan(plan_) => ("plan", plan(plan_)),
     };
@@ -32,6 +34,16 @@ fn reply(reply: Reply) -> Map {
     map([
         ("reasoning", optional(reasoning, reply.reasoning)),
         ("content", string(reply.content)),
+        ("citations", sequence(citation, reply.citations)),
+    ])
+}
+
+fn citation(citation: Citation) -> Map {
+    map([
+        ("index", u64(citation.index as u64)),
+        ("record_id", string(citation.record_id)),
+        ("collection", string(citation.collection)),
+        ("excerpt", string(citation.excerpt)),
     ])
 }
 
@@ -89,6 +101,16 @@ fn web_summary(summary: web::Summary) -> Map {
     map([
         ("url", url(summary.url)),
         ("content", string(summary.content)),
+        ("passages", sequence(passage, summary.passages)),
+    ])
+}
+
+fn passage(passage: readable::Chunk) -> Map {
+    map([
+        ("index", u64(passage.index as u64)),
+        ("start", u64(passage.start as u64)),
+        ("end", u64(passage.end as u64)),
+        ("text", string(passage.text)),
     ])
 }
 
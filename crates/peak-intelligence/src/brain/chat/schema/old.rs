@@ -60,6 +60,7 @@ impl Reply {
                 })
             },
             content: self.content,
+            citations: Vec::new(),
             last_token: None,
         }
     }
@@ -150,6 +151,7 @@ impl Outcome {
                     WebSummaries::Uknown(lines) => vec![web::Summary {
                         url: Url::parse("https://unknown.com/").expect("Parse URL"),
                         content: lines.join("\n"),
+                        passages: Vec::new(),
                     }],
                 },
             ))),
@@ -203,6 +205,7 @@ impl WebSummary {
         web::Summary {
             url: self.url,
             content: self.content,
+            passages: Vec::new(),
         }
     }
 }
@@ -0,0 +1,99 @@
+//! Chat persistence backend: JSON files under the data directory on native,
+//! `localStorage` in the browser.
+use crate::brain::Error;
+
+#[cfg(feature = "native")]
+use crate::brain::directory;
+#[cfg(feature = "native")]
+use tokio::fs;
+
+use std::io;
+#[cfg(feature = "native")]
+use std::path::PathBuf;
+
+#[cfg(all(not(feature = "native"), target_arch = "wasm32"))]
+const KEY_PREFIX: &str = "peak_chats/";
+
+pub async fn read(name: &str) -> Result<String, Error> {
+    #[cfg(feature = "native")]
+    {
+        Ok(fs::read_to_string(storage_dir().await?.join(name)).await?)
+    }
+
+    #[cfg(all(not(feature = "native"), target_arch = "wasm32"))]
+    {
+        local_storage()?
+            .get_item(&format!("{KEY_PREFIX}{name}"))
+            .map_err(|_| Error::WasmError("localStorage read failed".to_string()))?
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, name.to_owned()).into())
+    }
+
+    #[cfg(all(not(feature = "native"), not(target_arch = "wasm32")))]
+    {
+        Err(unsupported(name))
+    }
+}
+
+pub async fn write(name: &str, contents: String) -> Result<(), Error> {
+    #[cfg(feature = "native")]
+    {
+        Ok(fs::write(storage_dir().await?.join(name), contents).await?)
+    }
+
+    #[cfg(all(not(feature = "native"), target_arch = "wasm32"))]
+    {
+        local_storage()?
+            .set_item(&format!("{KEY_PREFIX}{name}"), &contents)
+            .map_err(|_| Error::WasmError("localStorage quota exceeded".to_string()))
+    }
+
+    #[cfg(all(not(feature = "native"), not(target_arch = "wasm32")))]
+    {
+        let _ = contents;
+        Err(unsupported(name))
+    }
+}
+
+pub async fn remove(name: &str) -> Result<(), Error> {
+    #[cfg(feature = "native")]
+    {
+        Ok(fs::remove_file(storage_dir().await?.join(name)).await?)
+    }
+
+    #[cfg(all(not(feature = "native"), target_arch = "wasm32"))]
+    {
+        local_storage()?
+            .remove_item(&format!("{KEY_PREFIX}{name}"))
+            .map_err(|_| Error::WasmError("localStorage remove failed".to_string()))
+    }
+
+    #[cfg(all(not(feature = "native"), not(target_arch = "wasm32")))]
+    {
+        Err(unsupported(name))
+    }
+}
+
+#[cfg(feature = "native")]
+async fn storage_dir() -> Result<PathBuf, io::Error> {
+    let directory = directory::data().join("chats");
+
+    fs::create_dir_all(&directory).await?;
+
+    Ok(directory)
+}
+
+#[cfg(all(not(feature = "native"), target_arch = "wasm32"))]
+fn local_storage() -> Result<web_sys::Storage, Error> {
+    web_sys::window()
+        .and_then(|window| window.local_storage().ok().flatten())
+        .ok_or_else(|| Error::WasmError("localStorage is unavailable".to_string()))
+}
+
+#[cfg(all(not(feature = "native"), not(target_arch = "wasm32")))]
+fn unsupported(name: &str) -> Error {
+    io::Error::new(
+        io::ErrorKind::Unsupported,
+        format!("no chat storage available for {name}"),
+    )
+    .into()
+}
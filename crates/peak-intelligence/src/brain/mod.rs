@@ -2,6 +2,7 @@ pub mod assistant;
 pub mod chat;
 pub mod model;
 pub mod plan;
+pub mod readable;
 pub mod settings;
 pub mod web;
 
@@ -12,7 +13,7 @@ pub use plan::Plan;
 pub use settings::Settings;
 pub use url::Url;
 
-pub(crate) mod directory;
+pub mod directory;
 pub(crate) mod request;
 
 use std::io;
@@ -46,6 +47,19 @@ pub enum Error {
     NoExecutorAvailable,
     #[error("operation not supported on WASM: {0}")]
     WasmError(String),
+    #[error("not enough disk space: {required} needed, {available} available")]
+    NotEnoughSpace {
+        required: model::Size,
+        available: model::Size,
+    },
+    #[error("invalid model: {0}")]
+    InvalidModel(String),
+    #[error("checksum mismatch for {file}: expected {expected}, got {actual}")]
+    ChecksumMismatch {
+        file: String,
+        expected: String,
+        actual: String,
+    },
 }
 
 #[cfg(feature = "native")]
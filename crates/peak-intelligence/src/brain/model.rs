@@ -1,3 +1,8 @@
+pub mod manager;
+
+pub use crate::brain::request::Progress;
+pub use manager::Manager;
+
 use crate::brain::directory;
 use crate::brain::request;
 use crate::brain::Error;
@@ -15,7 +20,40 @@ use std::fmt;
 use std::path::{Path, PathBuf};
 
 const HF_URL: &str = "https://huggingface.co";
-const API_URL: &str = "https://huggingface.co/api";
+
+/// A Hugging Face compatible model hub.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hub {
+    url: String,
+}
+
+impl Hub {
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            url: url.into().trim_end_matches('/').to_owned(),
+        }
+    }
+
+    fn api(&self) -> String {
+        format!("{}/api", self.url)
+    }
+
+    #[cfg_attr(not(feature = "native"), allow(dead_code))]
+    fn resolve(&self, file: &File) -> String {
+        format!(
+            "{}/{id}/resolve/main/{filename}?download=true",
+            self.url,
+            id = file.model.0,
+            filename = file.name
+        )
+    }
+}
+
+impl Default for Hub {
+    fn default() -> Self {
+        Self::new(HF_URL)
+    }
+}
 
 #[derive(Debug, Clone)]
 pub struct Model {
@@ -31,8 +69,13 @@ impl Model {
     }
 
     pub async fn search(query: String) -> Result<Vec<Self>, Error> {
+        Self::search_in(&Hub::default(), query).await
+    }
+
+    pub async fn search_in(hub: &Hub, query: String) -> Result<Vec<Self>, Error> {
         let url = format!(
-            "{API_URL}/models?search={query}&filter=gguf&limit=100&full=true",
+            "{api}/models?search={query}&filter=gguf&limit=100&full=true",
+            api = hub.api(),
             query = urlencoding::encode(&query)
         );
 
@@ -79,6 +122,24 @@ impl fmt::Display for Model {
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Id(pub String);
 
+impl std::str::FromStr for Id {
+    type Err = Error;
+
+    /// An id of the form `author/name`. The library keeps a model's files
+    /// under its id, so nothing that could lead out of it is accepted.
+    fn from_str(id: &str) -> Result<Self, Error> {
+        match id.split_once('/') {
+            Some((author, name)) if is_component(author) && is_component(name) => {
+                Ok(Self(id.to_string()))
+            }
+            _ => Err(Error::InvalidModel(format!(
+                "'{}' is not of the form author/name",
+                id
+            ))),
+        }
+    }
+}
+
 impl Id {
     pub fn name(&self) -> &str {
         self.0
@@ -122,7 +183,7 @@ impl Details {
             total: u64,
         }
 
-        let url = format!("{}/models/{}", API_URL, id.0);
+        let url = format!("{}/models/{}", Hub::default().api(), id.0);
         let response = crate::http::HttpClient::get(&url).await?;
         let response: Response = response.json()?;
 
@@ -189,17 +250,11 @@ pub struct File {
 
 impl File {
     pub async fn list(id: Id) -> Result<Files, Error> {
-        let url = format!("{}/models/{}/tree/main", API_URL, id.0);
-        let response = crate::http::HttpClient::get(&url).await?;
-
-        #[derive(Debug, Deserialize)]
-        struct Entry {
-            r#type: String,
-            path: String,
-            size: u64,
-        }
+        Self::list_in(&Hub::default(), id).await
+    }
 
-        let entries: Vec<Entry> = response.json()?;
+    pub async fn list_in(hub: &Hub, id: Id) -> Result<Files, Error> {
+        let entries = tree(hub, &id).await?;
         let mut files: BTreeMap<Bits, Vec<File>> = BTreeMap::new();
 
         for entry in entries {
@@ -238,6 +293,16 @@ impl File {
 
     pub fn download<'a>(
         &'a self,
+        directory: &'a Directory,
+    ) -> impl Straw<PathBuf, request::Progress, Error> + 'a {
+        self.download_from(Hub::default(), directory)
+    }
+
+    /// Downloads the file from `hub`, resuming a partial download left in
+    /// the library by a previous attempt.
+    pub fn download_from<'a>(
+        &'a self,
+        #[cfg_attr(not(feature = "native"), allow(unused_variables))] hub: Hub,
         #[cfg_attr(not(feature = "native"), allow(unused_variables))] directory: &'a Directory,
     ) -> impl Straw<PathBuf, request::Progress, Error> + 'a {
         sipper(
@@ -265,13 +330,7 @@ impl File {
                         return Ok(model_path);
                     }
 
-                    let url = format!(
-                        "{}/{id}/resolve/main/{filename}?download=true",
-                        HF_URL,
-                        id = self.model.0,
-                        filename = self.name
-                    );
-
+                    let url = hub.resolve(self);
                     let temp_path = model_path.with_extension("tmp");
 
                     request::download_file(url, &temp_path).run(&sender).await?;
@@ -289,6 +348,24 @@ impl File {
         )
     }
 
+    /// A file of `model` named `name`, which has to be a plain file name.
+    pub fn new(model: Id, name: impl Into<String>) -> Result<Self, Error> {
+        let name = name.into();
+
+        if !is_component(&name) {
+            return Err(Error::InvalidModel(format!(
+                "'{}' is not a file name",
+                name
+            )));
+        }
+
+        Ok(Self {
+            model,
+            name,
+            size: None,
+        })
+    }
+
     pub fn decode(value: decoder::Value) -> decoder::Result<Self> {
         use decoder::decode::{map, string, u64};
 
@@ -317,6 +394,33 @@ impl File {
     pub fn relative_path(&self) -> PathBuf {
         PathBuf::from(&self.model.0).join(&self.name)
     }
+
+    /// Where an unfinished download of the file is kept.
+    pub fn partial_path(&self, directory: &Directory) -> PathBuf {
+        directory.0.join(self.relative_path()).with_extension("tmp")
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct Entry {
+    r#type: String,
+    path: String,
+    size: u64,
+    #[serde(default)]
+    lfs: Option<Lfs>,
+}
+
+/// Git LFS pointer of a file; `oid` is the SHA256 of its contents.
+#[derive(Debug, Deserialize)]
+struct Lfs {
+    oid: String,
+}
+
+async fn tree(hub: &Hub, id: &Id) -> Result<Vec<Entry>, Error> {
+    let url = format!("{}/models/{}/tree/main", hub.api(), id.0);
+    let response = crate::http::HttpClient::get(&url).await?;
+
+    Ok(response.json()?)
 }
 
 impl fmt::Display for File {
@@ -341,7 +445,7 @@ pub struct Size(pub u64);
 
 impl fmt::Display for Size {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match self.0.ilog10() {
+        match self.0.checked_ilog10().unwrap_or(0) {
             0..3 => write!(f, "{} B", self.0),
             3..6 => write!(f, "{} KB", self.0 / 1000),
             6..9 => write!(f, "{} MB", self.0 / 1_000_000),
@@ -485,3 +589,8 @@ impl AsRef<Path> for Directory {
         &self.0
     }
 }
+
+/// A single path component that stays where it's joined.
+fn is_component(part: &str) -> bool {
+    !part.is_empty() && part != "." && part != ".." && !part.contains(['/', '\\', ':'])
+}
@@ -0,0 +1,996 @@
+//! End-to-end management of the model [`Library`]: a download queue that
+//! survives restarts, resumable transfers, SHA256 verification against the
+//! hub's LFS metadata, disk-space checks, and cleanup of variants nothing
+//! uses anymore.
+use crate::brain::directory;
+use crate::brain::model::{self, Directory, File, Files, Hub, Id, Library, Model, Progress, Size};
+use crate::brain::{Chat, Error};
+
+use serde::{Deserialize, Serialize};
+use sipper::{sipper, Sipper, Straw};
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::{LazyLock, Mutex, PoisonError};
+
+const QUEUE_FILE: &str = "downloads.json";
+
+/// Free space left on the disk once a download completes.
+const DISK_MARGIN: u64 = 512 * 1024 * 1024;
+
+/// Serializes read-modify-write cycles of queue files.
+static QUEUE_LOCK: LazyLock<futures::lock::Mutex<()>> =
+    LazyLock::new(|| futures::lock::Mutex::new(()));
+
+/// Queue files a [`Manager::run`] is currently working through.
+static RUNNING: LazyLock<Mutex<Vec<PathBuf>>> = LazyLock::new(Mutex::default);
+
+/// A queued download. Partial data is kept in the library between attempts,
+/// so a queued file resumes where it stopped.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Download {
+    pub file: File,
+    /// Checksum published by the hub, verified once the transfer completes.
+    #[serde(default)]
+    pub sha256: Option<String>,
+    /// Why the last attempt failed. Failed downloads are skipped until they
+    /// are queued again.
+    #[serde(default)]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub enum Event {
+    Started(File),
+    Progressed(File, Progress),
+    Verifying(File),
+    Finished(File, PathBuf),
+    Failed(File, String),
+}
+
+#[derive(Debug, Clone)]
+pub struct Manager {
+    hub: Hub,
+    directory: Directory,
+    queue: PathBuf,
+}
+
+impl Manager {
+    pub fn new(directory: Directory) -> Self {
+        Self {
+            hub: Hub::default(),
+            directory,
+            queue: directory::config().join(QUEUE_FILE),
+        }
+    }
+
+    /// Fetches models from `hub` instead of Hugging Face.
+    pub fn hub(mut self, hub: Hub) -> Self {
+        self.hub = hub;
+        self
+    }
+
+    /// Persists the queue in `path` instead of the config directory.
+    pub fn queue_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.queue = path.into();
+        self
+    }
+
+    pub fn directory(&self) -> &Directory {
+        &self.directory
+    }
+
+    /// `relative` within the library, unless it would lead out of it.
+    fn inside(&self, relative: &Path) -> Result<PathBuf, Error> {
+        let path = self.directory.path().join(relative);
+        let stays = path.strip_prefix(self.directory.path()).is_ok_and(|rest| {
+            rest.components()
+                .all(|component| matches!(component, std::path::Component::Normal(_)))
+        });
+
+        if !stays {
+            return Err(Error::InvalidModel(format!(
+                "{} is outside the model library",
+                relative.display()
+            )));
+        }
+
+        Ok(path)
+    }
+
+    pub async fn search(&self, query: String) -> Result<Vec<Model>, Error> {
+        Model::search_in(&self.hub, query).await
+    }
+
+    pub async fn files(&self, id: Id) -> Result<Files, Error> {
+        File::list_in(&self.hub, id).await
+    }
+
+    /// The downloaded files; empty until the library directory exists.
+    pub async fn installed(&self) -> Result<Library, Error> {
+        if !exists(self.directory.path()).await? {
+            return Ok(Library {
+                directory: self.directory.clone(),
+                files: Vec::new(),
+            });
+        }
+
+        Library::scan(&self.directory).await
+    }
+
+    /// Queued and failed downloads, oldest first.
+    pub async fn queue(&self) -> Result<Vec<Download>, Error> {
+        load(&self.queue).await
+    }
+
+    /// Adds `file` to the queue with the size and checksum the hub lists for
+    /// it. Fails when the rest of the file does not fit on disk.
+    pub async fn enqueue(&self, file: File) -> Result<Download, Error> {
+        let _ = self.inside(&file.relative_path())?;
+
+        let entry = model::tree(&self.hub, &file.model)
+            .await?
+            .into_iter()
+            .find(|entry| entry.r#type == "file" && entry.path == file.name)
+            .ok_or_else(|| {
+                Error::RequestFailed(format!("{} is not part of {}", file.name, file.model.0))
+            })?;
+
+        let download = Download {
+            file: File {
+                size: Some(Size(entry.size)),
+                ..file
+            },
+            sha256: entry.lfs.map(|lfs| lfs.oid),
+            error: None,
+        };
+
+        self.check_space(&download.file).await?;
+
+        let _lock = QUEUE_LOCK.lock().await;
+        let mut queue = load(&self.queue).await?;
+
+        match queue
+            .iter_mut()
+            .find(|queued| same_file(&queued.file, &download.file))
+        {
+            Some(queued) => *queued = download.clone(),
+            None => queue.push(download.clone()),
+        }
+
+        save(&self.queue, &queue).await?;
+
+        Ok(download)
+    }
+
+    /// Drops `file` from the queue and discards its partial data.
+    pub async fn dequeue(&self, file: &File) -> Result<(), Error> {
+        self.update_queue(file, |_| None).await?;
+
+        remove(&file.partial_path(&self.directory)).await
+    }
+
+    /// Works through the queue until only failed downloads remain, picking
+    /// up files queued in the meantime. Completes immediately when another
+    /// run already owns the queue.
+    pub fn run(self) -> impl Straw<(), Event, Error> {
+        sipper(move |mut sender| async move {
+            let Some(_running) = Running::acquire(&self.queue) else {
+                return Ok(());
+            };
+
+            while let Some(download) = self
+                .queue()
+                .await?
+                .into_iter()
+                .find(|download| download.error.is_none())
+            {
+                let file = download.file.clone();
+
+                sender.send(Event::Started(file.clone())).await;
+
+                match self.fetch(download, &mut sender).await {
+                    Ok(path) => {
+                        self.update_queue(&file, |_| None).await?;
+                        sender.send(Event::Finished(file, path)).await;
+                    }
+                    Err(error) => {
+                        let error = error.to_string();
+
+                        self.update_queue(&file, |download| {
+                            Some(Download {
+                                error: Some(error.clone()),
+                                ..download
+                            })
+                        })
+                        .await?;
+
+                        sender.send(Event::Failed(file, error)).await;
+                    }
+                }
+            }
+
+            Ok(())
+        })
+    }
+
+    /// Deletes `file` from the library, along with the other shards of a
+    /// split model and any partial data, and returns the removed paths.
+    pub async fn delete(&self, file: &File) -> Result<Vec<PathBuf>, Error> {
+        let directory = self.inside(Path::new(&file.model.0))?;
+        let Some(weights) = weights_name(&file.name) else {
+            return Ok(Vec::new());
+        };
+
+        let mut removed = Vec::new();
+
+        for path in entries(&directory).await? {
+            if path_weights(&path) == Some(weights) {
+                remove(&path).await?;
+                removed.push(path);
+            }
+        }
+
+        prune(&directory).await?;
+
+        Ok(removed)
+    }
+
+    /// Removes variants of a downloaded model that neither `keep` nor any
+    /// saved chat refers to, as long as a variant of the same model stays in
+    /// use, and partial downloads that are no longer queued.
+    pub async fn collect_garbage(&self, keep: &[File]) -> Result<Vec<PathBuf>, Error> {
+        let mut used = keep.to_vec();
+
+        // Chats are optional here; without them only `keep` counts
+        if let Ok(chats) = Chat::list().await {
+            used.extend(chats.into_iter().map(|entry| entry.file));
+        }
+
+        let in_use = |file: &File| used.iter().any(|used| same_file(used, file));
+
+        let library = self.installed().await?;
+        let mut models: BTreeMap<&str, Vec<&File>> = BTreeMap::new();
+
+        for file in library.files() {
+            models.entry(&file.model.0).or_default().push(file);
+        }
+
+        let mut removed = Vec::new();
+
+        for files in models.into_values() {
+            if !files.iter().any(|file| in_use(file)) {
+                continue;
+            }
+
+            for file in files.into_iter().filter(|file| !in_use(file)) {
+                removed.extend(self.delete(file).await?);
+            }
+        }
+
+        let queue = self.queue().await?;
+
+        for author in entries(self.directory.path()).await? {
+            for model in entries(&author).await? {
+                for path in entries(&model).await? {
+                    let is_partial = path.extension().is_some_and(|extension| extension == "tmp");
+                    let is_queued = queue
+                        .iter()
+                        .any(|download| download.file.partial_path(&self.directory) == path);
+
+                    if is_partial && !is_queued {
+                        remove(&path).await?;
+                        removed.push(path);
+                    }
+                }
+
+                prune(&model).await?;
+            }
+        }
+
+        Ok(removed)
+    }
+
+    async fn fetch(
+        &self,
+        download: Download,
+        sender: &mut sipper::Sender<Event>,
+    ) -> Result<PathBuf, Error> {
+        let file = download.file;
+
+        self.check_space(&file).await?;
+
+        let mut transfer = file.download_from(self.hub.clone(), &self.directory).pin();
+
+        while let Some(progress) = transfer.sip().await {
+            sender.send(Event::Progressed(file.clone(), progress)).await;
+        }
+
+        let path = transfer.await?;
+
+        if let Some(expected) = download.sha256 {
+            sender.send(Event::Verifying(file.clone())).await;
+
+            let actual = sha256(&path).await?;
+
+            if !actual.eq_ignore_ascii_case(&expected) {
+                remove(&path).await?;
+
+                return Err(Error::ChecksumMismatch {
+                    file: file.name.clone(),
+                    expected,
+                    actual,
+                });
+            }
+        }
+
+        Ok(path)
+    }
+
+    async fn check_space(&self, file: &File) -> Result<(), Error> {
+        let Some(Size(size)) = file.size else {
+            return Ok(());
+        };
+
+        let Some(available) = available_space(self.directory.path()) else {
+            return Ok(());
+        };
+
+        let present = len(&self.directory.path().join(file.relative_path()))
+            .await
+            .max(len(&file.partial_path(&self.directory)).await);
+
+        if present >= size {
+            return Ok(());
+        }
+
+        let required = size - present + DISK_MARGIN;
+
+        if required > available {
+            return Err(Error::NotEnoughSpace {
+                required: Size(required),
+                available: Size(available),
+            });
+        }
+
+        Ok(())
+    }
+
+    async fn update_queue(
+        &self,
+        file: &File,
+        f: impl Fn(Download) -> Option<Download>,
+    ) -> Result<(), Error> {
+        let _lock = QUEUE_LOCK.lock().await;
+
+        let queue = load(&self.queue)
+            .await?
+            .into_iter()
+            .filter_map(|download| {
+                if same_file(&download.file, file) {
+                    f(download)
+                } else {
+                    Some(download)
+                }
+            })
+            .collect::<Vec<_>>();
+
+        save(&self.queue, &queue).await
+    }
+}
+
+impl Default for Manager {
+    /// A manager for the library configured in [`Settings`](crate::brain::Settings).
+    fn default() -> Self {
+        Self::new(
+            crate::brain::Settings::fetch()
+                .map(|settings| settings.library)
+                .unwrap_or_default(),
+        )
+    }
+}
+
+struct Running(PathBuf);
+
+impl Running {
+    fn acquire(queue: &Path) -> Option<Self> {
+        let mut running = RUNNING.lock().unwrap_or_else(PoisonError::into_inner);
+
+        if running.iter().any(|path| path == queue) {
+            return None;
+        }
+
+        running.push(queue.to_path_buf());
+
+        Some(Self(queue.to_path_buf()))
+    }
+}
+
+impl Drop for Running {
+    fn drop(&mut self) {
+        RUNNING
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .retain(|path| path != &self.0);
+    }
+}
+
+fn same_file(a: &File, b: &File) -> bool {
+    a.model == b.model && a.name == b.name
+}
+
+/// The name shared by every shard of a model file and its partial download,
+/// e.g. `model-Q4_K_M` for `model-Q4_K_M-00001-of-00003.gguf`.
+fn weights_name(file_name: &str) -> Option<&str> {
+    let stem = file_name
+        .strip_suffix(".gguf")
+        .or_else(|| file_name.strip_suffix(".tmp"))?;
+
+    let is_number = |part: &str| !part.is_empty() && part.bytes().all(|b| b.is_ascii_digit());
+
+    let shard = stem.rsplit_once("-of-").and_then(|(head, count)| {
+        let (base, index) = head.rsplit_once('-')?;
+        (is_number(index) && is_number(count)).then_some(base)
+    });
+
+    Some(shard.unwrap_or(stem))
+}
+
+fn path_weights(path: &Path) -> Option<&str> {
+    weights_name(path.file_name()?.to_str()?)
+}
+
+async fn load(path: &Path) -> Result<Vec<Download>, Error> {
+    #[cfg(feature = "native")]
+    {
+        match tokio::fs::read_to_string(path).await {
+            Ok(json) => Ok(serde_json::from_str(&json)?),
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => Ok(Vec::new()),
+            Err(error) => Err(error.into()),
+        }
+    }
+
+    #[cfg(not(feature = "native"))]
+    {
+        let _ = path;
+        Ok(Vec::new())
+    }
+}
+
+async fn save(path: &Path, queue: &[Download]) -> Result<(), Error> {
+    #[cfg(feature = "native")]
+    {
+        if let Some(directory) = path.parent() {
+            tokio::fs::create_dir_all(directory).await?;
+        }
+
+        Ok(tokio::fs::write(path, serde_json::to_string_pretty(queue)?).await?)
+    }
+
+    #[cfg(not(feature = "native"))]
+    {
+        let _ = (path, queue);
+        Err(unsupported())
+    }
+}
+
+async fn exists(path: &Path) -> Result<bool, Error> {
+    #[cfg(feature = "native")]
+    {
+        Ok(tokio::fs::try_exists(path).await?)
+    }
+
+    #[cfg(not(feature = "native"))]
+    {
+        let _ = path;
+        Ok(false)
+    }
+}
+
+/// Size of the file at `path`, or 0 when there is none.
+async fn len(path: &Path) -> u64 {
+    #[cfg(feature = "native")]
+    {
+        tokio::fs::metadata(path)
+            .await
+            .map(|metadata| metadata.len())
+            .unwrap_or(0)
+    }
+
+    #[cfg(not(feature = "native"))]
+    {
+        let _ = path;
+        0
+    }
+}
+
+/// Paths inside `directory`, or none when it does not exist.
+async fn entries(directory: &Path) -> Result<Vec<PathBuf>, Error> {
+    #[cfg(feature = "native")]
+    {
+        let mut list = match tokio::fs::read_dir(directory).await {
+            Ok(list) => list,
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(error) => return Err(error.into()),
+        };
+
+        let mut paths = Vec::new();
+
+        while let Some(entry) = list.next_entry().await? {
+            paths.push(entry.path());
+        }
+
+        paths.sort();
+
+        Ok(paths)
+    }
+
+    #[cfg(not(feature = "native"))]
+    {
+        let _ = directory;
+        Ok(Vec::new())
+    }
+}
+
+async fn remove(path: &Path) -> Result<(), Error> {
+    #[cfg(feature = "native")]
+    {
+        match tokio::fs::remove_file(path).await {
+            Err(error) if error.kind() != std::io::ErrorKind::NotFound => Err(error.into()),
+            _ => Ok(()),
+        }
+    }
+
+    #[cfg(not(feature = "native"))]
+    {
+        let _ = path;
+        Err(unsupported())
+    }
+}
+
+/// Removes a model directory once it is empty, and its author directory too.
+async fn prune(model: &Path) -> Result<(), Error> {
+    for directory in [Some(model), model.parent()].into_iter().flatten() {
+        if !exists(directory).await? || !entries(directory).await?.is_empty() {
+            break;
+        }
+
+        #[cfg(feature = "native")]
+        tokio::fs::remove_dir(directory).await?;
+    }
+
+    Ok(())
+}
+
+async fn sha256(path: &Path) -> Result<String, Error> {
+    #[cfg(feature = "native")]
+    {
+        use sha2::{Digest, Sha256};
+        use tokio::io::AsyncReadExt;
+
+        let mut file = tokio::fs::File::open(path).await?;
+        let mut hasher = Sha256::new();
+        let mut buffer = vec![0; 1 << 20];
+
+        loop {
+            let read = file.read(&mut buffer).await?;
+
+            if read == 0 {
+                break;
+            }
+
+            hasher.update(&buffer[..read]);
+        }
+
+        Ok(format!("{:x}", hasher.finalize()))
+    }
+
+    #[cfg(not(feature = "native"))]
+    {
+        let _ = path;
+        Err(unsupported())
+    }
+}
+
+/// Space left on the disk holding `path`, which may not exist yet.
+fn available_space(path: &Path) -> Option<u64> {
+    #[cfg(feature = "native")]
+    {
+        let path = path
+            .ancestors()
+            .find_map(|ancestor| ancestor.canonicalize().ok())?;
+
+        sysinfo::Disks::new_with_refreshed_list()
+            .iter()
+            .filter(|disk| path.starts_with(disk.mount_point()))
+            .max_by_key(|disk| disk.mount_point().as_os_str().len())
+            .map(|disk| disk.available_space())
+    }
+
+    #[cfg(not(feature = "native"))]
+    {
+        let _ = path;
+        None
+    }
+}
+
+#[cfg(not(feature = "native"))]
+fn unsupported() -> Error {
+    Error::WasmError("Model management requires the native feature".to_string())
+}
+
+#[cfg(all(test, feature = "native"))]
+mod tests {
+    use super::*;
+    use sha2::{Digest, Sha256};
+    use std::sync::Arc;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    const MODEL: &str = "peak/tiny-GGUF";
+
+    struct Served {
+        name: &'static str,
+        contents: Vec<u8>,
+        sha256: String,
+        size: u64,
+    }
+
+    impl Served {
+        fn new(name: &'static str, contents: Vec<u8>) -> Self {
+            Self {
+                name,
+                sha256: format!("{:x}", Sha256::digest(&contents)),
+                size: contents.len() as u64,
+                contents,
+            }
+        }
+    }
+
+    /// Local stand-in for the hub: lists files with their LFS checksums and
+    /// serves their contents, honouring `Range` requests.
+    struct Fixture {
+        url: String,
+        ranges: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl Fixture {
+        async fn serve(files: Vec<Served>) -> Self {
+            let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let url = format!("http://{}", listener.local_addr().unwrap());
+            let files = Arc::new(files);
+            let ranges = Arc::new(Mutex::new(Vec::new()));
+
+            let log = ranges.clone();
+            tokio::spawn(async move {
+                while let Ok((mut socket, _)) = listener.accept().await {
+                    let files = files.clone();
+                    let log = log.clone();
+
+                    tokio::spawn(async move {
+                        let mut request = Vec::new();
+                        let mut buffer = [0; 1024];
+
+                        while !request.ends_with(b"\r\n\r\n") {
+                            match socket.read(&mut buffer).await {
+                                Ok(0) | Err(_) => return,
+                                Ok(read) => request.extend_from_slice(&buffer[..read]),
+                            }
+                        }
+
+                        let request = String::from_utf8_lossy(&request).to_string();
+                        let response = respond(&request, &files, &log);
+                        let _ = socket.write_all(&response).await;
+                    });
+                }
+            });
+
+            Self { url, ranges }
+        }
+
+        fn ranges(&self) -> Vec<String> {
+            self.ranges.lock().unwrap().clone()
+        }
+    }
+
+    fn respond(request: &str, files: &[Served], ranges: &Mutex<Vec<String>>) -> Vec<u8> {
+        let path = request.split_whitespace().nth(1).unwrap_or_default();
+        let path = path.split('?').next().unwrap_or_default();
+
+        if path == format!("/api/models/{MODEL}/tree/main") {
+            let tree = files
+                .iter()
+                .map(|file| {
+                    serde_json::json!({
+                        "type": "file",
+                        "path": file.name,
+                        "size": file.size,
+                        "lfs": { "oid": file.sha256, "size": file.size },
+                    })
+                })
+                .collect::<Vec<_>>();
+
+            return response("200 OK", &[], serde_json::to_vec(&tree).unwrap());
+        }
+
+        let Some(file) = files
+            .iter()
+            .find(|file| path == format!("/{MODEL}/resolve/main/{}", file.name))
+        else {
+            return response("404 Not Found", &[], Vec::new());
+        };
+
+        let range = request.lines().find_map(|line| {
+            let (name, value) = line.split_once(':')?;
+            name.eq_ignore_ascii_case("range")
+                .then(|| value.trim().to_string())
+        });
+
+        let Some(range) = range else {
+            return response("200 OK", &[], file.contents.clone());
+        };
+
+        ranges.lock().unwrap().push(range.clone());
+
+        let start: usize = range
+            .trim_start_matches("bytes=")
+            .trim_end_matches('-')
+            .parse()
+            .unwrap();
+
+        if start >= file.contents.len() {
+            return response("416 Range Not Satisfiable", &[], Vec::new());
+        }
+
+        let content_range = format!(
+            "Content-Range: bytes {start}-{}/{}",
+            file.contents.len() - 1,
+            file.contents.len()
+        );
+
+        response(
+            "206 Partial Content",
+            &[&content_range],
+            file.contents[start..].to_vec(),
+        )
+    }
+
+    fn response(status: &str, headers: &[&str], body: Vec<u8>) -> Vec<u8> {
+        let mut head = format!(
+            "HTTP/1.1 {status}\r\nContent-Length: {}\r\nConnection: close\r\n",
+            body.len()
+        );
+
+        for header in headers {
+            head.push_str(header);
+            head.push_str("\r\n");
+        }
+
+        head.push_str("\r\n");
+
+        [head.into_bytes(), body].concat()
+    }
+
+    fn contents(len: usize) -> Vec<u8> {
+        (0..len).map(|i| (i % 251) as u8).collect()
+    }
+
+    fn file(name: &str) -> File {
+        File {
+            model: Id(MODEL.to_string()),
+            name: name.to_string(),
+            size: None,
+        }
+    }
+
+    fn manager(root: &Path, fixture: &Fixture) -> Manager {
+        Manager::new(Directory(root.join("models")))
+            .hub(Hub::new(&fixture.url))
+            .queue_file(root.join(QUEUE_FILE))
+    }
+
+    async fn run(manager: &Manager) -> Vec<Event> {
+        let mut run = manager.clone().run().pin();
+        let mut events = Vec::new();
+
+        while let Some(event) = run.sip().await {
+            events.push(event);
+        }
+
+        run.await.unwrap();
+        events
+    }
+
+    #[tokio::test]
+    async fn test_resumes_partial_downloads_and_verifies_them() {
+        let root = tempfile::tempdir().unwrap();
+        let data = contents(64 * 1024);
+        let fixture = Fixture::serve(vec![Served::new("tiny-Q4_K_M.gguf", data.clone())]).await;
+        let manager = manager(root.path(), &fixture);
+        let file = file("tiny-Q4_K_M.gguf");
+
+        let partial = file.partial_path(manager.directory());
+        tokio::fs::create_dir_all(partial.parent().unwrap())
+            .await
+            .unwrap();
+        tokio::fs::write(&partial, &data[..10_000]).await.unwrap();
+
+        let download = manager.enqueue(file.clone()).await.unwrap();
+        assert_eq!(download.file.size, Some(Size(data.len() as u64)));
+        assert!(download.sha256.is_some());
+
+        let events = run(&manager).await;
+
+        assert!(matches!(events.last(), Some(Event::Finished(..))));
+        assert_eq!(fixture.ranges(), ["bytes=10000-"]);
+
+        let path = root.path().join("models").join(file.relative_path());
+        assert_eq!(tokio::fs::read(&path).await.unwrap(), data);
+        assert!(!partial.exists());
+        assert!(manager.queue().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_rejects_files_that_fail_verification() {
+        let root = tempfile::tempdir().unwrap();
+        let mut served = Served::new("tiny-Q8_0.gguf", contents(4096));
+        served.sha256 = format!("{:x}", Sha256::digest(b"something else"));
+        let fixture = Fixture::serve(vec![served]).await;
+        let manager = manager(root.path(), &fixture);
+        let file = file("tiny-Q8_0.gguf");
+
+        manager.enqueue(file.clone()).await.unwrap();
+        let events = run(&manager).await;
+
+        assert!(matches!(events.last(), Some(Event::Failed(..))));
+        assert!(!root
+            .path()
+            .join("models")
+            .join(file.relative_path())
+            .exists());
+
+        let queue = manager.queue().await.unwrap();
+        assert_eq!(queue.len(), 1);
+        assert!(queue[0]
+            .error
+            .as_deref()
+            .is_some_and(|error| error.contains("checksum mismatch")));
+
+        // Failed downloads wait until they are queued again
+        assert!(run(&manager).await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_refuses_downloads_that_do_not_fit_on_disk() {
+        let root = tempfile::tempdir().unwrap();
+        let mut served = Served::new("tiny-F16.gguf", contents(16));
+        served.size = u64::MAX / 2;
+        let fixture = Fixture::serve(vec![served]).await;
+        let manager = manager(root.path(), &fixture);
+
+        let result = manager.enqueue(file("tiny-F16.gguf")).await;
+
+        assert!(matches!(result, Err(Error::NotEnoughSpace { .. })));
+        assert!(manager.queue().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_queue_survives_restarts() {
+        let root = tempfile::tempdir().unwrap();
+        let fixture = Fixture::serve(vec![Served::new("tiny-Q4_0.gguf", contents(256))]).await;
+        let file = file("tiny-Q4_0.gguf");
+
+        manager(root.path(), &fixture)
+            .enqueue(file.clone())
+            .await
+            .unwrap();
+
+        let restarted = manager(root.path(), &fixture);
+        let queue = restarted.queue().await.unwrap();
+        assert_eq!(queue.len(), 1);
+        assert!(same_file(&queue[0].file, &file));
+
+        restarted.dequeue(&file).await.unwrap();
+        assert!(restarted.queue().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_collects_unused_variants_and_stale_partials() {
+        let root = tempfile::tempdir().unwrap();
+        let fixture = Fixture::serve(Vec::new()).await;
+        let manager = manager(root.path(), &fixture);
+
+        let models = root.path().join("models");
+        let tiny = models.join(MODEL);
+        let other = models.join("peak/other-GGUF");
+
+        for path in [
+            tiny.join("tiny-Q4_K_M.gguf"),
+            tiny.join("tiny-Q8_0-00001-of-00002.gguf"),
+            tiny.join("tiny-Q8_0-00002-of-00002.gguf"),
+            tiny.join("tiny-Q2_K.tmp"),
+            other.join("other-Q4_K_M.gguf"),
+            other.join("other-Q8_0.gguf"),
+        ] {
+            tokio::fs::create_dir_all(path.parent().unwrap())
+                .await
+                .unwrap();
+            tokio::fs::write(path, b"gguf").await.unwrap();
+        }
+
+        let mut removed = manager
+            .collect_garbage(&[file("tiny-Q4_K_M.gguf")])
+            .await
+            .unwrap();
+        removed.sort();
+
+        assert_eq!(
+            removed,
+            [
+                tiny.join("tiny-Q2_K.tmp"),
+                tiny.join("tiny-Q8_0-00001-of-00002.gguf"),
+                tiny.join("tiny-Q8_0-00002-of-00002.gguf"),
+            ]
+        );
+        assert!(tiny.join("tiny-Q4_K_M.gguf").exists());
+
+        // Models nothing uses are left for an explicit delete
+        assert!(other.join("other-Q8_0.gguf").exists());
+    }
+
+    #[tokio::test]
+    async fn test_refuses_paths_outside_the_library() {
+        let root = tempfile::tempdir().unwrap();
+        let fixture = Fixture::serve(Vec::new()).await;
+        let manager = manager(&root.path().join("library"), &fixture);
+
+        let outside = root.path().join("outside-Q8_0.gguf");
+        tokio::fs::write(&outside, b"gguf").await.unwrap();
+
+        for model in ["..", "../..", "/tmp", "peak/../.."] {
+            let file = File {
+                model: Id(model.to_string()),
+                name: "outside-Q8_0.gguf".to_string(),
+                size: None,
+            };
+
+            assert!(matches!(
+                manager.delete(&file).await,
+                Err(Error::InvalidModel(_))
+            ));
+            assert!(matches!(
+                manager.enqueue(file).await,
+                Err(Error::InvalidModel(_))
+            ));
+        }
+        assert!(outside.exists());
+
+        for id in [
+            "../..",
+            "peak",
+            "peak/",
+            "/peak/tiny",
+            "peak/tiny/more",
+            "peak/..",
+            ".\\x/y",
+        ] {
+            assert!(id.parse::<Id>().is_err(), "{} parsed", id);
+        }
+        assert_eq!("peak/tiny-GGUF".parse::<Id>().unwrap(), Id(MODEL.into()));
+        assert!(File::new(Id(MODEL.into()), "../tiny.gguf").is_err());
+    }
+
+    #[test]
+    fn test_shards_share_their_weights_name() {
+        assert_eq!(
+            weights_name("model-Q4_K_M-00002-of-00003.gguf"),
+            Some("model-Q4_K_M")
+        );
+        assert_eq!(weights_name("model-Q4_K_M.tmp"), Some("model-Q4_K_M"));
+        assert_eq!(
+            weights_name("model-of-thought.gguf"),
+            Some("model-of-thought")
+        );
+        assert_eq!(weights_name("README.md"), None);
+    }
+}
@@ -0,0 +1,834 @@
+//! Main-content extraction for web pages.
+//!
+//! Candidate containers are scored like Readability does: every paragraph
+//! adds to its parent and, halved, to its grandparent, with bonuses for
+//! commas and length. Class names and ids hint at content or boilerplate,
+//! and link-heavy containers are penalized. The winner is rendered as
+//! Markdown with navigation, ads, forms and the like left out, and can be
+//! split into [`Chunk`]s that keep their offsets into the Markdown, so a
+//! summary can point back at the passages it used.
+use crate::brain::Url;
+
+use scraper::{ElementRef, Html, Node, Selector};
+use std::collections::HashMap;
+
+/// Never rendered, wherever they appear.
+const SKIPPED: &[&str] = &[
+    "script", "style", "noscript", "template", "nav", "header", "footer", "aside", "form",
+    "button", "input", "select", "textarea", "iframe", "object", "embed", "canvas", "svg",
+    "dialog", "menu",
+];
+
+/// Words in a class or id that suggest content.
+const POSITIVE: &[&str] = &[
+    "article", "body", "content", "entry", "main", "page", "post", "text", "blog", "story",
+];
+
+/// Words in a class or id that suggest boilerplate.
+const NEGATIVE: &[&str] = &[
+    "ad",
+    "ads",
+    "advert",
+    "advertisement",
+    "banner",
+    "breadcrumb",
+    "breadcrumbs",
+    "comment",
+    "comments",
+    "cookie",
+    "cookies",
+    "footer",
+    "footnote",
+    "masthead",
+    "menu",
+    "modal",
+    "nav",
+    "navbar",
+    "navigation",
+    "newsletter",
+    "popup",
+    "promo",
+    "related",
+    "share",
+    "sharing",
+    "sidebar",
+    "social",
+    "sponsor",
+    "sponsored",
+    "subscribe",
+    "widget",
+];
+
+/// Paragraphs shorter than this do not count towards a container's score.
+const MIN_PARAGRAPH_LEN: usize = 25;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Article {
+    pub title: Option<String>,
+    pub markdown: String,
+}
+
+/// A passage of an [`Article`], with its byte range in the Markdown.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Chunk {
+    pub index: usize,
+    pub start: usize,
+    pub end: usize,
+    pub text: String,
+}
+
+/// Extracts the main content of an HTML page. Relative links are resolved
+/// against `base` when given.
+pub fn extract(html: &str, base: Option<&Url>) -> Article {
+    let document = Html::parse_document(html);
+
+    let mut writer = Writer {
+        base,
+        blocks: Vec::new(),
+        inline: String::new(),
+    };
+
+    if let Some(content) = main_content(&document) {
+        writer.container(content);
+        writer.flush();
+    }
+
+    Article {
+        title: title(&document),
+        markdown: writer.blocks.join("\n\n"),
+    }
+}
+
+impl Article {
+    /// Splits the Markdown between blocks into chunks of at most `max_len`
+    /// bytes, starting a new one at every heading. Blocks longer than that
+    /// are split between words.
+    pub fn chunks(&self, max_len: usize) -> Vec<Chunk> {
+        let max_len = max_len.max(1);
+        let mut spans: Vec<(usize, usize)> = Vec::new();
+        let mut current: Option<(usize, usize)> = None;
+
+        for (start, end) in pieces(&self.markdown, max_len) {
+            let heading = self.markdown[start..].starts_with('#');
+
+            current = match current {
+                Some((chunk_start, _)) if !heading && end - chunk_start <= max_len => {
+                    Some((chunk_start, end))
+                }
+                Some(chunk) => {
+                    spans.push(chunk);
+                    Some((start, end))
+                }
+                None => Some((start, end)),
+            };
+        }
+        spans.extend(current);
+
+        spans
+            .into_iter()
+            .enumerate()
+            .map(|(index, (start, end))| Chunk {
+                index,
+                start,
+                end,
+                text: self.markdown[start..end].to_string(),
+            })
+            .collect()
+    }
+
+    /// The first paragraph as plain text, cut between words to at most
+    /// `max_chars` characters, for previews like search snippets.
+    pub fn excerpt(&self, max_chars: usize) -> String {
+        let is_paragraph = |block: &str| {
+            !block.starts_with(['#', '-', '>', '|', '`', '~'])
+                && !block.starts_with(char::is_numeric)
+        };
+
+        let blocks: Vec<&str> = self.markdown.split("\n\n").collect();
+        let paragraph = blocks
+            .iter()
+            .find(|block| is_paragraph(block) && block.len() >= 2 * MIN_PARAGRAPH_LEN)
+            .or_else(|| blocks.iter().find(|block| is_paragraph(block)))
+            .or(blocks.first())
+            .copied()
+            .unwrap_or_default();
+
+        let text = plain(paragraph);
+        if text.chars().count() <= max_chars {
+            return text;
+        }
+
+        let cut: String = text.chars().take(max_chars.saturating_sub(1)).collect();
+        let cut = match cut.rfind(' ') {
+            Some(space) if space > 0 => &cut[..space],
+            _ => &cut,
+        };
+
+        format!(
+            "{}…",
+            cut.trim_end_matches(|c: char| c.is_ascii_punctuation())
+        )
+    }
+}
+
+/// Byte ranges of the Markdown's blocks, with long ones split between
+/// words into ranges of at most `max_len` bytes.
+fn pieces(markdown: &str, max_len: usize) -> Vec<(usize, usize)> {
+    let mut pieces = Vec::new();
+    let mut offset = 0;
+
+    for block in markdown.split("\n\n") {
+        let (mut start, end) = (offset, offset + block.len());
+        offset = end + 2;
+
+        while end - start > max_len {
+            let window = floor_char_boundary(markdown, start + max_len);
+            let split = markdown[start..window]
+                .rfind(char::is_whitespace)
+                .map(|i| start + i)
+                .filter(|&split| split > start)
+                .unwrap_or(window.max(start + 1));
+            let split = ceil_char_boundary(markdown, split);
+
+            pieces.push((start, split));
+            start = split + markdown[split..end].len() - markdown[split..end].trim_start().len();
+        }
+
+        if start < end {
+            pieces.push((start, end));
+        }
+    }
+
+    pieces
+}
+
+fn floor_char_boundary(text: &str, mut index: usize) -> usize {
+    while !text.is_char_boundary(index) {
+        index -= 1;
+    }
+    index
+}
+
+fn ceil_char_boundary(text: &str, mut index: usize) -> usize {
+    while !text.is_char_boundary(index) {
+        index += 1;
+    }
+    index
+}
+
+/// Markdown without emphasis, code spans or link targets.
+fn plain(markdown: &str) -> String {
+    let mut text = String::with_capacity(markdown.len());
+    let mut rest = markdown;
+
+    while let Some(open) = rest.find('[') {
+        text.push_str(&rest[..open]);
+        rest = &rest[open + 1..];
+
+        let link = rest
+            .find("](")
+            .filter(|&label| !rest[..label].contains('['))
+            .and_then(|label| Some((label, label + rest[label..].find(')')?)));
+
+        match link {
+            Some((label, close)) => {
+                text.push_str(&rest[..label]);
+                rest = &rest[close + 1..];
+            }
+            None => text.push('['),
+        }
+    }
+    text.push_str(rest);
+
+    text.replace(['*', '`'], "")
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn title(document: &Html) -> Option<String> {
+    let og_title = Selector::parse(r#"meta[property="og:title"]"#).unwrap();
+    let title = Selector::parse("title").unwrap();
+
+    document
+        .select(&og_title)
+        .find_map(|meta| meta.attr("content").map(str::to_owned))
+        .or_else(|| {
+            document
+                .select(&title)
+                .next()
+                .map(|title| title.text().collect())
+        })
+        .map(|title: String| collapse(&title))
+        .filter(|title| !title.is_empty())
+}
+
+/// The element most likely holding the page's content, falling back to
+/// the body.
+fn main_content(document: &Html) -> Option<ElementRef<'_>> {
+    let paragraphs = Selector::parse("p, pre, td").unwrap();
+    let body = Selector::parse("body").unwrap();
+
+    let mut scores = HashMap::new();
+    let mut candidates = Vec::new();
+
+    for paragraph in document.select(&paragraphs) {
+        if ancestors(paragraph).any(is_boilerplate) {
+            continue;
+        }
+
+        let text = collapse(&paragraph.text().collect::<String>());
+        if text.len() < MIN_PARAGRAPH_LEN {
+            continue;
+        }
+
+        let score = 1.0 + text.matches(',').count() as f64 + (text.len() / 100).min(3) as f64;
+
+        for (level, ancestor) in ancestors(paragraph).take(2).enumerate() {
+            let entry = scores.entry(ancestor.id()).or_insert_with(|| {
+                candidates.push(ancestor);
+                initial_score(ancestor)
+            });
+            *entry += if level == 0 { score } else { score / 2.0 };
+        }
+    }
+
+    candidates
+        .into_iter()
+        .map(|candidate| {
+            let score = scores[&candidate.id()] * (1.0 - link_density(candidate));
+            (candidate, score)
+        })
+        .max_by(|(_, a), (_, b)| a.total_cmp(b))
+        .map(|(candidate, _)| candidate)
+        .or_else(|| document.select(&body).next())
+}
+
+fn initial_score(element: ElementRef<'_>) -> f64 {
+    let tag = match element.value().name() {
+        "article" | "main" => 10.0,
+        "div" => 5.0,
+        "pre" | "td" | "blockquote" => 3.0,
+        "address" | "ol" | "ul" | "dl" | "dd" | "dt" | "li" | "form" => -3.0,
+        "h1" | "h2" | "h3" | "h4" | "h5" | "h6" | "th" => -5.0,
+        _ => 0.0,
+    };
+
+    tag + class_weight(element)
+}
+
+fn class_weight(element: ElementRef<'_>) -> f64 {
+    let words = hint_words(element);
+    let has = |list: &[&str]| words.iter().any(|word| list.contains(&word.as_str()));
+
+    let mut weight = 0.0;
+    if has(POSITIVE) {
+        weight += 25.0;
+    }
+    if has(NEGATIVE) {
+        weight -= 25.0;
+    }
+    weight
+}
+
+/// The words of an element's classes and id, e.g. `post-body` gives
+/// `post` and `body`.
+fn hint_words(element: ElementRef<'_>) -> Vec<String> {
+    let value = element.value();
+
+    value
+        .classes()
+        .chain(value.id())
+        .flat_map(|hint| hint.split(['-', '_']))
+        .map(str::to_lowercase)
+        .collect()
+}
+
+/// Share of an element's text inside links.
+fn link_density(element: ElementRef<'_>) -> f64 {
+    let links = Selector::parse("a").unwrap();
+
+    let total = element.text().map(str::len).sum::<usize>();
+    if total == 0 {
+        return 0.0;
+    }
+
+    let linked = element
+        .select(&links)
+        .flat_map(|link| link.text())
+        .map(str::len)
+        .sum::<usize>();
+
+    linked as f64 / total as f64
+}
+
+fn ancestors(element: ElementRef<'_>) -> impl Iterator<Item = ElementRef<'_>> {
+    element.ancestors().filter_map(ElementRef::wrap)
+}
+
+fn is_boilerplate(element: ElementRef<'_>) -> bool {
+    let value = element.value();
+
+    SKIPPED.contains(&value.name())
+        || value.attr("hidden").is_some()
+        || value.attr("aria-hidden") == Some("true")
+        || value
+            .attr("style")
+            .is_some_and(|style| style.replace(' ', "").contains("display:none"))
+        || hint_words(element)
+            .iter()
+            .any(|word| NEGATIVE.contains(&word.as_str()))
+}
+
+fn collapse(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Stands for a `<br>` while whitespace is collapsed.
+const LINE_BREAK: char = '\u{2028}';
+
+struct Writer<'a> {
+    base: Option<&'a Url>,
+    blocks: Vec<String>,
+    /// Inline content of the current paragraph.
+    inline: String,
+}
+
+impl Writer<'_> {
+    /// Renders the children of a block container.
+    fn container(&mut self, element: ElementRef<'_>) {
+        for child in element.children() {
+            match child.value() {
+                Node::Text(text) => self.inline.push_str(text),
+                Node::Element(_) => {
+                    let Some(child) = ElementRef::wrap(child) else {
+                        continue;
+                    };
+                    if !is_boilerplate(child) {
+                        self.element(child);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn element(&mut self, element: ElementRef<'_>) {
+        let name = element.value().name();
+
+        match name {
+            "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+                self.flush();
+                let level = name[1..].parse().unwrap_or(1);
+                let text = normalize(&self.inline_text(element));
+                if !text.is_empty() {
+                    self.blocks
+                        .push(format!("{} {}", "#".repeat(level), text.replace('\n', " ")));
+                }
+            }
+            "p" => {
+                self.flush();
+                self.inline = self.inline_text(element);
+                self.flush();
+            }
+            "ul" | "ol" => {
+                self.flush();
+                let list = self.list(element, 0);
+                if !list.is_empty() {
+                    self.blocks.push(list);
+                }
+            }
+            "pre" => {
+                self.flush();
+                self.blocks.push(code_block(element));
+            }
+            "blockquote" => {
+                self.flush();
+                let quote = self.nested(element);
+                if !quote.is_empty() {
+                    self.blocks.push(
+                        quote
+                            .lines()
+                            .map(|line| format!("> {line}").trim_end().to_string())
+                            .collect::<Vec<_>>()
+                            .join("\n"),
+                    );
+                }
+            }
+            "table" => {
+                self.flush();
+                match self.table(element) {
+                    Some(table) => self.blocks.push(table),
+                    // Layout tables are read like any other container
+                    None => {
+                        self.container(element);
+                        self.flush();
+                    }
+                }
+            }
+            "hr" => {
+                self.flush();
+                self.blocks.push("---".to_string());
+            }
+            "div" | "section" | "article" | "main" | "figure" | "figcaption" | "dl" | "dd"
+            | "dt" | "details" | "summary" | "body" | "center" => {
+                self.flush();
+                self.container(element);
+                self.flush();
+            }
+            _ => {
+                let inline = self.inline_text(element);
+                self.inline.push_str(&inline);
+            }
+        }
+    }
+
+    /// Ends the current paragraph.
+    fn flush(&mut self) {
+        let paragraph = normalize(&std::mem::take(&mut self.inline));
+        if !paragraph.is_empty() {
+            self.blocks.push(paragraph);
+        }
+    }
+
+    /// Renders an element on its own, as blocks joined by blank lines.
+    fn nested(&self, element: ElementRef<'_>) -> String {
+        let mut writer = Writer {
+            base: self.base,
+            blocks: Vec::new(),
+            inline: String::new(),
+        };
+        writer.container(element);
+        writer.flush();
+        writer.blocks.join("\n\n")
+    }
+
+    /// An element's content as inline Markdown, whitespace not yet
+    /// collapsed.
+    fn inline_text(&self, element: ElementRef<'_>) -> String {
+        let mut text = String::new();
+
+        for child in element.children() {
+            match child.value() {
+                Node::Text(content) => text.push_str(content),
+                Node::Element(_) => {
+                    let Some(child) = ElementRef::wrap(child) else {
+                        continue;
+                    };
+                    if is_boilerplate(child) {
+                        continue;
+                    }
+                    text.push_str(&self.inline_element(child));
+                }
+                _ => {}
+            }
+        }
+
+        text
+    }
+
+    fn inline_element(&self, element: ElementRef<'_>) -> String {
+        match element.value().name() {
+            "br" => LINE_BREAK.to_string(),
+            "img" => String::new(),
+            "strong" | "b" => emphasize(&self.inline_text(element), "**"),
+            "em" | "i" => emphasize(&self.inline_text(element), "*"),
+            "code" | "kbd" | "samp" => {
+                let code = collapse(&element.text().collect::<String>());
+                if code.is_empty() {
+                    String::new()
+                } else {
+                    format!("`{code}`")
+                }
+            }
+            "a" => {
+                let label = normalize(&self.inline_text(element)).replace('\n', " ");
+                match element.attr("href").and_then(|href| self.link(href)) {
+                    Some(href) if !label.is_empty() => format!("[{label}]({href})"),
+                    _ => label,
+                }
+            }
+            // Blocks nested in inline content still separate words
+            "p" | "div" | "li" | "tr" | "td" | "th" => {
+                format!(" {} ", self.inline_text(element))
+            }
+            _ => self.inline_text(element),
+        }
+    }
+
+    fn link(&self, href: &str) -> Option<String> {
+        let href = href.trim();
+        if href.is_empty() || href.starts_with('#') || href.starts_with("javascript:") {
+            return None;
+        }
+
+        match self.base {
+            Some(base) => base.join(href).ok().map(String::from),
+            None => Some(href.to_string()),
+        }
+    }
+
+    fn list(&self, list: ElementRef<'_>, depth: usize) -> String {
+        let ordered = list.value().name() == "ol";
+        let mut number = list
+            .attr("start")
+            .and_then(|start| start.parse().ok())
+            .unwrap_or(1usize);
+        let mut lines = Vec::new();
+
+        for item in list.child_elements() {
+            if item.value().name() != "li" || is_boilerplate(item) {
+                continue;
+            }
+
+            let mut text = String::new();
+            let mut nested = Vec::new();
+
+            for child in item.children() {
+                match child.value() {
+                    Node::Text(content) => text.push_str(content),
+                    Node::Element(_) => {
+                        let Some(child) = ElementRef::wrap(child) else {
+                            continue;
+                        };
+                        match child.value().name() {
+                            _ if is_boilerplate(child) => {}
+                            "ul" | "ol" => nested.push(self.list(child, depth + 1)),
+                            _ => text.push_str(&self.inline_element(child)),
+                        }
+                    }
+                    _ => {}
+                }
+            }
+
+            let marker = if ordered {
+                format!("{number}.")
+            } else {
+                "-".to_string()
+            };
+            number += 1;
+
+            let indent = "  ".repeat(depth);
+            let continuation = format!("\n{indent}{}", " ".repeat(marker.len() + 1));
+            let text = normalize(&text).replace('\n', &continuation);
+
+            if !text.is_empty() {
+                lines.push(format!("{indent}{marker} {text}"));
+            }
+            lines.extend(nested.into_iter().filter(|nested| !nested.is_empty()));
+        }
+
+        lines.join("\n")
+    }
+
+    /// A data table as a Markdown table, or `None` for layout tables with a
+    /// single column or row.
+    fn table(&self, table: ElementRef<'_>) -> Option<String> {
+        let rows_selector = Selector::parse("tr").unwrap();
+
+        let rows: Vec<Vec<String>> = table
+            .select(&rows_selector)
+            // Rows of nested tables belong to those
+            .filter(|row| {
+                ancestors(*row)
+                    .find(|e| e.value().name() == "table")
+                    .is_some_and(|owner| owner.id() == table.id())
+            })
+            .map(|row| {
+                row.child_elements()
+                    .filter(|cell| matches!(cell.value().name(), "td" | "th"))
+                    .map(|cell| {
+                        normalize(&self.inline_text(cell))
+                            .replace('\n', " ")
+                            .replace('|', "\\|")
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .filter(|row| !row.is_empty())
+            .collect();
+
+        let columns = rows.iter().map(Vec::len).max().unwrap_or(0);
+        if columns < 2 || rows.len() < 2 {
+            return None;
+        }
+
+        let line = |row: &[String]| {
+            let mut cells = row.to_vec();
+            cells.resize(columns, String::new());
+            format!("| {} |", cells.join(" | "))
+        };
+
+        let mut lines = vec![line(&rows[0]), line(&vec!["---".to_string(); columns])];
+        lines.extend(rows[1..].iter().map(|row| line(row)));
+
+        Some(lines.join("\n"))
+    }
+}
+
+fn code_block(pre: ElementRef<'_>) -> String {
+    let code = Selector::parse("code").unwrap();
+
+    let language = std::iter::once(pre)
+        .chain(pre.select(&code).next())
+        .flat_map(|element| element.value().classes())
+        .find_map(|class| {
+            class
+                .strip_prefix("language-")
+                .or_else(|| class.strip_prefix("lang-"))
+        })
+        .unwrap_or_default();
+
+    let text = pre.text().collect::<String>();
+    let text = text.trim_matches('\n');
+    let fence = if text.contains("```") { "~~~" } else { "```" };
+
+    format!("{fence}{language}\n{text}\n{fence}")
+}
+
+/// Collapses whitespace, keeping line breaks.
+fn normalize(text: &str) -> String {
+    text.split(LINE_BREAK)
+        .map(collapse)
+        .collect::<Vec<_>>()
+        .join("\n")
+        .trim()
+        .to_string()
+}
+
+/// Wraps text in a marker, keeping surrounding spaces outside of it.
+fn emphasize(text: &str, marker: &str) -> String {
+    let trimmed = text.trim();
+    if trimmed.is_empty() {
+        return text.to_string();
+    }
+
+    let leading = if text.starts_with(char::is_whitespace) {
+        " "
+    } else {
+        ""
+    };
+    let trailing = if text.ends_with(char::is_whitespace) {
+        " "
+    } else {
+        ""
+    };
+
+    format!("{leading}{marker}{trimmed}{marker}{trailing}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn page(body: &str) -> String {
+        format!("<html><head><title> Test  page </title></head><body>{body}</body></html>")
+    }
+
+    #[test]
+    fn test_boilerplate_is_dropped() {
+        let html = page(
+            r#"
+            <nav><a href="/">Home</a> <a href="/about">About</a></nav>
+            <div class="sidebar"><p>Subscribe to our newsletter, it is great, really.</p></div>
+            <div class="post-body">
+                <p>The first paragraph explains, at some length, what this page is about.</p>
+                <p>The second one adds detail, with commas, clauses, and <b>bold</b> words.</p>
+                <div class="share-buttons">Share this</div>
+                <script>track()</script>
+            </div>
+            <footer><p>Copyright notice, all rights reserved, and so on and so forth.</p></footer>
+            "#,
+        );
+
+        let article = extract(&html, None);
+        assert_eq!(article.title.as_deref(), Some("Test page"));
+        assert_eq!(
+            article.markdown,
+            "The first paragraph explains, at some length, what this page is about.\n\n\
+             The second one adds detail, with commas, clauses, and **bold** words."
+        );
+    }
+
+    #[test]
+    fn test_structure_becomes_markdown() {
+        let base = Url::parse("https://example.com/docs/").unwrap();
+        let html = page(
+            r#"
+            <article>
+                <h2>Install <code>peak</code></h2>
+                <p>Run the installer, then read the <a href="guide.html">guide</a>, twice.</p>
+                <ol start="3">
+                    <li>First step</li>
+                    <li>Second step<ul><li>Nested detail</li></ul></li>
+                </ol>
+                <pre class="language-rust"><code>fn main() {
+    println!("hi");
+}</code></pre>
+                <table>
+                    <tr><th>Name</th><th>Size</th></tr>
+                    <tr><td>a|b</td><td>1</td></tr>
+                </table>
+                <blockquote><p>Quoted, with a<br>break.</p></blockquote>
+            </article>
+            "#,
+        );
+
+        let article = extract(&html, Some(&base));
+        assert_eq!(
+            article.markdown,
+            "## Install `peak`\n\n\
+             Run the installer, then read the [guide](https://example.com/docs/guide.html), twice.\n\n\
+             3. First step\n\
+             4. Second step\n  \
+             - Nested detail\n\n\
+             ```rust\nfn main() {\n    println!(\"hi\");\n}\n```\n\n\
+             | Name | Size |\n\
+             | --- | --- |\n\
+             | a\\|b | 1 |\n\n\
+             > Quoted, with a\n\
+             > break."
+        );
+    }
+
+    #[test]
+    fn test_chunks_keep_their_offsets() {
+        let article = Article {
+            title: None,
+            markdown: "# One\n\nAlpha beta gamma.\n\nDelta.\n\n# Two\n\nEpsilon zeta eta theta iota kappa."
+                .to_string(),
+        };
+
+        let chunks = article.chunks(30);
+        let texts: Vec<&str> = chunks.iter().map(|chunk| chunk.text.as_str()).collect();
+        assert_eq!(
+            texts,
+            [
+                "# One\n\nAlpha beta gamma.",
+                "Delta.",
+                "# Two",
+                "Epsilon zeta eta theta iota",
+                "kappa."
+            ]
+        );
+
+        for (i, chunk) in chunks.iter().enumerate() {
+            assert_eq!(chunk.index, i);
+            assert_eq!(&article.markdown[chunk.start..chunk.end], chunk.text);
+        }
+    }
+
+    #[test]
+    fn test_excerpt_is_plain_text() {
+        let article = Article {
+            title: None,
+            markdown: "# Title\n\nShort.\n\nA **long** paragraph that links to [the docs](https://example.com) and `code`."
+                .to_string(),
+        };
+
+        assert_eq!(
+            article.excerpt(200),
+            "A long paragraph that links to the docs and code."
+        );
+        assert_eq!(article.excerpt(20), "A long paragraph…");
+    }
+}
@@ -29,6 +29,9 @@ impl Progress {
     }
 }
 
+/// Streams `url` into `destination`. Bytes already in `destination` are kept
+/// and only the rest is requested, so an interrupted download picks up where
+/// it stopped.
 pub fn download_file<'a>(
     url: impl AsRef<str> + Send + 'a,
     destination: impl AsRef<Path> + Send + 'a,
@@ -37,26 +40,64 @@ pub fn download_file<'a>(
         move |#[cfg_attr(not(feature = "native"), allow(unused_mut))] mut progress| async move {
             #[cfg(feature = "native")]
             {
+                use reqwest::{header, StatusCode};
+
                 let url = url.as_ref();
                 let destination = destination.as_ref();
-                let mut file = io::BufWriter::new(fs::File::create(destination).await?);
 
-                // For now, we use HttpClient which reads the whole body.
-                // In a future update, we can add streaming to HttpClient.
-                let response = crate::http::HttpClient::get(url).await?;
-                let body = response.bytes();
-                let total = Some(body.len() as u64);
+                let offset = match fs::metadata(destination).await {
+                    Ok(metadata) => metadata.len(),
+                    Err(error) if error.kind() == io::ErrorKind::NotFound => 0,
+                    Err(error) => return Err(error.into()),
+                };
+
+                let mut request = reqwest::Client::new().get(url);
+
+                if offset > 0 {
+                    request = request.header(header::RANGE, format!("bytes={offset}-"));
+                }
+
+                let mut response = request.send().await?;
+
+                // The partial file already holds everything
+                if response.status() == StatusCode::RANGE_NOT_SATISFIABLE {
+                    return Ok(());
+                }
+
+                response.error_for_status_ref()?;
+
+                // Servers without range support send the whole body again
+                let resumed = response.status() == StatusCode::PARTIAL_CONTENT;
+                let mut downloaded = if resumed { offset } else { 0 };
+                let total = response.content_length().map(|length| length + downloaded);
+
+                let file = fs::OpenOptions::new()
+                    .create(true)
+                    .write(true)
+                    .append(resumed)
+                    .truncate(!resumed)
+                    .open(destination)
+                    .await?;
+                let mut file = io::BufWriter::new(file);
+
                 let start = Instant::now();
+                let mut received = 0;
+
+                while let Some(chunk) = response.chunk().await? {
+                    file.write_all(&chunk).await?;
+
+                    downloaded += chunk.len() as u64;
+                    received += chunk.len() as u64;
 
-                progress
-                    .send(Progress {
-                        total,
-                        downloaded: body.len() as u64,
-                        speed: (body.len() as f32 / start.elapsed().as_secs_f32()) as u64,
-                    })
-                    .await;
+                    progress
+                        .send(Progress {
+                            total,
+                            downloaded,
+                            speed: (received as f32 / start.elapsed().as_secs_f32()) as u64,
+                        })
+                        .await;
+                }
 
-                file.write_all(body).await?;
                 file.flush().await?;
 
                 Ok(())
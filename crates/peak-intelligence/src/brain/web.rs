@@ -1,5 +1,7 @@
 use crate::brain::assistant::Message;
+use crate::brain::readable::{self, Article, Chunk};
 use crate::brain::{Assistant, Error, Url};
+use crate::tools::search_router::{self, SearchRouter};
 
 use sipper::{sipper, Sipper, Straw};
 
@@ -11,6 +13,8 @@ pub struct Search {
 pub struct Summary {
     pub url: Url,
     pub content: String,
+    /// Passages of the page the summary cites.
+    pub passages: Vec<Chunk>,
 }
 
 impl Summary {
@@ -19,46 +23,21 @@ impl Summary {
     }
 }
 
+/// Searches with the global [`SearchRouter`], so providers registered or
+/// configured there apply to plans too.
 pub async fn search(query: &str) -> Result<Search, Error> {
-    log::info!("Searching on DuckDuckGo: {query}");
-
-    let mut headers = std::collections::HashMap::new();
-    headers.insert(
-        "User-Agent".to_string(),
-        "Mozilla/5.0 (X11; Linux x86_64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/132.0.0.0 Safari/537.36".to_string(),
-    );
-    headers.insert("Accept".to_string(), "*/*".to_string());
-
-    let url = format!(
-        "https://html.duckduckgo.com/html/?q={}",
-        urlencoding::encode(query)
-    );
-
-    let response = crate::http::HttpClient::get_with_headers(&url, headers).await?;
-    let search_results = response
-        .text()
-        .map_err(|e| Error::WasmError(format!("Invalid text response: {}", e)))?;
-
-    let html = scraper::Html::parse_document(&search_results);
-    let selector = scraper::Selector::parse(".result__a").unwrap();
-
-    let results = html
-        .select(&selector)
-        .filter_map(|link| {
-            let encoded = link.attr("href")?;
-
-            if encoded.contains("ad_domain") {
-                return None;
-            }
-
-            let query_pairs = url::form_urlencoded::parse(encoded.as_bytes());
-            for (_key, value) in query_pairs {
-                if let Ok(url) = url::Url::parse(&value) {
-                    return Some(url);
-                }
-            }
-            None
-        })
+    search_with(&search_router::global(), query).await
+}
+
+pub async fn search_with(router: &SearchRouter, query: &str) -> Result<Search, Error> {
+    log::info!("Searching: {query}");
+
+    let results = router
+        .search(query, None, None)
+        .await
+        .map_err(|e| Error::RequestFailed(e.to_string()))?
+        .into_iter()
+        .filter_map(|result| Url::parse(&result.url).ok())
         .take(5)
         .collect();
 
@@ -67,13 +46,30 @@ pub async fn search(query: &str) -> Result<Search, Error> {
     Ok(Search { results })
 }
 
+/// Longest passage a page is split into for summarizing, in bytes.
+const PASSAGE_LEN: usize = 1_200;
+
+/// Passages of a page the model reads at most.
+const MAX_PASSAGES: usize = 20;
+
 pub fn summarize<'a>(
     assistant: &'a Assistant,
     query: &'a str,
     url: Url,
 ) -> impl Straw<Summary, Summary, Error> + 'a {
     sipper(move |sender| async move {
-        let text = scrape(url.clone()).await?;
+        let article = read(url.clone()).await?;
+        let passages: Vec<Chunk> = article
+            .chunks(PASSAGE_LEN)
+            .into_iter()
+            .take(MAX_PASSAGES)
+            .collect();
+
+        let text = passages
+            .iter()
+            .map(|passage| format!("[{}] {}", passage.index + 1, passage.text))
+            .collect::<Vec<_>>()
+            .join("\n\n");
 
         let reply = assistant
             .clone()
@@ -84,12 +80,14 @@ pub fn summarize<'a>(
                     {text}\n\
                     ```\n\n\
                     Please, summarize the parts of the previous text that \
-                    are relevant to the query: \"{query}\"."
+                    are relevant to the query: \"{query}\". Cite the numbered \
+                    passages you rely on, like [1]."
                 ))],
                 vec![],
             )
             .with(|(reply, _token)| Summary {
                 url: url.clone(),
+                passages: cited(&reply.content, &passages),
                 content: reply.content,
             })
             .run(sender)
@@ -97,40 +95,109 @@ pub fn summarize<'a>(
 
         Ok(Summary {
             url,
+            passages: cited(&reply.content, &passages),
             content: reply.content,
         })
     })
 }
 
-async fn scrape(url: Url) -> Result<String, Error> {
-    log::info!("Scraping text: {url}");
-
-    let candidates = scraper::Selector::parse("p, a").unwrap();
-
-    let mut headers = std::collections::HashMap::new();
-    headers.insert(
-        "User-Agent".to_string(),
-        "Mozilla/5.0 (X11; Linux x86_64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/132.0.0.0 Safari/537.36".to_string(),
-    );
+/// The passages a summary refers to with `[n]`, in page order.
+fn cited(content: &str, passages: &[Chunk]) -> Vec<Chunk> {
+    passages
+        .iter()
+        .filter(|passage| content.contains(&format!("[{}]", passage.index + 1)))
+        .cloned()
+        .collect()
+}
 
-    let response = crate::http::HttpClient::get_with_headers(url.as_str(), headers).await?;
-    let html = response
-        .text()
-        .map_err(|e| Error::WasmError(format!("Invalid text response: {}", e)))?;
+/// Loads a page and extracts its main content. `file://` URLs are read
+/// from disk, which lets local HTML fixtures stand in for sites.
+pub async fn read(url: Url) -> Result<Article, Error> {
+    log::info!("Reading: {url}");
+
+    let html = if url.scheme() == "file" {
+        read_file(&url)?
+    } else {
+        let mut headers = std::collections::HashMap::new();
+        headers.insert(
+            "User-Agent".to_string(),
+            "Mozilla/5.0 (X11; Linux x86_64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/132.0.0.0 Safari/537.36".to_string(),
+        );
+
+        let response = crate::http::HttpClient::get_with_headers(url.as_str(), headers).await?;
+        response
+            .text()
+            .map_err(|e| Error::WasmError(format!("Invalid text response: {}", e)))?
+    };
 
     log::info!("-- HTML retrieved ({} chars)", html.len());
     log::trace!("{html}");
 
-    let html = scraper::Html::parse_document(&html);
+    let article = readable::extract(&html, Some(&url));
 
-    let lines = html
-        .select(&candidates)
-        .flat_map(|candidate| candidate.text())
-        .map(str::trim)
-        .filter(|text| !text.is_empty())
-        .collect::<Vec<_>>();
+    log::info!("-- Extracted {} chars of Markdown", article.markdown.len());
 
-    log::info!("-- Scraped {} lines of text", lines.len());
+    Ok(article)
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn read_file(url: &Url) -> Result<String, Error> {
+    let path = url
+        .to_file_path()
+        .map_err(|_| Error::RequestFailed(format!("Invalid file URL: {url}")))?;
 
-    Ok(lines.join("\n"))
+    Ok(std::fs::read_to_string(path)?)
+}
+
+#[cfg(target_arch = "wasm32")]
+fn read_file(url: &Url) -> Result<String, Error> {
+    Err(Error::WasmError(format!("Cannot read local file {url}")))
+}
+
+#[cfg(all(test, feature = "native"))]
+mod tests {
+    use super::*;
+    use crate::tools::search_router::MockSearchProvider;
+
+    #[tokio::test]
+    async fn test_search_offline() {
+        let dir = tempfile::tempdir().unwrap();
+        let fixtures = dir.path().join("fixtures.json");
+        std::fs::write(
+            &fixtures,
+            r#"{
+                "rust": [
+                    { "title": "Rust", "url": "https://www.rust-lang.org/" },
+                    { "title": "Not a URL", "url": "rust-lang.org" }
+                ]
+            }"#,
+        )
+        .unwrap();
+
+        let router = SearchRouter::empty().with_provider(MockSearchProvider::new(&fixtures));
+
+        let search = search_with(&router, "Rust").await.unwrap();
+        assert_eq!(
+            search.results,
+            [Url::parse("https://www.rust-lang.org/").unwrap()]
+        );
+
+        assert!(matches!(
+            search_with(&router, "go").await,
+            Err(Error::RequestFailed(_))
+        ));
+    }
+
+    #[test]
+    fn test_summaries_keep_cited_passages() {
+        let article = Article {
+            title: None,
+            markdown: "# Intro\n\nFirst.\n\n# Usage\n\nSecond.".to_string(),
+        };
+        let passages = article.chunks(100);
+
+        let cited = cited("Use it like this [2], not [3].", &passages);
+        assert_eq!(cited.len(), 1);
+        assert_eq!(cited[0].text, "# Usage\n\nSecond.");
+    }
 }
@@ -0,0 +1,505 @@
+//! Prompt budgeting for [`LlmClient`]: token estimates, per-provider context
+//! budgets and compaction of long conversations.
+//!
+//! Compaction runs in priority order. Stale UI snapshots go first, then older
+//! turns are folded into a summary, then the oldest remaining turns are
+//! dropped. As a last resort the latest UI snapshot is truncated. System
+//! instructions and the latest turn are never removed.
+use crate::llm::{LlmClient, Message, ModelProvider};
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+
+/// Marks the system message that carries the summary of older turns.
+pub const SUMMARY_HEADER: &str = "### CONVERSATION SUMMARY";
+/// Marks system messages carrying a serialized `SemanticNode` tree.
+pub const UI_CONTEXT_HEADER: &str = "UI CONTEXT:";
+
+const STALE_SNAPSHOT: &str = "UI CONTEXT: [stale snapshot omitted]";
+const TRUNCATED: &str = "\n...[truncated to fit the context window]";
+/// Role and separator tokens every chat template adds around a message.
+const MESSAGE_OVERHEAD: usize = 4;
+
+const SUMMARY_PROMPT: &str = "Summarize the conversation below for your own future reference. \
+     Keep facts the user shared, decisions made, tool results that still matter and open tasks. \
+     Omit pleasantries. Answer with the summary only, in at most 200 words.";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ContextBudget {
+    /// Size of the model's context window.
+    pub max_tokens: usize,
+    /// Tokens kept free for the reply.
+    pub reply_tokens: usize,
+    /// Most recent messages that are never summarized.
+    pub keep_recent: usize,
+}
+
+impl ContextBudget {
+    pub fn new(max_tokens: usize) -> Self {
+        Self {
+            max_tokens,
+            reply_tokens: max_tokens / 4,
+            keep_recent: 6,
+        }
+    }
+
+    /// Conservative defaults: local runtimes are assumed to run with a small
+    /// window, hosted models with a mid-sized one.
+    pub fn for_provider(provider: ModelProvider) -> Self {
+        match provider {
+            ModelProvider::Ollama | ModelProvider::LlamaCpp => Self::new(4_096),
+            ModelProvider::OpenRouter => Self::new(32_768),
+        }
+    }
+
+    pub fn reply_tokens(mut self, reply_tokens: usize) -> Self {
+        self.reply_tokens = reply_tokens;
+        self
+    }
+
+    pub fn keep_recent(mut self, keep_recent: usize) -> Self {
+        self.keep_recent = keep_recent;
+        self
+    }
+
+    /// Tokens available to the prompt once the reply is reserved.
+    pub fn prompt_tokens(&self) -> usize {
+        self.max_tokens.saturating_sub(self.reply_tokens)
+    }
+
+    /// The budget left after `tokens` are spent elsewhere, e.g. on a system
+    /// prompt added downstream.
+    pub fn without(mut self, tokens: usize) -> Self {
+        self.max_tokens = self.max_tokens.saturating_sub(tokens);
+        self
+    }
+}
+
+/// Something compaction did to the prompt.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ContextEvent {
+    /// Older UI snapshots were replaced by a placeholder.
+    SnapshotsDropped(usize),
+    /// The oldest `turns` messages were folded into `summary`.
+    Summarized { turns: usize, summary: String },
+    /// Conversation turns removed without being summarized. An earlier
+    /// summary removed along with them isn't counted.
+    TurnsDropped(usize),
+    /// The latest UI snapshot was cut down to `tokens`.
+    SnapshotTruncated { tokens: usize },
+}
+
+#[derive(Debug, Clone)]
+pub struct Compacted {
+    pub messages: Vec<Message>,
+    pub before_tokens: usize,
+    pub after_tokens: usize,
+    pub events: Vec<ContextEvent>,
+}
+
+impl Compacted {
+    pub fn is_compacted(&self) -> bool {
+        !self.events.is_empty()
+    }
+}
+
+/// Estimates how many tokens `text` takes, erring on the high side.
+///
+/// Runs of ASCII letters and digits are split into word pieces, punctuation
+/// costs a token per character and non-ASCII characters a token per two
+/// UTF-8 bytes. Local models use smaller vocabularies, so pieces are shorter.
+pub fn estimate_tokens(provider: ModelProvider, text: &str) -> usize {
+    let piece = match provider {
+        ModelProvider::Ollama | ModelProvider::LlamaCpp => 3,
+        ModelProvider::OpenRouter => 4,
+    };
+
+    let mut tokens = 0;
+    let mut run = 0usize;
+
+    for c in text.chars() {
+        if c.is_ascii_alphanumeric() {
+            run += 1;
+            continue;
+        }
+
+        tokens += run.div_ceil(piece);
+        run = 0;
+
+        if c.is_ascii_whitespace() {
+            continue;
+        }
+
+        tokens += if c.is_ascii() {
+            1
+        } else {
+            c.len_utf8().div_ceil(2)
+        };
+    }
+
+    tokens + run.div_ceil(piece)
+}
+
+pub fn count_tokens(provider: ModelProvider, messages: &[Message]) -> usize {
+    messages
+        .iter()
+        .map(|message| estimate_tokens(provider, &message.content) + MESSAGE_OVERHEAD)
+        .sum()
+}
+
+pub fn is_summary(message: &Message) -> bool {
+    message.role == "system" && message.content.starts_with(SUMMARY_HEADER)
+}
+
+pub fn is_ui_snapshot(message: &Message) -> bool {
+    message.role == "system"
+        && (message.content.starts_with(UI_CONTEXT_HEADER)
+            || message.content.starts_with("UI Context:"))
+}
+
+/// Fits `messages` into `budget`, folding older turns through `summarize`.
+///
+/// A failed summary falls back to dropping the turns it would have covered.
+pub async fn compact<F, Fut>(
+    provider: ModelProvider,
+    budget: ContextBudget,
+    mut messages: Vec<Message>,
+    summarize: F,
+) -> Compacted
+where
+    F: FnOnce(Vec<Message>) -> Fut,
+    Fut: Future<Output = Result<String, String>>,
+{
+    let limit = budget.prompt_tokens();
+    let before_tokens = count_tokens(provider, &messages);
+
+    if before_tokens <= limit {
+        return Compacted {
+            messages,
+            before_tokens,
+            after_tokens: before_tokens,
+            events: Vec::new(),
+        };
+    }
+
+    let mut events = Vec::new();
+
+    drop_stale_snapshots(&mut messages, &mut events);
+
+    if count_tokens(provider, &messages) > limit {
+        let head = pinned_head(&messages);
+        let foldable = messages
+            .len()
+            .saturating_sub(head + budget.keep_recent.max(1));
+
+        if foldable > 0 {
+            let older: Vec<Message> = messages.drain(head..head + foldable).collect();
+            let turns = older.iter().filter(|m| !is_summary(m)).count();
+
+            match summarize(older).await {
+                Ok(summary) if !summary.trim().is_empty() => {
+                    let summary = summary.trim().to_string();
+                    messages.insert(
+                        head,
+                        Message {
+                            role: "system".to_string(),
+                            content: format!("{SUMMARY_HEADER}\n{summary}"),
+                        },
+                    );
+                    events.push(ContextEvent::Summarized { turns, summary });
+                }
+                _ => events.push(ContextEvent::TurnsDropped(turns)),
+            }
+        }
+    }
+
+    fit(provider, limit, &mut messages, &mut events);
+
+    Compacted {
+        after_tokens: count_tokens(provider, &messages),
+        messages,
+        before_tokens,
+        events,
+    }
+}
+
+/// Fits `messages` into `budget` without summarizing. Used as a last guard
+/// right before a request is sent.
+pub fn truncate(
+    provider: ModelProvider,
+    budget: ContextBudget,
+    mut messages: Vec<Message>,
+) -> Compacted {
+    let before_tokens = count_tokens(provider, &messages);
+    let mut events = Vec::new();
+
+    if before_tokens > budget.prompt_tokens() {
+        drop_stale_snapshots(&mut messages, &mut events);
+        fit(provider, budget.prompt_tokens(), &mut messages, &mut events);
+    }
+
+    Compacted {
+        after_tokens: count_tokens(provider, &messages),
+        messages,
+        before_tokens,
+        events,
+    }
+}
+
+/// Summarizes `turns` with `client`, keeping the transcript within `budget`.
+pub async fn summarize(
+    client: &LlmClient,
+    budget: ContextBudget,
+    turns: Vec<Message>,
+) -> Result<String, String> {
+    let provider = client.provider();
+    let room = budget
+        .prompt_tokens()
+        .saturating_sub(estimate_tokens(provider, SUMMARY_PROMPT) + 2 * MESSAGE_OVERHEAD);
+
+    // Keep the most recent lines when even the transcript is too large
+    let mut lines: Vec<String> = turns
+        .iter()
+        .map(|m| format!("{}: {}", m.role, m.content))
+        .collect();
+    let mut tokens: usize = lines.iter().map(|l| estimate_tokens(provider, l)).sum();
+    while tokens > room && lines.len() > 1 {
+        tokens -= estimate_tokens(provider, &lines.remove(0));
+    }
+    let transcript = truncate_text(provider, &lines.join("\n"), room);
+
+    client
+        .chat(vec![
+            Message {
+                role: "system".to_string(),
+                content: SUMMARY_PROMPT.to_string(),
+            },
+            Message {
+                role: "user".to_string(),
+                content: transcript,
+            },
+        ])
+        .await
+}
+
+/// Leading system messages (instructions, UI context, memories) that are kept
+/// out of summaries. An earlier summary is folded into the next one.
+fn pinned_head(messages: &[Message]) -> usize {
+    messages
+        .iter()
+        .take_while(|m| m.role == "system" && !is_summary(m))
+        .count()
+}
+
+fn drop_stale_snapshots(messages: &mut [Message], events: &mut Vec<ContextEvent>) {
+    let Some(latest) = messages.iter().rposition(is_ui_snapshot) else {
+        return;
+    };
+
+    let mut dropped = 0;
+    for message in &mut messages[..latest] {
+        if is_ui_snapshot(message) && message.content != STALE_SNAPSHOT {
+            message.content = STALE_SNAPSHOT.to_string();
+            dropped += 1;
+        }
+    }
+
+    if dropped > 0 {
+        events.push(ContextEvent::SnapshotsDropped(dropped));
+    }
+}
+
+fn fit(
+    provider: ModelProvider,
+    limit: usize,
+    messages: &mut Vec<Message>,
+    events: &mut Vec<ContextEvent>,
+) {
+    let mut total = count_tokens(provider, messages);
+
+    // Drop the oldest turns after the pinned head, keeping the latest one
+    let head = pinned_head(messages);
+    let mut dropped = 0;
+    while total > limit && messages.len() > head + 1 {
+        let index = if is_summary(&messages[head]) && messages.len() > head + 2 {
+            head + 1
+        } else {
+            head
+        };
+        let removed = messages.remove(index);
+        total -= estimate_tokens(provider, &removed.content) + MESSAGE_OVERHEAD;
+        if !is_summary(&removed) {
+            dropped += 1;
+        }
+    }
+    if dropped > 0 {
+        events.push(ContextEvent::TurnsDropped(dropped));
+    }
+
+    // Shrink the UI snapshot as a last resort
+    if total > limit {
+        if let Some(snapshot) = messages.iter_mut().rev().find(|m| is_ui_snapshot(m)) {
+            let current = estimate_tokens(provider, &snapshot.content);
+            let tokens = current.saturating_sub(total - limit);
+            snapshot.content = truncate_text(provider, &snapshot.content, tokens);
+            events.push(ContextEvent::SnapshotTruncated { tokens });
+        }
+    }
+}
+
+/// Cuts `text` down to roughly `max_tokens`, keeping its beginning.
+fn truncate_text(provider: ModelProvider, text: &str, max_tokens: usize) -> String {
+    let tokens = estimate_tokens(provider, text);
+    if tokens <= max_tokens {
+        return text.to_string();
+    }
+
+    let budget = max_tokens.saturating_sub(estimate_tokens(provider, TRUNCATED));
+    let chars = text.chars().count();
+    let mut keep = chars * budget / tokens.max(1);
+
+    loop {
+        let end = text
+            .char_indices()
+            .nth(keep)
+            .map_or(text.len(), |(index, _)| index);
+        let cut = &text[..end];
+
+        if keep == 0 || estimate_tokens(provider, cut) <= budget {
+            return format!("{cut}{TRUNCATED}");
+        }
+
+        keep = keep * 9 / 10;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::executor::block_on;
+
+    const PROVIDER: ModelProvider = ModelProvider::Ollama;
+
+    fn message(role: &str, content: impl Into<String>) -> Message {
+        Message {
+            role: role.to_string(),
+            content: content.into(),
+        }
+    }
+
+    fn conversation(turns: usize) -> Vec<Message> {
+        let mut messages = vec![
+            message("system", "You are PeakUI AI Assistant."),
+            message(
+                "system",
+                format!(
+                    "{UI_CONTEXT_HEADER}\n{}",
+                    "{\"role\":\"button\"}".repeat(20)
+                ),
+            ),
+        ];
+        for i in 0..turns {
+            let role = if i % 2 == 0 { "user" } else { "assistant" };
+            messages.push(message(
+                role,
+                format!("turn {i} {}", "lorem ipsum dolor ".repeat(10)),
+            ));
+        }
+        messages
+    }
+
+    #[test]
+    fn test_estimates_are_conservative() {
+        assert_eq!(estimate_tokens(PROVIDER, ""), 0);
+        assert_eq!(estimate_tokens(PROVIDER, "hello world"), 4);
+        assert!(estimate_tokens(PROVIDER, "{\"a\":1}") >= 5);
+        assert!(
+            estimate_tokens(ModelProvider::OpenRouter, "internationalization")
+                < estimate_tokens(PROVIDER, "internationalization")
+        );
+        assert_eq!(estimate_tokens(PROVIDER, "日本"), 4);
+    }
+
+    #[test]
+    fn test_small_prompts_are_untouched() {
+        let messages = conversation(2);
+        let compacted = block_on(compact(
+            PROVIDER,
+            ContextBudget::new(100_000),
+            messages.clone(),
+            |_| async { Err("unused".to_string()) },
+        ));
+
+        assert!(!compacted.is_compacted());
+        assert_eq!(compacted.messages.len(), messages.len());
+    }
+
+    #[test]
+    fn test_older_turns_are_summarized() {
+        let messages = conversation(20);
+        let budget = ContextBudget::new(1_000).reply_tokens(200).keep_recent(4);
+
+        let compacted = block_on(compact(PROVIDER, budget, messages, |older| async move {
+            Ok(format!("{} earlier messages", older.len()))
+        }));
+
+        assert!(compacted.after_tokens <= budget.prompt_tokens());
+        assert!(compacted.after_tokens < compacted.before_tokens);
+        assert!(compacted.events.contains(&ContextEvent::Summarized {
+            turns: 16,
+            summary: "16 earlier messages".to_string()
+        }));
+        assert!(is_ui_snapshot(&compacted.messages[1]));
+        assert!(is_summary(&compacted.messages[2]));
+        assert!(compacted
+            .messages
+            .last()
+            .unwrap()
+            .content
+            .starts_with("turn 19"));
+    }
+
+    #[test]
+    fn test_failed_summaries_drop_turns() {
+        let budget = ContextBudget::new(1_000).reply_tokens(200).keep_recent(4);
+
+        let compacted = block_on(compact(PROVIDER, budget, conversation(20), |_| async {
+            Err("offline".to_string())
+        }));
+
+        assert!(compacted.events.contains(&ContextEvent::TurnsDropped(16)));
+        assert_eq!(compacted.messages.len(), 6);
+    }
+
+    #[test]
+    fn test_stale_snapshots_are_replaced_before_summarizing() {
+        let mut messages = conversation(4);
+        messages.insert(3, messages[1].clone());
+        let budget = ContextBudget::new(600).reply_tokens(0);
+
+        let compacted = block_on(compact(PROVIDER, budget, messages, |_| async {
+            Err("unused".to_string())
+        }));
+
+        assert_eq!(compacted.events[0], ContextEvent::SnapshotsDropped(1));
+        assert_eq!(compacted.messages[1].content, STALE_SNAPSHOT);
+    }
+
+    #[test]
+    fn test_oversized_snapshots_are_truncated() {
+        let messages = vec![
+            message("system", "Instructions."),
+            message(
+                "system",
+                format!("{UI_CONTEXT_HEADER}\n{}", "{\"node\":1}".repeat(500)),
+            ),
+            message("user", "What is on screen?"),
+        ];
+        let budget = ContextBudget::new(300).reply_tokens(0);
+
+        let compacted = truncate(PROVIDER, budget, messages);
+
+        assert!(compacted.after_tokens <= 300);
+        assert!(compacted.messages[1].content.ends_with(TRUNCATED));
+        assert_eq!(compacted.messages[2].content, "What is on screen?");
+    }
+}
@@ -1,4 +1,5 @@
 pub mod brain;
+pub mod context;
 pub mod http;
 
 #[cfg(feature = "native")]
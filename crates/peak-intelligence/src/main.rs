@@ -2,26 +2,17 @@
 use anyhow::Result;
 
 #[cfg(not(target_arch = "wasm32"))]
-mod mcp;
-#[cfg(not(target_arch = "wasm32"))]
-mod terminal;
-#[cfg(not(target_arch = "wasm32"))]
-mod tools;
-#[cfg(not(target_arch = "wasm32"))]
-mod voice;
+use peak_intelligence::{mcp, terminal, tools};
 
 #[cfg(not(target_arch = "wasm32"))]
 #[cfg(feature = "voice")]
-use crate::voice::VoiceManager;
+use peak_intelligence::voice::{self, VoiceManager};
 #[cfg(not(target_arch = "wasm32"))]
 #[cfg(feature = "voice")]
 use voice::VOICE;
 
 #[cfg(not(target_arch = "wasm32"))]
-use mcp::{
-    CallToolParams, CallToolResult, JsonRpcRequest, JsonRpcResponse, ListToolsResult, Tool,
-    ToolContent,
-};
+use mcp::{JsonRpcRequest, JsonRpcResponse, Server, PARSE_ERROR};
 #[cfg(not(target_arch = "wasm32"))]
 use once_cell::sync::Lazy;
 #[cfg(not(target_arch = "wasm32"))]
@@ -29,38 +20,39 @@ use peak_intelligence::kernel;
 #[cfg(not(target_arch = "wasm32"))]
 use serde_json::json;
 #[cfg(not(target_arch = "wasm32"))]
+use std::sync::Arc;
+#[cfg(not(target_arch = "wasm32"))]
 use terminal::TerminalManager;
 #[cfg(not(target_arch = "wasm32"))]
 use tokio::io::{self, AsyncBufReadExt, AsyncWriteExt, BufReader};
 #[cfg(not(target_arch = "wasm32"))]
-use tokio::sync::mpsc;
+use tokio::sync::{broadcast, mpsc};
 #[cfg(not(target_arch = "wasm32"))]
 use tokio::time::{sleep, Duration};
 
 #[cfg(not(target_arch = "wasm32"))]
-use tools::fs_tools::{ReadDirTool, ReadFileTool, SearchFilesTool, WriteFileTool};
-#[cfg(not(target_arch = "wasm32"))]
-use tools::registry::ToolRegistry;
-#[cfg(not(target_arch = "wasm32"))]
-use tools::search_tool::WebSearchTool;
+use futures::StreamExt;
 #[cfg(not(target_arch = "wasm32"))]
-use tools::system_tools::{KillProcessTool, ListProcessesTool, SystemSnapshotTool};
+use tools::approval::Approvals;
 #[cfg(not(target_arch = "wasm32"))]
-use tools::terminal_tools::{TerminalOpenTool, TerminalResizeTool, TerminalWriteTool};
+use tools::registry::ToolRegistry;
 
 #[cfg(not(target_arch = "wasm32"))]
 static TERMINAL: Lazy<TerminalManager> = Lazy::new(TerminalManager::new);
+
+/// Address the streamable HTTP transport listens on when `--http` is given
+/// without one.
 #[cfg(not(target_arch = "wasm32"))]
-static TOOL_REGISTRY: Lazy<tokio::sync::RwLock<ToolRegistry>> =
-    Lazy::new(|| tokio::sync::RwLock::new(ToolRegistry::new()));
+const DEFAULT_HTTP_ADDR: &str = "127.0.0.1:8082";
 
 #[cfg(not(target_arch = "wasm32"))]
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
     // Verify Icebreaker Core Linkage
     let lib = peak_intelligence::brain::model::Library::default();
-    println!("Peak Intelligence initialized.");
-    println!("🤖 AI Model Directory: {:?}", lib.directory());
+    // Stdout carries the JSON-RPC stream, so diagnostics go to stderr
+    eprintln!("Peak Intelligence initialized.");
+    eprintln!("🤖 AI Model Directory: {:?}", lib.directory());
 
     // Setup Stdio
     let stdin = io::stdin();
@@ -69,40 +61,30 @@ async fn main() -> anyhow::Result<()> {
 
     let (tx, mut rx) = mpsc::channel::<String>(100);
 
+    // Server notifications, fanned out to stdio and HTTP event streams
+    let (notify_tx, mut notify_rx) = mpsc::channel::<String>(100);
+    let (events, _) = broadcast::channel::<String>(100);
+
+    // Protected tool calls wait here until the app decides on them
+    let approvals = Approvals::new();
+
     // Initialize Tool Registry
-    {
-        let mut registry = TOOL_REGISTRY.write().await;
-
-        // FS Tools
-        registry.register(ReadFileTool);
-        registry.register(WriteFileTool);
-        registry.register(ReadDirTool);
-        registry.register(SearchFilesTool);
-
-        // System Tools
-        registry.register(ListProcessesTool);
-        registry.register(KillProcessTool);
-        registry.register(SystemSnapshotTool);
-
-        // Search Tool
-        registry.register(WebSearchTool);
-
-        // Terminal Tools
-        registry.register(TerminalOpenTool {
-            manager: &TERMINAL,
-            tx: tx.clone(),
-        });
-        registry.register(TerminalWriteTool { manager: &TERMINAL });
-        registry.register(TerminalResizeTool { manager: &TERMINAL });
-
-        // Voice Tools
-        #[cfg(feature = "voice")]
-        {
-            use crate::tools::voice_tools::{SttTool, TtsTool};
-            registry.register(SttTool);
-            registry.register(TtsTool);
-        }
-    }
+    let server = {
+        let mut registry = ToolRegistry::new().with_approvals(approvals.clone());
+
+        tools::register_builtin(&mut registry, &TERMINAL, notify_tx.clone());
+
+        // External MCP Servers
+        import_external_tools(&mut registry).await;
+
+        // Only the app that launched us, holding the token, decides;
+        // without one protected calls are denied
+        let server = Server::new(registry);
+        Arc::new(match std::env::var("PEAK_APPROVAL_TOKEN") {
+            Ok(token) if !token.is_empty() => server.with_approval_token(token),
+            _ => server,
+        })
+    };
 
     // Stdout writer task
     let _stdout_tx = tx.clone();
@@ -115,8 +97,58 @@ async fn main() -> anyhow::Result<()> {
         }
     });
 
+    // Notification fan-out task
+    let stdio_tx = tx.clone();
+    let stdio_server = server.clone();
+    let http_events = events.clone();
+    tokio::spawn(async move {
+        while let Some(msg) = notify_rx.recv().await {
+            // Notifications wait for the client to finish the handshake
+            if stdio_server.is_initialized() {
+                let _ = stdio_tx.send(msg.clone()).await;
+            }
+            let _ = http_events.send(msg);
+        }
+    });
+
+    // Approval queue changes, so agents can wait for decisions
+    let approval_tx = notify_tx.clone();
+    let mut approval_events = approvals.subscribe();
+    tokio::spawn(async move {
+        while let Some(event) = approval_events.next().await {
+            let notification = mcp::server::approval_notification(&event);
+            let _ = approval_tx.send(notification.to_string()).await;
+        }
+    });
+
+    // Streamable HTTP transport
+    let http = match http_addr()? {
+        Some(addr) => {
+            // Clients beyond this machine must present the token
+            let token = std::env::var("PEAK_MCP_TOKEN")
+                .ok()
+                .filter(|token| !token.is_empty());
+            let listener = tokio::net::TcpListener::bind(&addr).await?;
+            if token.is_none() && !listener.local_addr()?.ip().is_loopback() {
+                anyhow::bail!("Set PEAK_MCP_TOKEN to serve MCP over HTTP beyond localhost");
+            }
+            eprintln!(
+                "MCP HTTP transport listening on http://{}{}",
+                addr,
+                mcp::http::ENDPOINT
+            );
+            Some(tokio::spawn(mcp::http::serve(
+                listener,
+                server.clone(),
+                events.clone(),
+                token,
+            )))
+        }
+        None => None,
+    };
+
     // --- DEEP CORE TELEMETRY LOOP ---
-    let telemetry_tx = tx.clone();
+    let telemetry_tx = notify_tx.clone();
     tokio::spawn(async move {
         loop {
             // "The Deep Core Pulse" - 2 seconds
@@ -146,15 +178,18 @@ async fn main() -> anyhow::Result<()> {
         match request {
             Ok(req) => {
                 let tx_clone = tx.clone();
+                let server = server.clone();
                 tokio::spawn(async move {
-                    let response = handle_request(req, tx_clone.clone()).await;
+                    let Some(response) = server.handle(req).await else {
+                        return;
+                    };
                     if let Ok(response_str) = serde_json::to_string(&response) {
                         let _ = tx_clone.send(response_str).await;
                     }
                 });
             }
             Err(_e) => {
-                let err_res = JsonRpcResponse::error(None, -32700, "Parse error".into());
+                let err_res = JsonRpcResponse::error(None, PARSE_ERROR, "Parse error".into());
                 if let Ok(err_str) = serde_json::to_string(&err_res) {
                     let _ = tx.send(err_str).await;
                 }
@@ -162,77 +197,53 @@ async fn main() -> anyhow::Result<()> {
         }
     }
 
+    // Keep serving HTTP clients once stdin closes
+    if let Some(http) = http {
+        http.await??;
+    }
+
     Ok(())
 }
 
+/// Registers the tools of the servers configured in `mcp.toml`. Servers
+/// that fail to start are reported and skipped.
 #[cfg(not(target_arch = "wasm32"))]
-async fn handle_request(req: JsonRpcRequest, _tx: mpsc::Sender<String>) -> JsonRpcResponse {
-    match req.method.as_str() {
-        "tools/list" => {
-            let registry = TOOL_REGISTRY.read().await;
-            let tools_raw = registry.list_tools();
-
-            // Map to MCP Tool format
-            let tools: Vec<Tool> = tools_raw
-                .into_iter()
-                .map(|t| Tool {
-                    name: t["name"].as_str().unwrap_or_default().into(),
-                    description: t["description"].as_str().unwrap_or_default().into(),
-                    input_schema: t["input_schema"].clone(),
-                })
-                .collect();
-
-            JsonRpcResponse::success(
-                req.id,
-                serde_json::to_value(ListToolsResult { tools }).unwrap(),
-            )
+async fn import_external_tools(registry: &mut ToolRegistry) {
+    let config = match mcp::client::Config::load() {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("⚠️ {:#}", e);
+            return;
         }
-        "tools/call" => {
-            if let Some(params) = req.params {
-                let call_params: Result<CallToolParams, _> = serde_json::from_value(params);
-                match call_params {
-                    Ok(p) => {
-                        let registry = TOOL_REGISTRY.read().await;
-                        let args = p.arguments.unwrap_or(json!({}));
-
-                        match registry.call(&p.name, args).await {
-                            Ok(tool_res) => {
-                                let content = vec![ToolContent {
-                                    r#type: "text".into(),
-                                    text: tool_res.to_string(),
-                                }];
-                                JsonRpcResponse::success(
-                                    req.id,
-                                    serde_json::to_value(CallToolResult {
-                                        content,
-                                        is_error: Some(false),
-                                    })
-                                    .unwrap(),
-                                )
-                            }
-                            Err(e) => {
-                                let content = vec![ToolContent {
-                                    r#type: "text".into(),
-                                    text: format!("Error: {}", e),
-                                }];
-                                JsonRpcResponse::success(
-                                    req.id,
-                                    serde_json::to_value(CallToolResult {
-                                        content,
-                                        is_error: Some(true),
-                                    })
-                                    .unwrap(),
-                                )
-                            }
-                        }
-                    }
-                    Err(_) => JsonRpcResponse::error(req.id, -32602, "Invalid params".into()),
-                }
-            } else {
-                JsonRpcResponse::error(req.id, -32602, "Missing params".into())
-            }
+    };
+
+    for (name, client) in mcp::client::connect_all(&config).await {
+        let imported = match client {
+            Ok(client) => mcp::client::register_tools(registry, &client).await,
+            Err(e) => Err(e),
+        };
+
+        match imported {
+            Ok(count) => eprintln!("🔌 Imported {} tools from MCP server '{}'", count, name),
+            Err(e) => eprintln!("⚠️ MCP server '{}' unavailable: {:#}", name, e),
         }
-        _ => JsonRpcResponse::error(req.id, -32601, "Method not found".into()),
+    }
+}
+
+/// Parses `--http [ADDR]` from the command line.
+#[cfg(not(target_arch = "wasm32"))]
+fn http_addr() -> anyhow::Result<Option<String>> {
+    let mut args = std::env::args().skip(1);
+
+    match args.next().as_deref() {
+        None => Ok(None),
+        Some("--http") => Ok(Some(
+            args.next().unwrap_or_else(|| DEFAULT_HTTP_ADDR.to_string()),
+        )),
+        Some(other) => match other.strip_prefix("--http=") {
+            Some(addr) => Ok(Some(addr.to_string())),
+            None => anyhow::bail!("Unknown argument: {}", other),
+        },
     }
 }
 
@@ -0,0 +1,695 @@
+//! Client for external MCP servers.
+//!
+//! Servers are listed in `mcp.toml` under the configuration directory,
+//! either as a command speaking stdio or as a streamable HTTP endpoint:
+//!
+//! ```toml
+//! [servers.tickets]
+//! command = "tickets-mcp"
+//! args = ["--stdio"]
+//!
+//! [servers.metrics]
+//! url = "http://127.0.0.1:9000/mcp"
+//! ```
+//!
+//! Their tools are imported as [`RemoteTool`]s named `<server>__<tool>`, so
+//! they cannot clash with built-in tools or with each other.
+use super::server::PROTOCOL_VERSIONS;
+use super::{JsonRpcResponse, Tool};
+use crate::tools::registry::{IntelligenceTool, ToolRegistry};
+
+use anyhow::{anyhow, bail, Context, Result};
+use async_trait::async_trait;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+use std::process::Stdio as Pipe;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::process::{Child, ChildStdin, Command};
+use tokio::sync::oneshot;
+
+/// How long a server gets to answer `initialize`.
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct Config {
+    #[serde(default)]
+    pub servers: BTreeMap<String, ServerConfig>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(untagged)]
+pub enum ServerConfig {
+    Stdio {
+        command: String,
+        #[serde(default)]
+        args: Vec<String>,
+        #[serde(default)]
+        env: BTreeMap<String, String>,
+    },
+    Http {
+        url: String,
+    },
+}
+
+impl Config {
+    pub fn path() -> PathBuf {
+        crate::brain::directory::config().join("mcp.toml")
+    }
+
+    /// Reads [`Config::path`]; a missing file means no servers.
+    pub fn load() -> Result<Self> {
+        match std::fs::read_to_string(Self::path()) {
+            Ok(config) => Self::parse(&config),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    pub fn parse(config: &str) -> Result<Self> {
+        toml::from_str(config).context("Invalid MCP server configuration")
+    }
+}
+
+/// Connects to every configured server. Servers that fail to start are
+/// returned with their error instead of aborting the others.
+pub async fn connect_all(config: &Config) -> Vec<(String, Result<Arc<Client>>)> {
+    let connections = config.servers.iter().map(|(name, server)| async move {
+        let client = Client::connect(name.clone(), server).await.map(Arc::new);
+        (name.clone(), client)
+    });
+
+    futures::future::join_all(connections).await
+}
+
+/// Registers the tools of `client` in `registry`, returning how many. A
+/// tool whose name is already taken is skipped, so the first one keeps it.
+pub async fn register_tools(registry: &mut ToolRegistry, client: &Arc<Client>) -> Result<usize> {
+    let mut count = 0;
+
+    for tool in client.list_tools().await? {
+        let tool = RemoteTool::new(client.clone(), tool);
+
+        if registry.get_tool(tool.name()).is_some() {
+            log::warn!(
+                "Skipping tool '{}' of MCP server '{}': another tool has the name '{}'",
+                tool.tool.name,
+                client.name(),
+                tool.name()
+            );
+            continue;
+        }
+
+        registry.register(tool);
+        count += 1;
+    }
+
+    Ok(count)
+}
+
+/// Longest name a remote tool is registered under.
+const MAX_NAME: usize = 64;
+
+/// Name a remote tool is registered under.
+pub fn namespaced(server: &str, tool: &str) -> String {
+    let sanitize = |name: &str| -> String {
+        name.chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect()
+    };
+
+    let name = format!("{}__{}", sanitize(server), sanitize(tool));
+
+    // Tool names are limited to 64 characters by most model APIs. Longer ones
+    // end in a hash of the full name, so they don't collide when cut short.
+    if name.len() <= MAX_NAME {
+        return name;
+    }
+
+    let hash = format!("{:x}", Sha256::digest(format!("{}\0{}", server, tool)));
+
+    format!("{}_{}", &name[..MAX_NAME - 9], &hash[..8])
+}
+
+pub struct Client {
+    name: String,
+    transport: Arc<Transport>,
+    next_id: AtomicU64,
+}
+
+enum Transport {
+    Stdio(StdioTransport),
+    Http(HttpTransport),
+}
+
+impl Client {
+    /// Starts or reaches the server and performs the `initialize` handshake.
+    pub async fn connect(name: impl Into<String>, config: &ServerConfig) -> Result<Self> {
+        let name = name.into();
+
+        let transport = match config {
+            ServerConfig::Stdio { command, args, env } => {
+                Transport::Stdio(StdioTransport::spawn(command, args, env)?)
+            }
+            ServerConfig::Http { url } => Transport::Http(HttpTransport::new(url.clone())),
+        };
+
+        let client = Self {
+            name,
+            transport: Arc::new(transport),
+            next_id: AtomicU64::new(1),
+        };
+
+        tokio::time::timeout(HANDSHAKE_TIMEOUT, client.initialize())
+            .await
+            .map_err(|_| anyhow!("MCP server '{}' did not answer initialize", client.name))??;
+
+        Ok(client)
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    async fn initialize(&self) -> Result<()> {
+        let result = self
+            .request(
+                "initialize",
+                json!({
+                    "protocolVersion": PROTOCOL_VERSIONS[0],
+                    "capabilities": {},
+                    "clientInfo": {
+                        "name": "peak-intelligence",
+                        "version": env!("CARGO_PKG_VERSION"),
+                    },
+                }),
+            )
+            .await?;
+
+        let version = result["protocolVersion"].as_str().unwrap_or_default();
+
+        if !PROTOCOL_VERSIONS.contains(&version) {
+            bail!(
+                "MCP server '{}' speaks unsupported protocol version '{}'",
+                self.name,
+                version
+            );
+        }
+
+        if let Transport::Http(http) = self.transport.as_ref() {
+            *lock(&http.version) = Some(version.to_string());
+        }
+
+        self.notify("notifications/initialized", json!({})).await
+    }
+
+    /// Sends a request and waits for its result. Dropping the future tells
+    /// the server to cancel the request.
+    pub async fn request(&self, method: &str, params: Value) -> Result<Value> {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let message = json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params });
+
+        let mut guard = CancelOnDrop {
+            transport: self.transport.clone(),
+            id: Some(id),
+        };
+
+        let response = match self.transport.as_ref() {
+            Transport::Stdio(stdio) => stdio.request(id, &message).await,
+            Transport::Http(http) => http.post(&message).await.and_then(|response| {
+                response.ok_or_else(|| anyhow!("MCP server sent no response to '{}'", method))
+            }),
+        };
+
+        // Answered or failed, nothing is left to cancel
+        guard.id = None;
+        let response = response?;
+
+        match (response.result, response.error) {
+            (_, Some(error)) => Err(anyhow!(
+                "MCP server '{}' failed '{}': {} ({})",
+                self.name,
+                method,
+                error.message,
+                error.code
+            )),
+            (Some(result), None) => Ok(result),
+            (None, None) => Ok(Value::Null),
+        }
+    }
+
+    pub async fn notify(&self, method: &str, params: Value) -> Result<()> {
+        self.transport.notify(method, params).await
+    }
+
+    /// Every tool the server offers, following pagination.
+    pub async fn list_tools(&self) -> Result<Vec<Tool>> {
+        let mut tools = Vec::new();
+        let mut cursor: Option<String> = None;
+
+        loop {
+            let params = match &cursor {
+                Some(cursor) => json!({ "cursor": cursor }),
+                None => json!({}),
+            };
+
+            let result = self.request("tools/list", params).await?;
+
+            for tool in result["tools"].as_array().into_iter().flatten() {
+                tools.push(Tool {
+                    name: tool["name"]
+                        .as_str()
+                        .ok_or_else(|| anyhow!("MCP tool without a name"))?
+                        .to_string(),
+                    description: tool["description"].as_str().unwrap_or_default().to_string(),
+                    input_schema: tool
+                        .get("inputSchema")
+                        .cloned()
+                        .unwrap_or_else(|| json!({ "type": "object" })),
+                });
+            }
+
+            match result["nextCursor"].as_str() {
+                Some(next) => cursor = Some(next.to_string()),
+                None => return Ok(tools),
+            }
+        }
+    }
+
+    /// Calls `name` and returns the raw `CallToolResult`.
+    pub async fn call_tool(&self, name: &str, arguments: Value) -> Result<Value> {
+        self.request(
+            "tools/call",
+            json!({ "name": name, "arguments": arguments }),
+        )
+        .await
+    }
+}
+
+impl Transport {
+    async fn notify(&self, method: &str, params: Value) -> Result<()> {
+        let message = json!({ "jsonrpc": "2.0", "method": method, "params": params });
+
+        match self {
+            Transport::Stdio(stdio) => stdio.send(&message).await,
+            Transport::Http(http) => http.post(&message).await.map(|_| ()),
+        }
+    }
+}
+
+/// Sends `notifications/cancelled` for a request abandoned before its
+/// response arrived.
+struct CancelOnDrop {
+    transport: Arc<Transport>,
+    id: Option<u64>,
+}
+
+impl Drop for CancelOnDrop {
+    fn drop(&mut self) {
+        let Some(id) = self.id else {
+            return;
+        };
+
+        if let Transport::Stdio(stdio) = self.transport.as_ref() {
+            lock(&stdio.pending).remove(&id);
+        }
+
+        let transport = self.transport.clone();
+
+        if let Ok(runtime) = tokio::runtime::Handle::try_current() {
+            runtime.spawn(async move {
+                let _ = transport
+                    .notify("notifications/cancelled", json!({ "requestId": id }))
+                    .await;
+            });
+        }
+    }
+}
+
+type Pending = Arc<Mutex<HashMap<u64, oneshot::Sender<JsonRpcResponse>>>>;
+
+struct StdioTransport {
+    stdin: Arc<tokio::sync::Mutex<ChildStdin>>,
+    pending: Pending,
+    _child: Child,
+}
+
+impl StdioTransport {
+    fn spawn(command: &str, args: &[String], env: &BTreeMap<String, String>) -> Result<Self> {
+        let mut child = Command::new(command)
+            .args(args)
+            .envs(env)
+            .stdin(Pipe::piped())
+            .stdout(Pipe::piped())
+            .stderr(Pipe::inherit())
+            .kill_on_drop(true)
+            .spawn()
+            .with_context(|| format!("Failed to start MCP server '{}'", command))?;
+
+        let stdin = Arc::new(tokio::sync::Mutex::new(
+            child.stdin.take().context("MCP server stdin unavailable")?,
+        ));
+        let stdout = child
+            .stdout
+            .take()
+            .context("MCP server stdout unavailable")?;
+        let pending = Pending::default();
+
+        tokio::spawn(read_messages(
+            BufReader::new(stdout),
+            stdin.clone(),
+            pending.clone(),
+        ));
+
+        Ok(Self {
+            stdin,
+            pending,
+            _child: child,
+        })
+    }
+
+    async fn request(&self, id: u64, message: &Value) -> Result<JsonRpcResponse> {
+        let (sender, receiver) = oneshot::channel();
+        lock(&self.pending).insert(id, sender);
+
+        self.send(message).await?;
+
+        receiver
+            .await
+            .map_err(|_| anyhow!("MCP server exited before responding"))
+    }
+
+    async fn send(&self, message: &Value) -> Result<()> {
+        write_line(&self.stdin, message).await
+    }
+}
+
+async fn write_line(stdin: &tokio::sync::Mutex<ChildStdin>, message: &Value) -> Result<()> {
+    let mut stdin = stdin.lock().await;
+    stdin.write_all(format!("{}\n", message).as_bytes()).await?;
+    stdin.flush().await?;
+    Ok(())
+}
+
+/// Routes responses to their waiting requests and answers the requests a
+/// server may send on its own.
+async fn read_messages(
+    stdout: BufReader<tokio::process::ChildStdout>,
+    stdin: Arc<tokio::sync::Mutex<ChildStdin>>,
+    pending: Pending,
+) {
+    let mut lines = stdout.lines();
+
+    while let Ok(Some(line)) = lines.next_line().await {
+        let Ok(message) = serde_json::from_str::<Value>(&line) else {
+            log::warn!("Ignoring malformed MCP message: {}", line);
+            continue;
+        };
+
+        match (message.get("id"), message.get("method")) {
+            // Response
+            (Some(id), None) => {
+                let sender = id.as_u64().and_then(|id| lock(&pending).remove(&id));
+
+                if let (Some(sender), Ok(response)) =
+                    (sender, serde_json::from_value::<JsonRpcResponse>(message))
+                {
+                    let _ = sender.send(response);
+                }
+            }
+            // Request from the server
+            (Some(id), Some(method)) => {
+                let response = if method == "ping" {
+                    JsonRpcResponse::success(Some(id.clone()), json!({}))
+                } else {
+                    JsonRpcResponse::error(
+                        Some(id.clone()),
+                        super::METHOD_NOT_FOUND,
+                        "Method not found".into(),
+                    )
+                };
+
+                if let Ok(response) = serde_json::to_value(response) {
+                    let _ = write_line(&stdin, &response).await;
+                }
+            }
+            // Notification
+            _ => log::debug!("MCP notification: {}", message),
+        }
+    }
+
+    // Fails every request still waiting
+    lock(&pending).clear();
+}
+
+struct HttpTransport {
+    client: reqwest::Client,
+    url: String,
+    session: Mutex<Option<String>>,
+    version: Mutex<Option<String>>,
+}
+
+impl HttpTransport {
+    fn new(url: String) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            url,
+            session: Mutex::default(),
+            version: Mutex::default(),
+        }
+    }
+
+    /// Posts one message. Requests are answered with either a JSON body or
+    /// an event stream carrying the response; notifications get none.
+    async fn post(&self, message: &Value) -> Result<Option<JsonRpcResponse>> {
+        let mut request = self
+            .client
+            .post(&self.url)
+            .header("Accept", "application/json, text/event-stream")
+            .json(message);
+
+        if let Some(session) = lock(&self.session).clone() {
+            request = request.header("Mcp-Session-Id", session);
+        }
+
+        if let Some(version) = lock(&self.version).clone() {
+            request = request.header("MCP-Protocol-Version", version);
+        }
+
+        let response = request.send().await?.error_for_status()?;
+
+        if let Some(session) = response
+            .headers()
+            .get("mcp-session-id")
+            .and_then(|session| session.to_str().ok())
+        {
+            *lock(&self.session) = Some(session.to_string());
+        }
+
+        if response.status() == reqwest::StatusCode::ACCEPTED {
+            return Ok(None);
+        }
+
+        let is_stream = response
+            .headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|content_type| content_type.to_str().ok())
+            .is_some_and(|content_type| content_type.starts_with("text/event-stream"));
+
+        if !is_stream {
+            return Ok(Some(response.json().await?));
+        }
+
+        let body = response.text().await?;
+
+        Ok(body
+            .lines()
+            .filter_map(|line| line.strip_prefix("data:"))
+            .filter_map(|data| serde_json::from_str::<JsonRpcResponse>(data.trim()).ok())
+            .find(|response| response.id == message.get("id").cloned()))
+    }
+}
+
+/// An MCP server tool, callable through the [`ToolRegistry`].
+pub struct RemoteTool {
+    client: Arc<Client>,
+    name: String,
+    tool: Tool,
+}
+
+impl RemoteTool {
+    pub fn new(client: Arc<Client>, tool: Tool) -> Self {
+        Self {
+            name: namespaced(client.name(), &tool.name),
+            client,
+            tool,
+        }
+    }
+}
+
+#[async_trait]
+impl IntelligenceTool for RemoteTool {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn description(&self) -> &str {
+        &self.tool.description
+    }
+
+    fn input_schema(&self) -> Value {
+        self.tool.input_schema.clone()
+    }
+
+    async fn execute(&self, args: Value) -> Result<Value> {
+        let result = self.client.call_tool(&self.tool.name, args).await?;
+
+        let text = result["content"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|content| content["text"].as_str())
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        if result["isError"].as_bool() == Some(true) {
+            bail!("{}", text);
+        }
+
+        if let Some(structured) = result.get("structuredContent") {
+            return Ok(structured.clone());
+        }
+
+        Ok(serde_json::from_str(&text).unwrap_or(Value::String(text)))
+    }
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> std::sync::MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mcp::{http, Server};
+    use tokio::net::TcpListener;
+    use tokio::sync::broadcast;
+
+    struct Add;
+
+    #[async_trait]
+    impl IntelligenceTool for Add {
+        fn name(&self) -> &str {
+            "add"
+        }
+        fn description(&self) -> &str {
+            "Adds two numbers"
+        }
+        fn input_schema(&self) -> Value {
+            json!({ "type": "object", "properties": { "a": { "type": "number" }, "b": { "type": "number" } } })
+        }
+        async fn execute(&self, args: Value) -> Result<Value> {
+            match (args["a"].as_f64(), args["b"].as_f64()) {
+                (Some(a), Some(b)) => Ok(json!(a + b)),
+                _ => bail!("'a' and 'b' must be numbers"),
+            }
+        }
+    }
+
+    #[test]
+    fn test_config_accepts_stdio_and_http_servers() {
+        let config = Config::parse(
+            r#"
+            [servers.tickets]
+            command = "tickets-mcp"
+            args = ["--stdio"]
+
+            [servers.metrics]
+            url = "http://127.0.0.1:9000/mcp"
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            config.servers["tickets"],
+            ServerConfig::Stdio {
+                command: "tickets-mcp".into(),
+                args: vec!["--stdio".into()],
+                env: BTreeMap::new(),
+            }
+        );
+        assert_eq!(
+            config.servers["metrics"],
+            ServerConfig::Http {
+                url: "http://127.0.0.1:9000/mcp".into()
+            }
+        );
+    }
+
+    #[test]
+    fn test_namespacing() {
+        assert_eq!(namespaced("tickets", "create"), "tickets__create");
+        assert_eq!(namespaced("my server", "a.b"), "my_server__a_b");
+
+        let long = namespaced("s", &format!("{}a", "x".repeat(100)));
+        let other = namespaced("s", &format!("{}b", "x".repeat(100)));
+        assert_eq!(long.len(), 64);
+        assert!(long.starts_with("s__xxx"));
+        assert_ne!(long, other);
+    }
+
+    #[tokio::test]
+    async fn test_imports_tools_over_http() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!(
+            "http://{}{}",
+            listener.local_addr().unwrap(),
+            http::ENDPOINT
+        );
+
+        let mut tools = ToolRegistry::new();
+        tools.register(Add);
+        let (events, _) = broadcast::channel(1);
+        tokio::spawn(http::serve(
+            listener,
+            Arc::new(Server::new(tools)),
+            events,
+            None,
+        ));
+
+        let client = Arc::new(
+            Client::connect("math", &ServerConfig::Http { url })
+                .await
+                .unwrap(),
+        );
+
+        let mut registry = ToolRegistry::new();
+        assert_eq!(register_tools(&mut registry, &client).await.unwrap(), 1);
+        assert_eq!(registry.list_tools()[0]["name"], "math__add");
+        // Already imported, so skipped
+        assert_eq!(register_tools(&mut registry, &client).await.unwrap(), 0);
+
+        let sum = registry
+            .call("math__add", json!({ "a": 2, "b": 3 }))
+            .await
+            .unwrap();
+        assert_eq!(sum, json!(5.0));
+
+        let error = registry
+            .call("math__add", json!({ "a": "two" }))
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("must be numbers"));
+    }
+}
@@ -0,0 +1,653 @@
+//! Streamable HTTP transport for the MCP [`Server`].
+//!
+//! Clients POST JSON-RPC messages to [`ENDPOINT`] and may open a `GET`
+//! event stream on the same path to receive server notifications such as
+//! `system/telemetry`, `terminal/output` and `approval/requested`. Every
+//! `initialize` starts a session, identified by the `Mcp-Session-Id` header
+//! on later requests and ended with `DELETE`. Sessions idle for longer than
+//! [`SESSION_TTL`] expire, and past [`MAX_SESSIONS`] the least recently used
+//! one is dropped.
+//!
+//! Requests must carry `Authorization: Bearer <token>` when [`serve`] is
+//! given a token, which it requires to listen beyond the loopback interface.
+use super::server::PROTOCOL_VERSIONS;
+use super::{JsonRpcRequest, JsonRpcResponse, Server, PARSE_ERROR};
+
+use std::collections::HashMap;
+use std::io;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::{Duration, Instant};
+use tokio::io::{
+    AsyncBufRead, AsyncBufReadExt, AsyncReadExt, AsyncWrite, AsyncWriteExt, BufReader,
+};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::broadcast;
+
+pub const ENDPOINT: &str = "/mcp";
+
+const SESSION_HEADER: &str = "mcp-session-id";
+const VERSION_HEADER: &str = "mcp-protocol-version";
+const MAX_BODY: usize = 4 * 1024 * 1024;
+/// Comment sent on idle event streams so dropped clients are noticed.
+const KEEP_ALIVE: Duration = Duration::from_secs(15);
+/// How long a session lasts without requests. Open event streams keep their
+/// session alive.
+pub const SESSION_TTL: Duration = Duration::from_secs(30 * 60);
+/// Most sessions open at once.
+pub const MAX_SESSIONS: usize = 64;
+
+struct Session {
+    server: Arc<Server>,
+    last_used: Instant,
+}
+
+#[derive(Default)]
+struct Sessions(Mutex<HashMap<String, Session>>);
+
+impl Sessions {
+    /// The session `id`, marked as just used.
+    fn get(&self, id: &str) -> Option<Arc<Server>> {
+        let mut sessions = self.lock();
+        let session = sessions.get_mut(id)?;
+        session.last_used = Instant::now();
+        Some(session.server.clone())
+    }
+
+    /// Adds a session, making room by dropping expired sessions and then the
+    /// least recently used.
+    fn insert(&self, id: String, server: Arc<Server>) {
+        let mut sessions = self.lock();
+        sessions.retain(|_, session| session.last_used.elapsed() < SESSION_TTL);
+
+        while sessions.len() >= MAX_SESSIONS {
+            let Some(oldest) = sessions
+                .iter()
+                .min_by_key(|(_, session)| session.last_used)
+                .map(|(id, _)| id.clone())
+            else {
+                break;
+            };
+            sessions.remove(&oldest);
+        }
+
+        sessions.insert(
+            id,
+            Session {
+                server,
+                last_used: Instant::now(),
+            },
+        );
+    }
+
+    fn remove(&self, id: &str) -> bool {
+        self.lock().remove(id).is_some()
+    }
+
+    /// Drops sessions unused for longer than [`SESSION_TTL`].
+    fn expire(&self) {
+        self.lock()
+            .retain(|_, session| session.last_used.elapsed() < SESSION_TTL);
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<String, Session>> {
+        self.0.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+struct Request {
+    method: String,
+    path: String,
+    headers: HashMap<String, String>,
+    body: Vec<u8>,
+}
+
+impl Request {
+    fn header(&self, name: &str) -> Option<&str> {
+        self.headers.get(name).map(String::as_str)
+    }
+}
+
+/// Accepts connections until the listener fails. Sessions share the tools of
+/// `server`; `events` carries the notifications pushed to event streams.
+/// With a `token`, only requests bearing it are served; without one the
+/// listener must be on a loopback address.
+pub async fn serve(
+    listener: TcpListener,
+    server: Arc<Server>,
+    events: broadcast::Sender<String>,
+    token: Option<String>,
+) -> io::Result<()> {
+    if token.is_none() && !listener.local_addr()?.ip().is_loopback() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "a bearer token is required to listen beyond the loopback interface",
+        ));
+    }
+
+    let sessions = Arc::new(Sessions::default());
+    let token: Option<Arc<str>> = token.map(Into::into);
+
+    // Expire idle sessions even when no new ones are started
+    let expiring = Arc::downgrade(&sessions);
+    tokio::spawn(async move {
+        loop {
+            tokio::time::sleep(KEEP_ALIVE).await;
+            let Some(sessions) = expiring.upgrade() else {
+                return;
+            };
+            sessions.expire();
+        }
+    });
+
+    loop {
+        let (socket, _) = listener.accept().await?;
+        let server = server.clone();
+        let sessions = sessions.clone();
+        let events = events.clone();
+        let token = token.clone();
+
+        tokio::spawn(async move {
+            if let Err(e) =
+                handle_connection(socket, &server, &sessions, &events, token.as_deref()).await
+            {
+                log::warn!("MCP HTTP connection failed: {}", e);
+            }
+        });
+    }
+}
+
+async fn handle_connection(
+    socket: TcpStream,
+    server: &Server,
+    sessions: &Sessions,
+    events: &broadcast::Sender<String>,
+    token: Option<&str>,
+) -> io::Result<()> {
+    let (read, mut write) = socket.into_split();
+    let mut reader = BufReader::new(read);
+
+    let Some(request) = read_request(&mut reader).await? else {
+        return Ok(());
+    };
+
+    if !is_local_origin(request.header("origin")) {
+        return respond(&mut write, "403 Forbidden", &[], "").await;
+    }
+
+    if token.is_some_and(|token| !is_authorized(request.header("authorization"), token)) {
+        return respond(
+            &mut write,
+            "401 Unauthorized",
+            &[("WWW-Authenticate", "Bearer")],
+            "",
+        )
+        .await;
+    }
+
+    if request.path.split('?').next() != Some(ENDPOINT) {
+        return respond(&mut write, "404 Not Found", &[], "").await;
+    }
+
+    if request
+        .header(VERSION_HEADER)
+        .is_some_and(|version| !PROTOCOL_VERSIONS.contains(&version))
+    {
+        return respond(
+            &mut write,
+            "400 Bad Request",
+            &[],
+            "Unsupported MCP-Protocol-Version",
+        )
+        .await;
+    }
+
+    match request.method.as_str() {
+        "POST" => post(&mut write, request, server, sessions).await,
+        "GET" => stream(&mut write, request, sessions, events).await,
+        "DELETE" => match request.header(SESSION_HEADER) {
+            Some(id) if sessions.remove(id) => respond(&mut write, "204 No Content", &[], "").await,
+            _ => respond(&mut write, "404 Not Found", &[], "").await,
+        },
+        _ => {
+            respond(
+                &mut write,
+                "405 Method Not Allowed",
+                &[("Allow", "GET, POST, DELETE")],
+                "",
+            )
+            .await
+        }
+    }
+}
+
+async fn post(
+    write: &mut (impl AsyncWrite + Unpin),
+    request: Request,
+    server: &Server,
+    sessions: &Sessions,
+) -> io::Result<()> {
+    let Ok(message) = serde_json::from_slice::<JsonRpcRequest>(&request.body) else {
+        let error = JsonRpcResponse::error(None, PARSE_ERROR, "Parse error".into());
+        return respond_json(write, "400 Bad Request", &[], &error).await;
+    };
+
+    let (id, session) = if message.method == "initialize" {
+        let id = uuid::Uuid::new_v4().to_string();
+        let session = Arc::new(server.new_session());
+        sessions.insert(id.clone(), session.clone());
+        (id, session)
+    } else {
+        let Some(id) = request.header(SESSION_HEADER) else {
+            return respond(write, "400 Bad Request", &[], "Missing Mcp-Session-Id").await;
+        };
+        let Some(session) = sessions.get(id) else {
+            return respond(write, "404 Not Found", &[], "Unknown session").await;
+        };
+        (id.to_string(), session)
+    };
+
+    let is_initialize = message.method == "initialize";
+
+    match session.handle(message).await {
+        Some(response) => {
+            if is_initialize && response.error.is_some() {
+                sessions.remove(&id);
+            }
+            respond_json(write, "200 OK", &[("Mcp-Session-Id", &id)], &response).await
+        }
+        // Notifications and cancelled requests
+        None => respond(write, "202 Accepted", &[], "").await,
+    }
+}
+
+async fn stream(
+    write: &mut (impl AsyncWrite + Unpin),
+    request: Request,
+    sessions: &Sessions,
+    events: &broadcast::Sender<String>,
+) -> io::Result<()> {
+    if !request
+        .header("accept")
+        .is_some_and(|accept| accept.contains("text/event-stream"))
+    {
+        return respond(write, "406 Not Acceptable", &[], "").await;
+    }
+
+    let Some(id) = request.header(SESSION_HEADER) else {
+        return respond(write, "400 Bad Request", &[], "Missing Mcp-Session-Id").await;
+    };
+    let Some(session) = sessions.get(id) else {
+        return respond(write, "404 Not Found", &[], "Unknown session").await;
+    };
+
+    let mut events = events.subscribe();
+
+    write
+        .write_all(
+            b"HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nCache-Control: no-cache\r\nConnection: keep-alive\r\n\r\n",
+        )
+        .await?;
+    write.flush().await?;
+
+    loop {
+        let frame = tokio::select! {
+            event = events.recv() => match event {
+                Ok(message) if session.is_initialized() => format!("event: message\ndata: {}\n\n", message),
+                Ok(_) | Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(broadcast::error::RecvError::Closed) => return Ok(()),
+            },
+            _ = tokio::time::sleep(KEEP_ALIVE) => ": keep-alive\n\n".to_string(),
+        };
+
+        // Stop once the session was deleted
+        if sessions.get(id).is_none() {
+            return Ok(());
+        }
+
+        write.write_all(frame.as_bytes()).await?;
+        write.flush().await?;
+    }
+}
+
+async fn read_request(reader: &mut (impl AsyncBufRead + Unpin)) -> io::Result<Option<Request>> {
+    let mut line = String::new();
+
+    if reader.read_line(&mut line).await? == 0 {
+        return Ok(None);
+    }
+
+    let mut parts = line.split_whitespace();
+    let method = parts.next().unwrap_or_default().to_string();
+    let path = parts.next().unwrap_or_default().to_string();
+
+    let mut headers = HashMap::new();
+
+    loop {
+        line.clear();
+
+        if reader.read_line(&mut line).await? == 0 {
+            break;
+        }
+
+        let header = line.trim_end();
+
+        if header.is_empty() {
+            break;
+        }
+
+        if let Some((name, value)) = header.split_once(':') {
+            headers.insert(name.trim().to_ascii_lowercase(), value.trim().to_string());
+        }
+    }
+
+    let length: usize = headers
+        .get("content-length")
+        .and_then(|length| length.parse().ok())
+        .unwrap_or(0);
+
+    if length > MAX_BODY {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "request body too large",
+        ));
+    }
+
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body).await?;
+
+    Ok(Some(Request {
+        method,
+        path,
+        headers,
+        body,
+    }))
+}
+
+/// Browsers may only reach the server from local pages, which guards
+/// against DNS rebinding.
+fn is_local_origin(origin: Option<&str>) -> bool {
+    let Some(origin) = origin else {
+        return true;
+    };
+
+    url::Url::parse(origin)
+        .ok()
+        .and_then(|url| url.host_str().map(str::to_owned))
+        .is_some_and(|host| matches!(host.as_str(), "localhost" | "127.0.0.1" | "[::1]"))
+}
+
+/// Whether an `Authorization` header carries the bearer `token`.
+fn is_authorized(authorization: Option<&str>, token: &str) -> bool {
+    authorization
+        .and_then(|authorization| authorization.strip_prefix("Bearer "))
+        .is_some_and(|bearer| {
+            // Compared in full, so the time taken doesn't give the token away
+            bearer.len() == token.len()
+                && bearer
+                    .bytes()
+                    .zip(token.bytes())
+                    .fold(0, |diff, (a, b)| diff | (a ^ b))
+                    == 0
+        })
+}
+
+async fn respond(
+    write: &mut (impl AsyncWrite + Unpin),
+    status: &str,
+    headers: &[(&str, &str)],
+    body: &str,
+) -> io::Result<()> {
+    respond_with(write, status, "text/plain", headers, body).await
+}
+
+async fn respond_json(
+    write: &mut (impl AsyncWrite + Unpin),
+    status: &str,
+    headers: &[(&str, &str)],
+    response: &JsonRpcResponse,
+) -> io::Result<()> {
+    let body = serde_json::to_string(response).map_err(io::Error::other)?;
+    respond_with(write, status, "application/json", headers, &body).await
+}
+
+async fn respond_with(
+    write: &mut (impl AsyncWrite + Unpin),
+    status: &str,
+    content_type: &str,
+    headers: &[(&str, &str)],
+    body: &str,
+) -> io::Result<()> {
+    let mut response = format!(
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n",
+        status,
+        content_type,
+        body.len()
+    );
+
+    for (name, value) in headers {
+        response.push_str(&format!("{}: {}\r\n", name, value));
+    }
+
+    response.push_str("\r\n");
+    response.push_str(body);
+
+    write.write_all(response.as_bytes()).await?;
+    write.flush().await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tools::registry::ToolRegistry;
+    use serde_json::{json, Value};
+
+    async fn start() -> (String, broadcast::Sender<String>) {
+        start_with(None).await
+    }
+
+    async fn start_with(token: Option<&str>) -> (String, broadcast::Sender<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}{}", listener.local_addr().unwrap(), ENDPOINT);
+        let (events, _) = broadcast::channel(16);
+
+        tokio::spawn(serve(
+            listener,
+            Arc::new(Server::new(ToolRegistry::new())),
+            events.clone(),
+            token.map(str::to_string),
+        ));
+
+        (url, events)
+    }
+
+    async fn post(
+        client: &reqwest::Client,
+        url: &str,
+        session: Option<&str>,
+        message: Value,
+    ) -> reqwest::Response {
+        let mut request = client.post(url).json(&message);
+
+        if let Some(session) = session {
+            request = request.header("Mcp-Session-Id", session);
+        }
+
+        request.send().await.unwrap()
+    }
+
+    async fn handshake(client: &reqwest::Client, url: &str) -> String {
+        let response = post(
+            client,
+            url,
+            None,
+            json!({
+                "jsonrpc": "2.0", "id": 1, "method": "initialize",
+                "params": { "protocolVersion": PROTOCOL_VERSIONS[0], "capabilities": {} }
+            }),
+        )
+        .await;
+        assert_eq!(response.status(), 200);
+
+        let session = response.headers()["mcp-session-id"]
+            .to_str()
+            .unwrap()
+            .to_string();
+
+        let response = post(
+            client,
+            url,
+            Some(&session),
+            json!({ "jsonrpc": "2.0", "method": "notifications/initialized" }),
+        )
+        .await;
+        assert_eq!(response.status(), 202);
+
+        session
+    }
+
+    #[tokio::test]
+    async fn test_requests_require_a_session() {
+        let (url, _events) = start().await;
+        let client = reqwest::Client::new();
+
+        let ping = json!({ "jsonrpc": "2.0", "id": 2, "method": "ping" });
+
+        let response = post(&client, &url, None, ping.clone()).await;
+        assert_eq!(response.status(), 400);
+
+        let response = post(&client, &url, Some("unknown"), ping.clone()).await;
+        assert_eq!(response.status(), 404);
+
+        let session = handshake(&client, &url).await;
+
+        let response = post(&client, &url, Some(&session), ping.clone()).await;
+        assert_eq!(response.status(), 200);
+        let body: Value = response.json().await.unwrap();
+        assert_eq!(body["id"], 2);
+        assert_eq!(body["result"], json!({}));
+
+        let response = client
+            .delete(&url)
+            .header("Mcp-Session-Id", &session)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 204);
+
+        let response = post(&client, &url, Some(&session), ping).await;
+        assert_eq!(response.status(), 404);
+    }
+
+    #[tokio::test]
+    async fn test_rejects_foreign_origins_and_bad_bodies() {
+        let (url, _events) = start().await;
+        let client = reqwest::Client::new();
+
+        let response = client
+            .post(&url)
+            .header("Origin", "http://evil.example")
+            .body("{}")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 403);
+
+        let response = client.post(&url).body("not json").send().await.unwrap();
+        assert_eq!(response.status(), 400);
+        let body: Value = response.json().await.unwrap();
+        assert_eq!(body["error"]["code"], PARSE_ERROR);
+    }
+
+    #[tokio::test]
+    async fn test_streams_notifications_to_initialized_sessions() {
+        let (url, events) = start().await;
+        let client = reqwest::Client::new();
+        let session = handshake(&client, &url).await;
+
+        let response = client
+            .get(&url)
+            .header("Accept", "text/event-stream")
+            .header("Mcp-Session-Id", &session)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 200);
+        assert_eq!(response.headers()["content-type"], "text/event-stream");
+
+        let notification = json!({ "jsonrpc": "2.0", "method": "terminal/output", "params": "ok" });
+        events.send(notification.to_string()).unwrap();
+
+        let mut stream = response.bytes_stream();
+        let mut received = String::new();
+
+        while !received.ends_with("\n\n") {
+            use futures::StreamExt;
+            let chunk = stream.next().await.unwrap().unwrap();
+            received.push_str(&String::from_utf8_lossy(&chunk));
+        }
+
+        assert_eq!(
+            received,
+            format!("event: message\ndata: {}\n\n", notification)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_requires_the_bearer_token() {
+        let (url, _events) = start_with(Some("secret")).await;
+        let client = reqwest::Client::new();
+        let ping = json!({ "jsonrpc": "2.0", "id": 1, "method": "ping" });
+
+        for authorization in [None, Some("Bearer wrong"), Some("secret")] {
+            let mut request = client.post(&url).json(&ping);
+            if let Some(authorization) = authorization {
+                request = request.header("Authorization", authorization);
+            }
+            let response = request.send().await.unwrap();
+            assert_eq!(response.status(), 401);
+            assert_eq!(response.headers()["www-authenticate"], "Bearer");
+        }
+
+        let response = client
+            .post(&url)
+            .bearer_auth("secret")
+            .json(&ping)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 400);
+    }
+
+    #[tokio::test]
+    async fn test_needs_a_token_beyond_loopback() {
+        let listener = TcpListener::bind("0.0.0.0:0").await.unwrap();
+        let (events, _) = broadcast::channel(16);
+        let server = Arc::new(Server::new(ToolRegistry::new()));
+
+        let error = serve(listener, server, events, None).await.unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn test_sessions_expire_and_are_capped() {
+        let sessions = Sessions::default();
+        let server = Arc::new(Server::new(ToolRegistry::new()));
+
+        sessions.insert("idle".into(), server.clone());
+        sessions.lock().get_mut("idle").unwrap().last_used -= SESSION_TTL;
+        sessions.expire();
+        assert!(sessions.get("idle").is_none());
+
+        for i in 0..MAX_SESSIONS {
+            sessions.insert(i.to_string(), server.clone());
+        }
+        // Using the first session makes the second the oldest
+        assert!(sessions.get("0").is_some());
+        sessions.insert("new".into(), server);
+
+        assert_eq!(sessions.lock().len(), MAX_SESSIONS);
+        assert!(sessions.get("0").is_some());
+        assert!(sessions.get("1").is_none());
+        assert!(sessions.get("new").is_some());
+    }
+}
@@ -1,6 +1,12 @@
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
+pub mod client;
+pub mod http;
+pub mod server;
+
+pub use server::Server;
+
 // JSON-RPC 2.0 Types
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -35,6 +41,7 @@ pub struct JsonRpcError {
 pub struct Tool {
     pub name: String,
     pub description: String,
+    #[serde(rename = "inputSchema")]
     pub input_schema: Value, // JSON Schema
 }
 
@@ -52,6 +59,13 @@ pub struct ListToolsResult {
 #[derive(Debug, Serialize, Deserialize)]
 pub struct CallToolResult {
     pub content: Vec<ToolContent>,
+    #[serde(
+        rename = "structuredContent",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub structured_content: Option<Value>,
+    #[serde(rename = "isError")]
     pub is_error: Option<bool>,
 }
 
@@ -61,6 +75,56 @@ pub struct ToolContent {
     pub text: String,
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+pub struct InitializeParams {
+    #[serde(rename = "protocolVersion")]
+    pub protocol_version: String,
+    #[serde(default)]
+    pub capabilities: Value,
+    #[serde(rename = "clientInfo")]
+    pub client_info: Option<Value>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Resource {
+    pub uri: String,
+    pub name: String,
+    pub description: String,
+    #[serde(rename = "mimeType")]
+    pub mime_type: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ResourceContents {
+    pub uri: String,
+    #[serde(rename = "mimeType")]
+    pub mime_type: String,
+    pub text: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Prompt {
+    pub name: String,
+    pub description: String,
+    #[serde(default)]
+    pub arguments: Vec<Value>,
+}
+
+// JSON-RPC and MCP error codes
+
+pub const PARSE_ERROR: i32 = -32700;
+pub const INVALID_REQUEST: i32 = -32600;
+pub const METHOD_NOT_FOUND: i32 = -32601;
+pub const INVALID_PARAMS: i32 = -32602;
+pub const RESOURCE_NOT_FOUND: i32 = -32002;
+
+impl JsonRpcRequest {
+    /// Requests without an id are notifications and get no response.
+    pub fn is_notification(&self) -> bool {
+        self.id.is_none()
+    }
+}
+
 impl JsonRpcResponse {
     pub fn success(id: Option<Value>, result: Value) -> Self {
         Self {
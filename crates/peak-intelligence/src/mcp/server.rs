@@ -0,0 +1,851 @@
+//! MCP method dispatch shared by every transport.
+//!
+//! A [`Server`] tracks the session lifecycle (`initialize` followed by
+//! `notifications/initialized`), negotiates the protocol version, and
+//! answers tools, resources and prompts requests. Tool calls run as their
+//! own tasks so `notifications/cancelled` can abort them.
+//!
+//! Calls to protected tools wait for a decision in the registry's approval
+//! queue. Clients see the queue through `approvals/list` and are told about
+//! changes with the notifications built by [`approval_notification`]. Only
+//! the app holding the server's approval token settles requests, with
+//! `approvals/decide`, so agents can't approve their own calls. Without a
+//! token protected calls are denied at once, and calls nobody decides on
+//! within [`APPROVAL_TIMEOUT`] are denied too.
+use super::{
+    CallToolParams, CallToolResult, InitializeParams, JsonRpcRequest, JsonRpcResponse,
+    ListToolsResult, Prompt, Resource, ResourceContents, Tool, ToolContent, INVALID_PARAMS,
+    INVALID_REQUEST, METHOD_NOT_FOUND, RESOURCE_NOT_FOUND,
+};
+use crate::tools::approval::{ApprovalError, Decision, Event};
+use crate::tools::edit::EditError;
+use crate::tools::fs_policy::Violation;
+use crate::tools::registry::ToolRegistry;
+
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::Duration;
+use tokio::sync::RwLock;
+use tokio::task::AbortHandle;
+
+/// Protocol revisions this server speaks, newest first.
+pub const PROTOCOL_VERSIONS: &[&str] = &["2025-06-18", "2025-03-26", "2024-11-05"];
+
+/// Semantic tree exported by the framework on every update.
+pub const VIEW_EXPORT: &str = ".peak/current_view.json";
+
+/// How long a protected call waits for a decision before it is denied.
+pub const APPROVAL_TIMEOUT: Duration = Duration::from_secs(5 * 60);
+
+const VIEW_URI: &str = "peak://view";
+const TELEMETRY_URI: &str = "peak://telemetry";
+
+#[derive(Debug, Default)]
+struct Session {
+    /// Version agreed on during `initialize`.
+    protocol_version: Option<String>,
+    /// Set once the client confirmed with `notifications/initialized`.
+    initialized: bool,
+    /// Name from the client's `clientInfo`, shown on its approval requests.
+    client: Option<String>,
+}
+
+pub struct Server {
+    registry: Arc<RwLock<ToolRegistry>>,
+    /// What `approvals/decide` has to be passed; with none, nobody decides.
+    approval_token: Option<Arc<str>>,
+    approval_timeout: Duration,
+    session: Mutex<Session>,
+    in_flight: Mutex<HashMap<String, AbortHandle>>,
+}
+
+impl Server {
+    pub fn new(registry: ToolRegistry) -> Self {
+        Self {
+            registry: Arc::new(RwLock::new(registry)),
+            approval_token: None,
+            approval_timeout: APPROVAL_TIMEOUT,
+            session: Mutex::default(),
+            in_flight: Mutex::default(),
+        }
+    }
+
+    /// Takes decisions on approval requests only along with `token`, which
+    /// the app's UI keeps from the agents it approves calls for.
+    pub fn with_approval_token(mut self, token: impl Into<String>) -> Self {
+        self.approval_token = Some(token.into().into());
+        self
+    }
+
+    /// How long protected calls wait for a decision, instead of
+    /// [`APPROVAL_TIMEOUT`].
+    pub fn with_approval_timeout(mut self, timeout: Duration) -> Self {
+        self.approval_timeout = timeout;
+        self
+    }
+
+    /// A server for another client, sharing this one's tools but with its
+    /// own lifecycle.
+    pub fn new_session(&self) -> Self {
+        Self {
+            registry: self.registry.clone(),
+            approval_token: self.approval_token.clone(),
+            approval_timeout: self.approval_timeout,
+            session: Mutex::default(),
+            in_flight: Mutex::default(),
+        }
+    }
+
+    /// Whether the client finished the handshake. Servers should not send
+    /// notifications before this.
+    pub fn is_initialized(&self) -> bool {
+        self.session().initialized
+    }
+
+    /// Handles one message. Notifications and cancelled requests produce no
+    /// response.
+    pub async fn handle(&self, req: JsonRpcRequest) -> Option<JsonRpcResponse> {
+        if req.is_notification() {
+            self.notify(&req.method, req.params);
+            return None;
+        }
+
+        let id = req.id;
+
+        match req.method.as_str() {
+            "initialize" => Some(self.initialize(id, req.params)),
+            "ping" => Some(JsonRpcResponse::success(id, json!({}))),
+            _ if self.session().protocol_version.is_none() => Some(JsonRpcResponse::error(
+                id,
+                INVALID_REQUEST,
+                "Server not initialized".into(),
+            )),
+            "tools/list" => Some(self.list_tools(id).await),
+            "tools/call" => self.call_tool(id, req.params).await,
+            "resources/list" => Some(JsonRpcResponse::success(
+                id,
+                json!({ "resources": resources() }),
+            )),
+            "resources/templates/list" => Some(JsonRpcResponse::success(
+                id,
+                json!({ "resourceTemplates": [] }),
+            )),
+            "resources/read" => Some(read_resource(id, req.params)),
+            "prompts/list" => Some(JsonRpcResponse::success(
+                id,
+                json!({ "prompts": prompts() }),
+            )),
+            "prompts/get" => Some(get_prompt(id, req.params)),
+            "approvals/list" => Some(self.list_approvals(id).await),
+            "approvals/decide" => Some(self.decide_approval(id, req.params).await),
+            _ => Some(JsonRpcResponse::error(
+                id,
+                METHOD_NOT_FOUND,
+                "Method not found".into(),
+            )),
+        }
+    }
+
+    fn notify(&self, method: &str, params: Option<Value>) {
+        match method {
+            "notifications/initialized" => self.session().initialized = true,
+            "notifications/cancelled" => {
+                let Some(request_id) = params.as_ref().and_then(|p| p.get("requestId")) else {
+                    return;
+                };
+
+                if let Some(task) = self.in_flight().remove(&request_id.to_string()) {
+                    log::info!("Cancelled MCP request {}", request_id);
+                    task.abort();
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn initialize(&self, id: Option<Value>, params: Option<Value>) -> JsonRpcResponse {
+        let Some(params) = params.and_then(|p| serde_json::from_value::<InitializeParams>(p).ok())
+        else {
+            return JsonRpcResponse::error(id, INVALID_PARAMS, "Invalid params".into());
+        };
+
+        // Echo the client's version when supported, otherwise offer ours
+        let version = PROTOCOL_VERSIONS
+            .iter()
+            .find(|version| **version == params.protocol_version)
+            .unwrap_or(&PROTOCOL_VERSIONS[0]);
+
+        let client = params
+            .client_info
+            .as_ref()
+            .and_then(|info| info["name"].as_str())
+            .map(str::to_owned);
+
+        *self.session() = Session {
+            protocol_version: Some(version.to_string()),
+            initialized: false,
+            client,
+        };
+
+        JsonRpcResponse::success(
+            id,
+            json!({
+                "protocolVersion": version,
+                "capabilities": {
+                    "tools": { "listChanged": false },
+                    "resources": { "subscribe": false, "listChanged": false },
+                    "prompts": { "listChanged": false },
+                },
+                "serverInfo": {
+                    "name": "peak-intelligence",
+                    "version": env!("CARGO_PKG_VERSION"),
+                },
+            }),
+        )
+    }
+
+    async fn list_tools(&self, id: Option<Value>) -> JsonRpcResponse {
+        let registry = self.registry.read().await;
+
+        let tools: Vec<Tool> = registry
+            .list_tools()
+            .into_iter()
+            .map(|t| Tool {
+                name: t["name"].as_str().unwrap_or_default().into(),
+                description: t["description"].as_str().unwrap_or_default().into(),
+                input_schema: t["input_schema"].clone(),
+            })
+            .collect();
+
+        JsonRpcResponse::success(id, json!(ListToolsResult { tools }))
+    }
+
+    async fn call_tool(&self, id: Option<Value>, params: Option<Value>) -> Option<JsonRpcResponse> {
+        let Some(params) = params.and_then(|p| serde_json::from_value::<CallToolParams>(p).ok())
+        else {
+            return Some(JsonRpcResponse::error(
+                id,
+                INVALID_PARAMS,
+                "Invalid params".into(),
+            ));
+        };
+
+        if self.registry.read().await.get_tool(&params.name).is_none() {
+            return Some(JsonRpcResponse::error(
+                id,
+                INVALID_PARAMS,
+                format!("Unknown tool: {}", params.name),
+            ));
+        }
+
+        let registry = self.registry.clone();
+        let args = params.arguments.unwrap_or(json!({}));
+        let requester = self
+            .session()
+            .client
+            .clone()
+            .unwrap_or_else(|| "MCP client".to_string());
+        let can_decide = self.approval_token.is_some();
+        let timeout = self.approval_timeout;
+        let task = tokio::spawn(async move {
+            // The registry is only locked to queue the call, not while it
+            // waits for a decision
+            let mut call = registry
+                .read()
+                .await
+                .prepare(&params.name, args, &requester)?;
+
+            if call.is_pending() {
+                let undecided = ApprovalError::Undecided {
+                    tool: params.name.clone(),
+                };
+                if !can_decide {
+                    return Err(undecided.into());
+                }
+                match tokio::time::timeout(timeout, call.approve()).await {
+                    Ok(approved) => approved?,
+                    Err(_) => return Err(undecided.into()),
+                }
+            }
+
+            call.run().await
+        });
+
+        let key = id.as_ref().map(Value::to_string).unwrap_or_default();
+        self.in_flight().insert(key.clone(), task.abort_handle());
+
+        let result = task.await;
+        self.in_flight().remove(&key);
+
+        let (text, structured_content, is_error) = match result {
+            Ok(Ok(value)) => (value.to_string(), None, false),
+            Ok(Err(e)) => {
+                // Policy violations, failed edits and denied calls are also
+                // reported as data agents can act on
+                let structured = e
+                    .downcast_ref::<Violation>()
+                    .map(Violation::to_json)
+                    .or_else(|| e.downcast_ref::<EditError>().map(EditError::to_json))
+                    .or_else(|| {
+                        e.downcast_ref::<ApprovalError>()
+                            .map(ApprovalError::to_json)
+                    })
+                    .map(|error| json!({ "error": error }));
+                (format!("Error: {}", e), structured, true)
+            }
+            // Cancelled requests must not be answered
+            Err(e) if e.is_cancelled() => return None,
+            Err(e) => (format!("Error: {}", e), None, true),
+        };
+
+        Some(JsonRpcResponse::success(
+            id,
+            json!(CallToolResult {
+                content: vec![ToolContent {
+                    r#type: "text".into(),
+                    text,
+                }],
+                structured_content,
+                is_error: Some(is_error),
+            }),
+        ))
+    }
+
+    async fn list_approvals(&self, id: Option<Value>) -> JsonRpcResponse {
+        let registry = self.registry.read().await;
+        let requests = registry
+            .approvals()
+            .map(|approvals| approvals.pending())
+            .unwrap_or_default();
+
+        JsonRpcResponse::success(id, json!({ "requests": requests }))
+    }
+
+    async fn decide_approval(&self, id: Option<Value>, params: Option<Value>) -> JsonRpcResponse {
+        let params = params.unwrap_or_default();
+
+        // Whoever made a call can't be the one approving it
+        let token = params["token"].as_str();
+        if self.approval_token.is_none() || token != self.approval_token.as_deref() {
+            return JsonRpcResponse::error(
+                id,
+                INVALID_REQUEST,
+                "Approvals are decided by the app, not by MCP clients".into(),
+            );
+        }
+
+        let (Some(request), Ok(decision)) = (
+            params["id"].as_u64(),
+            serde_json::from_value::<Decision>(params["decision"].clone()),
+        ) else {
+            return JsonRpcResponse::error(
+                id,
+                INVALID_PARAMS,
+                "Expected 'id' and a 'decision' of approve, deny or always_allow".into(),
+            );
+        };
+
+        let registry = self.registry.read().await;
+        let Some(approvals) = registry.approvals() else {
+            return JsonRpcResponse::error(id, INVALID_REQUEST, "Approvals are disabled".into());
+        };
+
+        match approvals.decide(request, decision) {
+            Ok(()) => JsonRpcResponse::success(id, json!({})),
+            Err(e) => JsonRpcResponse::error(id, INVALID_PARAMS, e.to_string()),
+        }
+    }
+
+    fn session(&self) -> std::sync::MutexGuard<'_, Session> {
+        self.session.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn in_flight(&self) -> std::sync::MutexGuard<'_, HashMap<String, AbortHandle>> {
+        self.in_flight
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// The notification telling clients about a change to the approval queue:
+/// `approval/requested` with the request, `approval/resolved` with its id
+/// and decision, or `approval/withdrawn` with its id.
+pub fn approval_notification(event: &Event) -> Value {
+    let (method, params) = match event {
+        Event::Requested(request) => ("approval/requested", json!(request)),
+        Event::Resolved { id, decision } => (
+            "approval/resolved",
+            json!({ "id": id, "decision": decision }),
+        ),
+        Event::Withdrawn { id } => ("approval/withdrawn", json!({ "id": id })),
+    };
+
+    json!({
+        "jsonrpc": "2.0",
+        "method": method,
+        "params": params,
+    })
+}
+
+fn resources() -> Vec<Resource> {
+    vec![
+        Resource {
+            uri: VIEW_URI.into(),
+            name: "view".into(),
+            description: "Semantic tree of the view currently shown by the PeakUI app".into(),
+            mime_type: "application/json".into(),
+        },
+        Resource {
+            uri: TELEMETRY_URI.into(),
+            name: "telemetry".into(),
+            description: "Current CPU temperature, battery level and memory usage".into(),
+            mime_type: "application/json".into(),
+        },
+    ]
+}
+
+fn read(uri: &str) -> Result<ResourceContents, (i32, String)> {
+    let text = match uri {
+        VIEW_URI => std::fs::read_to_string(VIEW_EXPORT)
+            .map_err(|_| (RESOURCE_NOT_FOUND, "View not exported yet".to_string()))?,
+        TELEMETRY_URI => json!(crate::kernel::SystemTelemetry::snapshot()).to_string(),
+        _ => return Err((RESOURCE_NOT_FOUND, format!("Resource not found: {}", uri))),
+    };
+
+    Ok(ResourceContents {
+        uri: uri.to_string(),
+        mime_type: "application/json".into(),
+        text,
+    })
+}
+
+fn read_resource(id: Option<Value>, params: Option<Value>) -> JsonRpcResponse {
+    let Some(uri) = params.as_ref().and_then(|p| p["uri"].as_str()) else {
+        return JsonRpcResponse::error(id, INVALID_PARAMS, "Missing 'uri'".into());
+    };
+
+    match read(uri) {
+        Ok(contents) => JsonRpcResponse::success(id, json!({ "contents": [contents] })),
+        Err((code, message)) => JsonRpcResponse::error(id, code, message),
+    }
+}
+
+fn prompts() -> Vec<Prompt> {
+    vec![
+        Prompt {
+            name: "describe_view".into(),
+            description: "Explain what the PeakUI app is currently showing".into(),
+            arguments: Vec::new(),
+        },
+        Prompt {
+            name: "system_health".into(),
+            description: "Assess the machine's current telemetry".into(),
+            arguments: Vec::new(),
+        },
+    ]
+}
+
+fn get_prompt(id: Option<Value>, params: Option<Value>) -> JsonRpcResponse {
+    let (uri, instruction) = match params.as_ref().and_then(|p| p["name"].as_str()) {
+        Some("describe_view") => (
+            VIEW_URI,
+            "Describe the screen above: where the user is and what they can do next.",
+        ),
+        Some("system_health") => (
+            TELEMETRY_URI,
+            "Summarize the telemetry above and point out anything that needs attention.",
+        ),
+        Some(name) => {
+            return JsonRpcResponse::error(id, INVALID_PARAMS, format!("Unknown prompt: {}", name))
+        }
+        None => return JsonRpcResponse::error(id, INVALID_PARAMS, "Missing 'name'".into()),
+    };
+
+    match read(uri) {
+        Ok(contents) => JsonRpcResponse::success(
+            id,
+            json!({
+                "messages": [
+                    { "role": "user", "content": { "type": "resource", "resource": contents } },
+                    { "role": "user", "content": { "type": "text", "text": instruction } },
+                ]
+            }),
+        ),
+        Err((code, message)) => JsonRpcResponse::error(id, code, message),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tools::registry::IntelligenceTool;
+    use anyhow::Result;
+    use async_trait::async_trait;
+    use std::time::Duration;
+
+    struct SlowTool;
+
+    #[async_trait]
+    impl IntelligenceTool for SlowTool {
+        fn name(&self) -> &'static str {
+            "slow_tool"
+        }
+        fn description(&self) -> &'static str {
+            "Sleeps for the requested number of milliseconds"
+        }
+        fn input_schema(&self) -> Value {
+            json!({ "type": "object" })
+        }
+        async fn execute(&self, args: Value) -> Result<Value> {
+            let ms = args["ms"].as_u64().unwrap_or(0);
+            tokio::time::sleep(Duration::from_millis(ms)).await;
+            Ok(json!({ "slept": ms }))
+        }
+    }
+
+    fn server() -> Server {
+        let mut registry = ToolRegistry::new();
+        registry.register(SlowTool);
+        Server::new(registry)
+    }
+
+    fn request(id: i64, method: &str, params: Value) -> JsonRpcRequest {
+        JsonRpcRequest {
+            jsonrpc: "2.0".into(),
+            method: method.into(),
+            params: Some(params),
+            id: Some(json!(id)),
+        }
+    }
+
+    fn notification(method: &str, params: Value) -> JsonRpcRequest {
+        JsonRpcRequest {
+            id: None,
+            ..request(0, method, params)
+        }
+    }
+
+    async fn initialize(server: &Server, version: &str) -> JsonRpcResponse {
+        let response = server
+            .handle(request(
+                0,
+                "initialize",
+                json!({ "protocolVersion": version, "capabilities": {}, "clientInfo": { "name": "test" } }),
+            ))
+            .await
+            .unwrap();
+
+        server
+            .handle(notification("notifications/initialized", json!({})))
+            .await;
+
+        response
+    }
+
+    #[tokio::test]
+    async fn test_lifecycle_and_version_negotiation() {
+        let server = server();
+
+        let ping = server.handle(request(1, "ping", json!({}))).await.unwrap();
+        assert_eq!(ping.result, Some(json!({})));
+
+        let early = server
+            .handle(request(2, "tools/list", json!({})))
+            .await
+            .unwrap();
+        assert_eq!(early.error.unwrap().code, INVALID_REQUEST);
+
+        let response = initialize(&server, "2024-11-05").await;
+        let result = response.result.unwrap();
+        assert_eq!(result["protocolVersion"], "2024-11-05");
+        assert_eq!(result["serverInfo"]["name"], "peak-intelligence");
+        assert!(result["capabilities"]["resources"].is_object());
+        assert!(server.is_initialized());
+
+        let response = initialize(&server, "1999-01-01").await;
+        assert_eq!(
+            response.result.unwrap()["protocolVersion"],
+            PROTOCOL_VERSIONS[0]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_tools_use_mcp_field_names() {
+        let server = server();
+        initialize(&server, PROTOCOL_VERSIONS[0]).await;
+
+        let list = server
+            .handle(request(1, "tools/list", json!({})))
+            .await
+            .unwrap();
+        let tools = list.result.unwrap()["tools"].clone();
+        assert_eq!(tools[0]["name"], "slow_tool");
+        assert_eq!(tools[0]["inputSchema"]["type"], "object");
+
+        let call = server
+            .handle(request(
+                2,
+                "tools/call",
+                json!({ "name": "slow_tool", "arguments": { "ms": 1 } }),
+            ))
+            .await
+            .unwrap();
+        let result = call.result.unwrap();
+        assert_eq!(result["isError"], false);
+        assert_eq!(result["content"][0]["text"], r#"{"slept":1}"#);
+
+        let unknown = server
+            .handle(request(3, "tools/call", json!({ "name": "missing" })))
+            .await
+            .unwrap();
+        assert_eq!(unknown.error.unwrap().code, INVALID_PARAMS);
+    }
+
+    #[tokio::test]
+    async fn test_undecided_calls_are_denied() {
+        use crate::tools::approval::Approvals;
+
+        let approvals = Approvals::new();
+        let mut registry = ToolRegistry::new().with_approvals(approvals.clone());
+        registry.register(SlowTool);
+        registry.set_requires_approval("slow_tool", true);
+        let call = json!({ "name": "slow_tool", "arguments": { "ms": 1 } });
+
+        // Without a token nobody could decide
+        let server = Server::new(registry);
+        initialize(&server, PROTOCOL_VERSIONS[0]).await;
+        let result = server
+            .handle(request(1, "tools/call", call.clone()))
+            .await
+            .unwrap()
+            .result
+            .unwrap();
+        assert_eq!(result["structuredContent"]["error"]["kind"], "undecided");
+        assert!(approvals.pending().is_empty());
+
+        let server = server
+            .new_session()
+            .with_approval_token("ui")
+            .with_approval_timeout(Duration::from_millis(10));
+        initialize(&server, PROTOCOL_VERSIONS[0]).await;
+        let result = server
+            .handle(request(2, "tools/call", call))
+            .await
+            .unwrap()
+            .result
+            .unwrap();
+        assert_eq!(result["structuredContent"]["error"]["kind"], "undecided");
+        assert!(approvals.pending().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_policy_violations_are_structured() {
+        use crate::tools::fs_policy::Policy;
+        use crate::tools::fs_tools::ReadFileTool;
+
+        let mut registry = ToolRegistry::new();
+        registry.register(ReadFileTool::new(Arc::new(Policy::new())));
+        let server = Server::new(registry);
+        initialize(&server, PROTOCOL_VERSIONS[0]).await;
+
+        let call = server
+            .handle(request(
+                1,
+                "tools/call",
+                json!({ "name": "read_file", "arguments": { "path": "/etc/hosts" } }),
+            ))
+            .await
+            .unwrap();
+        let result = call.result.unwrap();
+        assert_eq!(result["isError"], true);
+        assert_eq!(
+            result["structuredContent"]["error"]["kind"],
+            "outside_roots"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_cancelled_calls_are_not_answered() {
+        let server = Arc::new(server());
+        initialize(&server, PROTOCOL_VERSIONS[0]).await;
+
+        let call = tokio::spawn({
+            let server = server.clone();
+            async move {
+                server
+                    .handle(request(
+                        7,
+                        "tools/call",
+                        json!({ "name": "slow_tool", "arguments": { "ms": 60_000 } }),
+                    ))
+                    .await
+            }
+        });
+
+        while server.in_flight().is_empty() {
+            tokio::task::yield_now().await;
+        }
+
+        server
+            .handle(notification(
+                "notifications/cancelled",
+                json!({ "requestId": 7, "reason": "test" }),
+            ))
+            .await;
+
+        let response = tokio::time::timeout(Duration::from_secs(5), call)
+            .await
+            .expect("cancelled call finishes promptly")
+            .unwrap();
+        assert!(response.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_protected_calls_wait_for_a_decision() {
+        use crate::tools::approval::Approvals;
+
+        let approvals = Approvals::new();
+        let mut registry = ToolRegistry::new().with_approvals(approvals.clone());
+        registry.register(SlowTool);
+        registry.set_requires_approval("slow_tool", true);
+        let server = Arc::new(Server::new(registry).with_approval_token("ui"));
+        initialize(&server, PROTOCOL_VERSIONS[0]).await;
+
+        let call = |id: i64| {
+            let server = server.clone();
+            tokio::spawn(async move {
+                server
+                    .handle(request(
+                        id,
+                        "tools/call",
+                        json!({ "name": "slow_tool", "arguments": { "ms": 1 } }),
+                    ))
+                    .await
+                    .unwrap()
+            })
+        };
+        let pending = || async {
+            loop {
+                let list = server
+                    .handle(request(0, "approvals/list", json!({})))
+                    .await
+                    .unwrap();
+                let requests = list.result.unwrap()["requests"].clone();
+                if let Some(queued) = requests.get(0) {
+                    return queued.clone();
+                }
+                tokio::task::yield_now().await;
+            }
+        };
+
+        let denied = call(1);
+        let queued = pending().await;
+        assert_eq!(queued["tool"], "slow_tool");
+        assert_eq!(queued["requester"], "test");
+        assert_eq!(
+            approval_notification(&Event::Requested(approvals.pending()[0].clone()))["method"],
+            "approval/requested"
+        );
+
+        // The caller can't approve its own call
+        for token in [json!(null), json!("guess")] {
+            let refused = server
+                .handle(request(
+                    2,
+                    "approvals/decide",
+                    json!({ "id": queued["id"], "decision": "approve", "token": token }),
+                ))
+                .await
+                .unwrap();
+            assert_eq!(refused.error.unwrap().code, INVALID_REQUEST);
+        }
+        assert_eq!(approvals.pending().len(), 1);
+
+        let decided = server
+            .handle(request(
+                2,
+                "approvals/decide",
+                json!({ "id": queued["id"], "decision": "deny", "token": "ui" }),
+            ))
+            .await
+            .unwrap();
+        assert!(decided.error.is_none());
+        let result = denied.await.unwrap().result.unwrap();
+        assert_eq!(result["isError"], true);
+        assert_eq!(result["structuredContent"]["error"]["kind"], "denied");
+
+        let approved = call(3);
+        let queued = pending().await;
+        server
+            .handle(request(
+                4,
+                "approvals/decide",
+                json!({ "id": queued["id"], "decision": "approve", "token": "ui" }),
+            ))
+            .await;
+        let result = approved.await.unwrap().result.unwrap();
+        assert_eq!(result["isError"], false);
+
+        let unknown = server
+            .handle(request(
+                5,
+                "approvals/decide",
+                json!({ "id": 99, "decision": "approve", "token": "ui" }),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(unknown.error.unwrap().code, INVALID_PARAMS);
+    }
+
+    #[tokio::test]
+    async fn test_resources_and_prompts() {
+        let server = server();
+        initialize(&server, PROTOCOL_VERSIONS[0]).await;
+
+        let list = server
+            .handle(request(1, "resources/list", json!({})))
+            .await
+            .unwrap();
+        let uris: Vec<_> = list.result.unwrap()["resources"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|r| r["uri"].as_str().unwrap().to_string())
+            .collect();
+        assert_eq!(uris, [VIEW_URI, TELEMETRY_URI]);
+
+        let telemetry = server
+            .handle(request(
+                2,
+                "resources/read",
+                json!({ "uri": TELEMETRY_URI }),
+            ))
+            .await
+            .unwrap();
+        let contents = telemetry.result.unwrap()["contents"][0].clone();
+        let snapshot: Value = serde_json::from_str(contents["text"].as_str().unwrap()).unwrap();
+        assert!(snapshot.get("memory_used").is_some());
+
+        let missing = server
+            .handle(request(
+                3,
+                "resources/read",
+                json!({ "uri": "peak://nope" }),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(missing.error.unwrap().code, RESOURCE_NOT_FOUND);
+
+        let prompt = server
+            .handle(request(
+                4,
+                "prompts/get",
+                json!({ "name": "system_health" }),
+            ))
+            .await
+            .unwrap();
+        let messages = prompt.result.unwrap()["messages"].clone();
+        assert_eq!(messages[0]["content"]["resource"]["uri"], TELEMETRY_URI);
+    }
+}
@@ -1,18 +1,66 @@
+//! PTY sessions for agents driving shells.
+//!
+//! Each session runs a shell on its own pseudo-terminal. Its output is
+//! streamed as `terminal/output` notifications and fed to a [`Screen`], so
+//! tools can read the rendered screen and scrollback instead of raw escape
+//! sequences. `terminal/exit` reports the exit status when the shell ends.
 #[cfg(feature = "native")]
-use portable_pty::{native_pty_system, CommandBuilder, PtySize};
+pub mod screen;
+
+#[cfg(feature = "native")]
+pub use screen::Screen;
+
+#[cfg(feature = "native")]
+use portable_pty::{native_pty_system, ChildKiller, CommandBuilder, MasterPty, PtySize};
 #[cfg(feature = "native")]
 use serde_json::json;
 #[cfg(feature = "native")]
+use std::collections::BTreeMap;
+#[cfg(feature = "native")]
 use std::io::{Read, Write};
 #[cfg(feature = "native")]
-use std::sync::{Arc, Mutex};
+use std::sync::{Arc, Mutex, PoisonError};
 #[cfg(feature = "native")]
 use std::thread;
 
+/// Sessions open at once; agents must close one before opening more.
+pub const MAX_SESSIONS: usize = 16;
+
+#[cfg(feature = "native")]
+struct Session {
+    name: Option<String>,
+    master: Box<dyn MasterPty + Send>,
+    writer: Box<dyn Write + Send>,
+    killer: Box<dyn ChildKiller + Send + Sync>,
+    screen: Arc<Mutex<Screen>>,
+    exit: Arc<Mutex<Option<portable_pty::ExitStatus>>>,
+}
+
+#[cfg(feature = "native")]
+impl Session {
+    fn exit_code(&self) -> Option<u32> {
+        lock(&self.exit).as_ref().map(|status| status.exit_code())
+    }
+
+    fn describe(&self, id: u64) -> serde_json::Value {
+        let (rows, cols) = lock(&self.screen).size();
+        let exit_code = self.exit_code();
+
+        json!({
+            "session": id.to_string(),
+            "name": self.name,
+            "rows": rows,
+            "cols": cols,
+            "running": exit_code.is_none(),
+            "exit_code": exit_code,
+        })
+    }
+}
+
 #[cfg(feature = "native")]
 pub struct TerminalManager {
-    pty_pair: Arc<Mutex<Option<portable_pty::PtyPair>>>,
-    writer: Arc<Mutex<Option<Box<dyn Write + Send>>>>,
+    sessions: Mutex<BTreeMap<u64, Session>>,
+    next_id: std::sync::atomic::AtomicU64,
 }
 
 #[cfg(not(feature = "native"))]
@@ -28,8 +76,8 @@ impl TerminalManager {
     #[cfg(feature = "native")]
     pub fn new() -> Self {
         Self {
-            pty_pair: Arc::new(Mutex::new(None)),
-            writer: Arc::new(Mutex::new(None)),
+            sessions: Mutex::new(BTreeMap::new()),
+            next_id: std::sync::atomic::AtomicU64::new(1),
         }
     }
 
@@ -38,20 +86,34 @@ impl TerminalManager {
         Self
     }
 
+    /// Starts a shell in a new session, which becomes the default one.
     #[cfg(feature = "native")]
     pub fn open(
         &self,
+        name: Option<String>,
         rows: u16,
         cols: u16,
         tx: tokio::sync::mpsc::Sender<String>,
     ) -> anyhow::Result<serde_json::Value> {
-        let pty_system = native_pty_system();
-        let pair = pty_system.openpty(PtySize {
+        {
+            let sessions = lock(&self.sessions);
+
+            if sessions.len() >= MAX_SESSIONS {
+                anyhow::bail!("Too many terminal sessions; close one first");
+            }
+
+            if name.is_some() && sessions.values().any(|session| session.name == name) {
+                anyhow::bail!("A terminal session named {:?} already exists", name);
+            }
+        }
+
+        let size = PtySize {
             rows,
             cols,
             pixel_width: 0,
             pixel_height: 0,
-        })?;
+        };
+        let pair = native_pty_system().openpty(size)?;
 
         let shell = if cfg!(target_os = "windows") {
             "powershell.exe"
@@ -60,45 +122,96 @@ impl TerminalManager {
         };
 
         let cmd = CommandBuilder::new(shell);
-        let _child = pair.slave.spawn_command(cmd)?;
+        let mut child = pair.slave.spawn_command(cmd)?;
+        // Only the child keeps the slave open, so reads end when it exits
+        drop(pair.slave);
 
-        let reader = pair.master.try_clone_reader()?;
+        let mut reader = pair.master.try_clone_reader()?;
         let writer = pair.master.take_writer()?;
 
-        let mut pty_pair_lock = self.pty_pair.lock().unwrap();
-        *pty_pair_lock = Some(pair);
+        let id = self
+            .next_id
+            .fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+        let screen = Arc::new(Mutex::new(Screen::new(rows, cols)));
+        let exit = Arc::new(Mutex::new(None));
 
-        let mut writer_lock = self.writer.lock().unwrap();
-        *writer_lock = Some(writer);
-
-        let tx = tx.clone();
         // Spawn reader thread
-        thread::spawn(move || {
-            let mut reader = reader;
-            let mut buffer = [0u8; 1024];
-            loop {
-                match reader.read(&mut buffer) {
-                    Ok(n) if n > 0 => {
-                        let data = String::from_utf8_lossy(&buffer[..n]).to_string();
-                        let notification = json!({
-                            "jsonrpc": "2.0",
-                            "method": "terminal/output",
-                            "params": { "data": data }
-                        });
-                        let _ = tx.blocking_send(notification.to_string());
+        thread::spawn({
+            let screen = screen.clone();
+            let tx = tx.clone();
+
+            move || {
+                let mut buffer = [0u8; 4096];
+                let mut pending = Vec::new();
+
+                loop {
+                    match reader.read(&mut buffer) {
+                        Ok(n) if n > 0 => {
+                            lock(&screen).process(&buffer[..n]);
+
+                            let data = decode(&mut pending, &buffer[..n]);
+                            if data.is_empty() {
+                                continue;
+                            }
+
+                            let notification = json!({
+                                "jsonrpc": "2.0",
+                                "method": "terminal/output",
+                                "params": { "session": id.to_string(), "data": data }
+                            });
+                            let _ = tx.blocking_send(notification.to_string());
+                        }
+                        Ok(_) => break, // EOF
+                        Err(_) => break,
                     }
-                    Ok(_) => break, // EOF
-                    Err(_) => break,
                 }
             }
         });
 
-        Ok(json!("Terminal opened"))
+        // Spawn exit watcher
+        let killer = child.clone_killer();
+        thread::spawn({
+            let exit = exit.clone();
+
+            move || {
+                let Ok(status) = child.wait() else {
+                    return;
+                };
+
+                let notification = json!({
+                    "jsonrpc": "2.0",
+                    "method": "terminal/exit",
+                    "params": {
+                        "session": id.to_string(),
+                        "exit_code": status.exit_code(),
+                        "success": status.success(),
+                    }
+                });
+
+                *lock(&exit) = Some(status);
+                let _ = tx.blocking_send(notification.to_string());
+            }
+        });
+
+        let session = Session {
+            name,
+            master: pair.master,
+            writer,
+            killer,
+            screen,
+            exit,
+        };
+        let description = session.describe(id);
+
+        lock(&self.sessions).insert(id, session);
+
+        Ok(description)
     }
 
     #[cfg(not(feature = "native"))]
     pub fn open(
         &self,
+        _name: Option<String>,
         _rows: u16,
         _cols: u16,
         _tx: tokio::sync::mpsc::Sender<String>,
@@ -107,40 +220,275 @@ impl TerminalManager {
     }
 
     #[cfg(feature = "native")]
-    pub fn write(&self, data: &str) -> anyhow::Result<serde_json::Value> {
-        let mut writer_lock = self.writer.lock().unwrap();
-        if let Some(writer) = writer_lock.as_mut() {
-            writer.write_all(data.as_bytes())?;
-            writer.flush()?;
+    pub fn write(&self, session: Option<&str>, data: &str) -> anyhow::Result<serde_json::Value> {
+        self.with_session(session, |_, session| {
+            if session.exit_code().is_some() {
+                anyhow::bail!("Terminal session has exited");
+            }
+
+            session.writer.write_all(data.as_bytes())?;
+            session.writer.flush()?;
             Ok(json!("Data written"))
-        } else {
-            Err(anyhow::anyhow!("Terminal not open"))
-        }
+        })
     }
 
     #[cfg(not(feature = "native"))]
-    pub fn write(&self, _data: &str) -> anyhow::Result<serde_json::Value> {
+    pub fn write(&self, _session: Option<&str>, _data: &str) -> anyhow::Result<serde_json::Value> {
         Err(anyhow::anyhow!("Terminal not supported on web"))
     }
 
     #[cfg(feature = "native")]
-    pub fn resize(&self, rows: u16, cols: u16) -> anyhow::Result<serde_json::Value> {
-        let pty_pair_lock = self.pty_pair.lock().unwrap();
-        if let Some(pair) = pty_pair_lock.as_ref() {
-            pair.master.resize(PtySize {
+    pub fn resize(
+        &self,
+        session: Option<&str>,
+        rows: u16,
+        cols: u16,
+    ) -> anyhow::Result<serde_json::Value> {
+        self.with_session(session, |_, session| {
+            session.master.resize(PtySize {
                 rows,
                 cols,
                 pixel_width: 0,
                 pixel_height: 0,
             })?;
+            lock(&session.screen).resize(rows, cols);
             Ok(json!("Terminal resized"))
-        } else {
-            Err(anyhow::anyhow!("Terminal not open"))
+        })
+    }
+
+    #[cfg(not(feature = "native"))]
+    pub fn resize(
+        &self,
+        _session: Option<&str>,
+        _rows: u16,
+        _cols: u16,
+    ) -> anyhow::Result<serde_json::Value> {
+        Err(anyhow::anyhow!("Terminal not supported on web"))
+    }
+
+    /// The rendered screen of a session, with up to `scrollback` lines that
+    /// scrolled off it.
+    #[cfg(feature = "native")]
+    pub fn screen(
+        &self,
+        session: Option<&str>,
+        scrollback: usize,
+    ) -> anyhow::Result<serde_json::Value> {
+        self.with_session(session, |id, session| {
+            let mut description = session.describe(id);
+            let screen = lock(&session.screen);
+            let (row, col) = screen.cursor();
+
+            description["screen"] = json!(screen.text());
+            description["cursor"] = json!({ "row": row, "col": col });
+            description["title"] = json!(screen.title());
+            description["alternate_screen"] = json!(screen.is_alternate());
+            description["scrollback"] = json!(screen.scrollback(scrollback));
+
+            Ok(description)
+        })
+    }
+
+    #[cfg(not(feature = "native"))]
+    pub fn screen(
+        &self,
+        _session: Option<&str>,
+        _scrollback: usize,
+    ) -> anyhow::Result<serde_json::Value> {
+        Err(anyhow::anyhow!("Terminal not supported on web"))
+    }
+
+    #[cfg(feature = "native")]
+    pub fn list(&self) -> anyhow::Result<serde_json::Value> {
+        let sessions = lock(&self.sessions);
+
+        Ok(json!(sessions
+            .iter()
+            .map(|(id, session)| session.describe(*id))
+            .collect::<Vec<_>>()))
+    }
+
+    #[cfg(not(feature = "native"))]
+    pub fn list(&self) -> anyhow::Result<serde_json::Value> {
+        Ok(serde_json::json!([]))
+    }
+
+    /// Ends a session, killing its shell if it is still running.
+    #[cfg(feature = "native")]
+    pub fn close(&self, session: &str) -> anyhow::Result<serde_json::Value> {
+        let mut sessions = lock(&self.sessions);
+        let id = find(&sessions, Some(session))?;
+        let mut session = sessions.remove(&id).expect("session was just found");
+        drop(sessions);
+
+        if session.exit_code().is_none() {
+            // It may have exited in the meantime
+            let _ = session.killer.kill();
         }
+
+        Ok(json!({ "session": id.to_string(), "closed": true }))
     }
 
     #[cfg(not(feature = "native"))]
-    pub fn resize(&self, _rows: u16, _cols: u16) -> anyhow::Result<serde_json::Value> {
+    pub fn close(&self, _session: &str) -> anyhow::Result<serde_json::Value> {
         Err(anyhow::anyhow!("Terminal not supported on web"))
     }
+
+    #[cfg(feature = "native")]
+    fn with_session<T>(
+        &self,
+        session: Option<&str>,
+        f: impl FnOnce(u64, &mut Session) -> anyhow::Result<T>,
+    ) -> anyhow::Result<T> {
+        let mut sessions = lock(&self.sessions);
+        let id = find(&sessions, session)?;
+
+        f(id, sessions.get_mut(&id).expect("session was just found"))
+    }
+}
+
+#[cfg(feature = "native")]
+impl Drop for TerminalManager {
+    fn drop(&mut self) {
+        for session in lock(&self.sessions).values_mut() {
+            let _ = session.killer.kill();
+        }
+    }
+}
+
+/// Looks a session up by id or name; without either, the most recently
+/// opened session is used.
+#[cfg(feature = "native")]
+fn find(sessions: &BTreeMap<u64, Session>, session: Option<&str>) -> anyhow::Result<u64> {
+    let Some(session) = session else {
+        return sessions
+            .keys()
+            .next_back()
+            .copied()
+            .ok_or_else(|| anyhow::anyhow!("Terminal not open"));
+    };
+
+    session
+        .parse()
+        .ok()
+        .filter(|id| sessions.contains_key(id))
+        .or_else(|| {
+            sessions
+                .iter()
+                .find(|(_, s)| s.name.as_deref() == Some(session))
+                .map(|(id, _)| *id)
+        })
+        .ok_or_else(|| anyhow::anyhow!("No terminal session {:?}", session))
+}
+
+#[cfg(feature = "native")]
+fn lock<T>(mutex: &Mutex<T>) -> std::sync::MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Decodes PTY output as UTF-8, holding back a sequence split at the end of
+/// `bytes` until the rest of it arrives.
+#[cfg(feature = "native")]
+fn decode(pending: &mut Vec<u8>, bytes: &[u8]) -> String {
+    pending.extend_from_slice(bytes);
+
+    let complete = pending.len() - incomplete_tail(pending);
+    let data = String::from_utf8_lossy(&pending[..complete]).into_owned();
+    pending.drain(..complete);
+
+    data
+}
+
+/// Length of an unfinished UTF-8 sequence at the end of `bytes`.
+#[cfg(feature = "native")]
+fn incomplete_tail(bytes: &[u8]) -> usize {
+    for back in 1..=bytes.len().min(3) {
+        let byte = bytes[bytes.len() - back];
+
+        // Skip continuation bytes until the sequence start
+        if byte & 0b1100_0000 == 0b1000_0000 {
+            continue;
+        }
+
+        let length = match byte {
+            0xc0..=0xdf => 2,
+            0xe0..=0xef => 3,
+            0xf0..=0xf7 => 4,
+            _ => 1,
+        };
+
+        return if length > back { back } else { 0 };
+    }
+
+    0
+}
+
+#[cfg(all(test, feature = "native"))]
+mod tests {
+    use super::*;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn test_decode_holds_back_split_sequences() {
+        let mut pending = Vec::new();
+        let text = "→ é";
+        let bytes = text.as_bytes();
+
+        assert_eq!(decode(&mut pending, &bytes[..1]), "");
+        assert_eq!(decode(&mut pending, &bytes[1..5]), "→ ");
+        assert_eq!(decode(&mut pending, &bytes[5..]), "é");
+        assert!(pending.is_empty());
+
+        // Invalid bytes are still replaced rather than held forever
+        assert_eq!(decode(&mut pending, b"\xff!"), "\u{fffd}!");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_sessions_render_screen_and_report_exit() -> anyhow::Result<()> {
+        let manager = TerminalManager::new();
+        let (tx, _rx) = tokio::sync::mpsc::channel(1024);
+
+        let first = manager.open(Some("build".into()), 24, 80, tx.clone())?;
+        let second = manager.open(None, 24, 80, tx)?;
+        assert_eq!(manager.list()?.as_array().unwrap().len(), 2);
+        assert!(manager
+            .open(
+                Some("build".into()),
+                24,
+                80,
+                tokio::sync::mpsc::channel(1).0
+            )
+            .is_err());
+
+        manager.write(Some("build"), "printf '\\033[31mred\\033[0m\\n'; exit 3\n")?;
+
+        let deadline = Instant::now() + Duration::from_secs(10);
+        let screen = loop {
+            let screen = manager.screen(Some("build"), 100)?;
+
+            if !screen["running"].as_bool().unwrap() || Instant::now() > deadline {
+                break screen;
+            }
+
+            thread::sleep(Duration::from_millis(20));
+        };
+
+        assert_eq!(screen["exit_code"], 3);
+        assert!(screen["screen"]
+            .as_str()
+            .unwrap()
+            .lines()
+            .any(|line| line == "red"));
+        assert_eq!(screen["session"], first["session"]);
+
+        // Without a session, the latest one is used
+        assert_eq!(manager.screen(None, 0)?["session"], second["session"]);
+
+        manager.close("build")?;
+        manager.close(second["session"].as_str().unwrap())?;
+        assert!(manager.write(None, "ls\n").is_err());
+
+        Ok(())
+    }
 }
@@ -0,0 +1,591 @@
+//! VT100 screen model fed by a `vte` parser.
+//!
+//! Only what affects the rendered text is modelled: printing and wrapping,
+//! cursor movement, erasing, insertion and deletion, scroll regions and the
+//! alternate screen. Colors and other attributes are parsed and dropped.
+//! Lines scrolled off the top of the primary screen are kept in a bounded
+//! scrollback.
+use std::collections::VecDeque;
+use vte::{Params, Parser, Perform};
+
+/// Lines kept above the screen by default.
+pub const SCROLLBACK_LINES: usize = 2000;
+
+pub struct Screen {
+    parser: Parser,
+    grid: Grid,
+}
+
+impl Screen {
+    pub fn new(rows: u16, cols: u16) -> Self {
+        Self::with_scrollback(rows, cols, SCROLLBACK_LINES)
+    }
+
+    pub fn with_scrollback(rows: u16, cols: u16, lines: usize) -> Self {
+        Self {
+            parser: Parser::new(),
+            grid: Grid::new(usize::from(rows.max(1)), usize::from(cols.max(1)), lines),
+        }
+    }
+
+    /// Feeds raw terminal output. Escape and UTF-8 sequences may be split
+    /// across calls.
+    pub fn process(&mut self, bytes: &[u8]) {
+        self.parser.advance(&mut self.grid, bytes);
+    }
+
+    pub fn resize(&mut self, rows: u16, cols: u16) {
+        self.grid
+            .resize(usize::from(rows.max(1)), usize::from(cols.max(1)));
+    }
+
+    pub fn size(&self) -> (u16, u16) {
+        (self.grid.rows as u16, self.grid.cols as u16)
+    }
+
+    /// Zero-based `(row, column)` of the cursor.
+    pub fn cursor(&self) -> (u16, u16) {
+        (self.grid.row as u16, self.grid.col as u16)
+    }
+
+    pub fn title(&self) -> &str {
+        &self.grid.title
+    }
+
+    /// Whether a full-screen program switched to the alternate screen.
+    pub fn is_alternate(&self) -> bool {
+        self.grid.primary.is_some()
+    }
+
+    /// The visible lines, without trailing blanks.
+    pub fn lines(&self) -> Vec<String> {
+        self.grid.lines.iter().map(|line| render(line)).collect()
+    }
+
+    /// The visible screen as text, without trailing blank lines.
+    pub fn text(&self) -> String {
+        let mut lines = self.lines();
+
+        while lines.last().is_some_and(String::is_empty) {
+            lines.pop();
+        }
+
+        lines.join("\n")
+    }
+
+    /// The last `limit` lines that scrolled off the screen, oldest first.
+    pub fn scrollback(&self, limit: usize) -> Vec<String> {
+        let skip = self.grid.scrollback.len().saturating_sub(limit);
+        self.grid.scrollback.iter().skip(skip).cloned().collect()
+    }
+}
+
+fn render(line: &[char]) -> String {
+    line.iter().collect::<String>().trim_end().to_owned()
+}
+
+/// Screen contents plus the cursor position.
+type Saved = (Vec<Vec<char>>, (usize, usize));
+
+struct Grid {
+    rows: usize,
+    cols: usize,
+    lines: Vec<Vec<char>>,
+    row: usize,
+    col: usize,
+    /// Set after printing in the last column; the next character wraps.
+    pending_wrap: bool,
+    saved: (usize, usize),
+    top: usize,
+    bottom: usize,
+    /// The primary screen and cursor while the alternate one is shown.
+    primary: Option<Saved>,
+    scrollback: VecDeque<String>,
+    scrollback_len: usize,
+    title: String,
+}
+
+impl Grid {
+    fn new(rows: usize, cols: usize, scrollback_len: usize) -> Self {
+        Self {
+            rows,
+            cols,
+            lines: vec![vec![' '; cols]; rows],
+            row: 0,
+            col: 0,
+            pending_wrap: false,
+            saved: (0, 0),
+            top: 0,
+            bottom: rows - 1,
+            primary: None,
+            scrollback: VecDeque::new(),
+            scrollback_len,
+            title: String::new(),
+        }
+    }
+
+    fn blank(&self) -> Vec<char> {
+        vec![' '; self.cols]
+    }
+
+    fn resize(&mut self, rows: usize, cols: usize) {
+        if rows < self.rows {
+            // Keep the cursor line visible by dropping lines above it first
+            let excess = self.rows - rows;
+            let above = excess.min(self.row);
+
+            for line in self.lines.drain(..above).collect::<Vec<_>>() {
+                self.push_scrollback(&line);
+            }
+
+            self.lines.truncate(rows);
+            self.row -= above;
+            self.saved.0 = self.saved.0.saturating_sub(above);
+        }
+
+        for line in &mut self.lines {
+            line.resize(cols, ' ');
+        }
+
+        self.lines.resize(rows, vec![' '; cols]);
+
+        if let Some((lines, _)) = &mut self.primary {
+            for line in lines.iter_mut() {
+                line.resize(cols, ' ');
+            }
+
+            lines.resize(rows, vec![' '; cols]);
+        }
+
+        self.rows = rows;
+        self.cols = cols;
+        self.top = 0;
+        self.bottom = rows - 1;
+        self.row = self.row.min(rows - 1);
+        self.col = self.col.min(cols - 1);
+        self.saved = (self.saved.0.min(rows - 1), self.saved.1.min(cols - 1));
+        self.pending_wrap = false;
+    }
+
+    /// Moves the cursor back to where it was saved, kept on the screen in
+    /// case it shrank since.
+    fn restore_cursor(&mut self) {
+        let (row, col) = self.saved;
+        self.row = row.min(self.rows - 1);
+        self.col = col.min(self.cols - 1);
+        self.pending_wrap = false;
+    }
+
+    fn push_scrollback(&mut self, line: &[char]) {
+        if self.primary.is_some() || self.scrollback_len == 0 {
+            return;
+        }
+
+        if self.scrollback.len() == self.scrollback_len {
+            self.scrollback.pop_front();
+        }
+
+        self.scrollback.push_back(render(line));
+    }
+
+    fn scroll_up(&mut self, count: usize) {
+        for _ in 0..count.min(self.bottom - self.top + 1) {
+            let line = self.lines.remove(self.top);
+
+            if self.top == 0 {
+                self.push_scrollback(&line);
+            }
+
+            self.lines.insert(self.bottom, self.blank());
+        }
+    }
+
+    fn scroll_down(&mut self, count: usize) {
+        for _ in 0..count.min(self.bottom - self.top + 1) {
+            self.lines.remove(self.bottom);
+            self.lines.insert(self.top, self.blank());
+        }
+    }
+
+    fn linefeed(&mut self) {
+        if self.row == self.bottom {
+            self.scroll_up(1);
+        } else if self.row < self.rows - 1 {
+            self.row += 1;
+        }
+    }
+
+    fn reverse_index(&mut self) {
+        if self.row == self.top {
+            self.scroll_down(1);
+        } else {
+            self.row = self.row.saturating_sub(1);
+        }
+    }
+
+    fn erase(&mut self, row: usize, columns: std::ops::Range<usize>) {
+        let end = columns.end.min(self.cols);
+        let start = columns.start.min(end);
+
+        self.lines[row][start..end].fill(' ');
+    }
+
+    fn erase_display(&mut self, mode: u16) {
+        let (row, col) = (self.row, self.col);
+
+        match mode {
+            0 => {
+                self.erase(row, col..self.cols);
+                (row + 1..self.rows).for_each(|row| self.erase(row, 0..self.cols));
+            }
+            1 => {
+                (0..row).for_each(|row| self.erase(row, 0..self.cols));
+                self.erase(row, 0..col + 1);
+            }
+            2 | 3 => {
+                (0..self.rows).for_each(|row| self.erase(row, 0..self.cols));
+
+                if mode == 3 {
+                    self.scrollback.clear();
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn erase_line(&mut self, mode: u16) {
+        let (row, col) = (self.row, self.col);
+
+        match mode {
+            0 => self.erase(row, col..self.cols),
+            1 => self.erase(row, 0..col + 1),
+            2 => self.erase(row, 0..self.cols),
+            _ => {}
+        }
+    }
+
+    fn insert_lines(&mut self, count: usize) {
+        if !(self.top..=self.bottom).contains(&self.row) {
+            return;
+        }
+
+        for _ in 0..count.min(self.bottom - self.row + 1) {
+            self.lines.remove(self.bottom);
+            self.lines.insert(self.row, self.blank());
+        }
+    }
+
+    fn delete_lines(&mut self, count: usize) {
+        if !(self.top..=self.bottom).contains(&self.row) {
+            return;
+        }
+
+        for _ in 0..count.min(self.bottom - self.row + 1) {
+            self.lines.remove(self.row);
+            self.lines.insert(self.bottom, self.blank());
+        }
+    }
+
+    fn insert_chars(&mut self, count: usize) {
+        let (cols, col) = (self.cols, self.col);
+        let line = &mut self.lines[self.row];
+
+        for _ in 0..count.min(cols - col) {
+            line.insert(col, ' ');
+        }
+
+        line.truncate(cols);
+    }
+
+    fn delete_chars(&mut self, count: usize) {
+        let (cols, col) = (self.cols, self.col);
+        let line = &mut self.lines[self.row];
+
+        line.drain(col..(col + count).min(cols));
+        line.resize(cols, ' ');
+    }
+
+    fn alternate_screen(&mut self, enable: bool) {
+        if enable && self.primary.is_none() {
+            let blank = vec![vec![' '; self.cols]; self.rows];
+            let lines = std::mem::replace(&mut self.lines, blank);
+            self.primary = Some((lines, (self.row, self.col)));
+        } else if !enable {
+            if let Some((lines, (row, col))) = self.primary.take() {
+                self.lines = lines;
+                self.row = row.min(self.rows - 1);
+                self.col = col.min(self.cols - 1);
+            }
+        }
+    }
+
+    fn reset(&mut self) {
+        let scrollback = std::mem::take(&mut self.scrollback);
+
+        *self = Self::new(self.rows, self.cols, self.scrollback_len);
+        self.scrollback = scrollback;
+    }
+}
+
+impl Perform for Grid {
+    fn print(&mut self, c: char) {
+        if self.pending_wrap {
+            self.col = 0;
+            self.pending_wrap = false;
+            self.linefeed();
+        }
+
+        self.lines[self.row][self.col] = c;
+
+        if self.col + 1 == self.cols {
+            self.pending_wrap = true;
+        } else {
+            self.col += 1;
+        }
+    }
+
+    fn execute(&mut self, byte: u8) {
+        match byte {
+            // Backspace
+            0x08 => {
+                self.col = self.col.saturating_sub(1);
+                self.pending_wrap = false;
+            }
+            // Horizontal tab
+            0x09 => self.col = ((self.col / 8 + 1) * 8).min(self.cols - 1),
+            // Line feed, vertical tab and form feed
+            0x0a..=0x0c => self.linefeed(),
+            // Carriage return
+            0x0d => {
+                self.col = 0;
+                self.pending_wrap = false;
+            }
+            _ => {}
+        }
+    }
+
+    fn csi_dispatch(&mut self, params: &Params, intermediates: &[u8], ignore: bool, action: char) {
+        if ignore {
+            return;
+        }
+
+        let values: Vec<u16> = params.iter().map(|param| param[0]).collect();
+        // Missing and zero parameters both mean the default
+        let arg = |index: usize, default: u16| -> usize {
+            usize::from(
+                values
+                    .get(index)
+                    .copied()
+                    .filter(|&value| value != 0)
+                    .unwrap_or(default),
+            )
+        };
+        let mode = values.first().copied().unwrap_or(0);
+
+        if action != 'm' {
+            self.pending_wrap = false;
+        }
+
+        match (intermediates, action) {
+            ([], 'A') => self.row = self.row.saturating_sub(arg(0, 1)),
+            ([], 'B' | 'e') => self.row = (self.row + arg(0, 1)).min(self.rows - 1),
+            ([], 'C' | 'a') => self.col = (self.col + arg(0, 1)).min(self.cols - 1),
+            ([], 'D') => self.col = self.col.saturating_sub(arg(0, 1)),
+            ([], 'E') => {
+                self.row = (self.row + arg(0, 1)).min(self.rows - 1);
+                self.col = 0;
+            }
+            ([], 'F') => {
+                self.row = self.row.saturating_sub(arg(0, 1));
+                self.col = 0;
+            }
+            ([], 'G' | '`') => self.col = (arg(0, 1) - 1).min(self.cols - 1),
+            ([], 'd') => self.row = (arg(0, 1) - 1).min(self.rows - 1),
+            ([], 'H' | 'f') => {
+                self.row = (arg(0, 1) - 1).min(self.rows - 1);
+                self.col = (arg(1, 1) - 1).min(self.cols - 1);
+            }
+            ([], 'J') => self.erase_display(mode),
+            ([], 'K') => self.erase_line(mode),
+            ([], 'L') => self.insert_lines(arg(0, 1)),
+            ([], 'M') => self.delete_lines(arg(0, 1)),
+            ([], '@') => self.insert_chars(arg(0, 1)),
+            ([], 'P') => self.delete_chars(arg(0, 1)),
+            ([], 'X') => {
+                let (row, col) = (self.row, self.col);
+                self.erase(row, col..col + arg(0, 1));
+            }
+            ([], 'S') => self.scroll_up(arg(0, 1)),
+            ([], 'T') => self.scroll_down(arg(0, 1)),
+            ([], 'r') => {
+                let top = arg(0, 1) - 1;
+                let bottom = arg(1, self.rows as u16).min(self.rows) - 1;
+
+                if top < bottom {
+                    self.top = top;
+                    self.bottom = bottom;
+                    self.row = 0;
+                    self.col = 0;
+                }
+            }
+            ([], 's') => self.saved = (self.row, self.col),
+            ([], 'u') => self.restore_cursor(),
+            ([b'?'], 'h' | 'l') => {
+                for value in &values {
+                    if matches!(value, 47 | 1047 | 1049) {
+                        if *value == 1049 && action == 'h' {
+                            self.saved = (self.row, self.col);
+                        }
+
+                        self.alternate_screen(action == 'h');
+
+                        if *value == 1049 && action == 'l' {
+                            self.restore_cursor();
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn esc_dispatch(&mut self, intermediates: &[u8], ignore: bool, byte: u8) {
+        if ignore || !intermediates.is_empty() {
+            return;
+        }
+
+        match byte {
+            b'7' => self.saved = (self.row, self.col),
+            b'8' => self.restore_cursor(),
+            b'D' => self.linefeed(),
+            b'E' => {
+                self.col = 0;
+                self.linefeed();
+            }
+            b'M' => self.reverse_index(),
+            b'c' => self.reset(),
+            _ => return,
+        }
+
+        self.pending_wrap = false;
+    }
+
+    fn osc_dispatch(&mut self, params: &[&[u8]], _bell_terminated: bool) {
+        if let [b"0" | b"2", title, ..] = params {
+            self.title = String::from_utf8_lossy(title).into_owned();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn screen(rows: u16, cols: u16, output: &str) -> Screen {
+        let mut screen = Screen::new(rows, cols);
+        screen.process(output.as_bytes());
+        screen
+    }
+
+    #[test]
+    fn test_prints_and_wraps() {
+        let screen = screen(3, 5, "hello world");
+
+        assert_eq!(screen.lines(), ["hello", " worl", "d"]);
+        assert_eq!(screen.scrollback(10), Vec::<String>::new());
+
+        let screen = self::screen(2, 5, "abcde\r\nfg\r\nhi");
+        assert_eq!(screen.text(), "fg\nhi");
+        assert_eq!(screen.scrollback(10), ["abcde"]);
+        assert_eq!(screen.cursor(), (1, 2));
+    }
+
+    #[test]
+    fn test_cursor_movement_and_erasing() {
+        let screen = screen(3, 10, "one\r\ntwo\r\nthree\x1b[2;2H\x1b[K\x1b[3;1H\x1b[2PX");
+
+        assert_eq!(screen.lines(), ["one", "t", "Xee"]);
+
+        // A prompt redrawn over itself, as shells do
+        let screen = self::screen(1, 20, "$ ls -la\r\x1b[K$ pwd");
+        assert_eq!(screen.text(), "$ pwd");
+
+        let screen = self::screen(3, 10, "a\r\nb\r\nc\x1b[H\x1b[2J");
+        assert_eq!(screen.text(), "");
+    }
+
+    #[test]
+    fn test_scroll_region_and_line_editing() {
+        let screen = screen(4, 10, "head\r\n1\r\n2\r\nfoot\x1b[2;3r\x1b[3;1H\n");
+
+        assert_eq!(screen.lines(), ["head", "2", "", "foot"]);
+        // Lines scrolled inside a region do not reach the scrollback
+        assert!(screen.scrollback(10).is_empty());
+
+        let screen = self::screen(3, 10, "a\r\nb\r\nc\x1b[2;1H\x1b[L");
+        assert_eq!(screen.lines(), ["a", "", "b"]);
+    }
+
+    #[test]
+    fn test_scrollback_is_bounded() {
+        let mut screen = Screen::with_scrollback(2, 10, 3);
+        for line in 0..10 {
+            screen.process(format!("{line}\r\n").as_bytes());
+        }
+
+        assert_eq!(screen.scrollback(10), ["6", "7", "8"]);
+        assert_eq!(screen.scrollback(1), ["8"]);
+        assert_eq!(screen.text(), "9");
+    }
+
+    #[test]
+    fn test_alternate_screen_restores_primary() {
+        let mut screen = screen(3, 20, "$ vim\r\n");
+        screen.process(b"\x1b[?1049h\x1b[H~ editing\x1b]0;vim\x07");
+
+        assert!(screen.is_alternate());
+        assert_eq!(screen.text(), "~ editing");
+        assert_eq!(screen.title(), "vim");
+
+        screen.process(b"\x1b[?1049l");
+        assert!(!screen.is_alternate());
+        assert_eq!(screen.text(), "$ vim");
+        assert_eq!(screen.cursor(), (1, 0));
+    }
+
+    #[test]
+    fn test_split_sequences() {
+        let mut screen = Screen::new(2, 10);
+        let output = "é\x1b[1;5H→".as_bytes();
+
+        for byte in output {
+            screen.process(&[*byte]);
+        }
+
+        assert_eq!(screen.text(), "é   →");
+    }
+
+    #[test]
+    fn test_resize_keeps_cursor_line() {
+        let mut screen = screen(4, 10, "1\r\n2\r\n3\r\n$ ");
+        screen.resize(2, 4);
+
+        assert_eq!(screen.lines(), ["3", "$"]);
+        assert_eq!(screen.cursor(), (1, 2));
+        assert_eq!(screen.scrollback(10), ["1", "2"]);
+    }
+
+    #[test]
+    fn test_restored_cursor_stays_on_a_shrunk_screen() {
+        let mut screen = screen(10, 40, "\x1b[9;30H\x1b7");
+        screen.resize(4, 10);
+
+        // The saved line moved up with the screen, and the column is clamped
+        screen.process(b"\x1b8x");
+        assert_eq!(screen.lines()[2], "         x");
+        assert_eq!(screen.cursor(), (2, 9));
+
+        screen.process(b"\x1b[H\x1b[u\x1b[?1049h\x1b[?1049l");
+        assert_eq!(screen.cursor(), (2, 9));
+    }
+}
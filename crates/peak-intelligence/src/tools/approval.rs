@@ -0,0 +1,361 @@
+//! Human-in-the-loop approval for protected tool calls.
+//!
+//! The [`ToolRegistry`](super::registry::ToolRegistry) decides which tools
+//! need approval. Calls to them wait in an [`Approvals`] queue until someone
+//! approves or denies them; "always allow" also approves every later call of
+//! the same tool for the rest of the session. Subscribers get an [`Event`]
+//! whenever the queue changes, which the MCP server forwards to its clients
+//! so remote agents can follow their requests.
+use chrono::{DateTime, Utc};
+use futures::channel::{mpsc, oneshot};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, HashSet};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::task::{Context, Poll};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Decision {
+    Approve,
+    Deny,
+    /// Approves this call and every later call of the same tool.
+    AlwaysAllow,
+}
+
+impl Decision {
+    pub fn is_approved(self) -> bool {
+        self != Decision::Deny
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ApprovalRequest {
+    pub id: u64,
+    pub tool: String,
+    pub args: Value,
+    /// What the call does, in words, for whoever decides.
+    pub reason: String,
+    /// Who made the call, e.g. an MCP client's name.
+    pub requester: String,
+    pub requested_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum Event {
+    Requested(ApprovalRequest),
+    Resolved {
+        id: u64,
+        decision: Decision,
+    },
+    /// The caller stopped waiting, e.g. because its request was cancelled.
+    Withdrawn {
+        id: u64,
+    },
+}
+
+/// Why a protected call did not run, or a decision was rejected. Like
+/// policy violations, these are reported to agents with their `kind`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, thiserror::Error)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ApprovalError {
+    #[error("the user denied the call to '{tool}'")]
+    Denied { tool: String },
+    /// Nobody can decide on the call, or nobody did in time.
+    #[error("nobody approved the call to '{tool}' in time")]
+    Undecided { tool: String },
+    #[error("there is no pending approval request #{id}")]
+    UnknownRequest { id: u64 },
+}
+
+impl ApprovalError {
+    /// The error as JSON, with its message under `message`.
+    pub fn to_json(&self) -> Value {
+        let mut value = json!(self);
+        value["message"] = json!(self.to_string());
+        value
+    }
+}
+
+/// A queue of calls waiting for a decision. Clones share the same queue.
+#[derive(Clone, Default)]
+pub struct Approvals {
+    state: Arc<Mutex<State>>,
+}
+
+#[derive(Default)]
+struct State {
+    next_id: u64,
+    pending: BTreeMap<u64, Pending>,
+    /// Tools allowed for the rest of the session.
+    allowed: HashSet<String>,
+    subscribers: Vec<mpsc::UnboundedSender<Event>>,
+}
+
+struct Pending {
+    request: ApprovalRequest,
+    reply: oneshot::Sender<Decision>,
+}
+
+impl State {
+    fn emit(&mut self, event: Event) {
+        self.subscribers
+            .retain(|subscriber| subscriber.unbounded_send(event.clone()).is_ok());
+    }
+
+    fn resolve(&mut self, id: u64, decision: Decision) -> bool {
+        let Some(pending) = self.pending.remove(&id) else {
+            return false;
+        };
+
+        let _ = pending.reply.send(decision);
+        self.emit(Event::Resolved { id, decision });
+        true
+    }
+}
+
+impl Approvals {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Queues a call for approval. The request is pending as soon as this
+    /// returns; awaiting the [`Ticket`] yields the decision, and dropping it
+    /// withdraws the request.
+    ///
+    /// Tools allowed for the session are approved without queueing.
+    pub fn request(
+        &self,
+        tool: impl Into<String>,
+        args: Value,
+        reason: impl Into<String>,
+        requester: impl Into<String>,
+    ) -> Ticket {
+        let tool = tool.into();
+        let mut state = self.state();
+
+        if state.allowed.contains(&tool) {
+            return Ticket {
+                id: None,
+                reply: None,
+                approvals: self.clone(),
+            };
+        }
+
+        state.next_id += 1;
+        let (reply, receiver) = oneshot::channel();
+        let request = ApprovalRequest {
+            id: state.next_id,
+            tool,
+            args,
+            reason: reason.into(),
+            requester: requester.into(),
+            requested_at: Utc::now(),
+        };
+
+        state.emit(Event::Requested(request.clone()));
+        state.pending.insert(request.id, Pending { request, reply });
+
+        Ticket {
+            id: Some(state.next_id),
+            reply: Some(receiver),
+            approvals: self.clone(),
+        }
+    }
+
+    /// Settles a pending request. [`Decision::AlwaysAllow`] also approves
+    /// the other pending calls of the same tool.
+    pub fn decide(&self, id: u64, decision: Decision) -> Result<(), ApprovalError> {
+        let mut state = self.state();
+
+        let tool = match state.pending.get(&id) {
+            Some(pending) => pending.request.tool.clone(),
+            None => return Err(ApprovalError::UnknownRequest { id }),
+        };
+
+        state.resolve(id, decision);
+
+        if decision == Decision::AlwaysAllow {
+            let same_tool: Vec<u64> = state
+                .pending
+                .values()
+                .filter(|pending| pending.request.tool == tool)
+                .map(|pending| pending.request.id)
+                .collect();
+
+            for id in same_tool {
+                state.resolve(id, Decision::Approve);
+            }
+
+            state.allowed.insert(tool);
+        }
+
+        Ok(())
+    }
+
+    /// Pending requests, oldest first.
+    pub fn pending(&self) -> Vec<ApprovalRequest> {
+        self.state()
+            .pending
+            .values()
+            .map(|pending| pending.request.clone())
+            .collect()
+    }
+
+    pub fn is_always_allowed(&self, tool: &str) -> bool {
+        self.state().allowed.contains(tool)
+    }
+
+    /// Asks again for tools that were always allowed.
+    pub fn reset_session(&self) {
+        self.state().allowed.clear();
+    }
+
+    /// Receives every later change to the queue.
+    pub fn subscribe(&self) -> mpsc::UnboundedReceiver<Event> {
+        let (sender, receiver) = mpsc::unbounded();
+        self.state().subscribers.push(sender);
+        receiver
+    }
+
+    fn state(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl std::fmt::Debug for Approvals {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let state = self.state();
+        f.debug_struct("Approvals")
+            .field("pending", &state.pending.len())
+            .field("allowed", &state.allowed)
+            .finish()
+    }
+}
+
+/// A queued call, resolving to its [`Decision`].
+pub struct Ticket {
+    id: Option<u64>,
+    reply: Option<oneshot::Receiver<Decision>>,
+    approvals: Approvals,
+}
+
+impl Ticket {
+    /// The pending request, or `None` when the tool was already allowed.
+    pub fn id(&self) -> Option<u64> {
+        self.id
+    }
+}
+
+impl Future for Ticket {
+    type Output = Decision;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Decision> {
+        let Some(reply) = self.reply.as_mut() else {
+            return Poll::Ready(Decision::Approve);
+        };
+
+        // A request dropped from the queue without a decision is denied
+        let decision = std::task::ready!(Pin::new(reply).poll(cx)).unwrap_or(Decision::Deny);
+        self.reply = None;
+        self.id = None;
+
+        Poll::Ready(decision)
+    }
+}
+
+impl Drop for Ticket {
+    fn drop(&mut self) {
+        let Some(id) = self.id else {
+            return;
+        };
+
+        let mut state = self.approvals.state();
+        if state.pending.remove(&id).is_some() {
+            state.emit(Event::Withdrawn { id });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::StreamExt;
+
+    #[tokio::test]
+    async fn test_requests_wait_for_a_decision() {
+        let approvals = Approvals::new();
+        let mut events = approvals.subscribe();
+
+        let ticket = approvals.request(
+            "run_command",
+            json!({ "argv": ["ls"] }),
+            "Run `ls`",
+            "agent",
+        );
+        let id = ticket.id().unwrap();
+
+        let pending = approvals.pending();
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].reason, "Run `ls`");
+        assert_eq!(pending[0].requester, "agent");
+        assert!(matches!(events.next().await, Some(Event::Requested(r)) if r.id == id));
+
+        approvals.decide(id, Decision::Deny).unwrap();
+        assert_eq!(ticket.await, Decision::Deny);
+        assert_eq!(
+            events.next().await,
+            Some(Event::Resolved {
+                id,
+                decision: Decision::Deny
+            })
+        );
+        assert!(approvals.pending().is_empty());
+
+        assert_eq!(
+            approvals.decide(id, Decision::Approve),
+            Err(ApprovalError::UnknownRequest { id })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_always_allow_covers_the_session() {
+        let approvals = Approvals::new();
+
+        let first = approvals.request("kill_process", json!({}), "Kill 1", "a");
+        let second = approvals.request("kill_process", json!({}), "Kill 2", "b");
+        let other = approvals.request("run_command", json!({}), "Run", "a");
+
+        approvals
+            .decide(first.id().unwrap(), Decision::AlwaysAllow)
+            .unwrap();
+        assert_eq!(first.await, Decision::AlwaysAllow);
+        assert_eq!(second.await, Decision::Approve);
+        assert_eq!(approvals.pending().len(), 1);
+
+        let later = approvals.request("kill_process", json!({}), "Kill 3", "a");
+        assert_eq!(later.id(), None);
+        assert_eq!(later.await, Decision::Approve);
+
+        approvals.reset_session();
+        assert!(!approvals.is_always_allowed("kill_process"));
+        drop(other);
+    }
+
+    #[tokio::test]
+    async fn test_dropped_tickets_are_withdrawn() {
+        let approvals = Approvals::new();
+        let mut events = approvals.subscribe();
+
+        let ticket = approvals.request("run_command", json!({}), "Run", "agent");
+        let id = ticket.id().unwrap();
+        drop(ticket);
+
+        assert!(approvals.pending().is_empty());
+        assert!(matches!(events.next().await, Some(Event::Requested(_))));
+        assert_eq!(events.next().await, Some(Event::Withdrawn { id }));
+    }
+}
@@ -0,0 +1,325 @@
+//! Non-interactive command execution.
+//!
+//! Commands are spawned from an argv, never through a shell, so arguments
+//! reach the program verbatim. Output is captured up to a per-stream cap
+//! and the process is killed when it outlives its timeout; either way the
+//! caller gets the exit status instead of having to scrape a terminal.
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::process::{ExitStatus, Stdio};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWriteExt};
+use tokio::process::Command;
+use tokio::task::JoinHandle;
+
+pub const DEFAULT_TIMEOUT_MS: u64 = 60_000;
+pub const MAX_TIMEOUT_MS: u64 = 600_000;
+
+/// Bytes kept per stream unless the request asks for less (or more, up to
+/// [`MAX_OUTPUT_BYTES`]).
+pub const DEFAULT_OUTPUT_BYTES: usize = 64 * 1024;
+pub const MAX_OUTPUT_BYTES: usize = 1024 * 1024;
+
+/// How long output is still collected after a timed-out process was
+/// killed. Only the process itself is killed; grandchildren that keep the
+/// pipes open are not waited for.
+const KILL_GRACE: Duration = Duration::from_secs(1);
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct Request {
+    /// Program followed by its arguments.
+    pub argv: Vec<String>,
+    /// Working directory; `~` expands to the home directory.
+    #[serde(default)]
+    pub cwd: Option<String>,
+    /// Variables set on top of the inherited environment.
+    #[serde(default)]
+    pub env: BTreeMap<String, String>,
+    /// Starts from an empty environment instead of inheriting ours.
+    #[serde(default)]
+    pub clear_env: bool,
+    /// Written to the process, after which stdin is closed. Without it the
+    /// process reads from `/dev/null`.
+    #[serde(default)]
+    pub stdin: Option<String>,
+    #[serde(default)]
+    pub timeout_ms: Option<u64>,
+    #[serde(default)]
+    pub max_output_bytes: Option<usize>,
+}
+
+impl Request {
+    pub fn new(argv: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        Self {
+            argv: argv.into_iter().map(Into::into).collect(),
+            ..Self::default()
+        }
+    }
+
+    /// The argv as one line, quoting arguments a shell would split.
+    pub fn command_line(&self) -> String {
+        super::command_line(&self.argv)
+    }
+
+    fn timeout(&self) -> Duration {
+        Duration::from_millis(
+            self.timeout_ms
+                .unwrap_or(DEFAULT_TIMEOUT_MS)
+                .clamp(1, MAX_TIMEOUT_MS),
+        )
+    }
+
+    fn output_limit(&self) -> usize {
+        self.max_output_bytes
+            .unwrap_or(DEFAULT_OUTPUT_BYTES)
+            .min(MAX_OUTPUT_BYTES)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Output {
+    /// `None` when the process was killed by a signal, including on timeout.
+    pub exit_code: Option<i32>,
+    pub signal: Option<i32>,
+    pub success: bool,
+    pub timed_out: bool,
+    pub stdout: String,
+    pub stderr: String,
+    /// Set when a stream produced more than the output cap.
+    pub stdout_truncated: bool,
+    pub stderr_truncated: bool,
+    pub duration_ms: u64,
+}
+
+/// Runs `request` to completion or until its timeout.
+///
+/// Failing to start the program is an error; a non-zero exit is not, and
+/// is reported through [`Output::exit_code`].
+pub async fn run(request: &Request) -> Result<Output> {
+    let (program, args) = request
+        .argv
+        .split_first()
+        .filter(|(program, _)| !program.is_empty())
+        .ok_or_else(|| anyhow::anyhow!("argv must name a program"))?;
+
+    let mut command = Command::new(program);
+    command
+        .args(args)
+        .stdin(if request.stdin.is_some() {
+            Stdio::piped()
+        } else {
+            Stdio::null()
+        })
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+
+    if request.clear_env {
+        command.env_clear();
+    }
+    command.envs(&request.env);
+
+    if let Some(cwd) = &request.cwd {
+        let cwd = super::resolve_path(cwd);
+        if !cwd.is_dir() {
+            anyhow::bail!("Working directory {} does not exist", cwd.display());
+        }
+        command.current_dir(cwd);
+    }
+
+    let started = Instant::now();
+    let mut child = command
+        .spawn()
+        .with_context(|| format!("Failed to start `{}`", program))?;
+
+    let limit = request.output_limit();
+    let stdout = capture(child.stdout.take(), limit);
+    let stderr = capture(child.stderr.take(), limit);
+
+    if let (Some(mut pipe), Some(input)) = (child.stdin.take(), request.stdin.clone()) {
+        // A process that exits without reading its input is not an error.
+        tokio::spawn(async move {
+            let _ = pipe.write_all(input.as_bytes()).await;
+        });
+    }
+
+    let (status, timed_out) = match tokio::time::timeout(request.timeout(), child.wait()).await {
+        Ok(status) => (status?, false),
+        Err(_) => {
+            child.kill().await.ok();
+            (child.wait().await?, true)
+        }
+    };
+
+    let grace = if timed_out { Some(KILL_GRACE) } else { None };
+    let (stdout, stdout_truncated) = collect(stdout, grace).await;
+    let (stderr, stderr_truncated) = collect(stderr, grace).await;
+
+    Ok(Output {
+        exit_code: status.code(),
+        signal: signal(&status),
+        success: status.success() && !timed_out,
+        timed_out,
+        stdout,
+        stderr,
+        stdout_truncated,
+        stderr_truncated,
+        duration_ms: started.elapsed().as_millis() as u64,
+    })
+}
+
+#[derive(Default)]
+struct Captured {
+    bytes: Vec<u8>,
+    truncated: bool,
+}
+
+/// Reads a stream to its end, keeping the first `limit` bytes. The rest is
+/// drained so a chatty process never blocks on a full pipe.
+fn capture(
+    stream: Option<impl AsyncRead + Unpin + Send + 'static>,
+    limit: usize,
+) -> (JoinHandle<()>, Arc<Mutex<Captured>>) {
+    let captured = Arc::new(Mutex::new(Captured::default()));
+    let sink = captured.clone();
+
+    let reader = tokio::spawn(async move {
+        let Some(mut stream) = stream else {
+            return;
+        };
+
+        let mut buf = [0u8; 8192];
+        while let Ok(n) = stream.read(&mut buf).await {
+            if n == 0 {
+                break;
+            }
+            let mut captured = sink.lock().unwrap_or_else(PoisonError::into_inner);
+            let room = limit.saturating_sub(captured.bytes.len());
+            captured.bytes.extend_from_slice(&buf[..n.min(room)]);
+            captured.truncated |= n > room;
+        }
+    });
+
+    (reader, captured)
+}
+
+/// Waits for a stream to close, or at most `grace`, and returns what was
+/// read so far.
+async fn collect(
+    (mut reader, captured): (JoinHandle<()>, Arc<Mutex<Captured>>),
+    grace: Option<Duration>,
+) -> (String, bool) {
+    match grace {
+        None => {
+            let _ = (&mut reader).await;
+        }
+        Some(grace) => {
+            if tokio::time::timeout(grace, &mut reader).await.is_err() {
+                reader.abort();
+            }
+        }
+    }
+
+    let captured = captured.lock().unwrap_or_else(PoisonError::into_inner);
+    (
+        String::from_utf8_lossy(&captured.bytes).into_owned(),
+        captured.truncated,
+    )
+}
+
+#[cfg(unix)]
+fn signal(status: &ExitStatus) -> Option<i32> {
+    std::os::unix::process::ExitStatusExt::signal(status)
+}
+
+#[cfg(not(unix))]
+fn signal(_status: &ExitStatus) -> Option<i32> {
+    None
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_captures_output_and_exit_code() -> Result<()> {
+        let mut request = Request::new(["sh", "-c", "echo out; echo err >&2; exit 3"]);
+        request.env.insert("UNUSED".into(), "1".into());
+
+        let output = run(&request).await?;
+        assert_eq!(output.stdout, "out\n");
+        assert_eq!(output.stderr, "err\n");
+        assert_eq!(output.exit_code, Some(3));
+        assert!(!output.success);
+        assert!(!output.timed_out);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_argv_env_cwd_and_stdin() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let mut request = Request::new(["sh", "-c", "pwd; echo \"$GREETING\"; cat"]);
+        request.cwd = Some(dir.path().to_string_lossy().into_owned());
+        request.env.insert("GREETING".into(), "hi there".into());
+        request.stdin = Some("from stdin".into());
+
+        let output = run(&request).await?;
+        let cwd = dir.path().canonicalize()?;
+        assert_eq!(
+            output.stdout,
+            format!("{}\nhi there\nfrom stdin", cwd.display())
+        );
+        assert!(output.success);
+
+        // Arguments are passed verbatim, without shell expansion.
+        let output = run(&Request::new(["echo", "$HOME", "*"])).await?;
+        assert_eq!(output.stdout, "$HOME *\n");
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_timeout_kills_the_process() -> Result<()> {
+        let mut request = Request::new(["sh", "-c", "echo started; sleep 30"]);
+        request.timeout_ms = Some(200);
+
+        let output = run(&request).await?;
+        assert!(output.timed_out);
+        assert!(!output.success);
+        assert_eq!(output.exit_code, None);
+        assert_eq!(output.signal, Some(9));
+        assert_eq!(output.stdout, "started\n");
+        assert!(output.duration_ms < 10_000);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_output_is_capped() -> Result<()> {
+        let mut request = Request::new(["sh", "-c", "head -c 100000 /dev/zero | tr '\\0' x"]);
+        request.max_output_bytes = Some(10);
+
+        let output = run(&request).await?;
+        assert_eq!(output.stdout, "x".repeat(10));
+        assert!(output.stdout_truncated);
+        assert!(!output.stderr_truncated);
+        assert!(output.success);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_missing_program_is_an_error() {
+        assert!(run(&Request::new(["definitely-not-a-program"]))
+            .await
+            .is_err());
+        assert!(run(&Request::new(Vec::<String>::new())).await.is_err());
+
+        let mut request = Request::new(["true"]);
+        request.cwd = Some("/definitely/not/a/dir".into());
+        assert!(run(&request).await.is_err());
+    }
+}
@@ -0,0 +1,709 @@
+//! Patch-based file edits.
+//!
+//! Edits are planned in memory first: search/replace hunks and unified
+//! diffs must apply cleanly, and a dry run only returns the resulting diff.
+//! Applied edits are recorded in a [`Journal`] before any file changes, and
+//! every file is replaced atomically through a temporary file and a rename,
+//! so an interrupted edit never leaves a half-written file behind.
+use super::fs_policy::{Access, Policy};
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::io::{ErrorKind, Write};
+use std::path::{Path, PathBuf};
+
+/// How many applied edits the journal keeps for undo.
+const JOURNAL_LEN: usize = 50;
+
+const DEV_NULL: &str = "/dev/null";
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct Replacement {
+    pub search: String,
+    pub replace: String,
+    /// Replaces every occurrence instead of requiring exactly one.
+    #[serde(default)]
+    pub all: bool,
+}
+
+/// Why an edit could not be planned or undone. Like policy violations,
+/// these are reported to agents with their `kind`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, thiserror::Error)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum EditError {
+    #[error("edit #{index}: search text not found in {}", path.display())]
+    NotFound { path: PathBuf, index: usize },
+    #[error(
+        "edit #{index}: search text occurs {count} times in {}; add context or set `all`",
+        path.display()
+    )]
+    Ambiguous {
+        path: PathBuf,
+        index: usize,
+        count: usize,
+    },
+    #[error("{}: {message}", path.display())]
+    HunkFailed { path: PathBuf, message: String },
+    #[error("invalid patch: {message}")]
+    InvalidPatch { message: String },
+    #[error("{} changed after edit {id}", path.display())]
+    Conflict { path: PathBuf, id: String },
+    #[error("there is no edit to undo")]
+    NothingToUndo,
+}
+
+impl EditError {
+    /// The error as JSON, with its message under `message`.
+    pub fn to_json(&self) -> Value {
+        let mut value = json!(self);
+        value["message"] = json!(self.to_string());
+        value
+    }
+
+    fn invalid(message: impl Into<String>) -> anyhow::Error {
+        Self::InvalidPatch {
+            message: message.into(),
+        }
+        .into()
+    }
+}
+
+/// A planned change to one file. `None` stands for a missing file, so a
+/// change can create or delete it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Change {
+    pub path: PathBuf,
+    pub before: Option<String>,
+    pub after: Option<String>,
+}
+
+impl Change {
+    /// Unified diff of the change. It applies back through [`plan_patch`].
+    pub fn diff(&self) -> String {
+        let name = |content: &Option<String>| match content {
+            Some(_) => self.path.to_string_lossy().into_owned(),
+            None => DEV_NULL.to_owned(),
+        };
+
+        diffy::DiffOptions::new()
+            .set_original_filename(name(&self.before))
+            .set_modified_filename(name(&self.after))
+            .create_patch(
+                self.before.as_deref().unwrap_or_default(),
+                self.after.as_deref().unwrap_or_default(),
+            )
+            .to_string()
+    }
+
+    fn is_noop(&self) -> bool {
+        self.before == self.after
+    }
+}
+
+/// Applies `replacements` to the file at `path`, in order. Each search text
+/// must occur exactly once, unless the replacement sets `all`.
+pub fn plan_replacements(
+    policy: &Policy,
+    path: &str,
+    replacements: &[Replacement],
+) -> Result<Change> {
+    let resolved = policy.authorize(path, Access::ReadWrite)?;
+    let before = policy.read_text(&resolved)?;
+    let mut after = before.clone();
+
+    for (index, replacement) in replacements.iter().enumerate() {
+        let index = index + 1;
+
+        if replacement.search.is_empty() {
+            return Err(EditError::invalid(format!(
+                "edit #{index} has no search text"
+            )));
+        }
+
+        match after.matches(&replacement.search).count() {
+            0 => {
+                return Err(EditError::NotFound {
+                    path: resolved,
+                    index,
+                }
+                .into())
+            }
+            count if count > 1 && !replacement.all => {
+                return Err(EditError::Ambiguous {
+                    path: resolved,
+                    index,
+                    count,
+                }
+                .into())
+            }
+            _ => after = after.replace(&replacement.search, &replacement.replace),
+        }
+    }
+
+    policy.check_size(&resolved, after.len() as u64)?;
+
+    Ok(Change {
+        path: resolved,
+        before: Some(before),
+        after: Some(after),
+    })
+}
+
+/// Applies a unified diff touching one or more files. File names come from
+/// the `---`/`+++` headers (git's `a/` and `b/` prefixes are dropped);
+/// `path` names the file of a diff without headers.
+pub fn plan_patch(policy: &Policy, patch: &str, path: Option<&str>) -> Result<Vec<Change>> {
+    let sections = split_patch(patch);
+
+    if sections.is_empty() {
+        return Err(EditError::invalid("no hunks found"));
+    }
+
+    let mut changes: Vec<Change> = Vec::new();
+
+    for section in sections {
+        let parsed =
+            diffy::Patch::from_str(section).map_err(|e| EditError::invalid(e.to_string()))?;
+
+        let original = parsed.original().map(file_name);
+        let modified = parsed.modified().map(file_name);
+
+        let name = match (original, modified) {
+            (_, Some(Some(name))) | (Some(Some(name)), _) => name,
+            (None, None) => path.ok_or_else(|| EditError::invalid("missing file name"))?,
+            _ => return Err(EditError::invalid("missing file name")),
+        };
+
+        let resolved = policy.authorize(name, Access::ReadWrite)?;
+
+        if changes.iter().any(|change| change.path == resolved) {
+            return Err(EditError::invalid(format!(
+                "{} is patched more than once",
+                resolved.display()
+            )));
+        }
+
+        let before = match original {
+            Some(None) if resolved.exists() => {
+                return Err(EditError::HunkFailed {
+                    path: resolved,
+                    message: "the patch creates a file that already exists".to_owned(),
+                }
+                .into())
+            }
+            Some(None) => None,
+            _ => Some(policy.read_text(&resolved)?),
+        };
+
+        let patched =
+            diffy::apply(before.as_deref().unwrap_or_default(), &parsed).map_err(|e| {
+                EditError::HunkFailed {
+                    path: resolved.clone(),
+                    message: e.to_string(),
+                }
+            })?;
+
+        let after = match modified {
+            Some(None) if !patched.is_empty() => {
+                return Err(EditError::HunkFailed {
+                    path: resolved,
+                    message: "the deletion does not cover the whole file".to_owned(),
+                }
+                .into())
+            }
+            Some(None) => None,
+            _ => {
+                policy.check_size(&resolved, patched.len() as u64)?;
+                Some(patched)
+            }
+        };
+
+        changes.push(Change {
+            path: resolved,
+            before,
+            after,
+        });
+    }
+
+    Ok(changes)
+}
+
+/// Previews `changes` on a dry run; otherwise applies them through
+/// `journal`. Either way, the combined diff is returned.
+pub fn execute(journal: &Journal, changes: Vec<Change>, dry_run: bool) -> Result<Value> {
+    let changes: Vec<Change> = changes.into_iter().filter(|c| !c.is_noop()).collect();
+    let diff: String = changes.iter().map(Change::diff).collect();
+    let files: Vec<_> = changes.iter().map(|c| c.path.to_string_lossy()).collect();
+
+    let entry = if dry_run || changes.is_empty() {
+        None
+    } else {
+        Some(journal.apply(&changes)?.id)
+    };
+
+    Ok(json!({
+        "dry_run": dry_run,
+        "files": files,
+        "diff": diff,
+        "edit_id": entry,
+    }))
+}
+
+/// One applied edit, with what is needed to revert it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Entry {
+    pub id: String,
+    pub timestamp: DateTime<Utc>,
+    pub files: Vec<Recorded>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Recorded {
+    pub path: PathBuf,
+    pub before: Option<String>,
+    /// Lets undo detect files changed since the edit.
+    pub after_sha256: Option<String>,
+}
+
+/// Undo journal of applied edits, one JSON file per edit.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Journal {
+    directory: PathBuf,
+}
+
+impl Default for Journal {
+    /// The journal under `~/.peak/intelligence/edits`.
+    fn default() -> Self {
+        Self::new(crate::brain::directory::data().join("edits"))
+    }
+}
+
+impl Journal {
+    pub fn new(directory: impl Into<PathBuf>) -> Self {
+        Self {
+            directory: directory.into(),
+        }
+    }
+
+    /// Records `changes` and then applies them. When a file cannot be
+    /// written, the files already written are restored.
+    pub fn apply(&self, changes: &[Change]) -> Result<Entry> {
+        let timestamp = Utc::now();
+        let entry = Entry {
+            // Sorts chronologically, which is how the latest edit is found
+            id: format!(
+                "{}-{}",
+                timestamp.format("%Y%m%d%H%M%S%3f"),
+                &uuid::Uuid::new_v4().simple().to_string()[..8]
+            ),
+            timestamp,
+            files: changes
+                .iter()
+                .map(|change| Recorded {
+                    path: change.path.clone(),
+                    before: change.before.clone(),
+                    after_sha256: change.after.as_deref().map(sha256),
+                })
+                .collect(),
+        };
+
+        let record = self.path(&entry.id);
+        write_atomic(&record, &serde_json::to_string_pretty(&entry)?)
+            .context("Failed to record the edit")?;
+
+        for (applied, change) in changes.iter().enumerate() {
+            if let Err(error) = replace(&change.path, change.after.as_deref()) {
+                for change in &changes[..applied] {
+                    if let Err(e) = replace(&change.path, change.before.as_deref()) {
+                        log::error!("Failed to restore {}: {}", change.path.display(), e);
+                    }
+                }
+
+                let _ = fs::remove_file(&record);
+
+                return Err(error)
+                    .with_context(|| format!("Failed to write {}", change.path.display()));
+            }
+        }
+
+        self.prune();
+
+        Ok(entry)
+    }
+
+    /// Reverts the edit `id`, or the latest one. Refuses when any of its
+    /// files changed since.
+    pub fn undo(&self, policy: &Policy, id: Option<&str>) -> Result<Entry> {
+        let record = match id {
+            Some(id) if id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') => self.path(id),
+            Some(_) => return Err(EditError::NothingToUndo.into()),
+            None => self.records()?.pop().ok_or(EditError::NothingToUndo)?,
+        };
+
+        let entry: Entry = match fs::read_to_string(&record) {
+            Ok(entry) => serde_json::from_str(&entry)?,
+            Err(e) if e.kind() == ErrorKind::NotFound => {
+                return Err(EditError::NothingToUndo.into())
+            }
+            Err(e) => return Err(e.into()),
+        };
+
+        for file in &entry.files {
+            policy.authorize(&file.path.to_string_lossy(), Access::ReadWrite)?;
+
+            let current = match fs::read(&file.path) {
+                Ok(content) => Some(sha256(&content)),
+                Err(e) if e.kind() == ErrorKind::NotFound => None,
+                Err(e) => return Err(e.into()),
+            };
+
+            if current != file.after_sha256 {
+                return Err(EditError::Conflict {
+                    path: file.path.clone(),
+                    id: entry.id.clone(),
+                }
+                .into());
+            }
+        }
+
+        for file in &entry.files {
+            replace(&file.path, file.before.as_deref())
+                .with_context(|| format!("Failed to restore {}", file.path.display()))?;
+        }
+
+        fs::remove_file(record)?;
+
+        Ok(entry)
+    }
+
+    fn path(&self, id: &str) -> PathBuf {
+        self.directory.join(format!("{id}.json"))
+    }
+
+    /// Journal files, oldest first.
+    fn records(&self) -> Result<Vec<PathBuf>> {
+        let list = match fs::read_dir(&self.directory) {
+            Ok(list) => list,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e.into()),
+        };
+
+        let mut records: Vec<PathBuf> = list
+            .filter_map(|entry| Some(entry.ok()?.path()))
+            .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+            .collect();
+
+        records.sort();
+
+        Ok(records)
+    }
+
+    fn prune(&self) {
+        let Ok(records) = self.records() else {
+            return;
+        };
+
+        let excess = records.len().saturating_sub(JOURNAL_LEN);
+
+        for record in &records[..excess] {
+            let _ = fs::remove_file(record);
+        }
+    }
+}
+
+/// Splits a patch into single-file sections, which is what `diffy` parses.
+/// Hunk headers are followed so removed lines starting with `--` are not
+/// mistaken for file headers.
+fn split_patch(patch: &str) -> Vec<&str> {
+    let mut sections = Vec::new();
+    let mut start = None;
+    let mut pending: (usize, usize) = (0, 0);
+    let mut offset = 0;
+
+    for line in patch.split_inclusive('\n') {
+        if pending != (0, 0) {
+            let (old, new) = &mut pending;
+
+            match line.as_bytes().first() {
+                Some(b'-') => *old = old.saturating_sub(1),
+                Some(b'+') => *new = new.saturating_sub(1),
+                Some(b'\\') => {}
+                _ => {
+                    *old = old.saturating_sub(1);
+                    *new = new.saturating_sub(1);
+                }
+            }
+        } else if line.starts_with("--- ") {
+            if let Some(start) = start {
+                sections.push(&patch[start..offset]);
+            }
+
+            start = Some(offset);
+        } else if line.starts_with("@@ ") {
+            start.get_or_insert(offset);
+            pending = hunk_lengths(line).unwrap_or_default();
+        } else if !line.starts_with("+++ ") && !line.starts_with('\\') {
+            // Anything else (`diff --git`, `index`, ...) ends a section
+            if let Some(start) = start.take() {
+                sections.push(&patch[start..offset]);
+            }
+        }
+
+        offset += line.len();
+    }
+
+    if let Some(start) = start {
+        sections.push(&patch[start..]);
+    }
+
+    sections
+}
+
+/// Old and new line counts of a `@@ -a,b +c,d @@` header.
+fn hunk_lengths(header: &str) -> Option<(usize, usize)> {
+    let ranges = header.strip_prefix("@@ ")?.split(" @@").next()?;
+    let (old, new) = ranges.split_once(' ')?;
+    let length = |range: &str| match range.split_once(',') {
+        Some((_, length)) => length.trim().parse().ok(),
+        None => Some(1),
+    };
+
+    Some((
+        length(old.strip_prefix('-')?)?,
+        length(new.strip_prefix('+')?)?,
+    ))
+}
+
+/// A header file name, or `None` for `/dev/null`.
+fn file_name(name: &str) -> Option<&str> {
+    if name == DEV_NULL {
+        return None;
+    }
+
+    Some(
+        name.strip_prefix("a/")
+            .or_else(|| name.strip_prefix("b/"))
+            .unwrap_or(name),
+    )
+}
+
+/// Writes `content` to `path`, or deletes it for `None`.
+fn replace(path: &Path, content: Option<&str>) -> std::io::Result<()> {
+    match content {
+        Some(content) => write_atomic(path, content),
+        None => match fs::remove_file(path) {
+            Err(e) if e.kind() != ErrorKind::NotFound => Err(e),
+            _ => Ok(()),
+        },
+    }
+}
+
+/// Writes a sibling temporary file and renames it over `path`, keeping the
+/// permissions of the file it replaces.
+fn write_atomic(path: &Path, content: &str) -> std::io::Result<()> {
+    let parent = path.parent().unwrap_or(Path::new("."));
+    fs::create_dir_all(parent)?;
+
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    let temporary = parent.join(format!(".{}.{}.tmp", name, uuid::Uuid::new_v4().simple()));
+
+    let result = (|| {
+        let mut file = fs::File::create(&temporary)?;
+        file.write_all(content.as_bytes())?;
+        file.sync_all()?;
+
+        if let Ok(metadata) = fs::metadata(path) {
+            fs::set_permissions(&temporary, metadata.permissions())?;
+        }
+
+        fs::rename(&temporary, path)
+    })();
+
+    if result.is_err() {
+        let _ = fs::remove_file(&temporary);
+    }
+
+    result
+}
+
+fn sha256(content: impl AsRef<[u8]>) -> String {
+    format!("{:x}", Sha256::digest(content.as_ref()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn edit_error(result: Result<impl std::fmt::Debug>) -> EditError {
+        result
+            .unwrap_err()
+            .downcast::<EditError>()
+            .expect("an edit error")
+    }
+
+    fn replacement(search: &str, replace: &str) -> Replacement {
+        Replacement {
+            search: search.to_owned(),
+            replace: replace.to_owned(),
+            all: false,
+        }
+    }
+
+    #[test]
+    fn test_replacements_must_match_once() -> Result<()> {
+        let dir = tempdir()?;
+        let policy = Policy::new().with_root(dir.path(), Access::ReadWrite);
+        let file = dir.path().join("config.toml");
+        fs::write(&file, "port = 80\nhost = \"a\"\nbackup = \"a\"\n")?;
+        let path = file.to_str().unwrap();
+
+        let change = plan_replacements(&policy, path, &[replacement("port = 80", "port = 8080")])?;
+        assert_eq!(
+            change.after.as_deref(),
+            Some("port = 8080\nhost = \"a\"\nbackup = \"a\"\n")
+        );
+        assert!(change.diff().contains("-port = 80\n+port = 8080\n"));
+
+        assert!(matches!(
+            edit_error(plan_replacements(
+                &policy,
+                path,
+                &[replacement("missing", "")]
+            )),
+            EditError::NotFound { index: 1, .. }
+        ));
+        assert!(matches!(
+            edit_error(plan_replacements(
+                &policy,
+                path,
+                &[replacement("\"a\"", "\"b\"")]
+            )),
+            EditError::Ambiguous { count: 2, .. }
+        ));
+
+        let all = Replacement {
+            all: true,
+            ..replacement("\"a\"", "\"b\"")
+        };
+        let change = plan_replacements(&policy, path, &[all])?;
+        assert_eq!(change.after.unwrap().matches("\"b\"").count(), 2);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_patches_apply_cleanly_or_not_at_all() -> Result<()> {
+        let dir = tempdir()?;
+        let policy = Policy::new().with_root(dir.path(), Access::ReadWrite);
+        let file = dir.path().join("notes.txt");
+        fs::write(&file, "one\n-- two\nthree\n")?;
+        let created = dir.path().join("new.txt");
+
+        // The removed line looks like a file header
+        let patch = format!(
+            "diff --git a/notes.txt b/notes.txt\n--- {file}\n+++ {file}\n\
+             @@ -1,3 +1,3 @@\n one\n--- two\n+-- 2\n three\n\
+             --- /dev/null\n+++ {created}\n@@ -0,0 +1 @@\n+fresh\n",
+            file = file.display(),
+            created = created.display()
+        );
+        let changes = plan_patch(&policy, &patch, None)?;
+        assert_eq!(changes.len(), 2);
+        assert_eq!(changes[0].after.as_deref(), Some("one\n-- 2\nthree\n"));
+        assert_eq!(changes[1].before, None);
+        assert_eq!(changes[1].after.as_deref(), Some("fresh\n"));
+
+        // The preview diff applies back to the original
+        let preview = changes[0].diff();
+        assert_eq!(
+            plan_patch(&policy, &preview, None)?,
+            vec![changes[0].clone()]
+        );
+
+        let stale = "@@ -1,2 +1,2 @@\n one\n-zwei\n+two\n";
+        assert!(matches!(
+            edit_error(plan_patch(&policy, stale, file.to_str())),
+            EditError::HunkFailed { .. }
+        ));
+        assert!(matches!(
+            edit_error(plan_patch(&policy, "nothing here", None)),
+            EditError::InvalidPatch { .. }
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_dry_run_leaves_files_untouched() -> Result<()> {
+        let dir = tempdir()?;
+        let policy = Policy::new().with_root(dir.path(), Access::ReadWrite);
+        let journal = Journal::new(dir.path().join(".journal"));
+        let file = dir.path().join("a.txt");
+        fs::write(&file, "old\n")?;
+
+        let change = plan_replacements(
+            &policy,
+            file.to_str().unwrap(),
+            &[replacement("old", "new")],
+        )?;
+        let preview = execute(&journal, vec![change], true)?;
+
+        assert_eq!(preview["dry_run"], true);
+        assert!(preview["diff"].as_str().unwrap().contains("+new"));
+        assert!(preview["edit_id"].is_null());
+        assert_eq!(fs::read_to_string(&file)?, "old\n");
+        assert!(!dir.path().join(".journal").exists());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_undo_restores_and_detects_conflicts() -> Result<()> {
+        let dir = tempdir()?;
+        let policy = Policy::new().with_root(dir.path(), Access::ReadWrite);
+        let journal = Journal::new(dir.path().join(".journal"));
+        let file = dir.path().join("a.txt");
+        let created = dir.path().join("b.txt");
+        fs::write(&file, "old\n")?;
+
+        let changes = vec![
+            plan_replacements(
+                &policy,
+                file.to_str().unwrap(),
+                &[replacement("old", "new")],
+            )?,
+            Change {
+                path: created.clone(),
+                before: None,
+                after: Some("b\n".to_owned()),
+            },
+        ];
+        let applied = execute(&journal, changes, false)?;
+        assert_eq!(fs::read_to_string(&file)?, "new\n");
+        assert_eq!(fs::read_to_string(&created)?, "b\n");
+
+        fs::write(&created, "changed\n")?;
+        assert!(matches!(
+            edit_error(journal.undo(&policy, None)),
+            EditError::Conflict { .. }
+        ));
+
+        fs::write(&created, "b\n")?;
+        let entry = journal.undo(&policy, applied["edit_id"].as_str())?;
+        assert_eq!(entry.files.len(), 2);
+        assert_eq!(fs::read_to_string(&file)?, "old\n");
+        assert!(!created.exists());
+
+        assert!(matches!(
+            edit_error(journal.undo(&policy, None)),
+            EditError::NothingToUndo
+        ));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,510 @@
+//! Access policy for the file tools.
+//!
+//! Paths are only touched inside configured roots, after canonicalization,
+//! so neither `..` nor symlinks can reach outside them. Roots are read from
+//! `filesystem.toml` under the configuration directory:
+//!
+//! ```toml
+//! max_file_size = 10485760
+//!
+//! [[roots]]
+//! path = "~/Projects"
+//! access = "read-write"
+//!
+//! [[roots]]
+//! path = "~/Projects/secrets"
+//! access = "read-only"
+//! ```
+//!
+//! When roots overlap, the innermost one decides the access. Without a
+//! configuration file the Desktop, Documents and Downloads folders are
+//! writable and nothing else is reachable.
+use super::resolve_path;
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::fs;
+use std::io::{ErrorKind, Read};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, LazyLock, PoisonError, RwLock};
+use walkdir::WalkDir;
+
+/// Files larger than this are neither read nor written by default.
+pub const DEFAULT_MAX_FILE_SIZE: u64 = 10 * 1024 * 1024;
+
+/// How much of a file is sniffed for binary content.
+const SNIFF_LEN: usize = 8 * 1024;
+
+static GLOBAL: LazyLock<RwLock<Arc<Policy>>> = LazyLock::new(|| {
+    let policy = Policy::load().unwrap_or_else(|e| {
+        // Fail closed: a broken configuration must not widen access
+        log::error!("{:#}; file tools are disabled", e);
+        Policy::new()
+    });
+
+    RwLock::new(Arc::new(policy))
+});
+
+/// The policy applied by [`read_file`](super::read_file) and friends.
+pub fn global() -> Arc<Policy> {
+    GLOBAL
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .clone()
+}
+
+/// Replaces the policy returned by [`global`].
+pub fn set_global(policy: Policy) {
+    *GLOBAL.write().unwrap_or_else(PoisonError::into_inner) = Arc::new(policy);
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Access {
+    #[default]
+    ReadOnly,
+    ReadWrite,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Root {
+    pub path: PathBuf,
+    #[serde(default)]
+    pub access: Access,
+}
+
+/// Why a path was refused. Tools return it inside their `anyhow::Error`,
+/// so callers can downcast it and report the `kind` to agents.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, thiserror::Error)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Violation {
+    #[error("{} is outside the allowed directories", path.display())]
+    OutsideRoots { path: PathBuf },
+    #[error("{} is in a read-only directory", path.display())]
+    ReadOnly { path: PathBuf },
+    #[error("{} is {size} bytes, over the limit of {limit} bytes", path.display())]
+    TooLarge {
+        path: PathBuf,
+        size: u64,
+        limit: u64,
+    },
+    #[error("{} is a binary file", path.display())]
+    Binary { path: PathBuf },
+}
+
+impl Violation {
+    /// The violation as JSON, with its message under `message`.
+    pub fn to_json(&self) -> Value {
+        let mut value = json!(self);
+        value["message"] = json!(self.to_string());
+        value
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct Policy {
+    #[serde(default)]
+    roots: Vec<Root>,
+    #[serde(default = "default_max_file_size")]
+    max_file_size: u64,
+}
+
+fn default_max_file_size() -> u64 {
+    DEFAULT_MAX_FILE_SIZE
+}
+
+impl Default for Policy {
+    fn default() -> Self {
+        let Some(dirs) = directories::UserDirs::new() else {
+            return Self::new();
+        };
+
+        [dirs.desktop_dir(), dirs.document_dir(), dirs.download_dir()]
+            .into_iter()
+            .flatten()
+            .fold(Self::new(), |policy, dir| {
+                policy.with_root(dir, Access::ReadWrite)
+            })
+    }
+}
+
+impl Policy {
+    /// A policy without roots, refusing every path.
+    pub fn new() -> Self {
+        Self {
+            roots: Vec::new(),
+            max_file_size: DEFAULT_MAX_FILE_SIZE,
+        }
+    }
+
+    pub fn with_root(mut self, path: impl Into<PathBuf>, access: Access) -> Self {
+        self.roots.push(Root {
+            path: path.into(),
+            access,
+        });
+        self
+    }
+
+    pub fn with_max_file_size(mut self, bytes: u64) -> Self {
+        self.max_file_size = bytes;
+        self
+    }
+
+    pub fn roots(&self) -> &[Root] {
+        &self.roots
+    }
+
+    pub fn path() -> PathBuf {
+        crate::brain::directory::config().join("filesystem.toml")
+    }
+
+    /// Reads [`Policy::path`]; a missing file means the default roots.
+    pub fn load() -> Result<Self> {
+        match fs::read_to_string(Self::path()) {
+            Ok(config) => Self::parse(&config),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    pub fn parse(config: &str) -> Result<Self> {
+        let mut policy: Self =
+            toml::from_str(config).context("Invalid filesystem configuration")?;
+
+        for root in &mut policy.roots {
+            root.path = resolve_path(&root.path.to_string_lossy());
+        }
+
+        Ok(policy)
+    }
+
+    /// Resolves `path` and checks it against the roots, returning its
+    /// canonical form.
+    pub fn authorize(&self, path: &str, access: Access) -> Result<PathBuf> {
+        let requested = resolve_path(path);
+        let outside = || Violation::OutsideRoots {
+            path: requested.clone(),
+        };
+
+        let absolute = if requested.is_relative() {
+            std::env::current_dir()?.join(&requested)
+        } else {
+            requested.clone()
+        };
+
+        let resolved = canonicalize(&absolute)?.ok_or_else(outside)?;
+
+        let granted = self
+            .roots
+            .iter()
+            .filter_map(|root| Some((fs::canonicalize(&root.path).ok()?, root.access)))
+            .filter(|(root, _)| resolved.starts_with(root))
+            .max_by_key(|(root, _)| root.components().count())
+            .map(|(_, access)| access)
+            .ok_or_else(outside)?;
+
+        if access == Access::ReadWrite && granted == Access::ReadOnly {
+            return Err(Violation::ReadOnly { path: resolved }.into());
+        }
+
+        Ok(resolved)
+    }
+
+    pub fn read_file(&self, path: &str) -> Result<Value> {
+        let resolved = self.authorize(path, Access::ReadOnly)?;
+        Ok(json!(self.read_text(&resolved)?))
+    }
+
+    /// Contents of a path returned by [`Policy::authorize`], refusing large
+    /// and binary files.
+    pub fn read_text(&self, resolved: &Path) -> Result<String> {
+        let size = fs::metadata(resolved)?.len();
+        self.check_size(resolved, size)?;
+
+        let mut content = Vec::new();
+        fs::File::open(resolved)?
+            .take(self.max_file_size.saturating_add(1))
+            .read_to_end(&mut content)?;
+        // The file may have grown since it was measured
+        self.check_size(resolved, content.len() as u64)?;
+
+        let path = resolved.to_path_buf();
+        let sniffed = &content[..content.len().min(SNIFF_LEN)];
+        if sniffed.contains(&0) {
+            return Err(Violation::Binary { path }.into());
+        }
+
+        String::from_utf8(content).map_err(|_| Violation::Binary { path }.into())
+    }
+
+    pub fn write_file(&self, path: &str, content: &str) -> Result<Value> {
+        let resolved = self.authorize(path, Access::ReadWrite)?;
+        self.check_size(&resolved, content.len() as u64)?;
+
+        // Ensure parent directory exists
+        if let Some(parent) = resolved.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        fs::write(resolved, content)?;
+        Ok(json!("Successfully wrote file"))
+    }
+
+    pub fn read_dir(&self, path: &str) -> Result<Value> {
+        let resolved = self.authorize(path, Access::ReadOnly)?;
+        let mut entries = Vec::new();
+        for entry in fs::read_dir(resolved)? {
+            let entry = entry?;
+            let metadata = entry.metadata()?;
+            entries.push(json!({
+                "name": entry.file_name().to_string_lossy(),
+                "is_dir": metadata.is_dir(),
+                "size": metadata.len(),
+                "path": entry.path().to_string_lossy()
+            }));
+        }
+
+        // Sort: Directories first, then alphabetical
+        entries.sort_by(|a, b| {
+            let a_dir = a["is_dir"].as_bool().unwrap_or(false);
+            let b_dir = b["is_dir"].as_bool().unwrap_or(false);
+            if a_dir == b_dir {
+                a["name"]
+                    .as_str()
+                    .unwrap_or("")
+                    .cmp(b["name"].as_str().unwrap_or(""))
+            } else {
+                b_dir.cmp(&a_dir)
+            }
+        });
+
+        Ok(json!(entries))
+    }
+
+    /// Symlinks are listed but not followed, so the walk stays in the root.
+    pub fn search_files(&self, query: &str, base_path: &str) -> Result<Value> {
+        let resolved = self.authorize(base_path, Access::ReadOnly)?;
+        let mut results = Vec::new();
+        let max_results = 20;
+        let query_lower = query.to_lowercase();
+
+        for entry in WalkDir::new(resolved)
+            .max_depth(3) // Stay shallow for performance
+            .into_iter()
+            .filter_map(|e| e.ok())
+        {
+            let name = entry.file_name().to_string_lossy();
+            if name.to_lowercase().contains(&query_lower) {
+                let metadata = entry.metadata()?;
+                results.push(json!({
+                    "name": name,
+                    "path": entry.path().to_string_lossy(),
+                    "is_dir": metadata.is_dir(),
+                    "size": metadata.len()
+                }));
+                if results.len() >= max_results {
+                    break;
+                }
+            }
+        }
+
+        Ok(json!(results))
+    }
+
+    pub fn check_size(&self, path: &Path, size: u64) -> Result<()> {
+        if size > self.max_file_size {
+            return Err(Violation::TooLarge {
+                path: path.to_path_buf(),
+                size,
+                limit: self.max_file_size,
+            }
+            .into());
+        }
+
+        Ok(())
+    }
+}
+
+/// Canonical form of an absolute `path` that may not exist yet: its deepest
+/// existing ancestor is canonicalized and the missing part appended. `None`
+/// when the missing part could still escape, through `..` or a dangling
+/// symlink that a write would follow.
+fn canonicalize(path: &Path) -> std::io::Result<Option<PathBuf>> {
+    let mut existing = path;
+    let mut missing = Vec::new();
+
+    loop {
+        match fs::canonicalize(existing) {
+            Ok(base) => {
+                return Ok(Some(
+                    missing
+                        .iter()
+                        .rev()
+                        .fold(base, |path, name| path.join(name)),
+                ));
+            }
+            Err(e) if e.kind() == ErrorKind::NotFound => {
+                if fs::symlink_metadata(existing).is_ok() {
+                    return Ok(None);
+                }
+
+                let (Some(parent), Some(name)) = (existing.parent(), existing.file_name()) else {
+                    return Ok(None);
+                };
+
+                missing.push(name);
+                existing = parent;
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn violation(result: Result<Value>) -> Violation {
+        result
+            .unwrap_err()
+            .downcast::<Violation>()
+            .expect("a policy violation")
+    }
+
+    #[test]
+    fn test_paths_outside_roots_are_refused() -> Result<()> {
+        let dir = tempdir()?;
+        let root = dir.path().join("root");
+        fs::create_dir(&root)?;
+        fs::write(dir.path().join("secret.txt"), "secret")?;
+
+        let policy = Policy::new().with_root(&root, Access::ReadWrite);
+        let escape = root.join("../secret.txt");
+
+        assert!(matches!(
+            violation(policy.read_file(escape.to_str().unwrap())),
+            Violation::OutsideRoots { .. }
+        ));
+        assert!(matches!(
+            violation(policy.write_file(root.join("new/../../x").to_str().unwrap(), "")),
+            Violation::OutsideRoots { .. }
+        ));
+        assert!(matches!(
+            violation(Policy::new().read_dir(root.to_str().unwrap())),
+            Violation::OutsideRoots { .. }
+        ));
+
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_symlinks_cannot_escape() -> Result<()> {
+        let dir = tempdir()?;
+        let root = dir.path().join("root");
+        fs::create_dir(&root)?;
+        fs::write(dir.path().join("secret.txt"), "secret")?;
+        std::os::unix::fs::symlink(dir.path().join("secret.txt"), root.join("link"))?;
+        std::os::unix::fs::symlink(dir.path().join("planted.txt"), root.join("dangling"))?;
+
+        let policy = Policy::new().with_root(&root, Access::ReadWrite);
+
+        assert!(matches!(
+            violation(policy.read_file(root.join("link").to_str().unwrap())),
+            Violation::OutsideRoots { .. }
+        ));
+        assert!(matches!(
+            violation(policy.write_file(root.join("dangling").to_str().unwrap(), "x")),
+            Violation::OutsideRoots { .. }
+        ));
+        assert!(!dir.path().join("planted.txt").exists());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_innermost_root_decides_access() -> Result<()> {
+        let dir = tempdir()?;
+        let locked = dir.path().join("locked");
+        fs::create_dir(&locked)?;
+
+        let policy = Policy::new()
+            .with_root(dir.path(), Access::ReadWrite)
+            .with_root(&locked, Access::ReadOnly);
+
+        policy.write_file(dir.path().join("a.txt").to_str().unwrap(), "a")?;
+        assert!(matches!(
+            violation(policy.write_file(locked.join("b.txt").to_str().unwrap(), "b")),
+            Violation::ReadOnly { .. }
+        ));
+        assert!(policy.read_dir(locked.to_str().unwrap()).is_ok());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_size_limit_and_binary_files() -> Result<()> {
+        let dir = tempdir()?;
+        let policy = Policy::new()
+            .with_root(dir.path(), Access::ReadWrite)
+            .with_max_file_size(8);
+
+        let large = dir.path().join("large.txt");
+        fs::write(&large, "0123456789")?;
+        assert_eq!(
+            violation(policy.read_file(large.to_str().unwrap())),
+            Violation::TooLarge {
+                path: fs::canonicalize(&large)?,
+                size: 10,
+                limit: 8
+            }
+        );
+        assert!(matches!(
+            violation(policy.write_file(large.to_str().unwrap(), "0123456789")),
+            Violation::TooLarge { .. }
+        ));
+
+        let binary = dir.path().join("image.bin");
+        fs::write(&binary, [0x89, b'P', b'N', b'G', 0, 0])?;
+        let error = violation(policy.read_file(binary.to_str().unwrap()));
+        assert!(matches!(error, Violation::Binary { .. }));
+        assert_eq!(error.to_json()["kind"], "binary");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_config() -> Result<()> {
+        let policy = Policy::parse(
+            r#"
+            max_file_size = 1024
+
+            [[roots]]
+            path = "/srv/projects"
+            access = "read-write"
+
+            [[roots]]
+            path = "/srv/reference"
+            "#,
+        )?;
+
+        assert_eq!(policy.max_file_size, 1024);
+        assert_eq!(
+            policy.roots(),
+            [
+                Root {
+                    path: PathBuf::from("/srv/projects"),
+                    access: Access::ReadWrite
+                },
+                Root {
+                    path: PathBuf::from("/srv/reference"),
+                    access: Access::ReadOnly
+                },
+            ]
+        );
+
+        Ok(())
+    }
+}
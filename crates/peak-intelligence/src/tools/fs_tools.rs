@@ -1,9 +1,26 @@
-use crate::tools::{read_dir, read_file, search_files, write_file, IntelligenceTool};
+use crate::tools::edit::{self, Journal, Replacement};
+use crate::tools::fs_policy::{self, Policy};
+use crate::tools::IntelligenceTool;
 use anyhow::Result;
 use async_trait::async_trait;
 use serde_json::{json, Value};
+use std::sync::Arc;
 
-pub struct ReadFileTool;
+pub struct ReadFileTool {
+    policy: Arc<Policy>,
+}
+
+impl ReadFileTool {
+    pub fn new(policy: Arc<Policy>) -> Self {
+        Self { policy }
+    }
+}
+
+impl Default for ReadFileTool {
+    fn default() -> Self {
+        Self::new(fs_policy::global())
+    }
+}
 
 #[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
 #[cfg_attr(not(target_arch = "wasm32"), async_trait)]
@@ -12,7 +29,7 @@ impl IntelligenceTool for ReadFileTool {
         "read_file"
     }
     fn description(&self) -> &'static str {
-        "Read content of a file from the system."
+        "Read a text file inside the allowed directories."
     }
     fn input_schema(&self) -> Value {
         json!({
@@ -28,11 +45,25 @@ impl IntelligenceTool for ReadFileTool {
             .get("path")
             .and_then(|v| v.as_str())
             .ok_or_else(|| anyhow::anyhow!("Missing 'path' argument"))?;
-        read_file(path)
+        self.policy.read_file(path)
     }
 }
 
-pub struct WriteFileTool;
+pub struct WriteFileTool {
+    policy: Arc<Policy>,
+}
+
+impl WriteFileTool {
+    pub fn new(policy: Arc<Policy>) -> Self {
+        Self { policy }
+    }
+}
+
+impl Default for WriteFileTool {
+    fn default() -> Self {
+        Self::new(fs_policy::global())
+    }
+}
 
 #[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
 #[cfg_attr(not(target_arch = "wasm32"), async_trait)]
@@ -41,7 +72,7 @@ impl IntelligenceTool for WriteFileTool {
         "write_file"
     }
     fn description(&self) -> &'static str {
-        "Write content to a file."
+        "Write content to a file inside the writable directories."
     }
     fn input_schema(&self) -> Value {
         json!({
@@ -62,11 +93,25 @@ impl IntelligenceTool for WriteFileTool {
             .get("content")
             .and_then(|v| v.as_str())
             .ok_or_else(|| anyhow::anyhow!("Missing 'content' argument"))?;
-        write_file(path, content)
+        self.policy.write_file(path, content)
     }
 }
 
-pub struct ReadDirTool;
+pub struct ReadDirTool {
+    policy: Arc<Policy>,
+}
+
+impl ReadDirTool {
+    pub fn new(policy: Arc<Policy>) -> Self {
+        Self { policy }
+    }
+}
+
+impl Default for ReadDirTool {
+    fn default() -> Self {
+        Self::new(fs_policy::global())
+    }
+}
 
 #[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
 #[cfg_attr(not(target_arch = "wasm32"), async_trait)]
@@ -91,11 +136,25 @@ impl IntelligenceTool for ReadDirTool {
             .get("path")
             .and_then(|v| v.as_str())
             .ok_or_else(|| anyhow::anyhow!("Missing 'path' argument"))?;
-        read_dir(path)
+        self.policy.read_dir(path)
     }
 }
 
-pub struct SearchFilesTool;
+pub struct SearchFilesTool {
+    policy: Arc<Policy>,
+}
+
+impl SearchFilesTool {
+    pub fn new(policy: Arc<Policy>) -> Self {
+        Self { policy }
+    }
+}
+
+impl Default for SearchFilesTool {
+    fn default() -> Self {
+        Self::new(fs_policy::global())
+    }
+}
 
 #[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
 #[cfg_attr(not(target_arch = "wasm32"), async_trait)]
@@ -125,23 +184,193 @@ impl IntelligenceTool for SearchFilesTool {
             .get("base_path")
             .and_then(|v| v.as_str())
             .ok_or_else(|| anyhow::anyhow!("Missing 'base_path' argument"))?;
-        search_files(query, base_path)
+        self.policy.search_files(query, base_path)
+    }
+}
+
+/// Search/replace edits, which leave the rest of the file as it was.
+pub struct EditFileTool {
+    policy: Arc<Policy>,
+    journal: Journal,
+}
+
+impl EditFileTool {
+    pub fn new(policy: Arc<Policy>, journal: Journal) -> Self {
+        Self { policy, journal }
+    }
+}
+
+impl Default for EditFileTool {
+    fn default() -> Self {
+        Self::new(fs_policy::global(), Journal::default())
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl IntelligenceTool for EditFileTool {
+    fn name(&self) -> &'static str {
+        "edit_file"
+    }
+    fn description(&self) -> &'static str {
+        "Edit a file by replacing exact text. Each search text must occur exactly once unless 'all' is set. Returns the diff; use dry_run to preview without writing."
+    }
+    fn input_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "path": { "type": "string", "description": "Path to file" },
+                "edits": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "properties": {
+                            "search": { "type": "string", "description": "Exact text to replace" },
+                            "replace": { "type": "string", "description": "Replacement text" },
+                            "all": { "type": "boolean", "description": "Replace every occurrence" }
+                        },
+                        "required": ["search", "replace"]
+                    }
+                },
+                "dry_run": { "type": "boolean", "description": "Only return the diff" }
+            },
+            "required": ["path", "edits"]
+        })
+    }
+    async fn execute(&self, args: Value) -> Result<Value> {
+        let path = args
+            .get("path")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| anyhow::anyhow!("Missing 'path' argument"))?;
+        let edits: Vec<Replacement> = args
+            .get("edits")
+            .cloned()
+            .map(serde_json::from_value)
+            .transpose()?
+            .ok_or_else(|| anyhow::anyhow!("Missing 'edits' argument"))?;
+        let dry_run = args
+            .get("dry_run")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
+
+        let change = edit::plan_replacements(&self.policy, path, &edits)?;
+        edit::execute(&self.journal, vec![change], dry_run)
+    }
+}
+
+pub struct ApplyPatchTool {
+    policy: Arc<Policy>,
+    journal: Journal,
+}
+
+impl ApplyPatchTool {
+    pub fn new(policy: Arc<Policy>, journal: Journal) -> Self {
+        Self { policy, journal }
+    }
+}
+
+impl Default for ApplyPatchTool {
+    fn default() -> Self {
+        Self::new(fs_policy::global(), Journal::default())
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl IntelligenceTool for ApplyPatchTool {
+    fn name(&self) -> &'static str {
+        "apply_patch"
+    }
+    fn description(&self) -> &'static str {
+        "Apply a unified diff to one or more files. Every hunk must apply cleanly or nothing is written. Use dry_run to preview."
+    }
+    fn input_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "patch": { "type": "string", "description": "Unified diff with ---/+++ file headers" },
+                "path": { "type": "string", "description": "File to patch when the diff has no headers" },
+                "dry_run": { "type": "boolean", "description": "Only return the diff" }
+            },
+            "required": ["patch"]
+        })
+    }
+    async fn execute(&self, args: Value) -> Result<Value> {
+        let patch = args
+            .get("patch")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| anyhow::anyhow!("Missing 'patch' argument"))?;
+        let path = args.get("path").and_then(|v| v.as_str());
+        let dry_run = args
+            .get("dry_run")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
+
+        let changes = edit::plan_patch(&self.policy, patch, path)?;
+        edit::execute(&self.journal, changes, dry_run)
+    }
+}
+
+pub struct UndoEditTool {
+    policy: Arc<Policy>,
+    journal: Journal,
+}
+
+impl UndoEditTool {
+    pub fn new(policy: Arc<Policy>, journal: Journal) -> Self {
+        Self { policy, journal }
+    }
+}
+
+impl Default for UndoEditTool {
+    fn default() -> Self {
+        Self::new(fs_policy::global(), Journal::default())
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl IntelligenceTool for UndoEditTool {
+    fn name(&self) -> &'static str {
+        "undo_edit"
+    }
+    fn description(&self) -> &'static str {
+        "Revert an edit made by edit_file or apply_patch, the latest one by default. Fails if the files changed since."
+    }
+    fn input_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "edit_id": { "type": "string", "description": "Edit to revert, as returned by the edit" }
+            }
+        })
+    }
+    async fn execute(&self, args: Value) -> Result<Value> {
+        let id = args.get("edit_id").and_then(|v| v.as_str());
+        let entry = self.journal.undo(&self.policy, id)?;
+
+        Ok(json!({
+            "edit_id": entry.id,
+            "files": entry.files.iter().map(|f| f.path.to_string_lossy()).collect::<Vec<_>>(),
+        }))
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::tools::fs_policy::Access;
     use tempfile::tempdir;
 
     #[tokio::test]
     async fn test_fs_tools() -> Result<()> {
         let dir = tempdir()?;
+        let policy = Arc::new(Policy::new().with_root(dir.path(), Access::ReadWrite));
         let file_path = dir.path().join("test.txt");
         let file_path_str = file_path.to_str().unwrap();
 
         // Test WriteFileTool
-        let write_tool = WriteFileTool;
+        let write_tool = WriteFileTool::new(policy.clone());
         write_tool
             .execute(json!({
                 "path": file_path_str,
@@ -150,7 +379,7 @@ mod tests {
             .await?;
 
         // Test ReadFileTool
-        let read_tool = ReadFileTool;
+        let read_tool = ReadFileTool::new(policy.clone());
         let content = read_tool
             .execute(json!({
                 "path": file_path_str
@@ -159,7 +388,7 @@ mod tests {
         assert_eq!(content.as_str().unwrap(), "hello world");
 
         // Test ReadDirTool
-        let read_dir_tool = ReadDirTool;
+        let read_dir_tool = ReadDirTool::new(policy.clone());
         let entries = read_dir_tool
             .execute(json!({
                 "path": dir.path().to_str().unwrap()
@@ -175,7 +404,7 @@ mod tests {
                 .unwrap_or(false)));
 
         // Test SearchFilesTool
-        let search_tool = SearchFilesTool;
+        let search_tool = SearchFilesTool::new(policy.clone());
         let results = search_tool
             .execute(json!({
                 "query": "test",
@@ -1,15 +1,11 @@
 use anyhow::Result;
 use serde_json::{json, Value};
 
-#[cfg(feature = "native")]
-use std::fs;
 #[allow(unused_imports)]
 #[cfg(feature = "native")]
 use std::process::Command;
 #[cfg(feature = "native")]
 use sysinfo::{Pid, System};
-#[cfg(feature = "native")]
-use walkdir::WalkDir;
 
 #[cfg(feature = "native")]
 pub fn list_processes() -> Result<Value> {
@@ -108,12 +104,11 @@ fn resolve_path(path: &str) -> std::path::PathBuf {
     PathBuf::from(path_str)
 }
 
+/// Reads a text file inside the roots of [`fs_policy::global`].
 pub fn read_file(path: &str) -> Result<Value> {
     #[cfg(feature = "native")]
     {
-        let resolved = resolve_path(path);
-        let content = fs::read_to_string(resolved)?;
-        Ok(json!(content))
+        fs_policy::global().read_file(path)
     }
     #[cfg(not(feature = "native"))]
     {
@@ -122,18 +117,11 @@ pub fn read_file(path: &str) -> Result<Value> {
     }
 }
 
+/// Writes a file inside the writable roots of [`fs_policy::global`].
 pub fn write_file(path: &str, content: &str) -> Result<Value> {
     #[cfg(feature = "native")]
     {
-        let resolved = resolve_path(path);
-
-        // Ensure parent directory exists
-        if let Some(parent) = resolved.parent() {
-            fs::create_dir_all(parent)?;
-        }
-
-        fs::write(resolved, content)?;
-        Ok(json!("Successfully wrote file"))
+        fs_policy::global().write_file(path, content)
     }
     #[cfg(not(feature = "native"))]
     {
@@ -145,34 +133,7 @@ pub fn write_file(path: &str, content: &str) -> Result<Value> {
 pub fn read_dir(path: &str) -> Result<Value> {
     #[cfg(feature = "native")]
     {
-        let resolved = resolve_path(path);
-        let mut entries = Vec::new();
-        for entry in fs::read_dir(resolved)? {
-            let entry = entry?;
-            let metadata = entry.metadata()?;
-            entries.push(json!({
-                "name": entry.file_name().to_string_lossy(),
-                "is_dir": metadata.is_dir(),
-                "size": metadata.len(),
-                "path": entry.path().to_string_lossy()
-            }));
-        }
-
-        // Sort: Directories first, then alphabetical
-        entries.sort_by(|a, b| {
-            let a_dir = a["is_dir"].as_bool().unwrap_or(false);
-            let b_dir = b["is_dir"].as_bool().unwrap_or(false);
-            if a_dir == b_dir {
-                a["name"]
-                    .as_str()
-                    .unwrap_or("")
-                    .cmp(b["name"].as_str().unwrap_or(""))
-            } else {
-                b_dir.cmp(&a_dir)
-            }
-        });
-
-        Ok(json!(entries))
+        fs_policy::global().read_dir(path)
     }
     #[cfg(not(feature = "native"))]
     {
@@ -229,32 +190,7 @@ pub fn scan_wifi() -> Result<Value> {
 pub fn search_files(query: &str, base_path: &str) -> Result<Value> {
     #[cfg(feature = "native")]
     {
-        let resolved = resolve_path(base_path);
-        let mut results = Vec::new();
-        let max_results = 20;
-        let query_lower = query.to_lowercase();
-
-        for entry in WalkDir::new(resolved)
-            .max_depth(3) // Stay shallow for performance
-            .into_iter()
-            .filter_map(|e| e.ok())
-        {
-            let name = entry.file_name().to_string_lossy();
-            if name.to_lowercase().contains(&query_lower) {
-                let metadata = entry.metadata()?;
-                results.push(json!({
-                    "name": name,
-                    "path": entry.path().to_string_lossy(),
-                    "is_dir": metadata.is_dir(),
-                    "size": metadata.len()
-                }));
-                if results.len() >= max_results {
-                    break;
-                }
-            }
-        }
-
-        Ok(json!(results))
+        fs_policy::global().search_files(query, base_path)
     }
     #[cfg(not(feature = "native"))]
     {
@@ -343,9 +279,34 @@ pub fn get_system_snapshot() -> Result<Value> {
     Err(anyhow::anyhow!("System snapshot not supported on web"))
 }
 
+/// An argv as one line, quoting arguments a shell would split. Available
+/// without the `native` feature, so apps can show a command they can't run.
+pub fn command_line(argv: &[String]) -> String {
+    argv.iter()
+        .map(|arg| {
+            if arg.is_empty() || arg.contains(|c: char| c.is_whitespace() || c == '"') {
+                format!("{:?}", arg)
+            } else {
+                arg.clone()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+pub mod approval;
+#[cfg(feature = "native")]
+pub mod command;
+#[cfg(feature = "native")]
+pub mod edit;
+#[cfg(feature = "native")]
+pub mod fs_policy;
 #[cfg(feature = "native")]
 pub mod fs_tools;
+#[cfg(feature = "native")]
+pub mod model_tools;
 pub mod registry;
+pub mod search_cache;
 pub mod search_router;
 pub mod search_tool;
 #[cfg(feature = "native")]
@@ -358,74 +319,97 @@ pub mod voice_tools;
 #[allow(unused_imports)]
 pub use registry::{IntelligenceTool, ToolRegistry};
 
-pub async fn web_search(query: &str) -> Result<Value> {
-    // Check if we are being called via the router to avoid infinite recursion
-    // The router calls the crate::tools::web_search (the scraper) directly.
-
-    // Default scraper implementation
-    let url = format!(
-        "https://html.duckduckgo.com/html/?q={}",
-        urlencoding::encode(query)
-    );
-
-    let client = reqwest::Client::builder()
-        .user_agent("Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/121.0.0.0 Safari/537.36")
-        .build()?;
-
-    let html = client.get(url)
-        .header("Accept", "text/html,application/xhtml+xml,application/xml;q=0.9,image/avif,image/webp,image/apng,*/*;q=0.8")
-        .header("Accept-Language", "en-US,en;q=0.9")
-        .header("Sec-Fetch-Dest", "document")
-        .header("Sec-Fetch-Mode", "navigate")
-        .header("Sec-Fetch-Site", "none")
-        .header("Sec-Fetch-User", "?1")
-        .header("Upgrade-Insecure-Requests", "1")
-        .send()
-        .await?
-        .text()
-        .await?;
-
-    // Check for "No Results" or blocking message
-    if html.contains("No results.") || html.contains("Robot Check") {
-        log::warn!("🔍 DDG Scraper: No results found or robot check hit.");
-        return Ok(json!([]));
+/// Registers the built-in tools: files, processes and commands, web search,
+/// the model library and terminals on `terminal`, whose output is sent to
+/// `notifications`.
+#[cfg(feature = "native")]
+pub fn register_builtin(
+    registry: &mut ToolRegistry,
+    terminal: &'static crate::terminal::TerminalManager,
+    notifications: tokio::sync::mpsc::Sender<String>,
+) {
+    use edit::Journal;
+    use fs_tools::{
+        ApplyPatchTool, EditFileTool, ReadDirTool, ReadFileTool, SearchFilesTool, UndoEditTool,
+        WriteFileTool,
+    };
+    use model_tools::{
+        ModelDeleteTool, ModelDownloadTool, ModelFilesTool, ModelGcTool, ModelListTool,
+        ModelQueueTool, ModelSearchTool,
+    };
+    use search_tool::WebSearchTool;
+    use system_tools::{KillProcessTool, ListProcessesTool, RunCommandTool, SystemSnapshotTool};
+    use terminal_tools::{
+        TerminalCloseTool, TerminalListTool, TerminalOpenTool, TerminalResizeTool,
+        TerminalScreenTool, TerminalWriteTool,
+    };
+
+    // FS Tools
+    let policy = fs_policy::global();
+    registry.register(ReadFileTool::new(policy.clone()));
+    registry.register(WriteFileTool::new(policy.clone()));
+    registry.register(ReadDirTool::new(policy.clone()));
+    registry.register(SearchFilesTool::new(policy.clone()));
+    registry.register(EditFileTool::new(policy.clone(), Journal::default()));
+    registry.register(ApplyPatchTool::new(policy.clone(), Journal::default()));
+    registry.register(UndoEditTool::new(policy, Journal::default()));
+
+    // System Tools
+    registry.register(ListProcessesTool);
+    registry.register(KillProcessTool);
+    registry.register(SystemSnapshotTool);
+    registry.register(RunCommandTool);
+
+    // Search Tool
+    registry.register(WebSearchTool::default());
+
+    // Model Library Tools
+    let manager = crate::brain::model::Manager::default();
+    registry.register(ModelSearchTool {
+        manager: manager.clone(),
+    });
+    registry.register(ModelFilesTool {
+        manager: manager.clone(),
+    });
+    registry.register(ModelDownloadTool {
+        manager: manager.clone(),
+    });
+    registry.register(ModelQueueTool {
+        manager: manager.clone(),
+    });
+    registry.register(ModelListTool {
+        manager: manager.clone(),
+    });
+    registry.register(ModelDeleteTool {
+        manager: manager.clone(),
+    });
+    registry.register(ModelGcTool { manager });
+
+    // Terminal Tools
+    registry.register(TerminalOpenTool {
+        manager: terminal,
+        tx: notifications,
+    });
+    registry.register(TerminalWriteTool { manager: terminal });
+    registry.register(TerminalResizeTool { manager: terminal });
+    registry.register(TerminalScreenTool { manager: terminal });
+    registry.register(TerminalListTool { manager: terminal });
+    registry.register(TerminalCloseTool { manager: terminal });
+
+    // Voice Tools
+    #[cfg(feature = "voice")]
+    {
+        use voice_tools::{SttTool, TtsTool};
+        registry.register(SttTool);
+        registry.register(TtsTool);
     }
+}
 
-    let document = scraper::Html::parse_document(&html);
-    let selector = scraper::Selector::parse(".result").unwrap();
-    let title_selector = scraper::Selector::parse(".result__title").unwrap();
-    let snippet_selector = scraper::Selector::parse(".result__snippet").unwrap();
-    let link_selector = scraper::Selector::parse(".result__url").unwrap();
-
-    let mut results = Vec::new();
-    for element in document.select(&selector).take(8) {
-        let title = element
-            .select(&title_selector)
-            .next()
-            .map(|e| e.text().collect::<String>().trim().to_string())
-            .unwrap_or_default();
-
-        let snippet = element
-            .select(&snippet_selector)
-            .next()
-            .map(|e| e.text().collect::<String>().trim().to_string())
-            .unwrap_or_default();
-
-        let link = element
-            .select(&link_selector)
-            .next()
-            .map(|e| e.text().collect::<String>().trim().to_string())
-            .unwrap_or_default();
-
-        if !title.is_empty() {
-            results.push(json!({
-                "title": title,
-                "snippet": snippet,
-                "link": link
-            }));
-        }
-    }
+/// Scrapes DuckDuckGo, without going through the other providers.
+pub async fn web_search(query: &str) -> Result<Value> {
+    use search_router::SearchProvider;
 
+    let results = search_router::DuckDuckGoScraper.search(query, None).await?;
     Ok(json!(results))
 }
 
@@ -434,14 +418,14 @@ pub async fn web_search_routed(
     brave_key: Option<String>,
     tavily_key: Option<String>,
 ) -> Result<Value> {
-    let router = search_router::SearchRouter::new();
-    router.execute(query, brave_key, tavily_key).await
+    search_router::global()
+        .execute(query, brave_key, tavily_key)
+        .await
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::fs;
 
     #[test]
     fn test_resolve_path_tilde() {
@@ -471,18 +455,23 @@ mod tests {
     fn test_file_operations() -> Result<()> {
         #[cfg(feature = "native")]
         {
-            let test_path = "test_file_ops.txt";
+            use fs_policy::{Access, Policy};
+
+            let dir = tempfile::tempdir()?;
+            let policy = Policy::new().with_root(dir.path(), Access::ReadWrite);
+            let test_path = dir.path().join("test_file_ops.txt");
+            let test_path = test_path.to_str().unwrap();
             let test_content = "Hello, PeakOS!";
 
             // 1. Write
-            write_file(test_path, test_content)?;
+            policy.write_file(test_path, test_content)?;
 
             // 2. Read
-            let read_content = read_file(test_path)?;
+            let read_content = policy.read_file(test_path)?;
             assert_eq!(read_content.as_str().unwrap(), test_content);
 
-            // 3. Clean up
-            fs::remove_file(test_path)?;
+            // 3. Outside the roots
+            assert!(Policy::new().read_file(test_path).is_err());
         }
         Ok(())
     }
@@ -491,7 +480,8 @@ mod tests {
     fn test_read_dir() -> Result<()> {
         #[cfg(feature = "native")]
         {
-            let res = read_dir(".")?;
+            let policy = fs_policy::Policy::new().with_root(".", fs_policy::Access::ReadOnly);
+            let res = policy.read_dir(".")?;
             assert!(res.is_array());
             assert!(res.as_array().unwrap().len() > 0);
         }
@@ -0,0 +1,299 @@
+use crate::brain::model::{File, Id, Manager};
+use crate::tools::IntelligenceTool;
+use anyhow::Result;
+use async_trait::async_trait;
+use serde_json::{json, Value};
+
+fn string_arg<'a>(args: &'a Value, name: &str) -> Result<&'a str> {
+    args.get(name)
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| anyhow::anyhow!("Missing '{}' argument", name))
+}
+
+fn file_arg(args: &Value) -> Result<File> {
+    let model = string_arg(args, "model")?;
+    let name = string_arg(args, "file")?;
+
+    Ok(File::new(model.parse::<Id>()?, name)?)
+}
+
+fn file_schema() -> Value {
+    json!({
+        "type": "object",
+        "properties": {
+            "model": { "type": "string", "description": "Model repository, e.g. 'author/name-GGUF'" },
+            "file": { "type": "string", "description": "GGUF file name within the repository" }
+        },
+        "required": ["model", "file"]
+    })
+}
+
+pub struct ModelSearchTool {
+    pub manager: Manager,
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl IntelligenceTool for ModelSearchTool {
+    fn name(&self) -> &'static str {
+        "model_search"
+    }
+    fn description(&self) -> &'static str {
+        "Search the model hub for GGUF models."
+    }
+    fn input_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "query": { "type": "string", "description": "Search terms; empty lists popular models" }
+            }
+        })
+    }
+    async fn execute(&self, args: Value) -> Result<Value> {
+        let query = args
+            .get("query")
+            .and_then(|v| v.as_str())
+            .unwrap_or_default();
+        let models = self.manager.search(query.to_string()).await?;
+
+        Ok(json!(models
+            .iter()
+            .map(|model| json!({
+                "id": model.id.0,
+                "downloads": model.downloads.to_string(),
+                "likes": model.likes.to_string(),
+                "last_modified": model.last_modified.to_rfc3339(),
+            }))
+            .collect::<Vec<_>>()))
+    }
+}
+
+pub struct ModelFilesTool {
+    pub manager: Manager,
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl IntelligenceTool for ModelFilesTool {
+    fn name(&self) -> &'static str {
+        "model_files"
+    }
+    fn description(&self) -> &'static str {
+        "List the downloadable GGUF variants of a model, grouped by precision."
+    }
+    fn input_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "model": { "type": "string", "description": "Model repository, e.g. 'author/name-GGUF'" }
+            },
+            "required": ["model"]
+        })
+    }
+    async fn execute(&self, args: Value) -> Result<Value> {
+        let id = string_arg(&args, "model")?.parse::<Id>()?;
+        let files = self.manager.files(id).await?;
+
+        Ok(json!(files
+            .iter()
+            .flat_map(|(bits, files)| files.iter().map(move |file| json!({
+                "file": file.name,
+                "bits": bits.to_string(),
+                "size": file.size.map(|size| size.0),
+            })))
+            .collect::<Vec<_>>()))
+    }
+}
+
+pub struct ModelDownloadTool {
+    pub manager: Manager,
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl IntelligenceTool for ModelDownloadTool {
+    fn name(&self) -> &'static str {
+        "model_download"
+    }
+    fn description(&self) -> &'static str {
+        "Queue a GGUF file for download. Downloads run in the background, resume after interruptions and are checked against the hub's SHA256."
+    }
+    fn input_schema(&self) -> Value {
+        file_schema()
+    }
+    async fn execute(&self, args: Value) -> Result<Value> {
+        let download = self.manager.enqueue(file_arg(&args)?).await?;
+
+        let manager = self.manager.clone();
+        tokio::spawn(async move {
+            if let Err(error) = manager.run().await {
+                log::warn!("Model downloads stopped: {}", error);
+            }
+        });
+
+        Ok(serde_json::to_value(download)?)
+    }
+}
+
+pub struct ModelQueueTool {
+    pub manager: Manager,
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl IntelligenceTool for ModelQueueTool {
+    fn name(&self) -> &'static str {
+        "model_queue"
+    }
+    fn description(&self) -> &'static str {
+        "List pending and failed model downloads."
+    }
+    fn input_schema(&self) -> Value {
+        json!({ "type": "object", "properties": {} })
+    }
+    async fn execute(&self, _args: Value) -> Result<Value> {
+        Ok(serde_json::to_value(self.manager.queue().await?)?)
+    }
+}
+
+pub struct ModelListTool {
+    pub manager: Manager,
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl IntelligenceTool for ModelListTool {
+    fn name(&self) -> &'static str {
+        "model_list"
+    }
+    fn description(&self) -> &'static str {
+        "List the models installed in the local library."
+    }
+    fn input_schema(&self) -> Value {
+        json!({ "type": "object", "properties": {} })
+    }
+    async fn execute(&self, _args: Value) -> Result<Value> {
+        Ok(serde_json::to_value(
+            self.manager.installed().await?.files(),
+        )?)
+    }
+}
+
+pub struct ModelDeleteTool {
+    pub manager: Manager,
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl IntelligenceTool for ModelDeleteTool {
+    fn name(&self) -> &'static str {
+        "model_delete"
+    }
+    fn description(&self) -> &'static str {
+        "Delete an installed model file, including all shards of a split model."
+    }
+    fn input_schema(&self) -> Value {
+        file_schema()
+    }
+    fn requires_approval(&self) -> bool {
+        true
+    }
+    async fn execute(&self, args: Value) -> Result<Value> {
+        let file = file_arg(&args)?;
+
+        self.manager.dequeue(&file).await?;
+        let removed = self.manager.delete(&file).await?;
+
+        Ok(json!({ "removed": removed }))
+    }
+}
+
+pub struct ModelGcTool {
+    pub manager: Manager,
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl IntelligenceTool for ModelGcTool {
+    fn name(&self) -> &'static str {
+        "model_gc"
+    }
+    fn description(&self) -> &'static str {
+        "Remove unused variants of installed models and abandoned partial downloads. Variants used by saved chats are kept."
+    }
+    fn input_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "keep": {
+                    "type": "array",
+                    "description": "Additional files to keep",
+                    "items": file_schema()
+                }
+            }
+        })
+    }
+    fn requires_approval(&self) -> bool {
+        true
+    }
+    async fn execute(&self, args: Value) -> Result<Value> {
+        let keep = args
+            .get("keep")
+            .and_then(|v| v.as_array())
+            .map(|keep| keep.iter().map(file_arg).collect::<Result<Vec<_>>>())
+            .transpose()?
+            .unwrap_or_default();
+
+        let removed = self.manager.collect_garbage(&keep).await?;
+
+        Ok(json!({ "removed": removed }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_model_tools_require_a_file() {
+        let tool = ModelDeleteTool {
+            manager: Manager::default(),
+        };
+
+        let result = tool.execute(json!({ "model": "author/model-GGUF" })).await;
+        assert_eq!(result.unwrap_err().to_string(), "Missing 'file' argument");
+
+        let result = ModelGcTool {
+            manager: Manager::default(),
+        }
+        .execute(json!({ "keep": [{ "file": "model.gguf" }] }))
+        .await;
+        assert_eq!(result.unwrap_err().to_string(), "Missing 'model' argument");
+    }
+
+    #[test]
+    fn test_deleting_models_requires_approval() {
+        let manager = Manager::default();
+        assert!(ModelDeleteTool {
+            manager: manager.clone()
+        }
+        .requires_approval());
+        assert!(ModelGcTool { manager }.requires_approval());
+    }
+
+    #[tokio::test]
+    async fn test_model_tools_refuse_paths_outside_the_library() {
+        let tool = ModelDeleteTool {
+            manager: Manager::default(),
+        };
+
+        for args in [
+            json!({ "model": "../../..", "file": "model.gguf" }),
+            json!({ "model": "/etc/model", "file": "model.gguf" }),
+            json!({ "model": "author/model-GGUF", "file": "../../model.gguf" }),
+        ] {
+            let error = tool.execute(args).await.unwrap_err();
+            assert!(error.to_string().starts_with("invalid model"), "{}", error);
+        }
+    }
+}
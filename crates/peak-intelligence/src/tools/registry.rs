@@ -1,3 +1,5 @@
+use super::approval::{ApprovalError, Approvals, Ticket};
+
 use anyhow::Result;
 use async_trait::async_trait;
 use serde_json::{json, Value};
@@ -8,10 +10,10 @@ use std::sync::Arc;
 #[cfg_attr(not(target_arch = "wasm32"), async_trait)]
 pub trait IntelligenceTool: Send + Sync {
     /// Unique name of the tool (e.g., "read_file")
-    fn name(&self) -> &'static str;
+    fn name(&self) -> &str;
 
     /// Human-readable description of what the tool does
-    fn description(&self) -> &'static str;
+    fn description(&self) -> &str;
 
     /// JSON schema for the tool's input arguments
     fn input_schema(&self) -> Value;
@@ -23,16 +25,57 @@ pub trait IntelligenceTool: Send + Sync {
     fn is_available(&self) -> bool {
         true
     }
+
+    /// Whether calls need a human's approval, unless the registry's policy
+    /// says otherwise
+    fn requires_approval(&self) -> bool {
+        false
+    }
+
+    /// What a call does, shown to whoever approves it
+    fn describe_call(&self, args: &Value) -> String {
+        format!("Call '{}' with {}", self.name(), args)
+    }
 }
 
 pub struct ToolRegistry {
     tools: HashMap<String, Arc<dyn IntelligenceTool>>,
+    /// Per-tool overrides of `IntelligenceTool::requires_approval`.
+    approval_policy: HashMap<String, bool>,
+    /// Where protected calls wait. Without one they run unchecked.
+    approvals: Option<Approvals>,
 }
 
 impl ToolRegistry {
     pub fn new() -> Self {
         Self {
             tools: HashMap::new(),
+            approval_policy: HashMap::new(),
+            approvals: None,
+        }
+    }
+
+    /// Queues calls to protected tools in `approvals` until they are decided.
+    pub fn with_approvals(mut self, approvals: Approvals) -> Self {
+        self.approvals = Some(approvals);
+        self
+    }
+
+    pub fn approvals(&self) -> Option<&Approvals> {
+        self.approvals.as_ref()
+    }
+
+    /// Overrides whether calls to `name` need approval.
+    pub fn set_requires_approval(&mut self, name: impl Into<String>, required: bool) {
+        self.approval_policy.insert(name.into(), required);
+    }
+
+    pub fn requires_approval(&self, name: &str) -> bool {
+        match self.approval_policy.get(name) {
+            Some(required) => *required,
+            None => self
+                .get_tool(name)
+                .is_some_and(|tool| tool.requires_approval()),
         }
     }
 
@@ -53,29 +96,104 @@ impl ToolRegistry {
                     "name": t.name(),
                     "description": t.description(),
                     "input_schema": t.input_schema(),
+                    "requires_approval": self.requires_approval(t.name()),
                 })
             })
             .collect()
     }
 
     pub async fn call(&self, name: &str, args: Value) -> Result<Value> {
-        if let Some(tool) = self.get_tool(name) {
-            if !tool.is_available() {
-                return Err(anyhow::anyhow!(
-                    "Tool '{}' is not available in this environment",
-                    name
-                ));
+        self.call_as(name, args, "local").await
+    }
+
+    /// Calls a tool on behalf of `requester`, waiting for approval first
+    /// when the tool is protected.
+    pub async fn call_as(&self, name: &str, args: Value, requester: &str) -> Result<Value> {
+        self.prepare(name, args, requester)?.run().await
+    }
+
+    /// Checks a call and, when the tool is protected, queues it for
+    /// approval. The returned [`Call`] no longer borrows the registry, so
+    /// callers sharing it behind a lock can release it before waiting.
+    pub fn prepare(&self, name: &str, args: Value, requester: &str) -> Result<Call> {
+        let Some(tool) = self.get_tool(name) else {
+            return Err(anyhow::anyhow!("Tool '{}' not found", name));
+        };
+
+        if !tool.is_available() {
+            return Err(anyhow::anyhow!(
+                "Tool '{}' is not available in this environment",
+                name
+            ));
+        }
+
+        let approval = match self.approvals.as_ref() {
+            Some(approvals) if self.requires_approval(name) => {
+                let reason = tool.describe_call(&args);
+                Approval::Pending(approvals.request(name, args.clone(), reason, requester))
             }
-            tool.execute(args).await
-        } else {
-            Err(anyhow::anyhow!("Tool '{}' not found", name))
+            _ => Approval::Approved,
+        };
+
+        Ok(Call {
+            name: name.to_string(),
+            tool,
+            args,
+            approval,
+        })
+    }
+}
+
+/// A call checked by [`ToolRegistry::prepare`], run once it is approved.
+pub struct Call {
+    name: String,
+    tool: Arc<dyn IntelligenceTool>,
+    args: Value,
+    approval: Approval,
+}
+
+enum Approval {
+    Pending(Ticket),
+    Approved,
+    Denied,
+}
+
+impl Call {
+    /// Whether the call still waits for a decision before it runs.
+    pub fn is_pending(&self) -> bool {
+        matches!(self.approval, Approval::Pending(_))
+    }
+
+    /// Waits for the decision on the call, if it needs one. Dropping the
+    /// future keeps the request pending; dropping the call withdraws it.
+    pub async fn approve(&mut self) -> Result<(), ApprovalError> {
+        if let Approval::Pending(ticket) = &mut self.approval {
+            self.approval = if ticket.await.is_approved() {
+                Approval::Approved
+            } else {
+                Approval::Denied
+            };
         }
+
+        match self.approval {
+            Approval::Denied => Err(ApprovalError::Denied {
+                tool: self.name.clone(),
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    /// Runs the tool once the call is approved.
+    pub async fn run(mut self) -> Result<Value> {
+        self.approve().await?;
+        self.tool.execute(self.args).await
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::tools::approval::Decision;
 
     struct MockTool;
     #[async_trait]
@@ -119,4 +237,51 @@ mod tests {
             "Tool 'non_existent' not found"
         );
     }
+
+    #[tokio::test]
+    async fn test_protected_calls_wait_for_approval() -> Result<()> {
+        let approvals = Approvals::new();
+        let mut registry = ToolRegistry::new().with_approvals(approvals.clone());
+        registry.register(MockTool);
+
+        // Unprotected tools run straight away
+        registry.call("mock_tool", json!({})).await?;
+        assert!(approvals.pending().is_empty());
+
+        registry.set_requires_approval("mock_tool", true);
+        assert_eq!(registry.list_tools()[0]["requires_approval"], true);
+
+        let call = registry.call_as("mock_tool", json!({ "n": 1 }), "agent");
+        let decide = async {
+            while approvals.pending().is_empty() {
+                tokio::task::yield_now().await;
+            }
+            let request = &approvals.pending()[0];
+            assert_eq!(request.requester, "agent");
+            assert_eq!(request.reason, r#"Call 'mock_tool' with {"n":1}"#);
+            approvals.decide(request.id, Decision::Deny).unwrap();
+        };
+        let (result, ()) = tokio::join!(call, decide);
+        let error = result.unwrap_err();
+        assert_eq!(
+            error.downcast_ref::<ApprovalError>(),
+            Some(&ApprovalError::Denied {
+                tool: "mock_tool".into()
+            })
+        );
+
+        let call = registry.call("mock_tool", json!({ "n": 2 }));
+        let decide = async {
+            while approvals.pending().is_empty() {
+                tokio::task::yield_now().await;
+            }
+            approvals
+                .decide(approvals.pending()[0].id, Decision::Approve)
+                .unwrap();
+        };
+        let (result, ()) = tokio::join!(call, decide);
+        assert_eq!(result?["received"]["n"], 2);
+
+        Ok(())
+    }
 }
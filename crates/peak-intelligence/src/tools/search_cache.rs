@@ -0,0 +1,181 @@
+//! Query cache for the [`SearchRouter`](super::search_router::SearchRouter).
+//!
+//! Results are keyed by the query, case- and whitespace-insensitively, and
+//! served until they are older than the cache's TTL. A cache opened with a
+//! path writes itself back to that file after every change, so answers
+//! survive restarts.
+use super::search_router::SearchResult;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard, PoisonError};
+use std::time::Duration;
+
+pub const DEFAULT_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Queries kept at most; the oldest answers are dropped first.
+const MAX_ENTRIES: usize = 256;
+
+#[derive(Debug)]
+pub struct SearchCache {
+    path: Option<PathBuf>,
+    ttl: Duration,
+    entries: Mutex<HashMap<String, Entry>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Entry {
+    results: Vec<SearchResult>,
+    stored_at: DateTime<Utc>,
+}
+
+impl SearchCache {
+    /// A cache that is lost when dropped.
+    pub fn in_memory(ttl: Duration) -> Self {
+        Self {
+            path: None,
+            ttl,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// A cache persisted to `path`. An unreadable file starts it empty.
+    pub fn open(path: impl Into<PathBuf>, ttl: Duration) -> Self {
+        let path = path.into();
+
+        let entries = match std::fs::read_to_string(&path) {
+            Ok(json) => serde_json::from_str(&json).unwrap_or_else(|e| {
+                log::warn!("Ignoring corrupt search cache {}: {}", path.display(), e);
+                HashMap::new()
+            }),
+            Err(_) => HashMap::new(),
+        };
+
+        Self {
+            path: Some(path),
+            ttl,
+            entries: Mutex::new(entries),
+        }
+    }
+
+    pub fn path() -> PathBuf {
+        crate::brain::directory::data().join("search_cache.json")
+    }
+
+    pub fn ttl(&self) -> Duration {
+        self.ttl
+    }
+
+    /// Fresh results for `query`, if any.
+    pub fn get(&self, query: &str) -> Option<Vec<SearchResult>> {
+        let entries = self.entries();
+        let entry = entries.get(&key(query))?;
+
+        self.is_fresh(entry).then(|| entry.results.clone())
+    }
+
+    pub fn insert(&self, query: &str, results: Vec<SearchResult>) {
+        let mut entries = self.entries();
+
+        entries.retain(|_, entry| self.is_fresh(entry));
+        while entries.len() >= MAX_ENTRIES {
+            let Some(oldest) = entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.stored_at)
+                .map(|(key, _)| key.clone())
+            else {
+                break;
+            };
+            entries.remove(&oldest);
+        }
+
+        entries.insert(
+            key(query),
+            Entry {
+                results,
+                stored_at: Utc::now(),
+            },
+        );
+
+        self.save(&entries);
+    }
+
+    pub fn clear(&self) {
+        let mut entries = self.entries();
+        entries.clear();
+        self.save(&entries);
+    }
+
+    fn is_fresh(&self, entry: &Entry) -> bool {
+        (Utc::now() - entry.stored_at)
+            .to_std()
+            .is_ok_and(|age| age < self.ttl)
+    }
+
+    fn save(&self, entries: &HashMap<String, Entry>) {
+        let Some(path) = &self.path else {
+            return;
+        };
+
+        if let Err(e) = write(path, entries) {
+            log::warn!("Failed to save search cache {}: {}", path.display(), e);
+        }
+    }
+
+    fn entries(&self) -> MutexGuard<'_, HashMap<String, Entry>> {
+        self.entries.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+fn write(path: &Path, entries: &HashMap<String, Entry>) -> anyhow::Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(path, serde_json::to_vec(entries)?)?;
+    Ok(())
+}
+
+/// Queries that differ only in case or spacing share an entry.
+pub fn key(query: &str) -> String {
+    query
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+#[cfg(all(test, feature = "native"))]
+mod tests {
+    use super::*;
+
+    fn result(title: &str) -> SearchResult {
+        SearchResult {
+            title: title.into(),
+            url: format!("https://example.com/{}", title),
+            snippet: String::new(),
+            source: "Test".into(),
+        }
+    }
+
+    #[test]
+    fn test_cache_persists_and_expires() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("cache.json");
+
+        let cache = SearchCache::open(&path, DEFAULT_TTL);
+        assert_eq!(cache.get("rust"), None);
+        cache.insert("Rust  Lang", vec![result("rust")]);
+        assert_eq!(cache.get("rust lang"), Some(vec![result("rust")]));
+
+        let reopened = SearchCache::open(&path, DEFAULT_TTL);
+        assert_eq!(reopened.get(" RUST lang "), Some(vec![result("rust")]));
+
+        let expired = SearchCache::open(&path, Duration::ZERO);
+        assert_eq!(expired.get("rust lang"), None);
+
+        reopened.clear();
+        assert_eq!(SearchCache::open(&path, DEFAULT_TTL).get("rust lang"), None);
+    }
+}
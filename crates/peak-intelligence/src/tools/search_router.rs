@@ -1,13 +1,62 @@
-use anyhow::Result;
+//! Web search across pluggable providers.
+//!
+//! A [`SearchRouter`] asks its providers in order and returns the first
+//! non-empty answer as normalized [`SearchResult`]s. Besides the built-in
+//! Brave, Tavily and DuckDuckGo providers, more can be registered at
+//! runtime, or declared in `search.toml` under the configuration directory:
+//!
+//! ```toml
+//! cache_ttl_secs = 86400
+//!
+//! [[providers]]
+//! kind = "searxng"
+//! url = "https://searx.example.org"
+//!
+//! [[providers]]
+//! kind = "json"
+//! name = "Docs"
+//! url = "https://docs.example.org/api/search?q={query}"
+//! results = "/hits"
+//! title_field = "/name"
+//! url_field = "/href"
+//! snippet_field = "/summary"
+//!
+//! [[providers]]
+//! kind = "file"
+//! path = "~/search-fixtures.json"
+//! ```
+//!
+//! Configured providers are tried before the built-in ones. Answers are
+//! cached for `cache_ttl_secs` (a day by default; `0` disables the cache).
+use super::search_cache::{self, SearchCache};
+
+use anyhow::{Context, Result};
 use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
+use std::collections::{BTreeMap, HashMap};
 use std::env;
+use std::path::PathBuf;
+use std::sync::{Arc, LazyLock, PoisonError, RwLock};
+use std::time::Duration;
+
+/// One hit, in the same shape whichever provider found it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SearchResult {
+    pub title: String,
+    pub url: String,
+    #[serde(default)]
+    pub snippet: String,
+    /// Name of the provider that found it.
+    #[serde(default)]
+    pub source: String,
+}
 
 #[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
 #[cfg_attr(not(target_arch = "wasm32"), async_trait)]
-pub trait SearchProvider {
-    fn name(&self) -> &'static str;
-    async fn search(&self, query: &str, api_key: Option<String>) -> Result<Value>;
+pub trait SearchProvider: Send + Sync {
+    fn name(&self) -> &str;
+    async fn search(&self, query: &str, api_key: Option<String>) -> Result<Vec<SearchResult>>;
     fn is_available(&self, has_arg_key: bool) -> bool;
 }
 
@@ -17,7 +66,7 @@ pub struct BraveSearchProvider;
 #[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
 #[cfg_attr(not(target_arch = "wasm32"), async_trait)]
 impl SearchProvider for BraveSearchProvider {
-    fn name(&self) -> &'static str {
+    fn name(&self) -> &str {
         "Brave Search"
     }
 
@@ -27,7 +76,7 @@ impl SearchProvider for BraveSearchProvider {
             || option_env!("BRAVE_SEARCH_API_KEY").is_some()
     }
 
-    async fn search(&self, query: &str, arg_key: Option<String>) -> Result<Value> {
+    async fn search(&self, query: &str, arg_key: Option<String>) -> Result<Vec<SearchResult>> {
         let api_key = arg_key
             .or_else(|| {
                 env::var("BRAVE_SEARCH_API_KEY")
@@ -47,19 +96,11 @@ impl SearchProvider for BraveSearchProvider {
 
         let data: Value = res.json().await?;
 
-        // Transform Brave Results to standard format
-        let mut standard_results = Vec::new();
-        if let Some(web) = data["web"]["results"].as_array() {
-            for item in web {
-                standard_results.push(json!({
-                    "title": item["title"],
-                    "snippet": item["description"],
-                    "link": item["url"]
-                }));
-            }
-        }
-
-        Ok(json!(standard_results))
+        Ok(extract(
+            &data["web"]["results"],
+            &Fields::new("/title", "/url", "/description"),
+            self.name(),
+        ))
     }
 }
 
@@ -69,7 +110,7 @@ pub struct TavilySearchProvider;
 #[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
 #[cfg_attr(not(target_arch = "wasm32"), async_trait)]
 impl SearchProvider for TavilySearchProvider {
-    fn name(&self) -> &'static str {
+    fn name(&self) -> &str {
         "Tavily Search"
     }
 
@@ -77,7 +118,7 @@ impl SearchProvider for TavilySearchProvider {
         has_arg_key || env::var("TAVILY_API_KEY").is_ok() || option_env!("TAVILY_API_KEY").is_some()
     }
 
-    async fn search(&self, query: &str, arg_key: Option<String>) -> Result<Value> {
+    async fn search(&self, query: &str, arg_key: Option<String>) -> Result<Vec<SearchResult>> {
         let api_key = arg_key
             .or_else(|| {
                 env::var("TAVILY_API_KEY")
@@ -100,29 +141,286 @@ impl SearchProvider for TavilySearchProvider {
 
         let data: Value = res.json().await?;
 
-        // Transform Tavily Results to standard format
-        let mut standard_results = Vec::new();
-        if let Some(results) = data["results"].as_array() {
-            for item in results {
-                standard_results.push(json!({
-                    "title": item["title"],
-                    "snippet": item["content"],
-                    "link": item["url"]
-                }));
-            }
+        Ok(extract(
+            &data["results"],
+            &Fields::new("/title", "/url", "/content"),
+            self.name(),
+        ))
+    }
+}
+
+// 3. SEARXNG (SELF-HOSTED/FREE)
+/// A SearXNG instance with its JSON output format enabled.
+pub struct SearxngProvider {
+    base_url: String,
+}
+
+impl SearxngProvider {
+    pub fn new(base_url: impl Into<String>) -> Self {
+        Self {
+            base_url: base_url.into().trim_end_matches('/').to_string(),
+        }
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl SearchProvider for SearxngProvider {
+    fn name(&self) -> &str {
+        "SearXNG"
+    }
+
+    fn is_available(&self, _has_arg_key: bool) -> bool {
+        !self.base_url.is_empty()
+    }
+
+    async fn search(&self, query: &str, _api_key: Option<String>) -> Result<Vec<SearchResult>> {
+        let data: Value = reqwest::Client::new()
+            .get(format!("{}/search", self.base_url))
+            .query(&[("q", query), ("format", "json")])
+            .header("Accept", "application/json")
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        Ok(extract(
+            &data["results"],
+            &Fields::new("/title", "/url", "/content"),
+            self.name(),
+        ))
+    }
+}
+
+// 4. ANY JSON API
+/// Where a [`JsonEndpointProvider`] finds each field of a hit, as JSON
+/// pointers relative to the hit.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct Fields {
+    #[serde(default = "default_title_field", rename = "title_field")]
+    pub title: String,
+    #[serde(default = "default_url_field", rename = "url_field")]
+    pub url: String,
+    #[serde(default = "default_snippet_field", rename = "snippet_field")]
+    pub snippet: String,
+}
+
+impl Fields {
+    pub fn new(title: &str, url: &str, snippet: &str) -> Self {
+        Self {
+            title: title.into(),
+            url: url.into(),
+            snippet: snippet.into(),
+        }
+    }
+}
+
+impl Default for Fields {
+    fn default() -> Self {
+        Self::new("/title", "/url", "/snippet")
+    }
+}
+
+fn default_title_field() -> String {
+    Fields::default().title
+}
+
+fn default_url_field() -> String {
+    Fields::default().url
+}
+
+fn default_snippet_field() -> String {
+    Fields::default().snippet
+}
+
+/// Any HTTP API answering a GET with JSON. `{query}` in the URL is replaced
+/// by the encoded query; the hits are the array at the `results` pointer.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct JsonEndpointProvider {
+    pub name: String,
+    pub url: String,
+    #[serde(default)]
+    pub headers: BTreeMap<String, String>,
+    #[serde(default)]
+    pub results: String,
+    #[serde(flatten)]
+    pub fields: Fields,
+}
+
+impl JsonEndpointProvider {
+    pub fn new(name: impl Into<String>, url: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            url: url.into(),
+            headers: BTreeMap::new(),
+            results: String::new(),
+            fields: Fields::default(),
+        }
+    }
+
+    pub fn header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.insert(name.into(), value.into());
+        self
+    }
+
+    pub fn results(mut self, pointer: impl Into<String>) -> Self {
+        self.results = pointer.into();
+        self
+    }
+
+    pub fn fields(mut self, fields: Fields) -> Self {
+        self.fields = fields;
+        self
+    }
+
+    /// The hits in a response body.
+    pub fn parse(&self, data: &Value) -> Vec<SearchResult> {
+        data.pointer(&self.results)
+            .map(|hits| extract(hits, &self.fields, &self.name))
+            .unwrap_or_default()
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl SearchProvider for JsonEndpointProvider {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn is_available(&self, _has_arg_key: bool) -> bool {
+        true
+    }
+
+    async fn search(&self, query: &str, _api_key: Option<String>) -> Result<Vec<SearchResult>> {
+        let url = self.url.replace("{query}", &urlencoding::encode(query));
+
+        let mut request = reqwest::Client::new()
+            .get(url)
+            .header("Accept", "application/json");
+        for (name, value) in &self.headers {
+            request = request.header(name, value);
         }
 
-        Ok(json!(standard_results))
+        let data: Value = request.send().await?.error_for_status()?.json().await?;
+
+        Ok(self.parse(&data))
+    }
+}
+
+// 5. CANNED RESULTS (OFFLINE/TESTS)
+/// Answers from a JSON file mapping queries to hits, for working offline:
+///
+/// ```json
+/// {
+///     "rust": [{ "title": "Rust", "url": "https://www.rust-lang.org" }],
+///     "*": []
+/// }
+/// ```
+///
+/// Queries match like cache keys, ignoring case and spacing; `*` answers
+/// every other query. The file is read on each search, so it can be edited
+/// while in use.
+pub struct MockSearchProvider {
+    path: PathBuf,
+}
+
+impl MockSearchProvider {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
     }
 }
 
-// 3. DUCKDUCKGO SCRAPER (FALLBACK/FREE)
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl SearchProvider for MockSearchProvider {
+    fn name(&self) -> &str {
+        "Mock"
+    }
+
+    fn is_available(&self, _has_arg_key: bool) -> bool {
+        true
+    }
+
+    async fn search(&self, query: &str, _api_key: Option<String>) -> Result<Vec<SearchResult>> {
+        let json = std::fs::read_to_string(&self.path)
+            .with_context(|| format!("Failed to read {}", self.path.display()))?;
+        let answers: HashMap<String, Vec<SearchResult>> = serde_json::from_str(&json)
+            .with_context(|| format!("Invalid search fixtures in {}", self.path.display()))?;
+
+        let answers: HashMap<String, Vec<SearchResult>> = answers
+            .into_iter()
+            .map(|(query, results)| (search_cache::key(&query), results))
+            .collect();
+
+        let results = answers
+            .get(&search_cache::key(query))
+            .or_else(|| answers.get("*"))
+            .cloned()
+            .unwrap_or_default();
+
+        Ok(results)
+    }
+}
+
+// 6. DUCKDUCKGO SCRAPER (FALLBACK/FREE)
 pub struct DuckDuckGoScraper;
 
+impl DuckDuckGoScraper {
+    /// The hits on a DuckDuckGo HTML results page, ads excluded.
+    pub fn parse(html: &str) -> Vec<SearchResult> {
+        let document = scraper::Html::parse_document(html);
+        let result_selector = scraper::Selector::parse(".result").unwrap();
+        let title_selector = scraper::Selector::parse(".result__a").unwrap();
+        let snippet_selector = scraper::Selector::parse(".result__snippet").unwrap();
+        let url_selector = scraper::Selector::parse(".result__url").unwrap();
+
+        let text = |element: scraper::ElementRef<'_>| {
+            element.text().collect::<String>().trim().to_string()
+        };
+
+        document
+            .select(&result_selector)
+            .filter_map(|element| {
+                let anchor = element.select(&title_selector).next()?;
+                let href = anchor.attr("href").unwrap_or_default();
+
+                if href.contains("ad_domain") {
+                    return None;
+                }
+
+                // Links go through a redirect carrying the target in `uddg`
+                let url =
+                    url::form_urlencoded::parse(href.split('?').nth(1).unwrap_or("").as_bytes())
+                        .find_map(|(key, value)| (key == "uddg").then(|| value.into_owned()))
+                        .or_else(|| url::Url::parse(href).ok().map(String::from))
+                        .or_else(|| {
+                            let shown = text(element.select(&url_selector).next()?);
+                            (!shown.is_empty()).then(|| format!("https://{}", shown))
+                        })?;
+
+                Some(SearchResult {
+                    title: text(anchor),
+                    url,
+                    snippet: element
+                        .select(&snippet_selector)
+                        .next()
+                        .map(text)
+                        .unwrap_or_default(),
+                    source: DuckDuckGoScraper.name().to_string(),
+                })
+            })
+            .filter(|result| !result.title.is_empty())
+            .take(8)
+            .collect()
+    }
+}
+
 #[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
 #[cfg_attr(not(target_arch = "wasm32"), async_trait)]
 impl SearchProvider for DuckDuckGoScraper {
-    fn name(&self) -> &'static str {
+    fn name(&self) -> &str {
         "DuckDuckGo Scraper (Free)"
     }
     fn is_available(&self, _has_arg_key: bool) -> bool {
@@ -133,63 +431,289 @@ impl SearchProvider for DuckDuckGoScraper {
         return true;
     }
 
-    async fn search(&self, query: &str, _api_key: Option<String>) -> Result<Value> {
-        crate::tools::web_search(query).await
+    async fn search(&self, query: &str, _api_key: Option<String>) -> Result<Vec<SearchResult>> {
+        let url = format!(
+            "https://html.duckduckgo.com/html/?q={}",
+            urlencoding::encode(query)
+        );
+
+        let client = reqwest::Client::builder()
+            .user_agent("Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/121.0.0.0 Safari/537.36")
+            .build()?;
+
+        let html = client.get(url)
+            .header("Accept", "text/html,application/xhtml+xml,application/xml;q=0.9,image/avif,image/webp,image/apng,*/*;q=0.8")
+            .header("Accept-Language", "en-US,en;q=0.9")
+            .header("Sec-Fetch-Dest", "document")
+            .header("Sec-Fetch-Mode", "navigate")
+            .header("Sec-Fetch-Site", "none")
+            .header("Sec-Fetch-User", "?1")
+            .header("Upgrade-Insecure-Requests", "1")
+            .send()
+            .await?
+            .text()
+            .await?;
+
+        // Check for "No Results" or blocking message
+        if html.contains("No results.") || html.contains("Robot Check") {
+            log::warn!("🔍 DDG Scraper: No results found or robot check hit.");
+            return Ok(Vec::new());
+        }
+
+        Ok(Self::parse(&html))
+    }
+}
+
+/// Reads the hits in a JSON array, skipping those without a title or URL.
+fn extract(hits: &Value, fields: &Fields, source: &str) -> Vec<SearchResult> {
+    let field = |hit: &Value, pointer: &str| {
+        hit.pointer(pointer)
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+            .trim()
+            .to_string()
+    };
+
+    hits.as_array()
+        .into_iter()
+        .flatten()
+        .map(|hit| SearchResult {
+            title: field(hit, &fields.title),
+            url: field(hit, &fields.url),
+            snippet: field(hit, &fields.snippet),
+            source: source.to_string(),
+        })
+        .filter(|result| !result.title.is_empty() && !result.url.is_empty())
+        .collect()
+}
+
+/// A provider declared in `search.toml`.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ProviderConfig {
+    Searxng { url: String },
+    Json(JsonEndpointProvider),
+    File { path: PathBuf },
+}
+
+impl ProviderConfig {
+    fn into_provider(self) -> Arc<dyn SearchProvider> {
+        match self {
+            ProviderConfig::Searxng { url } => Arc::new(SearxngProvider::new(url)),
+            ProviderConfig::Json(provider) => Arc::new(provider),
+            ProviderConfig::File { path } => {
+                #[cfg(feature = "native")]
+                let path = super::resolve_path(&path.to_string_lossy());
+
+                Arc::new(MockSearchProvider::new(path))
+            }
+        }
     }
 }
 
-// 4. THE ROUTER
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct Config {
+    #[serde(default)]
+    pub providers: Vec<ProviderConfig>,
+    #[serde(default = "default_cache_ttl_secs")]
+    pub cache_ttl_secs: u64,
+}
+
+fn default_cache_ttl_secs() -> u64 {
+    search_cache::DEFAULT_TTL.as_secs()
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            providers: Vec::new(),
+            cache_ttl_secs: default_cache_ttl_secs(),
+        }
+    }
+}
+
+impl Config {
+    pub fn path() -> PathBuf {
+        crate::brain::directory::config().join("search.toml")
+    }
+
+    /// Reads [`Config::path`]; a missing file means the defaults.
+    pub fn load() -> Result<Self> {
+        match std::fs::read_to_string(Self::path()) {
+            Ok(config) => Self::parse(&config),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    pub fn parse(config: &str) -> Result<Self> {
+        toml::from_str(config).context("Invalid search configuration")
+    }
+}
+
+static GLOBAL: LazyLock<RwLock<Arc<SearchRouter>>> = LazyLock::new(|| {
+    let config = Config::load().unwrap_or_else(|e| {
+        log::error!("{:#}; using the default search providers", e);
+        Config::default()
+    });
+
+    RwLock::new(Arc::new(SearchRouter::from_config(config)))
+});
+
+/// The router used by [`web_search_routed`](super::web_search_routed) and
+/// `brain::web::search`.
+pub fn global() -> Arc<SearchRouter> {
+    GLOBAL
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .clone()
+}
+
+/// Replaces the router returned by [`global`].
+pub fn set_global(router: SearchRouter) {
+    *GLOBAL.write().unwrap_or_else(PoisonError::into_inner) = Arc::new(router);
+}
+
+/// Adds `provider` to the [`global`] router, ahead of the others.
+pub fn register(provider: impl SearchProvider + 'static) {
+    let mut global = GLOBAL.write().unwrap_or_else(PoisonError::into_inner);
+    let mut router = SearchRouter::clone(&global);
+    router.register(provider);
+    *global = Arc::new(router);
+}
+
+// 7. THE ROUTER
+#[derive(Clone)]
 pub struct SearchRouter {
-    #[cfg(not(target_arch = "wasm32"))]
-    providers: Vec<Box<dyn SearchProvider + Send + Sync>>,
-    #[cfg(target_arch = "wasm32")]
-    providers: Vec<Box<dyn SearchProvider>>,
+    providers: Vec<Arc<dyn SearchProvider>>,
+    cache: Option<Arc<SearchCache>>,
 }
 
 impl SearchRouter {
+    /// The built-in providers, plus SearXNG when `SEARXNG_URL` is set.
     pub fn new() -> Self {
-        #[cfg(not(target_arch = "wasm32"))]
-        let providers: Vec<Box<dyn SearchProvider + Send + Sync>> = vec![
-            Box::new(BraveSearchProvider),
-            Box::new(TavilySearchProvider),
-            Box::new(DuckDuckGoScraper),
+        let mut providers: Vec<Arc<dyn SearchProvider>> = vec![
+            Arc::new(BraveSearchProvider),
+            Arc::new(TavilySearchProvider),
         ];
 
-        #[cfg(target_arch = "wasm32")]
-        let providers: Vec<Box<dyn SearchProvider>> = vec![
-            Box::new(BraveSearchProvider),
-            Box::new(TavilySearchProvider),
-            Box::new(DuckDuckGoScraper),
-        ];
+        if let Ok(url) = env::var("SEARXNG_URL") {
+            providers.push(Arc::new(SearxngProvider::new(url)));
+        }
 
-        Self { providers }
+        providers.push(Arc::new(DuckDuckGoScraper));
+
+        Self {
+            providers,
+            cache: None,
+        }
     }
 
-    pub async fn execute(
+    /// A router without providers, finding nothing until some are
+    /// registered.
+    pub fn empty() -> Self {
+        Self {
+            providers: Vec::new(),
+            cache: None,
+        }
+    }
+
+    /// The configured providers ahead of the built-in ones, with a
+    /// persistent cache unless its TTL is zero.
+    pub fn from_config(config: Config) -> Self {
+        let mut router = Self::new();
+
+        for provider in config.providers.into_iter().rev() {
+            router.providers.insert(0, provider.into_provider());
+        }
+
+        if config.cache_ttl_secs > 0 {
+            let ttl = Duration::from_secs(config.cache_ttl_secs);
+
+            #[cfg(not(target_arch = "wasm32"))]
+            let cache = SearchCache::open(SearchCache::path(), ttl);
+            #[cfg(target_arch = "wasm32")]
+            let cache = SearchCache::in_memory(ttl);
+
+            router = router.with_cache(cache);
+        }
+
+        router
+    }
+
+    pub fn with_provider(mut self, provider: impl SearchProvider + 'static) -> Self {
+        self.register(provider);
+        self
+    }
+
+    pub fn with_cache(mut self, cache: SearchCache) -> Self {
+        self.cache = Some(Arc::new(cache));
+        self
+    }
+
+    /// Adds `provider` ahead of the others, replacing any provider with the
+    /// same name.
+    pub fn register(&mut self, provider: impl SearchProvider + 'static) {
+        self.unregister(provider.name());
+        self.providers.insert(0, Arc::new(provider));
+    }
+
+    /// Removes the provider called `name`, returning whether there was one.
+    pub fn unregister(&mut self, name: &str) -> bool {
+        let before = self.providers.len();
+        self.providers.retain(|provider| provider.name() != name);
+        self.providers.len() != before
+    }
+
+    /// Provider names, in the order they are tried.
+    pub fn providers(&self) -> Vec<&str> {
+        self.providers
+            .iter()
+            .map(|provider| provider.name())
+            .collect()
+    }
+
+    pub fn cache(&self) -> Option<&SearchCache> {
+        self.cache.as_deref()
+    }
+
+    /// The first non-empty answer of the available providers, from the
+    /// cache while it is fresh.
+    pub async fn search(
         &self,
         query: &str,
         brave_key: Option<String>,
         tavily_key: Option<String>,
-    ) -> Result<Value> {
+    ) -> Result<Vec<SearchResult>> {
+        if let Some(results) = self.cache.as_ref().and_then(|cache| cache.get(query)) {
+            log::info!("🔍 Serving cached results for: {}", query);
+            return Ok(results);
+        }
+
         for provider in &self.providers {
-            let has_arg_key = match provider.name() {
-                "Brave Search" => brave_key.is_some(),
-                "Tavily Search" => tavily_key.is_some(),
-                _ => false,
+            let arg_key = match provider.name() {
+                "Brave Search" => brave_key.clone(),
+                "Tavily Search" => tavily_key.clone(),
+                _ => None,
             };
 
-            if provider.is_available(has_arg_key) {
+            if provider.is_available(arg_key.is_some()) {
                 log::info!("🔍 Attempting search with: {}", provider.name());
-                let arg_key = match provider.name() {
-                    "Brave Search" => brave_key.clone(),
-                    "Tavily Search" => tavily_key.clone(),
-                    _ => None,
-                };
 
                 match provider.search(query, arg_key).await {
-                    Ok(results) => {
+                    Ok(mut results) => {
                         // Ensure it's not an empty result array from an API error
-                        if results.as_array().map(|a| !a.is_empty()).unwrap_or(false) {
+                        if !results.is_empty() {
+                            for result in &mut results {
+                                if result.source.is_empty() {
+                                    result.source = provider.name().to_string();
+                                }
+                            }
+
+                            if let Some(cache) = &self.cache {
+                                cache.insert(query, results.clone());
+                            }
+
                             return Ok(results);
                         }
                         log::warn!(
@@ -212,6 +736,22 @@ impl SearchRouter {
             "All search providers failed or were unavailable."
         ))
     }
+
+    /// [`SearchRouter::search`] with the results as a JSON array.
+    pub async fn execute(
+        &self,
+        query: &str,
+        brave_key: Option<String>,
+        tavily_key: Option<String>,
+    ) -> Result<Value> {
+        Ok(json!(self.search(query, brave_key, tavily_key).await?))
+    }
+}
+
+impl Default for SearchRouter {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 #[cfg(test)]
@@ -250,4 +790,129 @@ mod tests {
         assert_eq!(providers[1].name(), "Tavily Search");
         assert_eq!(providers[2].name(), "DuckDuckGo Scraper (Free)");
     }
+
+    #[cfg(feature = "native")]
+    #[tokio::test]
+    async fn test_registered_providers_answer_first_and_are_cached() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let fixtures = dir.path().join("fixtures.json");
+        std::fs::write(
+            &fixtures,
+            r#"{
+                "Rust Lang": [{ "title": "Rust", "url": "https://www.rust-lang.org" }],
+                "*": []
+            }"#,
+        )?;
+
+        let mut router = SearchRouter::empty()
+            .with_provider(MockSearchProvider::new(&fixtures))
+            .with_cache(SearchCache::in_memory(search_cache::DEFAULT_TTL));
+        assert_eq!(router.providers(), ["Mock"]);
+
+        let results = router.search("rust  lang", None, None).await?;
+        assert_eq!(
+            results,
+            [SearchResult {
+                title: "Rust".into(),
+                url: "https://www.rust-lang.org".into(),
+                snippet: String::new(),
+                source: "Mock".into(),
+            }]
+        );
+
+        // Served from the cache once the fixtures are gone
+        std::fs::remove_file(&fixtures)?;
+        assert_eq!(router.search("Rust Lang", None, None).await?, results);
+
+        // Nothing found anywhere is an error
+        assert!(router.search("other", None, None).await.is_err());
+
+        assert!(router.unregister("Mock"));
+        assert!(router.providers().is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_json_endpoint_fields() {
+        let provider = JsonEndpointProvider::new("Docs", "https://docs.example.org?q={query}")
+            .results("/data/hits")
+            .fields(Fields::new("/name", "/links/0", "/summary"));
+
+        let results = provider.parse(&json!({
+            "data": { "hits": [
+                { "name": "Guide", "links": ["https://docs.example.org/guide"], "summary": "How to" },
+                { "name": "No link" }
+            ] }
+        }));
+
+        assert_eq!(
+            results,
+            [SearchResult {
+                title: "Guide".into(),
+                url: "https://docs.example.org/guide".into(),
+                snippet: "How to".into(),
+                source: "Docs".into(),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_config_declares_providers() -> Result<()> {
+        let config = Config::parse(
+            r#"
+            cache_ttl_secs = 0
+
+            [[providers]]
+            kind = "searxng"
+            url = "https://searx.example.org/"
+
+            [[providers]]
+            kind = "json"
+            name = "Docs"
+            url = "https://docs.example.org?q={query}"
+            results = "/hits"
+            url_field = "/href"
+            "#,
+        )?;
+
+        assert_eq!(
+            config.providers[1],
+            ProviderConfig::Json(
+                JsonEndpointProvider::new("Docs", "https://docs.example.org?q={query}")
+                    .results("/hits")
+                    .fields(Fields::new("/title", "/href", "/snippet"))
+            )
+        );
+
+        let router = SearchRouter::from_config(config);
+        assert_eq!(router.providers()[..2], ["SearXNG", "Docs"]);
+        assert!(router.cache().is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_duckduckgo_results_are_decoded() {
+        let html = r#"
+            <div class="result">
+                <a class="result__a" href="//duckduckgo.com/l/?uddg=https%3A%2F%2Fwww.rust-lang.org%2F&rut=abc">Rust Programming Language</a>
+                <a class="result__snippet">A language empowering everyone.</a>
+                <a class="result__url">www.rust-lang.org</a>
+            </div>
+            <div class="result">
+                <a class="result__a" href="//duckduckgo.com/y.js?ad_domain=example.com">Ad</a>
+            </div>
+        "#;
+
+        assert_eq!(
+            DuckDuckGoScraper::parse(html),
+            [SearchResult {
+                title: "Rust Programming Language".into(),
+                url: "https://www.rust-lang.org/".into(),
+                snippet: "A language empowering everyone.".into(),
+                source: "DuckDuckGo Scraper (Free)".into(),
+            }]
+        );
+    }
 }
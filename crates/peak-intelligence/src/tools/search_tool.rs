@@ -1,9 +1,27 @@
-use crate::tools::{web_search_routed, IntelligenceTool};
+use crate::tools::search_router::{self, SearchResult, SearchRouter};
+use crate::tools::IntelligenceTool;
 use anyhow::Result;
 use async_trait::async_trait;
 use serde_json::{json, Value};
+use std::sync::Arc;
 
-pub struct WebSearchTool;
+/// Longest snippet taken from a page's content, in characters.
+const EXCERPT_LEN: usize = 240;
+
+/// Searches with a given router, or with the global one at the time of
+/// each call.
+#[derive(Default)]
+pub struct WebSearchTool {
+    router: Option<Arc<SearchRouter>>,
+}
+
+impl WebSearchTool {
+    pub fn new(router: SearchRouter) -> Self {
+        Self {
+            router: Some(Arc::new(router)),
+        }
+    }
+}
 
 #[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
 #[cfg_attr(not(target_arch = "wasm32"), async_trait)]
@@ -20,7 +38,8 @@ impl IntelligenceTool for WebSearchTool {
             "properties": {
                 "query": { "type": "string", "description": "The search query" },
                 "brave_key": { "type": "string", "description": "Optional Brave Search API key" },
-                "tavily_key": { "type": "string", "description": "Optional Tavily Search API key" }
+                "tavily_key": { "type": "string", "description": "Optional Tavily Search API key" },
+                "excerpts": { "type": "boolean", "description": "Fill missing snippets from the pages themselves (slower)" }
             },
             "required": ["query"]
         })
@@ -38,6 +57,114 @@ impl IntelligenceTool for WebSearchTool {
             .get("tavily_key")
             .and_then(|v| v.as_str())
             .map(|s| s.to_string());
-        web_search_routed(query, brave_key, tavily_key).await
+        let router = self.router.clone().unwrap_or_else(search_router::global);
+        let mut results = router.search(query, brave_key, tavily_key).await?;
+
+        if args
+            .get("excerpts")
+            .and_then(Value::as_bool)
+            .unwrap_or(false)
+        {
+            fill_snippets(&mut results).await;
+        }
+
+        Ok(json!(results))
+    }
+}
+
+/// Reads the pages of results without a snippet and takes the start of
+/// their main content instead. Pages that fail to load keep theirs empty.
+async fn fill_snippets(results: &mut [SearchResult]) {
+    let excerpts = futures::future::join_all(results.iter().map(|result| async move {
+        if !result.snippet.is_empty() {
+            return None;
+        }
+
+        let url = crate::brain::Url::parse(&result.url).ok()?;
+        match crate::brain::web::read(url).await {
+            Ok(article) => Some(article.excerpt(EXCERPT_LEN)),
+            Err(e) => {
+                log::warn!("Failed to read {} for a snippet: {}", result.url, e);
+                None
+            }
+        }
+    }))
+    .await;
+
+    for (result, excerpt) in results.iter_mut().zip(excerpts) {
+        if let Some(excerpt) = excerpt {
+            result.snippet = excerpt;
+        }
+    }
+}
+
+#[cfg(all(test, feature = "native"))]
+mod tests {
+    use super::*;
+    use crate::tools::search_router::MockSearchProvider;
+
+    #[tokio::test]
+    async fn test_web_search_offline() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let fixtures = dir.path().join("fixtures.json");
+        std::fs::write(
+            &fixtures,
+            r#"{ "*": [{ "title": "Rust", "url": "https://www.rust-lang.org", "snippet": "Fast" }] }"#,
+        )?;
+
+        let tool = WebSearchTool::new(
+            SearchRouter::empty().with_provider(MockSearchProvider::new(&fixtures)),
+        );
+
+        let results = tool.execute(json!({ "query": "anything" })).await?;
+        assert_eq!(
+            results,
+            json!([{
+                "title": "Rust",
+                "url": "https://www.rust-lang.org",
+                "snippet": "Fast",
+                "source": "Mock"
+            }])
+        );
+
+        assert!(tool.execute(json!({})).await.is_err());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_excerpts_fill_missing_snippets() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let page = dir.path().join("page.html");
+        std::fs::write(
+            &page,
+            "<html><body><nav>Home</nav><article><h1>Title</h1>\
+             <p>The main content of the page, long enough to be an excerpt.</p>\
+             </article></body></html>",
+        )?;
+
+        let url = crate::brain::Url::from_file_path(&page).unwrap();
+        let fixtures = dir.path().join("fixtures.json");
+        std::fs::write(
+            &fixtures,
+            json!({ "*": [{ "title": "Page", "url": url.as_str() }] }).to_string(),
+        )?;
+
+        let tool = WebSearchTool::new(
+            SearchRouter::empty().with_provider(MockSearchProvider::new(&fixtures)),
+        );
+
+        let results = tool.execute(json!({ "query": "page" })).await?;
+        assert_eq!(results[0]["snippet"], "");
+
+        let results = tool
+            .execute(json!({ "query": "page", "excerpts": true }))
+            .await?;
+        assert_eq!(
+            results[0]["snippet"],
+            "The main content of the page, long enough to be an excerpt."
+        );
+
+        Ok(())
     }
 }
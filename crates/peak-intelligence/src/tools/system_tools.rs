@@ -1,3 +1,4 @@
+use crate::tools::command::{self, Request};
 use crate::tools::{get_system_snapshot, kill_process, list_processes, IntelligenceTool};
 use anyhow::Result;
 use async_trait::async_trait;
@@ -42,6 +43,12 @@ impl IntelligenceTool for KillProcessTool {
             "required": ["pid"]
         })
     }
+    fn requires_approval(&self) -> bool {
+        true
+    }
+    fn describe_call(&self, args: &Value) -> String {
+        format!("Kill process {}", args["pid"].as_str().unwrap_or("?"))
+    }
     async fn execute(&self, args: Value) -> Result<Value> {
         let pid = args
             .get("pid")
@@ -70,6 +77,73 @@ impl IntelligenceTool for SystemSnapshotTool {
     }
 }
 
+pub struct RunCommandTool;
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl IntelligenceTool for RunCommandTool {
+    fn name(&self) -> &'static str {
+        "run_command"
+    }
+    fn description(&self) -> &'static str {
+        "Run a program to completion without a shell and return its stdout, stderr and exit code. Use it for builds and tests; use the terminal tools for interactive programs."
+    }
+    fn input_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "argv": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "Program followed by its arguments, e.g. [\"cargo\", \"test\"]"
+                },
+                "cwd": { "type": "string", "description": "Working directory" },
+                "env": {
+                    "type": "object",
+                    "additionalProperties": { "type": "string" },
+                    "description": "Extra environment variables"
+                },
+                "clear_env": { "type": "boolean", "description": "Do not inherit the environment" },
+                "stdin": { "type": "string", "description": "Text written to the program's stdin" },
+                "timeout_ms": {
+                    "type": "integer",
+                    "description": format!(
+                        "Kill the program after this long (default {}, max {})",
+                        command::DEFAULT_TIMEOUT_MS,
+                        command::MAX_TIMEOUT_MS
+                    )
+                },
+                "max_output_bytes": {
+                    "type": "integer",
+                    "description": format!(
+                        "Bytes kept per stream (default {}, max {})",
+                        command::DEFAULT_OUTPUT_BYTES,
+                        command::MAX_OUTPUT_BYTES
+                    )
+                }
+            },
+            "required": ["argv"]
+        })
+    }
+    fn requires_approval(&self) -> bool {
+        true
+    }
+    fn describe_call(&self, args: &Value) -> String {
+        let Ok(request) = serde_json::from_value::<Request>(args.clone()) else {
+            return format!("Run a command with {}", args);
+        };
+
+        match &request.cwd {
+            Some(cwd) => format!("Run `{}` in {}", request.command_line(), cwd),
+            None => format!("Run `{}`", request.command_line()),
+        }
+    }
+    async fn execute(&self, args: Value) -> Result<Value> {
+        let request: Request = serde_json::from_value(args)?;
+        Ok(json!(command::run(&request).await?))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -88,4 +162,25 @@ mod tests {
 
         Ok(())
     }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_run_command_tool() -> Result<()> {
+        let result = RunCommandTool
+            .execute(json!({ "argv": ["sh", "-c", "echo ok; exit 2"] }))
+            .await?;
+        assert_eq!(result["stdout"], "ok\n");
+        assert_eq!(result["exit_code"], 2);
+        assert_eq!(result["success"], false);
+
+        assert!(RunCommandTool.execute(json!({})).await.is_err());
+        assert!(RunCommandTool.requires_approval());
+        assert_eq!(
+            RunCommandTool
+                .describe_call(&json!({ "argv": ["git", "commit", "-m", "a b"], "cwd": "~/src" })),
+            r#"Run `git commit -m "a b"` in ~/src"#
+        );
+
+        Ok(())
+    }
 }
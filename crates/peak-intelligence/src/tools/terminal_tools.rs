@@ -5,6 +5,15 @@ use async_trait::async_trait;
 use serde_json::{json, Value};
 use tokio::sync::mpsc;
 
+/// Schema of the `session` argument shared by the tools.
+fn session_schema() -> Value {
+    json!({ "type": "string", "description": "Session id or name; defaults to the latest session" })
+}
+
+fn session(args: &Value) -> Option<&str> {
+    args.get("session").and_then(|v| v.as_str())
+}
+
 pub struct TerminalOpenTool {
     pub manager: &'static TerminalManager,
     pub tx: mpsc::Sender<String>,
@@ -17,21 +26,23 @@ impl IntelligenceTool for TerminalOpenTool {
         "terminal_open"
     }
     fn description(&self) -> &'static str {
-        "Open a new terminal PTY session."
+        "Open a new terminal PTY session running a shell. Returns its session id; it becomes the default session for the other terminal tools."
     }
     fn input_schema(&self) -> Value {
         json!({
             "type": "object",
             "properties": {
+                "name": { "type": "string", "description": "Optional name to refer to the session by" },
                 "rows": { "type": "number", "default": 24 },
                 "cols": { "type": "number", "default": 80 }
             }
         })
     }
     async fn execute(&self, args: Value) -> Result<Value> {
+        let name = args.get("name").and_then(|v| v.as_str()).map(str::to_owned);
         let rows = args.get("rows").and_then(|v| v.as_u64()).unwrap_or(24) as u16;
         let cols = args.get("cols").and_then(|v| v.as_u64()).unwrap_or(80) as u16;
-        self.manager.open(rows, cols, self.tx.clone())
+        self.manager.open(name, rows, cols, self.tx.clone())
     }
 }
 
@@ -46,20 +57,31 @@ impl IntelligenceTool for TerminalWriteTool {
         "terminal_write"
     }
     fn description(&self) -> &'static str {
-        "Write data to the active terminal."
+        "Write data to a terminal session, the latest one by default. Include '\\n' to run a command."
     }
     fn input_schema(&self) -> Value {
         json!({
             "type": "object",
             "properties": {
+                "session": session_schema(),
                 "data": { "type": "string" }
             },
             "required": ["data"]
         })
     }
+    fn requires_approval(&self) -> bool {
+        true
+    }
+    fn describe_call(&self, args: &Value) -> String {
+        let data = args.get("data").and_then(|v| v.as_str()).unwrap_or("");
+        match session(args) {
+            Some(session) => format!("Type {:?} into terminal '{}'", data, session),
+            None => format!("Type {:?} into the latest terminal", data),
+        }
+    }
     async fn execute(&self, args: Value) -> Result<Value> {
         let data = args.get("data").and_then(|v| v.as_str()).unwrap_or("");
-        self.manager.write(data)
+        self.manager.write(session(&args), data)
     }
 }
 
@@ -74,12 +96,13 @@ impl IntelligenceTool for TerminalResizeTool {
         "terminal_resize"
     }
     fn description(&self) -> &'static str {
-        "Resize the active terminal."
+        "Resize a terminal session, the latest one by default."
     }
     fn input_schema(&self) -> Value {
         json!({
             "type": "object",
             "properties": {
+                "session": session_schema(),
                 "rows": { "type": "number" },
                 "cols": { "type": "number" }
             },
@@ -95,6 +118,83 @@ impl IntelligenceTool for TerminalResizeTool {
             .get("cols")
             .and_then(|v| v.as_u64())
             .ok_or_else(|| anyhow::anyhow!("Missing 'cols'"))? as u16;
-        self.manager.resize(rows, cols)
+        self.manager.resize(session(&args), rows, cols)
+    }
+}
+
+pub struct TerminalScreenTool {
+    pub manager: &'static TerminalManager,
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl IntelligenceTool for TerminalScreenTool {
+    fn name(&self) -> &'static str {
+        "terminal_screen"
+    }
+    fn description(&self) -> &'static str {
+        "Read what a terminal session currently shows, as plain text, with the cursor position, exit status and optionally the lines that scrolled off."
+    }
+    fn input_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "session": session_schema(),
+                "scrollback": { "type": "number", "default": 0, "description": "Lines of scrollback to include" }
+            }
+        })
+    }
+    async fn execute(&self, args: Value) -> Result<Value> {
+        let scrollback = args.get("scrollback").and_then(|v| v.as_u64()).unwrap_or(0);
+        self.manager.screen(session(&args), scrollback as usize)
+    }
+}
+
+pub struct TerminalListTool {
+    pub manager: &'static TerminalManager,
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl IntelligenceTool for TerminalListTool {
+    fn name(&self) -> &'static str {
+        "terminal_list"
+    }
+    fn description(&self) -> &'static str {
+        "List the open terminal sessions with their size and exit status."
+    }
+    fn input_schema(&self) -> Value {
+        json!({ "type": "object", "properties": {} })
+    }
+    async fn execute(&self, _args: Value) -> Result<Value> {
+        self.manager.list()
+    }
+}
+
+pub struct TerminalCloseTool {
+    pub manager: &'static TerminalManager,
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl IntelligenceTool for TerminalCloseTool {
+    fn name(&self) -> &'static str {
+        "terminal_close"
+    }
+    fn description(&self) -> &'static str {
+        "Close a terminal session, killing its shell if it is still running."
+    }
+    fn input_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "session": session_schema()
+            },
+            "required": ["session"]
+        })
+    }
+    async fn execute(&self, args: Value) -> Result<Value> {
+        let session = session(&args).ok_or_else(|| anyhow::anyhow!("Missing 'session'"))?;
+        self.manager.close(session)
     }
 }
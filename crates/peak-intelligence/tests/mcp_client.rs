@@ -0,0 +1,70 @@
+#![cfg(feature = "native")]
+use peak_intelligence::mcp::client::{self, Client, ServerConfig};
+use peak_intelligence::tools::registry::ToolRegistry;
+use serde_json::json;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+fn fixture() -> ServerConfig {
+    ServerConfig::Stdio {
+        command: env!("CARGO_BIN_EXE_mcp_fixture").to_string(),
+        args: Vec::new(),
+        env: BTreeMap::new(),
+    }
+}
+
+#[tokio::test]
+async fn test_stdio_server_tools_are_namespaced() {
+    let client = Arc::new(Client::connect("fixture", &fixture()).await.unwrap());
+
+    let mut registry = ToolRegistry::new();
+    assert_eq!(
+        client::register_tools(&mut registry, &client)
+            .await
+            .unwrap(),
+        3
+    );
+
+    let mut names: Vec<_> = registry
+        .list_tools()
+        .iter()
+        .map(|tool| tool["name"].as_str().unwrap().to_string())
+        .collect();
+    names.sort();
+    assert_eq!(names, ["fixture__add", "fixture__echo", "fixture__fail"]);
+
+    let sum = registry
+        .call("fixture__add", json!({ "a": 2, "b": 40 }))
+        .await
+        .unwrap();
+    assert_eq!(sum, json!(42));
+
+    let echo = registry
+        .call("fixture__echo", json!({ "ticket": "PEAK-1" }))
+        .await
+        .unwrap();
+    assert_eq!(echo, json!({ "ticket": "PEAK-1" }));
+
+    let error = registry.call("fixture__fail", json!({})).await.unwrap_err();
+    assert_eq!(error.to_string(), "Error: fixture failure");
+}
+
+#[tokio::test]
+async fn test_connect_all_reports_broken_servers() {
+    let mut servers = BTreeMap::new();
+    servers.insert("fixture".to_string(), fixture());
+    servers.insert(
+        "missing".to_string(),
+        ServerConfig::Stdio {
+            command: "peak-mcp-server-that-does-not-exist".into(),
+            args: Vec::new(),
+            env: BTreeMap::new(),
+        },
+    );
+
+    let connections = client::connect_all(&client::Config { servers }).await;
+
+    assert_eq!(connections.len(), 2);
+    assert!(connections[0].1.is_ok());
+    assert!(connections[1].1.is_err());
+}
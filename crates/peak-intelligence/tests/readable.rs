@@ -0,0 +1,79 @@
+#![cfg(feature = "native")]
+use peak_intelligence::brain::readable;
+use peak_intelligence::brain::web;
+use peak_intelligence::brain::Url;
+
+fn fixture(name: &str) -> Url {
+    let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(name);
+
+    Url::from_file_path(path).unwrap()
+}
+
+#[tokio::test]
+async fn test_article_is_extracted_from_a_fixture() {
+    let article = web::read(fixture("article.html")).await.unwrap();
+
+    assert_eq!(article.title.as_deref(), Some("Ring Buffers in Rust"));
+    assert_eq!(
+        article.markdown,
+        "# Ring Buffers in Rust\n\n\
+         By a contributor, in [Rust](file:///tags/rust)\n\n\
+         A ring buffer keeps the most recent values in a fixed amount of memory, \
+         overwriting the oldest entry once it is full. It suits telemetry, audio and \
+         logs, where new data matters more than old data.\n\n\
+         ## Why not a `VecDeque`?\n\n\
+         A `VecDeque` grows without bound unless you pop from it yourself, \
+         while a ring buffer enforces its capacity, which makes memory use predictable.\n\n\
+         - Constant memory, whatever the input rate\n\
+         - Cheap pushes, with no reallocation\n\
+         - Iteration from oldest to newest\n  \
+         - or newest to oldest, if you prefer\n\n\
+         ## Implementation\n\n\
+         The buffer stores a head index and a length, and wraps both around the \
+         capacity, as shown below.\n\n\
+         ```rust\n\
+         pub fn push(&mut self, value: T) {\n    \
+         let index = (self.head + self.len) % self.capacity;\n    \
+         self.items[index] = value;\n\
+         }\n\
+         ```\n\n\
+         | Operation | Cost |\n\
+         | --- | --- |\n\
+         | push | O(1) |\n\
+         | iterate | O(n) |"
+    );
+}
+
+#[tokio::test]
+async fn test_fixture_chunks_cite_back_into_the_page() {
+    let article = web::read(fixture("article.html")).await.unwrap();
+    let chunks = article.chunks(400);
+
+    assert!(chunks.len() > 1);
+    assert!(chunks.iter().all(|chunk| chunk.text.len() <= 400));
+    for chunk in &chunks {
+        assert_eq!(&article.markdown[chunk.start..chunk.end], chunk.text);
+    }
+
+    let implementation = chunks
+        .iter()
+        .find(|chunk| chunk.text.starts_with("## Implementation"))
+        .unwrap();
+    assert!(implementation.text.contains("```rust"));
+
+    assert_eq!(
+        article.excerpt(80),
+        "A ring buffer keeps the most recent values in a fixed amount of memory…"
+    );
+}
+
+#[test]
+fn test_missing_content_yields_an_empty_article() {
+    let article = readable::extract("<html><body><nav>Menu</nav></body></html>", None);
+
+    assert_eq!(article.title, None);
+    assert_eq!(article.markdown, "");
+    assert!(article.chunks(100).is_empty());
+}
@@ -180,6 +180,23 @@ impl Backend for AIBackend {
             .with_content(format!("{:?}", color))
     }
 
+    fn plot<Message: 'static + Send + Sync>(
+        plot: super::Plot,
+        _width: Length,
+        _height: Length,
+        _context: &Context,
+    ) -> Self::AnyView<Message> {
+        let tooltips: Vec<&str> = plot
+            .hovers
+            .iter()
+            .map(|region| region.tooltip.as_str())
+            .collect();
+
+        SemanticNode::new("plot")
+            .with_label(format!("{} shapes", plot.shapes.len()))
+            .with_content(tooltips.join("\n"))
+    }
+
     fn capsule<Message: 'static + Send + Sync>(
         _width: Length,
         _height: Length,
@@ -39,6 +39,174 @@ impl IcedBackend {
     }
 }
 
+impl IcedBackend {
+    /// A styled scrollable, shared by the scroll views.
+    fn scrollable<Message: 'static + Send + Sync>(
+        content: iced::Element<'static, Message, Theme, Renderer>,
+        width: Length,
+        height: Length,
+        id: Option<&'static str>,
+        show_indicators: bool,
+        direction: crate::style::ScrollDirection,
+        context: &Context,
+    ) -> iced::widget::Scrollable<'static, Message, Theme, Renderer> {
+        let text_color = context.theme.colors.text_primary;
+
+        let final_height = if context.is_inside_scrollable
+            && height == Length::Fill
+            && direction != crate::style::ScrollDirection::Horizontal
+        {
+            Length::Fixed(300.0)
+        } else {
+            height
+        };
+
+        let mut scroll = iced::widget::scrollable(content)
+            .width(width)
+            .height(final_height)
+            .direction(match direction {
+                crate::style::ScrollDirection::Vertical => {
+                    iced::widget::scrollable::Direction::Vertical(
+                        iced::widget::scrollable::Scrollbar::default()
+                            .width(4.0)
+                            .margin(0.0)
+                            .scroller_width(4.0),
+                    )
+                }
+                crate::style::ScrollDirection::Horizontal => {
+                    iced::widget::scrollable::Direction::Horizontal(
+                        iced::widget::scrollable::Scrollbar::default()
+                            .width(4.0)
+                            .margin(0.0)
+                            .scroller_width(4.0),
+                    )
+                }
+                crate::style::ScrollDirection::Both => iced::widget::scrollable::Direction::Both {
+                    vertical: iced::widget::scrollable::Scrollbar::default()
+                        .width(4.0)
+                        .margin(0.0)
+                        .scroller_width(4.0),
+                    horizontal: iced::widget::scrollable::Scrollbar::default()
+                        .width(4.0)
+                        .margin(0.0)
+                        .scroller_width(4.0),
+                },
+            });
+
+        scroll = scroll.style(move |_, status| {
+            if !show_indicators {
+                iced::widget::scrollable::Style {
+                    container: iced::widget::container::Style::default(),
+                    vertical_rail: iced::widget::scrollable::Rail {
+                        background: None,
+                        border: iced::Border::default(),
+                        scroller: iced::widget::scrollable::Scroller {
+                            background: iced::Color::TRANSPARENT.into(),
+                            border: iced::Border::default(),
+                        },
+                    },
+                    horizontal_rail: iced::widget::scrollable::Rail {
+                        background: None,
+                        border: iced::Border::default(),
+                        scroller: iced::widget::scrollable::Scroller {
+                            background: iced::Color::TRANSPARENT.into(),
+                            border: iced::Border::default(),
+                        },
+                    },
+                    gap: None,
+                    auto_scroll: iced::widget::scrollable::AutoScroll {
+                        background: iced::Background::Color(iced::Color::TRANSPARENT),
+                        border: iced::Border::default(),
+                        shadow: iced::Shadow::default(),
+                        icon: iced::Color::TRANSPARENT,
+                    },
+                }
+            } else {
+                let scroller_alpha = match status {
+                    iced::widget::scrollable::Status::Hovered { .. } => 0.4,
+                    iced::widget::scrollable::Status::Dragged { .. } => 0.6,
+                    _ => 0.15,
+                };
+
+                iced::widget::scrollable::Style {
+                    container: iced::widget::container::Style::default(),
+                    vertical_rail: iced::widget::scrollable::Rail {
+                        background: None,
+                        border: iced::Border::default(),
+                        scroller: iced::widget::scrollable::Scroller {
+                            background: iced::Color {
+                                a: scroller_alpha,
+                                ..text_color
+                            }
+                            .into(),
+                            border: iced::Border {
+                                radius: 2.0.into(),
+                                width: 0.0,
+                                ..Default::default()
+                            },
+                        },
+                    },
+                    horizontal_rail: iced::widget::scrollable::Rail {
+                        background: None,
+                        border: iced::Border::default(),
+                        scroller: iced::widget::scrollable::Scroller {
+                            background: iced::Color {
+                                a: scroller_alpha,
+                                ..text_color
+                            }
+                            .into(),
+                            border: iced::Border {
+                                radius: 2.0.into(),
+                                width: 0.0,
+                                ..Default::default()
+                            },
+                        },
+                    },
+                    gap: None,
+                    auto_scroll: iced::widget::scrollable::AutoScroll {
+                        background: iced::Background::Color(iced::Color::TRANSPARENT),
+                        border: iced::Border::default(),
+                        shadow: iced::Shadow::default(),
+                        icon: iced::Color::TRANSPARENT,
+                    },
+                }
+            }
+        });
+
+        if let Some(id_val) = id {
+            scroll = scroll.id(Id::new(id_val));
+        }
+        scroll
+    }
+
+    fn spans(spans: Vec<TextSpan>, scale: f32) -> Vec<iced::advanced::text::Span<'static, String>> {
+        use iced::advanced::text::Span;
+
+        spans
+            .into_iter()
+            .map(|s| {
+                let mut span = Span::new(s.content)
+                    .strikethrough(s.is_strikethrough)
+                    .underline(s.link.is_some())
+                    .link_maybe(s.link);
+                if let Some(c) = s.color {
+                    span = span.color(Self::apply_gamma(c, 2.2));
+                }
+                if let Some(f) = s.font {
+                    span = span.font(f);
+                }
+                if let Some(sz) = s.size {
+                    span = span.size(sz * scale);
+                }
+                if let Some(bg) = s.background {
+                    span = span.background(Self::apply_gamma(bg, 2.2));
+                }
+                span
+            })
+            .collect()
+    }
+}
+
 impl Backend for IcedBackend {
     type AnyView<Message: 'static + Send + Sync> = iced::Element<'static, Message, Theme, Renderer>;
 
@@ -181,33 +349,34 @@ impl Backend for IcedBackend {
         alignment: Alignment,
         context: &Context,
     ) -> Self::AnyView<Message> {
-        use iced::advanced::text::Span;
         use iced::widget::rich_text;
 
         let scale = context.theme.scaling;
-        let scaled_size = size * scale;
 
-        let iced_spans: Vec<Span<'static, ()>> = spans
-            .into_iter()
-            .map(|s| {
-                let mut span = Span::new(s.content.to_string());
-                if let Some(c) = s.color {
-                    span = span.color(Self::apply_gamma(c, 2.2));
-                }
-                if let Some(f) = s.font {
-                    span = span.font(f);
-                }
-                if let Some(sz) = s.size {
-                    span = span.size(sz * scale);
-                }
-                span
-            })
-            .collect();
+        rich_text(Self::spans(spans, scale))
+            .size(size * scale)
+            .width(width)
+            .align_x(alignment)
+            .into()
+    }
+
+    fn rich_text_with_links<Message: Clone + 'static + Send + Sync>(
+        spans: Vec<TextSpan>,
+        size: f32,
+        width: Length,
+        alignment: Alignment,
+        on_link: Arc<dyn Fn(String) -> Message + Send + Sync>,
+        context: &Context,
+    ) -> Self::AnyView<Message> {
+        use iced::widget::rich_text;
+
+        let scale = context.theme.scaling;
 
-        rich_text(iced_spans)
-            .size(scaled_size)
+        rich_text(Self::spans(spans, scale))
+            .size(size * scale)
             .width(width)
             .align_x(alignment)
+            .on_link_click(move |link| on_link(link))
             .into()
     }
 
@@ -509,6 +678,322 @@ impl Backend for IcedBackend {
         .into()
     }
 
+    fn plot<Message: 'static + Send + Sync>(
+        plot: super::Plot,
+        width: Length,
+        height: Length,
+        context: &Context,
+    ) -> Self::AnyView<Message> {
+        use super::{Plot, Shape};
+        use iced::widget::canvas::{self, Canvas, Frame, Geometry, Path, Program, Stroke};
+        use iced::{alignment, mouse, Point, Rectangle, Size};
+        use std::cell::Cell;
+
+        struct PlotProgram {
+            plot: Plot,
+            scale: f32,
+            highlight: Color,
+            tooltip_background: Color,
+            tooltip_border: Color,
+            tooltip_text: Color,
+        }
+
+        #[derive(Default)]
+        struct PlotState {
+            hovered: Option<usize>,
+            /// The plot as it was when the cursor entered, if it pauses.
+            paused: Option<Plot>,
+            /// The shapes of the plot with the cached revision, at a scale.
+            cache: canvas::Cache,
+            revision: Cell<Option<(u64, u32)>>,
+        }
+
+        impl PlotProgram {
+            /// What to show: the paused plot while there is one.
+            fn shown<'a>(&'a self, state: &'a PlotState) -> &'a Plot {
+                state.paused.as_ref().unwrap_or(&self.plot)
+            }
+
+            /// Where the viewport is stretched to, inside the padding.
+            fn area(&self, plot: &Plot, size: Size) -> Rectangle {
+                let padding = plot.padding;
+                Rectangle {
+                    x: padding.left * self.scale,
+                    y: padding.top * self.scale,
+                    width: (size.width - (padding.left + padding.right) * self.scale).max(0.0),
+                    height: (size.height - (padding.top + padding.bottom) * self.scale).max(0.0),
+                }
+            }
+
+            fn to_screen(plot: &Plot, area: Rectangle, point: Point) -> Point {
+                let viewport = plot.viewport;
+                Point::new(
+                    area.x + point.x / viewport.width * area.width,
+                    area.y + point.y / viewport.height * area.height,
+                )
+            }
+
+            fn to_viewport(plot: &Plot, area: Rectangle, point: Point) -> Point {
+                let viewport = plot.viewport;
+                Point::new(
+                    (point.x - area.x) / area.width.max(1.0) * viewport.width,
+                    (point.y - area.y) / area.height.max(1.0) * viewport.height,
+                )
+            }
+
+            fn rect(plot: &Plot, area: Rectangle, a: Point, b: Point) -> (Point, Size) {
+                let a = Self::to_screen(plot, area, a);
+                let b = Self::to_screen(plot, area, b);
+                (
+                    Point::new(a.x.min(b.x), a.y.min(b.y)),
+                    Size::new((a.x - b.x).abs(), (a.y - b.y).abs()),
+                )
+            }
+
+            fn polyline(plot: &Plot, area: Rectangle, points: &[Point], close: bool) -> Path {
+                Path::new(|p| {
+                    if let Some((first, rest)) = points.split_first() {
+                        p.move_to(Self::to_screen(plot, area, *first));
+                        for point in rest {
+                            p.line_to(Self::to_screen(plot, area, *point));
+                        }
+                        if close {
+                            p.close();
+                        }
+                    }
+                })
+            }
+
+            fn draw_shapes(&self, plot: &Plot, frame: &mut Frame) {
+                let area = self.area(plot, frame.size());
+
+                for shape in &plot.shapes {
+                    match shape {
+                        Shape::Line {
+                            points,
+                            color,
+                            width,
+                        } => frame.stroke(
+                            &Self::polyline(plot, area, points, false),
+                            Stroke {
+                                style: canvas::Style::Solid(*color),
+                                width: width * self.scale,
+                                line_cap: canvas::LineCap::Round,
+                                line_join: canvas::LineJoin::Round,
+                                ..Default::default()
+                            },
+                        ),
+                        Shape::Area { points, color } => {
+                            frame.fill(&Self::polyline(plot, area, points, true), *color)
+                        }
+                        Shape::Rect {
+                            top_left,
+                            bottom_right,
+                            color,
+                        } => {
+                            let (top_left, size) = Self::rect(plot, area, *top_left, *bottom_right);
+                            frame.fill_rectangle(top_left, size, *color);
+                        }
+                        Shape::Dot {
+                            center,
+                            radius,
+                            color,
+                        } => frame.fill(
+                            &Path::circle(
+                                Self::to_screen(plot, area, *center),
+                                radius * self.scale,
+                            ),
+                            *color,
+                        ),
+                        Shape::Text {
+                            at,
+                            offset,
+                            content,
+                            size,
+                            color,
+                            align_x,
+                            align_y,
+                        } => frame.fill_text(canvas::Text {
+                            content: content.clone(),
+                            position: Self::to_screen(plot, area, *at) + *offset * self.scale,
+                            color: *color,
+                            size: (size * self.scale).into(),
+                            align_x: match align_x {
+                                Alignment::Start => iced::advanced::text::Alignment::Left,
+                                Alignment::Center => iced::advanced::text::Alignment::Center,
+                                Alignment::End => iced::advanced::text::Alignment::Right,
+                            },
+                            align_y: match align_y {
+                                Alignment::Start => alignment::Vertical::Top,
+                                Alignment::Center => alignment::Vertical::Center,
+                                Alignment::End => alignment::Vertical::Bottom,
+                            },
+                            ..Default::default()
+                        }),
+                    }
+                }
+            }
+
+            fn draw_tooltip(
+                &self,
+                frame: &mut Frame,
+                tooltip: &str,
+                position: Point,
+                bounds: Size,
+            ) {
+                let text_size = 12.0 * self.scale;
+                let line_height = text_size * 1.3;
+                let inset = 8.0 * self.scale;
+                let lines: Vec<&str> = tooltip.lines().collect();
+                let longest = lines.iter().map(|l| l.chars().count()).max().unwrap_or(0);
+                let size = Size::new(
+                    longest as f32 * text_size * 0.6 + inset * 2.0,
+                    lines.len() as f32 * line_height + inset * 2.0,
+                );
+
+                // Beside the cursor, flipped when it would leave the plot
+                let gap = 12.0 * self.scale;
+                let mut x = position.x + gap;
+                if x + size.width > bounds.width {
+                    x = (position.x - gap - size.width).max(0.0);
+                }
+                let mut y = position.y + gap;
+                if y + size.height > bounds.height {
+                    y = (position.y - gap - size.height).max(0.0);
+                }
+
+                let background =
+                    Path::rounded_rectangle(Point::new(x, y), size, (6.0 * self.scale).into());
+                frame.fill(&background, self.tooltip_background);
+                frame.stroke(
+                    &background,
+                    Stroke::default()
+                        .with_color(self.tooltip_border)
+                        .with_width(1.0),
+                );
+                for (i, line) in lines.iter().enumerate() {
+                    frame.fill_text(canvas::Text {
+                        content: line.to_string(),
+                        position: Point::new(x + inset, y + inset + i as f32 * line_height),
+                        color: self.tooltip_text,
+                        size: text_size.into(),
+                        ..Default::default()
+                    });
+                }
+            }
+        }
+
+        impl<Message> Program<Message, Theme, Renderer> for PlotProgram {
+            type State = PlotState;
+
+            fn update(
+                &self,
+                state: &mut Self::State,
+                event: &canvas::Event,
+                bounds: Rectangle,
+                cursor: mouse::Cursor,
+            ) -> Option<canvas::Action<Message>> {
+                if !matches!(
+                    event,
+                    canvas::Event::Mouse(
+                        mouse::Event::CursorMoved { .. } | mouse::Event::CursorLeft
+                    )
+                ) {
+                    return None;
+                }
+
+                let position = cursor.position_in(bounds);
+                let was_paused = state.paused.is_some();
+                match position {
+                    Some(_) if self.plot.pause_on_hover && !was_paused => {
+                        state.paused = Some(self.plot.clone());
+                    }
+                    None => state.paused = None,
+                    _ => {}
+                }
+
+                let plot = self.shown(state);
+                let area = self.area(plot, bounds.size());
+                let hovered = position.and_then(|position| {
+                    let point = Self::to_viewport(plot, area, position);
+                    plot.hovers.iter().position(|region| {
+                        (region.top_left.x..=region.bottom_right.x).contains(&point.x)
+                            && (region.top_left.y..=region.bottom_right.y).contains(&point.y)
+                    })
+                });
+
+                if hovered == state.hovered && was_paused == state.paused.is_some() {
+                    return None;
+                }
+                state.hovered = hovered;
+                Some(canvas::Action::request_redraw())
+            }
+
+            fn draw(
+                &self,
+                state: &Self::State,
+                renderer: &Renderer,
+                _theme: &Theme,
+                bounds: Rectangle,
+                cursor: mouse::Cursor,
+            ) -> Vec<Geometry> {
+                let plot = self.shown(state);
+                let hovered = state.hovered.and_then(|i| plot.hovers.get(i));
+                let mut layers = Vec::with_capacity(3);
+
+                if let Some(region) = hovered {
+                    let mut frame = Frame::new(renderer, bounds.size());
+                    let area = self.area(plot, bounds.size());
+                    let (top_left, size) =
+                        Self::rect(plot, area, region.top_left, region.bottom_right);
+                    frame.fill_rectangle(top_left, size, self.highlight);
+                    layers.push(frame.into_geometry());
+                }
+
+                // Unchanged plots are drawn from the cache; the cache itself
+                // starts over when the bounds change
+                match plot.revision {
+                    Some(revision) => {
+                        let key = (revision, self.scale.to_bits());
+                        if state.revision.replace(Some(key)) != Some(key) {
+                            state.cache.clear();
+                        }
+                        layers.push(state.cache.draw(renderer, bounds.size(), |frame| {
+                            self.draw_shapes(plot, frame)
+                        }));
+                    }
+                    None => {
+                        let mut frame = Frame::new(renderer, bounds.size());
+                        self.draw_shapes(plot, &mut frame);
+                        layers.push(frame.into_geometry());
+                    }
+                }
+
+                if let (Some(region), Some(position)) = (hovered, cursor.position_in(bounds)) {
+                    let mut frame = Frame::new(renderer, bounds.size());
+                    self.draw_tooltip(&mut frame, &region.tooltip, position, bounds.size());
+                    layers.push(frame.into_geometry());
+                }
+
+                layers
+            }
+        }
+
+        let colors = context.theme.colors;
+        let scale = context.theme.scaling;
+        Canvas::new(PlotProgram {
+            plot,
+            scale,
+            highlight: colors.text_primary.scale_alpha(0.05),
+            tooltip_background: colors.surface,
+            tooltip_border: colors.border,
+            tooltip_text: colors.text_primary,
+        })
+        .width(scale_length(width, scale))
+        .height(scale_length(height, scale))
+        .into()
+    }
+
     fn capsule<Message: 'static + Send + Sync>(
         width: Length,
         height: Length,
@@ -1142,133 +1627,44 @@ impl Backend for IcedBackend {
         direction: crate::style::ScrollDirection,
         context: &Context,
     ) -> Self::AnyView<Message> {
-        let text_color = context.theme.colors.text_primary;
-
-        let final_height = if context.is_inside_scrollable
-            && height == Length::Fill
-            && direction != crate::style::ScrollDirection::Horizontal
-        {
-            Length::Fixed(300.0)
-        } else {
-            height
-        };
-
-        let mut scroll = iced::widget::scrollable(content)
-            .width(width)
-            .height(final_height)
-            .direction(match direction {
-                crate::style::ScrollDirection::Vertical => {
-                    iced::widget::scrollable::Direction::Vertical(
-                        iced::widget::scrollable::Scrollbar::default()
-                            .width(4.0)
-                            .margin(0.0)
-                            .scroller_width(4.0),
-                    )
-                }
-                crate::style::ScrollDirection::Horizontal => {
-                    iced::widget::scrollable::Direction::Horizontal(
-                        iced::widget::scrollable::Scrollbar::default()
-                            .width(4.0)
-                            .margin(0.0)
-                            .scroller_width(4.0),
-                    )
-                }
-                crate::style::ScrollDirection::Both => iced::widget::scrollable::Direction::Both {
-                    vertical: iced::widget::scrollable::Scrollbar::default()
-                        .width(4.0)
-                        .margin(0.0)
-                        .scroller_width(4.0),
-                    horizontal: iced::widget::scrollable::Scrollbar::default()
-                        .width(4.0)
-                        .margin(0.0)
-                        .scroller_width(4.0),
-                },
-            });
-
-        scroll = scroll.style(move |_, status| {
-            if !show_indicators {
-                iced::widget::scrollable::Style {
-                    container: iced::widget::container::Style::default(),
-                    vertical_rail: iced::widget::scrollable::Rail {
-                        background: None,
-                        border: iced::Border::default(),
-                        scroller: iced::widget::scrollable::Scroller {
-                            background: iced::Color::TRANSPARENT.into(),
-                            border: iced::Border::default(),
-                        },
-                    },
-                    horizontal_rail: iced::widget::scrollable::Rail {
-                        background: None,
-                        border: iced::Border::default(),
-                        scroller: iced::widget::scrollable::Scroller {
-                            background: iced::Color::TRANSPARENT.into(),
-                            border: iced::Border::default(),
-                        },
-                    },
-                    gap: None,
-                    auto_scroll: iced::widget::scrollable::AutoScroll {
-                        background: iced::Background::Color(iced::Color::TRANSPARENT),
-                        border: iced::Border::default(),
-                        shadow: iced::Shadow::default(),
-                        icon: iced::Color::TRANSPARENT,
-                    },
-                }
-            } else {
-                let scroller_alpha = match status {
-                    iced::widget::scrollable::Status::Hovered { .. } => 0.4,
-                    iced::widget::scrollable::Status::Dragged { .. } => 0.6,
-                    _ => 0.15,
-                };
-
-                iced::widget::scrollable::Style {
-                    container: iced::widget::container::Style::default(),
-                    vertical_rail: iced::widget::scrollable::Rail {
-                        background: None,
-                        border: iced::Border::default(),
-                        scroller: iced::widget::scrollable::Scroller {
-                            background: iced::Color {
-                                a: scroller_alpha,
-                                ..text_color
-                            }
-                            .into(),
-                            border: iced::Border {
-                                radius: 2.0.into(),
-                                width: 0.0,
-                                ..Default::default()
-                            },
-                        },
-                    },
-                    horizontal_rail: iced::widget::scrollable::Rail {
-                        background: None,
-                        border: iced::Border::default(),
-                        scroller: iced::widget::scrollable::Scroller {
-                            background: iced::Color {
-                                a: scroller_alpha,
-                                ..text_color
-                            }
-                            .into(),
-                            border: iced::Border {
-                                radius: 2.0.into(),
-                                width: 0.0,
-                                ..Default::default()
-                            },
-                        },
-                    },
-                    gap: None,
-                    auto_scroll: iced::widget::scrollable::AutoScroll {
-                        background: iced::Background::Color(iced::Color::TRANSPARENT),
-                        border: iced::Border::default(),
-                        shadow: iced::Shadow::default(),
-                        icon: iced::Color::TRANSPARENT,
-                    },
-                }
-            }
-        });
+        Self::scrollable(
+            content,
+            width,
+            height,
+            id,
+            show_indicators,
+            direction,
+            context,
+        )
+        .into()
+    }
 
-        if let Some(id_val) = id {
-            scroll = scroll.id(Id::new(id_val));
-        }
-        scroll.into()
+    fn scroll_view_with_offset<Message: 'static + Send + Sync>(
+        content: Self::AnyView<Message>,
+        width: Length,
+        height: Length,
+        id: Option<&'static str>,
+        show_indicators: bool,
+        on_scroll: Arc<dyn Fn(f32, f32) -> Message + Send + Sync>,
+        context: &Context,
+    ) -> Self::AnyView<Message> {
+        let scale = context.theme.scaling;
+        Self::scrollable(
+            content,
+            width,
+            height,
+            id,
+            show_indicators,
+            crate::style::ScrollDirection::Vertical,
+            context,
+        )
+        .on_scroll(move |viewport| {
+            on_scroll(
+                viewport.absolute_offset().y / scale,
+                viewport.bounds().height / scale,
+            )
+        })
+        .into()
     }
 
     fn mouse_area<Message: Clone + Send + Sync + 'static>(
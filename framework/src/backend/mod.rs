@@ -7,6 +7,8 @@ pub mod ai;
 pub use ai::AIBackend;
 pub mod iced_backend;
 pub use iced_backend::IcedBackend;
+pub mod plot;
+pub use plot::{HoverRegion, Plot, Shape};
 pub mod spatial;
 pub use spatial::{SpatialBackend, SpatialNode};
 pub mod term;
@@ -45,8 +47,16 @@ pub struct TextSpan {
     pub size: Option<f32>,
     pub is_bold: bool,
     pub is_dim: bool,
+    #[serde(default)]
+    pub is_strikethrough: bool,
+    /// Target reported when the span is clicked, see [`Backend::rich_text_with_links`].
+    #[serde(default)]
+    pub link: Option<String>,
     #[serde(skip)]
     pub font: Option<iced::Font>,
+    /// Drawn behind the span, e.g. for a text selection.
+    #[serde(default, with = "color_serde")]
+    pub background: Option<Color>,
 }
 
 impl TextSpan {
@@ -57,7 +67,10 @@ impl TextSpan {
             size: None,
             is_bold: false,
             is_dim: false,
+            is_strikethrough: false,
+            link: None,
             font: None,
+            background: None,
         }
     }
 
@@ -81,10 +94,25 @@ impl TextSpan {
         self
     }
 
+    pub fn strikethrough(mut self) -> Self {
+        self.is_strikethrough = true;
+        self
+    }
+
+    pub fn link(mut self, target: impl Into<String>) -> Self {
+        self.link = Some(target.into());
+        self
+    }
+
     pub fn font(mut self, font: iced::Font) -> Self {
         self.font = Some(font);
         self
     }
+
+    pub fn background(mut self, color: Color) -> Self {
+        self.background = Some(color);
+        self
+    }
 }
 
 pub trait Backend: Sized + Clone + Send + Sync + 'static {
@@ -137,6 +165,19 @@ pub trait Backend: Sized + Clone + Send + Sync + 'static {
         context: &Context,
     ) -> Self::AnyView<Message>;
 
+    /// Rich text whose linked spans call `on_link` with their target when
+    /// clicked. Backends without pointer input render plain rich text.
+    fn rich_text_with_links<Message: Clone + 'static + Send + Sync>(
+        spans: Vec<TextSpan>,
+        size: f32,
+        width: Length,
+        alignment: Alignment,
+        _on_link: Arc<dyn Fn(String) -> Message + Send + Sync>,
+        context: &Context,
+    ) -> Self::AnyView<Message> {
+        Self::rich_text(spans, size, width, alignment, context)
+    }
+
     fn text<Message: Clone + 'static + Send + Sync>(
         content: String,
         size: f32,
@@ -186,6 +227,13 @@ pub trait Backend: Sized + Clone + Send + Sync + 'static {
         context: &Context,
     ) -> Self::AnyView<Message>;
 
+    fn plot<Message: 'static + Send + Sync>(
+        plot: Plot,
+        width: Length,
+        height: Length,
+        context: &Context,
+    ) -> Self::AnyView<Message>;
+
     fn capsule<Message: 'static + Send + Sync>(
         width: Length,
         height: Length,
@@ -311,6 +359,29 @@ pub trait Backend: Sized + Clone + Send + Sync + 'static {
         context: &Context,
     ) -> Self::AnyView<Message>;
 
+    /// A vertical scroll view that reports its offset and height, in
+    /// logical pixels, as it scrolls, e.g. to only build the rows in view.
+    /// Backends without scrolling render a plain scroll view.
+    fn scroll_view_with_offset<Message: 'static + Send + Sync>(
+        content: Self::AnyView<Message>,
+        width: Length,
+        height: Length,
+        id: Option<&'static str>,
+        show_indicators: bool,
+        _on_scroll: Arc<dyn Fn(f32, f32) -> Message + Send + Sync>,
+        context: &Context,
+    ) -> Self::AnyView<Message> {
+        Self::scroll_view(
+            content,
+            width,
+            height,
+            id,
+            show_indicators,
+            ScrollDirection::Vertical,
+            context,
+        )
+    }
+
     fn mouse_area<Message: Clone + Send + Sync + 'static>(
         content: Self::AnyView<Message>,
         on_move: Option<Arc<dyn Fn(iced::Point) -> Message + Send + Sync>>,
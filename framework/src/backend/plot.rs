@@ -0,0 +1,113 @@
+use iced::{Alignment, Color, Padding, Point, Size, Vector};
+
+/// Vector graphics for charts and diagrams, drawn by [`Backend::plot`].
+///
+/// Shapes are positioned in `viewport` units, which are stretched to fill
+/// the view inside `padding`. Stroke widths, dot radii, text and offsets are
+/// in pixels, so they keep their size however the view is stretched; text
+/// may sit in the padding, e.g. for axis labels.
+///
+/// Backends may keep what they drew for a plot with a [`revision`], and
+/// draw it again for as long as the revision stays the same.
+///
+/// [`Backend::plot`]: super::Backend::plot
+/// [`revision`]: Plot::revision
+#[derive(Debug, Clone, PartialEq)]
+pub struct Plot {
+    pub viewport: Size,
+    pub padding: Padding,
+    pub shapes: Vec<Shape>,
+    /// Areas that show a tooltip while hovered, the first match winning.
+    pub hovers: Vec<HoverRegion>,
+    /// Changes whenever the shapes do, e.g. a hash of the plotted data.
+    pub revision: Option<u64>,
+    /// Keeps showing the plot as it was when the cursor entered it, so that
+    /// live data can be read.
+    pub pause_on_hover: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Shape {
+    Line {
+        points: Vec<Point>,
+        color: Color,
+        width: f32,
+    },
+    /// A filled polygon.
+    Area { points: Vec<Point>, color: Color },
+    Rect {
+        top_left: Point,
+        bottom_right: Point,
+        color: Color,
+    },
+    Dot {
+        center: Point,
+        radius: f32,
+        color: Color,
+    },
+    Text {
+        at: Point,
+        /// Moves the text by pixels, away from what it labels.
+        offset: Vector,
+        content: String,
+        size: f32,
+        color: Color,
+        align_x: Alignment,
+        align_y: Alignment,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct HoverRegion {
+    pub top_left: Point,
+    pub bottom_right: Point,
+    pub tooltip: String,
+}
+
+impl Plot {
+    pub fn new(viewport: Size) -> Self {
+        Self {
+            viewport,
+            padding: Padding::ZERO,
+            shapes: Vec::new(),
+            hovers: Vec::new(),
+            revision: None,
+            pause_on_hover: false,
+        }
+    }
+
+    pub fn padding(mut self, padding: impl Into<Padding>) -> Self {
+        self.padding = padding.into();
+        self
+    }
+
+    pub fn revision(mut self, revision: u64) -> Self {
+        self.revision = Some(revision);
+        self
+    }
+
+    pub fn pause_on_hover(mut self, pause: bool) -> Self {
+        self.pause_on_hover = pause;
+        self
+    }
+
+    pub fn push(&mut self, shape: Shape) {
+        self.shapes.push(shape);
+    }
+
+    pub fn hover(&mut self, top_left: Point, bottom_right: Point, tooltip: impl Into<String>) {
+        self.hovers.push(HoverRegion {
+            top_left,
+            bottom_right,
+            tooltip: tooltip.into(),
+        });
+    }
+
+    /// The hover region under a point in viewport units.
+    pub fn hovered(&self, point: Point) -> Option<&HoverRegion> {
+        self.hovers.iter().find(|region| {
+            (region.top_left.x..=region.bottom_right.x).contains(&point.x)
+                && (region.top_left.y..=region.bottom_right.y).contains(&point.y)
+        })
+    }
+}
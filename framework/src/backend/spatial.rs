@@ -482,6 +482,27 @@ impl Backend for SpatialBackend {
         }
     }
 
+    fn plot<Message: 'static + Send + Sync>(
+        _plot: super::Plot,
+        _width: Length,
+        _height: Length,
+        _context: &Context,
+    ) -> Self::AnyView<Message> {
+        SpatialNode {
+            role: "plot".into(),
+            width: 0.0,
+            height: 0.0,
+            depth: 0.0,
+            transform: Transform3D::default(),
+            bounds: BoundingBox3D::zero(),
+            layout: Layout::Vertical,
+            is_focused: false,
+            billboarding: false,
+            on_press: None,
+            children: Vec::new(),
+        }
+    }
+
     fn capsule<Message: 'static + Send + Sync>(
         _width: Length,
         _height: Length,
@@ -18,13 +18,43 @@ impl Backend for TermBackend {
     }
 
     fn rich_text<Message: Clone + 'static + Send + Sync>(
-        _spans: Vec<TextSpan>,
+        spans: Vec<TextSpan>,
         _size: f32,
         _width: Length,
         _alignment: Alignment,
         _context: &Context,
     ) -> Self::AnyView<Message> {
-        String::new()
+        spans
+            .into_iter()
+            .map(|span| {
+                let mut codes = Vec::new();
+                if span.is_bold {
+                    codes.push("1".to_string());
+                } else if span.is_dim {
+                    codes.push("2".to_string());
+                }
+                if span.link.is_some() {
+                    codes.push("4".to_string());
+                }
+                if span.is_strikethrough {
+                    codes.push("9".to_string());
+                }
+                if let Some(color) = span.color {
+                    let [r, g, b, _] = color.into_rgba8();
+                    codes.push(format!("38;2;{};{};{}", r, g, b));
+                }
+                if let Some(background) = span.background {
+                    let [r, g, b, _] = background.into_rgba8();
+                    codes.push(format!("48;2;{};{};{}", r, g, b));
+                }
+
+                if codes.is_empty() {
+                    span.content
+                } else {
+                    format!("\x1b[{}m{}\x1b[0m", codes.join(";"), span.content)
+                }
+            })
+            .collect()
     }
 
     fn with_tooltip<Message: 'static + Send + Sync>(
@@ -184,6 +214,15 @@ impl Backend for TermBackend {
         format!("~ ({} pts)", points.len())
     }
 
+    fn plot<Message: 'static + Send + Sync>(
+        plot: super::Plot,
+        _width: Length,
+        _height: Length,
+        _context: &Context,
+    ) -> Self::AnyView<Message> {
+        format!("~ plot ({} shapes)", plot.shapes.len())
+    }
+
     fn capsule<Message: 'static + Send + Sync>(
         _width: Length,
         _height: Length,
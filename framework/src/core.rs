@@ -1,8 +1,8 @@
 pub use crate::backend::{AIBackend, Backend, IcedBackend, TextSpan};
 pub use crate::engine::accessibility::{AccessibilityNode, AccessibilityRole};
-#[cfg(feature = "intelligence")]
-pub use crate::semantic::IntelligenceProvider;
 pub use crate::semantic::{ChatCompletionMessage, DataProvider, SemanticNode, SemanticRecord};
+#[cfg(feature = "intelligence")]
+pub use crate::semantic::{CompactedPrompt, IntelligenceProvider};
 pub use crate::style::{Context, DeviceType, Intent, ScrollDirection, Variant};
 use iced::{Element, Subscription, Task, Theme};
 pub use nalgebra::{Isometry3, Point3, Quaternion, Translation3, UnitQuaternion, Vector3};
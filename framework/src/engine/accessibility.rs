@@ -126,6 +126,8 @@ pub enum AccessibilityRole {
     Text,
     WebView,
     Group,
+    Tree,
+    TreeItem,
 }
 
 impl std::fmt::Display for AccessibilityRole {
@@ -150,6 +152,12 @@ pub struct AccessibilityNode {
     pub is_hidden: bool,
     #[serde(rename = "dis", skip_serializing_if = "is_false")]
     pub is_disabled: bool,
+    /// How deep a tree item is, from 1 for the roots.
+    #[serde(rename = "lv", skip_serializing_if = "Option::is_none")]
+    pub level: Option<u32>,
+    /// Whether an item with children shows them.
+    #[serde(rename = "ex", skip_serializing_if = "Option::is_none")]
+    pub expanded: Option<bool>,
 }
 
 fn is_false(b: &bool) -> bool {
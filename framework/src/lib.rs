@@ -55,10 +55,22 @@ pub mod prelude {
     pub use crate::semantic::{ChatCompletionMessage, DataProvider, SemanticNode, SemanticRecord};
     pub use crate::shell::toolbar::{ToolbarGroup, ToolbarItem};
     pub use crate::style::{Context, DeviceType, Intent, ScrollDirection, Variant};
-    pub use crate::views::chart::{Chart, ChartDataPoint, ChartType};
+    pub use crate::views::chart::{
+        Chart, ChartDataPoint, ChartType, Downsample, Series, SeriesSummary, StreamingChart,
+        TimeSeries, Trend, XAxis,
+    };
     pub use crate::views::code_block::CodeBlock;
-    pub use crate::views::data_table::{DataTable, DataTablePreset};
+    pub use crate::views::code_editor::{CodeEditor, EditorMessage, EditorState};
+    pub use crate::views::command_palette::{
+        CommandPalette, CommandPaletteState, CommandRegistry, PaletteCommand, PaletteMessage,
+    };
+    pub use crate::views::data_table::{
+        CellValue, DataTable, DataTableMessage, DataTablePreset, DataTableState, ExportFormat,
+        TableSource,
+    };
+    pub use crate::views::form::{FieldKind, FormField, FormMessage, FormState, FormView};
     pub use crate::views::markdown::MarkdownView;
+    pub use crate::views::tree_view::{TreeMessage, TreeMove, TreeNode, TreeState, TreeView};
     pub use crate::{hstack, vstack, zstack};
     pub use peak_theme::ThemeTone;
 
@@ -0,0 +1,111 @@
+//! What the command palette offers. Each command's id is the [`Command`] it
+//! runs, as JSON, so an agent reading the registry from the semantic tree or
+//! `GET /commands` can post the id as is to `POST /command`.
+use super::message::{Command, Message};
+use crate::reference::AppPage;
+use crate::views::{CommandRegistry, PaletteCommand};
+use peak_theme::{PeakTheme, ThemeTone};
+use serde_json::json;
+
+pub fn registry() -> CommandRegistry {
+    let mut registry = CommandRegistry::new();
+
+    for page in AppPage::all() {
+        let path = page.to_path();
+        registry.register(
+            PaletteCommand::new(json!({ "SetTab": page }).to_string(), page.to_string())
+                .keywords(path.split(['/', '-']).filter(|word| !word.is_empty()))
+                .icon("file-text")
+                .section(page.navigation_mode()),
+        );
+    }
+
+    for (command, title, icon, keywords) in [
+        ("ToggleSearch", "Toggle Search", "search", "find filter"),
+        (
+            "ToggleSidebar",
+            "Toggle Sidebar",
+            "panel-left",
+            "navigation",
+        ),
+        (
+            "ToggleInspector",
+            "Toggle Inspector",
+            "panel-right",
+            "properties",
+        ),
+        (
+            "ToggleUserProfile",
+            "Toggle User Profile",
+            "user",
+            "account",
+        ),
+    ] {
+        registry.register(
+            PaletteCommand::new(json!(command).to_string(), title)
+                .keywords(keywords.split(' '))
+                .icon(icon)
+                .section("View"),
+        );
+    }
+
+    for (tone, title, icon) in [
+        (ThemeTone::Light, "Use Light Mode", "sun"),
+        (ThemeTone::Dark, "Use Dark Mode", "moon"),
+    ] {
+        registry.register(
+            PaletteCommand::new(json!({ "SetTheme": tone }).to_string(), title)
+                .keywords(["theme", "appearance"])
+                .icon(icon)
+                .section("Theme"),
+        );
+    }
+    for theme in PeakTheme::all() {
+        registry.register(
+            PaletteCommand::new(
+                json!({ "SetThemeKind": theme }).to_string(),
+                format!("Use {} Theme", theme.display_name()),
+            )
+            .keywords(["appearance"])
+            .icon("palette")
+            .section("Theme"),
+        );
+    }
+
+    registry
+}
+
+/// What running command `id` does, if it's a [`Command`].
+pub fn message(id: &str) -> Message {
+    match serde_json::from_str::<Command>(id) {
+        Ok(command) => command.into_message(),
+        Err(e) => {
+            log::warn!("Unknown palette command {}: {}", id, e);
+            Message::None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::reference::app::ShellMessage;
+
+    #[test]
+    fn test_commands_run_what_they_describe() {
+        let registry = registry();
+        assert!(registry.commands().len() > AppPage::all().len());
+
+        for command in registry.commands() {
+            assert!(
+                !matches!(message(&command.id), Message::None),
+                "{} doesn't parse",
+                command.id
+            );
+        }
+        assert!(matches!(
+            message(&registry.search("colors")[0].command.id),
+            Message::Shell(ShellMessage::SetTab(AppPage::Colors))
+        ));
+    }
+}
@@ -2,6 +2,8 @@ use super::state::*;
 use crate::prelude::*;
 use crate::reference::AppPage;
 use crate::views::chat::ChatViewMessage;
+#[cfg(feature = "intelligence")]
+use crate::views::{ChatMessage, ChatSummary};
 
 #[derive(Debug, Clone)]
 pub enum ShellMessage {
@@ -21,6 +23,7 @@ pub enum ShellMessage {
     StopResizingSidebar,
     StartResizingInspector,
     StopResizingInspector,
+    CommandPalette(PaletteMessage),
 }
 
 #[derive(Debug, Clone)]
@@ -38,6 +41,59 @@ pub enum IntelligenceMessage {
     SetAIProvider(AIProviderChoice),
     #[cfg(feature = "intelligence")]
     ProcessToolResult(String, serde_json::Value), // name, result
+    #[cfg(feature = "intelligence")]
+    MemoryRecalled(Vec<crate::core::SemanticRecord>),
+    #[cfg(feature = "intelligence")]
+    ContextPrepared(crate::core::CompactedPrompt),
+    #[cfg(feature = "intelligence")]
+    ConversationsListed(std::result::Result<Vec<ChatSummary>, String>),
+    #[cfg(feature = "intelligence")]
+    ConversationOpened(std::result::Result<(ChatSummary, Vec<ChatMessage>), String>),
+    #[cfg(feature = "intelligence")]
+    ConversationSaved(std::result::Result<ChatSummary, String>),
+    #[cfg(feature = "intelligence")]
+    ConversationDeleted(std::result::Result<String, String>),
+    #[cfg(feature = "intelligence")]
+    TitleGenerated(String, std::result::Result<String, String>),
+    #[cfg(feature = "intelligence")]
+    Models(ModelMessage),
+    #[cfg(feature = "intelligence")]
+    ToolsConnected(std::result::Result<Vec<String>, String>),
+    /// Runs a tool once the user approved it through Neural Sudo.
+    #[cfg(feature = "intelligence")]
+    RunApprovedTool(String, serde_json::Value), // name, args
+}
+
+/// Model library actions on the AI settings page.
+#[cfg(feature = "intelligence")]
+#[derive(Debug, Clone)]
+pub enum ModelMessage {
+    QueryChanged(String),
+    Search,
+    Found(std::result::Result<Vec<peak_intelligence::brain::model::Model>, String>),
+    Select(peak_intelligence::brain::model::Id),
+    VariantsListed(
+        peak_intelligence::brain::model::Id,
+        std::result::Result<Vec<peak_intelligence::brain::model::File>, String>,
+    ),
+    Download(peak_intelligence::brain::model::File),
+    Queued(std::result::Result<peak_intelligence::brain::model::manager::Download, String>),
+    Progress(std::result::Result<peak_intelligence::brain::model::manager::Event, String>),
+    QueueDrained,
+    Cancel(peak_intelligence::brain::model::File),
+    Delete(peak_intelligence::brain::model::File),
+    CollectGarbage,
+    Removed(std::result::Result<Vec<std::path::PathBuf>, String>),
+    Refresh,
+    Refreshed(
+        std::result::Result<
+            (
+                Vec<peak_intelligence::brain::model::manager::Download>,
+                Vec<peak_intelligence::brain::model::File>,
+            ),
+            String,
+        >,
+    ),
 }
 
 #[derive(Debug, Clone)]
@@ -97,8 +153,8 @@ pub enum InteractionMessage {
     ContextMenuAction(String),
     UpdateCursorPos(Point),
     SudoRequest(SudoAction),
-    SudoApprove,
-    SudoDeny,
+    #[cfg(feature = "intelligence")]
+    ApprovalDecided(u64, peak_intelligence::tools::approval::Decision),
 }
 
 #[derive(Debug, Clone)]
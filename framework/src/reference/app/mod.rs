@@ -1,3 +1,4 @@
+pub mod commands;
 pub mod message;
 pub mod state;
 pub mod subscription;
@@ -3,7 +3,7 @@ use crate::engine::modifiers::ControlSize;
 use crate::prelude::*;
 use crate::reference::AppPage;
 use crate::style::{Intent, Variant};
-use crate::views::{ChatMessage, ChatRole};
+use crate::views::{ChatCitation, ChatHistory, ChatMessage, ChatRole, ChatSummary};
 use peak_core::registry::ShellMode;
 use peak_theme::{PeakTheme, ThemeTokens, ThemeTone};
 use std::sync::Arc;
@@ -67,6 +67,9 @@ pub struct Settings {
     pub api_key: String,
     pub ai_provider: AIProviderChoice,
     pub enable_exposure: bool,
+    /// Context window override in tokens; `None` uses the provider default.
+    #[serde(default)]
+    pub context_tokens: Option<usize>,
 }
 
 impl Default for Settings {
@@ -75,6 +78,7 @@ impl Default for Settings {
             api_key: String::new(),
             ai_provider: AIProviderChoice::Ollama,
             enable_exposure: false,
+            context_tokens: None,
         }
     }
 }
@@ -111,6 +115,7 @@ impl Settings {
             api_key,
             ai_provider,
             enable_exposure: false,
+            context_tokens: None,
         }
     }
 
@@ -139,6 +144,11 @@ pub struct ShellState {
     pub window_width: f32,
     pub window_height: f32,
     pub localization: Localization,
+    #[serde(skip, default)]
+    pub command_palette: CommandPaletteState,
+    /// The commands as last written to `.peak/commands.json`.
+    #[serde(skip, default)]
+    pub exported_commands: Option<CommandRegistry>,
 }
 
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
@@ -155,11 +165,130 @@ pub struct IntelligenceState {
     pub typewriter_phrase_index: usize,
     pub is_deleting: bool,
     pub is_typing: bool,
+    /// Memory sources retrieved for the reply currently being generated.
+    #[serde(skip)]
+    pub pending_citations: Vec<ChatCitation>,
+    /// Record opened from a chat citation, highlighted on the PeakDB page.
+    pub focused_record: Option<String>,
+    /// Saved conversations, most recent first.
+    pub conversations: Vec<ChatSummary>,
+    /// Conversation the current messages belong to; `None` until first saved.
+    pub active_conversation: Option<String>,
+    #[serde(skip)]
+    pub renaming_conversation: Option<(String, String)>,
+    /// Set while the first save of a new conversation is in flight.
+    #[serde(skip)]
+    pub creating_conversation: bool,
+    pub show_history: bool,
+    /// Context window override in tokens; `None` uses the provider default.
+    pub context_tokens: Option<usize>,
+    /// Summary of the oldest messages, sent in their place.
+    #[serde(skip)]
+    pub context_summary: Option<ContextSummary>,
+    /// Shown in the chat view after the prompt had to be compressed.
+    pub context_notice: Option<String>,
+    #[cfg(feature = "intelligence")]
+    #[serde(skip)]
+    pub models: ModelLibrary,
     #[cfg(feature = "intelligence")]
     #[serde(skip)]
     pub bridge: Arc<crate::reference::intelligence::bridge::PeakIntelligenceBridge>,
 }
 
+/// Older chat messages folded into a summary by context compaction.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct ContextSummary {
+    /// Number of leading `chat_messages` the summary replaces, apart from
+    /// leading system messages, which are still sent.
+    pub covered: usize,
+    /// Empty when turns were only dropped.
+    pub text: String,
+}
+
+impl ContextSummary {
+    /// The summary once compaction did `events` to a prompt of `messages`
+    /// sent after `summary`: turns it folded or dropped aren't sent again.
+    /// Compaction keeps leading system messages out of both, so without a
+    /// summary before them, turns are counted from after those.
+    #[cfg(feature = "intelligence")]
+    pub fn compacted(
+        summary: Option<Self>,
+        messages: &[ChatMessage],
+        events: &[peak_intelligence::context::ContextEvent],
+    ) -> Option<Self> {
+        use peak_intelligence::context::ContextEvent;
+
+        let mut summary = summary;
+        for event in events {
+            let (turns, text) = match event {
+                ContextEvent::Summarized { turns, summary } => (*turns, Some(summary.clone())),
+                ContextEvent::TurnsDropped(turns) => (*turns, None),
+                _ => continue,
+            };
+            let (covered, previous) = match summary.take() {
+                Some(summary) => (summary.covered.min(messages.len()), summary.text),
+                None => (0, String::new()),
+            };
+            let start = if previous.is_empty() {
+                covered
+                    + messages[covered..]
+                        .iter()
+                        .take_while(|m| m.role == ChatRole::System)
+                        .count()
+            } else {
+                covered
+            };
+            summary = Some(Self {
+                covered: (start + turns).min(messages.len()),
+                text: text.unwrap_or(previous),
+            });
+        }
+        summary
+    }
+}
+
+/// Model search, downloads and installed files on the AI settings page.
+#[cfg(feature = "intelligence")]
+#[derive(Debug, Clone, Default)]
+pub struct ModelLibrary {
+    pub query: String,
+    pub results: Vec<peak_intelligence::brain::model::Model>,
+    pub is_searching: bool,
+    /// Model whose variants are listed below the search results.
+    pub selected: Option<peak_intelligence::brain::model::Id>,
+    pub variants: Vec<peak_intelligence::brain::model::File>,
+    pub queue: Vec<peak_intelligence::brain::model::manager::Download>,
+    pub installed: Vec<peak_intelligence::brain::model::File>,
+    /// Transfer currently in flight.
+    pub active: Option<(
+        peak_intelligence::brain::model::File,
+        Option<peak_intelligence::brain::model::Progress>,
+    )>,
+    /// Set while the download queue is being worked through.
+    pub is_downloading: bool,
+    pub status: Option<String>,
+}
+
+impl IntelligenceState {
+    /// The messages a fresh conversation starts with.
+    pub fn welcome_messages() -> Vec<ChatMessage> {
+        vec![ChatMessage {
+            role: ChatRole::System,
+            content: "Welcome to PeakUI. I am your autonomous interface agent.".to_string(),
+            citations: Vec::new(),
+        }]
+    }
+
+    pub fn chat_history(&self) -> ChatHistory {
+        ChatHistory {
+            conversations: self.conversations.clone(),
+            active: self.active_conversation.clone(),
+            renaming: self.renaming_conversation.clone(),
+            is_open: self.show_history,
+        }
+    }
+}
+
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct LabState {
     pub render_mode: RenderMode,
@@ -186,7 +315,10 @@ pub struct InteractionState {
     #[serde(skip, default = "Point::default")]
     pub last_cursor_pos: Point,
     pub last_copied_code: Option<String>,
-    pub pending_sudo_action: Option<SudoAction>,
+    /// Protected actions waiting for the user's decision.
+    #[cfg(feature = "intelligence")]
+    #[serde(skip)]
+    pub approvals: peak_intelligence::tools::approval::Approvals,
     pub theme_tone: ThemeTone,
     pub theme: PeakTheme,
     pub scaling: f32,
@@ -219,6 +351,7 @@ impl App {
                 api_key: self.intelligence.api_key.clone(),
                 ai_provider: self.intelligence.ai_provider,
                 enable_exposure: self.interaction.enable_exposure,
+                context_tokens: self.intelligence.context_tokens,
             };
             settings.save();
         }
@@ -253,6 +386,8 @@ impl App {
 }
 
 /// A protected action that requires user confirmation or "Sudo" elevation.
+/// It waits in the approval queue under `tool` and `args`, and `message` is
+/// dispatched once it is approved.
 ///
 /// The `message` is skipped during serialization as it contains a dynamic boxed enum
 /// that cannot be easily serialized across boundaries.
@@ -261,6 +396,10 @@ pub struct SudoAction {
     #[serde(skip, default = "dummy_message")]
     pub message: Box<Message>,
     pub reason: String,
+    #[serde(default)]
+    pub tool: String,
+    #[serde(default)]
+    pub args: serde_json::Value,
 }
 
 fn dummy_message() -> Box<Message> {
@@ -458,7 +597,8 @@ impl Default for App {
                 db.clone(),
                 #[cfg(not(feature = "neural"))]
                 Arc::new(crate::reference::data::stub_db::StubDB::new()),
-            ),
+            )
+            .with_context_window(settings.context_tokens),
         );
 
         #[cfg(feature = "intelligence")]
@@ -500,12 +640,11 @@ impl Default for App {
                     800.0
                 },
                 localization: Localization::default(),
+                command_palette: CommandPaletteState::new(super::commands::registry()),
+                exported_commands: None,
             },
             intelligence: IntelligenceState {
-                chat_messages: Arc::new(vec![ChatMessage {
-                    role: ChatRole::System,
-                    content: "Welcome to PeakUI. I am your autonomous interface agent.".to_string(),
-                }]),
+                chat_messages: Arc::new(IntelligenceState::welcome_messages()),
                 chat_input: String::new(),
                 is_thinking: false,
                 api_key: settings.api_key,
@@ -516,6 +655,18 @@ impl Default for App {
                 typewriter_phrase_index: 0,
                 is_deleting: false,
                 is_typing: false,
+                pending_citations: Vec::new(),
+                focused_record: None,
+                conversations: Vec::new(),
+                active_conversation: None,
+                renaming_conversation: None,
+                creating_conversation: false,
+                show_history: false,
+                context_tokens: settings.context_tokens,
+                context_summary: None,
+                context_notice: None,
+                #[cfg(feature = "intelligence")]
+                models: ModelLibrary::default(),
                 #[cfg(feature = "intelligence")]
                 bridge: intelligence_bridge,
             },
@@ -540,7 +691,8 @@ impl Default for App {
                 context_menu_pos: None,
                 last_cursor_pos: Point::ORIGIN,
                 last_copied_code: None,
-                pending_sudo_action: None,
+                #[cfg(feature = "intelligence")]
+                approvals: Default::default(),
                 theme_tone: ThemeTone::Light,
                 theme: PeakTheme::Mono,
                 scaling: 1.0,
@@ -555,3 +707,63 @@ impl Default for App {
         }
     }
 }
+
+#[cfg(all(test, feature = "intelligence"))]
+mod tests {
+    use super::*;
+    use peak_intelligence::context::ContextEvent;
+
+    fn message(role: ChatRole, content: &str) -> ChatMessage {
+        ChatMessage {
+            role,
+            content: content.to_string(),
+            citations: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_compaction_counts_turns_after_pinned_messages() {
+        let mut messages = IntelligenceState::welcome_messages();
+        for i in 0..6 {
+            let role = if i % 2 == 0 {
+                ChatRole::User
+            } else {
+                ChatRole::Assistant
+            };
+            messages.push(message(role, &format!("turn {}", i)));
+        }
+
+        // The welcome message was pinned, so the summary starts after it
+        let summarized = ContextSummary::compacted(
+            None,
+            &messages,
+            &[
+                ContextEvent::SnapshotsDropped(1),
+                ContextEvent::Summarized {
+                    turns: 2,
+                    summary: "Two turns".to_string(),
+                },
+                ContextEvent::TurnsDropped(1),
+            ],
+        )
+        .unwrap();
+        assert_eq!(summarized.covered, 4);
+        assert_eq!(summarized.text, "Two turns");
+
+        let dropped = ContextSummary::compacted(None, &messages, &[ContextEvent::TurnsDropped(2)]);
+        assert_eq!(
+            dropped,
+            Some(ContextSummary {
+                covered: 3,
+                text: String::new(),
+            })
+        );
+
+        let untouched = ContextSummary::compacted(
+            dropped.clone(),
+            &messages,
+            &[ContextEvent::SnapshotTruncated { tokens: 10 }],
+        );
+        assert_eq!(untouched, dropped);
+    }
+}
@@ -1,6 +1,6 @@
 #[cfg(not(target_arch = "wasm32"))]
 use super::message::Command;
-use super::message::Message;
+use super::message::{Message, ShellMessage};
 use super::state::*;
 use crate::prelude::*;
 
@@ -64,6 +64,8 @@ impl App {
             Event::Window(window::Event::Resized(size)) => Some(Message::WindowResized(size)),
             _ => None,
         });
+        let command_palette = CommandPaletteState::subscription()
+            .map(|msg| Message::Shell(ShellMessage::CommandPalette(msg)));
 
         #[cfg(target_arch = "wasm32")]
         {
@@ -108,6 +110,7 @@ impl App {
                 hash_sub,
                 hotkeys,
                 window_events,
+                command_palette,
                 iced::time::every(std::time::Duration::from_millis(100))
                     .map(|_| Message::Heartbeat),
                 iced::time::every(std::time::Duration::from_millis(100)).map(|_t| {
@@ -142,14 +145,29 @@ impl App {
                 #[cfg(feature = "intelligence")]
                 {
                     if self.interaction.enable_exposure {
-                        Subscription::run(|| {
+                        let exposure = Subscription::run(|| {
                             let (sender, receiver) =
                                 crate::prelude::futures::channel::mpsc::channel(100);
                             tokio::spawn(async move {
                                 crate::reference::intelligence::exposure::run_server(sender).await;
                             });
                             receiver
-                        })
+                        });
+                        // Agents' protected calls join the app's approval queue
+                        #[cfg(feature = "native")]
+                        let exposure = {
+                            use crate::reference::intelligence::tool_server;
+                            let tool_server = Subscription::run_with(
+                                tool_server::Queue(self.interaction.approvals.clone()),
+                                |queue| {
+                                    use crate::prelude::futures::{future, stream, StreamExt};
+                                    stream::once(tool_server::run(queue.0.clone()))
+                                        .filter_map(|()| future::ready(None))
+                                },
+                            );
+                            Subscription::batch(vec![exposure, tool_server])
+                        };
+                        exposure
                     } else {
                         Subscription::none()
                     }
@@ -164,6 +182,7 @@ impl App {
                 exposure_sub,
                 hotkeys,
                 window_events,
+                command_palette,
                 iced::time::every(std::time::Duration::from_millis(100))
                     .map(|_| Message::Heartbeat),
                 iced::time::every(std::time::Duration::from_millis(100))
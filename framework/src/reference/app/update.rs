@@ -1,18 +1,20 @@
 use crate::prelude::*;
 #[cfg(feature = "intelligence")]
-use crate::reference::intelligence::Action;
+use crate::reference::intelligence::{conversations, models, rag, Action};
 #[cfg(feature = "intelligence")]
 use crate::views::chat::ChatViewMessage;
 use crate::views::{ChatMessage, ChatRole};
+#[cfg(feature = "intelligence")]
+use peak_intelligence::context::{SUMMARY_HEADER, UI_CONTEXT_HEADER};
 // #[cfg(feature = "intelligence")]
 // use peak_theme::ThemeTone;
 use std::sync::Arc;
 // #[cfg(feature = "intelligence")]
 // use crate::core::Context;
 
-#[cfg(feature = "intelligence")]
-use super::message::IntelligenceMessage;
 use super::message::InteractionMessage;
+#[cfg(feature = "intelligence")]
+use super::message::{IntelligenceMessage, ModelMessage};
 use super::message::{LabMessage, Message, ShellMessage};
 use super::state::*;
 use crate::reference::AppPage;
@@ -169,6 +171,14 @@ impl App {
                     self.interaction.is_resizing_inspector = false;
                     Task::none()
                 }
+                ShellMessage::CommandPalette(PaletteMessage::Ran(id)) => {
+                    Task::done(super::commands::message(&id))
+                }
+                ShellMessage::CommandPalette(msg) => self
+                    .shell
+                    .command_palette
+                    .update(msg)
+                    .map(|msg| Message::Shell(ShellMessage::CommandPalette(msg))),
             },
             #[cfg(feature = "intelligence")]
             Message::Intelligence(int_msg) => match int_msg {
@@ -209,6 +219,66 @@ impl App {
                             |_| Message::Interaction(InteractionMessage::ClearCopiedFeedback),
                         )
                     }
+                    ChatViewMessage::OpenLink(url) => {
+                        Task::done(Message::Shell(ShellMessage::OpenUrl(url)))
+                    }
+                    ChatViewMessage::OpenCitation(citation) => {
+                        self.intelligence.focused_record = Some(citation.record_id);
+                        Task::done(Message::Shell(ShellMessage::SetTab(AppPage::PeakDBDetail)))
+                    }
+                    ChatViewMessage::ToggleHistory => {
+                        self.intelligence.show_history = !self.intelligence.show_history;
+                        if self.intelligence.show_history {
+                            return Task::perform(conversations::list(), |res| {
+                                Message::Intelligence(IntelligenceMessage::ConversationsListed(res))
+                            });
+                        }
+                        Task::none()
+                    }
+                    ChatViewMessage::NewConversation => {
+                        self.intelligence.reset_conversation();
+                        Task::none()
+                    }
+                    ChatViewMessage::OpenConversation(id) => {
+                        Task::perform(conversations::open(id), |res| {
+                            Message::Intelligence(IntelligenceMessage::ConversationOpened(res))
+                        })
+                    }
+                    ChatViewMessage::StartRename(id) => {
+                        let title = self
+                            .intelligence
+                            .conversations
+                            .iter()
+                            .find(|c| c.id == id)
+                            .and_then(|c| c.title.clone())
+                            .unwrap_or_default();
+                        self.intelligence.renaming_conversation = Some((id, title));
+                        Task::none()
+                    }
+                    ChatViewMessage::RenameChanged(value) => {
+                        if let Some((_, title)) = &mut self.intelligence.renaming_conversation {
+                            *title = value;
+                        }
+                        Task::none()
+                    }
+                    ChatViewMessage::CommitRename => {
+                        let Some((id, title)) = self.intelligence.renaming_conversation.take()
+                        else {
+                            return Task::none();
+                        };
+                        let title = title.trim().to_string();
+                        Task::perform(
+                            conversations::rename(id, (!title.is_empty()).then_some(title)),
+                            |res| {
+                                Message::Intelligence(IntelligenceMessage::ConversationSaved(res))
+                            },
+                        )
+                    }
+                    ChatViewMessage::DeleteConversation(id) => {
+                        Task::perform(conversations::delete(id), |res| {
+                            Message::Intelligence(IntelligenceMessage::ConversationDeleted(res))
+                        })
+                    }
                 },
 
                 #[cfg(feature = "intelligence")]
@@ -216,16 +286,22 @@ impl App {
                     self.intelligence.is_thinking = false;
                     match res {
                         Ok(content) => {
+                            let pending = std::mem::take(&mut self.intelligence.pending_citations);
                             self.intelligence.append_message(ChatMessage {
                                 role: ChatRole::Assistant,
                                 content: content.clone(),
+                                citations: rag::cited(&content, &pending),
                             });
-                            return self.process_assistant_actions(&content);
+                            return Task::batch([
+                                self.process_assistant_actions(&content),
+                                self.save_conversation(),
+                            ]);
                         }
                         Err(e) => {
                             self.intelligence.append_message(ChatMessage {
                                 role: ChatRole::System,
                                 content: format!("Error: {}", e),
+                                citations: Vec::new(),
                             });
                         }
                     }
@@ -243,12 +319,14 @@ impl App {
                                     messages.push(ChatMessage {
                                         role: ChatRole::Assistant,
                                         content: delta,
+                                        citations: Vec::new(),
                                     });
                                 }
                             } else {
                                 messages.push(ChatMessage {
                                     role: ChatRole::Assistant,
                                     content: delta,
+                                    citations: Vec::new(),
                                 });
                             }
                         }
@@ -257,6 +335,7 @@ impl App {
                             self.intelligence.append_message(ChatMessage {
                                 role: ChatRole::System,
                                 content: format!("Error: {}", e),
+                                citations: Vec::new(),
                             });
                         }
                     }
@@ -265,14 +344,168 @@ impl App {
                 #[cfg(feature = "intelligence")]
                 IntelligenceMessage::AIChatComplete => {
                     self.intelligence.is_thinking = false;
-                    if let Some(last) = self.intelligence.chat_messages.last() {
+                    let pending = std::mem::take(&mut self.intelligence.pending_citations);
+                    let messages = Arc::make_mut(&mut self.intelligence.chat_messages);
+                    if let Some(last) = messages.last_mut() {
                         if last.role == ChatRole::Assistant {
-                            return self.process_assistant_actions(&last.content.clone());
+                            last.citations = rag::cited(&last.content, &pending);
+                            let content = last.content.clone();
+                            return Task::batch([
+                                self.process_assistant_actions(&content),
+                                self.save_conversation(),
+                            ]);
+                        }
+                    }
+                    Task::none()
+                }
+                #[cfg(feature = "intelligence")]
+                IntelligenceMessage::MemoryRecalled(records) => self.stream_ai_chat(records),
+                #[cfg(feature = "intelligence")]
+                IntelligenceMessage::ContextPrepared(prompt) => {
+                    self.intelligence.context_summary = ContextSummary::compacted(
+                        self.intelligence.context_summary.take(),
+                        &self.intelligence.chat_messages,
+                        &prompt.events,
+                    );
+
+                    self.intelligence.context_notice = if prompt.events.is_empty() {
+                        None
+                    } else {
+                        Some(format!(
+                            "Context compressed: {} → {} tokens",
+                            prompt.before_tokens, prompt.after_tokens
+                        ))
+                    };
+
+                    self.send_ai_chat(prompt.messages)
+                }
+                #[cfg(feature = "intelligence")]
+                IntelligenceMessage::ConversationsListed(res) => {
+                    match res {
+                        Ok(list) => self.intelligence.conversations = list,
+                        Err(e) => log::warn!("Failed to list conversations: {}", e),
+                    }
+                    Task::none()
+                }
+                #[cfg(feature = "intelligence")]
+                IntelligenceMessage::ConversationOpened(res) => {
+                    match res {
+                        Ok((summary, messages)) => {
+                            self.intelligence.open_conversation(summary, messages)
                         }
+                        Err(e) => log::warn!("Failed to open conversation: {}", e),
                     }
                     Task::none()
                 }
                 #[cfg(feature = "intelligence")]
+                IntelligenceMessage::ConversationSaved(res) => {
+                    let summary = match res {
+                        Ok(summary) => summary,
+                        Err(e) => {
+                            self.intelligence.creating_conversation = false;
+                            log::warn!("Failed to save conversation: {}", e);
+                            return Task::none();
+                        }
+                    };
+
+                    let created = self.intelligence.creating_conversation
+                        && self.intelligence.active_conversation.is_none();
+                    self.intelligence.upsert_conversation(summary.clone());
+
+                    if !created {
+                        return Task::none();
+                    }
+
+                    self.intelligence.creating_conversation = false;
+                    self.intelligence.active_conversation = Some(summary.id.clone());
+
+                    // Messages may have arrived while the chat was being created
+                    let bridge = self.intelligence.bridge.clone();
+                    let history = conversations::to_items(&self.intelligence.chat_messages);
+                    let id = summary.id;
+                    Task::batch([
+                        self.save_conversation(),
+                        Task::perform(async move { bridge.title(history).await }, move |res| {
+                            Message::Intelligence(IntelligenceMessage::TitleGenerated(
+                                id.clone(),
+                                res,
+                            ))
+                        }),
+                    ])
+                }
+                #[cfg(feature = "intelligence")]
+                IntelligenceMessage::ConversationDeleted(res) => {
+                    match res {
+                        Ok(id) => {
+                            self.intelligence.conversations.retain(|c| c.id != id);
+                            if self.intelligence.active_conversation.as_ref() == Some(&id) {
+                                self.intelligence.reset_conversation();
+                            }
+                        }
+                        Err(e) => log::warn!("Failed to delete conversation: {}", e),
+                    }
+                    Task::none()
+                }
+                #[cfg(feature = "intelligence")]
+                IntelligenceMessage::TitleGenerated(id, res) => match res {
+                    Ok(title) if !title.is_empty() => {
+                        Task::perform(conversations::rename(id, Some(title)), |res| {
+                            Message::Intelligence(IntelligenceMessage::ConversationSaved(res))
+                        })
+                    }
+                    Ok(_) => Task::none(),
+                    Err(e) => {
+                        log::warn!("Failed to generate conversation title: {}", e);
+                        Task::none()
+                    }
+                },
+                #[cfg(feature = "intelligence")]
+                IntelligenceMessage::Models(message) => self.update_models(message),
+                #[cfg(feature = "intelligence")]
+                IntelligenceMessage::ToolsConnected(res) => {
+                    match res {
+                        Ok(tools) => log::info!("Imported {} external tools", tools.len()),
+                        Err(e) => log::warn!("Failed to connect MCP servers: {}", e),
+                    }
+                    Task::none()
+                }
+                #[cfg(feature = "intelligence")]
+                IntelligenceMessage::RunApprovedTool(name, args) => {
+                    let task = match name.as_str() {
+                        #[cfg(feature = "native")]
+                        "run_command" => {
+                            crate::reference::intelligence::bridge::run_approved_command(args)
+                        }
+                        #[cfg(feature = "native")]
+                        imported
+                            if crate::reference::intelligence::protocol::is_imported_tool(
+                                imported,
+                            ) =>
+                        {
+                            let imported = imported.to_string();
+                            Task::perform(
+                                async move {
+                                    crate::reference::intelligence::external_tools::call(
+                                        &imported, args,
+                                    )
+                                    .await
+                                    .unwrap_or_else(|| {
+                                        Err(format!("No imported tool '{}'", imported))
+                                    })
+                                },
+                                |res| res,
+                            )
+                        }
+                        _ => self.intelligence.bridge.execute_tool(name.clone(), args),
+                    };
+                    task.map(move |res| {
+                        Message::Intelligence(IntelligenceMessage::ProcessToolResult(
+                            name.clone(),
+                            res.unwrap_or_else(|e| serde_json::json!({"error": e})),
+                        ))
+                    })
+                }
+                #[cfg(feature = "intelligence")]
                 IntelligenceMessage::SetApiKey(key) => {
                     self.intelligence.api_key = key;
                     self.save_settings();
@@ -306,6 +539,7 @@ impl App {
                     self.intelligence.append_message(ChatMessage {
                         role: ChatRole::System,
                         content: result_str,
+                        citations: Vec::new(),
                     });
                     return self.ai_chat_completion();
                 }
@@ -513,18 +747,42 @@ impl App {
                     Task::none()
                 }
                 InteractionMessage::SudoRequest(action) => {
-                    self.interaction.pending_sudo_action = Some(action);
-                    Task::none()
-                }
-                InteractionMessage::SudoApprove => {
-                    if let Some(action) = self.interaction.pending_sudo_action.take() {
-                        Task::perform(async {}, move |_| *action.message)
-                    } else {
+                    #[cfg(feature = "intelligence")]
+                    {
+                        let ticket = self.interaction.approvals.request(
+                            action.tool.clone(),
+                            action.args.clone(),
+                            action.reason.clone(),
+                            "assistant",
+                        );
+
+                        Task::perform(ticket, move |decision| {
+                            if decision.is_approved() {
+                                *action.message
+                            } else {
+                                Message::Intelligence(IntelligenceMessage::ProcessToolResult(
+                                    action.tool,
+                                    serde_json::json!({
+                                        "error": format!("The user denied: {}", action.reason)
+                                    }),
+                                ))
+                            }
+                        })
+                    }
+
+                    // Nothing can approve protected actions without the
+                    // intelligence runtime
+                    #[cfg(not(feature = "intelligence"))]
+                    {
+                        log::warn!("Denied protected action: {}", action.reason);
                         Task::none()
                     }
                 }
-                InteractionMessage::SudoDeny => {
-                    self.interaction.pending_sudo_action = None;
+                #[cfg(feature = "intelligence")]
+                InteractionMessage::ApprovalDecided(id, decision) => {
+                    if let Err(e) = self.interaction.approvals.decide(id, decision) {
+                        log::warn!("{}", e);
+                    }
                     Task::none()
                 }
             },
@@ -617,6 +875,7 @@ impl App {
                 self.intelligence.append_message(ChatMessage {
                     role: ChatRole::System,
                     content: format!("Shell command executed securely: `{}`", cmd),
+                    citations: Vec::new(),
                 });
                 Task::none()
             }
@@ -635,7 +894,7 @@ impl App {
         }
     }
 
-    fn export_view(&self) {
+    fn export_view(&mut self) {
         #[cfg(not(target_arch = "wasm32"))]
         {
             // Only export if exposure is enabled or we are in dev/debug mode
@@ -650,6 +909,17 @@ impl App {
                     log::error!("Failed to export Neural View: {}", e);
                 }
             }
+            // What can be done from anywhere, for `GET /commands`, written
+            // only when the commands or the recently used change
+            let registry = &self.shell.command_palette.registry;
+            if self.shell.exported_commands.as_ref() != Some(registry) {
+                if let Ok(json) = serde_json::to_string(registry) {
+                    match std::fs::write(".peak/commands.json", json) {
+                        Ok(()) => self.shell.exported_commands = Some(registry.clone()),
+                        Err(e) => log::error!("Failed to export commands: {}", e),
+                    }
+                }
+            }
         }
     }
 
@@ -658,35 +928,305 @@ impl App {
         self.intelligence.append_message(ChatMessage {
             role: ChatRole::User,
             content: query,
+            citations: Vec::new(),
         });
         self.ai_chat_completion()
     }
 
+    /// Loads saved conversations and reopens the one that was last active.
+    pub fn restore_conversations(&self) -> Task<Message> {
+        #[cfg(feature = "intelligence")]
+        {
+            Task::batch([
+                Task::perform(conversations::list(), |res| {
+                    Message::Intelligence(IntelligenceMessage::ConversationsListed(res))
+                }),
+                Task::perform(conversations::last_opened(), |res| {
+                    Message::Intelligence(IntelligenceMessage::ConversationOpened(res))
+                }),
+            ])
+        }
+        #[cfg(not(feature = "intelligence"))]
+        Task::none()
+    }
+
+    /// Loads the model library and resumes downloads left in the queue.
+    pub fn restore_models(&self) -> Task<Message> {
+        #[cfg(feature = "intelligence")]
+        {
+            Task::perform(models::library(), ModelMessage::Refreshed).map(model_message)
+        }
+        #[cfg(not(feature = "intelligence"))]
+        Task::none()
+    }
+
+    /// Connects the MCP servers from `mcp.toml` so their tools can be
+    /// called by the assistant.
+    pub fn connect_tools(&self) -> Task<Message> {
+        #[cfg(all(feature = "intelligence", feature = "native"))]
+        {
+            Task::perform(
+                crate::reference::intelligence::external_tools::connect(),
+                |res| Message::Intelligence(IntelligenceMessage::ToolsConnected(res)),
+            )
+        }
+        #[cfg(not(all(feature = "intelligence", feature = "native")))]
+        Task::none()
+    }
+
+    #[cfg(feature = "intelligence")]
+    fn update_models(&mut self, message: ModelMessage) -> Task<Message> {
+        use peak_intelligence::brain::model::manager::Event;
+
+        let library = &mut self.intelligence.models;
+
+        match message {
+            ModelMessage::QueryChanged(query) => {
+                library.query = query;
+                Task::none()
+            }
+            ModelMessage::Search => {
+                library.is_searching = true;
+                library.status = None;
+                Task::perform(models::search(library.query.clone()), ModelMessage::Found)
+                    .map(model_message)
+            }
+            ModelMessage::Found(res) => {
+                library.is_searching = false;
+                match res {
+                    Ok(results) => library.results = results,
+                    Err(e) => library.status = Some(format!("Search failed: {}", e)),
+                }
+                Task::none()
+            }
+            ModelMessage::Select(id) => {
+                library.selected = Some(id.clone());
+                library.variants.clear();
+                Task::perform(models::files(id.clone()), move |res| {
+                    ModelMessage::VariantsListed(id.clone(), res)
+                })
+                .map(model_message)
+            }
+            ModelMessage::VariantsListed(id, res) => {
+                if library.selected.as_ref() == Some(&id) {
+                    match res {
+                        Ok(variants) => library.variants = variants,
+                        Err(e) => library.status = Some(format!("Failed to list files: {}", e)),
+                    }
+                }
+                Task::none()
+            }
+            ModelMessage::Download(file) => {
+                Task::perform(models::enqueue(file), ModelMessage::Queued).map(model_message)
+            }
+            ModelMessage::Queued(res) => match res {
+                Ok(download) => {
+                    library.queue.retain(|queued| queued.file != download.file);
+                    library.queue.push(download);
+                    self.start_model_downloads()
+                }
+                Err(e) => {
+                    library.status = Some(e);
+                    Task::none()
+                }
+            },
+            ModelMessage::Progress(res) => match res {
+                Ok(Event::Started(file)) => {
+                    library.active = Some((file, None));
+                    Task::none()
+                }
+                Ok(Event::Progressed(file, progress)) => {
+                    library.active = Some((file, Some(progress)));
+                    Task::none()
+                }
+                Ok(Event::Verifying(file)) => {
+                    library.status = Some(format!("Verifying {}...", file.name));
+                    Task::none()
+                }
+                Ok(Event::Finished(file, _)) => {
+                    library.active = None;
+                    library.status = Some(format!("Downloaded {}", file.name));
+                    self.update_models(ModelMessage::Refresh)
+                }
+                Ok(Event::Failed(file, e)) => {
+                    library.active = None;
+                    library.status = Some(format!("{} failed: {}", file.name, e));
+                    self.update_models(ModelMessage::Refresh)
+                }
+                Err(e) => {
+                    log::warn!("Model downloads stopped: {}", e);
+                    library.status = Some(e);
+                    Task::none()
+                }
+            },
+            ModelMessage::QueueDrained => {
+                library.is_downloading = false;
+                library.active = None;
+                // Picks up files queued while the run was finishing
+                self.update_models(ModelMessage::Refresh)
+            }
+            ModelMessage::Cancel(file) => {
+                Task::perform(models::cancel(file), ModelMessage::Removed).map(model_message)
+            }
+            ModelMessage::Delete(file) => {
+                Task::perform(models::delete(file), ModelMessage::Removed).map(model_message)
+            }
+            ModelMessage::CollectGarbage => {
+                Task::perform(models::collect_garbage(), ModelMessage::Removed).map(model_message)
+            }
+            ModelMessage::Removed(res) => {
+                match res {
+                    Ok(paths) if paths.is_empty() => {}
+                    Ok(paths) => library.status = Some(format!("Removed {} files", paths.len())),
+                    Err(e) => library.status = Some(e),
+                }
+                self.update_models(ModelMessage::Refresh)
+            }
+            ModelMessage::Refresh => {
+                Task::perform(models::library(), ModelMessage::Refreshed).map(model_message)
+            }
+            ModelMessage::Refreshed(res) => match res {
+                Ok((queue, installed)) => {
+                    let pending = queue.iter().any(|download| download.error.is_none());
+                    library.queue = queue;
+                    library.installed = installed;
+
+                    if pending {
+                        self.start_model_downloads()
+                    } else {
+                        Task::none()
+                    }
+                }
+                Err(e) => {
+                    log::warn!("Failed to load model library: {}", e);
+                    Task::none()
+                }
+            },
+        }
+    }
+
+    /// Streams the download queue unless a run is already in progress.
+    #[cfg(feature = "intelligence")]
+    fn start_model_downloads(&mut self) -> Task<Message> {
+        if self.intelligence.models.is_downloading {
+            return Task::none();
+        }
+        self.intelligence.models.is_downloading = true;
+
+        use crate::prelude::futures::StreamExt;
+        let stream = models::run()
+            .map(|res| model_message(ModelMessage::Progress(res)))
+            .chain(crate::prelude::futures::stream::once(async {
+                model_message(ModelMessage::QueueDrained)
+            }));
+        Task::stream(stream)
+    }
+
+    /// Persists the current messages, creating the conversation on first save.
+    #[cfg(feature = "intelligence")]
+    fn save_conversation(&mut self) -> Task<Message> {
+        let id = self.intelligence.active_conversation.clone();
+        if id.is_none() {
+            if self.intelligence.creating_conversation {
+                // Saved again once the pending creation completes
+                return Task::none();
+            }
+            self.intelligence.creating_conversation = true;
+        }
+
+        let title = id.as_ref().and_then(|id| {
+            self.intelligence
+                .conversations
+                .iter()
+                .find(|c| &c.id == id)
+                .and_then(|c| c.title.clone())
+        });
+
+        Task::perform(
+            conversations::save(
+                id,
+                title,
+                self.intelligence.bridge.model().to_string(),
+                self.intelligence.chat_messages.to_vec(),
+            ),
+            |res| Message::Intelligence(IntelligenceMessage::ConversationSaved(res)),
+        )
+    }
+
     #[cfg(feature = "intelligence")]
     pub fn ai_chat_completion(&mut self) -> Task<Message> {
         self.intelligence.is_thinking = true;
-        let system_prompt = self.get_system_prompt();
-        let mut history: Vec<crate::core::ChatCompletionMessage> = self
+
+        // Recall memories for the latest user turn first so the reply can cite them
+        let query = self
             .intelligence
             .chat_messages
             .iter()
-            .map(|m| crate::core::ChatCompletionMessage {
-                role: match m.role {
-                    ChatRole::System => "system".to_string(),
-                    ChatRole::User => "user".to_string(),
-                    ChatRole::Assistant => "assistant".to_string(),
-                },
-                content: m.content.clone(),
-            })
-            .collect();
-        history.insert(
-            0,
-            crate::core::ChatCompletionMessage {
-                role: "system".to_string(),
-                content: system_prompt,
+            .rev()
+            .find(|m| m.role == ChatRole::User)
+            .map(|m| m.content.clone());
+
+        match query {
+            Some(query) => self.intelligence.bridge.recall(query).map(|res| {
+                Message::Intelligence(IntelligenceMessage::MemoryRecalled(res.unwrap_or_default()))
+            }),
+            None => self.stream_ai_chat(Vec::new()),
+        }
+    }
+
+    #[cfg(feature = "intelligence")]
+    fn stream_ai_chat(&mut self, memories: Vec<crate::core::SemanticRecord>) -> Task<Message> {
+        let system = |content| crate::core::ChatCompletionMessage {
+            role: "system".to_string(),
+            content,
+        };
+
+        let mut history = vec![system(self.get_system_prompt()), system(self.ui_context())];
+
+        if let Some(memory) = rag::context_message(&memories) {
+            history.push(memory);
+        }
+        self.intelligence.pending_citations = rag::citations(&memories);
+
+        let chat = |m: &ChatMessage| crate::core::ChatCompletionMessage {
+            role: match m.role {
+                ChatRole::System => "system".to_string(),
+                ChatRole::User => "user".to_string(),
+                ChatRole::Assistant => "assistant".to_string(),
             },
-        );
+            content: m.content.clone(),
+        };
+
+        // Turns already folded into the summary are sent as the summary
+        // alone, after the leading system messages compaction kept
+        let messages = &self.intelligence.chat_messages;
+        let covered = match &self.intelligence.context_summary {
+            Some(summary) => {
+                let covered = summary.covered.min(messages.len());
+                history.extend(
+                    messages[..covered]
+                        .iter()
+                        .take_while(|m| m.role == ChatRole::System)
+                        .map(chat),
+                );
+                if !summary.text.is_empty() {
+                    history.push(system(format!("{}\n{}", SUMMARY_HEADER, summary.text)));
+                }
+                covered
+            }
+            None => 0,
+        };
+
+        history.extend(messages[covered..].iter().map(chat));
 
+        self.intelligence
+            .bridge
+            .compact(history)
+            .map(|prompt| Message::Intelligence(IntelligenceMessage::ContextPrepared(prompt)))
+    }
+
+    #[cfg(feature = "intelligence")]
+    fn send_ai_chat(&mut self, history: Vec<crate::core::ChatCompletionMessage>) -> Task<Message> {
         let stream = self.intelligence.bridge.chat_stream(history);
         use crate::prelude::futures::StreamExt;
         let mapped_stream = stream
@@ -781,12 +1321,78 @@ impl App {
                             }),
                     );
                 }
+                Action::CallTool {
+                    ref name,
+                    ref arguments,
+                } => {
+                    let name = name.clone();
+                    if !crate::reference::intelligence::protocol::is_imported_tool(&name) {
+                        let error = format!("'{}' is not a tool imported from an MCP server", name);
+                        tasks.push(Task::done(Message::Intelligence(
+                            IntelligenceMessage::ProcessToolResult(
+                                name,
+                                serde_json::json!({ "error": error }),
+                            ),
+                        )));
+                        continue;
+                    }
+                    let reason = action.protection_reason().unwrap_or_default();
+                    let args = arguments.clone();
+                    tasks.push(Task::perform(async {}, move |_| {
+                        Message::Interaction(InteractionMessage::SudoRequest(
+                            crate::reference::app::SudoAction {
+                                message: Box::new(Message::Intelligence(
+                                    IntelligenceMessage::RunApprovedTool(
+                                        name.clone(),
+                                        args.clone(),
+                                    ),
+                                )),
+                                reason: reason.clone(),
+                                tool: name.clone(),
+                                args: args.clone(),
+                            },
+                        ))
+                    }));
+                }
                 Action::Shell(command) => {
                     tasks.push(Task::perform(async {}, move |_| {
                         Message::Interaction(InteractionMessage::SudoRequest(
                             crate::reference::app::SudoAction {
                                 message: Box::new(Message::ExecuteShell(command.clone())),
                                 reason: format!("Execute Shell Command: `{}`", command),
+                                tool: "shell".to_string(),
+                                args: serde_json::json!({ "command": command }),
+                            },
+                        ))
+                    }));
+                }
+                Action::RunCommand {
+                    ref argv,
+                    ref cwd,
+                    ref env,
+                    ref stdin,
+                    timeout_ms,
+                } => {
+                    let reason = action.protection_reason().unwrap_or_default();
+                    let args = serde_json::json!({
+                        "argv": argv,
+                        "cwd": cwd,
+                        "env": env,
+                        "stdin": stdin,
+                        "timeout_ms": timeout_ms,
+                    });
+                    tasks.push(Task::perform(async {}, move |_| {
+                        Message::Interaction(InteractionMessage::SudoRequest(
+                            crate::reference::app::SudoAction {
+                                message: Box::new(Message::Intelligence(
+                                    IntelligenceMessage::RunApprovedTool(
+                                        "run_command".to_string(),
+                                        args.clone(),
+                                    ),
+                                )),
+                                reason: reason.clone(),
+                                tool: "run_command".to_string(),
+                                args: args.clone(),
                             },
                         ))
                     }));
@@ -819,11 +1425,18 @@ impl App {
 
     #[cfg(feature = "intelligence")]
     fn get_system_prompt(&self) -> String {
+        "You are PeakUI AI Assistant. Help the user explore the framework.".to_string()
+    }
+
+    /// Snapshot of the current UI tree. Only the latest one is kept when the
+    /// prompt gets compacted.
+    #[cfg(feature = "intelligence")]
+    fn ui_context(&self) -> String {
         let ctx = self.context();
         let view = crate::reference::views::ContentView::new(self);
         let tree = view.describe(&ctx);
         let ui_json = serde_json::to_string(&tree).unwrap_or_default();
-        format!("You are PeakUI AI Assistant. Help the user explore the framework.\n\nUI CONTEXT:\n{}\n", ui_json)
+        format!("{}\n{}", UI_CONTEXT_HEADER, ui_json)
     }
 
     #[cfg(feature = "intelligence")]
@@ -849,7 +1462,8 @@ impl App {
                 self.db.clone(),
                 #[cfg(not(feature = "neural"))]
                 Arc::new(crate::reference::data::stub_db::StubDB::new()),
-            ),
+            )
+            .with_context_window(self.intelligence.context_tokens),
         );
     }
 
@@ -864,7 +1478,50 @@ impl IntelligenceState {
         Arc::make_mut(&mut self.chat_messages).push(msg);
     }
 
+    /// Starts a new, unsaved conversation.
+    pub fn reset_conversation(&mut self) {
+        self.chat_messages = Arc::new(Self::welcome_messages());
+        self.active_conversation = None;
+        self.renaming_conversation = None;
+        self.creating_conversation = false;
+        self.pending_citations.clear();
+        self.context_summary = None;
+        self.context_notice = None;
+    }
+
+    /// Shows a saved conversation. A chat still being created is only
+    /// listed once its save lands, as it is no longer the active one.
+    pub fn open_conversation(
+        &mut self,
+        summary: crate::views::ChatSummary,
+        messages: Vec<ChatMessage>,
+    ) {
+        self.chat_messages = Arc::new(if messages.is_empty() {
+            Self::welcome_messages()
+        } else {
+            messages
+        });
+        self.active_conversation = Some(summary.id.clone());
+        self.creating_conversation = false;
+        self.pending_citations.clear();
+        self.context_summary = None;
+        self.context_notice = None;
+        self.upsert_conversation(summary);
+    }
+
+    pub fn upsert_conversation(&mut self, summary: crate::views::ChatSummary) {
+        match self.conversations.iter_mut().find(|c| c.id == summary.id) {
+            Some(existing) => *existing = summary,
+            None => self.conversations.insert(0, summary),
+        }
+    }
+
     pub fn update_last_message_delta(&mut self, _delta: &str) {
         // Logic for streaming updates already inlined in update
     }
 }
+
+#[cfg(feature = "intelligence")]
+fn model_message(message: ModelMessage) -> Message {
+    Message::Intelligence(IntelligenceMessage::Models(message))
+}
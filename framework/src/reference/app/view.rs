@@ -3,7 +3,7 @@ use crate::prelude::*;
 use peak_core::registry::ShellMode;
 use peak_theme::ThemeTokens;
 
-use super::message::{InteractionMessage, Message};
+use super::message::{InteractionMessage, Message, ShellMessage};
 use super::state::*;
 use crate::reference::AppPage;
 
@@ -55,6 +55,7 @@ impl App {
                     &context,
                     context.is_slim(),
                     db_records.to_vec(),
+                    self.intelligence.focused_record.clone(),
                 )
                 .view
                 .view(&context)
@@ -167,67 +168,60 @@ impl App {
                 );
             }
 
-            // Overlay Sudo Prompt
-            if let Some(sudo) = &content.state.interaction.pending_sudo_action {
-                let prompt = crate::elements::atoms::Container::<Message, IcedBackend>::new(
-                    crate::layout::VStack::<Message, IcedBackend>::new_generic()
-                        .push(
-                            crate::elements::atoms::Text::<IcedBackend>::new(
-                                "Neural Sudo Permission",
-                            )
-                            .title1(),
-                        )
-                        .push(crate::elements::atoms::Text::<IcedBackend>::new(format!(
-                            "AI wants to perform: {:?}",
-                            sudo.message
-                        )))
-                        .push(
-                            crate::layout::HStack::<Message, IcedBackend>::new_generic()
-                                .push(
-                                    crate::elements::controls::Button::<Message, IcedBackend>::new(
-                                        crate::elements::atoms::Text::<IcedBackend>::new("Deny")
-                                            .color(Color::from_rgb8(50, 50, 50)),
-                                    )
-                                    .variant(crate::style::Variant::Ghost)
-                                    .intent(crate::style::Intent::Neutral)
-                                    .on_press(Message::Interaction(InteractionMessage::SudoDeny)),
-                                )
-                                .push(
-                                    crate::elements::controls::Button::<Message, IcedBackend>::new(
-                                        crate::elements::atoms::Text::<IcedBackend>::new("Approve")
-                                            .color(Color::WHITE),
-                                    )
-                                    .variant(crate::style::Variant::Solid)
-                                    .intent(crate::style::Intent::Danger)
-                                    .on_press(
-                                        Message::Interaction(InteractionMessage::SudoApprove),
-                                    ),
-                                )
-                                .spacing(10.0)
-                                .align_x(Alignment::Center),
-                        )
-                        .spacing(20.0)
-                        .align_x(Alignment::Center),
-                )
-                .padding(30.0)
-                .background(Color::WHITE)
-                .border(1.0, Color::from_rgb8(220, 220, 220))
-                .radius(16.0)
-                .shadow(Shadow {
-                    color: Color::from_rgba8(0, 0, 0, 0.1),
-                    offset: Vector::new(0.0, 10.0),
-                    blur_radius: 40.0,
-                });
-
-                // Wrap in centering container
-                let centered =
-                    crate::elements::atoms::Container::<Message, IcedBackend>::new(prompt)
+            // Overlay pending Neural Sudo approvals
+            #[cfg(feature = "intelligence")]
+            {
+                let pending = content.state.interaction.approvals.pending();
+                if !pending.is_empty() {
+                    let queue = crate::views::ApprovalQueue::<Message, IcedBackend>::new(
+                        pending,
+                        |id, decision| {
+                            Message::Interaction(InteractionMessage::ApprovalDecided(id, decision))
+                        },
+                    );
+
+                    let prompt =
+                        crate::elements::atoms::Container::<Message, IcedBackend>::new(queue)
+                            .padding(30.0)
+                            .background(Color::WHITE)
+                            .border(1.0, Color::from_rgb8(220, 220, 220))
+                            .radius(16.0)
+                            .shadow(Shadow {
+                                color: Color::from_rgba8(0, 0, 0, 0.1),
+                                offset: Vector::new(0.0, 10.0),
+                                blur_radius: 40.0,
+                            });
+
+                    // Wrap in centering container
+                    let centered =
+                        crate::elements::atoms::Container::<Message, IcedBackend>::new(prompt)
+                            .width(Length::Fill)
+                            .height(Length::Fill)
+                            .center_x(Length::Fill)
+                            .center_y(Length::Fill);
+
+                    stack = stack.push(centered.into_box());
+                }
+            }
+
+            // Overlay the Command Palette near the top, like a spotlight
+            let command_palette = &content.state.shell.command_palette;
+            if command_palette.is_open {
+                let palette = crate::views::CommandPalette::<Message, IcedBackend>::new(
+                    command_palette,
+                    |msg| Message::Shell(ShellMessage::CommandPalette(msg)),
+                );
+                stack = stack.push(
+                    crate::elements::atoms::Container::<Message, IcedBackend>::new(palette)
+                        .padding(Padding {
+                            top: 96.0,
+                            ..Default::default()
+                        })
                         .width(Length::Fill)
                         .height(Length::Fill)
                         .center_x(Length::Fill)
-                        .center_y(Length::Fill);
-
-                stack = stack.push(centered.into_box());
+                        .into_box(),
+                );
             }
 
             stack.into_box()
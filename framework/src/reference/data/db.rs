@@ -1,5 +1,5 @@
 use crate::core::DataProvider;
-use crate::semantic::SemanticRecord;
+use crate::semantic::{Metric, SemanticRecord, VectorIndex};
 use iced::Task;
 use peak_db::{PeakDB, PeakRecord};
 use std::sync::{Arc, Mutex};
@@ -10,8 +10,17 @@ pub struct PeakDBBridge {
     storage: Arc<Mutex<Arc<Vec<SemanticRecord>>>>,
     // Real SQLite connection (Global PeakDB)
     db: Arc<tokio::sync::RwLock<Option<PeakDB>>>,
+    // HNSW index over `storage`, persisted next to the memory file. Shared
+    // like `storage`, so snapshots for disk are cheap
+    index: Arc<Mutex<Arc<VectorIndex>>>,
 }
 
+#[cfg(not(target_arch = "wasm32"))]
+const INDEX_PATH: &str = ".peak/vector_index.json";
+/// Flush the index to disk after this many inserts/deletes.
+#[cfg(not(target_arch = "wasm32"))]
+const INDEX_FLUSH_THRESHOLD: usize = 32;
+
 impl Default for PeakDBBridge {
     fn default() -> Self {
         Self::new()
@@ -23,7 +32,14 @@ impl PeakDBBridge {
         let storage = Arc::new(Mutex::new(Arc::new(Vec::new())));
         let db = Arc::new(tokio::sync::RwLock::new(None));
 
-        let slf = Self { storage, db };
+        #[cfg(not(target_arch = "wasm32"))]
+        let index =
+            VectorIndex::load(INDEX_PATH).unwrap_or_else(|| VectorIndex::new(Metric::Cosine));
+        #[cfg(target_arch = "wasm32")]
+        let index = VectorIndex::new(Metric::Cosine);
+        let index = Arc::new(Mutex::new(Arc::new(index)));
+
+        let slf = Self { storage, db, index };
 
         // Load initial state (from JSON fallback if exists)
         slf.load();
@@ -31,6 +47,7 @@ impl PeakDBBridge {
         // Spawn background initialization for Global SQLite
         let storage_clone = slf.storage.clone();
         let db_clone = slf.db.clone();
+        let index_clone = slf.index.clone();
 
         #[cfg(not(target_arch = "wasm32"))]
         {
@@ -96,6 +113,12 @@ impl PeakDBBridge {
                                 let count = loaded.len();
                                 *storage = Arc::new(loaded);
                                 eprintln!("[PeakDB] Synced {} records from SQLite.", count);
+
+                                if let Ok(mut index) = index_clone.lock() {
+                                    Arc::make_mut(&mut index).sync(storage.iter());
+                                    persist_index(&mut index, 1);
+                                    eprintln!("[PeakDB] Indexed {} vectors.", index.len());
+                                }
                             }
                         }
 
@@ -142,6 +165,11 @@ impl PeakDBBridge {
                 }
             }
         }
+
+        // A persisted index may lag behind the records (or be missing entirely)
+        if let (Ok(storage), Ok(mut index)) = (self.storage.lock(), self.index.lock()) {
+            Arc::make_mut(&mut index).sync(storage.iter());
+        }
     }
 
     pub fn save_to_disk(&self) {
@@ -153,6 +181,9 @@ impl PeakDBBridge {
                     let _ = std::fs::write(".peak/memory.json", content);
                 }
             }
+            if let Ok(mut index) = self.index.lock() {
+                persist_index(&mut index, 1);
+            }
         }
 
         #[cfg(target_arch = "wasm32")]
@@ -181,9 +212,12 @@ impl DataProvider for PeakDBBridge {
     fn save(&self, record: SemanticRecord) -> Task<std::result::Result<(), String>> {
         let storage_handle = self.storage.clone();
         let db_handle = self.db.clone();
+        let index_handle = self.index.clone();
 
         Task::perform(
             async move {
+                index_record(&index_handle, &record);
+
                 // Update in-memory cache first
                 if let Ok(mut mutex) = storage_handle.lock() {
                     let mut new_vec = (**mutex).clone();
@@ -260,8 +294,14 @@ impl DataProvider for PeakDBBridge {
 
     fn delete(&self, id: String) -> Task<std::result::Result<(), String>> {
         let storage_handle = self.storage.clone();
+        let index_handle = self.index.clone();
         Task::perform(
             async move {
+                if let Ok(mut index) = index_handle.lock() {
+                    Arc::make_mut(&mut index).remove(&id);
+                    #[cfg(not(target_arch = "wasm32"))]
+                    persist_index(&mut index, INDEX_FLUSH_THRESHOLD);
+                }
                 let mut mutex = storage_handle.lock().map_err(|e| e.to_string())?;
                 let mut new_vec = (**mutex).clone();
                 new_vec.retain(|r| r.id != id);
@@ -338,8 +378,11 @@ impl DataProvider for PeakDBBridge {
     ) -> iced::futures::future::BoxFuture<'static, std::result::Result<(), String>> {
         let storage_handle = self.storage.clone();
         let db_handle = self.db.clone();
+        let index_handle = self.index.clone();
 
         Box::pin(async move {
+            index_record(&index_handle, &record);
+
             // Update in-memory cache
             if let Ok(mut mutex) = storage_handle.lock() {
                 let mut new_vec = (**mutex).clone();
@@ -391,9 +434,25 @@ impl DataProvider for PeakDBBridge {
     {
         let db_handle = self.db.clone();
         let storage_handle = self.storage.clone();
+        let index_handle = self.index.clone();
 
         Box::pin(async move {
-            // Try Global SQLite first for high-quality semantic search
+            // The HNSW index mirrors the in-memory cache, so answer from it when populated
+            let hits = index_handle
+                .lock()
+                .map(|index| index.search(&vector, limit))
+                .unwrap_or_default();
+            if !hits.is_empty() {
+                let db = storage_handle.lock().map_err(|e| e.to_string())?;
+                let by_id: std::collections::HashMap<&str, &SemanticRecord> =
+                    db.iter().map(|r| (r.id.as_str(), r)).collect();
+                return Ok(hits
+                    .into_iter()
+                    .filter_map(|(id, _)| by_id.get(id.as_str()).map(|r| (*r).clone()))
+                    .collect());
+            }
+
+            // Fall back to Global SQLite while the index is still empty
             let db_read = db_handle.read().await;
             if let Some(pdb) = &*db_read {
                 if let Ok(records) = pdb.find_semantic(&vector, limit).await {
@@ -438,6 +497,53 @@ impl DataProvider for PeakDBBridge {
     }
 }
 
+fn index_record(index: &Mutex<Arc<VectorIndex>>, record: &SemanticRecord) {
+    let Some(vector) = &record.vector else {
+        return;
+    };
+    if let Ok(mut index) = index.lock() {
+        let vectors = Arc::make_mut(&mut index);
+        if let Err(e) = vectors.insert(record.collection.clone(), record.id.clone(), vector) {
+            eprintln!("[PeakDB] Could not index record {}: {}", record.id, e);
+            // The record is still saved, just not found by the vector it replaced
+            vectors.remove(&record.id);
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        persist_index(&mut index, INDEX_FLUSH_THRESHOLD);
+    }
+}
+
+/// Writes a snapshot of `index` once it has `threshold` unsaved changes.
+/// The snapshot shares the index rather than copying it, so the caller's
+/// lock is held only to hand it off; serializing and writing it happen on a
+/// blocking thread. Only a change made while the snapshot is still being
+/// written copies the index, once.
+#[cfg(not(target_arch = "wasm32"))]
+fn persist_index(index: &mut Arc<VectorIndex>, threshold: usize) {
+    // Writes share a temporary file, so they go one at a time. One landing
+    // out of order only leaves the index behind, which `load` syncs up.
+    static WRITING: Mutex<()> = Mutex::new(());
+
+    if index.unsaved_changes() < threshold {
+        return;
+    }
+    Arc::make_mut(index).mark_saved();
+    let snapshot = Arc::clone(index);
+
+    let write = move || {
+        let _writing = WRITING.lock();
+        if let Err(e) = snapshot.write(INDEX_PATH) {
+            eprintln!("[PeakDB] Failed to persist vector index: {}", e);
+        }
+    };
+    match tokio::runtime::Handle::try_current() {
+        Ok(runtime) => {
+            runtime.spawn_blocking(write);
+        }
+        Err(_) => write(),
+    }
+}
+
 fn cosine_similarity(v1: &[f32], v2: &[f32]) -> f32 {
     if v1.len() != v2.len() || v1.is_empty() {
         return 0.0;
@@ -1,67 +1,190 @@
 use crate::core::SemanticRecord;
-use crate::semantic::DataProvider;
+use crate::semantic::{DataProvider, VectorIndex};
 use iced::Task;
+use std::sync::{Arc, Mutex};
 
-pub struct StubDB;
+/// In-memory provider used when the `neural` feature (and PeakDB) is disabled.
+///
+/// Records live only for the lifetime of the process, but semantic search goes
+/// through the same [`VectorIndex`] as `PeakDBBridge`.
+#[derive(Debug, Default)]
+pub struct StubDB {
+    records: Arc<Mutex<Vec<SemanticRecord>>>,
+    index: Arc<Mutex<VectorIndex>>,
+}
 
 impl StubDB {
     pub fn new() -> Self {
-        Self
+        Self::default()
     }
 
-    pub fn save(&self, _record: SemanticRecord) -> Task<std::result::Result<(), String>> {
-        Task::none()
+    pub fn save(&self, record: SemanticRecord) -> Task<std::result::Result<(), String>> {
+        DataProvider::save(self, record)
     }
 
     pub fn search(
         &self,
-        _query: &str,
-        _limit: usize,
+        query: &str,
+        limit: usize,
     ) -> Task<std::result::Result<Vec<SemanticRecord>, String>> {
-        Task::none()
+        let records = self.records.clone();
+        let query = query.to_lowercase();
+        Task::perform(
+            async move {
+                let records = records.lock().map_err(|e| e.to_string())?;
+                Ok(records
+                    .iter()
+                    .filter(|r| r.content.to_lowercase().contains(&query))
+                    .take(limit)
+                    .cloned()
+                    .collect())
+            },
+            |res| res,
+        )
     }
 
     pub fn get_all(&self) -> Vec<SemanticRecord> {
-        Vec::new()
+        self.records
+            .lock()
+            .map(|records| records.clone())
+            .unwrap_or_default()
+    }
+}
+
+fn upsert(
+    records: &Mutex<Vec<SemanticRecord>>,
+    index: &Mutex<VectorIndex>,
+    record: SemanticRecord,
+) -> std::result::Result<(), String> {
+    if let Some(vector) = &record.vector {
+        let mut index = index.lock().map_err(|e| e.to_string())?;
+        // The record is still saved, as `PeakDBBridge` saves it, just not
+        // found by the vector it replaced
+        if let Err(e) = index.insert(record.collection.clone(), record.id.clone(), vector) {
+            log::warn!("Could not index record {}: {}", record.id, e);
+            index.remove(&record.id);
+        }
     }
+
+    let mut records = records.lock().map_err(|e| e.to_string())?;
+    if let Some(pos) = records.iter().position(|r| r.id == record.id) {
+        records[pos] = record;
+    } else {
+        records.push(record);
+    }
+    Ok(())
 }
 
 impl DataProvider for StubDB {
-    fn save(&self, _record: SemanticRecord) -> Task<std::result::Result<(), String>> {
-        Task::none()
+    fn save(&self, record: SemanticRecord) -> Task<std::result::Result<(), String>> {
+        let records = self.records.clone();
+        let index = self.index.clone();
+        Task::perform(async move { upsert(&records, &index, record) }, |res| res)
     }
 
-    fn find(&self, _query: String) -> Task<std::result::Result<Vec<SemanticRecord>, String>> {
-        Task::none()
+    fn find(&self, query: String) -> Task<std::result::Result<Vec<SemanticRecord>, String>> {
+        self.search(&query, usize::MAX)
     }
 
-    fn delete(&self, _id: String) -> Task<std::result::Result<(), String>> {
-        Task::none()
+    fn delete(&self, id: String) -> Task<std::result::Result<(), String>> {
+        let records = self.records.clone();
+        let index = self.index.clone();
+        Task::perform(
+            async move {
+                index.lock().map_err(|e| e.to_string())?.remove(&id);
+                records
+                    .lock()
+                    .map_err(|e| e.to_string())?
+                    .retain(|r| r.id != id);
+                Ok(())
+            },
+            |res| res,
+        )
     }
 
     fn async_find(
         &self,
-        _query: String,
+        query: String,
     ) -> iced::futures::future::BoxFuture<'static, std::result::Result<Vec<SemanticRecord>, String>>
     {
         use iced::futures::FutureExt;
-        async { Ok(Vec::new()) }.boxed()
+        let records = self.records.clone();
+        async move {
+            let records = records.lock().map_err(|e| e.to_string())?;
+            let query = query.to_lowercase();
+            Ok(records
+                .iter()
+                .filter(|r| r.content.to_lowercase().contains(&query))
+                .cloned()
+                .collect())
+        }
+        .boxed()
     }
 
     fn async_save(
         &self,
-        _record: SemanticRecord,
+        record: SemanticRecord,
     ) -> iced::futures::future::BoxFuture<'static, std::result::Result<(), String>> {
         use iced::futures::FutureExt;
-        async { Ok(()) }.boxed()
+        let records = self.records.clone();
+        let index = self.index.clone();
+        async move { upsert(&records, &index, record) }.boxed()
     }
 
     fn async_find_semantic(
         &self,
-        _vector: Vec<f32>,
-        _limit: usize,
-    ) -> iced::futures::future::BoxFuture<'static, std::result::Result<Vec<SemanticRecord>, String>> {
+        vector: Vec<f32>,
+        limit: usize,
+    ) -> iced::futures::future::BoxFuture<'static, std::result::Result<Vec<SemanticRecord>, String>>
+    {
         use iced::futures::FutureExt;
-        async { Ok(Vec::new()) }.boxed()
+        let records = self.records.clone();
+        let index = self.index.clone();
+        async move {
+            let hits = index
+                .lock()
+                .map_err(|e| e.to_string())?
+                .search(&vector, limit);
+            let records = records.lock().map_err(|e| e.to_string())?;
+            Ok(hits
+                .into_iter()
+                .filter_map(|(id, _)| records.iter().find(|r| r.id == id).cloned())
+                .collect())
+        }
+        .boxed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(id: &str, vector: Vec<f32>) -> SemanticRecord {
+        SemanticRecord {
+            id: id.to_string(),
+            collection: "Memory".to_string(),
+            content: id.to_string(),
+            vector: Some(vector),
+            metadata: serde_json::json!({}),
+            timestamp: 0,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_records_are_kept_when_their_vector_cannot_be_indexed() {
+        let db = StubDB::new();
+        db.async_save(record("a", vec![1.0, 0.0])).await.unwrap();
+        db.async_save(record("b", vec![1.0, 0.0, 0.0]))
+            .await
+            .unwrap();
+        db.async_save(record("a", vec![])).await.unwrap();
+
+        let mut ids: Vec<String> = db.get_all().into_iter().map(|r| r.id).collect();
+        ids.sort();
+        assert_eq!(ids, ["a", "b"]);
+
+        // Neither is found by a vector the index doesn't hold for it
+        let found = db.async_find_semantic(vec![1.0, 0.0], 5).await.unwrap();
+        assert!(found.is_empty());
     }
 }
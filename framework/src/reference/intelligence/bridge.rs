@@ -1,7 +1,10 @@
 #![cfg(feature = "intelligence")]
-use crate::core::IntelligenceProvider;
+use crate::core::{CompactedPrompt, IntelligenceProvider, SemanticRecord};
+use crate::reference::intelligence::rag;
 use chrono;
 use iced::Task;
+use peak_intelligence::brain::chat;
+use peak_intelligence::context::{self, ContextBudget};
 use peak_intelligence::llm::{LlmClient, Message, ModelProvider};
 use serde_json::Value;
 use std::sync::Arc;
@@ -14,6 +17,7 @@ pub static GLOBAL_BRIDGE: OnceLock<Arc<PeakIntelligenceBridge>> = OnceLock::new(
 pub struct PeakIntelligenceBridge {
     client: LlmClient,
     db: Arc<dyn crate::core::DataProvider>,
+    budget: ContextBudget,
 }
 
 impl std::fmt::Debug for PeakIntelligenceBridge {
@@ -29,6 +33,7 @@ impl Clone for PeakIntelligenceBridge {
         Self {
             client: self.client.clone(),
             db: self.db.clone(),
+            budget: self.budget,
         }
     }
 }
@@ -54,9 +59,18 @@ impl PeakIntelligenceBridge {
         Self {
             client: LlmClient::new(provider, model.into(), api_key),
             db,
+            budget: ContextBudget::for_provider(provider),
         }
     }
 
+    /// Overrides the provider's default context window, in tokens.
+    pub fn with_context_window(mut self, tokens: Option<usize>) -> Self {
+        if let Some(tokens) = tokens {
+            self.budget = ContextBudget::new(tokens);
+        }
+        self
+    }
+
     pub async fn chat_direct(
         &self,
         messages: Vec<crate::core::ChatCompletionMessage>,
@@ -66,61 +80,14 @@ impl PeakIntelligenceBridge {
 
         let mut final_messages = Vec::new();
 
-        // Generate Action Schema
-        let schema = schemars::schema_for!(crate::reference::intelligence::Action);
-        let schema_json = serde_json::to_string_pretty(&schema).unwrap_or_default();
-
-        let system_instruction = format!(
-            "You are the PeakOS Intelligence AI Assistant. You perceive the UI as a Dense JSON tree.\n\n\
-             You can trigger UI actions and external tools by including valid JSON in your response using the format [action: {{...}})].\n\n\
-             REQUIRED ACTION SCHEMA:\n{}\n\n\
-             CRITICAL TOOLS:\n\
-             - Use 'WebSearch' for any information you don't know.\n\
-             - Use 'WriteFile' to save documents or code. ALWAYS prefer '~/Desktop/' for user visibility. DO NOT use the OS root '/' as it is read-only.\n\
-             - Use 'Navigate' to move between pages.\n\n\
-             CRITICAL: You MUST terminate actions with ')]'. \n\
-             Example: [action: {{\"WebSearch\": \"latest rust version\"}})]",
-            schema_json
-        );
-
         final_messages.push(Message {
             role: "system".to_string(),
-            content: system_instruction,
+            content: assistant_instruction(),
         });
 
-        // 1. RAG: Search for context if we have a user message
-        if let Some(user_msg) = messages.iter().rev().find(|m| m.role == "user") {
-            let query = user_msg.content.clone();
-
-            let mut final_records = Vec::new();
-            // Try semantic search first
-            if let Ok(vector) = client.embeddings(&query).await {
-                if let Ok(semantic_records) = db.async_find_semantic(vector, 10).await {
-                    final_records = semantic_records;
-                }
-            }
-
-            // Fallback/Supplement with keyword search
-            if let Ok(keyword_records) = db.async_find(query).await {
-                for r in keyword_records {
-                    if !final_records.iter().any(|existing| existing.id == r.id) {
-                        final_records.push(r);
-                    }
-                }
-            }
-
-            if !final_records.is_empty() {
-                let context = final_records
-                    .iter()
-                    .map(|r| format!("[Memory: {}] {}", r.collection, r.content))
-                    .collect::<Vec<_>>()
-                    .join("\n");
-
-                final_messages.push(Message {
-                    role: "system".to_string(),
-                    content: format!("### LONG-TERM MEMORY (Neural Layer)\nThe following information was retrieved from your persistent memory bank. Priority context:\n\n{}", context),
-                });
-            }
+        // 1. RAG: Inject cited memories unless the caller already did
+        if let Some(memory) = recall_context(&client, &db, &messages).await {
+            final_messages.push(memory);
         }
 
         // 2. Append original messages
@@ -131,8 +98,241 @@ impl PeakIntelligenceBridge {
             });
         }
 
-        client.chat(final_messages).await
+        client
+            .chat(fit_context(&client, self.budget, final_messages))
+            .await
+    }
+
+    /// Names a conversation with the same prompt as `brain::chat::title`,
+    /// completed through the configured provider.
+    pub async fn title(&self, history: Vec<chat::Item>) -> std::result::Result<String, String> {
+        let messages = chat::title_messages(&history)
+            .iter()
+            .map(|message| {
+                let (role, content) = message.to_tuple();
+                Message {
+                    role: role.to_string(),
+                    content: content.to_string(),
+                }
+            })
+            .collect();
+
+        let title = self.client.chat(messages).await?;
+
+        Ok(chat::sanitize_title(&title))
+    }
+
+    /// Runs the built-in tool `name`. Commands aren't among them: those run
+    /// only once approved, through [`run_approved_command`], as do imported
+    /// tools.
+    fn run_tool(
+        &self,
+        name: String,
+        args: Value,
+    ) -> impl std::future::Future<Output = std::result::Result<Value, String>> + 'static {
+        let client = self.client.clone();
+        let db = self.db.clone();
+
+        async move {
+            match name.as_str() {
+                #[cfg(not(target_arch = "wasm32"))]
+                #[cfg(feature = "native")]
+                "web_search" => {
+                    let query = args.get("query").and_then(|v| v.as_str()).unwrap_or("");
+                    let brave_key = args
+                        .get("brave_key")
+                        .and_then(|v| v.as_str())
+                        .map(|s| s.to_string());
+                    let tavily_key = args
+                        .get("tavily_key")
+                        .and_then(|v| v.as_str())
+                        .map(|s| s.to_string());
+                    peak_intelligence::tools::web_search_routed(query, brave_key, tavily_key)
+                        .await
+                        .map_err(|e| e.to_string())
+                }
+                #[cfg(target_arch = "wasm32")]
+                "web_search" => {
+                    let query = args
+                        .get("query")
+                        .and_then(|v| v.as_str())
+                        .unwrap_or("")
+                        .to_string();
+                    let brave_key = args
+                        .get("brave_key")
+                        .and_then(|v| v.as_str())
+                        .map(|s| s.to_string());
+                    let tavily_key = args
+                        .get("tavily_key")
+                        .and_then(|v| v.as_str())
+                        .map(|s| s.to_string());
+                    let (tx, rx) = futures::channel::oneshot::channel();
+
+                    wasm_bindgen_futures::spawn_local(async move {
+                        let res = peak_intelligence::tools::web_search_routed(
+                            &query, brave_key, tavily_key,
+                        )
+                        .await;
+                        let _ = tx.send(res.map_err(|e| e.to_string()));
+                    });
+
+                    rx.await.map_err(|e| e.to_string())?
+                }
+                #[cfg(feature = "native")]
+                "get_system_snapshot" => {
+                    peak_intelligence::tools::get_system_snapshot().map_err(|e| e.to_string())
+                }
+                #[cfg(feature = "native")]
+                "read_file" => {
+                    let path = args.get("path").and_then(|v| v.as_str()).unwrap_or("");
+                    peak_intelligence::tools::read_file(path).map_err(|e| e.to_string())
+                }
+                #[cfg(feature = "native")]
+                "write_file" => {
+                    let path = args.get("path").and_then(|v| v.as_str()).unwrap_or("");
+                    let content = args.get("content").and_then(|v| v.as_str()).unwrap_or("");
+                    peak_intelligence::tools::write_file(path, content).map_err(|e| e.to_string())
+                }
+                #[cfg(feature = "native")]
+                "list_processes" => {
+                    peak_intelligence::tools::list_processes().map_err(|e| e.to_string())
+                }
+                "memorize" => {
+                    let content = args.get("content").and_then(|v| v.as_str()).unwrap_or("");
+                    if content.is_empty() {
+                        return Ok(
+                            serde_json::json!({ "status": "error", "message": "No content provided" }),
+                        );
+                    }
+
+                    let db = db.clone();
+                    let client = client.clone();
+                    let content_owned = content.to_string();
+
+                    // Generate embedding for the new memory
+                    let vector = client.embeddings(&content_owned).await.ok();
+
+                    let record = crate::semantic::SemanticRecord {
+                        id: uuid::Uuid::new_v4().to_string(),
+                        collection: "Memory".to_string(),
+                        content: content_owned,
+                        vector,
+                        metadata: serde_json::json!({}),
+                        timestamp: chrono::Utc::now().timestamp() as u64,
+                    };
+
+                    db.async_save(record).await?;
+
+                    Ok(serde_json::json!({
+                        "status": "success",
+                        "message": "Information saved to memory with semantic embedding."
+                    }))
+                }
+                _ => Ok(serde_json::json!({
+                    "status": "success",
+                    "tool": name,
+                    "args": args,
+                    "message": "Tool stub executed (logic not yet linked or platform not supported)"
+                })),
+            }
+        }
+    }
+}
+
+/// Runs a `run_command` the user approved. Only
+/// `IntelligenceMessage::RunApprovedTool` comes here; no tool call by name
+/// does.
+#[cfg(feature = "native")]
+pub fn run_approved_command(args: Value) -> Task<std::result::Result<Value, String>> {
+    use peak_intelligence::tools::command;
+
+    Task::perform(
+        async move {
+            let request: command::Request =
+                serde_json::from_value(args).map_err(|e| e.to_string())?;
+            command::run(&request)
+                .await
+                .map(|output| serde_json::json!(output))
+                .map_err(|e| format!("{:#}", e))
+        },
+        |res| res,
+    )
+}
+
+/// Instructions `chat` and `chat_direct` prepend to every request.
+fn assistant_instruction() -> String {
+    let schema = schemars::schema_for!(crate::reference::intelligence::Action);
+    let schema_json = serde_json::to_string_pretty(&schema).unwrap_or_default();
+
+    format!(
+        "You are the PeakOS Intelligence AI Assistant. You perceive the UI as a Dense JSON tree.\n\n\
+         You can trigger UI actions and external tools by including valid JSON in your response using the format [action: {{...}})].\n\n\
+         REQUIRED ACTION SCHEMA:\n{}\n\n\
+         CRITICAL TOOLS:\n\
+         - Use 'WebSearch' for any information you don't know.\n\
+         - Use 'WriteFile' to save documents or code. ALWAYS prefer '~/Desktop/' for user visibility. DO NOT use the OS root '/' as it is read-only.\n\
+         - Use 'RunCommand' to run builds, tests and other programs. Pass the program and its arguments as 'argv'; it is not run through a shell. The user must approve every command.\n\
+         - Use 'Navigate' to move between pages.\n\n\
+         CRITICAL: You MUST terminate actions with ')]'. \n\
+         Example: [action: {{\"WebSearch\": \"latest rust version\"}})]{}",
+        schema_json,
+        external_tools()
+    )
+}
+
+/// Instructions `chat_stream` prepends to every request.
+fn bridge_instruction() -> String {
+    let schema = schemars::schema_for!(crate::reference::intelligence::Action);
+    let schema_json = serde_json::to_string_pretty(&schema).unwrap_or_default();
+
+    format!(
+        "You are the PeakOS Intelligence Bridge. You can trigger UI actions by including valid JSON in your response using the format [action: {{...}})].\n\nREQUIRED ACTION SCHEMA:\n{}\n\nExample: To navigate to settings, output: [action: {{\"Navigate\": \"SettingsAI\"}})]{}",
+        schema_json,
+        external_tools()
+    )
+}
+
+/// Tools from connected MCP servers, appended to the instructions.
+fn external_tools() -> String {
+    #[cfg(feature = "native")]
+    return crate::reference::intelligence::external_tools::instructions();
+
+    #[cfg(not(feature = "native"))]
+    String::new()
+}
+
+/// Last guard before a request leaves: drops and truncates whatever still
+/// exceeds the budget. Callers wanting summaries use `compact` beforehand.
+fn fit_context(client: &LlmClient, budget: ContextBudget, messages: Vec<Message>) -> Vec<Message> {
+    let fitted = context::truncate(client.provider(), budget, messages);
+
+    if fitted.is_compacted() {
+        log::warn!(
+            "Prompt exceeded the context budget ({} -> {} tokens): {:?}",
+            fitted.before_tokens,
+            fitted.after_tokens,
+            fitted.events
+        );
     }
+
+    fitted.messages
+}
+
+async fn recall_context(
+    client: &LlmClient,
+    db: &Arc<dyn crate::core::DataProvider>,
+    messages: &[crate::core::ChatCompletionMessage],
+) -> Option<Message> {
+    if rag::has_context(messages) {
+        return None;
+    }
+    let query = rag::last_user_query(messages)?;
+    let records = rag::retrieve(client, db, query, rag::TOP_K).await;
+
+    rag::context_message(&records).map(|m| Message {
+        role: m.role,
+        content: m.content,
+    })
 }
 
 impl IntelligenceProvider for PeakIntelligenceBridge {
@@ -150,69 +350,21 @@ impl IntelligenceProvider for PeakIntelligenceBridge {
     ) -> Task<std::result::Result<String, String>> {
         let client = self.client.clone();
         let db = self.db.clone();
+        let budget = self.budget;
         let messages_clone = messages.clone();
 
         Task::perform(
             async move {
                 let mut final_messages = Vec::new();
 
-                // Generate Action Schema
-                let schema = schemars::schema_for!(crate::reference::intelligence::Action);
-                let schema_json = serde_json::to_string_pretty(&schema).unwrap_or_default();
-
-                let system_instruction = format!(
-                    "You are the PeakOS Intelligence AI Assistant. You perceive the UI as a Dense JSON tree.\n\n\
-                     You can trigger UI actions and external tools by including valid JSON in your response using the format [action: {{...}})].\n\n\
-                     REQUIRED ACTION SCHEMA:\n{}\n\n\
-                     CRITICAL TOOLS:\n\
-                     - Use 'WebSearch' for any information you don't know.\n\
-                     - Use 'WriteFile' to save documents or code. ALWAYS prefer '~/Desktop/' for user visibility. DO NOT use the OS root '/' as it is read-only.\n\
-                     - Use 'Navigate' to move between pages.\n\n\
-                     CRITICAL: You MUST terminate actions with ')]'. \n\
-                     Example: [action: {{\"WebSearch\": \"latest rust version\"}})]",
-                    schema_json
-                );
-
                 final_messages.push(Message {
                     role: "system".to_string(),
-                    content: system_instruction,
+                    content: assistant_instruction(),
                 });
 
-                // 1. RAG: Search for context if we have a user message
-                if let Some(user_msg) = messages_clone.iter().rev().find(|m| m.role == "user") {
-                    let client = client.clone();
-                    let db = db.clone();
-                    let query = user_msg.content.clone();
-
-                    let mut final_records = Vec::new();
-                    // Try semantic search first
-                    if let Ok(vector) = client.embeddings(&query).await {
-                        if let Ok(semantic_records) = db.async_find_semantic(vector, 10).await {
-                            final_records = semantic_records;
-                        }
-                    }
-
-                    // Fallback/Supplement with keyword search
-                    if let Ok(keyword_records) = db.async_find(query).await {
-                        for r in keyword_records {
-                            if !final_records.iter().any(|existing| existing.id == r.id) {
-                                final_records.push(r);
-                            }
-                        }
-                    }
-
-                    if !final_records.is_empty() {
-                        let context = final_records
-                            .iter()
-                            .map(|r| format!("[Memory: {}] {}", r.collection, r.content))
-                            .collect::<Vec<_>>()
-                            .join("\n");
-
-                        final_messages.push(Message {
-                            role: "system".to_string(),
-                            content: format!("### LONG-TERM MEMORY (Neural Layer)\nThe following information was retrieved from your persistent memory bank. Priority context:\n\n{}", context),
-                        });
-                    }
+                // 1. RAG: Inject cited memories unless the caller already did
+                if let Some(memory) = recall_context(&client, &db, &messages_clone).await {
+                    final_messages.push(memory);
                 }
 
                 // 2. Append original messages
@@ -223,12 +375,50 @@ impl IntelligenceProvider for PeakIntelligenceBridge {
                     });
                 }
 
-                client.chat(final_messages).await
+                client
+                    .chat(fit_context(&client, budget, final_messages))
+                    .await
             },
             |res| res,
         )
     }
 
+    fn context_budget(&self) -> ContextBudget {
+        self.budget
+    }
+
+    fn compact(&self, messages: Vec<crate::core::ChatCompletionMessage>) -> Task<CompactedPrompt> {
+        let client = self.client.clone();
+        let provider = client.provider();
+        // Leave room for the instructions `chat_stream` adds
+        let budget = self
+            .budget
+            .without(context::estimate_tokens(provider, &bridge_instruction()));
+
+        Task::perform(
+            async move {
+                context::compact(
+                    provider,
+                    budget,
+                    messages.into_iter().map(Into::into).collect(),
+                    |turns| context::summarize(&client, budget, turns),
+                )
+                .await
+            },
+            CompactedPrompt::from,
+        )
+    }
+
+    fn recall(&self, query: String) -> Task<std::result::Result<Vec<SemanticRecord>, String>> {
+        let client = self.client.clone();
+        let db = self.db.clone();
+
+        Task::perform(
+            async move { Ok(rag::retrieve(&client, &db, &query, rag::TOP_K).await) },
+            |res| res,
+        )
+    }
+
     #[cfg(not(target_arch = "wasm32"))]
     fn chat_stream(
         &self,
@@ -237,45 +427,18 @@ impl IntelligenceProvider for PeakIntelligenceBridge {
         use iced::futures::StreamExt;
 
         let client = self.client.clone();
-        let db = self.db.clone();
+        let budget = self.budget;
         let messages_clone = messages.clone();
 
         async_stream::stream! {
             let mut final_messages = Vec::new();
 
-            // Generate Action Schema
-            let schema = schemars::schema_for!(crate::reference::intelligence::Action);
-            let schema_json = serde_json::to_string_pretty(&schema).unwrap_or_default();
-
-            let system_instruction = format!(
-                "You are the PeakOS Intelligence Bridge. You can trigger UI actions by including valid JSON in your response using the format [action: {{...}})].\n\nREQUIRED ACTION SCHEMA:\n{}\n\nExample: To navigate to settings, output: [action: {{\"Navigate\": \"SettingsAI\"}})]",
-                schema_json
-            );
-
             final_messages.push(Message {
                 role: "system".to_string(),
-                content: system_instruction,
+                content: bridge_instruction(),
             });
 
-            // 1. RAG: Search for context if we have a user message
-            if let Some(user_msg) = messages_clone.iter().rev().find(|m| m.role == "user") {
-                if let Ok(records) = db.async_find(user_msg.content.clone()).await {
-                    if !records.is_empty() {
-                        let context = records
-                            .iter()
-                            .map(|r| format!("[Memory: {}] {}", r.collection, r.content))
-                            .collect::<Vec<_>>()
-                            .join("\n");
-
-                        final_messages.push(Message {
-                            role: "system".to_string(),
-                            content: format!("Relevant context from PeakDB:\n{}", context),
-                        });
-                    }
-                }
-            }
-
-            // 2. Append original messages
+            // Memories are recalled and cited by the caller, see `recall`
             for m in messages_clone {
                 final_messages.push(Message {
                     role: m.role,
@@ -283,6 +446,7 @@ impl IntelligenceProvider for PeakIntelligenceBridge {
                 });
             }
 
+            let final_messages = fit_context(&client, budget, final_messages);
             let mut stream = Box::pin(client.chat_stream(final_messages));
             while let Some(res) = stream.next().await {
                 match res {
@@ -308,45 +472,18 @@ impl IntelligenceProvider for PeakIntelligenceBridge {
         let (mut sender, receiver) = iced::futures::channel::mpsc::channel(100);
 
         let client = self.client.clone();
-        let db = self.db.clone();
+        let budget = self.budget;
         let messages_clone = messages.clone();
 
         wasm_bindgen_futures::spawn_local(async move {
             let mut final_messages = Vec::new();
 
-            // Generate Action Schema
-            let schema = schemars::schema_for!(crate::reference::intelligence::Action);
-            let schema_json = serde_json::to_string_pretty(&schema).unwrap_or_default();
-
-            let system_instruction = format!(
-                "You are the PeakOS Intelligence Bridge. You can trigger UI actions by including valid JSON in your response using the format [action: {{...}})].\n\nREQUIRED ACTION SCHEMA:\n{}\n\nExample: To navigate to settings, output: [action: {{\"Navigate\": \"SettingsAI\"}})]",
-                schema_json
-            );
-
             final_messages.push(Message {
                 role: "system".to_string(),
-                content: system_instruction,
+                content: bridge_instruction(),
             });
 
-            // 1. RAG: Search for context if we have a user message
-            if let Some(user_msg) = messages_clone.iter().rev().find(|m| m.role == "user") {
-                if let Ok(records) = db.async_find(user_msg.content.clone()).await {
-                    if !records.is_empty() {
-                        let context = records
-                            .iter()
-                            .map(|r| format!("[Memory: {}] {}", r.collection, r.content))
-                            .collect::<Vec<_>>()
-                            .join("\n");
-
-                        final_messages.push(Message {
-                            role: "system".to_string(),
-                            content: format!("Relevant context from PeakDB:\n{}", context),
-                        });
-                    }
-                }
-            }
-
-            // 2. Append original messages
+            // Memories are recalled and cited by the caller, see `recall`
             for m in messages_clone {
                 final_messages.push(Message {
                     role: m.role,
@@ -354,6 +491,7 @@ impl IntelligenceProvider for PeakIntelligenceBridge {
                 });
             }
 
+            let final_messages = fit_context(&client, budget, final_messages);
             let stream = client.chat_stream(final_messages);
             let mut stream = Box::pin(stream);
 
@@ -374,117 +512,7 @@ impl IntelligenceProvider for PeakIntelligenceBridge {
     }
 
     fn execute_tool(&self, name: String, args: Value) -> Task<std::result::Result<Value, String>> {
-        let name_clone = name.clone();
-        let client = self.client.clone();
-        let db = self.db.clone();
-
-        Task::perform(
-            async move {
-                match name_clone.as_str() {
-                    #[cfg(not(target_arch = "wasm32"))]
-                    #[cfg(feature = "native")]
-                    "web_search" => {
-                        let query = args.get("query").and_then(|v| v.as_str()).unwrap_or("");
-                        let brave_key = args
-                            .get("brave_key")
-                            .and_then(|v| v.as_str())
-                            .map(|s| s.to_string());
-                        let tavily_key = args
-                            .get("tavily_key")
-                            .and_then(|v| v.as_str())
-                            .map(|s| s.to_string());
-                        peak_intelligence::tools::web_search_routed(query, brave_key, tavily_key)
-                            .await
-                            .map_err(|e| e.to_string())
-                    }
-                    #[cfg(target_arch = "wasm32")]
-                    "web_search" => {
-                        let query = args
-                            .get("query")
-                            .and_then(|v| v.as_str())
-                            .unwrap_or("")
-                            .to_string();
-                        let brave_key = args
-                            .get("brave_key")
-                            .and_then(|v| v.as_str())
-                            .map(|s| s.to_string());
-                        let tavily_key = args
-                            .get("tavily_key")
-                            .and_then(|v| v.as_str())
-                            .map(|s| s.to_string());
-                        let (tx, rx) = futures::channel::oneshot::channel();
-
-                        wasm_bindgen_futures::spawn_local(async move {
-                            let res = peak_intelligence::tools::web_search_routed(
-                                &query, brave_key, tavily_key,
-                            )
-                            .await;
-                            let _ = tx.send(res.map_err(|e| e.to_string()));
-                        });
-
-                        rx.await.map_err(|e| e.to_string())?
-                    }
-                    #[cfg(feature = "native")]
-                    "get_system_snapshot" => {
-                        peak_intelligence::tools::get_system_snapshot().map_err(|e| e.to_string())
-                    }
-                    #[cfg(feature = "native")]
-                    "read_file" => {
-                        let path = args.get("path").and_then(|v| v.as_str()).unwrap_or("");
-                        peak_intelligence::tools::read_file(path).map_err(|e| e.to_string())
-                    }
-                    #[cfg(feature = "native")]
-                    "write_file" => {
-                        let path = args.get("path").and_then(|v| v.as_str()).unwrap_or("");
-                        let content = args.get("content").and_then(|v| v.as_str()).unwrap_or("");
-                        peak_intelligence::tools::write_file(path, content)
-                            .map_err(|e| e.to_string())
-                    }
-                    #[cfg(feature = "native")]
-                    "list_processes" => {
-                        peak_intelligence::tools::list_processes().map_err(|e| e.to_string())
-                    }
-                    "memorize" => {
-                        let content = args.get("content").and_then(|v| v.as_str()).unwrap_or("");
-                        if content.is_empty() {
-                            return Ok(
-                                serde_json::json!({ "status": "error", "message": "No content provided" }),
-                            );
-                        }
-
-                        let db = db.clone();
-                        let client = client.clone();
-                        let content_owned = content.to_string();
-
-                        // Generate embedding for the new memory
-                        let vector = client.embeddings(&content_owned).await.ok();
-
-                        let record = crate::semantic::SemanticRecord {
-                            id: uuid::Uuid::new_v4().to_string(),
-                            collection: "Memory".to_string(),
-                            content: content_owned,
-                            vector,
-                            metadata: serde_json::json!({}),
-                            timestamp: chrono::Utc::now().timestamp() as u64,
-                        };
-
-                        db.async_save(record).await?;
-
-                        Ok(serde_json::json!({
-                            "status": "success",
-                            "message": "Information saved to memory with semantic embedding."
-                        }))
-                    }
-                    _ => Ok(serde_json::json!({
-                        "status": "success",
-                        "tool": name_clone,
-                        "args": args,
-                        "message": "Tool stub executed (logic not yet linked or platform not supported)"
-                    })),
-                }
-            },
-            |res| res,
-        )
+        Task::perform(self.run_tool(name, args), |res| res)
     }
 
     fn get_system_context(&self) -> String {
@@ -496,3 +524,21 @@ impl IntelligenceProvider for PeakIntelligenceBridge {
         format!("PeakOS {}. Model: {}", provider_name, self.client.model())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_call_tool_cannot_run_commands() {
+        let marker = std::env::temp_dir().join(format!("peak-unapproved-{}", uuid::Uuid::new_v4()));
+        let args = serde_json::json!({ "argv": ["touch", marker] });
+
+        let result = PeakIntelligenceBridge::default()
+            .run_tool("run_command".to_string(), args)
+            .await;
+
+        assert!(result.unwrap().get("exit_code").is_none());
+        assert!(!marker.exists());
+    }
+}
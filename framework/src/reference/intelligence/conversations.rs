@@ -0,0 +1,202 @@
+#![cfg(feature = "intelligence")]
+//! Persistent conversations for `AIChatView`, stored through
+//! `peak_intelligence::brain::Chat`.
+//!
+//! Chats live under the brain data directory on native and in `localStorage`
+//! on WASM. The ids handed to the view are the string form of `chat::Id`.
+use crate::views::{ChatCitation, ChatMessage, ChatRole, ChatSummary};
+use peak_intelligence::brain::chat::{self, Entry, Item};
+use peak_intelligence::brain::{assistant, model, Chat};
+
+/// Saved conversations, most recent first.
+pub async fn list() -> Result<Vec<ChatSummary>, String> {
+    Chat::list()
+        .await
+        .map(|entries| entries.iter().map(summary).collect())
+        .map_err(|e| e.to_string())
+}
+
+/// Reopens the conversation that was active when the app last closed.
+pub async fn last_opened() -> Result<(ChatSummary, Vec<ChatMessage>), String> {
+    Chat::fetch_last_opened()
+        .await
+        .map(opened)
+        .map_err(|e| e.to_string())
+}
+
+/// Loads a conversation and marks it as last opened.
+pub async fn open(id: String) -> Result<(ChatSummary, Vec<ChatMessage>), String> {
+    Chat::fetch(parse(&id)?)
+        .await
+        .map(opened)
+        .map_err(|e| e.to_string())
+}
+
+/// Writes `messages` to the conversation `id`, creating it when `id` is `None`.
+pub async fn save(
+    id: Option<String>,
+    title: Option<String>,
+    model: String,
+    messages: Vec<ChatMessage>,
+) -> Result<ChatSummary, String> {
+    let file = file(model);
+    let history = to_items(&messages);
+
+    let chat = match id {
+        Some(id) => {
+            Chat {
+                id: parse(&id)?,
+                file,
+                title,
+                history,
+            }
+            .save()
+            .await
+        }
+        None => Chat::create(file, title, history).await,
+    }
+    .map_err(|e| e.to_string())?;
+
+    Ok(ChatSummary {
+        id: chat.id.to_string(),
+        title: chat.title,
+    })
+}
+
+pub async fn rename(id: String, title: Option<String>) -> Result<ChatSummary, String> {
+    let chat = Chat::rename(parse(&id)?, title)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    Ok(ChatSummary {
+        id: chat.id.to_string(),
+        title: chat.title,
+    })
+}
+
+/// Deletes a conversation, returning its id.
+pub async fn delete(id: String) -> Result<String, String> {
+    Chat::delete(parse(&id)?)
+        .await
+        .map(|_| id)
+        .map_err(|e| e.to_string())
+}
+
+pub fn to_items(messages: &[ChatMessage]) -> Vec<Item> {
+    messages
+        .iter()
+        .map(|message| match message.role {
+            ChatRole::User => Item::User(message.content.clone()),
+            ChatRole::System => Item::System(message.content.clone()),
+            ChatRole::Assistant => Item::Reply(assistant::Reply {
+                content: message.content.clone(),
+                citations: message
+                    .citations
+                    .iter()
+                    .map(|citation| assistant::Citation {
+                        index: citation.index,
+                        record_id: citation.record_id.clone(),
+                        collection: citation.collection.clone(),
+                        excerpt: citation.excerpt.clone(),
+                    })
+                    .collect(),
+                ..assistant::Reply::default()
+            }),
+        })
+        .collect()
+}
+
+pub fn to_messages(items: &[Item]) -> Vec<ChatMessage> {
+    let message = |role, content: &str| ChatMessage {
+        role,
+        content: content.to_string(),
+        citations: Vec::new(),
+    };
+    let reply = |reply: &assistant::Reply| ChatMessage {
+        citations: reply
+            .citations
+            .iter()
+            .map(|citation| ChatCitation {
+                index: citation.index,
+                record_id: citation.record_id.clone(),
+                collection: citation.collection.clone(),
+                excerpt: citation.excerpt.clone(),
+            })
+            .collect(),
+        ..message(ChatRole::Assistant, &reply.content)
+    };
+
+    items
+        .iter()
+        .flat_map(|item| match item {
+            Item::User(content) => vec![message(ChatRole::User, content)],
+            Item::System(content) => vec![message(ChatRole::System, content)],
+            Item::Reply(content) => vec![reply(content)],
+            Item::Plan(plan) => plan.answers().map(reply).collect(),
+        })
+        .collect()
+}
+
+fn opened(chat: Chat) -> (ChatSummary, Vec<ChatMessage>) {
+    (
+        ChatSummary {
+            id: chat.id.to_string(),
+            title: chat.title,
+        },
+        to_messages(&chat.history),
+    )
+}
+
+fn summary(entry: &Entry) -> ChatSummary {
+    ChatSummary {
+        id: entry.id.to_string(),
+        title: entry.title.clone(),
+    }
+}
+
+fn parse(id: &str) -> Result<chat::Id, String> {
+    id.parse()
+        .map_err(|e| format!("Invalid conversation id {id}: {e}"))
+}
+
+/// Remote providers have no local weights, so the file only records the
+/// model name the conversation was held with.
+fn file(model: String) -> model::File {
+    model::File {
+        model: model::Id(model.clone()),
+        name: model,
+        size: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_citations_survive_saving() {
+        let messages = vec![
+            ChatMessage {
+                role: ChatRole::User,
+                content: "When do we ship?".into(),
+                citations: Vec::new(),
+            },
+            ChatMessage {
+                role: ChatRole::Assistant,
+                content: "On Fridays [1].".into(),
+                citations: vec![ChatCitation {
+                    index: 1,
+                    record_id: "note-1".into(),
+                    collection: "notes".into(),
+                    excerpt: "Peak ships on Fridays".into(),
+                }],
+            },
+        ];
+
+        let restored = to_messages(&to_items(&messages));
+        assert_eq!(restored.len(), 2);
+        assert_eq!(restored[1].content, messages[1].content);
+        assert_eq!(restored[1].citations, messages[1].citations);
+        assert!(restored[0].citations.is_empty());
+    }
+}
@@ -52,6 +52,13 @@ Response: {"response": "Hi there!"}
 Use the `SetTab` command to navigate between pages. 
 Available pages: Colors, Typography, Layout, Icons, Buttons, etc.
 
+## Commands
+GET /commands lists what can be done from anywhere in the app, as in its
+command palette (Cmd/Ctrl+K): each command has a title, keywords and section,
+and the most recently used are listed under `recent`.
+A command's `id` is the JSON to POST to /command to run it.
+Example: `{"id": "{\"SetTab\":\"Colors\"}", "title": "Colors", ...}`
+
 ## Interaction
 - You can trigger actions by sending JSON commands.
 - Commands are mapped 1:1 to the application's `Command` enum.
@@ -82,6 +89,17 @@ Use the `Search` command to filter components or search for documentation within
                                     view_json
                                 );
                                 let _ = socket.write_all(response.as_bytes()).await;
+                            } else if request.starts_with("GET /commands") {
+                                let commands_json = std::fs::read_to_string(".peak/commands.json")
+                                    .unwrap_or_else(|_| {
+                                        "{\"error\": \"Commands not exported yet\"}".to_string()
+                                    });
+                                let response = format!(
+                                    "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                                    commands_json.len(),
+                                    commands_json
+                                );
+                                let _ = socket.write_all(response.as_bytes()).await;
                             } else if request.starts_with("POST /chat") {
                                 if let Some(body_start) = request.find("\r\n\r\n") {
                                     let json_body = &request[body_start + 4..];
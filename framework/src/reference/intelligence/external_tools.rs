@@ -0,0 +1,79 @@
+#![cfg(all(feature = "intelligence", feature = "native"))]
+//! Tools imported from the external MCP servers listed in `mcp.toml`
+//! (see `peak_intelligence::mcp::client`), callable by the assistant
+//! through `Action::CallTool` once the user approves each call.
+use peak_intelligence::mcp::client;
+use peak_intelligence::tools::registry::ToolRegistry;
+use serde_json::Value;
+use std::sync::{LazyLock, PoisonError, RwLock};
+
+static TOOLS: LazyLock<RwLock<ToolRegistry>> = LazyLock::new(|| RwLock::new(ToolRegistry::new()));
+
+/// Connects the configured servers and returns the names of the imported
+/// tools. Servers that fail to start are logged and skipped.
+pub async fn connect() -> Result<Vec<String>, String> {
+    let config = client::Config::load().map_err(|e| format!("{:#}", e))?;
+    let mut registry = ToolRegistry::new();
+
+    for (name, client) in client::connect_all(&config).await {
+        let imported = match client {
+            Ok(client) => client::register_tools(&mut registry, &client).await,
+            Err(e) => Err(e),
+        };
+
+        if let Err(e) = imported {
+            log::warn!("MCP server '{}' unavailable: {:#}", name, e);
+        }
+    }
+
+    let names = registry
+        .list_tools()
+        .iter()
+        .filter_map(|tool| tool["name"].as_str().map(str::to_owned))
+        .collect();
+
+    *TOOLS.write().unwrap_or_else(PoisonError::into_inner) = registry;
+
+    Ok(names)
+}
+
+/// Runs an imported tool, or returns `None` when `name` is not one.
+pub async fn call(name: &str, args: Value) -> Option<Result<Value, String>> {
+    let tool = TOOLS
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .get_tool(name)?;
+
+    Some(tool.execute(args).await.map_err(|e| e.to_string()))
+}
+
+/// Lists the imported tools for the assistant's instructions; empty when
+/// none are connected.
+pub fn instructions() -> String {
+    let tools = TOOLS
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .list_tools();
+
+    if tools.is_empty() {
+        return String::new();
+    }
+
+    let list = tools
+        .iter()
+        .map(|tool| {
+            format!(
+                "- '{}': {} Arguments schema: {}",
+                tool["name"].as_str().unwrap_or_default(),
+                tool["description"].as_str().unwrap_or_default(),
+                tool["input_schema"]
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    format!(
+        "\n\nEXTERNAL TOOLS (call with [action: {{\"CallTool\": {{\"name\": \"...\", \"arguments\": {{...}}}}}})]; the user must approve every call):\n{}",
+        list
+    )
+}
@@ -1,11 +1,16 @@
+pub mod ai;
+pub mod bridge;
+pub mod conversations;
 #[cfg(not(target_arch = "wasm32"))]
 pub mod exposure;
-pub mod protocol;
-pub mod bridge;
+pub mod external_tools;
 pub mod mcp;
-pub mod ai;
+pub mod models;
+pub mod protocol;
+pub mod rag;
+pub mod tool_server;
 
-pub use protocol::{Action, ActionParser, ContentPart};
-pub use bridge::PeakIntelligenceBridge;
 pub use ai::OpenRouterClient;
+pub use bridge::PeakIntelligenceBridge;
 pub use mcp::get_framework_schema;
+pub use protocol::{Action, ActionParser, ContentPart};
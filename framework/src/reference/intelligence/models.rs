@@ -0,0 +1,104 @@
+#![cfg(feature = "intelligence")]
+//! Local model library for the AI settings page, backed by
+//! `peak_intelligence::brain::model::Manager`.
+//!
+//! Every call uses the library configured in the brain settings, so queued
+//! downloads started here resume on the next launch.
+use peak_intelligence::brain::model::manager::{Download, Event};
+use peak_intelligence::brain::model::{File, Id, Manager, Model};
+use peak_intelligence::sipper::Sipper;
+
+use futures::Stream;
+use std::path::PathBuf;
+
+/// Models on the hub matching `query`, most downloaded first.
+pub async fn search(query: String) -> Result<Vec<Model>, String> {
+    Manager::default()
+        .search(query)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Downloadable variants of `id`, from the lowest precision up.
+pub async fn files(id: Id) -> Result<Vec<File>, String> {
+    Manager::default()
+        .files(id)
+        .await
+        .map(|files| files.into_values().flatten().collect())
+        .map_err(|e| e.to_string())
+}
+
+/// The download queue and the installed files.
+pub async fn library() -> Result<(Vec<Download>, Vec<File>), String> {
+    let manager = Manager::default();
+
+    let queue = manager.queue().await.map_err(|e| e.to_string())?;
+    let installed = manager.installed().await.map_err(|e| e.to_string())?;
+
+    Ok((queue, installed.files().to_vec()))
+}
+
+pub async fn enqueue(file: File) -> Result<Download, String> {
+    Manager::default()
+        .enqueue(file)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Removes `file` from the queue along with its partial data.
+pub async fn cancel(file: File) -> Result<Vec<PathBuf>, String> {
+    Manager::default()
+        .dequeue(&file)
+        .await
+        .map(|_| Vec::new())
+        .map_err(|e| e.to_string())
+}
+
+/// Deletes an installed file, returning the removed paths.
+pub async fn delete(file: File) -> Result<Vec<PathBuf>, String> {
+    Manager::default()
+        .delete(&file)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Removes unused variants and abandoned partial downloads.
+pub async fn collect_garbage() -> Result<Vec<PathBuf>, String> {
+    Manager::default()
+        .collect_garbage(&[])
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Works through the download queue. Progress is only forwarded when the
+/// percentage (or, without a known size, the downloaded megabyte) changes,
+/// so the view is not redrawn for every chunk.
+pub fn run() -> impl Stream<Item = Result<Event, String>> {
+    async_stream::stream! {
+        let mut run = Manager::default().run().pin();
+        let mut last_step = None;
+
+        while let Some(event) = run.sip().await {
+            if let Event::Progressed(_, progress) = &event {
+                let step = progress
+                    .percent()
+                    .map(|(_, percent)| u64::from(percent))
+                    .unwrap_or(progress.downloaded >> 20);
+
+                if last_step == Some(step) {
+                    continue;
+                }
+
+                last_step = Some(step);
+            } else {
+                last_step = None;
+            }
+
+            yield Ok(event);
+        }
+
+        if let Err(error) = run.await {
+            yield Err(error.to_string());
+        }
+    }
+}
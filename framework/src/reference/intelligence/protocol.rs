@@ -1,6 +1,7 @@
 use crate::prelude::*;
 use crate::reference::app::RenderMode;
 use crate::reference::AppPage;
+pub use peak_intelligence::tools::command_line;
 pub use peak_theme::{PeakTheme, ThemeTone};
 
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
@@ -19,6 +20,19 @@ pub enum Action {
     SetLabMode(RenderMode),
     #[serde(alias = "shell", alias = "Shell")]
     Shell(String), // New: Shell command execution (PROTECTED)
+    /// Runs a program to completion without a shell (PROTECTED).
+    #[serde(alias = "run_command", alias = "RunCommand")]
+    RunCommand {
+        argv: Vec<String>,
+        #[serde(default)]
+        cwd: Option<String>,
+        #[serde(default)]
+        env: std::collections::BTreeMap<String, String>,
+        #[serde(default)]
+        stdin: Option<String>,
+        #[serde(default)]
+        timeout_ms: Option<u64>,
+    },
     #[serde(alias = "memorize", alias = "Memorize")]
     Memorize(String), // New: Save information to PeakDB
     #[serde(alias = "teleport", alias = "Teleport")]
@@ -43,6 +57,13 @@ pub enum Action {
     ReadFile(String),
     #[serde(alias = "write_file", alias = "WriteFile")]
     WriteFile { path: String, content: String },
+    /// Calls a tool imported from an external MCP server.
+    #[serde(alias = "call_tool", alias = "CallTool")]
+    CallTool {
+        name: String,
+        #[serde(default)]
+        arguments: serde_json::Value,
+    },
     #[serde(alias = "unknown", alias = "Unknown")]
     Unknown(String),
 }
@@ -50,7 +71,7 @@ pub enum Action {
 impl Action {
     pub fn is_protected(&self) -> bool {
         match self {
-            Action::Shell(_) => true,
+            Action::Shell(_) | Action::RunCommand { .. } | Action::CallTool { .. } => true,
             Action::Navigate(AppPage::Roadmap) | Action::Navigate(AppPage::SettingsAI) => true,
             _ => false,
         }
@@ -59,6 +80,13 @@ impl Action {
     pub fn protection_reason(&self) -> Option<String> {
         match self {
             Action::Shell(cmd) => Some(format!("Execute shell command: `{}`", cmd)),
+            Action::RunCommand { argv, cwd, .. } => Some(match cwd {
+                Some(cwd) => format!("Run command `{}` in {}", command_line(argv), cwd),
+                None => format!("Run command `{}`", command_line(argv)),
+            }),
+            Action::CallTool { name, arguments } => {
+                Some(format!("Call external tool `{}` with {}", name, arguments))
+            }
             Action::Navigate(AppPage::Roadmap) => {
                 Some("Accessing vision-critical roadmap data".to_string())
             }
@@ -70,6 +98,13 @@ impl Action {
     }
 }
 
+/// Whether `name` is how tools imported from MCP servers are named,
+/// `<server>__<tool>`. Nothing built in is.
+pub fn is_imported_tool(name: &str) -> bool {
+    name.split_once("__")
+        .is_some_and(|(server, tool)| !server.is_empty() && !tool.is_empty())
+}
+
 pub struct ActionParser;
 
 impl ActionParser {
@@ -281,4 +316,29 @@ mod tests {
             _ => panic!("Expected SetThemeTone(Dark), got {:?}", actions[1]),
         }
     }
+
+    #[test]
+    fn test_run_command_is_protected() {
+        let text = "[action: {\"RunCommand\": {\"argv\": [\"cargo\", \"test\", \"two words\"], \"cwd\": \"~/project\"}})]";
+        let actions = ActionParser::parse_text(text);
+        assert_eq!(actions.len(), 1);
+        assert!(actions[0].is_protected());
+        assert_eq!(
+            actions[0].protection_reason().as_deref(),
+            Some("Run command `cargo test \"two words\"` in ~/project")
+        );
+    }
+
+    #[test]
+    fn test_call_tool_is_protected_and_only_imported() {
+        let text = "[action: {\"CallTool\": {\"name\": \"run_command\", \"arguments\": {\"argv\": [\"rm\"]}}})]";
+        let actions = ActionParser::parse_text(text);
+        assert!(actions[0].is_protected());
+
+        assert!(!is_imported_tool("run_command"));
+        assert!(!is_imported_tool("write_file"));
+        assert!(!is_imported_tool("__write_file"));
+        assert!(!is_imported_tool("tickets__"));
+        assert!(is_imported_tool("tickets__create"));
+    }
 }
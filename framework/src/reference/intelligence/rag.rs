@@ -0,0 +1,262 @@
+#![cfg(feature = "intelligence")]
+//! Retrieval-augmented generation over the `DataProvider` memory bank.
+//!
+//! Records saved through `Action::Memorize` are embedded on write. Before a
+//! reply is generated, the latest user turn is embedded the same way and the
+//! closest records are injected into the system prompt as numbered sources
+//! that the model is asked to cite (`[1]`, `[2]`, ...). Neighbours scoring
+//! below [`MIN_SCORE`] are left out, so unrelated memories don't crowd the
+//! prompt.
+use crate::core::{ChatCompletionMessage, DataProvider, SemanticRecord};
+use crate::semantic::Metric;
+use crate::views::chat::ChatCitation;
+use peak_intelligence::llm::LlmClient;
+use std::sync::Arc;
+
+/// Marks the system message that carries retrieved memories.
+pub const MEMORY_HEADER: &str = "### LONG-TERM MEMORY (Neural Layer)";
+/// Number of records injected per turn.
+pub const TOP_K: usize = 8;
+/// Least cosine similarity for a semantic neighbour to count as relevant.
+pub const MIN_SCORE: f32 = 0.3;
+
+const EXCERPT_CHARS: usize = 96;
+
+/// Finds the records most relevant to `query`: semantic neighbours first,
+/// topped up with keyword matches when the vector search comes back short.
+/// Providers may pad keyword results with recent records, so top-ups that
+/// don't mention the query are dropped.
+pub async fn retrieve(
+    client: &LlmClient,
+    db: &Arc<dyn DataProvider>,
+    query: &str,
+    top_k: usize,
+) -> Vec<SemanticRecord> {
+    let mut records = Vec::new();
+
+    if let Ok(vector) = client.embeddings(query).await {
+        if let Ok(found) = db.async_find_semantic(vector.clone(), top_k).await {
+            records = relevant(&vector, found);
+        }
+    }
+
+    if records.len() < top_k {
+        if let Ok(keyword_records) = db.async_find(query.to_string()).await {
+            for record in keyword_records {
+                if records.len() >= top_k {
+                    break;
+                }
+                if mentions(query, &record.content)
+                    && !records.iter().any(|existing| existing.id == record.id)
+                {
+                    records.push(record);
+                }
+            }
+        }
+    }
+
+    records
+}
+
+/// The `records` close enough to the `query` vector to be worth citing.
+fn relevant(query: &[f32], records: Vec<SemanticRecord>) -> Vec<SemanticRecord> {
+    records
+        .into_iter()
+        .filter(|record| {
+            record
+                .vector
+                .as_ref()
+                .is_some_and(|vector| Metric::Cosine.score(query, vector) >= MIN_SCORE)
+        })
+        .collect()
+}
+
+/// Whether `content` contains `query` or one of its longer words.
+fn mentions(query: &str, content: &str) -> bool {
+    let query = query.to_lowercase();
+    let content = content.to_lowercase();
+    content.contains(&query)
+        || query
+            .split(|c: char| !c.is_alphanumeric())
+            .filter(|word| word.len() > 3)
+            .any(|word| content.contains(word))
+}
+
+/// The content of the most recent user turn, if any.
+pub fn last_user_query(messages: &[ChatCompletionMessage]) -> Option<&str> {
+    messages
+        .iter()
+        .rev()
+        .find(|m| m.role == "user")
+        .map(|m| m.content.as_str())
+}
+
+/// Whether the caller already injected retrieved memories.
+pub fn has_context(messages: &[ChatCompletionMessage]) -> bool {
+    messages
+        .iter()
+        .any(|m| m.role == "system" && m.content.starts_with(MEMORY_HEADER))
+}
+
+/// Builds the system message listing `records` as numbered, citable sources.
+pub fn context_message(records: &[SemanticRecord]) -> Option<ChatCompletionMessage> {
+    if records.is_empty() {
+        return None;
+    }
+
+    let sources = records
+        .iter()
+        .enumerate()
+        .map(|(i, r)| format!("[{}] ({}) {}", i + 1, r.collection, r.content))
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    Some(ChatCompletionMessage {
+        role: "system".to_string(),
+        content: format!(
+            "{}\nThe following entries were retrieved from your persistent memory bank. \
+             The user asked you to remember them; treat them as known facts. \
+             When an answer relies on an entry, cite it inline by its number, e.g. [1].\n\n{}",
+            MEMORY_HEADER, sources
+        ),
+    })
+}
+
+/// Citations for `records`, numbered to match [`context_message`].
+pub fn citations(records: &[SemanticRecord]) -> Vec<ChatCitation> {
+    records
+        .iter()
+        .enumerate()
+        .map(|(i, r)| ChatCitation {
+            index: i + 1,
+            record_id: r.id.clone(),
+            collection: r.collection.clone(),
+            excerpt: excerpt(&r.content),
+        })
+        .collect()
+}
+
+/// The citations a reply actually refers to by their markers; none when it
+/// uses no marker. Markers inside code, like `arr[1]`, don't count.
+pub fn cited(reply: &str, citations: &[ChatCitation]) -> Vec<ChatCitation> {
+    let prose = prose(reply);
+    citations
+        .iter()
+        .filter(|c| prose.contains(&format!("[{}]", c.index)))
+        .cloned()
+        .collect()
+}
+
+/// `markdown` without its fenced blocks and inline code spans.
+fn prose(markdown: &str) -> String {
+    let mut prose = String::new();
+    let mut fence: Option<&str> = None;
+
+    for line in markdown.lines() {
+        let trimmed = line.trim_start();
+        let marker = ["```", "~~~"]
+            .into_iter()
+            .find(|marker| trimmed.starts_with(marker));
+
+        match (fence, marker) {
+            (None, Some(marker)) => fence = Some(marker),
+            (Some(open), Some(marker)) if open == marker => fence = None,
+            (None, None) => {
+                prose.extend(line.split('`').step_by(2));
+                prose.push('\n');
+            }
+            _ => {}
+        }
+    }
+
+    prose
+}
+
+fn excerpt(content: &str) -> String {
+    let content = content.trim();
+    match content.char_indices().nth(EXCERPT_CHARS) {
+        Some((end, _)) => format!("{}…", content[..end].trim_end()),
+        None => content.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(id: &str, content: &str, vector: Option<Vec<f32>>) -> SemanticRecord {
+        SemanticRecord {
+            id: id.to_string(),
+            collection: "Memory".to_string(),
+            content: content.to_string(),
+            vector,
+            metadata: serde_json::json!({}),
+            timestamp: 0,
+        }
+    }
+
+    #[test]
+    fn test_context_lists_numbered_sources() {
+        assert!(context_message(&[]).is_none());
+
+        let records = [
+            record("a", "The user's cat is called Miso.", None),
+            record("b", &"x".repeat(200), None),
+        ];
+        let message = context_message(&records).unwrap();
+        assert_eq!(message.role, "system");
+        assert!(message.content.starts_with(MEMORY_HEADER));
+        assert!(message
+            .content
+            .contains("[1] (Memory) The user's cat is called Miso.\n[2] (Memory) xxx"));
+        assert!(has_context(&[message]));
+
+        let citations = citations(&records);
+        assert_eq!(citations[1].index, 2);
+        assert_eq!(citations[1].record_id, "b");
+        assert!(citations[1].excerpt.ends_with('…'));
+    }
+
+    #[test]
+    fn test_cited_keeps_only_referenced_sources() {
+        let citations = citations(&[
+            record("a", "first", None),
+            record("b", "second", None),
+            record("c", "third", None),
+        ]);
+
+        let cited_ids = |reply: &str| -> Vec<String> {
+            cited(reply, &citations)
+                .into_iter()
+                .map(|c| c.record_id)
+                .collect()
+        };
+        assert_eq!(cited_ids("Miso [3] likes fish [1]."), ["a", "c"]);
+        assert!(cited_ids("Nothing to cite here.").is_empty());
+        assert!(cited_ids("See [4].").is_empty());
+        assert!(cited_ids("Index with `arr[1]`.").is_empty());
+        assert_eq!(
+            cited_ids("```rust\nlet x = xs[2];\n```\nAs noted [3]."),
+            ["c"]
+        );
+    }
+
+    #[test]
+    fn test_top_ups_must_mention_the_query() {
+        assert!(mentions("cat", "The user's cat is called Miso."));
+        assert!(mentions("Where does Miso sleep?", "MISO naps on the sofa."));
+        assert!(!mentions("Where does Miso sleep?", "Buy milk tomorrow."));
+    }
+
+    #[test]
+    fn test_relevant_drops_distant_neighbours() {
+        let records = vec![
+            record("near", "near", Some(vec![1.0, 0.1])),
+            record("far", "far", Some(vec![-1.0, 0.2])),
+            record("unembedded", "unembedded", None),
+        ];
+        let kept = relevant(&[1.0, 0.0], records);
+        assert_eq!(kept.len(), 1);
+        assert_eq!(kept[0].id, "near");
+    }
+}
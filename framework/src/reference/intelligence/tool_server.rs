@@ -0,0 +1,79 @@
+#![cfg(all(feature = "intelligence", feature = "native"))]
+//! The MCP server agents call the app's tools through, over the streamable
+//! HTTP transport (see `peak_intelligence::mcp::http`).
+//!
+//! Its protected calls wait in the app's own approval queue, so they show up
+//! in the [`ApprovalQueue`](crate::views::ApprovalQueue) like the
+//! assistant's. The server's approval token never leaves the app, so no MCP
+//! client can decide on a call.
+use iced::futures::StreamExt;
+use peak_intelligence::mcp::{self, Server};
+use peak_intelligence::terminal::TerminalManager;
+use peak_intelligence::tools::{self, approval::Approvals, registry::ToolRegistry};
+use std::hash::{Hash, Hasher};
+use std::sync::{Arc, LazyLock};
+use tokio::sync::{broadcast, mpsc};
+
+/// Where agents reach the server.
+pub const ADDR: &str = "127.0.0.1:8082";
+
+static TERMINAL: LazyLock<TerminalManager> = LazyLock::new(TerminalManager::new);
+
+/// The approval queue the server's protected calls wait in, as the data of
+/// the subscription running it. There is only ever one server.
+#[derive(Clone)]
+pub struct Queue(pub Approvals);
+
+impl Hash for Queue {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        "tool_server".hash(state);
+    }
+}
+
+/// Serves the built-in tools until the listener fails.
+pub async fn run(approvals: Approvals) {
+    let (notify_tx, mut notify_rx) = mpsc::channel::<String>(100);
+    let (events, _) = broadcast::channel::<String>(100);
+
+    let mut registry = ToolRegistry::new().with_approvals(approvals.clone());
+    tools::register_builtin(&mut registry, &TERMINAL, notify_tx.clone());
+    let token = uuid::Uuid::new_v4().to_string();
+    let server = Arc::new(Server::new(registry).with_approval_token(token));
+
+    let listener = match tokio::net::TcpListener::bind(ADDR).await {
+        Ok(listener) => listener,
+        Err(e) => {
+            log::error!("Failed to bind MCP server: {}", e);
+            return;
+        }
+    };
+    log::info!(
+        "MCP server listening on http://{}{}",
+        ADDR,
+        mcp::http::ENDPOINT
+    );
+
+    // Terminal output and approval queue changes, for event streams
+    let forward = async {
+        while let Some(message) = notify_rx.recv().await {
+            let _ = events.send(message);
+        }
+    };
+    let mut approval_events = approvals.subscribe();
+    let announce = async {
+        while let Some(event) = approval_events.next().await {
+            let notification = mcp::server::approval_notification(&event);
+            let _ = notify_tx.send(notification.to_string()).await;
+        }
+    };
+
+    tokio::select! {
+        result = mcp::http::serve(listener, server, events.clone(), None) => {
+            if let Err(e) = result {
+                log::error!("MCP server stopped: {}", e);
+            }
+        }
+        () = forward => {}
+        () = announce => {}
+    }
+}
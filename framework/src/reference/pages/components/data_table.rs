@@ -43,10 +43,10 @@ pub fn view(ctx: &Context, render_mode: RenderMode) -> AppPageResult {
     .render_mode(render_mode)
     .on_render_mode_change(|mode| Message::Lab(LabMessage::SetRenderMode(mode)))
     .theory(
-       "### High-Density Data\nData tables in PeakUI are more than just grids. They are semantic structures that handle complex layout logic across different display kernels.\n\n- **Kernel Agnostic**: The same `data_table!` DSL manifests as a rich interactive grid on Canvas, a character-based table in Terminal, and a structured array in Neural mode.\n- **Performance**: Built with virtualization in mind (planned) to handle thousands of rows without dropping frames."
+       "### High-Density Data\nData tables in PeakUI are more than just grids. They are semantic structures that handle complex layout logic across different display kernels.\n\n- **Kernel Agnostic**: The same `data_table!` DSL manifests as a rich interactive grid on Canvas, a character-based table in Terminal, and a structured array in Neural mode.\n- **Performance**: With a `TableSource`, only the rows in view are built, so tables of hundreds of thousands of rows scroll, sort and filter without dropping frames."
     )
     .props_table(
-        "| Modifier | Type | Description |\n| :--- | :--- | :--- |\n| `preset(p)` | `DataTablePreset` | Professional, Minimal, or Custom styles. |\n| `column(n, w)` | `&str, Length` | Defines a table column and its width. |\n| `sortable_column(n, w, f)` | `&str, Length, Fn` | Defines a sortable column with a callback. |\n| `row(cells)` | `Vec<Box<dyn View>>`| Adds a row of content. |\n| `row_with_action(cells, m)` | `Vec<...>, Msg` | Adds an interactive row. |\n| `on_selection_change(f)` | `Fn(HashSet<String>)` | Enables row selection with checkboxes. |\n| `on_page_change(f)` | `Fn(usize)` | Enables pagination controls. |"
+        "| Modifier | Type | Description |\n| :--- | :--- | :--- |\n| `preset(p)` | `DataTablePreset` | Professional, Minimal, or Custom styles. |\n| `column(n, w)` | `&str, Length` | Defines a table column and its width. |\n| `sortable_column(n, w, f)` | `&str, Length, Fn` | Defines a sortable column with a callback. |\n| `row(cells)` | `Vec<Box<dyn View>>`| Adds a row of content. |\n| `row_with_action(cells, m)` | `Vec<...>, Msg` | Adds an interactive row. |\n| `on_selection_change(f)` | `Fn(HashSet<String>)` | Enables row selection with checkboxes. |\n| `on_page_change(f)` | `Fn(usize)` | Enables pagination controls. |\n| `source(s, state, f)` | `Arc<dyn TableSource>, &DataTableState, Fn` | Pulls rows lazily, with sorting, filters, resizing and reordering. |\n| `row_height(h)` | `f32` | The fixed row height of a sourced table. |\n| `values(v)` | `Vec<CellValue>` | The raw values of the last row, for export. |\n| `on_copy(f)` | `Fn(String)` | Offers to copy selected rows as a Markdown table. |\n| `exportable(b)` | `bool` | Offers CSV, TSV and JSON export under a sourced table. |"
     );
 
     AppPageResult::new(doc)
@@ -120,9 +120,10 @@ pub fn view(_context: &Context, is_mobile: bool, _api_key: String) -> AppPageRes
             vec![
                 Box::new(Text::new("Critical or destructive AI actions are intercepted by the Neural Sudo layer. This ensures that an AI agent cannot execute restricted commands without explicit human authorization.")),
                 Box::new(Text::new("• Action Interception: Commands like 'Execute Shell' or 'Delete Record' trigger a SudoRequest.")),
-                Box::new(Text::new("• Human-in-the-Loop: The framework pauses the action and presents a confirmation UI to the user.")),
+                Box::new(Text::new("• Human-in-the-Loop: The action waits in an approval queue, shown by the ApprovalQueue view, until the user approves, denies or always allows it for the session.")),
+                Box::new(Text::new("• Remote Agents: MCP tools that need approval block `tools/call` and announce `approval/requested` and `approval/resolved` notifications.")),
                 Box::new(Text::new("• Stateless Approval: The AI only receives the result of the action (Approved/Denied), maintaining strict security boundaries.")),
-                Box::new(code_block("// Example: Securing a sensitive command\nif action.requires_sudo() {\n    ctx.emit(Message::SudoRequest(action));\n}\n\n// Approval triggers the final execution\nMessage::SudoApprove => action.execute(),")),
+                Box::new(code_block("// Example: Securing a sensitive command\nlet ticket = approvals.request(\"run_command\", args, reason, \"assistant\");\n\n// The decision triggers the final execution\nTask::perform(ticket, |decision| if decision.is_approved() { run() } else { deny() })")),
             ],
         );
 
@@ -5,9 +5,19 @@ use crate::reference::AppPageResult;
 pub fn view(
     context: &Context,
     is_mobile: bool,
-    records: Vec<crate::core::SemanticRecord>,
+    mut records: Vec<crate::core::SemanticRecord>,
+    focused: Option<String>,
 ) -> AppPageResult {
     let _ = context;
+
+    // A record opened from a chat citation is listed first
+    if let Some(id) = &focused {
+        if let Some(pos) = records.iter().position(|r| &r.id == id) {
+            let record = records.remove(pos);
+            records.insert(0, record);
+        }
+    }
+
     AppPageResult::new(ProxyView::<Message, IcedBackend>::new(move |ctx| {
         let t = ctx.theme;
         let mut root = VStack::new().width(Length::Fill).spacing(0.0);
@@ -75,6 +85,7 @@ pub fn view(
         if !records.is_empty() {
             let mut records_col = VStack::new().spacing(16.0);
             for record in &records {
+                let is_focused = focused.as_ref() == Some(&record.id);
                 records_col = records_col.push(
                     Container::new(
                         VStack::new()
@@ -94,6 +105,14 @@ pub fn view(
                     )
                     .padding(20.0)
                     .background(t.colors.surface_variant)
+                    .border(
+                        if is_focused { 2.0 } else { 0.0 },
+                        if is_focused {
+                            t.colors.primary
+                        } else {
+                            t.colors.border
+                        },
+                    )
                     .radius(12.0)
                     .width(Length::Fill),
                 );
@@ -1,5 +1,8 @@
 use crate::prelude::*;
-use crate::reference::app::{AIProviderChoice, IntelligenceMessage, InteractionMessage, Message};
+use crate::reference::app::{
+    AIProviderChoice, IntelligenceMessage, InteractionMessage, Message, ModelLibrary, ModelMessage,
+};
+use peak_intelligence::brain::model::{File, Size};
 
 pub fn view<B: Backend>(
     _context: &Context,
@@ -8,6 +11,7 @@ pub fn view<B: Backend>(
     ai_provider: AIProviderChoice,
     enable_exposure: bool,
     state_json: Option<String>,
+    models: ModelLibrary,
 ) -> PageResult<Message, B> {
     PageResult::new(crate::core::ProxyView::new(move |context| {
         // Safe Area Adaptation
@@ -109,6 +113,7 @@ pub fn view<B: Backend>(
                         .on_submit(Message::None),
                     ),
             )
+            .push(models_view::<B>(&models))
             .push(
                 VStack::<Message, B>::new_generic()
                     .push(Text::<B>::new("Local Exposure").title3().bold())
@@ -139,3 +144,207 @@ pub fn view<B: Backend>(
         main_view.view(context)
     }))
 }
+
+fn model_message(message: ModelMessage) -> Message {
+    Message::Intelligence(IntelligenceMessage::Models(message))
+}
+
+fn file_label(file: &File) -> String {
+    match file.size {
+        Some(size) => format!("{} ({})", file.name, size),
+        None => file.name.clone(),
+    }
+}
+
+/// Sizes may be unknown on one side, so files are matched by name.
+fn same_file(a: &File, b: &File) -> bool {
+    a.model == b.model && a.name == b.name
+}
+
+/// Hub search, the download queue and the installed GGUF files.
+fn models_view<B: Backend>(models: &ModelLibrary) -> VStack<Message, B> {
+    let mut section = VStack::<Message, B>::new_generic()
+        .spacing(12.0)
+        .width(Length::Fill)
+        .push(Text::<B>::new("Models").title3().bold())
+        .push(
+            Text::<B>::new("Download GGUF models for local inference. Downloads resume after restarts and are verified before use.")
+                .caption1(),
+        )
+        .push(
+            HStack::<Message, B>::new_generic()
+                .spacing(8.0)
+                .width(Length::Fill)
+                .push(
+                    TextInput::<Message, B>::new(models.query.clone(), "Search models...", |s| {
+                        model_message(ModelMessage::QueryChanged(s))
+                    })
+                    .on_submit(model_message(ModelMessage::Search)),
+                )
+                .push(
+                    Button::<Message, B>::label(if models.is_searching {
+                        "Searching..."
+                    } else {
+                        "Search"
+                    })
+                    .variant(Variant::Soft)
+                    .on_press_maybe(
+                        (!models.is_searching).then(|| model_message(ModelMessage::Search)),
+                    ),
+                ),
+        );
+
+    if let Some(status) = &models.status {
+        section = section.push(Text::<B>::new(status.clone()).caption1().secondary());
+    }
+
+    for model in &models.results {
+        let is_selected = models.selected.as_ref() == Some(&model.id);
+
+        section = section.push(
+            Button::<Message, B>::new(
+                HStack::<Message, B>::new_generic()
+                    .spacing(8.0)
+                    .push(Icon::<B>::new("brain").size(16.0))
+                    .push(Text::<B>::new(model.id.0.clone()).body())
+                    .push(
+                        Text::<B>::new(format!("{} downloads", model.downloads))
+                            .caption2()
+                            .secondary(),
+                    ),
+            )
+            .variant(if is_selected {
+                Variant::Soft
+            } else {
+                Variant::Ghost
+            })
+            .width(Length::Fill)
+            .on_press(model_message(ModelMessage::Select(model.id.clone()))),
+        );
+
+        if is_selected {
+            for file in &models.variants {
+                let installed = models.installed.iter().any(|other| same_file(other, file));
+                let queued = models
+                    .queue
+                    .iter()
+                    .any(|download| same_file(&download.file, file));
+
+                section = section.push(
+                    HStack::<Message, B>::new_generic()
+                        .spacing(8.0)
+                        .width(Length::Fill)
+                        .push(
+                            Text::<B>::new(file_label(file))
+                                .caption1()
+                                .width(Length::Fill),
+                        )
+                        .push(
+                            Button::<Message, B>::label(if installed {
+                                "Installed"
+                            } else if queued {
+                                "Queued"
+                            } else {
+                                "Download"
+                            })
+                            .variant(Variant::Outline)
+                            .on_press_maybe(
+                                (!installed && !queued)
+                                    .then(|| model_message(ModelMessage::Download(file.clone()))),
+                            ),
+                        ),
+                );
+            }
+        }
+    }
+
+    if !models.queue.is_empty() {
+        section = section.push(Text::<B>::new("Downloads").headline());
+    }
+
+    for download in &models.queue {
+        let file = &download.file;
+        let progress = models
+            .active
+            .as_ref()
+            .filter(|(active, _)| active == file)
+            .map(
+                |(_, progress)| match progress.and_then(|progress| progress.percent()) {
+                    Some((total, percent)) => format!("{}% of {}", percent, Size(total)),
+                    None => "Starting...".to_string(),
+                },
+            );
+
+        let detail = match (&download.error, progress) {
+            (Some(error), _) => error.clone(),
+            (None, Some(progress)) => progress,
+            (None, None) => "Waiting".to_string(),
+        };
+
+        let mut row = HStack::<Message, B>::new_generic()
+            .spacing(8.0)
+            .width(Length::Fill)
+            .push(
+                VStack::<Message, B>::new_generic()
+                    .width(Length::Fill)
+                    .push(Text::<B>::new(format!("{} / {}", file.model.0, file.name)).caption1())
+                    .push(Text::<B>::new(detail).caption2().secondary()),
+            );
+
+        if download.error.is_some() {
+            row = row.push(
+                Button::<Message, B>::label("Retry")
+                    .variant(Variant::Soft)
+                    .on_press(model_message(ModelMessage::Download(file.clone()))),
+            );
+        }
+
+        section = section.push(
+            row.push(
+                Button::<Message, B>::label("Cancel")
+                    .variant(Variant::Ghost)
+                    .on_press(model_message(ModelMessage::Cancel(file.clone()))),
+            ),
+        );
+    }
+
+    section = section.push(
+        HStack::<Message, B>::new_generic()
+            .width(Length::Fill)
+            .push(Text::<B>::new("Installed").headline().width(Length::Fill))
+            .push(
+                Button::<Message, B>::label("Clean Up")
+                    .variant(Variant::Ghost)
+                    .on_press(model_message(ModelMessage::CollectGarbage)),
+            ),
+    );
+
+    if models.installed.is_empty() {
+        section = section.push(
+            Text::<B>::new("No models installed.")
+                .caption1()
+                .secondary(),
+        );
+    }
+
+    for file in &models.installed {
+        section = section.push(
+            HStack::<Message, B>::new_generic()
+                .spacing(8.0)
+                .width(Length::Fill)
+                .push(
+                    Text::<B>::new(format!("{} / {}", file.model.0, file_label(file)))
+                        .caption1()
+                        .width(Length::Fill),
+                )
+                .push(
+                    Button::<Message, B>::label("Delete")
+                        .intent(Intent::Danger)
+                        .variant(Variant::Ghost)
+                        .on_press(model_message(ModelMessage::Delete(file.clone()))),
+                ),
+        );
+    }
+
+    section
+}
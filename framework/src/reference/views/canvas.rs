@@ -208,15 +208,19 @@ impl CanvasView {
                     self.state.intelligence.ai_provider,
                     self.state.interaction.enable_exposure,
                     state_json,
+                    self.state.intelligence.models.clone(),
                 )
             }
 
             // Details (from Landing)
             ReferenceAppPage::PeakOSDetail => pages::landing::peak_os::view(context, is_mobile),
             ReferenceAppPage::PeakUIDetail => pages::landing::peak_ui::view(context, is_mobile),
-            ReferenceAppPage::PeakDBDetail => {
-                pages::landing::peak_db::view(context, is_mobile, self.state.db_records.to_vec())
-            }
+            ReferenceAppPage::PeakDBDetail => pages::landing::peak_db::view(
+                context,
+                is_mobile,
+                self.state.db_records.to_vec(),
+                self.state.intelligence.focused_record.clone(),
+            ),
             ReferenceAppPage::PeakRelayDetail => {
                 pages::landing::peak_relay::view(context, is_mobile)
             }
@@ -227,7 +227,9 @@ impl ContentView {
                 self.state.intelligence.is_thinking,
                 #[cfg(feature = "intelligence")]
                 |m| Message::Intelligence(IntelligenceMessage::Chat(m)),
-            );
+            )
+            .history(self.state.intelligence.chat_history())
+            .notice(self.state.intelligence.context_notice.clone());
 
             let inspector_content: Option<Box<dyn View<Message, IcedBackend> + Send + Sync>> =
                 if let Some(p_inspector) = page.inspector.take() {
@@ -359,8 +361,14 @@ impl ContentView {
             }
         }
 
+        let command_palette = crate::views::CommandPalette::<Message, IcedBackend>::new(
+            &self.state.shell.command_palette,
+            |msg| Message::Shell(ShellMessage::CommandPalette(msg)),
+        );
+
         crate::core::SemanticNode::new("content_view")
             .with_label(format!("AppPage: {:?}", self.state.shell.active_tab))
             .push_child(split_view.describe(context))
+            .push_child(command_palette.describe(context))
     }
 }
@@ -0,0 +1,737 @@
+//! Approximate nearest neighbour search over [`SemanticRecord`](super::SemanticRecord) vectors.
+//!
+//! [`VectorIndex`] keeps one HNSW graph per record `collection` so that
+//! `DataProvider::async_find_semantic` no longer needs to score every stored
+//! vector. Inserts and deletes are incremental; deleted nodes stay in the graph
+//! as tombstones (they still route searches) until enough of them accumulate to
+//! justify a rebuild.
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap, HashSet};
+use std::path::Path;
+
+/// Similarity metric used to rank neighbours.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum Metric {
+    /// Cosine similarity. Vectors are normalized on insert.
+    #[default]
+    Cosine,
+    /// Raw inner product, for embeddings that encode magnitude.
+    DotProduct,
+}
+
+impl Metric {
+    fn prepare(&self, vector: &[f32]) -> Vec<f32> {
+        match self {
+            Metric::Cosine => {
+                let norm = vector.iter().map(|v| v * v).sum::<f32>().sqrt();
+                if norm == 0.0 {
+                    vector.to_vec()
+                } else {
+                    vector.iter().map(|v| v / norm).collect()
+                }
+            }
+            Metric::DotProduct => vector.to_vec(),
+        }
+    }
+
+    /// Higher is more similar. Both metrics reduce to a dot product once
+    /// cosine vectors have been normalized by [`Metric::prepare`].
+    fn similarity(&self, a: &[f32], b: &[f32]) -> f32 {
+        a.iter().zip(b.iter()).map(|(x, y)| x * y).sum()
+    }
+
+    /// How similar two vectors as stored are, e.g. to judge a search hit.
+    /// Vectors of different dimensions have nothing in common.
+    pub fn score(&self, a: &[f32], b: &[f32]) -> f32 {
+        if a.len() != b.len() {
+            return 0.0;
+        }
+        self.similarity(&self.prepare(a), &self.prepare(b))
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Scored {
+    distance: f32,
+    node: usize,
+}
+
+impl PartialEq for Scored {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Scored {}
+
+impl PartialOrd for Scored {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Scored {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.distance
+            .total_cmp(&other.distance)
+            .then(self.node.cmp(&other.node))
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Node {
+    id: String,
+    vector: Vec<f32>,
+    /// Neighbour lists, one per layer the node lives on.
+    links: Vec<Vec<usize>>,
+    deleted: bool,
+}
+
+/// A single Hierarchical Navigable Small World graph.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HnswIndex {
+    metric: Metric,
+    m: usize,
+    ef_construction: usize,
+    ef_search: usize,
+    dimension: Option<usize>,
+    nodes: Vec<Node>,
+    ids: HashMap<String, usize>,
+    entry: Option<usize>,
+    max_level: usize,
+    tombstones: usize,
+    seed: u64,
+}
+
+const MAX_LEVEL: usize = 16;
+
+impl HnswIndex {
+    pub fn new(metric: Metric) -> Self {
+        Self {
+            metric,
+            m: 16,
+            ef_construction: 100,
+            ef_search: 64,
+            dimension: None,
+            nodes: Vec::new(),
+            ids: HashMap::new(),
+            entry: None,
+            max_level: 0,
+            tombstones: 0,
+            seed: 0x9E37_79B9_7F4A_7C15,
+        }
+    }
+
+    /// Maximum number of neighbours per node on upper layers (layer 0 keeps twice as many).
+    pub fn m(mut self, m: usize) -> Self {
+        self.m = m.max(2);
+        self
+    }
+
+    pub fn ef_construction(mut self, ef: usize) -> Self {
+        self.ef_construction = ef.max(1);
+        self
+    }
+
+    pub fn ef_search(mut self, ef: usize) -> Self {
+        self.ef_search = ef.max(1);
+        self
+    }
+
+    pub fn metric(&self) -> Metric {
+        self.metric
+    }
+
+    pub fn dimension(&self) -> Option<usize> {
+        self.dimension
+    }
+
+    pub fn len(&self) -> usize {
+        self.ids.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ids.is_empty()
+    }
+
+    pub fn contains(&self, id: &str) -> bool {
+        self.ids.contains_key(id)
+    }
+
+    /// The vector stored under `id`, as prepared for the metric.
+    pub fn vector(&self, id: &str) -> Option<&[f32]> {
+        self.ids
+            .get(id)
+            .map(|&index| self.nodes[index].vector.as_slice())
+    }
+
+    /// Whether `vector` can be stored under `id`: it has to match the
+    /// dimension of the vectors already indexed.
+    pub fn check(&self, id: &str, vector: &[f32]) -> Result<(), String> {
+        if vector.is_empty() {
+            return Err(format!("Vector for '{}' is empty", id));
+        }
+        match self.dimension {
+            Some(dimension) if dimension != vector.len() => Err(format!(
+                "Vector for '{}' has dimension {}, index expects {}",
+                id,
+                vector.len(),
+                dimension
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// Inserts a vector, replacing any previous vector stored under `id`.
+    pub fn insert(&mut self, id: impl Into<String>, vector: &[f32]) -> Result<(), String> {
+        let id = id.into();
+        self.check(&id, vector)?;
+        self.dimension = Some(vector.len());
+
+        self.remove(&id);
+
+        let vector = self.metric.prepare(vector);
+        let level = self.random_level();
+        let index = self.nodes.len();
+        self.nodes.push(Node {
+            id: id.clone(),
+            vector,
+            links: vec![Vec::new(); level + 1],
+            deleted: false,
+        });
+        self.ids.insert(id, index);
+
+        let Some(mut entry) = self.entry else {
+            self.entry = Some(index);
+            self.max_level = level;
+            return Ok(());
+        };
+
+        let query = self.nodes[index].vector.clone();
+
+        for layer in (level + 1..=self.max_level).rev() {
+            entry = self.greedy_closest(&query, entry, layer);
+        }
+
+        for layer in (0..=level.min(self.max_level)).rev() {
+            let candidates = self.search_layer(&query, entry, self.ef_construction, layer);
+            let neighbours: Vec<usize> = candidates
+                .iter()
+                .map(|scored| scored.node)
+                .filter(|&node| node != index)
+                .take(self.m)
+                .collect();
+
+            self.nodes[index].links[layer] = neighbours.clone();
+
+            let max_links = if layer == 0 { self.m * 2 } else { self.m };
+            for neighbour in neighbours {
+                self.nodes[neighbour].links[layer].push(index);
+                if self.nodes[neighbour].links[layer].len() > max_links {
+                    self.prune(neighbour, layer, max_links);
+                }
+            }
+
+            if let Some(closest) = candidates.first() {
+                entry = closest.node;
+            }
+        }
+
+        if level > self.max_level {
+            self.max_level = level;
+            self.entry = Some(index);
+        }
+
+        Ok(())
+    }
+
+    /// Marks `id` as deleted. Returns `false` if it was not indexed.
+    pub fn remove(&mut self, id: &str) -> bool {
+        let Some(index) = self.ids.remove(id) else {
+            return false;
+        };
+        self.nodes[index].deleted = true;
+        self.tombstones += 1;
+
+        if self.ids.is_empty() {
+            self.clear();
+        } else if self.tombstones > self.ids.len() {
+            self.rebuild();
+        }
+        true
+    }
+
+    /// Returns up to `limit` `(id, similarity)` pairs, most similar first.
+    pub fn search(&self, query: &[f32], limit: usize) -> Vec<(String, f32)> {
+        let Some(mut entry) = self.entry else {
+            return Vec::new();
+        };
+        if limit == 0 || Some(query.len()) != self.dimension {
+            return Vec::new();
+        }
+
+        let query = self.metric.prepare(query);
+        for layer in (1..=self.max_level).rev() {
+            entry = self.greedy_closest(&query, entry, layer);
+        }
+
+        // Widen the beam by the tombstone count so deleted nodes don't starve the result set.
+        let ef = self.ef_search.max(limit) + self.tombstones.min(self.ef_search);
+        self.search_layer(&query, entry, ef, 0)
+            .into_iter()
+            .filter(|scored| !self.nodes[scored.node].deleted)
+            .take(limit)
+            .map(|scored| (self.nodes[scored.node].id.clone(), -scored.distance))
+            .collect()
+    }
+
+    /// Drops tombstones by re-inserting every live vector into a fresh graph.
+    pub fn rebuild(&mut self) {
+        let live: Vec<(String, Vec<f32>)> = self
+            .nodes
+            .iter()
+            .filter(|node| !node.deleted)
+            .map(|node| (node.id.clone(), node.vector.clone()))
+            .collect();
+
+        self.clear();
+        for (id, vector) in live {
+            // Vectors were already prepared on first insert; re-preparing is idempotent.
+            let _ = self.insert(id, &vector);
+        }
+    }
+
+    fn clear(&mut self) {
+        self.nodes.clear();
+        self.ids.clear();
+        self.entry = None;
+        self.max_level = 0;
+        self.tombstones = 0;
+    }
+
+    fn distance(&self, query: &[f32], node: usize) -> f32 {
+        -self.metric.similarity(query, &self.nodes[node].vector)
+    }
+
+    fn random_level(&mut self) -> usize {
+        // xorshift64*; deterministic so rebuilt indexes stay reproducible.
+        self.seed ^= self.seed >> 12;
+        self.seed ^= self.seed << 25;
+        self.seed ^= self.seed >> 27;
+        let bits = self.seed.wrapping_mul(0x2545_F491_4F6C_DD1D) >> 11;
+        let uniform = (bits as f64 + 1.0) / (1u64 << 53) as f64;
+        let multiplier = 1.0 / (self.m as f64).ln();
+        ((-uniform.ln() * multiplier) as usize).min(MAX_LEVEL)
+    }
+
+    fn greedy_closest(&self, query: &[f32], mut current: usize, layer: usize) -> usize {
+        let mut best = self.distance(query, current);
+        loop {
+            let mut changed = false;
+            for &neighbour in self.links(current, layer) {
+                let distance = self.distance(query, neighbour);
+                if distance < best {
+                    best = distance;
+                    current = neighbour;
+                    changed = true;
+                }
+            }
+            if !changed {
+                return current;
+            }
+        }
+    }
+
+    /// Beam search on a single layer. Results are sorted closest first.
+    fn search_layer(&self, query: &[f32], entry: usize, ef: usize, layer: usize) -> Vec<Scored> {
+        let start = Scored {
+            distance: self.distance(query, entry),
+            node: entry,
+        };
+
+        let mut visited = HashSet::from([entry]);
+        let mut candidates = BinaryHeap::from([std::cmp::Reverse(start)]);
+        let mut results = BinaryHeap::from([start]);
+
+        while let Some(std::cmp::Reverse(current)) = candidates.pop() {
+            let furthest = results.peek().map(|s| s.distance).unwrap_or(f32::MAX);
+            if current.distance > furthest && results.len() >= ef {
+                break;
+            }
+
+            for &neighbour in self.links(current.node, layer) {
+                if !visited.insert(neighbour) {
+                    continue;
+                }
+                let scored = Scored {
+                    distance: self.distance(query, neighbour),
+                    node: neighbour,
+                };
+                let furthest = results.peek().map(|s| s.distance).unwrap_or(f32::MAX);
+                if results.len() < ef || scored.distance < furthest {
+                    candidates.push(std::cmp::Reverse(scored));
+                    results.push(scored);
+                    if results.len() > ef {
+                        results.pop();
+                    }
+                }
+            }
+        }
+
+        results.into_sorted_vec()
+    }
+
+    fn links(&self, node: usize, layer: usize) -> &[usize] {
+        self.nodes[node]
+            .links
+            .get(layer)
+            .map(Vec::as_slice)
+            .unwrap_or(&[])
+    }
+
+    fn prune(&mut self, node: usize, layer: usize, max_links: usize) {
+        let origin = self.nodes[node].vector.clone();
+        let mut scored: Vec<Scored> = self.nodes[node].links[layer]
+            .iter()
+            .map(|&neighbour| Scored {
+                distance: self.distance(&origin, neighbour),
+                node: neighbour,
+            })
+            .collect();
+        scored.sort();
+        scored.dedup_by_key(|s| s.node);
+        scored.truncate(max_links);
+        self.nodes[node].links[layer] = scored.into_iter().map(|s| s.node).collect();
+    }
+}
+
+/// A set of [`HnswIndex`] graphs keyed by record collection.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct VectorIndex {
+    metric: Metric,
+    collections: HashMap<String, HnswIndex>,
+    /// Maps record id to the collection it was indexed under.
+    owners: HashMap<String, String>,
+    #[serde(skip)]
+    unsaved: usize,
+}
+
+impl VectorIndex {
+    pub fn new(metric: Metric) -> Self {
+        Self {
+            metric,
+            ..Default::default()
+        }
+    }
+
+    pub fn metric(&self) -> Metric {
+        self.metric
+    }
+
+    pub fn len(&self) -> usize {
+        self.owners.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.owners.is_empty()
+    }
+
+    pub fn contains(&self, id: &str) -> bool {
+        self.owners.contains_key(id)
+    }
+
+    pub fn collections(&self) -> impl Iterator<Item = &str> {
+        self.collections.keys().map(String::as_str)
+    }
+
+    pub fn ids(&self) -> impl Iterator<Item = &str> {
+        self.owners.keys().map(String::as_str)
+    }
+
+    /// Brings the index in line with `records`: indexes every record that
+    /// carries a vector it doesn't already hold, and drops ids that no
+    /// longer exist.
+    pub fn sync<'a>(&mut self, records: impl IntoIterator<Item = &'a super::SemanticRecord>) {
+        let mut live = HashSet::new();
+        for record in records {
+            let Some(vector) = &record.vector else {
+                continue;
+            };
+            live.insert(record.id.clone());
+            if !self.holds(&record.collection, &record.id, vector) {
+                let _ = self.insert(record.collection.clone(), record.id.clone(), vector);
+            }
+        }
+
+        let stale: Vec<String> = self
+            .owners
+            .keys()
+            .filter(|id| !live.contains(*id))
+            .cloned()
+            .collect();
+        for id in stale {
+            self.remove(&id);
+        }
+    }
+
+    /// Whether `vector` is indexed under `id` in `collection` as it is.
+    fn holds(&self, collection: &str, id: &str, vector: &[f32]) -> bool {
+        self.owners.get(id).is_some_and(|owner| owner == collection)
+            && self
+                .collections
+                .get(collection)
+                .and_then(|index| index.vector(id))
+                .is_some_and(|stored| stored == self.metric.prepare(vector))
+    }
+
+    /// Indexes `vector` under `collection`, moving it if `id` was stored
+    /// elsewhere. A vector that doesn't fit `collection` leaves `id` where
+    /// it was.
+    pub fn insert(
+        &mut self,
+        collection: impl Into<String>,
+        id: impl Into<String>,
+        vector: &[f32],
+    ) -> Result<(), String> {
+        let collection = collection.into();
+        let id = id.into();
+
+        match self.collections.get(&collection) {
+            Some(index) => index.check(&id, vector)?,
+            None if vector.is_empty() => return Err(format!("Vector for '{}' is empty", id)),
+            None => {}
+        }
+
+        if self
+            .owners
+            .get(&id)
+            .is_some_and(|owner| owner != &collection)
+        {
+            self.remove(&id);
+        }
+
+        let metric = self.metric;
+        self.collections
+            .entry(collection.clone())
+            .or_insert_with(|| HnswIndex::new(metric))
+            .insert(id.clone(), vector)?;
+        self.owners.insert(id, collection);
+        self.unsaved += 1;
+        Ok(())
+    }
+
+    pub fn remove(&mut self, id: &str) -> bool {
+        let Some(collection) = self.owners.remove(id) else {
+            return false;
+        };
+        if let Some(index) = self.collections.get_mut(&collection) {
+            index.remove(id);
+            if index.is_empty() {
+                self.collections.remove(&collection);
+            }
+        }
+        self.unsaved += 1;
+        true
+    }
+
+    /// Searches every collection whose dimension matches `query`.
+    pub fn search(&self, query: &[f32], limit: usize) -> Vec<(String, f32)> {
+        let mut results: Vec<(String, f32)> = self
+            .collections
+            .values()
+            .flat_map(|index| index.search(query, limit))
+            .collect();
+        results.sort_by(|a, b| b.1.total_cmp(&a.1));
+        results.truncate(limit);
+        results
+    }
+
+    pub fn search_collection(
+        &self,
+        collection: &str,
+        query: &[f32],
+        limit: usize,
+    ) -> Vec<(String, f32)> {
+        self.collections
+            .get(collection)
+            .map(|index| index.search(query, limit))
+            .unwrap_or_default()
+    }
+
+    /// Number of mutations since the index was last loaded or saved.
+    pub fn unsaved_changes(&self) -> usize {
+        self.unsaved
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Option<Self> {
+        let content = std::fs::read(path).ok()?;
+        serde_json::from_slice(&content).ok()
+    }
+
+    pub fn save(&mut self, path: impl AsRef<Path>) -> std::io::Result<()> {
+        self.write(path)?;
+        self.unsaved = 0;
+        Ok(())
+    }
+
+    /// Counts the index as saved, e.g. once a snapshot of it is handed off
+    /// to [`VectorIndex::write`].
+    pub fn mark_saved(&mut self) {
+        self.unsaved = 0;
+    }
+
+    /// Writes the index to `path`, leaving [`VectorIndex::unsaved_changes`]
+    /// as it is.
+    pub fn write(&self, path: impl AsRef<Path>) -> std::io::Result<()> {
+        let path = path.as_ref();
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let content = serde_json::to_vec(self).map_err(std::io::Error::other)?;
+
+        // Write to a sibling file first so a crash never leaves a truncated index behind.
+        let temp = path.with_extension("json.tmp");
+        std::fs::write(&temp, content)?;
+        std::fs::rename(&temp, path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vectors() -> Vec<(String, Vec<f32>)> {
+        (0..200)
+            .map(|i| {
+                let angle = i as f32 * 0.031;
+                (format!("rec-{}", i), vec![angle.cos(), angle.sin(), 0.5])
+            })
+            .collect()
+    }
+
+    fn brute_force(data: &[(String, Vec<f32>)], query: &[f32], metric: Metric) -> String {
+        let query = metric.prepare(query);
+        data.iter()
+            .max_by(|a, b| {
+                let sa = metric.similarity(&query, &metric.prepare(&a.1));
+                let sb = metric.similarity(&query, &metric.prepare(&b.1));
+                sa.total_cmp(&sb)
+            })
+            .map(|(id, _)| id.clone())
+            .unwrap()
+    }
+
+    #[test]
+    fn test_matches_brute_force() {
+        for metric in [Metric::Cosine, Metric::DotProduct] {
+            let data = vectors();
+            let mut index = HnswIndex::new(metric);
+            for (id, vector) in &data {
+                index.insert(id.clone(), vector).unwrap();
+            }
+
+            for query in [[1.0, 0.0, 0.5], [0.2, 0.9, 0.5], [-0.7, 0.7, 0.5]] {
+                let results = index.search(&query, 5);
+                assert_eq!(results.len(), 5);
+                assert_eq!(results[0].0, brute_force(&data, &query, metric));
+                assert!(results.windows(2).all(|w| w[0].1 >= w[1].1));
+            }
+        }
+    }
+
+    #[test]
+    fn test_delete_and_replace() {
+        let mut index = HnswIndex::new(Metric::Cosine);
+        for (id, vector) in vectors() {
+            index.insert(id, &vector).unwrap();
+        }
+
+        let top = index.search(&[1.0, 0.0, 0.5], 1)[0].0.clone();
+        assert!(index.remove(&top));
+        assert!(!index.remove(&top));
+        assert!(index
+            .search(&[1.0, 0.0, 0.5], 10)
+            .iter()
+            .all(|(id, _)| *id != top));
+
+        index.insert("rec-5", &[0.0, 0.0, 1.0]).unwrap();
+        assert_eq!(index.search(&[0.0, 0.0, 1.0], 1)[0].0, "rec-5");
+        assert_eq!(index.len(), 199);
+
+        assert!(index.insert("bad", &[1.0, 2.0]).is_err());
+    }
+
+    #[test]
+    fn test_rebuild_after_mass_delete() {
+        let mut index = HnswIndex::new(Metric::Cosine);
+        for (id, vector) in vectors() {
+            index.insert(id, &vector).unwrap();
+        }
+        for i in 0..150 {
+            index.remove(&format!("rec-{}", i));
+        }
+        assert_eq!(index.len(), 50);
+        assert!(index.tombstones <= index.len());
+        assert_eq!(index.search(&[1.0, 0.0, 0.5], 100).len(), 50);
+    }
+
+    #[test]
+    fn test_collections_and_persistence() {
+        let mut index = VectorIndex::new(Metric::Cosine);
+        index.insert("Memory", "a", &[1.0, 0.0]).unwrap();
+        index.insert("Docs", "b", &[0.9, 0.1]).unwrap();
+        index.insert("Docs", "c", &[0.0, 1.0]).unwrap();
+
+        assert_eq!(index.search(&[1.0, 0.0], 2)[0].0, "a");
+        assert_eq!(index.search_collection("Docs", &[1.0, 0.0], 1)[0].0, "b");
+
+        // Moving a record between collections drops the old entry.
+        index.insert("Memory", "c", &[0.0, 1.0]).unwrap();
+        assert!(index
+            .search_collection("Docs", &[0.0, 1.0], 5)
+            .iter()
+            .all(|(id, _)| id != "c"));
+
+        let path = std::env::temp_dir().join(format!("peak_index_{}.json", uuid::Uuid::new_v4()));
+        index.save(&path).unwrap();
+        assert_eq!(index.unsaved_changes(), 0);
+
+        let restored = VectorIndex::load(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+        assert_eq!(restored.len(), 3);
+        assert_eq!(restored.search(&[0.0, 1.0], 1)[0].0, "c");
+    }
+
+    #[test]
+    fn test_sync_reindexes_changed_vectors() {
+        let record = |id: &str, collection: &str, vector: Vec<f32>| super::super::SemanticRecord {
+            id: id.to_string(),
+            collection: collection.to_string(),
+            content: String::new(),
+            vector: Some(vector),
+            metadata: serde_json::json!({}),
+            timestamp: 0,
+        };
+
+        let mut index = VectorIndex::new(Metric::Cosine);
+        index.sync(&[
+            record("a", "Memory", vec![1.0, 0.0]),
+            record("b", "Memory", vec![0.0, 1.0]),
+        ]);
+        let unsaved = index.unsaved_changes();
+
+        // Unchanged records are left alone, changed ones indexed again
+        index.sync(&[
+            record("a", "Memory", vec![1.0, 0.0]),
+            record("b", "Memory", vec![-1.0, 0.0]),
+        ]);
+        assert_eq!(index.unsaved_changes(), unsaved + 1);
+        assert_eq!(index.search(&[-1.0, 0.0], 1)[0].0, "b");
+
+        // A vector that doesn't fit the new collection doesn't move the record
+        index.insert("Docs", "c", &[1.0, 0.0, 0.0]).unwrap();
+        assert!(index.insert("Docs", "a", &[1.0, 0.0]).is_err());
+        assert_eq!(index.search_collection("Memory", &[1.0, 0.0], 1)[0].0, "a");
+    }
+}
@@ -2,6 +2,12 @@ use crate::engine::accessibility::AccessibilityNode;
 use iced::Task;
 use std::borrow::Cow;
 
+pub mod index;
+
+pub use index::{Metric, VectorIndex};
+#[cfg(feature = "intelligence")]
+pub use peak_intelligence::context::{ContextBudget, ContextEvent};
+
 /// A semantic representation of a UI component for AI agents and Accessibility.
 ///
 /// `SemanticNode` is a simplified, structured graph of the UI that AI models can
@@ -154,6 +160,49 @@ pub struct ChatCompletionMessage {
     pub content: String,
 }
 
+#[cfg(feature = "intelligence")]
+impl From<ChatCompletionMessage> for peak_intelligence::llm::Message {
+    fn from(message: ChatCompletionMessage) -> Self {
+        Self {
+            role: message.role,
+            content: message.content,
+        }
+    }
+}
+
+#[cfg(feature = "intelligence")]
+impl From<peak_intelligence::llm::Message> for ChatCompletionMessage {
+    fn from(message: peak_intelligence::llm::Message) -> Self {
+        Self {
+            role: message.role,
+            content: message.content,
+        }
+    }
+}
+
+/// A prompt fitted into the model's context window.
+#[cfg(feature = "intelligence")]
+#[derive(Debug, Clone)]
+pub struct CompactedPrompt {
+    pub messages: Vec<ChatCompletionMessage>,
+    pub before_tokens: usize,
+    pub after_tokens: usize,
+    /// What was compressed; empty when the prompt already fit.
+    pub events: Vec<ContextEvent>,
+}
+
+#[cfg(feature = "intelligence")]
+impl From<peak_intelligence::context::Compacted> for CompactedPrompt {
+    fn from(compacted: peak_intelligence::context::Compacted) -> Self {
+        Self {
+            messages: compacted.messages.into_iter().map(Into::into).collect(),
+            before_tokens: compacted.before_tokens,
+            after_tokens: compacted.after_tokens,
+            events: compacted.events,
+        }
+    }
+}
+
 #[cfg(feature = "intelligence")]
 pub trait IntelligenceProvider: Send + Sync {
     fn model(&self) -> &str;
@@ -198,6 +247,28 @@ pub trait IntelligenceProvider: Send + Sync {
         args: serde_json::Value,
     ) -> Task<std::result::Result<serde_json::Value, String>>;
 
+    /// The context window prompts are fitted into.
+    fn context_budget(&self) -> ContextBudget {
+        ContextBudget::for_provider(self.provider())
+    }
+
+    /// Fits `messages` into [`Self::context_budget`]. The default drops and
+    /// truncates; providers that can summarize older turns should override it.
+    fn compact(&self, messages: Vec<ChatCompletionMessage>) -> Task<CompactedPrompt> {
+        let compacted = peak_intelligence::context::truncate(
+            self.provider(),
+            self.context_budget(),
+            messages.into_iter().map(Into::into).collect(),
+        );
+        Task::done(compacted.into())
+    }
+
+    /// Retrieves long-term memory relevant to `query` so replies can cite it.
+    /// `chat_stream` doesn't recall on its own; callers inject what this returns.
+    fn recall(&self, _query: String) -> Task<std::result::Result<Vec<SemanticRecord>, String>> {
+        Task::done(Ok(Vec::new()))
+    }
+
     fn get_system_context(&self) -> String {
         "Peak Intelligence Provider".to_string()
     }
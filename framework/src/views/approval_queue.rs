@@ -0,0 +1,105 @@
+use crate::core::{Backend, Context, IcedBackend, SemanticNode, View};
+use crate::prelude::*;
+use peak_intelligence::tools::approval::{ApprovalRequest, Decision};
+use std::sync::Arc;
+
+/// Longest argument preview shown for a request, in characters.
+const ARGS_PREVIEW: usize = 240;
+
+/// Protected tool calls waiting for a human, each with Deny, Always Allow and
+/// Approve buttons. Pair it with `peak_intelligence::tools::approval::Approvals`:
+/// render its `pending()` requests and pass decisions to its `decide`.
+pub struct ApprovalQueue<Message: Clone + Send + Sync + 'static, B: Backend = IcedBackend> {
+    requests: Vec<ApprovalRequest>,
+    on_decide: Arc<dyn Fn(u64, Decision) -> Message + Send + Sync>,
+    _phantom: std::marker::PhantomData<B>,
+}
+
+impl<Message: Clone + Send + Sync + 'static, B: Backend> ApprovalQueue<Message, B> {
+    pub fn new(
+        requests: Vec<ApprovalRequest>,
+        on_decide: impl Fn(u64, Decision) -> Message + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            requests,
+            on_decide: Arc::new(on_decide),
+            _phantom: std::marker::PhantomData,
+        }
+    }
+
+    fn request_card(&self, request: &ApprovalRequest) -> Card<Message, B> {
+        let decide = |decision| (self.on_decide)(request.id, decision);
+
+        let mut args = request.args.to_string();
+        if args.chars().count() > ARGS_PREVIEW {
+            args = args.chars().take(ARGS_PREVIEW).collect::<String>() + "…";
+        }
+
+        let content = VStack::<Message, B>::new_generic()
+            .spacing(8.0)
+            .push(
+                HStack::<Message, B>::new_generic()
+                    .spacing(8.0)
+                    .align_y(Alignment::Center)
+                    .push(Text::<B>::new(request.tool.clone()).headline())
+                    .push(Text::<B>::new(format!("requested by {}", request.requester)).caption1()),
+            )
+            .push(Text::<B>::new(request.reason.clone()).body())
+            .push(Text::<B>::new(args).footnote())
+            .push(
+                HStack::<Message, B>::new_generic()
+                    .spacing(10.0)
+                    .push(
+                        Button::<Message, B>::label("Deny")
+                            .variant(Variant::Ghost)
+                            .intent(Intent::Neutral)
+                            .on_press(decide(Decision::Deny)),
+                    )
+                    .push(
+                        Button::<Message, B>::label("Always Allow")
+                            .variant(Variant::Outline)
+                            .intent(Intent::Neutral)
+                            .on_press(decide(Decision::AlwaysAllow)),
+                    )
+                    .push(
+                        Button::<Message, B>::label("Approve")
+                            .variant(Variant::Solid)
+                            .intent(Intent::Danger)
+                            .on_press(decide(Decision::Approve)),
+                    ),
+            );
+
+        Card::<Message, B>::new_generic(content).width(Length::Fill)
+    }
+}
+
+impl<Message: Clone + Send + Sync + 'static, B: Backend> View<Message, B>
+    for ApprovalQueue<Message, B>
+{
+    fn view(&self, context: &Context) -> B::AnyView<Message> {
+        let mut list = VStack::<Message, B>::new_generic()
+            .spacing(12.0)
+            .width(Length::Fixed(460.0));
+
+        if !self.requests.is_empty() {
+            list = list.push(Text::<B>::new("Neural Sudo Permission").title3());
+        }
+
+        for request in &self.requests {
+            list = list.push(self.request_card(request));
+        }
+
+        list.view(context)
+    }
+
+    fn describe(&self, _context: &Context) -> SemanticNode {
+        let mut node = SemanticNode::new("approval_queue")
+            .with_label(format!("{} pending approvals", self.requests.len()));
+        node.is_protected = true;
+        node.protection_reason = self
+            .requests
+            .first()
+            .map(|request| request.reason.clone().into());
+        node
+    }
+}
@@ -1,12 +1,30 @@
+use crate::backend::{Plot, Shape};
 use crate::prelude::*;
+use chrono::{DateTime, Utc};
+use peak_theme::PeakColors;
 use std::borrow::Cow;
 
+pub mod axis;
+pub mod downsample;
+pub mod ring;
+pub mod stream;
+
+use axis::Scale;
+pub use downsample::Downsample;
+pub use ring::RingBuffer;
+pub use stream::{StreamingChart, TimeSeries};
+
 #[derive(Debug, Clone, serde::Serialize, PartialEq)]
 pub enum ChartType {
     Line,
     Bar,
     Area,
     Pie,
+    /// Bars of each series stacked on one another, negatives downwards.
+    StackedBar,
+    Scatter,
+    /// How the values of each series are distributed, see [`Chart::bins`].
+    Histogram,
 }
 
 #[derive(Debug, Clone, serde::Serialize)]
@@ -15,27 +33,137 @@ pub struct ChartDataPoint {
     pub value: f32,
 }
 
+/// A named list of `(x, y)` points. What x means depends on the chart's
+/// [`XAxis`].
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct Series {
+    pub name: String,
+    pub points: Vec<(f64, f64)>,
+    /// Overrides the chart's palette.
+    #[serde(skip)]
+    pub color: Option<Color>,
+}
+
+impl Series {
+    /// Values at `x = 0, 1, 2…`, e.g. one per category.
+    pub fn new(name: impl Into<String>, values: impl IntoIterator<Item = f64>) -> Self {
+        Self::xy(
+            name,
+            values
+                .into_iter()
+                .enumerate()
+                .map(|(i, value)| (i as f64, value)),
+        )
+    }
+
+    pub fn xy(name: impl Into<String>, points: impl IntoIterator<Item = (f64, f64)>) -> Self {
+        Self {
+            name: name.into(),
+            points: points.into_iter().collect(),
+            color: None,
+        }
+    }
+
+    /// Values over time, for an [`XAxis::Time`] chart.
+    pub fn time(
+        name: impl Into<String>,
+        points: impl IntoIterator<Item = (DateTime<Utc>, f64)>,
+    ) -> Self {
+        Self::xy(
+            name,
+            points
+                .into_iter()
+                .map(|(time, value)| (time.timestamp_millis() as f64 / 1000.0, value)),
+        )
+    }
+
+    pub fn color(mut self, color: Color) -> Self {
+        self.color = Some(color);
+        self
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub enum XAxis {
+    /// Named slots at `x = 0, 1, 2…`.
+    Category(Vec<String>),
+    Numeric,
+    /// Unix timestamps in seconds.
+    Time,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Trend {
+    Rising,
+    Falling,
+    Flat,
+}
+
+/// The shape of a series at a glance, for agents reading the chart.
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct SeriesSummary {
+    pub name: String,
+    pub count: usize,
+    pub min: f64,
+    pub max: f64,
+    pub mean: f64,
+    pub last: f64,
+    pub trend: Trend,
+    /// How much a straight line fitted to the series rises from its first
+    /// x to its last.
+    pub change: f64,
+}
+
+/// Most ticks on an axis.
+const X_TICKS: usize = 8;
+const Y_TICKS: usize = 5;
+
+/// Series with more points than this are drawn without markers.
+const MAX_MARKERS: usize = 60;
+
 pub struct Chart<Message: 'static + Send + Sync, B: Backend = IcedBackend> {
     pub chart_type: ChartType,
-    pub data: Vec<ChartDataPoint>,
+    pub series: Vec<Series>,
+    pub x_axis: XAxis,
     pub title: Option<String>,
     pub color: Color,
     pub palette: Vec<Color>,
     pub width: Length,
     pub height: Length,
+    /// Defaults to showing a legend for more than one series.
+    pub show_legend: Option<bool>,
+    pub show_grid: bool,
+    /// Histogram bins; by default chosen from the number of values.
+    pub bins: Option<usize>,
     _phantom: std::marker::PhantomData<(Message, B)>,
 }
 
 impl<Message: 'static + Send + Sync, B: Backend> Chart<Message, B> {
+    /// A single series of labelled values.
     pub fn new(chart_type: ChartType, data: Vec<ChartDataPoint>) -> Self {
+        let labels = data.iter().map(|point| point.label.clone()).collect();
+        let values = data.iter().map(|point| point.value as f64);
+
+        Self::with_series(chart_type, vec![Series::new("Value", values)])
+            .x_axis(XAxis::Category(labels))
+    }
+
+    /// Several series over a numeric x axis; see [`Chart::x_axis`] for time
+    /// and categories.
+    pub fn with_series(chart_type: ChartType, series: Vec<Series>) -> Self {
         Self {
             chart_type,
-            data,
+            series,
+            x_axis: XAxis::Numeric,
             title: None,
             color: Color::from_rgb(0.31, 0.98, 0.48), // Default Peak accent
             palette: Vec::new(),
             width: Length::Fill,
             height: Length::Fixed(300.0),
+            show_legend: None,
+            show_grid: true,
+            bins: None,
             _phantom: std::marker::PhantomData,
         }
     }
@@ -45,6 +173,11 @@ impl<Message: 'static + Send + Sync, B: Backend> Chart<Message, B> {
         self
     }
 
+    pub fn x_axis(mut self, x_axis: XAxis) -> Self {
+        self.x_axis = x_axis;
+        self
+    }
+
     pub fn color(mut self, color: Color) -> Self {
         self.color = color;
         self
@@ -64,6 +197,26 @@ impl<Message: 'static + Send + Sync, B: Backend> Chart<Message, B> {
         self.height = height;
         self
     }
+
+    pub fn legend(mut self, show: bool) -> Self {
+        self.show_legend = Some(show);
+        self
+    }
+
+    pub fn grid(mut self, show: bool) -> Self {
+        self.show_grid = show;
+        self
+    }
+
+    pub fn bins(mut self, bins: usize) -> Self {
+        self.bins = Some(bins.max(1));
+        self
+    }
+
+    /// Min, max, mean and trend of each non-empty series.
+    pub fn summaries(&self) -> Vec<SeriesSummary> {
+        self.series.iter().filter_map(summarize).collect()
+    }
 }
 
 impl<Message: Clone + Send + Sync + 'static, B: Backend> View<Message, B> for Chart<Message, B> {
@@ -85,22 +238,25 @@ impl<Message: Clone + Send + Sync + 'static, B: Backend> View<Message, B> for Ch
             ));
         }
 
-        let chart_content = match self.chart_type {
-            ChartType::Bar => self.render_bar(context),
-            ChartType::Line => self.render_line(context),
-            ChartType::Pie => self.render_pie(context),
-            _ => B::rectangle(
+        if self.chart_type == ChartType::Pie {
+            children.push(self.render_pie(context));
+        } else {
+            children.push(B::plot(
+                self.plot(context),
                 Length::Fill,
                 Length::Fill,
-                None,
-                8.0,
-                1.0,
-                Some(context.theme.colors.border),
                 context,
-            ),
-        };
+            ));
 
-        children.push(chart_content);
+            if self.show_legend.unwrap_or(self.series.len() > 1) {
+                let entries = self
+                    .series
+                    .iter()
+                    .enumerate()
+                    .map(|(i, series)| (series.name.clone(), self.series_color(i, context)));
+                children.push(legend::<Message, B>(entries, context));
+            }
+        }
 
         B::vstack(
             children,
@@ -119,12 +275,38 @@ impl<Message: Clone + Send + Sync + 'static, B: Backend> View<Message, B> for Ch
         node.label = self.title.as_ref().map(|t| Cow::Owned(t.to_string()));
 
         // Export data for AI perception
-        let data_json = serde_json::to_string(&self.data).unwrap_or_default();
-        node.content = Some(Cow::Owned(format!(
-            "Type: {:?}, Data: {}",
-            self.chart_type, data_json
-        )));
+        let mut lines = vec![format!(
+            "Type: {:?}, X axis: {}",
+            self.chart_type,
+            match &self.x_axis {
+                XAxis::Category(_) => "categories",
+                XAxis::Numeric => "numeric",
+                XAxis::Time => "time",
+            }
+        )];
+        for summary in self.summaries() {
+            lines.push(format!(
+                "{} ({} points): min {}, max {}, mean {}, last {}, trend {:?} ({}{})",
+                summary.name,
+                summary.count,
+                format_value(summary.min),
+                format_value(summary.max),
+                format_value(summary.mean),
+                format_value(summary.last),
+                summary.trend,
+                if summary.change >= 0.0 { "+" } else { "" },
+                format_value(summary.change),
+            ));
+        }
+
+        // Small charts also carry their raw data
+        let count: usize = self.series.iter().map(|s| s.points.len()).sum();
+        if count <= 50 {
+            let data = serde_json::json!({ "x_axis": self.x_axis, "series": self.series });
+            lines.push(format!("Data: {}", data));
+        }
 
+        node.content = Some(Cow::Owned(lines.join("\n")));
         node
     }
 
@@ -134,259 +316,415 @@ impl<Message: Clone + Send + Sync + 'static, B: Backend> View<Message, B> for Ch
 }
 
 impl<Message: Clone + Send + Sync + 'static, B: Backend> Chart<Message, B> {
-    fn render_bar(&self, context: &Context) -> B::AnyView<Message> {
-        let max_value = self
-            .data
+    fn series_color(&self, index: usize, context: &Context) -> Color {
+        if let Some(color) = self.series.get(index).and_then(|s| s.color) {
+            return color;
+        }
+        if !self.palette.is_empty() {
+            return self.palette[index % self.palette.len()];
+        }
+
+        let colors = context.theme.colors;
+        [
+            self.color,
+            colors.primary,
+            colors.accent,
+            colors.warning,
+            colors.info,
+            colors.danger,
+        ][index % 6]
+    }
+
+    /// The label of slot `index` on a category axis.
+    fn category(&self, index: usize) -> String {
+        match &self.x_axis {
+            XAxis::Category(labels) => labels.get(index).cloned().unwrap_or_default(),
+            _ => self
+                .series
+                .first()
+                .and_then(|series| series.points.get(index))
+                .map(|&(x, _)| self.x_label(x))
+                .unwrap_or_default(),
+        }
+    }
+
+    fn x_label(&self, x: f64) -> String {
+        match &self.x_axis {
+            XAxis::Category(labels) => labels.get(x.round() as usize).cloned().unwrap_or_default(),
+            XAxis::Numeric => format_value(x),
+            XAxis::Time => DateTime::<Utc>::from_timestamp(x.floor() as i64, 0)
+                .map(|time| time.format("%Y-%m-%d %H:%M:%S").to_string())
+                .unwrap_or_else(|| format_value(x)),
+        }
+    }
+
+    fn slots(&self) -> usize {
+        match &self.x_axis {
+            XAxis::Category(labels) => labels.len(),
+            _ => 0,
+        }
+        .max(
+            self.series
+                .iter()
+                .map(|s| s.points.len())
+                .max()
+                .unwrap_or(0),
+        )
+    }
+
+    fn x_scale(&self) -> Scale {
+        if matches!(self.chart_type, ChartType::Bar | ChartType::StackedBar) {
+            let labels: Vec<String> = (0..self.slots()).map(|i| self.category(i)).collect();
+            return Scale::category(&labels, X_TICKS);
+        }
+
+        let xs = self
+            .series
             .iter()
-            .map(|d| d.value)
-            .fold(0.0f32, |a, b| a.max(b));
+            .flat_map(|s| s.points.iter().map(|p| p.0));
+        let (min, max) = bounds(xs);
+        match &self.x_axis {
+            XAxis::Category(labels) => Scale::category(labels, X_TICKS),
+            XAxis::Time => Scale::time(min, max, X_TICKS - 2),
+            XAxis::Numeric if self.chart_type == ChartType::Scatter => {
+                Scale::nice(min, max, X_TICKS)
+            }
+            XAxis::Numeric => Scale::linear(min, max, X_TICKS),
+        }
+    }
 
-        let chart_height = 140.0; // Slightly reduced to give label area more room
-        let mut bars = Vec::new();
+    /// Values of each series in slot `index`, for bars.
+    fn slot_values(&self, index: usize) -> Vec<Option<f64>> {
+        self.series
+            .iter()
+            .map(|series| series.points.get(index).map(|p| p.1))
+            .collect()
+    }
 
-        for (i, point) in self.data.iter().enumerate() {
-            let ratio = if max_value > 0.0 {
-                point.value / max_value
-            } else {
-                0.0
-            };
+    fn plot(&self, context: &Context) -> Plot {
+        let colors = context.theme.colors;
+        let histogram = (self.chart_type == ChartType::Histogram).then(|| self.histogram());
 
-            let bar_h = (ratio * chart_height).max(1.0);
-            let spacer_h = chart_height - bar_h;
+        let x = match &histogram {
+            Some(histogram) => Scale::linear(histogram.min, histogram.max, X_TICKS),
+            None => self.x_scale(),
+        };
+        let y = match &histogram {
+            Some(histogram) => {
+                let most = histogram
+                    .counts
+                    .iter()
+                    .flatten()
+                    .copied()
+                    .max()
+                    .unwrap_or(0);
+                Scale::nice(0.0, most.max(1) as f64, Y_TICKS)
+            }
+            None => self.y_scale(),
+        };
 
-            let color = if self.palette.is_empty() {
-                self.color
-            } else {
-                self.palette[i % self.palette.len()]
-            };
+        let to_point =
+            |px: f64, py: f64| Point::new(x.position(px) as f32, 1.0 - y.position(py) as f32);
 
-            let bar_item = B::vstack(
-                vec![
-                    // Bar Area
-                    B::vstack(
-                        vec![
-                            B::rectangle(
-                                Length::Fixed(1.0),
-                                Length::Fixed(spacer_h),
-                                None,
-                                0.0,
-                                0.0,
-                                None,
-                                context,
-                            ),
-                            B::rectangle(
-                                Length::Fixed(40.0),
-                                Length::Fixed(bar_h),
-                                Some(color),
-                                4.0,
-                                0.0,
-                                None,
-                                context,
-                            ),
-                        ],
-                        0.0,
-                        Padding::ZERO,
-                        Length::Fixed(40.0),
-                        Length::Fixed(chart_height),
-                        Alignment::Center,
-                        Alignment::End,
-                        context,
-                    ),
-                    // Label Area
-                    B::vstack(
-                        vec![
-                            B::text(
-                                point.label.clone(),
-                                11.0,
-                                None,
-                                true,
-                                false,
-                                None,
-                                None,
-                                Length::Shrink,
-                                Alignment::Center,
-                                context,
-                            ),
-                            B::text(
-                                format!("{:.0}", point.value),
-                                10.0,
-                                None,
-                                false,
-                                true,
-                                None,
-                                None,
-                                Length::Shrink,
-                                Alignment::Center,
-                                context,
-                            ),
-                        ],
-                        2.0,
-                        Padding::ZERO,
-                        Length::Shrink,
-                        Length::Shrink,
-                        Alignment::Center,
-                        Alignment::Start,
-                        context,
-                    ),
-                ],
-                8.0,
-                Padding::ZERO,
-                Length::Fixed(60.0), // Give more width to labels
-                Length::Shrink,
-                Alignment::Center,
-                Alignment::Start,
-                context,
+        let vertical_grid = self.show_grid
+            && !matches!(
+                self.chart_type,
+                ChartType::Bar | ChartType::StackedBar | ChartType::Histogram
+            );
+        let (mut plot, base_y) = axes(&x, &y, self.show_grid, vertical_grid, &colors);
+
+        match self.chart_type {
+            ChartType::Line | ChartType::Area | ChartType::Scatter | ChartType::Pie => {
+                for (i, series) in self.series.iter().enumerate() {
+                    let color = self.series_color(i, context);
+                    let points: Vec<Point> = series
+                        .points
+                        .iter()
+                        .map(|&(px, py)| to_point(px, py))
+                        .collect();
+
+                    if self.chart_type == ChartType::Area && points.len() > 1 {
+                        let mut area = vec![Point::new(points[0].x, base_y)];
+                        area.extend(points.iter().copied());
+                        area.push(Point::new(points[points.len() - 1].x, base_y));
+                        plot.push(Shape::Area {
+                            points: area,
+                            color: color.scale_alpha(0.25),
+                        });
+                    }
+
+                    if self.chart_type != ChartType::Scatter {
+                        plot.push(Shape::Line {
+                            points: points.clone(),
+                            color,
+                            width: 2.0,
+                        });
+                    }
+
+                    let radius = if self.chart_type == ChartType::Scatter {
+                        4.0
+                    } else {
+                        3.0
+                    };
+                    if self.chart_type == ChartType::Scatter || points.len() <= MAX_MARKERS {
+                        for center in points {
+                            plot.push(Shape::Dot {
+                                center,
+                                radius,
+                                color,
+                            });
+                        }
+                    }
+                }
+
+                if self.chart_type == ChartType::Scatter {
+                    self.scatter_hovers(&mut plot, &to_point);
+                } else {
+                    self.band_hovers(&mut plot, &x);
+                }
+            }
+            ChartType::Bar | ChartType::StackedBar => {
+                let stacked = self.chart_type == ChartType::StackedBar;
+                let slot_width = (1.0 / (x.max - x.min)) as f32;
+                let group = slot_width * 0.8;
+                let series_count = self.series.len().max(1);
+
+                for slot in 0..self.slots() {
+                    let center = x.position(slot as f64) as f32;
+                    let values = self.slot_values(slot);
+                    let (mut above, mut below) = (0.0, 0.0);
+
+                    for (i, value) in values.iter().enumerate() {
+                        let Some(value) = *value else { continue };
+                        let color = self.series_color(i, context);
+
+                        let (left, right, from, to) = if stacked {
+                            let from = if value >= 0.0 { above } else { below };
+                            if value >= 0.0 {
+                                above += value;
+                            } else {
+                                below += value;
+                            }
+                            (
+                                center - group / 2.0,
+                                center + group / 2.0,
+                                from,
+                                from + value,
+                            )
+                        } else {
+                            let bar = group / series_count as f32;
+                            let left = center - group / 2.0 + bar * i as f32;
+                            (left + bar * 0.1, left + bar * 0.9, 0.0, value)
+                        };
+
+                        plot.push(Shape::Rect {
+                            top_left: Point::new(left, to_point(x.min, to).y),
+                            bottom_right: Point::new(right, to_point(x.min, from).y),
+                            color,
+                        });
+                    }
+
+                    let mut tooltip = vec![self.category(slot)];
+                    for (series, value) in self.series.iter().zip(&values) {
+                        if let Some(value) = value {
+                            tooltip.push(format!("{}: {}", series.name, format_value(*value)));
+                        }
+                    }
+                    if stacked && self.series.len() > 1 {
+                        tooltip.push(format!("Total: {}", format_value(above + below)));
+                    }
+                    plot.hover(
+                        Point::new(center - slot_width / 2.0, 0.0),
+                        Point::new(center + slot_width / 2.0, 1.0),
+                        tooltip.join("\n"),
+                    );
+                }
+            }
+            ChartType::Histogram => {
+                let Some(histogram) = histogram else {
+                    return plot;
+                };
+                let overlay = if self.series.len() > 1 { 0.6 } else { 1.0 };
+                let bin_width = (histogram.max - histogram.min) / histogram.bins as f64;
+
+                for bin in 0..histogram.bins {
+                    let from = histogram.min + bin as f64 * bin_width;
+                    let to = from + bin_width;
+                    let left = x.position(from) as f32;
+                    let right = x.position(to) as f32;
+                    let gap = (right - left) * 0.05;
+
+                    let mut tooltip =
+                        vec![format!("{} – {}", format_value(from), format_value(to))];
+                    for (i, counts) in histogram.counts.iter().enumerate() {
+                        let count = counts[bin];
+                        plot.push(Shape::Rect {
+                            top_left: Point::new(left + gap, to_point(from, count as f64).y),
+                            bottom_right: Point::new(right - gap, base_y),
+                            color: self.series_color(i, context).scale_alpha(overlay),
+                        });
+                        tooltip.push(format!("{}: {}", self.series[i].name, count));
+                    }
+
+                    plot.hover(
+                        Point::new(left, 0.0),
+                        Point::new(right, 1.0),
+                        tooltip.join("\n"),
+                    );
+                }
+            }
+        }
+
+        plot
+    }
+
+    fn y_scale(&self) -> Scale {
+        let stacked = self.chart_type == ChartType::StackedBar;
+        let mut values: Vec<f64> = Vec::new();
+
+        if stacked {
+            for slot in 0..self.slots() {
+                let slot_values: Vec<f64> = self.slot_values(slot).into_iter().flatten().collect();
+                values.push(slot_values.iter().filter(|v| **v > 0.0).sum());
+                values.push(slot_values.iter().filter(|v| **v < 0.0).sum());
+            }
+        } else {
+            values.extend(
+                self.series
+                    .iter()
+                    .flat_map(|s| s.points.iter().map(|p| p.1)),
             );
-            bars.push(bar_item);
         }
 
-        B::hstack(
-            bars,
-            16.0,
-            Padding::ZERO,
-            Length::Fill,
-            Length::Fill,
-            Alignment::Center,
-            Alignment::End,
-            context,
-        )
+        // Bars and areas grow from zero
+        if matches!(
+            self.chart_type,
+            ChartType::Bar | ChartType::StackedBar | ChartType::Area
+        ) {
+            values.push(0.0);
+        }
+
+        let (min, max) = bounds(values.into_iter());
+        Scale::nice(min, max, Y_TICKS)
     }
 
-    fn render_line(&self, context: &Context) -> B::AnyView<Message> {
-        let max_value = self
-            .data
+    /// One hover band per x value, reaching halfway to its neighbours.
+    fn band_hovers(&self, plot: &mut Plot, x: &Scale) {
+        let mut xs: Vec<f64> = self
+            .series
             .iter()
-            .map(|d| d.value)
-            .fold(0.0f32, |a, b| a.max(b));
+            .flat_map(|s| s.points.iter().map(|p| p.0))
+            .collect();
+        xs.sort_by(f64::total_cmp);
+        xs.dedup();
 
-        let chart_height = 140.0;
-        let step_width = 80.0;
-        let mut markers = Vec::new();
-        let mut path_points = Vec::new();
+        for (i, &value) in xs.iter().enumerate() {
+            let at = x.position(value) as f32;
+            let left = i
+                .checked_sub(1)
+                .map(|prev| (x.position(xs[prev]) as f32 + at) / 2.0)
+                .unwrap_or(0.0);
+            let right = xs
+                .get(i + 1)
+                .map(|&next| (x.position(next) as f32 + at) / 2.0)
+                .unwrap_or(1.0);
 
-        for (i, point) in self.data.iter().enumerate() {
-            let ratio = if max_value > 0.0 {
-                point.value / max_value
-            } else {
-                0.0
-            };
+            let mut tooltip = vec![self.x_label(value)];
+            for series in &self.series {
+                if let Some(&(_, y)) = series.points.iter().find(|p| p.0 == value) {
+                    tooltip.push(format!("{}: {}", series.name, format_value(y)));
+                }
+            }
 
-            let x = i as f32 * step_width + step_width / 2.0;
-            let y = chart_height - (ratio * chart_height);
-            path_points.push(iced::Point::new(x, y));
+            plot.hover(
+                Point::new(left, 0.0),
+                Point::new(right, 1.0),
+                tooltip.join("\n"),
+            );
+        }
+    }
 
-            let col_item = B::vstack(
-                vec![
-                    // Chart Drawing Area (Fixed Height)
-                    B::vstack(
-                        vec![
-                            B::rectangle(
-                                Length::Fixed(1.0),
-                                Length::Fixed((y - 4.0).max(0.0)),
-                                None,
-                                0.0,
-                                0.0,
-                                None,
-                                context,
-                            ),
-                            B::circle(4.0, Some(self.color), context),
-                            B::rectangle(
-                                Length::Fixed(1.0),
-                                Length::Fixed((chart_height - y - 4.0).max(0.0)),
-                                Some(self.color.scale_alpha(0.2)),
-                                0.0,
-                                0.0,
-                                None,
-                                context,
-                            ),
-                        ],
-                        0.0,
-                        Padding::ZERO,
-                        Length::Fixed(step_width),
-                        Length::Fixed(chart_height),
-                        Alignment::Center,
-                        Alignment::End,
-                        context,
-                    ),
-                    // Label and Value Area
-                    B::vstack(
-                        vec![
-                            B::text(
-                                point.label.clone(),
-                                11.0,
-                                None,
-                                true, // Bold Title
-                                false,
-                                None,
-                                None,
-                                Length::Shrink,
-                                Alignment::Center,
-                                context,
-                            ),
-                            B::text(
-                                format!("{:.0}", point.value),
-                                10.0,
-                                None,
-                                false,
-                                true, // Dim Value
-                                None,
-                                None,
-                                Length::Shrink,
-                                Alignment::Center,
-                                context,
-                            ),
-                        ],
-                        2.0,
-                        Padding::ZERO,
-                        Length::Shrink,
-                        Length::Shrink,
-                        Alignment::Center,
-                        Alignment::Start,
-                        context,
+    fn scatter_hovers(&self, plot: &mut Plot, to_point: &impl Fn(f64, f64) -> Point) {
+        const REACH: f32 = 0.015;
+
+        for series in &self.series {
+            for &(px, py) in &series.points {
+                let at = to_point(px, py);
+                plot.hover(
+                    Point::new(at.x - REACH, at.y - REACH),
+                    Point::new(at.x + REACH, at.y + REACH),
+                    format!(
+                        "{}\nx: {}\ny: {}",
+                        series.name,
+                        self.x_label(px),
+                        format_value(py)
                     ),
-                ],
-                8.0,
-                Padding::ZERO,
-                Length::Fixed(step_width),
-                Length::Shrink,
-                Alignment::Center,
-                Alignment::Start,
-                context,
-            );
-            markers.push(col_item);
+                );
+            }
         }
+    }
 
-        let line_width = step_width * self.data.len() as f32;
+    fn histogram(&self) -> Histogram {
+        let values = self
+            .series
+            .iter()
+            .flat_map(|s| s.points.iter().map(|p| p.1));
+        let count = values.clone().count();
+        let (min, max) = bounds(values);
+        let (min, max) = if min < max {
+            (min, max)
+        } else {
+            (min - 0.5, max + 0.5)
+        };
 
-        B::zstack(
-            vec![
-                B::path(path_points, Some(self.color), 2.0, context),
-                B::hstack(
-                    markers,
-                    0.0,
-                    Padding::ZERO,
-                    Length::Fixed(line_width),
-                    Length::Fill,
-                    Alignment::Start,
-                    Alignment::End,
-                    context,
-                ),
-            ],
-            Length::Fixed(line_width),
-            Length::Fill,
-            Alignment::End,
-            context,
-        )
+        // Sturges' rule
+        let bins = self
+            .bins
+            .unwrap_or_else(|| (count.max(1) as f64).log2().ceil() as usize + 1);
+        let width = (max - min) / bins as f64;
+
+        let counts = self
+            .series
+            .iter()
+            .map(|series| {
+                let mut counts = vec![0; bins];
+                for &(_, value) in &series.points {
+                    let bin = (((value - min) / width) as usize).min(bins - 1);
+                    counts[bin] += 1;
+                }
+                counts
+            })
+            .collect();
+
+        Histogram {
+            min,
+            max,
+            bins,
+            counts,
+        }
     }
 
     fn render_pie(&self, context: &Context) -> B::AnyView<Message> {
-        let total_value: f32 = self.data.iter().map(|d| d.value).sum();
+        let data: Vec<(String, f64)> = self
+            .series
+            .first()
+            .map(|series| {
+                series
+                    .points
+                    .iter()
+                    .enumerate()
+                    .map(|(i, &(_, value))| (self.category(i), value))
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let total_value: f64 = data.iter().map(|(_, value)| value).sum();
         let mut current_angle = -std::f32::consts::FRAC_PI_2;
         let mut arcs = Vec::new();
         let mut segments = Vec::new();
         let radius = 60.0;
 
-        for (i, point) in self.data.iter().enumerate() {
+        for (i, (label, value)) in data.iter().enumerate() {
             let color = if self.palette.is_empty() {
                 self.color.scale_alpha(1.0 - (i as f32 * 0.2))
             } else {
@@ -394,7 +732,7 @@ impl<Message: Clone + Send + Sync + 'static, B: Backend> Chart<Message, B> {
             };
 
             let sweep = if total_value > 0.0 {
-                (point.value / total_value) * 2.0 * std::f32::consts::PI
+                (value / total_value) as f32 * 2.0 * std::f32::consts::PI
             } else {
                 0.0
             };
@@ -412,7 +750,7 @@ impl<Message: Clone + Send + Sync + 'static, B: Backend> Chart<Message, B> {
                 vec![
                     B::circle(6.0, Some(color), context),
                     B::text(
-                        format!("{}: {:.0}", point.label, point.value),
+                        format!("{}: {:.0}", label, value),
                         12.0,
                         None,
                         false,
@@ -477,3 +815,328 @@ impl<Message: Clone + Send + Sync + 'static, B: Backend> Chart<Message, B> {
         )
     }
 }
+
+/// A swatch and name per series.
+fn legend<Message: Clone + Send + Sync + 'static, B: Backend>(
+    entries: impl Iterator<Item = (String, Color)>,
+    context: &Context,
+) -> B::AnyView<Message> {
+    let items = entries
+        .map(|(name, color)| {
+            B::hstack(
+                vec![
+                    B::circle(5.0, Some(color), context),
+                    B::text(
+                        name,
+                        11.0,
+                        None,
+                        false,
+                        false,
+                        None,
+                        None,
+                        Length::Shrink,
+                        Alignment::Start,
+                        context,
+                    ),
+                ],
+                6.0,
+                Padding::ZERO,
+                Length::Shrink,
+                Length::Shrink,
+                Alignment::Start,
+                Alignment::Center,
+                context,
+            )
+        })
+        .collect();
+
+    B::wrap(
+        items,
+        16.0,
+        8.0,
+        Padding::ZERO,
+        Length::Fill,
+        Length::Shrink,
+        Alignment::Center,
+        Alignment::Center,
+        context,
+    )
+}
+
+/// A plot with gridlines, tick labels and the zero line for `x` and `y`,
+/// and where the zero line is.
+fn axes(
+    x: &Scale,
+    y: &Scale,
+    horizontal_grid: bool,
+    vertical_grid: bool,
+    colors: &PeakColors,
+) -> (Plot, f32) {
+    let to_point =
+        |px: f64, py: f64| Point::new(x.position(px) as f32, 1.0 - y.position(py) as f32);
+
+    // Room for the y labels on the left and x labels below
+    let label_chars = y
+        .ticks
+        .iter()
+        .map(|t| t.label.chars().count())
+        .max()
+        .unwrap_or(1);
+    let mut plot = Plot::new(Size::new(1.0, 1.0)).padding(Padding {
+        top: 8.0,
+        right: 16.0,
+        bottom: 24.0,
+        left: label_chars as f32 * 6.5 + 12.0,
+    });
+
+    let label = |plot: &mut Plot, at: Point, offset: Vector, content: &str, align_x, align_y| {
+        plot.push(Shape::Text {
+            at,
+            offset,
+            content: content.to_string(),
+            size: 10.0,
+            color: colors.text_secondary,
+            align_x,
+            align_y,
+        });
+    };
+
+    for tick in &y.ticks {
+        let at = to_point(x.min, tick.value);
+        if horizontal_grid {
+            plot.push(Shape::Line {
+                points: vec![Point::new(0.0, at.y), Point::new(1.0, at.y)],
+                color: colors.divider.scale_alpha(0.5),
+                width: 1.0,
+            });
+        }
+        label(
+            &mut plot,
+            Point::new(0.0, at.y),
+            Vector::new(-8.0, 0.0),
+            &tick.label,
+            Alignment::End,
+            Alignment::Center,
+        );
+    }
+
+    for tick in &x.ticks {
+        let at = Point::new(x.position(tick.value) as f32, 1.0);
+        if vertical_grid {
+            plot.push(Shape::Line {
+                points: vec![Point::new(at.x, 0.0), at],
+                color: colors.divider.scale_alpha(0.3),
+                width: 1.0,
+            });
+        }
+        label(
+            &mut plot,
+            at,
+            Vector::new(0.0, 6.0),
+            &tick.label,
+            Alignment::Center,
+            Alignment::Start,
+        );
+    }
+
+    // The zero line, or the bottom edge
+    let base = if y.min <= 0.0 && y.max >= 0.0 {
+        0.0
+    } else {
+        y.min
+    };
+    let base_y = to_point(x.min, base).y;
+    plot.push(Shape::Line {
+        points: vec![Point::new(0.0, base_y), Point::new(1.0, base_y)],
+        color: colors.border,
+        width: 1.0,
+    });
+
+    (plot, base_y)
+}
+
+struct Histogram {
+    min: f64,
+    max: f64,
+    bins: usize,
+    /// Per series, the number of values in each bin.
+    counts: Vec<Vec<usize>>,
+}
+
+fn bounds(values: impl Iterator<Item = f64>) -> (f64, f64) {
+    values
+        .filter(|value| value.is_finite())
+        .fold(None, |bounds: Option<(f64, f64)>, value| match bounds {
+            Some((min, max)) => Some((min.min(value), max.max(value))),
+            None => Some((value, value)),
+        })
+        .unwrap_or((0.0, 1.0))
+}
+
+fn summarize(series: &Series) -> Option<SeriesSummary> {
+    let (_, last) = *series.points.last()?;
+    let count = series.points.len();
+    let ys = series.points.iter().map(|p| p.1);
+    let (min, max) = bounds(ys.clone());
+    let mean = ys.sum::<f64>() / count as f64;
+
+    // Least-squares slope over the series' x range
+    let mean_x = series.points.iter().map(|p| p.0).sum::<f64>() / count as f64;
+    let (covariance, variance) = series.points.iter().fold((0.0, 0.0), |(c, v), &(x, y)| {
+        (c + (x - mean_x) * (y - mean), v + (x - mean_x).powi(2))
+    });
+    let (first_x, last_x) = bounds(series.points.iter().map(|p| p.0));
+    let change = if variance > 0.0 {
+        covariance / variance * (last_x - first_x)
+    } else {
+        0.0
+    };
+
+    // Changes under 5% of the value range count as flat
+    let trend = if max == min || change.abs() < (max - min) * 0.05 {
+        Trend::Flat
+    } else if change > 0.0 {
+        Trend::Rising
+    } else {
+        Trend::Falling
+    };
+
+    Some(SeriesSummary {
+        name: series.name.clone(),
+        count,
+        min,
+        max,
+        mean,
+        last,
+        trend,
+        change,
+    })
+}
+
+/// Up to two decimals, without trailing zeros.
+fn format_value(value: f64) -> String {
+    let formatted = format!("{:.2}", value);
+    let formatted = formatted.trim_end_matches('0').trim_end_matches('.');
+    if formatted == "-0" {
+        "0".to_string()
+    } else {
+        formatted.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::AIBackend;
+
+    type TestChart = Chart<(), AIBackend>;
+
+    fn context() -> Context {
+        Context::default()
+    }
+
+    fn texts(plot: &Plot) -> Vec<&str> {
+        plot.shapes
+            .iter()
+            .filter_map(|shape| match shape {
+                Shape::Text { content, .. } => Some(content.as_str()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_summaries_describe_trends() {
+        let chart = TestChart::with_series(
+            ChartType::Line,
+            vec![
+                Series::new("cpu", [10.0, 20.0, 15.0, 40.0]),
+                Series::new("idle", [5.0, 5.0, 5.0, 5.0]),
+                Series::new("empty", []),
+            ],
+        );
+
+        let summaries = chart.summaries();
+        assert_eq!(summaries.len(), 2);
+        assert_eq!((summaries[0].min, summaries[0].max), (10.0, 40.0));
+        assert_eq!(summaries[0].mean, 21.25);
+        assert_eq!(summaries[0].last, 40.0);
+        assert_eq!(summaries[0].trend, Trend::Rising);
+        assert_eq!(summaries[1].trend, Trend::Flat);
+
+        let description = View::<(), AIBackend>::describe(&chart, &context());
+        let content = description.content.unwrap();
+        assert!(
+            content.contains("cpu (4 points): min 10, max 40, mean 21.25, last 40, trend Rising")
+        );
+        assert!(content.contains("Data: "));
+    }
+
+    #[test]
+    fn test_stacked_bars_share_a_value_axis() {
+        let chart = TestChart::with_series(
+            ChartType::StackedBar,
+            vec![
+                Series::new("a", [30.0, 10.0]),
+                Series::new("b", [50.0, -20.0]),
+            ],
+        )
+        .x_axis(XAxis::Category(vec!["Mon".into(), "Tue".into()]));
+
+        let plot = chart.plot(&context());
+        let labels = texts(&plot);
+        // The stacked total of 80 fits, and so does the negative bar
+        assert!(labels.contains(&"80"));
+        assert!(labels.contains(&"-20"));
+        assert!(labels.contains(&"Mon"));
+
+        let rects = plot
+            .shapes
+            .iter()
+            .filter(|shape| matches!(shape, Shape::Rect { .. }))
+            .count();
+        assert_eq!(rects, 4);
+        assert_eq!(plot.hovers.len(), 2);
+        assert_eq!(plot.hovers[1].tooltip, "Tue\na: 10\nb: -20\nTotal: -10");
+    }
+
+    #[test]
+    fn test_line_hovers_cover_each_x() {
+        let start = DateTime::<Utc>::from_timestamp(1_704_103_200, 0).unwrap();
+        let minute = chrono::Duration::minutes(1);
+        let chart = TestChart::with_series(
+            ChartType::Area,
+            vec![
+                Series::time("rx", (0..3).map(|i| (start + minute * i, i as f64))),
+                Series::time("tx", [(start + minute, 7.0)]),
+            ],
+        )
+        .x_axis(XAxis::Time);
+
+        let plot = chart.plot(&context());
+        assert_eq!(plot.hovers.len(), 3);
+        assert_eq!(plot.hovers[0].top_left.x, 0.0);
+        assert_eq!(plot.hovers[2].bottom_right.x, 1.0);
+        assert_eq!(plot.hovers[1].tooltip, "2024-01-01 10:01:00\nrx: 1\ntx: 7");
+        assert!(plot
+            .shapes
+            .iter()
+            .any(|shape| matches!(shape, Shape::Area { .. })));
+    }
+
+    #[test]
+    fn test_histograms_bin_values() {
+        let chart = TestChart::with_series(
+            ChartType::Histogram,
+            vec![Series::new("latency", [1.0, 2.0, 2.5, 3.0, 9.0])],
+        )
+        .bins(4);
+
+        let histogram = chart.histogram();
+        assert_eq!((histogram.min, histogram.max), (1.0, 9.0));
+        assert_eq!(histogram.counts, vec![vec![3, 1, 0, 1]]);
+
+        let plot = chart.plot(&context());
+        assert_eq!(plot.hovers[0].tooltip, "1 – 3\nlatency: 3");
+    }
+}
@@ -0,0 +1,266 @@
+//! Scales and tick marks for chart axes.
+use chrono::{DateTime, Utc};
+
+/// A linear mapping from a data domain onto `0.0..=1.0`, with the ticks to
+/// label it at.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Scale {
+    pub min: f64,
+    pub max: f64,
+    pub ticks: Vec<Tick>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Tick {
+    pub value: f64,
+    pub label: String,
+}
+
+/// Tick spacings for time axes, in seconds.
+const TIME_STEPS: [f64; 19] = [
+    1.0,
+    5.0,
+    15.0,
+    30.0,
+    60.0,
+    300.0,
+    900.0,
+    1800.0,
+    3600.0,
+    3.0 * 3600.0,
+    6.0 * 3600.0,
+    12.0 * 3600.0,
+    86400.0,
+    2.0 * 86400.0,
+    7.0 * 86400.0,
+    14.0 * 86400.0,
+    30.0 * 86400.0,
+    91.0 * 86400.0,
+    365.0 * 86400.0,
+];
+
+impl Scale {
+    /// Widens `min..max` to the nearest ticks, so the data starts and ends
+    /// on a labelled line. Used for value axes.
+    pub fn nice(min: f64, max: f64, max_ticks: usize) -> Self {
+        let (min, max) = widen(min, max);
+        let step = nice_step(max - min, max_ticks);
+        let min = (min / step).floor() * step;
+        let max = (max / step).ceil() * step;
+
+        Self {
+            min,
+            max,
+            ticks: steps(min, max, step)
+                .map(|value| Tick {
+                    value,
+                    label: format_number(value, step),
+                })
+                .collect(),
+        }
+    }
+
+    /// Keeps `min..max` as it is, with nicely rounded ticks inside it.
+    pub fn linear(min: f64, max: f64, max_ticks: usize) -> Self {
+        let (min, max) = widen(min, max);
+        let step = nice_step(max - min, max_ticks);
+
+        Self {
+            min,
+            max,
+            ticks: steps((min / step).ceil() * step, max, step)
+                .map(|value| Tick {
+                    value,
+                    label: format_number(value, step),
+                })
+                .collect(),
+        }
+    }
+
+    /// Unix timestamps in seconds, ticked at round times in UTC.
+    pub fn time(min: f64, max: f64, max_ticks: usize) -> Self {
+        let (min, max) = widen(min, max);
+        let range = max - min;
+        let step = TIME_STEPS
+            .iter()
+            .copied()
+            .find(|step| range / step <= max_ticks.max(1) as f64)
+            .unwrap_or_else(|| nice_step(range / 86400.0 / 365.0, max_ticks) * 365.0 * 86400.0);
+
+        Self {
+            min,
+            max,
+            ticks: steps((min / step).ceil() * step, max, step)
+                .map(|value| Tick {
+                    value,
+                    label: format_time(value, step),
+                })
+                .collect(),
+        }
+    }
+
+    /// One slot per label, centred on `0, 1, 2…`. With more labels than
+    /// `max_ticks`, only every n-th is shown.
+    pub fn category(labels: &[String], max_ticks: usize) -> Self {
+        let every = labels.len().div_ceil(max_ticks.max(1)).max(1);
+
+        Self {
+            min: -0.5,
+            max: labels.len() as f64 - 0.5,
+            ticks: labels
+                .iter()
+                .enumerate()
+                .step_by(every)
+                .map(|(i, label)| Tick {
+                    value: i as f64,
+                    label: label.clone(),
+                })
+                .collect(),
+        }
+    }
+
+    /// Where `value` falls in the domain, from 0 at `min` to 1 at `max`.
+    pub fn position(&self, value: f64) -> f64 {
+        if self.max == self.min {
+            0.5
+        } else {
+            (value - self.min) / (self.max - self.min)
+        }
+    }
+}
+
+/// Gives an empty domain some room.
+fn widen(min: f64, max: f64) -> (f64, f64) {
+    if !min.is_finite() || !max.is_finite() {
+        return (0.0, 1.0);
+    }
+    if min < max {
+        return (min, max);
+    }
+
+    let margin = if min == 0.0 { 1.0 } else { min.abs() * 0.1 };
+    (min - margin, max + margin)
+}
+
+/// A step of 1, 2, 2.5 or 5 times a power of ten giving at most about
+/// `max_ticks` ticks over `range`.
+fn nice_step(range: f64, max_ticks: usize) -> f64 {
+    let raw = range / max_ticks.max(1) as f64;
+    let magnitude = 10f64.powf(raw.log10().floor());
+    let step = [1.0, 2.0, 2.5, 5.0, 10.0]
+        .into_iter()
+        .find(|nice| raw <= nice * magnitude * (1.0 + 1e-9))
+        .unwrap_or(10.0);
+
+    step * magnitude
+}
+
+fn steps(from: f64, to: f64, step: f64) -> impl Iterator<Item = f64> {
+    let count = ((to - from) / step + 1e-9).floor().max(-1.0) as i64 + 1;
+    (0..count).map(move |i| {
+        let value = from + i as f64 * step;
+        // Avoid labelling -0
+        if value.abs() < step * 1e-9 {
+            0.0
+        } else {
+            value
+        }
+    })
+}
+
+/// Formats `value` with as many decimals as the tick step needs, and
+/// large values with a k/M/G suffix.
+pub fn format_number(value: f64, step: f64) -> String {
+    let (value, step, suffix) = [(1e9, "G"), (1e6, "M"), (1e3, "k")]
+        .into_iter()
+        .find(|(unit, _)| step >= *unit / 10.0 && value.abs() >= *unit)
+        .map(|(unit, suffix)| (value / unit, step / unit, suffix))
+        .unwrap_or((value, step, ""));
+
+    // Enough decimals to tell ticks apart, e.g. two for a step of 0.25
+    let decimals = (0..10)
+        .find(|&d| {
+            let scaled = step * 10f64.powi(d);
+            (scaled - scaled.round()).abs() < 1e-6 * scaled.max(1.0)
+        })
+        .unwrap_or(10) as usize;
+
+    format!("{:.*}{}", decimals, value, suffix)
+}
+
+/// Formats a Unix timestamp at the precision a tick step needs.
+pub fn format_time(seconds: f64, step: f64) -> String {
+    let Some(time) = DateTime::<Utc>::from_timestamp(seconds.floor() as i64, 0) else {
+        return format_number(seconds, step);
+    };
+
+    let format = if step < 60.0 {
+        "%H:%M:%S"
+    } else if step < 86400.0 {
+        "%H:%M"
+    } else if step < 365.0 * 86400.0 {
+        "%b %d"
+    } else {
+        "%Y"
+    };
+    time.format(format).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn labels(scale: &Scale) -> Vec<&str> {
+        scale.ticks.iter().map(|tick| tick.label.as_str()).collect()
+    }
+
+    #[test]
+    fn test_nice_scales_round_outwards() {
+        let scale = Scale::nice(3.0, 97.0, 5);
+        assert_eq!((scale.min, scale.max), (0.0, 100.0));
+        assert_eq!(labels(&scale), ["0", "20", "40", "60", "80", "100"]);
+
+        let scale = Scale::nice(-0.13, 0.42, 6);
+        assert_eq!(
+            labels(&scale),
+            ["-0.2", "-0.1", "0.0", "0.1", "0.2", "0.3", "0.4", "0.5"]
+        );
+
+        let scale = Scale::nice(0.0, 2_400_000.0, 5);
+        assert_eq!(
+            labels(&scale),
+            ["0", "500k", "1.0M", "1.5M", "2.0M", "2.5M"]
+        );
+
+        let flat = Scale::nice(5.0, 5.0, 5);
+        assert!(flat.min < 5.0 && flat.max > 5.0);
+    }
+
+    #[test]
+    fn test_linear_scales_keep_their_domain() {
+        let scale = Scale::linear(1.5, 9.2, 4);
+        assert_eq!((scale.min, scale.max), (1.5, 9.2));
+        assert_eq!(labels(&scale), ["2", "4", "6", "8"]);
+        assert_eq!(scale.position(1.5), 0.0);
+        assert_eq!(scale.position(9.2), 1.0);
+    }
+
+    #[test]
+    fn test_time_scales_tick_at_round_times() {
+        // 2024-01-01 10:03:00 to 10:58:00 UTC
+        let start = 1_704_103_380.0;
+        let scale = Scale::time(start, start + 55.0 * 60.0, 6);
+        assert_eq!(labels(&scale), ["10:15", "10:30", "10:45"]);
+
+        let scale = Scale::time(start, start + 6.0 * 86400.0, 4);
+        assert_eq!(labels(&scale), ["Jan 02", "Jan 04", "Jan 06"]);
+    }
+
+    #[test]
+    fn test_category_scales_thin_out_labels() {
+        let names: Vec<String> = (0..10).map(|i| format!("c{}", i)).collect();
+        let scale = Scale::category(&names, 4);
+        assert_eq!(labels(&scale), ["c0", "c3", "c6", "c9"]);
+        assert_eq!(scale.position(0.0), 0.05);
+    }
+}
@@ -0,0 +1,174 @@
+//! Thinning long series down to what a chart can show.
+//!
+//! Both algorithms work on an index range of anything indexable by position,
+//! like a slice or a [`RingBuffer`](super::ring::RingBuffer), and keep the
+//! original points, so what is drawn is always real data.
+use std::ops::{Index, Range};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, serde::Serialize)]
+pub enum Downsample {
+    /// Every point.
+    None,
+    /// The lowest and highest point of each bucket, so spikes always show.
+    #[default]
+    MinMax,
+    /// Largest-Triangle-Three-Buckets: the point of each bucket that best
+    /// keeps the shape of the line.
+    Lttb,
+}
+
+impl Downsample {
+    /// Up to `max_points` of the points in `range`.
+    pub fn apply<P>(self, points: &P, range: Range<usize>, max_points: usize) -> Vec<(f64, f64)>
+    where
+        P: Index<usize, Output = (f64, f64)> + ?Sized,
+    {
+        match self {
+            Downsample::None => range.map(|i| points[i]).collect(),
+            Downsample::MinMax => min_max(points, range, max_points),
+            Downsample::Lttb => lttb(points, range, max_points),
+        }
+    }
+}
+
+/// Splits `range` into `max_points / 2` buckets and keeps the lowest and
+/// highest point of each, in order. Keeps at least 2 points.
+pub fn min_max<P>(points: &P, range: Range<usize>, max_points: usize) -> Vec<(f64, f64)>
+where
+    P: Index<usize, Output = (f64, f64)> + ?Sized,
+{
+    let max_points = max_points.max(2);
+    if range.len() <= max_points {
+        return range.map(|i| points[i]).collect();
+    }
+
+    let buckets = max_points / 2;
+    let mut sampled = Vec::with_capacity(buckets * 2);
+
+    for bucket in 0..buckets {
+        let bucket = bucket_range(&range, buckets, bucket);
+        let (mut low, mut high) = (bucket.start, bucket.start);
+        for i in bucket {
+            if points[i].1 < points[low].1 {
+                low = i;
+            }
+            if points[i].1 > points[high].1 {
+                high = i;
+            }
+        }
+
+        sampled.push(points[low.min(high)]);
+        if low != high {
+            sampled.push(points[low.max(high)]);
+        }
+    }
+
+    sampled
+}
+
+/// Largest-Triangle-Three-Buckets (Steinarsson, 2013): keeps the first and
+/// last point, and from each bucket in between the one making the largest
+/// triangle with the point kept before it and the average of the next bucket.
+/// Keeps at least 3 points.
+pub fn lttb<P>(points: &P, range: Range<usize>, max_points: usize) -> Vec<(f64, f64)>
+where
+    P: Index<usize, Output = (f64, f64)> + ?Sized,
+{
+    let max_points = max_points.max(3);
+    if range.len() <= max_points {
+        return range.map(|i| points[i]).collect();
+    }
+
+    // The first and last point are their own buckets
+    let inner = range.start + 1..range.end - 1;
+    let buckets = max_points - 2;
+    let mut sampled = Vec::with_capacity(max_points);
+    let mut kept = points[range.start];
+    sampled.push(kept);
+
+    for bucket in 0..buckets {
+        let next = if bucket + 1 < buckets {
+            bucket_range(&inner, buckets, bucket + 1)
+        } else {
+            range.end - 1..range.end
+        };
+        let count = next.len() as f64;
+        let (sum_x, sum_y) = next.fold((0.0, 0.0), |(x, y), i| (x + points[i].0, y + points[i].1));
+        let average = (sum_x / count, sum_y / count);
+
+        let best = bucket_range(&inner, buckets, bucket)
+            .map(|i| points[i])
+            .max_by(|a, b| area(kept, *a, average).total_cmp(&area(kept, *b, average)));
+        if let Some(best) = best {
+            kept = best;
+            sampled.push(best);
+        }
+    }
+
+    sampled.push(points[range.end - 1]);
+    sampled
+}
+
+/// The `bucket`th of `buckets` equal parts of `range`.
+fn bucket_range(range: &Range<usize>, buckets: usize, bucket: usize) -> Range<usize> {
+    let len = range.len();
+    range.start + bucket * len / buckets..range.start + (bucket + 1) * len / buckets
+}
+
+/// Twice the area of the triangle `a b c`.
+fn area(a: (f64, f64), b: (f64, f64), c: (f64, f64)) -> f64 {
+    ((a.0 - c.0) * (b.1 - a.1) - (a.0 - b.0) * (c.1 - a.1)).abs()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn wave(len: usize) -> Vec<(f64, f64)> {
+        (0..len)
+            .map(|i| (i as f64, if i == 500 { 100.0 } else { (i % 7) as f64 }))
+            .collect()
+    }
+
+    #[test]
+    fn test_min_max_keeps_spikes() {
+        let points = wave(1000);
+        let sampled = min_max(&points, 0..points.len(), 100);
+
+        assert!(sampled.len() <= 100);
+        assert!(sampled.contains(&(500.0, 100.0)));
+        assert!(sampled.windows(2).all(|pair| pair[0].0 < pair[1].0));
+
+        // Short series are left alone
+        assert_eq!(min_max(&points, 10..20, 100), points[10..20]);
+    }
+
+    #[test]
+    fn test_lttb_keeps_ends_and_shape() {
+        let points = wave(1000);
+        let sampled = lttb(&points, 0..points.len(), 50);
+
+        assert_eq!(sampled.len(), 50);
+        assert_eq!(sampled[0], points[0]);
+        assert_eq!(sampled[49], points[999]);
+        assert!(sampled.contains(&(500.0, 100.0)));
+        assert!(sampled.windows(2).all(|pair| pair[0].0 < pair[1].0));
+
+        assert_eq!(Downsample::None.apply(&points, 5..8, 2).len(), 3);
+    }
+
+    #[test]
+    fn test_tiny_point_budgets_keep_the_minimum() {
+        let points = wave(1000);
+
+        for max_points in [0, 1] {
+            let sampled = lttb(&points, 0..points.len(), max_points);
+            assert_eq!(sampled.len(), 3);
+            assert_eq!(sampled[0], points[0]);
+            assert_eq!(sampled[2], points[999]);
+
+            assert_eq!(min_max(&points, 0..points.len(), max_points).len(), 2);
+            assert_eq!(lttb(&points, 0..2, max_points), points[0..2]);
+        }
+    }
+}
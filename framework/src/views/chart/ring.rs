@@ -0,0 +1,103 @@
+//! A fixed-capacity buffer for live samples.
+use std::collections::VecDeque;
+use std::ops::Index;
+
+/// Keeps the latest `capacity` items: once full, each push drops the oldest.
+/// Space for all of them is allocated up front, so pushing never allocates.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RingBuffer<T> {
+    items: VecDeque<T>,
+    capacity: usize,
+}
+
+impl<T> RingBuffer<T> {
+    pub fn new(capacity: usize) -> Self {
+        let capacity = capacity.max(1);
+        Self {
+            items: VecDeque::with_capacity(capacity),
+            capacity,
+        }
+    }
+
+    /// Adds `item` as the newest, returning the oldest if it had to go.
+    pub fn push(&mut self, item: T) -> Option<T> {
+        let evicted = if self.items.len() == self.capacity {
+            self.items.pop_front()
+        } else {
+            None
+        };
+        self.items.push_back(item);
+        evicted
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.items.len() == self.capacity
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn clear(&mut self) {
+        self.items.clear();
+    }
+
+    pub fn first(&self) -> Option<&T> {
+        self.items.front()
+    }
+
+    pub fn last(&self) -> Option<&T> {
+        self.items.back()
+    }
+
+    pub fn get(&self, index: usize) -> Option<&T> {
+        self.items.get(index)
+    }
+
+    /// From the oldest item to the newest.
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &T> + ExactSizeIterator {
+        self.items.iter()
+    }
+
+    /// The index of the first item for which `pred` is false, in a buffer
+    /// where it holds for a prefix, e.g. samples older than a time.
+    pub fn partition_point(&self, pred: impl FnMut(&T) -> bool) -> usize {
+        self.items.partition_point(pred)
+    }
+}
+
+impl<T> Index<usize> for RingBuffer<T> {
+    type Output = T;
+
+    fn index(&self, index: usize) -> &T {
+        &self.items[index]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ring_buffers_drop_the_oldest() {
+        let mut buffer = RingBuffer::new(3);
+        assert_eq!(buffer.push(1), None);
+        assert_eq!(buffer.push(2), None);
+        assert_eq!(buffer.push(3), None);
+        assert!(buffer.is_full());
+
+        assert_eq!(buffer.push(4), Some(1));
+        assert_eq!(buffer.iter().copied().collect::<Vec<_>>(), [2, 3, 4]);
+        assert_eq!((buffer[0], buffer.last()), (2, Some(&4)));
+        assert_eq!(buffer.partition_point(|&item| item < 4), 2);
+        assert_eq!(buffer.capacity(), 3);
+    }
+}
@@ -0,0 +1,535 @@
+//! Charts of live data, like telemetry sampled every few seconds.
+//!
+//! Samples go into a [`TimeSeries`] kept in app state, which holds a fixed
+//! number of them in a [`RingBuffer`]. A [`StreamingChart`] shows the latest
+//! window of one or more series, scrolling along as samples arrive, with long
+//! histories downsampled to about as many points as it can draw. Its plot
+//! carries a revision, so the canvas only redraws the lines when there is new
+//! data, and it pauses while hovered so values can be read.
+use super::axis::Scale;
+use super::downsample::Downsample;
+use super::ring::RingBuffer;
+use super::{axes, format_value, legend, summarize, Series, Y_TICKS};
+use crate::backend::{Plot, Shape};
+use crate::prelude::*;
+use chrono::{DateTime, Utc};
+use std::borrow::Cow;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::sync::Arc;
+use std::time::Duration;
+
+const X_TICKS: usize = 6;
+
+/// Hover bands across the window.
+const HOVER_BANDS: usize = 60;
+
+/// The latest samples of a value over time.
+///
+/// Cloning is cheap: the samples are shared until the next push, which only
+/// copies them while a clone, e.g. a chart being drawn, still holds them.
+#[derive(Debug, Clone)]
+pub struct TimeSeries {
+    pub name: String,
+    /// Overrides the chart's palette.
+    pub color: Option<Color>,
+    samples: Arc<RingBuffer<(f64, f64)>>,
+    revision: u64,
+}
+
+impl TimeSeries {
+    /// Keeps the latest `capacity` samples, e.g. 1800 for an hour of
+    /// samples every two seconds.
+    pub fn new(name: impl Into<String>, capacity: usize) -> Self {
+        Self {
+            name: name.into(),
+            color: None,
+            samples: Arc::new(RingBuffer::new(capacity)),
+            revision: 0,
+        }
+    }
+
+    pub fn color(mut self, color: Color) -> Self {
+        self.color = Some(color);
+        self
+    }
+
+    pub fn push(&mut self, time: DateTime<Utc>, value: f64) {
+        self.push_at(time.timestamp_millis() as f64 / 1000.0, value);
+    }
+
+    /// Adds a sample at a Unix timestamp in seconds. Samples older than the
+    /// latest one, and values that aren't finite, are dropped.
+    pub fn push_at(&mut self, seconds: f64, value: f64) {
+        if !seconds.is_finite() || !value.is_finite() {
+            return;
+        }
+        if self.latest().is_some_and(|(latest, _)| seconds < latest) {
+            return;
+        }
+
+        Arc::make_mut(&mut self.samples).push((seconds, value));
+        self.revision = self.revision.wrapping_add(1);
+    }
+
+    pub fn clear(&mut self) {
+        Arc::make_mut(&mut self.samples).clear();
+        self.revision = self.revision.wrapping_add(1);
+    }
+
+    pub fn latest(&self) -> Option<(f64, f64)> {
+        self.samples.last().copied()
+    }
+
+    /// `(seconds, value)` samples, oldest first.
+    pub fn samples(&self) -> &RingBuffer<(f64, f64)> {
+        &self.samples
+    }
+
+    pub fn len(&self) -> usize {
+        self.samples.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.samples.is_empty()
+    }
+
+    /// Changes with every sample.
+    pub fn revision(&self) -> u64 {
+        self.revision
+    }
+
+    /// The samples from `start` to `end`, downsampled to `max_points`.
+    fn window(
+        &self,
+        start: f64,
+        end: f64,
+        downsample: Downsample,
+        max_points: usize,
+    ) -> Vec<(f64, f64)> {
+        let from = self.samples.partition_point(|sample| sample.0 < start);
+        let to = self.samples.partition_point(|sample| sample.0 <= end);
+        downsample.apply(&*self.samples, from..to.max(from), max_points)
+    }
+}
+
+/// A line chart of the latest `window` of some [`TimeSeries`].
+pub struct StreamingChart<Message: 'static + Send + Sync, B: Backend = IcedBackend> {
+    pub series: Vec<TimeSeries>,
+    pub window: Duration,
+    /// Where the window ends; by default at the latest sample.
+    pub end: Option<DateTime<Utc>>,
+    pub downsample: Downsample,
+    /// Most points drawn per series.
+    pub resolution: usize,
+    /// A fixed value axis; by default it fits the visible samples.
+    pub range: Option<(f64, f64)>,
+    pub title: Option<String>,
+    pub palette: Vec<Color>,
+    pub width: Length,
+    pub height: Length,
+    pub show_grid: bool,
+    /// Defaults to showing a legend for more than one series.
+    pub show_legend: Option<bool>,
+    pub pause_on_hover: bool,
+    _phantom: std::marker::PhantomData<(Message, B)>,
+}
+
+impl<Message: 'static + Send + Sync, B: Backend> StreamingChart<Message, B> {
+    pub fn new(window: Duration) -> Self {
+        Self {
+            series: Vec::new(),
+            window,
+            end: None,
+            downsample: Downsample::default(),
+            resolution: 400,
+            range: None,
+            title: None,
+            palette: Vec::new(),
+            width: Length::Fill,
+            height: Length::Fixed(240.0),
+            show_grid: true,
+            show_legend: None,
+            pause_on_hover: true,
+            _phantom: std::marker::PhantomData,
+        }
+    }
+
+    pub fn series(mut self, series: TimeSeries) -> Self {
+        self.series.push(series);
+        self
+    }
+
+    pub fn until(mut self, end: DateTime<Utc>) -> Self {
+        self.end = Some(end);
+        self
+    }
+
+    pub fn downsample(mut self, downsample: Downsample) -> Self {
+        self.downsample = downsample;
+        self
+    }
+
+    pub fn resolution(mut self, points: usize) -> Self {
+        self.resolution = points.max(4);
+        self
+    }
+
+    pub fn range(mut self, min: f64, max: f64) -> Self {
+        self.range = Some((min, max));
+        self
+    }
+
+    pub fn title(mut self, title: impl Into<String>) -> Self {
+        self.title = Some(title.into());
+        self
+    }
+
+    pub fn palette(mut self, palette: Vec<Color>) -> Self {
+        self.palette = palette;
+        self
+    }
+
+    pub fn width(mut self, width: Length) -> Self {
+        self.width = width;
+        self
+    }
+
+    pub fn height(mut self, height: Length) -> Self {
+        self.height = height;
+        self
+    }
+
+    pub fn grid(mut self, show: bool) -> Self {
+        self.show_grid = show;
+        self
+    }
+
+    pub fn legend(mut self, show: bool) -> Self {
+        self.show_legend = Some(show);
+        self
+    }
+
+    pub fn pause_on_hover(mut self, pause: bool) -> Self {
+        self.pause_on_hover = pause;
+        self
+    }
+
+    /// The visible window, in Unix seconds.
+    fn bounds(&self) -> (f64, f64) {
+        let end = match self.end {
+            Some(end) => end.timestamp_millis() as f64 / 1000.0,
+            None => self
+                .series
+                .iter()
+                .filter_map(|series| series.latest())
+                .map(|(time, _)| time)
+                .fold(f64::NEG_INFINITY, f64::max),
+        };
+        let end = if end.is_finite() { end } else { 0.0 };
+
+        (end - self.window.as_secs_f64().max(1.0), end)
+    }
+
+    fn series_color(&self, index: usize, context: &Context) -> Color {
+        if let Some(color) = self.series.get(index).and_then(|s| s.color) {
+            return color;
+        }
+        if !self.palette.is_empty() {
+            return self.palette[index % self.palette.len()];
+        }
+
+        let colors = context.theme.colors;
+        [
+            colors.primary,
+            colors.accent,
+            colors.warning,
+            colors.info,
+            colors.danger,
+            colors.success,
+        ][index % 6]
+    }
+
+    /// Identifies everything the plot's shapes are made from.
+    fn revision(&self, start: f64, end: f64, context: &Context) -> u64 {
+        let colors = context.theme.colors;
+        let mut hasher = DefaultHasher::new();
+
+        (
+            start.to_bits(),
+            end.to_bits(),
+            self.downsample,
+            self.resolution,
+        )
+            .hash(&mut hasher);
+        self.range
+            .map(|(min, max)| (min.to_bits(), max.to_bits()))
+            .hash(&mut hasher);
+        self.show_grid.hash(&mut hasher);
+        for (i, series) in self.series.iter().enumerate() {
+            (&series.name, series.revision).hash(&mut hasher);
+            self.series_color(i, context).into_rgba8().hash(&mut hasher);
+        }
+        for color in [colors.text_secondary, colors.divider, colors.border] {
+            color.into_rgba8().hash(&mut hasher);
+        }
+
+        hasher.finish()
+    }
+
+    fn plot(&self, context: &Context) -> Plot {
+        let (start, end) = self.bounds();
+        let windows: Vec<Vec<(f64, f64)>> = self
+            .series
+            .iter()
+            .map(|series| series.window(start, end, self.downsample, self.resolution))
+            .collect();
+
+        let x = Scale::time(start, end, X_TICKS);
+        let y = match self.range {
+            Some((min, max)) => Scale::nice(min, max, Y_TICKS),
+            None => {
+                let (min, max) = super::bounds(windows.iter().flatten().map(|&(_, value)| value));
+                Scale::nice(min, max, Y_TICKS)
+            }
+        };
+        let to_point =
+            |px: f64, py: f64| Point::new(x.position(px) as f32, 1.0 - y.position(py) as f32);
+
+        let colors = context.theme.colors;
+        let (plot, _) = axes(&x, &y, self.show_grid, self.show_grid, &colors);
+        let mut plot = plot
+            .revision(self.revision(start, end, context))
+            .pause_on_hover(self.pause_on_hover);
+
+        for (i, window) in windows.iter().enumerate() {
+            let color = self.series_color(i, context);
+            let points: Vec<Point> = window.iter().map(|&(px, py)| to_point(px, py)).collect();
+
+            match points.as_slice() {
+                [] => {}
+                [center] => plot.push(Shape::Dot {
+                    center: *center,
+                    radius: 3.0,
+                    color,
+                }),
+                _ => plot.push(Shape::Line {
+                    points,
+                    color,
+                    width: 2.0,
+                }),
+            }
+        }
+
+        // Each band shows the latest value of each series within it
+        let band = (end - start) / HOVER_BANDS as f64;
+        for i in 0..HOVER_BANDS {
+            let (from, to) = (start + band * i as f64, start + band * (i + 1) as f64);
+            let mut time = None;
+            let mut lines = Vec::new();
+            let last_band = i + 1 == HOVER_BANDS;
+
+            for (series, window) in self.series.iter().zip(&windows) {
+                let last =
+                    window.partition_point(|sample| sample.0 < to || (last_band && sample.0 == to));
+                if let Some(&(at, value)) = window[..last].last().filter(|s| s.0 >= from) {
+                    time = Some(time.map_or(at, |time: f64| time.max(at)));
+                    lines.push(format!("{}: {}", series.name, format_value(value)));
+                }
+            }
+
+            if let Some(time) = time {
+                let header = DateTime::<Utc>::from_timestamp(time.floor() as i64, 0)
+                    .map(|time| time.format("%H:%M:%S").to_string())
+                    .unwrap_or_default();
+                lines.insert(0, header);
+                plot.hover(
+                    Point::new(x.position(from) as f32, 0.0),
+                    Point::new(x.position(to) as f32, 1.0),
+                    lines.join("\n"),
+                );
+            }
+        }
+
+        plot
+    }
+}
+
+impl<Message: Clone + Send + Sync + 'static, B: Backend> View<Message, B>
+    for StreamingChart<Message, B>
+{
+    fn view(&self, context: &Context) -> B::AnyView<Message> {
+        let mut children = Vec::new();
+
+        if let Some(title) = &self.title {
+            children.push(B::text(
+                title.clone(),
+                14.0,
+                None,
+                true,
+                false,
+                None,
+                None,
+                Length::Shrink,
+                Alignment::Start,
+                context,
+            ));
+        }
+
+        children.push(B::plot(
+            self.plot(context),
+            Length::Fill,
+            Length::Fill,
+            context,
+        ));
+
+        if self.show_legend.unwrap_or(self.series.len() > 1) {
+            let entries = self
+                .series
+                .iter()
+                .enumerate()
+                .map(|(i, series)| (series.name.clone(), self.series_color(i, context)));
+            children.push(legend::<Message, B>(entries, context));
+        }
+
+        B::vstack(
+            children,
+            16.0,
+            Padding::ZERO,
+            self.width,
+            self.height,
+            Alignment::Start,
+            Alignment::Start,
+            context,
+        )
+    }
+
+    fn describe(&self, _context: &Context) -> SemanticNode {
+        let mut node = SemanticNode::new("chart");
+        node.label = self.title.as_ref().map(|t| Cow::Owned(t.to_string()));
+
+        // What is on screen, not the whole history
+        let (start, end) = self.bounds();
+        let mut lines = vec![format!(
+            "Type: Live, Window: last {}s",
+            format_value(end - start)
+        )];
+        for series in &self.series {
+            let window = series.window(start, end, Downsample::None, 0);
+            if let Some(summary) = summarize(&Series::xy(series.name.clone(), window)) {
+                lines.push(format!(
+                    "{} ({} samples): min {}, max {}, mean {}, last {}, trend {:?}",
+                    summary.name,
+                    summary.count,
+                    format_value(summary.min),
+                    format_value(summary.max),
+                    format_value(summary.mean),
+                    format_value(summary.last),
+                    summary.trend,
+                ));
+            }
+        }
+
+        node.content = Some(Cow::Owned(lines.join("\n")));
+        node
+    }
+
+    fn describe_iced(&self, context: &Context) -> SemanticNode {
+        self.describe(context)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::AIBackend;
+
+    type TestChart = StreamingChart<(), AIBackend>;
+
+    /// One sample a second from 2024-01-01 10:00:00 UTC.
+    fn series(name: &str, capacity: usize, values: impl IntoIterator<Item = f64>) -> TimeSeries {
+        let mut series = TimeSeries::new(name, capacity);
+        for (i, value) in values.into_iter().enumerate() {
+            series.push_at(1_704_103_200.0 + i as f64, value);
+        }
+        series
+    }
+
+    fn lines(plot: &Plot) -> Vec<&Vec<Point>> {
+        plot.shapes
+            .iter()
+            .filter_map(|shape| match shape {
+                Shape::Line { points, width, .. } if *width == 2.0 => Some(points),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_time_series_keep_the_latest_samples() {
+        let mut cpu = series("cpu", 3, [1.0, 2.0, 3.0, 4.0]);
+        assert_eq!(cpu.len(), 3);
+        assert_eq!(cpu.samples().first(), Some(&(1_704_103_201.0, 2.0)));
+        assert_eq!(cpu.revision(), 4);
+
+        // A chart holding a clone keeps its samples
+        let shown = cpu.clone();
+        cpu.push_at(1_704_103_210.0, 5.0);
+        cpu.push_at(1_704_103_205.0, 6.0);
+        cpu.push_at(1_704_103_211.0, f64::NAN);
+        assert_eq!(cpu.latest(), Some((1_704_103_210.0, 5.0)));
+        assert_eq!(cpu.revision(), 5);
+        assert_eq!(shown.latest(), Some((1_704_103_203.0, 4.0)));
+    }
+
+    #[test]
+    fn test_windows_scroll_with_new_samples() {
+        let context = Context::default();
+        let mut cpu = series("cpu", 600, (0..120).map(|i| i as f64));
+
+        let chart = TestChart::new(Duration::from_secs(60)).series(cpu.clone());
+        let plot = chart.plot(&context);
+        let line = lines(&plot)[0];
+        // The last minute, ending at the right edge
+        assert_eq!(line.len(), 61);
+        assert_eq!(line[0].x, 0.0);
+        assert_eq!(line[60].x, 1.0);
+        assert!(plot.pause_on_hover);
+
+        // Nothing new, nothing to redraw
+        let again = TestChart::new(Duration::from_secs(60)).series(cpu.clone());
+        assert_eq!(again.plot(&context).revision, plot.revision);
+
+        cpu.push_at(1_704_103_200.0 + 120.0, 120.0);
+        let scrolled = TestChart::new(Duration::from_secs(60)).series(cpu);
+        let scrolled = scrolled.plot(&context);
+        assert_ne!(scrolled.revision, plot.revision);
+        assert_eq!(lines(&scrolled)[0].len(), 61);
+        assert!(scrolled
+            .hovers
+            .last()
+            .unwrap()
+            .tooltip
+            .ends_with("10:02:00\ncpu: 120"));
+    }
+
+    #[test]
+    fn test_long_histories_are_downsampled() {
+        let context = Context::default();
+        let load = series("load", 3600, (0..3600).map(|i| (i % 10) as f64));
+
+        for downsample in [Downsample::MinMax, Downsample::Lttb] {
+            let chart = TestChart::new(Duration::from_secs(3600))
+                .series(load.clone())
+                .downsample(downsample)
+                .resolution(200);
+            assert!(lines(&chart.plot(&context))[0].len() <= 200);
+        }
+
+        let description = View::<(), AIBackend>::describe(
+            &TestChart::new(Duration::from_secs(60)).series(load),
+            &context,
+        );
+        let content = description.content.unwrap();
+        assert!(content.contains("load (61 samples): min 0, max 9"));
+    }
+}
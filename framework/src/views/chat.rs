@@ -15,6 +15,19 @@ use std::sync::Arc;
 pub struct ChatMessage {
     pub role: ChatRole,
     pub content: String,
+    /// Memory records the reply was grounded on, in the order they were cited.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub citations: Vec<ChatCitation>,
+}
+
+/// A reference from an assistant reply back to the `SemanticRecord` it used.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct ChatCitation {
+    /// 1-based marker used inline in the reply, e.g. `[2]`.
+    pub index: usize,
+    pub record_id: String,
+    pub collection: String,
+    pub excerpt: String,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
@@ -24,11 +37,43 @@ pub enum ChatRole {
     System,
 }
 
+/// A saved conversation as listed in the chat history sidebar.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct ChatSummary {
+    pub id: String,
+    pub title: Option<String>,
+}
+
+impl ChatSummary {
+    pub fn display_title(&self) -> &str {
+        self.title.as_deref().unwrap_or("New Chat")
+    }
+}
+
+/// State of the chat history sidebar.
+#[derive(Debug, Clone, Default)]
+pub struct ChatHistory {
+    pub conversations: Vec<ChatSummary>,
+    pub active: Option<String>,
+    /// Conversation being renamed and the title typed so far.
+    pub renaming: Option<(String, String)>,
+    pub is_open: bool,
+}
+
 #[derive(Debug, Clone)]
 pub enum ChatViewMessage {
     InputChanged(String),
     SendPressed,
     CopyCode(String),
+    OpenLink(String),
+    OpenCitation(ChatCitation),
+    ToggleHistory,
+    NewConversation,
+    OpenConversation(String),
+    StartRename(String),
+    RenameChanged(String),
+    CommitRename,
+    DeleteConversation(String),
 }
 
 pub struct AIChatView<Message: Clone + Send + Sync + 'static> {
@@ -44,6 +89,10 @@ pub struct AIChatView<Message: Clone + Send + Sync + 'static> {
     #[allow(dead_code)]
     #[cfg(feature = "intelligence")]
     on_action: Arc<dyn Fn(ChatViewMessage) -> Message + Send + Sync>,
+    #[allow(dead_code)]
+    history: Option<ChatHistory>,
+    #[allow(dead_code)]
+    notice: Option<String>,
     _phantom: PhantomData<Message>,
 }
 
@@ -66,9 +115,25 @@ impl<Message: Clone + Send + Sync + 'static> AIChatView<Message> {
             is_thinking,
             #[cfg(feature = "intelligence")]
             on_action: Arc::new(on_action),
+            history: None,
+            notice: None,
             _phantom: PhantomData,
         }
     }
+
+    /// Shows a header with saved conversations that can be opened, renamed
+    /// and deleted.
+    pub fn history(mut self, history: ChatHistory) -> Self {
+        self.history = Some(history);
+        self
+    }
+
+    /// Shows a caption above the input, e.g. when older messages were
+    /// summarized to fit the model's context window.
+    pub fn notice(mut self, notice: Option<String>) -> Self {
+        self.notice = notice;
+        self
+    }
 }
 
 // Consolidate backend implementations into one generic implementation
@@ -201,11 +266,45 @@ impl<Message: Clone + Send + Sync + 'static, B: Backend> View<Message, B> for AI
 
         let divider = B::divider(context);
 
+        let mut input_rows = Vec::new();
+        if let Some(notice) = &self.notice {
+            let t = context.theme;
+            input_rows.push(B::hstack(
+                vec![
+                    crate::elements::atoms::Icon::<B>::new("info")
+                        .size(12.0)
+                        .color(t.colors.text_secondary)
+                        .view(context),
+                    B::text(
+                        notice.clone(),
+                        11.0,
+                        Some(t.colors.text_secondary),
+                        false,
+                        false,
+                        None,
+                        None,
+                        Length::Fill,
+                        iced::Alignment::Start,
+                        context,
+                    ),
+                ],
+                6.0,
+                Padding::ZERO,
+                Length::Fill,
+                Length::Shrink,
+                iced::Alignment::Start,
+                iced::Alignment::Center,
+                context,
+            ));
+        }
+        input_rows.push(input_row_1);
+        input_rows.push(input_row_2_fixed);
+
         let input_area = B::vstack(
             vec![
                 divider,
                 B::vstack(
-                    vec![input_row_1, input_row_2_fixed],
+                    input_rows,
                     8.0,
                     Padding::from(12),
                     Length::Fill,
@@ -225,7 +324,7 @@ impl<Message: Clone + Send + Sync + 'static, B: Backend> View<Message, B> for AI
         );
 
         // 3. Combine
-        B::vstack(
+        let conversation = B::vstack(
             vec![scroll, input_area],
             0.0,
             Padding::ZERO,
@@ -234,11 +333,284 @@ impl<Message: Clone + Send + Sync + 'static, B: Backend> View<Message, B> for AI
             iced::Alignment::Start,
             iced::Alignment::Start,
             context,
+        );
+
+        let Some(history) = &self.history else {
+            return conversation;
+        };
+
+        // 4. History header and sidebar
+        let active_title = history
+            .active
+            .as_ref()
+            .and_then(|id| history.conversations.iter().find(|c| &c.id == id))
+            .map(|c| c.display_title().to_string())
+            .unwrap_or_else(|| "New Chat".to_string());
+
+        let header = B::hstack(
+            vec![
+                header_button::<Message, B>(
+                    "history",
+                    "Conversations",
+                    (on_action)(ChatViewMessage::ToggleHistory),
+                    context,
+                ),
+                B::text(
+                    active_title,
+                    13.0,
+                    Some(context.theme.colors.text_primary),
+                    true,
+                    false,
+                    None,
+                    None,
+                    Length::Fill,
+                    iced::Alignment::Start,
+                    context,
+                ),
+                header_button::<Message, B>(
+                    "plus",
+                    "New conversation",
+                    (on_action)(ChatViewMessage::NewConversation),
+                    context,
+                ),
+            ],
+            8.0,
+            Padding::from([8, 12]),
+            Length::Fill,
+            Length::Shrink,
+            iced::Alignment::Start,
+            iced::Alignment::Center,
+            context,
+        );
+
+        let body = if history.is_open {
+            B::hstack(
+                vec![
+                    View::<Message, B>::view(
+                        &ConversationList::new(history.clone(), self.on_action.clone()),
+                        context,
+                    ),
+                    conversation,
+                ],
+                0.0,
+                Padding::ZERO,
+                Length::Fill,
+                Length::Fill,
+                iced::Alignment::Start,
+                iced::Alignment::Start,
+                context,
+            )
+        } else {
+            conversation
+        };
+
+        B::vstack(
+            vec![header, B::divider(context), body],
+            0.0,
+            Padding::ZERO,
+            Length::Fill,
+            Length::Fill,
+            iced::Alignment::Start,
+            iced::Alignment::Start,
+            context,
+        )
+    }
+
+    fn describe(&self, _context: &Context) -> SemanticNode {
+        let node = SemanticNode::new("ai_chat").with_label("AI Assistant");
+
+        match &self.history {
+            Some(history) => node.extend_children(history.conversations.iter().map(|c| {
+                let conversation =
+                    SemanticNode::new("conversation").with_label(c.display_title().to_string());
+                if history.active.as_ref() == Some(&c.id) {
+                    conversation.with_content("active")
+                } else {
+                    conversation
+                }
+            })),
+            None => node,
+        }
+    }
+}
+
+#[cfg(feature = "intelligence")]
+fn header_button<Message: Clone + Send + Sync + 'static, B: Backend>(
+    icon: &'static str,
+    tooltip: &str,
+    on_press: Message,
+    context: &Context,
+) -> B::AnyView<Message> {
+    B::with_tooltip(
+        B::button(
+            crate::elements::atoms::Icon::<B>::new(icon).view(context),
+            Some(on_press),
+            Variant::Ghost,
+            Intent::Neutral,
+            Length::Shrink,
+            Length::Shrink,
+            true,
+            context,
+        ),
+        tooltip.into(),
+        context,
+    )
+}
+
+#[cfg(feature = "intelligence")]
+struct ConversationList<Message> {
+    history: ChatHistory,
+    on_action: Arc<dyn Fn(ChatViewMessage) -> Message + Send + Sync>,
+}
+
+#[cfg(feature = "intelligence")]
+impl<Message> ConversationList<Message> {
+    fn new(
+        history: ChatHistory,
+        on_action: Arc<dyn Fn(ChatViewMessage) -> Message + Send + Sync>,
+    ) -> Self {
+        Self { history, on_action }
+    }
+}
+
+#[cfg(feature = "intelligence")]
+impl<Message: Clone + Send + Sync + 'static, B: Backend> View<Message, B>
+    for ConversationList<Message>
+{
+    fn view(&self, context: &Context) -> B::AnyView<Message> {
+        let t = context.theme;
+        let on_action = &self.on_action;
+
+        let rows = self
+            .history
+            .conversations
+            .iter()
+            .map(|conversation| {
+                let is_active = self.history.active.as_ref() == Some(&conversation.id);
+
+                if let Some((_, title)) = self
+                    .history
+                    .renaming
+                    .as_ref()
+                    .filter(|(id, _)| id == &conversation.id)
+                {
+                    return B::text_input(
+                        title.clone(),
+                        "Conversation title".to_string(),
+                        {
+                            let act = self.on_action.clone();
+                            move |s| (act)(ChatViewMessage::RenameChanged(s))
+                        },
+                        Some((on_action)(ChatViewMessage::CommitRename)),
+                        None,
+                        false,
+                        Variant::Outline,
+                        Some(iced::widget::Id::new("chat_rename_input")),
+                        context,
+                    );
+                }
+
+                B::hstack(
+                    vec![
+                        B::button(
+                            B::sidebar_item(
+                                conversation.display_title().to_string(),
+                                "message-square".to_string(),
+                                is_active,
+                                context,
+                            ),
+                            Some((on_action)(ChatViewMessage::OpenConversation(
+                                conversation.id.clone(),
+                            ))),
+                            Variant::Ghost,
+                            Intent::Neutral,
+                            Length::Fill,
+                            Length::Shrink,
+                            true,
+                            context,
+                        ),
+                        header_button::<Message, B>(
+                            "pencil",
+                            "Rename",
+                            (on_action)(ChatViewMessage::StartRename(conversation.id.clone())),
+                            context,
+                        ),
+                        header_button::<Message, B>(
+                            "trash-2",
+                            "Delete",
+                            (on_action)(ChatViewMessage::DeleteConversation(
+                                conversation.id.clone(),
+                            )),
+                            context,
+                        ),
+                    ],
+                    2.0,
+                    Padding::ZERO,
+                    Length::Fill,
+                    Length::Shrink,
+                    iced::Alignment::Start,
+                    iced::Alignment::Center,
+                    context,
+                )
+            })
+            .collect::<Vec<_>>();
+
+        let list = if rows.is_empty() {
+            B::text(
+                "No saved conversations yet".to_string(),
+                11.0,
+                Some(t.colors.text_secondary),
+                false,
+                true,
+                None,
+                None,
+                Length::Fill,
+                iced::Alignment::Center,
+                context,
+            )
+        } else {
+            B::vstack(
+                rows,
+                4.0,
+                Padding::ZERO,
+                Length::Fill,
+                Length::Shrink,
+                iced::Alignment::Start,
+                iced::Alignment::Start,
+                context,
+            )
+        };
+
+        B::container(
+            B::scroll_view(
+                list,
+                Length::Fill,
+                Length::Fill,
+                Some("chat_history_scroll"),
+                false,
+                ScrollDirection::Vertical,
+                context,
+            ),
+            Padding::from(8),
+            Length::Fixed(200.0),
+            Length::Fill,
+            Some(t.colors.surface_variant),
+            0.0,
+            0.0,
+            None,
+            None,
+            iced::Alignment::Start,
+            iced::Alignment::Start,
+            context,
         )
     }
 
     fn describe(&self, _context: &Context) -> SemanticNode {
-        SemanticNode::new("ai_chat").with_label("AI Assistant")
+        SemanticNode::new("chat_history")
+            .with_label("Conversations")
+            .extend_children(self.history.conversations.iter().map(|c| {
+                SemanticNode::new("conversation").with_label(c.display_title().to_string())
+            }))
     }
 }
 
@@ -408,11 +780,13 @@ impl<Message: Clone + Send + Sync + 'static, B: Backend> View<Message, B> for Ch
             for part in parts {
                 match part {
                     ContentPart::Text(text) => {
-                        let on_act_inner = on_act.clone();
+                        let on_copy = on_act.clone();
+                        let on_link = on_act.clone();
                         let content = MarkdownView::new(text)
                             .size(12.0)
                             .padding(Padding::ZERO)
-                            .on_copy(move |code| (on_act_inner)(ChatViewMessage::CopyCode(code)));
+                            .on_copy(move |code| (on_copy)(ChatViewMessage::CopyCode(code)))
+                            .on_link(move |url| (on_link)(ChatViewMessage::OpenLink(url)));
                         assistant_children.push(View::<Message, B>::view(&content, context));
                     }
                     ContentPart::Action(action) => {
@@ -428,6 +802,13 @@ impl<Message: Clone + Send + Sync + 'static, B: Backend> View<Message, B> for Ch
                 }
             }
 
+            if !self.message.citations.is_empty() {
+                assistant_children.push(View::<Message, B>::view(
+                    &CitationList::new(self.message.citations.clone(), self.on_action.clone()),
+                    context,
+                ));
+            }
+
             let assistant_col = B::vstack(
                 assistant_children,
                 12.0,
@@ -457,7 +838,137 @@ impl<Message: Clone + Send + Sync + 'static, B: Backend> View<Message, B> for Ch
     }
 
     fn describe(&self, _context: &Context) -> SemanticNode {
-        SemanticNode::new("chat_bubble").with_content(self.message.content.clone())
+        SemanticNode::new("chat_bubble")
+            .with_content(self.message.content.clone())
+            .extend_children(self.message.citations.iter().map(|citation| {
+                SemanticNode::new("citation")
+                    .with_label(format!("[{}] {}", citation.index, citation.collection))
+                    .with_content(citation.excerpt.clone())
+            }))
+    }
+}
+
+#[cfg(feature = "intelligence")]
+struct CitationList<Message> {
+    citations: Vec<ChatCitation>,
+    on_action: Arc<dyn Fn(ChatViewMessage) -> Message + Send + Sync>,
+}
+
+#[cfg(feature = "intelligence")]
+impl<Message> CitationList<Message> {
+    fn new(
+        citations: Vec<ChatCitation>,
+        on_action: Arc<dyn Fn(ChatViewMessage) -> Message + Send + Sync>,
+    ) -> Self {
+        Self {
+            citations,
+            on_action,
+        }
+    }
+}
+
+#[cfg(feature = "intelligence")]
+impl<Message: Clone + Send + Sync + 'static, B: Backend> View<Message, B>
+    for CitationList<Message>
+{
+    fn view(&self, context: &Context) -> B::AnyView<Message> {
+        let t = context.theme;
+
+        let chips = self
+            .citations
+            .iter()
+            .map(|citation| {
+                let label = B::hstack(
+                    vec![
+                        B::icon(
+                            "database".to_string(),
+                            10.0,
+                            Some(t.colors.primary),
+                            context,
+                        ),
+                        B::text(
+                            format!("[{}] {}", citation.index, citation.excerpt),
+                            10.0,
+                            Some(t.colors.text_secondary),
+                            false,
+                            false,
+                            None,
+                            None,
+                            Length::Shrink,
+                            iced::Alignment::Start,
+                            context,
+                        ),
+                    ],
+                    6.0,
+                    Padding::ZERO,
+                    Length::Shrink,
+                    Length::Shrink,
+                    iced::Alignment::Start,
+                    iced::Alignment::Center,
+                    context,
+                );
+
+                B::with_tooltip(
+                    B::button(
+                        label,
+                        Some((self.on_action)(ChatViewMessage::OpenCitation(
+                            citation.clone(),
+                        ))),
+                        Variant::Soft,
+                        Intent::Neutral,
+                        Length::Shrink,
+                        Length::Shrink,
+                        true,
+                        context,
+                    ),
+                    format!("Open {} record", citation.collection).into(),
+                    context,
+                )
+            })
+            .collect();
+
+        B::vstack(
+            vec![
+                B::text(
+                    "Sources".to_string(),
+                    10.0,
+                    Some(t.colors.text_secondary),
+                    true,
+                    false,
+                    None,
+                    None,
+                    Length::Shrink,
+                    iced::Alignment::Start,
+                    context,
+                ),
+                B::wrap(
+                    chips,
+                    6.0,
+                    6.0,
+                    Padding::ZERO,
+                    Length::Fill,
+                    Length::Shrink,
+                    iced::Alignment::Start,
+                    iced::Alignment::Start,
+                    context,
+                ),
+            ],
+            6.0,
+            Padding::ZERO,
+            Length::Fill,
+            Length::Shrink,
+            iced::Alignment::Start,
+            iced::Alignment::Start,
+            context,
+        )
+    }
+
+    fn describe(&self, _context: &Context) -> SemanticNode {
+        SemanticNode::new("citations").extend_children(self.citations.iter().map(|citation| {
+            SemanticNode::new("citation")
+                .with_label(format!("[{}] {}", citation.index, citation.collection))
+                .with_content(citation.excerpt.clone())
+        }))
     }
 }
 
@@ -483,7 +994,7 @@ impl<Message: Clone + Send + Sync + 'static, B: Backend> View<Message, B> for To
             Action::SetThemeKind(_) | Action::SetThemeTone(_) => "palette",
             Action::SetButtonVariant(_) | Action::SetButtonIntent(_) => "mouse-pointer",
             Action::SetLabMode(_) => "flask-conical",
-            Action::Shell(_) => "terminal",
+            Action::Shell(_) | Action::RunCommand { .. } => "terminal",
             Action::Memorize(_) => "database",
             Action::Teleport { .. } => "move",
             Action::Scale { .. } => "maximize",
@@ -491,6 +1002,7 @@ impl<Message: Clone + Send + Sync + 'static, B: Backend> View<Message, B> for To
             Action::WebSearch(_) => "search",
             Action::ReadFile(_) => "file-text",
             Action::WriteFile { .. } => "save",
+            Action::CallTool { .. } => "plug",
             Action::Unknown(_) => "help-circle",
         };
 
@@ -502,6 +1014,10 @@ impl<Message: Clone + Send + Sync + 'static, B: Backend> View<Message, B> for To
             Action::SetButtonIntent(i) => ("Update Button Intent", format!("{:?}", i)),
             Action::SetLabMode(m) => ("Switch Mode", format!("{:?}", m)),
             Action::Shell(cmd) => ("Execute Shell", cmd.clone()),
+            Action::RunCommand { argv, .. } => (
+                "Run Command",
+                crate::reference::intelligence::protocol::command_line(argv),
+            ),
             Action::Memorize(content) => ("Memorize", content.clone()),
             Action::Teleport { target, x, y, z } => (
                 "Teleport",
@@ -515,6 +1031,7 @@ impl<Message: Clone + Send + Sync + 'static, B: Backend> View<Message, B> for To
             Action::WebSearch(query) => ("Web Search", query.clone()),
             Action::ReadFile(path) => ("Read File", path.clone()),
             Action::WriteFile { path, .. } => ("Write File", path.clone()),
+            Action::CallTool { name, .. } => ("Call Tool", name.clone()),
             Action::Unknown(raw) => ("Action", raw.clone()),
         };
 
@@ -676,7 +1193,10 @@ impl<Message: Clone + Send + Sync + 'static, B: Backend> View<Message, B> for We
         let t = context.theme;
         let title = self.data["title"].as_str().unwrap_or("Untitled");
         let snippet = self.data["snippet"].as_str().unwrap_or("");
-        let link = self.data["link"].as_str().unwrap_or("");
+        let link = self.data["url"]
+            .as_str()
+            .or_else(|| self.data["link"].as_str())
+            .unwrap_or("");
 
         let content = B::vstack(
             vec![
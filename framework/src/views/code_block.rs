@@ -1,13 +1,37 @@
 use crate::core::{Backend, Context, TextSpan, View};
 use crate::elements::atoms::Icon;
 use crate::style::{Intent, Radius, ScrollDirection, Variant};
+use crate::views::highlight;
 use iced::{Color, Length};
+use std::ops::RangeInclusive;
+use std::sync::{Arc, Mutex};
 
+/// Most code blocks whose highlighting is kept between renders.
+const MAX_HIGHLIGHTS: usize = 32;
+
+/// Code blocks highlighted for recent renders, the most recently used last.
+static HIGHLIGHTS: Mutex<Vec<Highlights>> = Mutex::new(Vec::new());
+
+/// The lines of a code block, highlighted for a language and palette.
+struct Highlights {
+    code: String,
+    language: String,
+    palette: highlight::Palette,
+    lines: Arc<Vec<Vec<TextSpan>>>,
+}
+
+/// Source code in a window-like frame with a copy button. The code is
+/// highlighted for its [`language`](Self::language) by
+/// [`highlight`](crate::views::highlight), optionally with line numbers,
+/// emphasised line ranges or as a diff.
 pub struct CodeBlock<Message: 'static + Send + Sync = ()> {
     code: String,
     language: String,
     height: Length,
     is_transparent: bool,
+    show_line_numbers: bool,
+    highlighted_lines: Vec<RangeInclusive<usize>>,
+    is_diff: bool,
     on_copy: Option<Box<dyn Fn(String) -> Message + Send + Sync>>,
 }
 
@@ -18,6 +42,9 @@ impl<Message: 'static + Send + Sync> CodeBlock<Message> {
             language: "rust".to_string(),
             height: Length::Shrink,
             is_transparent: false,
+            show_line_numbers: false,
+            highlighted_lines: Vec::new(),
+            is_diff: false,
             on_copy: None,
         }
     }
@@ -28,6 +55,9 @@ impl<Message: 'static + Send + Sync> CodeBlock<Message> {
             language: "rust".to_string(),
             height: Length::Shrink,
             is_transparent: false,
+            show_line_numbers: false,
+            highlighted_lines: Vec::new(),
+            is_diff: false,
             on_copy: None,
         }
     }
@@ -54,6 +84,26 @@ impl<Message: 'static + Send + Sync> CodeBlock<Message> {
         self.is_transparent = true;
         self
     }
+
+    pub fn line_numbers(mut self) -> Self {
+        self.show_line_numbers = true;
+        self
+    }
+
+    /// Emphasises a range of lines, counted from 1. Can be called more than
+    /// once.
+    pub fn highlight_lines(mut self, lines: RangeInclusive<usize>) -> Self {
+        self.highlighted_lines.push(lines);
+        self
+    }
+
+    /// Treats lines starting with `+` or `-` as added or removed; the rest
+    /// of each line is still highlighted as [`language`](Self::language).
+    /// Code in the `diff` language is always shown this way.
+    pub fn diff(mut self) -> Self {
+        self.is_diff = true;
+        self
+    }
 }
 
 impl<Message, B: Backend> View<Message, B> for CodeBlock<Message>
@@ -61,9 +111,10 @@ where
     Message: Clone + Send + Sync + 'static,
 {
     fn view(&self, context: &Context) -> B::AnyView<Message> {
-        let bg_color = Color::from_rgb8(20, 20, 20); // Darker code area
-        let header_bg = Color::from_rgb8(45, 45, 45); // Distinct header
-        let border_color = Color::from_rgb8(64, 64, 64);
+        let colors = &context.theme.colors;
+        let bg_color = colors.surface; // Code area
+        let header_bg = colors.surface_variant; // Distinct header
+        let border_color = colors.border;
 
         // Feedback Logic
         let is_recently_copied = context
@@ -77,7 +128,7 @@ where
             B::text(
                 self.language.to_uppercase(),
                 10.0,
-                Some(Color::from_rgb8(150, 150, 150)),
+                Some(colors.text_secondary),
                 false,
                 false,
                 None,
@@ -99,9 +150,9 @@ where
                         Icon::<B>::new(if is_recently_copied { "check" } else { "copy" })
                             .size(10.0)
                             .color(if is_recently_copied {
-                                Color::from_rgb8(100, 200, 100)
+                                colors.success
                             } else {
-                                Color::from_rgb8(150, 150, 150)
+                                colors.text_secondary
                             })
                             .view(context),
                         B::text(
@@ -112,9 +163,9 @@ where
                             },
                             10.0,
                             Some(if is_recently_copied {
-                                Color::from_rgb8(100, 200, 100)
+                                colors.success
                             } else {
-                                Color::from_rgb8(150, 150, 150)
+                                colors.text_secondary
                             }),
                             false,
                             false,
@@ -182,7 +233,7 @@ where
         );
 
         // 2. Code Area with Syntax Highlighting
-        let raw_code_view = highlight_rust::<Message, B>(&self.code, context);
+        let raw_code_view = self.code_lines::<B>(context);
         let scroll_area = B::scroll_view(
             raw_code_view,
             Length::Fill,
@@ -249,63 +300,158 @@ where
     }
 }
 
-// Helper: Heuristic Syntax Highlighter
-fn highlight_rust<Message, B: Backend>(content: &str, context: &Context) -> B::AnyView<Message>
-where
-    Message: 'static + Clone + Send + Sync,
-{
-    let trimmed_content = trim_common_indentation(content);
-    let mut spans: Vec<TextSpan> = Vec::new();
-
-    // VS Code Dark Theme Palette
-    let c_keyword = Color::from_rgb8(197, 134, 192); // Purple
-    let c_type = Color::from_rgb8(78, 201, 176); // Teal
-    let c_string = Color::from_rgb8(206, 145, 120); // Orange
-    let c_comment = Color::from_rgb8(106, 153, 85); // Green
-    let c_fn = Color::from_rgb8(220, 220, 170); // Yellow
-    let c_plain = Color::from_rgb8(212, 212, 212); // White/Grey
-
-    // Very naive tokenization (split by whitespace but keep delimiters)
-    let tokens =
-        trimmed_content.split_inclusive(|c: char| c.is_whitespace() || "(){}[],.;:".contains(c));
-
-    for token in tokens {
-        let trimmed = token.trim();
-        let color = if trimmed.starts_with("//") {
-            c_comment
-        } else if trimmed.starts_with('"') {
-            c_string
-        } else if [
-            "fn", "pub", "struct", "impl", "use", "mod", "let", "mut", "return", "match", "if",
-            "else",
-        ]
-        .contains(&trimmed)
-        {
-            c_keyword
-        } else if [
-            "String", "Option", "Vec", "u32", "bool", "Self", "Context", "View",
-        ]
-        .contains(&trimmed)
-            || (trimmed.chars().next().map_or(false, |c| c.is_uppercase()))
-        {
-            c_type
-        } else if trimmed.ends_with('(') {
-            c_fn
+impl<Message: Clone + Send + Sync + 'static> CodeBlock<Message> {
+    fn code_lines<B: Backend>(&self, context: &Context) -> B::AnyView<Message> {
+        let colors = &context.theme.colors;
+        let is_diff_language = ["diff", "patch"].contains(&self.language.to_lowercase().as_str());
+        let is_diff = self.is_diff || is_diff_language;
+        // Leading spaces mark context lines in a diff
+        let code = if is_diff {
+            self.code.trim_end().to_string()
         } else {
-            c_plain
+            trim_common_indentation(&self.code)
         };
 
-        spans.push(TextSpan {
-            content: token.into(),
-            color: Some(color),
-            font: Some(iced::Font::MONOSPACE),
-            size: None, // Default size
-            is_bold: false,
-            is_dim: false,
-        });
+        // In a diff the markers are split off and the rest highlighted
+        let (markers, lines): (Vec<Option<char>>, Vec<&str>) = code
+            .lines()
+            .map(|line| match line.chars().next() {
+                Some(marker @ ('+' | '-' | ' ')) if is_diff => (Some(marker), &line[1..]),
+                _ => (None, line),
+            })
+            .unzip();
+        let language = if is_diff_language { "" } else { &self.language };
+        let highlighted = highlighted(&code, &lines, language, colors);
+
+        let number_width = lines.len().to_string().len() as f32 * 8.0 + 8.0;
+        let rows = highlighted
+            .iter()
+            .cloned()
+            .zip(markers)
+            .enumerate()
+            .map(|(i, (mut spans, marker))| {
+                let number = i + 1;
+                let mut cells = Vec::new();
+
+                if self.show_line_numbers {
+                    cells.push(B::text(
+                        number.to_string(),
+                        13.0,
+                        Some(colors.text_tertiary),
+                        false,
+                        false,
+                        None,
+                        Some(iced::Font::MONOSPACE),
+                        Length::Fixed(number_width),
+                        iced::Alignment::End,
+                        context,
+                    ));
+                }
+
+                let mut background = self
+                    .highlighted_lines
+                    .iter()
+                    .any(|range| range.contains(&number))
+                    .then(|| colors.primary.scale_alpha(0.15));
+
+                if is_diff {
+                    let marker_color = match marker {
+                        Some('+') => Some(colors.success),
+                        Some('-') => Some(colors.danger),
+                        _ => None,
+                    };
+                    if let Some(color) = marker_color {
+                        background = background.or(Some(color.scale_alpha(0.12)));
+                    }
+                    spans.insert(
+                        0,
+                        TextSpan::new(format!("{} ", marker.unwrap_or(' ')))
+                            .color(marker_color.unwrap_or(colors.text_tertiary))
+                            .font(iced::Font::MONOSPACE),
+                    );
+                }
+
+                // Empty lines keep their height
+                if spans.is_empty() {
+                    spans.push(TextSpan::new(" ").font(iced::Font::MONOSPACE));
+                }
+                cells.push(B::rich_text(
+                    spans,
+                    13.0,
+                    Length::Fill,
+                    iced::Alignment::Start,
+                    context,
+                ));
+
+                B::container(
+                    B::hstack(
+                        cells,
+                        12.0,
+                        iced::Padding::ZERO,
+                        Length::Fill,
+                        Length::Shrink,
+                        iced::Alignment::Start,
+                        iced::Alignment::Start,
+                        context,
+                    ),
+                    iced::Padding::ZERO,
+                    Length::Fill,
+                    Length::Shrink,
+                    background,
+                    0.0,
+                    0.0,
+                    None,
+                    None,
+                    iced::Alignment::Start,
+                    iced::Alignment::Start,
+                    context,
+                )
+            })
+            .collect();
+
+        B::vstack(
+            rows,
+            0.0,
+            iced::Padding::ZERO,
+            Length::Fill,
+            Length::Shrink,
+            iced::Alignment::Start,
+            iced::Alignment::Start,
+            context,
+        )
+    }
+}
+
+/// `lines` of `code` highlighted as `language`, worked out again only when
+/// the code, language or palette changes.
+fn highlighted(
+    code: &str,
+    lines: &[&str],
+    language: &str,
+    colors: &peak_theme::PeakColors,
+) -> Arc<Vec<Vec<TextSpan>>> {
+    let palette = highlight::palette(colors);
+    let mut cache = HIGHLIGHTS.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(index) = cache.iter().position(|highlights| {
+        highlights.code == code && highlights.language == language && highlights.palette == palette
+    }) {
+        let highlights = cache.remove(index);
+        let lines = highlights.lines.clone();
+        cache.push(highlights);
+        return lines;
     }
 
-    B::rich_text(spans, 13.0, Length::Fill, iced::Alignment::Start, context)
+    let lines = Arc::new(highlight::highlight(lines, language, colors));
+    if cache.len() >= MAX_HIGHLIGHTS {
+        cache.remove(0);
+    }
+    cache.push(Highlights {
+        code: code.to_string(),
+        language: language.to_string(),
+        palette,
+        lines: lines.clone(),
+    });
+    lines
 }
 
 fn trim_common_indentation(s: &str) -> String {
@@ -340,3 +486,64 @@ fn trim_common_indentation(s: &str) -> String {
         .trim()
         .to_string()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::TermBackend;
+    use peak_theme::{PeakTheme, ThemeTokens, ThemeTone};
+
+    fn context() -> Context {
+        Context {
+            theme: ThemeTokens::with_theme(PeakTheme::Cupertino, ThemeTone::Dark),
+            ..Context::default()
+        }
+    }
+
+    #[test]
+    fn test_terminal_output_is_highlighted() {
+        let context = context();
+        let block = CodeBlock::<()>::new("fn main() {}").language("rust");
+
+        let output = View::<(), TermBackend>::view(&block, &context);
+        let [r, g, b, _] = context.theme.colors.accent.into_rgba8();
+        assert!(output.contains(&format!("\x1b[38;2;{};{};{}mfn\x1b[0m", r, g, b)));
+    }
+
+    #[test]
+    fn test_diff_lines_keep_their_markers() {
+        let context = context();
+        let block = CodeBlock::<()>::new("-let a = 1;\n+let a = 2;\n unchanged")
+            .language("rust")
+            .diff()
+            .line_numbers();
+
+        let output = View::<(), TermBackend>::view(&block, &context);
+        let [r, g, b, _] = context.theme.colors.danger.into_rgba8();
+        assert!(output.contains(&format!("\x1b[38;2;{};{};{}m- \x1b[0m", r, g, b)));
+        assert!(output.contains("unchanged"));
+        assert!(!output.contains("-let"));
+    }
+
+    #[test]
+    fn test_highlighting_is_kept_between_renders() {
+        let colors = context().theme.colors;
+        let code = format!("let id = \"{}\";", uuid::Uuid::new_v4());
+
+        let first = highlighted(&code, &[&code], "rust", &colors);
+        assert!(Arc::ptr_eq(
+            &first,
+            &highlighted(&code, &[&code], "rust", &colors)
+        ));
+        assert!(!Arc::ptr_eq(
+            &first,
+            &highlighted(&code, &[&code], "python", &colors)
+        ));
+
+        let light = ThemeTokens::with_theme(PeakTheme::Cupertino, ThemeTone::Light).colors;
+        assert!(!Arc::ptr_eq(
+            &first,
+            &highlighted(&code, &[&code], "rust", &light)
+        ));
+    }
+}
@@ -0,0 +1,587 @@
+use crate::core::{AccessibilityNode, AccessibilityRole, TextSpan};
+use crate::prelude::*;
+use std::ops::Range;
+use std::sync::Arc;
+
+pub mod find;
+pub mod state;
+
+pub use state::{EditorMessage, EditorState, Find, Position, Selection};
+
+/// How wide a monospace character is, for the font size.
+const CHAR_WIDTH: f32 = 0.6;
+
+/// The space between the line numbers and the text.
+const GUTTER_SPACING: f32 = 12.0;
+
+/// A multi-line text editor for code, with several cursors, undo, find and
+/// replace, and the highlighting of [`CodeBlock`](super::CodeBlock), showing
+/// the text of its [`EditorState`].
+pub struct CodeEditor<M, B>
+where
+    M: 'static + Send + Sync,
+    B: Backend + Send + Sync,
+{
+    pub state: EditorState,
+    pub on_message: Arc<dyn Fn(EditorMessage) -> M + Send + Sync>,
+    pub language: String,
+    pub show_line_numbers: bool,
+    /// How many characters fit on a row when soft wrap is on.
+    pub wrap_column: usize,
+    pub height: Length,
+    pub size: f32,
+    _phantom: std::marker::PhantomData<B>,
+}
+
+impl<M, B> CodeEditor<M, B>
+where
+    M: 'static + Clone + Send + Sync,
+    B: Backend + Send + Sync + 'static,
+{
+    /// An editor of `state`'s text, which reports what the user does
+    /// through `on_message`, for [`EditorState::update`].
+    pub fn new(
+        state: &EditorState,
+        on_message: impl Fn(EditorMessage) -> M + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            state: state.clone(),
+            on_message: Arc::new(on_message),
+            language: String::new(),
+            show_line_numbers: true,
+            wrap_column: 80,
+            height: Length::Fixed(320.0),
+            size: 13.0,
+            _phantom: std::marker::PhantomData,
+        }
+    }
+
+    /// The language to highlight, a name like "Rust" or an extension like
+    /// "rs". Unknown languages are plain text.
+    pub fn language(mut self, language: impl Into<String>) -> Self {
+        self.language = language.into();
+        self
+    }
+
+    pub fn line_numbers(mut self, show: bool) -> Self {
+        self.show_line_numbers = show;
+        self
+    }
+
+    pub fn wrap_column(mut self, column: usize) -> Self {
+        self.wrap_column = column.max(1);
+        self
+    }
+
+    pub fn height(mut self, height: Length) -> Self {
+        self.height = height;
+        self
+    }
+
+    pub fn size(mut self, size: f32) -> Self {
+        self.size = size;
+        self
+    }
+
+    fn gutter_width(&self) -> f32 {
+        if self.show_line_numbers {
+            self.state.line_count().to_string().len() as f32 * self.size * CHAR_WIDTH + 8.0
+        } else {
+            0.0
+        }
+    }
+
+    /// The spans of `line`, split where their backgrounds change, in rows
+    /// of at most `wrap` characters.
+    fn line_rows(
+        &self,
+        line: usize,
+        spans: &[TextSpan],
+        matches: &[Range<usize>],
+        wrap: Option<usize>,
+        context: &Context,
+    ) -> Vec<Vec<TextSpan>> {
+        let palette = context.theme.colors;
+        let range = self.state.line_range(line).unwrap_or_default();
+        let text = &self.state.text()[range.clone()];
+        let selections = self.state.selections();
+        let cursor = palette.primary.scale_alpha(0.6);
+        let background = |offset: usize| {
+            if selections.iter().any(|s| s.head == offset) {
+                Some(cursor)
+            } else if selections.iter().any(|s| s.range().contains(&offset)) {
+                Some(palette.primary.scale_alpha(0.25))
+            } else if matches.iter().any(|m| m.contains(&offset)) {
+                Some(palette.warning.scale_alpha(0.3))
+            } else {
+                None
+            }
+        };
+
+        // The style of each character, from the span it's in
+        let plain = TextSpan::new("").color(palette.text_primary);
+        let styles = spans
+            .iter()
+            .enumerate()
+            .flat_map(|(i, span)| span.content.chars().map(move |_| Some(i)));
+        let styles = styles.chain(std::iter::repeat(None));
+
+        let mut rows: Vec<Vec<TextSpan>> = vec![Vec::new()];
+        let mut last: Option<(Option<usize>, Option<Color>)> = None;
+        for (column, ((i, c), style)) in text.char_indices().zip(styles).enumerate() {
+            if wrap.is_some_and(|wrap| column > 0 && column % wrap == 0) {
+                rows.push(Vec::new());
+                last = None;
+            }
+            let row = rows.last_mut().unwrap();
+            let key = (style, background(range.start + i));
+            match row.last_mut() {
+                Some(span) if last == Some(key) => span.content.push(c),
+                _ => {
+                    let mut span = style.map_or_else(|| plain.clone(), |i| spans[i].clone());
+                    span.content = c.to_string();
+                    span.font = Some(iced::Font::MONOSPACE);
+                    span.background = key.1;
+                    row.push(span);
+                }
+            }
+            last = Some(key);
+        }
+
+        // A cursor at the end of the line, or a space to keep the height
+        let mut end = TextSpan::new(" ").font(iced::Font::MONOSPACE);
+        if selections.iter().any(|s| s.head == range.end) {
+            end = end.background(cursor);
+        }
+        rows.last_mut().unwrap().push(end);
+        rows
+    }
+
+    fn text_rows(&self, context: &Context) -> B::AnyView<M> {
+        let palette = context.theme.colors;
+        let highlighted = self.state.highlighted(&self.language, &palette);
+        let matches = self.state.matches();
+        let wrap = self.state.soft_wrap.then_some(self.wrap_column);
+        let gutter = self.gutter_width();
+        let scale = context.theme.scaling;
+        let char_width = self.size * CHAR_WIDTH;
+        let primary_line = self.state.position(self.state.primary().head).line;
+
+        let mut rows = Vec::new();
+        for line in 0..self.state.line_count() {
+            let spans = highlighted.get(line).map(Vec::as_slice).unwrap_or_default();
+            for (row, spans) in self
+                .line_rows(line, spans, &matches, wrap, context)
+                .into_iter()
+                .enumerate()
+            {
+                let mut cells = Vec::new();
+                if self.show_line_numbers {
+                    cells.push(B::text(
+                        if row == 0 {
+                            (line + 1).to_string()
+                        } else {
+                            String::new()
+                        },
+                        self.size,
+                        Some(if line == primary_line {
+                            palette.text_secondary
+                        } else {
+                            palette.text_tertiary
+                        }),
+                        false,
+                        false,
+                        None,
+                        Some(iced::Font::MONOSPACE),
+                        Length::Fixed(gutter),
+                        iced::Alignment::End,
+                        context,
+                    ));
+                }
+                cells.push(B::rich_text(
+                    spans,
+                    self.size,
+                    Length::Shrink,
+                    iced::Alignment::Start,
+                    context,
+                ));
+
+                // The column under the pointer, past the line numbers
+                let first_column = row * self.wrap_column;
+                let text_start = if self.show_line_numbers {
+                    gutter + GUTTER_SPACING
+                } else {
+                    0.0
+                };
+                let on_message = self.on_message.clone();
+                let on_move = Arc::new(move |point: iced::Point| {
+                    let x = (point.x / scale - text_start).max(0.0);
+                    on_message(EditorMessage::PointerMoved(Position {
+                        line,
+                        column: first_column + (x / char_width).round() as usize,
+                    }))
+                });
+
+                rows.push(B::mouse_area(
+                    B::hstack(
+                        cells,
+                        GUTTER_SPACING,
+                        Padding::default(),
+                        Length::Fill,
+                        Length::Shrink,
+                        iced::Alignment::Start,
+                        iced::Alignment::Start,
+                        context,
+                    ),
+                    Some(on_move),
+                    Some((self.on_message)(EditorMessage::Pressed)),
+                    Some((self.on_message)(EditorMessage::Released)),
+                    context,
+                ));
+            }
+        }
+
+        B::scroll_view(
+            B::vstack(
+                rows,
+                0.0,
+                Padding::from([8, 0]),
+                if wrap.is_some() {
+                    Length::Fill
+                } else {
+                    Length::Shrink
+                },
+                Length::Shrink,
+                iced::Alignment::Start,
+                iced::Alignment::Start,
+                context,
+            ),
+            Length::Fill,
+            self.height,
+            None,
+            true,
+            if wrap.is_some() {
+                ScrollDirection::Vertical
+            } else {
+                ScrollDirection::Both
+            },
+            context,
+        )
+    }
+
+    fn button(
+        &self,
+        label: &str,
+        message: EditorMessage,
+        active: bool,
+        context: &Context,
+    ) -> B::AnyView<M> {
+        B::button(
+            B::text(
+                label.to_string(),
+                12.0,
+                None,
+                false,
+                false,
+                None,
+                None,
+                Length::Shrink,
+                iced::Alignment::Center,
+                context,
+            ),
+            Some((self.on_message)(message)),
+            if active {
+                Variant::Soft
+            } else {
+                Variant::Ghost
+            },
+            if active {
+                Intent::Primary
+            } else {
+                Intent::Neutral
+            },
+            Length::Shrink,
+            Length::Shrink,
+            true,
+            context,
+        )
+    }
+
+    fn find_bar(&self, find: &Find, context: &Context) -> B::AnyView<M> {
+        let matches = self.state.matches();
+        let current = matches
+            .iter()
+            .position(|range| *range == self.state.primary().range());
+        let count = match (current, matches.len()) {
+            (_, 0) if find.query.is_empty() => String::new(),
+            (_, 0) => "No results".to_string(),
+            (Some(i), n) => format!("{} of {}", i + 1, n),
+            (None, n) => format!("{} found", n),
+        };
+
+        let on_find = self.on_message.clone();
+        let on_replace = self.on_message.clone();
+        B::hstack(
+            vec![
+                B::text_input(
+                    find.query.clone(),
+                    "Find".to_string(),
+                    move |query| on_find(EditorMessage::FindChanged(query)),
+                    Some((self.on_message)(EditorMessage::FindNext)),
+                    Some(iced::Font::MONOSPACE),
+                    false,
+                    Variant::Outline,
+                    None,
+                    context,
+                ),
+                B::text_input(
+                    find.replacement.clone(),
+                    "Replace".to_string(),
+                    move |replacement| on_replace(EditorMessage::ReplaceChanged(replacement)),
+                    Some((self.on_message)(EditorMessage::Replace)),
+                    Some(iced::Font::MONOSPACE),
+                    false,
+                    Variant::Outline,
+                    None,
+                    context,
+                ),
+                self.button(
+                    "Aa",
+                    EditorMessage::ToggleMatchCase,
+                    find.match_case,
+                    context,
+                ),
+                B::text(
+                    count,
+                    12.0,
+                    None,
+                    false,
+                    true,
+                    None,
+                    None,
+                    Length::Fixed(80.0),
+                    iced::Alignment::Center,
+                    context,
+                ),
+                self.button("↑", EditorMessage::FindPrevious, false, context),
+                self.button("↓", EditorMessage::FindNext, false, context),
+                self.button("Replace", EditorMessage::Replace, false, context),
+                self.button("All", EditorMessage::ReplaceAll, false, context),
+                self.button("✕", EditorMessage::ToggleFind, false, context),
+            ],
+            6.0,
+            [6, 8].into(),
+            Length::Fill,
+            Length::Shrink,
+            iced::Alignment::Start,
+            iced::Alignment::Center,
+            context,
+        )
+    }
+
+    fn status_bar(&self, context: &Context) -> B::AnyView<M> {
+        let position = self.state.position(self.state.primary().head);
+        let mut status = format!("Ln {}, Col {}", position.line + 1, position.column + 1);
+        let cursors = self.state.selections().len();
+        if cursors > 1 {
+            status.push_str(&format!(" ({} cursors)", cursors));
+        }
+
+        let mut children = vec![B::text(
+            status,
+            12.0,
+            None,
+            false,
+            true,
+            None,
+            None,
+            Length::Fill,
+            iced::Alignment::Start,
+            context,
+        )];
+        if !self.language.is_empty() {
+            children.push(B::text(
+                self.language.clone(),
+                12.0,
+                None,
+                false,
+                true,
+                None,
+                None,
+                Length::Shrink,
+                iced::Alignment::Start,
+                context,
+            ));
+        }
+        children.push(self.button(
+            "Wrap",
+            EditorMessage::ToggleSoftWrap,
+            self.state.soft_wrap,
+            context,
+        ));
+
+        B::hstack(
+            children,
+            12.0,
+            [4, 8].into(),
+            Length::Fill,
+            Length::Shrink,
+            iced::Alignment::Start,
+            iced::Alignment::Center,
+            context,
+        )
+    }
+
+    fn describe_selection(&self, selection: Selection) -> SemanticNode {
+        let label = |offset| {
+            let position = self.state.position(offset);
+            format!("Ln {}, Col {}", position.line + 1, position.column + 1)
+        };
+        if selection.is_empty() {
+            SemanticNode::new("cursor").with_label(label(selection.head))
+        } else {
+            let range = selection.range();
+            SemanticNode::new("selection")
+                .with_label(format!("{} to {}", label(range.start), label(range.end)))
+                .with_content(self.state.text()[range].to_string())
+        }
+    }
+}
+
+impl<M, B> View<M, B> for CodeEditor<M, B>
+where
+    M: 'static + Clone + Send + Sync,
+    B: Backend + Send + Sync + 'static,
+{
+    fn view(&self, context: &Context) -> B::AnyView<M> {
+        let palette = context.theme.colors;
+        let mut children = Vec::new();
+        if let Some(find) = &self.state.find {
+            children.push(self.find_bar(find, context));
+        }
+        children.push(self.text_rows(context));
+        children.push(self.status_bar(context));
+
+        B::container(
+            B::vstack(
+                children,
+                0.0,
+                Padding::default(),
+                Length::Fill,
+                Length::Shrink,
+                iced::Alignment::Start,
+                iced::Alignment::Start,
+                context,
+            ),
+            Padding::from([0, 8]),
+            Length::Fill,
+            Length::Shrink,
+            Some(palette.surface),
+            8.0,
+            1.0,
+            Some(palette.border),
+            None,
+            iced::Alignment::Start,
+            iced::Alignment::Start,
+            context,
+        )
+    }
+
+    fn describe(&self, _context: &Context) -> SemanticNode {
+        let position = self.state.position(self.state.primary().head);
+        let label = if self.language.is_empty() {
+            "Code editor".to_string()
+        } else {
+            format!("{} editor", self.language)
+        };
+
+        let mut node = SemanticNode::new("code_editor")
+            .with_label(label.clone())
+            .with_content(self.state.text().to_string())
+            .with_accessibility(AccessibilityNode {
+                role: AccessibilityRole::TextArea,
+                label: label.into(),
+                value: Some(
+                    format!("Ln {}, Col {}", position.line + 1, position.column + 1).into(),
+                ),
+                states: if self.state.soft_wrap {
+                    vec!["multiline".into(), "wrapped".into()]
+                } else {
+                    vec!["multiline".into()]
+                },
+                ..Default::default()
+            })
+            .extend_children(
+                self.state
+                    .selections()
+                    .iter()
+                    .map(|selection| self.describe_selection(*selection)),
+            );
+
+        if let Some(find) = &self.state.find {
+            node = node.push_child(
+                SemanticNode::new("find")
+                    .with_label(find.query.clone())
+                    .with_content(format!("{} matches", self.state.matches().len())),
+            );
+        }
+        node
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::TermBackend;
+
+    fn state() -> EditorState {
+        let mut state = EditorState::new("fn main() {\n    let port = 8080;\n}");
+        state.set_selections(vec![
+            Selection::cursor(0),
+            Selection {
+                anchor: 20,
+                head: 24,
+            },
+        ]);
+        state
+    }
+
+    #[test]
+    fn test_editors_describe_cursors_and_selections() {
+        let mut state = state();
+        let _ = state.update(EditorMessage::FindChanged("port".into()));
+        let editor = CodeEditor::<(), TermBackend>::new(&state, |_| ()).language("rust");
+        let description = editor.describe(&Context::default());
+
+        let a11y = description.accessibility.as_ref().unwrap();
+        assert_eq!(a11y.role, AccessibilityRole::TextArea);
+        assert_eq!(a11y.value.as_deref(), Some("Ln 2, Col 13"));
+        assert_eq!(description.content.as_deref(), Some(state.text()));
+
+        let children = &description.children;
+        assert_eq!(children[0].role, "cursor");
+        assert_eq!(children[0].label.as_deref(), Some("Ln 1, Col 1"));
+        assert_eq!(children[1].role, "selection");
+        assert_eq!(
+            children[1].label.as_deref(),
+            Some("Ln 2, Col 9 to Ln 2, Col 13")
+        );
+        assert_eq!(children[1].content.as_deref(), Some("port"));
+        assert_eq!(children[2].content.as_deref(), Some("1 matches"));
+    }
+
+    #[test]
+    fn test_editors_render_lines_and_status() {
+        let rendered = CodeEditor::<(), TermBackend>::new(&state(), |_| ())
+            .language("rust")
+            .view(&Context::default());
+        for text in ["main", "port", "8080", "Ln 2, Col 13 (2 cursors)"] {
+            assert!(
+                rendered.contains(text),
+                "{} missing from {}",
+                text,
+                rendered
+            );
+        }
+    }
+}
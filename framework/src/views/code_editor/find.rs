@@ -0,0 +1,46 @@
+//! Searching the text of an editor.
+use std::ops::Range;
+
+/// The byte ranges of `query` in `text`, left to right and not overlapping.
+/// Without `match_case`, ASCII letters match either case.
+pub fn matches(text: &str, query: &str, match_case: bool) -> Vec<Range<usize>> {
+    if query.is_empty() {
+        return Vec::new();
+    }
+    if match_case {
+        return text
+            .match_indices(query)
+            .map(|(start, found)| start..start + found.len())
+            .collect();
+    }
+
+    // Byte-wise, so ranges stay on the text's character boundaries
+    let (haystack, needle) = (text.as_bytes(), query.as_bytes());
+    let mut found = Vec::new();
+    let mut start = 0;
+    while start + needle.len() <= haystack.len() {
+        if text.is_char_boundary(start)
+            && haystack[start..start + needle.len()].eq_ignore_ascii_case(needle)
+        {
+            found.push(start..start + needle.len());
+            start += needle.len();
+        } else {
+            start += 1;
+        }
+    }
+    found
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_matches_ignore_ascii_case_unless_asked() {
+        let text = "Port = 80\nport_name = \"pörT\"\nPORTPORT";
+        assert_eq!(matches(text, "port", true), vec![10..14]);
+        assert_eq!(matches(text, "port", false), [0..4, 10..14, 30..34, 34..38]);
+        assert!(matches(text, "", false).is_empty());
+        assert_eq!(matches("aaaa", "aa", false), [0..2, 2..4]);
+    }
+}
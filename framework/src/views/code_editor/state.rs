@@ -0,0 +1,1188 @@
+//! The text, selections and history of a [`CodeEditor`](super::CodeEditor).
+//!
+//! An [`EditorState`] in app state holds the text along with any number of
+//! selections, the undo history and the find bar. The editor reports what the
+//! user does as [`EditorMessage`]s, to be handed back to
+//! [`EditorState::update`], which also takes keys from
+//! [`EditorState::subscription`].
+use super::find;
+use crate::core::TextSpan;
+use crate::prelude::*;
+use peak_theme::PeakColors;
+use std::ops::Range;
+use std::sync::{Arc, Mutex};
+
+/// Most edits kept for undo.
+const MAX_UNDO: usize = 500;
+
+const INDENT: &str = "    ";
+
+/// A line and column, both from 0, with columns counted in characters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub struct Position {
+    pub line: usize,
+    pub column: usize,
+}
+
+/// The text between where a selection started and the cursor, as byte
+/// offsets. A plain cursor is an empty selection.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Selection {
+    pub anchor: usize,
+    pub head: usize,
+}
+
+impl Selection {
+    pub fn cursor(offset: usize) -> Self {
+        Self {
+            anchor: offset,
+            head: offset,
+        }
+    }
+
+    pub fn range(&self) -> Range<usize> {
+        self.anchor.min(self.head)..self.anchor.max(self.head)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.anchor == self.head
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum EditorMessage {
+    KeyPressed {
+        key: keyboard::Key,
+        modifiers: keyboard::Modifiers,
+        /// What the key types, if anything.
+        text: Option<String>,
+    },
+    ModifiersChanged(keyboard::Modifiers),
+    /// The pointer moved over the text, to this position.
+    PointerMoved(Position),
+    /// The mouse went down on the text: the cursor goes where the pointer
+    /// is, or with alt a cursor is added there, or with shift the selection
+    /// is extended to it.
+    Pressed,
+    Released,
+    /// Text to put in place of every selection, e.g. from the clipboard.
+    Paste(String),
+    Undo,
+    Redo,
+    ToggleSoftWrap,
+    /// Opens the find bar, with the selected text, or closes it.
+    ToggleFind,
+    FindChanged(String),
+    ReplaceChanged(String),
+    ToggleMatchCase,
+    FindNext,
+    FindPrevious,
+    /// Replaces the selected match, then selects the next.
+    Replace,
+    ReplaceAll,
+}
+
+/// What the find bar holds.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Find {
+    pub query: String,
+    pub replacement: String,
+    pub match_case: bool,
+}
+
+/// The text and selections at a point in the history.
+#[derive(Debug, Clone)]
+struct Snapshot {
+    text: Arc<String>,
+    selections: Vec<Selection>,
+    primary: usize,
+}
+
+/// Replaces `range` with `text`.
+#[derive(Debug, Clone)]
+struct Edit {
+    range: Range<usize>,
+    text: String,
+}
+
+/// Highlighted lines, for the text and language they were worked out for.
+#[derive(Debug)]
+struct Highlights {
+    text: Arc<String>,
+    language: String,
+    color: Color,
+    lines: Arc<Vec<Vec<TextSpan>>>,
+}
+
+/// How an editor is shown and edited. Keep it in app state.
+#[derive(Debug, Clone)]
+pub struct EditorState {
+    /// Long lines wrap instead of scrolling sideways.
+    pub soft_wrap: bool,
+    /// The find bar, while open.
+    pub find: Option<Find>,
+    text: Arc<String>,
+    /// In order and not overlapping.
+    selections: Vec<Selection>,
+    /// The selection that scrolls and finds, and that the status bar shows.
+    primary: usize,
+    undo: Vec<Snapshot>,
+    redo: Vec<Snapshot>,
+    /// Whether further typing joins the last undo step.
+    typing: bool,
+    /// The column up and down keep to, through shorter lines.
+    preferred_column: Option<usize>,
+    hovered: Option<Position>,
+    dragging: bool,
+    modifiers: keyboard::Modifiers,
+    revision: u64,
+    highlights: Arc<Mutex<Option<Highlights>>>,
+}
+
+impl Default for EditorState {
+    fn default() -> Self {
+        Self::new("")
+    }
+}
+
+impl EditorState {
+    pub fn new(text: impl Into<String>) -> Self {
+        Self {
+            soft_wrap: false,
+            find: None,
+            text: Arc::new(text.into()),
+            selections: vec![Selection::default()],
+            primary: 0,
+            undo: Vec::new(),
+            redo: Vec::new(),
+            typing: false,
+            preferred_column: None,
+            hovered: None,
+            dragging: false,
+            modifiers: keyboard::Modifiers::default(),
+            revision: 0,
+            highlights: Arc::default(),
+        }
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Replaces the text, e.g. with a file just loaded, forgetting the
+    /// history.
+    pub fn set_text(&mut self, text: impl Into<String>) {
+        self.text = Arc::new(text.into());
+        self.selections = vec![Selection::default()];
+        self.primary = 0;
+        self.undo.clear();
+        self.redo.clear();
+        self.typing = false;
+        self.revision += 1;
+    }
+
+    /// Goes up with every change to the text, e.g. to know when to save.
+    pub fn revision(&self) -> u64 {
+        self.revision
+    }
+
+    pub fn selections(&self) -> &[Selection] {
+        &self.selections
+    }
+
+    pub fn primary(&self) -> Selection {
+        self.selections[self.primary]
+    }
+
+    pub fn set_selections(&mut self, selections: Vec<Selection>) {
+        let len = self.text.len();
+        let selections: Vec<Selection> = selections
+            .into_iter()
+            .map(|selection| Selection {
+                anchor: self.floor(selection.anchor.min(len)),
+                head: self.floor(selection.head.min(len)),
+            })
+            .collect();
+        if selections.is_empty() {
+            return;
+        }
+        let primary = *selections.last().unwrap();
+        self.selections = selections;
+        self.normalize(primary);
+        self.typing = false;
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    /// The text of each non-empty selection, joined by line breaks.
+    pub fn selected_text(&self) -> String {
+        self.selections
+            .iter()
+            .filter(|selection| !selection.is_empty())
+            .map(|selection| &self.text[selection.range()])
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// Where byte `offset` is.
+    pub fn position(&self, offset: usize) -> Position {
+        let offset = self.floor(offset.min(self.text.len()));
+        let before = &self.text[..offset];
+        let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+        Position {
+            line: before.matches('\n').count(),
+            column: before[line_start..].chars().count(),
+        }
+    }
+
+    /// The byte offset of `position`, kept within its line and the text.
+    pub fn offset(&self, position: Position) -> usize {
+        let Some(line) = self.line_range(position.line) else {
+            return self.text.len();
+        };
+        self.text[line.clone()]
+            .char_indices()
+            .nth(position.column)
+            .map_or(line.end, |(i, _)| line.start + i)
+    }
+
+    pub fn line_count(&self) -> usize {
+        self.text.matches('\n').count() + 1
+    }
+
+    /// The bytes of line `line`, without its line break.
+    pub fn line_range(&self, line: usize) -> Option<Range<usize>> {
+        let mut start = 0;
+        for _ in 0..line {
+            start += self.text[start..].find('\n')? + 1;
+        }
+        let end = self.text[start..]
+            .find('\n')
+            .map_or(self.text.len(), |i| start + i);
+        Some(start..end)
+    }
+
+    /// The matches of the find bar's query.
+    pub fn matches(&self) -> Vec<Range<usize>> {
+        match &self.find {
+            Some(find) => find::matches(&self.text, &find.query, find.match_case),
+            None => Vec::new(),
+        }
+    }
+
+    /// The text's lines highlighted as `language`, worked out again only
+    /// when the text changes.
+    pub(crate) fn highlighted(
+        &self,
+        language: &str,
+        colors: &PeakColors,
+    ) -> Arc<Vec<Vec<TextSpan>>> {
+        let mut cache = self.highlights.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(highlights) = cache.as_ref() {
+            if Arc::ptr_eq(&highlights.text, &self.text)
+                && highlights.language == language
+                && highlights.color == colors.text_primary
+            {
+                return highlights.lines.clone();
+            }
+        }
+
+        let lines: Vec<&str> = self.text.split('\n').collect();
+        let lines = Arc::new(crate::views::highlight::highlight(&lines, language, colors));
+        *cache = Some(Highlights {
+            text: self.text.clone(),
+            language: language.to_string(),
+            color: colors.text_primary,
+            lines: lines.clone(),
+        });
+        lines
+    }
+
+    /// Handles a message from the editor. Copying, cutting and pasting go
+    /// through the clipboard, in the returned task.
+    pub fn update(&mut self, message: EditorMessage) -> Task<EditorMessage> {
+        match message {
+            EditorMessage::KeyPressed {
+                key,
+                modifiers,
+                text,
+            } => {
+                self.modifiers = modifiers;
+                return self.key_pressed(key, modifiers, text);
+            }
+            EditorMessage::ModifiersChanged(modifiers) => self.modifiers = modifiers,
+            EditorMessage::PointerMoved(position) => {
+                self.hovered = Some(position);
+                if self.dragging {
+                    let head = self.offset(position);
+                    self.selections[self.primary].head = head;
+                    let primary = self.primary();
+                    self.normalize(primary);
+                }
+            }
+            EditorMessage::Pressed => {
+                let Some(position) = self.hovered else {
+                    return Task::none();
+                };
+                let offset = self.offset(position);
+                self.typing = false;
+                self.preferred_column = None;
+                self.dragging = true;
+
+                if self.modifiers.alt() {
+                    let cursor = Selection::cursor(offset);
+                    self.selections.push(cursor);
+                    self.normalize(cursor);
+                } else if self.modifiers.shift() {
+                    self.selections[self.primary].head = offset;
+                    let primary = self.primary();
+                    self.selections = vec![primary];
+                    self.primary = 0;
+                } else {
+                    self.selections = vec![Selection::cursor(offset)];
+                    self.primary = 0;
+                }
+            }
+            EditorMessage::Released => self.dragging = false,
+            EditorMessage::Paste(text) => self.paste(&text),
+            EditorMessage::Undo => self.undo(),
+            EditorMessage::Redo => self.redo(),
+            EditorMessage::ToggleSoftWrap => self.soft_wrap = !self.soft_wrap,
+            EditorMessage::ToggleFind => {
+                if self.find.take().is_none() {
+                    let selected = &self.text[self.primary().range()];
+                    self.find = Some(Find {
+                        query: if selected.contains('\n') {
+                            String::new()
+                        } else {
+                            selected.to_string()
+                        },
+                        ..Find::default()
+                    });
+                }
+            }
+            EditorMessage::FindChanged(query) => {
+                self.find.get_or_insert_with(Find::default).query = query;
+            }
+            EditorMessage::ReplaceChanged(replacement) => {
+                self.find.get_or_insert_with(Find::default).replacement = replacement;
+            }
+            EditorMessage::ToggleMatchCase => {
+                let find = self.find.get_or_insert_with(Find::default);
+                find.match_case = !find.match_case;
+            }
+            EditorMessage::FindNext => self.find_next(true),
+            EditorMessage::FindPrevious => self.find_next(false),
+            EditorMessage::Replace => {
+                let matches = self.matches();
+                let selected = self.primary().range();
+                if matches.contains(&selected) {
+                    let replacement = self.replacement();
+                    self.selections = vec![self.primary()];
+                    self.primary = 0;
+                    self.replace_selections(|_| replacement.clone());
+                }
+                self.find_next(true);
+            }
+            EditorMessage::ReplaceAll => {
+                let replacement = self.replacement();
+                let edits = self
+                    .matches()
+                    .into_iter()
+                    .map(|range| Edit {
+                        range,
+                        text: replacement.clone(),
+                    })
+                    .collect();
+                self.apply(edits, false);
+            }
+        }
+        Task::none()
+    }
+
+    /// Keys and modifiers not taken by a widget, e.g. the find bar.
+    /// Subscribe while the editor is the thing keys are meant for.
+    pub fn subscription() -> Subscription<EditorMessage> {
+        event::listen_with(|event, status, _window| match (event, status) {
+            (
+                Event::Keyboard(keyboard::Event::KeyPressed {
+                    key,
+                    modifiers,
+                    text,
+                    ..
+                }),
+                event::Status::Ignored,
+            ) => Some(EditorMessage::KeyPressed {
+                key,
+                modifiers,
+                text: text.map(|text| text.to_string()),
+            }),
+            (Event::Keyboard(keyboard::Event::ModifiersChanged(modifiers)), _) => {
+                Some(EditorMessage::ModifiersChanged(modifiers))
+            }
+            _ => None,
+        })
+    }
+
+    pub fn undo(&mut self) {
+        if let Some(snapshot) = self.undo.pop() {
+            let current = self.snapshot();
+            self.redo.push(current);
+            self.restore(snapshot);
+        }
+    }
+
+    pub fn redo(&mut self) {
+        if let Some(snapshot) = self.redo.pop() {
+            let current = self.snapshot();
+            self.undo.push(current);
+            self.restore(snapshot);
+        }
+    }
+
+    /// Types `text` in place of every selection.
+    pub fn insert(&mut self, text: &str) {
+        // Typing a word is one undo step, up to and including what ends it
+        let join = self.typing && self.selections.iter().all(Selection::is_empty);
+        let revision = self.revision;
+        self.replace_selections(|_| text.to_string());
+        if join && self.revision != revision {
+            self.undo.pop();
+        }
+        self.typing = !text.chars().any(char::is_whitespace);
+    }
+
+    fn key_pressed(
+        &mut self,
+        key: keyboard::Key,
+        modifiers: keyboard::Modifiers,
+        text: Option<String>,
+    ) -> Task<EditorMessage> {
+        use keyboard::key::Named;
+
+        let extend = modifiers.shift();
+        let word = modifiers.jump();
+
+        if modifiers.command() {
+            match key.as_ref() {
+                keyboard::Key::Character("z") if extend => self.redo(),
+                keyboard::Key::Character("z") => self.undo(),
+                keyboard::Key::Character("y") => self.redo(),
+                keyboard::Key::Character("a") => {
+                    self.selections = vec![Selection {
+                        anchor: 0,
+                        head: self.text.len(),
+                    }];
+                    self.primary = 0;
+                }
+                keyboard::Key::Character("c") => {
+                    return clipboard::write(self.selected_text());
+                }
+                keyboard::Key::Character("x") => {
+                    let selected = self.selected_text();
+                    self.replace_selections(|_| String::new());
+                    return clipboard::write(selected);
+                }
+                keyboard::Key::Character("v") => {
+                    return clipboard::read()
+                        .map(|text| EditorMessage::Paste(text.unwrap_or_default()));
+                }
+                keyboard::Key::Character("d") => self.select_next_occurrence(),
+                keyboard::Key::Character("f") => {
+                    return self.update(EditorMessage::ToggleFind);
+                }
+                keyboard::Key::Named(Named::Home) => self.move_to(extend, |_, _| 0),
+                keyboard::Key::Named(Named::End) => {
+                    self.move_to(extend, |state, _| state.text.len())
+                }
+                // Word moves where command is the jump modifier
+                keyboard::Key::Named(_) if word => {
+                    self.navigate(key.as_ref(), extend, word);
+                }
+                _ => {}
+            }
+            return Task::none();
+        }
+
+        match key.as_ref() {
+            keyboard::Key::Named(Named::Enter) => {
+                let indents: Vec<String> = self
+                    .selections
+                    .iter()
+                    .map(|selection| self.indentation(selection.range().start))
+                    .collect();
+                self.typing = false;
+                self.replace_selections(|i| format!("\n{}", indents[i]));
+            }
+            keyboard::Key::Named(Named::Tab) => {
+                let multiline = self
+                    .selections
+                    .iter()
+                    .any(|selection| self.text[selection.range()].contains('\n'));
+                if extend || multiline {
+                    self.indent_lines(!extend);
+                } else {
+                    self.typing = false;
+                    self.replace_selections(|_| INDENT.to_string());
+                }
+            }
+            keyboard::Key::Named(Named::Backspace) => self.delete(false, word),
+            keyboard::Key::Named(Named::Delete) => self.delete(true, word),
+            keyboard::Key::Named(Named::Escape) => {
+                if self.selections.len() > 1 {
+                    self.selections = vec![self.primary()];
+                    self.primary = 0;
+                } else {
+                    self.find = None;
+                }
+            }
+            keyboard::Key::Named(_) => self.navigate(key.as_ref(), extend, word),
+            _ => {
+                if let Some(text) = text.filter(|text| !text.chars().any(char::is_control)) {
+                    self.insert(&text);
+                }
+            }
+        }
+        Task::none()
+    }
+
+    fn navigate(&mut self, key: keyboard::Key<&str>, extend: bool, word: bool) {
+        use keyboard::key::Named;
+
+        match key {
+            keyboard::Key::Named(Named::ArrowLeft) => {
+                if !extend && !word && self.selections.iter().any(|s| !s.is_empty()) {
+                    // Collapses the selections to their starts
+                    self.move_to(false, |_, selection| selection.range().start);
+                } else if word {
+                    self.move_to(extend, |state, selection| state.word_left(selection.head));
+                } else {
+                    self.move_to(extend, |state, selection| state.previous(selection.head));
+                }
+            }
+            keyboard::Key::Named(Named::ArrowRight) => {
+                if !extend && !word && self.selections.iter().any(|s| !s.is_empty()) {
+                    self.move_to(false, |_, selection| selection.range().end);
+                } else if word {
+                    self.move_to(extend, |state, selection| state.word_right(selection.head));
+                } else {
+                    self.move_to(extend, |state, selection| state.next(selection.head));
+                }
+            }
+            keyboard::Key::Named(Named::ArrowUp) => self.move_vertically(-1, extend),
+            keyboard::Key::Named(Named::ArrowDown) => self.move_vertically(1, extend),
+            keyboard::Key::Named(Named::PageUp) => self.move_vertically(-20, extend),
+            keyboard::Key::Named(Named::PageDown) => self.move_vertically(20, extend),
+            keyboard::Key::Named(Named::Home) => self.move_to(extend, |state, selection| {
+                // To the first character that isn't indentation, then the
+                // very start
+                let line = state.line_of(selection.head);
+                let text = state.line_start(line) + state.indentation(selection.head).len();
+                if selection.head == text {
+                    state.line_start(line)
+                } else {
+                    text
+                }
+            }),
+            keyboard::Key::Named(Named::End) => self.move_to(extend, |state, selection| {
+                let line = state.line_of(selection.head);
+                state
+                    .line_range(line)
+                    .map_or(state.text.len(), |range| range.end)
+            }),
+            _ => {}
+        }
+    }
+
+    /// Moves every cursor to where `to` says, extending the selections or
+    /// leaving plain cursors.
+    fn move_to(&mut self, extend: bool, to: impl Fn(&Self, Selection) -> usize) {
+        let primary = self.primary;
+        let mut moved = self.primary();
+        for (i, selection) in self.selections.clone().into_iter().enumerate() {
+            let head = to(self, selection);
+            let selection = if extend {
+                Selection {
+                    anchor: selection.anchor,
+                    head,
+                }
+            } else {
+                Selection::cursor(head)
+            };
+            self.selections[i] = selection;
+            if i == primary {
+                moved = selection;
+            }
+        }
+        self.typing = false;
+        self.preferred_column = None;
+        self.normalize(moved);
+    }
+
+    fn move_vertically(&mut self, lines: isize, extend: bool) {
+        let column = self
+            .preferred_column
+            .unwrap_or_else(|| self.position(self.primary().head).column);
+        let single = self.selections.len() == 1;
+        self.move_to(extend, |state, selection| {
+            let position = state.position(selection.head);
+            let line = position.line as isize + lines;
+            if line < 0 {
+                return 0;
+            }
+            if line as usize >= state.line_count() {
+                return state.text.len();
+            }
+            state.offset(Position {
+                line: line as usize,
+                column: if single { column } else { position.column },
+            })
+        });
+        self.preferred_column = single.then_some(column);
+    }
+
+    /// Deletes the selections, or the character or word before or after
+    /// each cursor.
+    fn delete(&mut self, forward: bool, word: bool) {
+        let edits = self
+            .selections
+            .iter()
+            .map(|selection| {
+                let range = if !selection.is_empty() {
+                    selection.range()
+                } else if forward && word {
+                    selection.head..self.word_right(selection.head)
+                } else if forward {
+                    selection.head..self.next(selection.head)
+                } else if word {
+                    self.word_left(selection.head)..selection.head
+                } else {
+                    self.previous(selection.head)..selection.head
+                };
+                Edit {
+                    range,
+                    text: String::new(),
+                }
+            })
+            .collect();
+        self.typing = false;
+        self.apply(edits, true);
+    }
+
+    /// Pastes `text` in place of every selection, or one line of it in
+    /// each when there are as many cursors as lines.
+    fn paste(&mut self, text: &str) {
+        if text.is_empty() {
+            return;
+        }
+        let lines: Vec<&str> = text.lines().collect();
+        let spread = self.selections.len() > 1 && lines.len() == self.selections.len();
+        self.typing = false;
+        self.replace_selections(|i| {
+            if spread {
+                lines[i].to_string()
+            } else {
+                text.to_string()
+            }
+        });
+    }
+
+    /// Indents the lines of every selection, or outdents them.
+    fn indent_lines(&mut self, indent: bool) {
+        let mut lines: Vec<usize> = self
+            .selections
+            .iter()
+            .flat_map(|selection| {
+                let range = selection.range();
+                let last = if range.end > range.start && self.text[..range.end].ends_with('\n') {
+                    self.line_of(range.end) - 1
+                } else {
+                    self.line_of(range.end)
+                };
+                self.line_of(range.start)..=last
+            })
+            .collect();
+        lines.dedup();
+
+        let edits = lines
+            .into_iter()
+            .filter_map(|line| {
+                let start = self.line_start(line);
+                if indent {
+                    return Some(Edit {
+                        range: start..start,
+                        text: INDENT.to_string(),
+                    });
+                }
+                let spaces = self.text[start..]
+                    .chars()
+                    .take(INDENT.len())
+                    .take_while(|c| *c == ' ')
+                    .count();
+                (spaces > 0).then(|| Edit {
+                    range: start..start + spaces,
+                    text: String::new(),
+                })
+            })
+            .collect();
+        self.typing = false;
+        self.apply(edits, false);
+    }
+
+    /// Selects the word at the cursor, or adds a selection of the next
+    /// occurrence of what is selected.
+    fn select_next_occurrence(&mut self) {
+        let primary = self.primary();
+        if primary.is_empty() {
+            let start = self.word_left(self.next(primary.head).min(self.word_right(primary.head)));
+            let end = self.word_right(start);
+            self.selections[self.primary] = Selection {
+                anchor: start,
+                head: end,
+            };
+            self.normalize(self.selections[self.primary]);
+            return;
+        }
+
+        let needle = &self.text[primary.range()];
+        let after = self.selections.last().map_or(0, |last| last.range().end);
+        let found = self.text[after..]
+            .find(needle)
+            .map(|i| after + i)
+            .or_else(|| self.text.find(needle))
+            .map(|start| start..start + needle.len());
+        if let Some(range) = found {
+            if !self.selections.iter().any(|s| s.range() == range) {
+                let selection = Selection {
+                    anchor: range.start,
+                    head: range.end,
+                };
+                self.selections.push(selection);
+                self.normalize(selection);
+            }
+        }
+    }
+
+    /// Selects the next match after the cursor, or the previous one before
+    /// it, going round at the ends.
+    fn find_next(&mut self, forward: bool) {
+        let matches = self.matches();
+        let selected = self.primary().range();
+        let found = if forward {
+            matches
+                .iter()
+                .find(|range| range.start >= selected.end && *range != &selected)
+                .or(matches.first())
+        } else {
+            matches
+                .iter()
+                .rev()
+                .find(|range| range.end <= selected.start)
+                .or(matches.last())
+        };
+        if let Some(range) = found {
+            self.selections = vec![Selection {
+                anchor: range.start,
+                head: range.end,
+            }];
+            self.primary = 0;
+            self.typing = false;
+        }
+    }
+
+    fn replacement(&self) -> String {
+        self.find
+            .as_ref()
+            .map(|find| find.replacement.clone())
+            .unwrap_or_default()
+    }
+
+    /// Replaces selection `i` with `text(i)`, leaving a cursor after each.
+    fn replace_selections(&mut self, text: impl Fn(usize) -> String) {
+        let edits = self
+            .selections
+            .iter()
+            .enumerate()
+            .map(|(i, selection)| Edit {
+                range: selection.range(),
+                text: text(i),
+            })
+            .collect();
+        self.apply(edits, true);
+    }
+
+    /// Makes `edits`, which are in order and don't overlap, as one undo
+    /// step. With `collapse`, each selection becomes a cursor after the edit
+    /// that replaced it; otherwise selections move along with the text.
+    fn apply(&mut self, edits: Vec<Edit>, collapse: bool) {
+        let edits: Vec<Edit> = edits
+            .into_iter()
+            .filter(|edit| !edit.range.is_empty() || !edit.text.is_empty())
+            .collect();
+        if edits.is_empty() {
+            return;
+        }
+        let snapshot = self.snapshot();
+        self.push_undo(snapshot);
+        self.redo.clear();
+
+        let mut text = String::with_capacity(self.text.len());
+        let mut end = 0;
+        for edit in &edits {
+            let start = edit.range.start.max(end);
+            text.push_str(&self.text[end..start]);
+            text.push_str(&edit.text);
+            end = edit.range.end.max(start);
+        }
+        text.push_str(&self.text[end..]);
+
+        let map = |offset: usize| {
+            let mut shift = 0isize;
+            for edit in &edits {
+                if edit.range.end <= offset {
+                    shift += edit.text.len() as isize - edit.range.len() as isize;
+                } else if edit.range.start < offset {
+                    return (edit.range.start as isize + shift) as usize + edit.text.len();
+                } else {
+                    break;
+                }
+            }
+            (offset as isize + shift) as usize
+        };
+
+        let primary = self.primary;
+        let selections: Vec<Selection> = self
+            .selections
+            .iter()
+            .map(|selection| {
+                if collapse {
+                    Selection::cursor(map(selection.range().end))
+                } else {
+                    Selection {
+                        anchor: map(selection.anchor),
+                        head: map(selection.head),
+                    }
+                }
+            })
+            .collect();
+
+        self.text = Arc::new(text);
+        let moved = selections[primary];
+        self.selections = selections;
+        self.preferred_column = None;
+        self.revision += 1;
+        self.normalize(moved);
+    }
+
+    /// Sorts the selections and merges those that touch, keeping `primary`
+    /// or what it merged into as the primary.
+    fn normalize(&mut self, primary: Selection) {
+        let mut selections = std::mem::take(&mut self.selections);
+        selections.sort_by_key(|selection| selection.range().start);
+
+        let mut merged: Vec<Selection> = Vec::with_capacity(selections.len());
+        let mut primary_index = 0;
+        for selection in selections {
+            match merged.last_mut() {
+                Some(last)
+                    if selection.range().start < last.range().end
+                        || selection.range() == last.range() =>
+                {
+                    let range = last.range().start..last.range().end.max(selection.range().end);
+                    *last = if last.head < last.anchor {
+                        Selection {
+                            anchor: range.end,
+                            head: range.start,
+                        }
+                    } else {
+                        Selection {
+                            anchor: range.start,
+                            head: range.end,
+                        }
+                    };
+                }
+                _ => merged.push(selection),
+            }
+            if selection == primary {
+                primary_index = merged.len() - 1;
+            }
+        }
+
+        self.selections = merged;
+        self.primary = primary_index;
+    }
+
+    fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            text: self.text.clone(),
+            selections: self.selections.clone(),
+            primary: self.primary,
+        }
+    }
+
+    fn restore(&mut self, snapshot: Snapshot) {
+        self.text = snapshot.text;
+        self.selections = snapshot.selections;
+        self.primary = snapshot.primary;
+        self.typing = false;
+        self.preferred_column = None;
+        self.revision += 1;
+    }
+
+    fn push_undo(&mut self, snapshot: Snapshot) {
+        self.undo.push(snapshot);
+        if self.undo.len() > MAX_UNDO {
+            self.undo.remove(0);
+        }
+    }
+
+    fn floor(&self, mut offset: usize) -> usize {
+        while !self.text.is_char_boundary(offset) {
+            offset -= 1;
+        }
+        offset
+    }
+
+    fn previous(&self, offset: usize) -> usize {
+        self.text[..offset]
+            .char_indices()
+            .next_back()
+            .map_or(0, |(i, _)| i)
+    }
+
+    fn next(&self, offset: usize) -> usize {
+        self.text[offset..]
+            .chars()
+            .next()
+            .map_or(offset, |c| offset + c.len_utf8())
+    }
+
+    /// The start of the word before `offset`, past any space.
+    fn word_left(&self, offset: usize) -> usize {
+        let before: Vec<(usize, char)> = self.text[..offset].char_indices().collect();
+        let mut i = before.len();
+        while i > 0 && before[i - 1].1.is_whitespace() {
+            i -= 1;
+        }
+        let class = i.checked_sub(1).map(|j| is_word(before[j].1));
+        while i > 0 && !before[i - 1].1.is_whitespace() && Some(is_word(before[i - 1].1)) == class {
+            i -= 1;
+        }
+        before.get(i).map_or(offset, |(start, _)| *start)
+    }
+
+    /// The end of the word after `offset`, past any space.
+    fn word_right(&self, offset: usize) -> usize {
+        let mut chars = self.text[offset..].char_indices().peekable();
+        while chars.next_if(|(_, c)| c.is_whitespace()).is_some() {}
+        let class = chars.peek().map(|(_, c)| is_word(*c));
+        while chars
+            .next_if(|(_, c)| !c.is_whitespace() && Some(is_word(*c)) == class)
+            .is_some()
+        {}
+        chars.peek().map_or(self.text.len(), |(i, _)| offset + i)
+    }
+
+    fn line_of(&self, offset: usize) -> usize {
+        self.text[..offset].matches('\n').count()
+    }
+
+    fn line_start(&self, line: usize) -> usize {
+        self.line_range(line)
+            .map_or(self.text.len(), |range| range.start)
+    }
+
+    /// The leading spaces and tabs of the line `offset` is on.
+    fn indentation(&self, offset: usize) -> String {
+        let start = self.line_start(self.line_of(offset));
+        self.text[start..]
+            .chars()
+            .take_while(|c| *c == ' ' || *c == '\t')
+            .collect()
+    }
+}
+
+fn is_word(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use keyboard::key::Named;
+
+    fn press(state: &mut EditorState, key: keyboard::Key, modifiers: keyboard::Modifiers) {
+        let text = match &key {
+            keyboard::Key::Character(c) => Some(c.to_string()),
+            _ => None,
+        };
+        let _ = state.update(EditorMessage::KeyPressed {
+            key,
+            modifiers,
+            text,
+        });
+    }
+
+    fn named(state: &mut EditorState, key: Named) {
+        press(
+            state,
+            keyboard::Key::Named(key),
+            keyboard::Modifiers::default(),
+        );
+    }
+
+    fn command(state: &mut EditorState, c: &str) {
+        press(
+            state,
+            keyboard::Key::Character(c.into()),
+            keyboard::Modifiers::COMMAND,
+        );
+    }
+
+    fn type_text(state: &mut EditorState, text: &str) {
+        for c in text.chars() {
+            press(
+                state,
+                keyboard::Key::Character(c.to_string().into()),
+                keyboard::Modifiers::default(),
+            );
+        }
+    }
+
+    #[test]
+    fn test_typing_undoes_a_word_at_a_time() {
+        let mut state = EditorState::new("");
+        type_text(&mut state, "let port");
+        named(&mut state, Named::Enter);
+        assert_eq!(state.text(), "let port\n");
+
+        let _ = state.update(EditorMessage::Undo);
+        assert_eq!(state.text(), "let port");
+        let _ = state.update(EditorMessage::Undo);
+        assert_eq!(state.text(), "let ");
+        let _ = state.update(EditorMessage::Undo);
+        assert_eq!(state.text(), "");
+        assert!(!state.can_undo());
+
+        command(&mut state, "y");
+        assert_eq!(state.text(), "let ");
+        assert_eq!(state.primary(), Selection::cursor(4));
+    }
+
+    #[test]
+    fn test_keys_move_and_select() {
+        let mut state = EditorState::new("fn main() {\n    run(1);\n}");
+        state.set_selections(vec![Selection::cursor(
+            state.offset(Position { line: 1, column: 9 }),
+        )]);
+
+        // Home goes to the text, then the line start
+        named(&mut state, Named::Home);
+        assert_eq!(state.position(state.primary().head).column, 4);
+        named(&mut state, Named::Home);
+        assert_eq!(state.position(state.primary().head).column, 0);
+
+        // Up keeps to the column through shorter lines
+        named(&mut state, Named::End);
+        named(&mut state, Named::ArrowDown);
+        named(&mut state, Named::ArrowUp);
+        assert_eq!(
+            state.position(state.primary().head),
+            Position {
+                line: 1,
+                column: 11
+            }
+        );
+
+        // Enter keeps the indentation
+        named(&mut state, Named::Enter);
+        assert_eq!(state.text(), "fn main() {\n    run(1);\n    \n}");
+    }
+
+    #[test]
+    fn test_edits_apply_at_every_cursor() {
+        let mut state = EditorState::new("port = 1\nport = 2\nhost = 3");
+
+        // Selecting the word, then its next occurrence
+        command(&mut state, "d");
+        command(&mut state, "d");
+        assert_eq!(state.selections().len(), 2);
+        type_text(&mut state, "listen");
+        assert_eq!(state.text(), "listen = 1\nlisten = 2\nhost = 3");
+
+        named(&mut state, Named::Backspace);
+        named(&mut state, Named::Escape);
+        assert_eq!(state.text(), "liste = 1\nliste = 2\nhost = 3");
+        assert_eq!(state.selections(), [Selection::cursor(15)]);
+
+        // Tab indents every selected line, shift-tab takes it back out
+        command(&mut state, "a");
+        named(&mut state, Named::Tab);
+        assert_eq!(state.text(), "    liste = 1\n    liste = 2\n    host = 3");
+        press(
+            &mut state,
+            keyboard::Key::Named(Named::Tab),
+            keyboard::Modifiers::SHIFT,
+        );
+        assert_eq!(state.text(), "liste = 1\nliste = 2\nhost = 3");
+        assert_eq!(state.primary().range(), 0..state.text().len());
+    }
+
+    #[test]
+    fn test_find_and_replace() {
+        let mut state = EditorState::new("Port = 80\nport = 81\nPORT = 82");
+        let _ = state.update(EditorMessage::FindChanged("port".into()));
+        assert_eq!(state.matches().len(), 3);
+
+        let _ = state.update(EditorMessage::ToggleMatchCase);
+        let _ = state.update(EditorMessage::FindNext);
+        assert_eq!(state.primary().range(), 10..14);
+
+        let _ = state.update(EditorMessage::ReplaceChanged("listen".into()));
+        let _ = state.update(EditorMessage::Replace);
+        assert_eq!(state.text(), "Port = 80\nlisten = 81\nPORT = 82");
+
+        let _ = state.update(EditorMessage::ToggleMatchCase);
+        let _ = state.update(EditorMessage::ReplaceAll);
+        assert_eq!(state.text(), "listen = 80\nlisten = 81\nlisten = 82");
+
+        // All of it is one undo step
+        let _ = state.update(EditorMessage::Undo);
+        assert_eq!(state.text(), "Port = 80\nlisten = 81\nPORT = 82");
+    }
+
+    #[test]
+    fn test_clicks_place_add_and_extend_cursors() {
+        let mut state = EditorState::new("alpha\nbeta\ngamma");
+        let click = |state: &mut EditorState, line, column, modifiers| {
+            let _ = state.update(EditorMessage::ModifiersChanged(modifiers));
+            let _ = state.update(EditorMessage::PointerMoved(Position { line, column }));
+            let _ = state.update(EditorMessage::Pressed);
+            let _ = state.update(EditorMessage::Released);
+        };
+
+        click(&mut state, 1, 2, keyboard::Modifiers::default());
+        click(&mut state, 2, 99, keyboard::Modifiers::ALT);
+        assert_eq!(
+            state.selections(),
+            [Selection::cursor(8), Selection::cursor(16)]
+        );
+
+        let _ = state.update(EditorMessage::Paste("1\n2".into()));
+        assert_eq!(state.text(), "alpha\nbe1ta\ngamma2");
+
+        click(&mut state, 0, 1, keyboard::Modifiers::default());
+        click(&mut state, 1, 0, keyboard::Modifiers::SHIFT);
+        assert_eq!(state.selected_text(), "lpha\n");
+        assert_eq!(
+            state.position(state.primary().head),
+            Position { line: 1, column: 0 }
+        );
+    }
+}
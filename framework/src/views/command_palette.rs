@@ -0,0 +1,325 @@
+use crate::core::{AccessibilityNode, AccessibilityRole, TextSpan};
+use crate::prelude::*;
+use std::sync::Arc;
+
+pub mod fuzzy;
+pub mod registry;
+pub mod state;
+
+pub use fuzzy::{fuzzy_match, FuzzyMatch};
+pub use registry::{CommandMatch, CommandRegistry, PaletteCommand};
+pub use state::{CommandPaletteState, PaletteMessage};
+
+/// A search box over the app's commands, opened with Cmd/Ctrl+K, which ranks
+/// the commands of its [`CommandPaletteState`] by what's typed and runs the
+/// one picked.
+pub struct CommandPalette<M, B>
+where
+    M: 'static + Send + Sync,
+    B: Backend + Send + Sync,
+{
+    pub state: CommandPaletteState,
+    pub on_message: Arc<dyn Fn(PaletteMessage) -> M + Send + Sync>,
+    pub placeholder: String,
+    /// Most results shown at once.
+    pub max_results: usize,
+    pub width: f32,
+    _phantom: std::marker::PhantomData<B>,
+}
+
+impl<M, B> CommandPalette<M, B>
+where
+    M: 'static + Clone + Send + Sync,
+    B: Backend + Send + Sync + 'static,
+{
+    /// A palette over `state`'s commands, which reports what the user does
+    /// through `on_message`, for [`CommandPaletteState::update`].
+    pub fn new(
+        state: &CommandPaletteState,
+        on_message: impl Fn(PaletteMessage) -> M + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            state: state.clone(),
+            on_message: Arc::new(on_message),
+            placeholder: "Type a command or search…".to_string(),
+            max_results: 8,
+            width: 560.0,
+            _phantom: std::marker::PhantomData,
+        }
+    }
+
+    pub fn placeholder(mut self, placeholder: impl Into<String>) -> Self {
+        self.placeholder = placeholder.into();
+        self
+    }
+
+    pub fn max_results(mut self, max_results: usize) -> Self {
+        self.max_results = max_results.max(1);
+        self
+    }
+
+    pub fn width(mut self, width: f32) -> Self {
+        self.width = width;
+        self
+    }
+
+    /// The results around the selected one, at most `max_results` of them.
+    fn visible<'a>(&self, results: &'a [CommandMatch<'a>]) -> (usize, &'a [CommandMatch<'a>]) {
+        let start = (self.state.selected() + 1).saturating_sub(self.max_results);
+        let end = (start + self.max_results).min(results.len());
+        (start, &results[start.min(end)..end])
+    }
+
+    fn title(&self, found: &CommandMatch, context: &Context) -> B::AnyView<M> {
+        let palette = context.theme.colors;
+        let mut spans: Vec<TextSpan> = Vec::new();
+        for (i, c) in found.command.title.chars().enumerate() {
+            let highlighted = found.highlights.contains(&i);
+            match spans.last_mut() {
+                Some(span) if span.is_bold == highlighted => span.content.push(c),
+                _ => {
+                    let span = TextSpan::new(c.to_string());
+                    spans.push(if highlighted {
+                        span.color(palette.primary).bold()
+                    } else {
+                        span.color(palette.text_primary)
+                    });
+                }
+            }
+        }
+        B::rich_text(spans, 14.0, Length::Fill, iced::Alignment::Start, context)
+    }
+
+    fn row(&self, found: &CommandMatch, selected: bool, context: &Context) -> B::AnyView<M> {
+        let command = found.command;
+        let mut cells = Vec::new();
+        if let Some(icon) = &command.icon {
+            cells.push(B::icon(icon.clone(), 16.0, None, context));
+        }
+        cells.push(self.title(found, context));
+        let detail = if found.recent && self.state.query.trim().is_empty() {
+            Some("Recent".to_string())
+        } else {
+            command.section.clone()
+        };
+        for (text, width) in [(detail, 100.0), (command.shortcut.clone(), 60.0)] {
+            if let Some(text) = text {
+                cells.push(B::text(
+                    text,
+                    12.0,
+                    None,
+                    false,
+                    true,
+                    None,
+                    None,
+                    Length::Fixed(width),
+                    iced::Alignment::End,
+                    context,
+                ));
+            }
+        }
+
+        B::button(
+            B::hstack(
+                cells,
+                10.0,
+                Padding::default(),
+                Length::Fill,
+                Length::Shrink,
+                iced::Alignment::Start,
+                iced::Alignment::Center,
+                context,
+            ),
+            Some((self.on_message)(PaletteMessage::Run(command.id.clone()))),
+            if selected {
+                Variant::Soft
+            } else {
+                Variant::Ghost
+            },
+            if selected {
+                Intent::Primary
+            } else {
+                Intent::Neutral
+            },
+            Length::Fill,
+            Length::Shrink,
+            true,
+            context,
+        )
+    }
+}
+
+impl<M, B> View<M, B> for CommandPalette<M, B>
+where
+    M: 'static + Clone + Send + Sync,
+    B: Backend + Send + Sync + 'static,
+{
+    fn view(&self, context: &Context) -> B::AnyView<M> {
+        let palette = context.theme.colors;
+        let results = self.state.results();
+        let (start, visible) = self.visible(&results);
+
+        let on_message = self.on_message.clone();
+        let mut children = vec![B::text_input(
+            self.state.query.clone(),
+            self.placeholder.clone(),
+            move |query| on_message(PaletteMessage::QueryChanged(query)),
+            None,
+            None,
+            false,
+            Variant::Ghost,
+            Some(iced::widget::Id::new(state::INPUT_ID)),
+            context,
+        )];
+        if visible.is_empty() {
+            children.push(B::text(
+                "No matching commands".to_string(),
+                13.0,
+                None,
+                false,
+                true,
+                None,
+                None,
+                Length::Fill,
+                iced::Alignment::Center,
+                context,
+            ));
+        }
+        children.extend(
+            visible
+                .iter()
+                .enumerate()
+                .map(|(i, found)| self.row(found, start + i == self.state.selected(), context)),
+        );
+
+        B::container(
+            B::vstack(
+                children,
+                4.0,
+                Padding::default(),
+                Length::Fill,
+                Length::Shrink,
+                iced::Alignment::Start,
+                iced::Alignment::Start,
+                context,
+            ),
+            Padding::from(8),
+            Length::Fixed(self.width),
+            Length::Shrink,
+            Some(palette.surface),
+            12.0,
+            1.0,
+            Some(palette.border),
+            Some(iced::Shadow {
+                color: iced::Color::from_rgba(0.0, 0.0, 0.0, 0.2),
+                offset: iced::Vector::new(0.0, 8.0),
+                blur_radius: 24.0,
+            }),
+            iced::Alignment::Start,
+            iced::Alignment::Start,
+            context,
+        )
+    }
+
+    /// The commands matching the query while open, and otherwise all of
+    /// them, so agents can see what can be done either way.
+    fn describe(&self, _context: &Context) -> SemanticNode {
+        let mut palette = SemanticNode::new("command_palette")
+            .with_label(if self.state.is_open {
+                "Command palette (open)"
+            } else {
+                "Command palette"
+            })
+            .with_accessibility(AccessibilityNode {
+                role: AccessibilityRole::Dialog,
+                label: "Command palette".into(),
+                hint: Some("Cmd/Ctrl+K".into()),
+                expanded: Some(self.state.is_open),
+                ..Default::default()
+            });
+        let mut commands = self.state.registry.describe();
+
+        if self.state.is_open {
+            palette = palette.push_child(
+                SemanticNode::new("search")
+                    .with_content(self.state.query.clone())
+                    .with_accessibility(AccessibilityNode {
+                        role: AccessibilityRole::SearchBox,
+                        label: self.placeholder.clone().into(),
+                        value: Some(self.state.query.clone().into()),
+                        states: vec!["focused".into()],
+                        ..Default::default()
+                    }),
+            );
+            commands.children = self
+                .state
+                .results()
+                .iter()
+                .enumerate()
+                .map(|(i, found)| {
+                    let mut child = registry::describe_command(found.command, found.recent);
+                    if let Some(accessibility) = &mut child.accessibility {
+                        if i == self.state.selected() {
+                            accessibility.states.push("selected".into());
+                        }
+                    }
+                    child
+                })
+                .collect();
+        }
+        palette.push_child(commands)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::TermBackend;
+
+    fn state() -> CommandPaletteState {
+        CommandPaletteState::new(
+            CommandRegistry::new()
+                .with(
+                    PaletteCommand::new("colors", "Colors")
+                        .section("Navigation")
+                        .icon("palette"),
+                )
+                .with(PaletteCommand::new("sidebar", "Toggle Sidebar").shortcut("⌘B")),
+        )
+    }
+
+    #[test]
+    fn test_view_lists_results() {
+        let mut state = state();
+        let _ = state.update(PaletteMessage::Toggle);
+        let _ = state.update(PaletteMessage::QueryChanged("sid".into()));
+        let context = Context::default();
+        let text = CommandPalette::<(), TermBackend>::new(&state, |_| ()).view(&context);
+        assert!(text.contains("ebar") && text.contains("⌘B"));
+        assert!(!text.contains("Colors"));
+
+        let _ = state.update(PaletteMessage::QueryChanged("zzz".into()));
+        let text = CommandPalette::<(), TermBackend>::new(&state, |_| ()).view(&context);
+        assert!(text.contains("No matching commands"));
+    }
+
+    #[test]
+    fn test_describe_lists_commands() {
+        let mut state = state();
+        let context = Context::default();
+        let closed = CommandPalette::<(), TermBackend>::new(&state, |_| ()).describe(&context);
+        let commands = &closed.children[0];
+        assert_eq!(commands.children.len(), 2);
+        assert_eq!(commands.children[0].id.as_deref(), Some("colors"));
+
+        let _ = state.update(PaletteMessage::Toggle);
+        let _ = state.update(PaletteMessage::QueryChanged("tog".into()));
+        let open = CommandPalette::<(), TermBackend>::new(&state, |_| ()).describe(&context);
+        assert_eq!(open.children[0].content.as_deref(), Some("tog"));
+        let commands = &open.children[1];
+        assert_eq!(commands.children.len(), 1);
+        assert_eq!(commands.children[0].id.as_deref(), Some("sidebar"));
+        let states = &commands.children[0].accessibility.as_ref().unwrap().states;
+        assert!(states.iter().any(|state| state == "selected"));
+    }
+}
@@ -0,0 +1,114 @@
+//! Ranking text by how well it matches what's been typed.
+
+/// Where a query matched some text, and how well.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FuzzyMatch {
+    /// Higher is better.
+    pub score: i32,
+    /// The characters of the text that matched, by index.
+    pub indices: Vec<usize>,
+}
+
+/// How well `query` matches `text` if its characters appear in `text` in
+/// order, ignoring case. Matches at the start of words, runs of matched
+/// characters and matches near the start score higher, so "col" ranks
+/// "Colors" above "Toggle Column".
+pub fn fuzzy_match(query: &str, text: &str) -> Option<FuzzyMatch> {
+    let query: Vec<char> = query
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .flat_map(char::to_lowercase)
+        .collect();
+    if query.is_empty() {
+        return Some(FuzzyMatch {
+            score: 0,
+            indices: Vec::new(),
+        });
+    }
+    let chars: Vec<char> = text.chars().collect();
+    let lower: Vec<char> = chars
+        .iter()
+        .map(|c| c.to_lowercase().next().unwrap_or(*c))
+        .collect();
+
+    // Greedy from each place the first character matches, keeping the best
+    (0..chars.len())
+        .filter(|&start| lower[start] == query[0])
+        .filter_map(|start| matched_from(&query, &chars, &lower, start))
+        .max_by_key(|found| found.score)
+}
+
+fn matched_from(
+    query: &[char],
+    chars: &[char],
+    lower: &[char],
+    start: usize,
+) -> Option<FuzzyMatch> {
+    let mut indices = Vec::with_capacity(query.len());
+    let mut at = start;
+    for wanted in query {
+        // A later word start beats the next plain match
+        let next = (at..chars.len()).find(|&i| lower[i] == *wanted)?;
+        let word = (next..chars.len()).find(|&i| lower[i] == *wanted && is_word_start(chars, i));
+        let index = match (indices.last(), word) {
+            (Some(last), _) if next == last + 1 => next,
+            (_, Some(word)) => word,
+            _ => next,
+        };
+        indices.push(index);
+        at = index + 1;
+    }
+
+    let mut score = 0;
+    for (n, &index) in indices.iter().enumerate() {
+        score += 1;
+        if is_word_start(chars, index) {
+            score += 8;
+        }
+        if n > 0 && index == indices[n - 1] + 1 {
+            score += 5;
+        } else if n > 0 {
+            score -= (index - indices[n - 1] - 1).min(3) as i32;
+        }
+    }
+    score -= indices[0].min(5) as i32;
+    if indices.len() == chars.len() {
+        score += 10;
+    }
+    Some(FuzzyMatch { score, indices })
+}
+
+/// At the start of the text, after a separator or at a capital after a
+/// lowercase letter.
+fn is_word_start(chars: &[char], index: usize) -> bool {
+    match index.checked_sub(1).map(|i| chars[i]) {
+        None => true,
+        Some(previous) => {
+            !previous.is_alphanumeric() || (previous.is_lowercase() && chars[index].is_uppercase())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn score(query: &str, text: &str) -> i32 {
+        fuzzy_match(query, text).map_or(i32::MIN, |found| found.score)
+    }
+
+    #[test]
+    fn test_matches_prefer_word_starts_and_runs() {
+        assert_eq!(
+            fuzzy_match("tsb", "Toggle Sidebar").unwrap().indices,
+            [0, 7, 11]
+        );
+        assert!(score("tsb", "Toggle Sidebar") > score("tsb", "Settings bar"));
+        assert!(score("col", "Colors") > score("col", "Toggle Column"));
+        assert!(score("dark", "Use dark theme") > score("dark", "Dashboard (work)"));
+        assert!(score("DataTable", "DataTable") > score("data", "DataTable"));
+
+        assert!(fuzzy_match("xyz", "Colors").is_none());
+        assert_eq!(fuzzy_match("  ", "Colors").unwrap().score, 0);
+    }
+}
@@ -0,0 +1,312 @@
+//! The commands an app offers, for the [`CommandPalette`](super::CommandPalette)
+//! and for agents asking what can be done.
+use super::fuzzy::fuzzy_match;
+use crate::core::{AccessibilityNode, AccessibilityRole, SemanticNode};
+use serde::{Deserialize, Serialize};
+
+/// Most commands remembered as recently used.
+const MAX_RECENT: usize = 8;
+
+/// How much a keyword match counts for against a match in the title.
+const KEYWORD_PENALTY: i32 = 4;
+
+/// Something the user can do from the palette, like going to a page or
+/// switching the theme.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PaletteCommand {
+    /// Unique within the registry, and what the app is handed back when the
+    /// command is run.
+    pub id: String,
+    pub title: String,
+    /// Other words to find the command by, which aren't shown.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub keywords: Vec<String>,
+    /// The keys for the command, as shown, e.g. "⌘B".
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub shortcut: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub icon: Option<String>,
+    /// A group like "Navigation", shown beside the title.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub section: Option<String>,
+}
+
+impl PaletteCommand {
+    pub fn new(id: impl Into<String>, title: impl Into<String>) -> Self {
+        Self {
+            id: id.into(),
+            title: title.into(),
+            keywords: Vec::new(),
+            shortcut: None,
+            icon: None,
+            section: None,
+        }
+    }
+
+    pub fn keywords(mut self, keywords: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.keywords = keywords.into_iter().map(Into::into).collect();
+        self
+    }
+
+    pub fn shortcut(mut self, shortcut: impl Into<String>) -> Self {
+        self.shortcut = Some(shortcut.into());
+        self
+    }
+
+    pub fn icon(mut self, icon: impl Into<String>) -> Self {
+        self.icon = Some(icon.into());
+        self
+    }
+
+    pub fn section(mut self, section: impl Into<String>) -> Self {
+        self.section = Some(section.into());
+        self
+    }
+}
+
+/// A command found by [`CommandRegistry::search`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct CommandMatch<'a> {
+    pub command: &'a PaletteCommand,
+    pub score: i32,
+    /// The characters of the title that matched, by index.
+    pub highlights: Vec<usize>,
+    pub recent: bool,
+}
+
+/// The commands an app has registered, and which were used last.
+#[derive(Debug, Clone, PartialEq, Default, Serialize)]
+pub struct CommandRegistry {
+    commands: Vec<PaletteCommand>,
+    /// Ids of commands, the most recently used first.
+    recent: Vec<String>,
+}
+
+impl CommandRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds `command`, in place of any with the same id.
+    pub fn register(&mut self, command: PaletteCommand) {
+        match self.commands.iter_mut().find(|c| c.id == command.id) {
+            Some(existing) => *existing = command,
+            None => self.commands.push(command),
+        }
+    }
+
+    pub fn with(mut self, command: PaletteCommand) -> Self {
+        self.register(command);
+        self
+    }
+
+    pub fn unregister(&mut self, id: &str) -> Option<PaletteCommand> {
+        self.recent.retain(|recent| recent != id);
+        let index = self.commands.iter().position(|c| c.id == id)?;
+        Some(self.commands.remove(index))
+    }
+
+    pub fn get(&self, id: &str) -> Option<&PaletteCommand> {
+        self.commands.iter().find(|c| c.id == id)
+    }
+
+    /// Every command, in the order they were registered.
+    pub fn commands(&self) -> &[PaletteCommand] {
+        &self.commands
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.commands.is_empty()
+    }
+
+    /// Puts command `id` first among the recently used.
+    pub fn mark_used(&mut self, id: &str) {
+        if self.get(id).is_none() {
+            return;
+        }
+        self.recent.retain(|recent| recent != id);
+        self.recent.insert(0, id.to_string());
+        self.recent.truncate(MAX_RECENT);
+    }
+
+    /// The recently used commands, the most recent first.
+    pub fn recent(&self) -> impl Iterator<Item = &PaletteCommand> {
+        self.recent.iter().filter_map(|id| self.get(id))
+    }
+
+    /// The commands matching `query`, best first. A command matches by its
+    /// title or, for less, by a keyword, and recently used commands rank
+    /// above others that match about as well. With no query, the recently
+    /// used come first and then the rest as registered.
+    pub fn search(&self, query: &str) -> Vec<CommandMatch<'_>> {
+        let recency = |id: &str| self.recent.iter().position(|recent| recent == id);
+
+        if query.trim().is_empty() {
+            let recent = self.recent().map(|command| CommandMatch {
+                command,
+                score: 0,
+                highlights: Vec::new(),
+                recent: true,
+            });
+            let rest = self
+                .commands
+                .iter()
+                .filter(|command| recency(&command.id).is_none())
+                .map(|command| CommandMatch {
+                    command,
+                    score: 0,
+                    highlights: Vec::new(),
+                    recent: false,
+                });
+            return recent.chain(rest).collect();
+        }
+
+        let mut matches: Vec<CommandMatch> = self
+            .commands
+            .iter()
+            .filter_map(|command| {
+                let title = fuzzy_match(query, &command.title);
+                let keyword = command
+                    .keywords
+                    .iter()
+                    .filter_map(|keyword| fuzzy_match(query, keyword))
+                    .map(|found| found.score - KEYWORD_PENALTY)
+                    .max();
+                let (score, highlights) = match (title, keyword) {
+                    (Some(title), Some(keyword)) if keyword > title.score => (keyword, Vec::new()),
+                    (Some(title), _) => (title.score, title.indices),
+                    (None, Some(keyword)) => (keyword, Vec::new()),
+                    (None, None) => return None,
+                };
+                let recent = recency(&command.id);
+                Some(CommandMatch {
+                    command,
+                    score: score + recent.map_or(0, |rank| 2 * (MAX_RECENT - rank) as i32),
+                    highlights,
+                    recent: recent.is_some(),
+                })
+            })
+            .collect();
+        // Stable, so equal scores stay as registered
+        matches.sort_by_key(|found| std::cmp::Reverse(found.score));
+        matches
+    }
+
+    /// Every command, for agents to find and run by id.
+    pub fn describe(&self) -> SemanticNode {
+        let mut node = SemanticNode::new("commands")
+            .with_label(format!("{} commands", self.commands.len()))
+            .with_accessibility(AccessibilityNode {
+                role: AccessibilityRole::Menu,
+                label: "Commands".into(),
+                ..Default::default()
+            });
+        for command in self.recent().chain(
+            self.commands
+                .iter()
+                .filter(|command| !self.recent.contains(&command.id)),
+        ) {
+            node = node.push_child(describe_command(command, self.recent.contains(&command.id)));
+        }
+        node
+    }
+}
+
+pub(crate) fn describe_command(command: &PaletteCommand, recent: bool) -> SemanticNode {
+    let mut hint = command.section.clone().unwrap_or_default();
+    if let Some(shortcut) = &command.shortcut {
+        if !hint.is_empty() {
+            hint.push_str(", ");
+        }
+        hint.push_str(shortcut);
+    }
+    let mut node = SemanticNode::new("command")
+        .with_label(command.title.clone())
+        .with_accessibility(AccessibilityNode {
+            role: AccessibilityRole::MenuItem,
+            label: command.title.clone().into(),
+            hint: (!hint.is_empty()).then(|| hint.into()),
+            states: if recent {
+                vec!["recent".into()]
+            } else {
+                Vec::new()
+            },
+            ..Default::default()
+        });
+    if !command.keywords.is_empty() {
+        node = node.with_content(command.keywords.join(", "));
+    }
+    node.id = Some(command.id.clone().into());
+    node
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn registry() -> CommandRegistry {
+        CommandRegistry::new()
+            .with(PaletteCommand::new("colors", "Colors").section("Navigation"))
+            .with(
+                PaletteCommand::new("sidebar", "Toggle Sidebar")
+                    .keywords(["navigation", "panel"])
+                    .shortcut("⌘B"),
+            )
+            .with(PaletteCommand::new("columns", "Toggle Column"))
+            .with(PaletteCommand::new("dark", "Use Dark Theme").keywords(["night"]))
+    }
+
+    fn ids(matches: &[CommandMatch]) -> Vec<String> {
+        matches.iter().map(|m| m.command.id.clone()).collect()
+    }
+
+    #[test]
+    fn test_search_ranks_matches_and_recent_first() {
+        let mut registry = registry();
+        assert_eq!(
+            ids(&registry.search("")),
+            ["colors", "sidebar", "columns", "dark"]
+        );
+        assert_eq!(ids(&registry.search("col")), ["colors", "columns"]);
+        assert_eq!(registry.search("col")[0].highlights, [0, 1, 2]);
+
+        // Found by a keyword, with nothing in the title to highlight
+        let night = registry.search("night");
+        assert_eq!(ids(&night), ["dark"]);
+        assert!(night[0].highlights.is_empty());
+
+        registry.mark_used("columns");
+        registry.mark_used("dark");
+        registry.mark_used("missing");
+        assert_eq!(
+            ids(&registry.search("")),
+            ["dark", "columns", "colors", "sidebar"]
+        );
+        assert_eq!(ids(&registry.search("col")), ["columns", "colors"]);
+        assert!(registry.search("col")[0].recent);
+
+        registry.unregister("dark");
+        assert_eq!(registry.recent().count(), 1);
+    }
+
+    #[test]
+    fn test_register_replaces_and_describes() {
+        let mut registry = registry();
+        registry.register(PaletteCommand::new("colors", "Open Colors"));
+        assert_eq!(registry.commands().len(), 4);
+        assert_eq!(registry.get("colors").unwrap().title, "Open Colors");
+
+        registry.mark_used("sidebar");
+        let node = registry.describe();
+        let first = &node.children[0];
+        assert_eq!(first.id.as_deref(), Some("sidebar"));
+        let accessibility = first.accessibility.as_ref().unwrap();
+        assert_eq!(accessibility.hint.as_deref(), Some("⌘B"));
+        assert_eq!(accessibility.states, ["recent"]);
+        assert_eq!(first.content.as_deref(), Some("navigation, panel"));
+
+        let json = serde_json::to_value(&registry).unwrap();
+        assert_eq!(json["recent"][0], "sidebar");
+        assert!(json["commands"][1].get("icon").is_none());
+    }
+}
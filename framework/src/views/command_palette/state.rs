@@ -0,0 +1,201 @@
+//! Whether a [`CommandPalette`](super::CommandPalette) is open, and what's
+//! been typed into it.
+//!
+//! A [`CommandPaletteState`] in app state holds the [`CommandRegistry`] along
+//! with the query and the selected result. The palette reports what the user
+//! does as [`PaletteMessage`]s, to be handed back to
+//! [`CommandPaletteState::update`], which also takes keys from
+//! [`CommandPaletteState::subscription`]. Running a command sends
+//! [`PaletteMessage::Ran`] with its id, for the app to act on.
+use super::registry::{CommandMatch, CommandRegistry};
+use iced::{event, keyboard, Event, Subscription, Task};
+
+/// The palette's text input, focused when it opens.
+pub const INPUT_ID: &str = "command-palette-input";
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum PaletteMessage {
+    /// Opens the palette, or closes it.
+    Toggle,
+    Close,
+    QueryChanged(String),
+    KeyPressed(keyboard::Key, keyboard::Modifiers),
+    /// Runs command `id`, as when it's clicked.
+    Run(String),
+    /// Command `id` was run, for the app to do what it stands for.
+    Ran(String),
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct CommandPaletteState {
+    pub registry: CommandRegistry,
+    pub is_open: bool,
+    pub query: String,
+    /// The highlighted result, by position in [`Self::results`].
+    selected: usize,
+}
+
+impl CommandPaletteState {
+    pub fn new(registry: CommandRegistry) -> Self {
+        Self {
+            registry,
+            ..Self::default()
+        }
+    }
+
+    /// The commands matching the query, best first.
+    pub fn results(&self) -> Vec<CommandMatch<'_>> {
+        self.registry.search(&self.query)
+    }
+
+    pub fn selected(&self) -> usize {
+        self.selected
+    }
+
+    pub fn open(&mut self) -> Task<PaletteMessage> {
+        self.is_open = true;
+        self.query.clear();
+        self.selected = 0;
+        iced::widget::operation::focus(iced::widget::Id::new(INPUT_ID))
+    }
+
+    pub fn close(&mut self) {
+        self.is_open = false;
+        self.query.clear();
+        self.selected = 0;
+    }
+
+    pub fn update(&mut self, message: PaletteMessage) -> Task<PaletteMessage> {
+        match message {
+            PaletteMessage::Toggle if self.is_open => self.close(),
+            PaletteMessage::Toggle => return self.open(),
+            PaletteMessage::Close => self.close(),
+            PaletteMessage::QueryChanged(query) => {
+                self.query = query;
+                self.selected = 0;
+            }
+            PaletteMessage::KeyPressed(key, modifiers) => return self.key_pressed(key, modifiers),
+            PaletteMessage::Run(id) => {
+                if self.registry.get(&id).is_none() {
+                    return Task::none();
+                }
+                self.registry.mark_used(&id);
+                self.close();
+                return Task::done(PaletteMessage::Ran(id));
+            }
+            PaletteMessage::Ran(_) => {}
+        }
+        Task::none()
+    }
+
+    /// Cmd/Ctrl+K wherever focus is, and while open, the keys that move
+    /// through the results, even as they're typed into the query.
+    pub fn subscription() -> Subscription<PaletteMessage> {
+        event::listen_with(|event, _status, _window| match event {
+            Event::Keyboard(keyboard::Event::KeyPressed { key, modifiers, .. })
+                if is_palette_key(&key, modifiers) =>
+            {
+                Some(PaletteMessage::KeyPressed(key, modifiers))
+            }
+            _ => None,
+        })
+    }
+
+    fn key_pressed(
+        &mut self,
+        key: keyboard::Key,
+        modifiers: keyboard::Modifiers,
+    ) -> Task<PaletteMessage> {
+        use keyboard::key::Named;
+
+        if modifiers.command() && key.as_ref() == keyboard::Key::Character("k") {
+            return self.update(PaletteMessage::Toggle);
+        }
+        if !self.is_open {
+            return Task::none();
+        }
+        let count = self.results().len();
+        match key.as_ref() {
+            keyboard::Key::Named(Named::ArrowDown) if count > 0 => {
+                self.selected = (self.selected + 1) % count;
+            }
+            keyboard::Key::Named(Named::ArrowUp) if count > 0 => {
+                self.selected = (self.selected + count - 1) % count;
+            }
+            keyboard::Key::Named(Named::Enter) => {
+                let selected = self
+                    .results()
+                    .get(self.selected)
+                    .map(|found| found.command.id.clone());
+                if let Some(id) = selected {
+                    return self.update(PaletteMessage::Run(id));
+                }
+            }
+            keyboard::Key::Named(Named::Escape) => self.close(),
+            _ => {}
+        }
+        Task::none()
+    }
+}
+
+fn is_palette_key(key: &keyboard::Key, modifiers: keyboard::Modifiers) -> bool {
+    use keyboard::key::Named;
+
+    match key.as_ref() {
+        keyboard::Key::Character("k") => modifiers.command(),
+        keyboard::Key::Named(Named::ArrowDown | Named::ArrowUp | Named::Enter | Named::Escape) => {
+            true
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::views::command_palette::PaletteCommand;
+    use keyboard::key::Named;
+
+    fn state() -> CommandPaletteState {
+        CommandPaletteState::new(
+            CommandRegistry::new()
+                .with(PaletteCommand::new("colors", "Colors"))
+                .with(PaletteCommand::new("sidebar", "Toggle Sidebar"))
+                .with(PaletteCommand::new("theme", "Toggle Theme")),
+        )
+    }
+
+    fn press(state: &mut CommandPaletteState, key: keyboard::Key, modifiers: keyboard::Modifiers) {
+        let _ = state.update(PaletteMessage::KeyPressed(key, modifiers));
+    }
+
+    #[test]
+    fn test_keys_open_select_and_run() {
+        let mut state = state();
+        press(&mut state, Named::ArrowDown.into(), Default::default());
+        assert_eq!(state.selected(), 0);
+
+        press(
+            &mut state,
+            keyboard::Key::Character("k".into()),
+            keyboard::Modifiers::COMMAND,
+        );
+        assert!(state.is_open);
+
+        let _ = state.update(PaletteMessage::QueryChanged("tog".into()));
+        press(&mut state, Named::ArrowUp.into(), Default::default());
+        assert_eq!(state.selected(), 1);
+        press(&mut state, Named::Enter.into(), Default::default());
+        assert!(!state.is_open && state.query.is_empty());
+        assert_eq!(state.registry.recent().next().unwrap().id, "theme");
+
+        // Run from the results, the last used is first
+        let _ = state.update(PaletteMessage::Toggle);
+        assert_eq!(state.results()[0].command.id, "theme");
+        press(&mut state, Named::Escape.into(), Default::default());
+        assert!(!state.is_open);
+
+        let _ = state.update(PaletteMessage::Run("missing".into()));
+        assert_eq!(state.registry.recent().count(), 1);
+    }
+}
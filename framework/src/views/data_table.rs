@@ -2,6 +2,21 @@ use crate::prelude::*;
 use std::collections::HashSet;
 use std::sync::Arc;
 
+pub mod export;
+pub mod source;
+pub mod value;
+
+pub use export::ExportFormat;
+pub use source::{DataTableMessage, DataTableState, SortColumn, TableSource};
+pub use value::CellValue;
+
+/// Rows built either side of the ones in view, so fast scrolling doesn't
+/// show gaps.
+const OVERSCAN: usize = 8;
+
+/// The width of the resize handle after each column of a sourced table.
+const HANDLE_WIDTH: f32 = 7.0;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 pub enum DataTablePreset {
     #[default]
@@ -30,9 +45,23 @@ where
 {
     pub id: Option<String>,
     pub cells: Vec<Box<dyn View<M, B> + Send + Sync>>,
+    /// The raw values behind `cells`, for export; cells without one export
+    /// what they describe.
+    pub values: Vec<CellValue>,
     pub on_press: Option<M>,
 }
 
+/// Where a sourced table gets its rows, and how they are shown.
+pub struct DataTableSource<M, B>
+where
+    M: 'static + Send + Sync,
+    B: Backend + Send + Sync,
+{
+    pub source: Arc<dyn TableSource<M, B>>,
+    pub state: DataTableState,
+    pub on_message: Arc<dyn Fn(DataTableMessage) -> M + Send + Sync>,
+}
+
 pub struct DataTable<M, B>
 where
     M: 'static + Send + Sync,
@@ -55,6 +84,14 @@ where
     // Selection
     pub selected_ids: HashSet<String>,
     pub on_selection_change: Option<Arc<dyn Fn(HashSet<String>) -> M + Send + Sync>>,
+    // Export
+    pub on_copy: Option<Arc<dyn Fn(String) -> M + Send + Sync>>,
+    pub exportable: bool,
+    // Rows pulled from a source instead
+    pub source: Option<DataTableSource<M, B>>,
+    pub row_height: f32,
+    pub height: Length,
+    pub show_filters: bool,
 }
 
 impl<M, B> DataTable<M, B>
@@ -79,6 +116,12 @@ where
             on_page_change: None,
             selected_ids: HashSet::new(),
             on_selection_change: None,
+            on_copy: None,
+            exportable: false,
+            source: None,
+            row_height: 40.0,
+            height: Length::Fixed(480.0),
+            show_filters: true,
         }
     }
 
@@ -154,6 +197,7 @@ where
         self.rows.push(DataTableRow {
             id: None,
             cells,
+            values: Vec::new(),
             on_press: None,
         });
         self
@@ -167,6 +211,7 @@ where
         self.rows.push(DataTableRow {
             id: Some(id.into()),
             cells,
+            values: Vec::new(),
             on_press: None,
         });
         self
@@ -180,6 +225,7 @@ where
         self.rows.push(DataTableRow {
             id: None,
             cells,
+            values: Vec::new(),
             on_press: Some(on_press),
         });
         self
@@ -194,6 +240,7 @@ where
         self.rows.push(DataTableRow {
             id: Some(id.into()),
             cells,
+            values: Vec::new(),
             on_press: Some(on_press),
         });
         self
@@ -241,6 +288,74 @@ where
         self
     }
 
+    /// Sets the raw values of the last row, for export.
+    pub fn values(mut self, values: Vec<CellValue>) -> Self {
+        if let Some(row) = self.rows.last_mut() {
+            row.values = values;
+        }
+        self
+    }
+
+    /// Offers to copy the selected rows as a Markdown table, which
+    /// `on_copy` gets, e.g. for [`clipboard::write`](iced::clipboard::write).
+    /// Sourced tables offer it anyway, as [`DataTableMessage::CopySelection`].
+    pub fn on_copy(mut self, on_copy: impl Fn(String) -> M + Send + Sync + 'static) -> Self {
+        self.on_copy = Some(Arc::new(on_copy));
+        self
+    }
+
+    /// Offers export buttons under a sourced table, sending
+    /// [`DataTableMessage::Export`].
+    pub fn exportable(mut self, exportable: bool) -> Self {
+        self.exportable = exportable;
+        self
+    }
+
+    /// Writes the rows as shown: in a sourced table, filtered and sorted.
+    pub fn export(&self, format: ExportFormat, context: &Context) -> String {
+        self.export_rows(format, false, context)
+    }
+
+    /// Writes the selected rows as shown.
+    pub fn export_selected(&self, format: ExportFormat, context: &Context) -> String {
+        self.export_rows(format, true, context)
+    }
+
+    /// Pulls rows from `source` as they scroll into view, instead of taking
+    /// them up front, sorted and filtered as `state` says. Columns can be
+    /// sorted, filtered, resized and reordered; `on_message` reports that,
+    /// for [`DataTableState::update`].
+    pub fn source(
+        mut self,
+        source: Arc<dyn TableSource<M, B>>,
+        state: &DataTableState,
+        on_message: impl Fn(DataTableMessage) -> M + Send + Sync + 'static,
+    ) -> Self {
+        self.source = Some(DataTableSource {
+            source,
+            state: state.clone(),
+            on_message: Arc::new(on_message),
+        });
+        self
+    }
+
+    /// The height of every row of a sourced table.
+    pub fn row_height(mut self, height: f32) -> Self {
+        self.row_height = height;
+        self
+    }
+
+    /// The height of a sourced table's scrolling rows.
+    pub fn height(mut self, height: Length) -> Self {
+        self.height = height;
+        self
+    }
+
+    pub fn show_filters(mut self, show: bool) -> Self {
+        self.show_filters = show;
+        self
+    }
+
     fn header_cell(
         &self,
         context: &Context,
@@ -365,16 +480,15 @@ where
     }
 }
 
-impl<M, B> View<M, B> for DataTable<M, B>
+impl<M, B> DataTable<M, B>
 where
     M: 'static + Clone + Send + Sync,
     B: Backend + Send + Sync + 'static,
 {
-    fn view(&self, context: &Context) -> B::AnyView<M> {
-        let theme = context.theme;
-        let palette = theme.colors;
-
-        let (header_bg, border_color, border_width, radius) = match self.preset {
+    /// Header background, border colour, border width and radius.
+    fn preset_style(&self, context: &Context) -> (Option<Color>, Option<Color>, f32, f32) {
+        let palette = context.theme.colors;
+        match self.preset {
             DataTablePreset::Professional => (
                 Some(palette.surface.scale_alpha(0.3)),
                 Some(palette.border.scale_alpha(0.1)),
@@ -388,7 +502,567 @@ where
                 1.0,
                 12.0,
             ),
-        };
+        }
+    }
+
+    fn line(&self, width: Length, height: Length, context: &Context) -> B::AnyView<M> {
+        B::container(
+            B::space(Length::Fixed(0.0), Length::Fixed(0.0), context),
+            Padding::default(),
+            width,
+            height,
+            Some(context.theme.colors.border.scale_alpha(0.15)),
+            0.0,
+            0.0,
+            None,
+            None,
+            iced::Alignment::Start,
+            iced::Alignment::Start,
+            context,
+        )
+    }
+
+    fn export_columns(&self) -> Vec<(&str, Alignment)> {
+        self.columns
+            .iter()
+            .map(|col| (col.label.as_str(), col.alignment))
+            .collect()
+    }
+
+    fn export_rows(&self, format: ExportFormat, selected: bool, context: &Context) -> String {
+        let columns = self.export_columns();
+        if let Some(source) = &self.source {
+            return if selected {
+                source
+                    .state
+                    .export_selected(source.source.as_ref(), &columns, format)
+            } else {
+                source
+                    .state
+                    .export(source.source.as_ref(), &columns, format)
+            };
+        }
+
+        let rows = self
+            .rows
+            .iter()
+            .filter(|row| {
+                !selected
+                    || row
+                        .id
+                        .as_ref()
+                        .is_some_and(|id| self.selected_ids.contains(id))
+            })
+            .map(|row| self.row_values(row, context));
+        export::export(format, &columns, rows)
+    }
+
+    /// The raw values of `row`, falling back to what its cells describe.
+    fn row_values(&self, row: &DataTableRow<M, B>, context: &Context) -> Vec<CellValue> {
+        (0..row.cells.len().max(row.values.len()))
+            .map(|i| match (row.values.get(i), row.cells.get(i)) {
+                (Some(value), _) => value.clone(),
+                (None, Some(cell)) => {
+                    let node = cell.describe(context);
+                    node.content.or(node.label).map(String::from).into()
+                }
+                (None, None) => CellValue::Empty,
+            })
+            .collect()
+    }
+
+    /// A row as cells labelled by column, holding their raw values.
+    fn describe_row(
+        &self,
+        id: Option<String>,
+        values: Vec<CellValue>,
+        order: &[usize],
+    ) -> SemanticNode {
+        let mut node = SemanticNode::new("row").extend_children(order.iter().map(|&column| {
+            SemanticNode::new("cell")
+                .with_label(self.columns[column].label.clone())
+                .with_content(values.get(column).cloned().unwrap_or_default().to_string())
+        }));
+        node.id = id.map(Into::into);
+        node
+    }
+
+    /// The width of `column`, as resized.
+    fn source_width(&self, state: &DataTableState, column: usize) -> Length {
+        state
+            .widths
+            .get(&column)
+            .map(|width| Length::Fixed(*width))
+            .unwrap_or(self.columns[column].width)
+    }
+
+    fn source_header_cell(
+        &self,
+        context: &Context,
+        state: &DataTableState,
+        column: usize,
+    ) -> B::AnyView<M> {
+        let col = &self.columns[column];
+        let mut content_children = vec![B::text(
+            col.label.clone(),
+            12.0,
+            None,
+            true,
+            false,
+            None,
+            None,
+            Length::Shrink,
+            iced::Alignment::Start,
+            context,
+        )];
+
+        if let Some((rank, ascending)) = state.sorted(column) {
+            content_children.push(B::icon(
+                if ascending {
+                    "chevron-up".to_string()
+                } else {
+                    "chevron-down".to_string()
+                },
+                12.0,
+                Some(context.theme.colors.primary),
+                context,
+            ));
+
+            // Which sort decides first, once there are several
+            if state.sort.len() > 1 {
+                content_children.push(B::text(
+                    (rank + 1).to_string(),
+                    10.0,
+                    Some(context.theme.colors.primary),
+                    false,
+                    false,
+                    None,
+                    None,
+                    Length::Shrink,
+                    iced::Alignment::Start,
+                    context,
+                ));
+            }
+        }
+
+        B::container(
+            B::hstack(
+                content_children,
+                4.0,
+                Padding::default(),
+                Length::Shrink,
+                Length::Shrink,
+                iced::Alignment::Start,
+                iced::Alignment::Center,
+                context,
+            ),
+            [12, 12].into(),
+            self.source_width(state, column),
+            Length::Shrink,
+            None,
+            0.0,
+            0.0,
+            None,
+            None,
+            col.alignment,
+            iced::Alignment::Start,
+            context,
+        )
+    }
+
+    fn source_view(&self, source: &DataTableSource<M, B>, context: &Context) -> B::AnyView<M> {
+        let palette = context.theme.colors;
+        let (header_bg, border_color, border_width, radius) = self.preset_style(context);
+        let state = &source.state;
+        let on_message = &source.on_message;
+        let order = state.column_order(self.columns.len());
+
+        // --- Sticky Header: outside the scrolling rows ---
+        let mut header_children = Vec::new();
+        for &column in &order {
+            header_children.push(B::mouse_area(
+                self.source_header_cell(context, state, column),
+                None,
+                Some(on_message(DataTableMessage::HeaderPressed(column))),
+                Some(on_message(DataTableMessage::HeaderReleased(column))),
+                context,
+            ));
+
+            // Dragging the handle resizes the column before it
+            header_children.push(B::mouse_area(
+                B::container(
+                    self.line(Length::Fixed(1.0), Length::Fixed(16.0), context),
+                    Padding::default(),
+                    Length::Fixed(HANDLE_WIDTH),
+                    Length::Shrink,
+                    None,
+                    0.0,
+                    0.0,
+                    None,
+                    None,
+                    iced::Alignment::Center,
+                    iced::Alignment::Center,
+                    context,
+                ),
+                None,
+                Some(on_message(DataTableMessage::ResizePressed(column))),
+                None,
+                context,
+            ));
+        }
+
+        // Pointer positions are scaled, like the widths they resize
+        let on_move = on_message.clone();
+        let scale = context.theme.scaling;
+        let header = B::mouse_area(
+            B::hstack(
+                header_children,
+                0.0,
+                Padding::default(),
+                Length::Fill,
+                Length::Shrink,
+                iced::Alignment::Start,
+                iced::Alignment::Center,
+                context,
+            ),
+            Some(Arc::new(move |point: iced::Point| {
+                on_move(DataTableMessage::PointerMoved(point.x / scale))
+            })),
+            None,
+            Some(on_message(DataTableMessage::PointerReleased)),
+            context,
+        );
+
+        let mut table_children = vec![B::container(
+            header,
+            Padding::default(),
+            Length::Fill,
+            Length::Shrink,
+            header_bg,
+            0.0,
+            0.0,
+            None,
+            None,
+            iced::Alignment::Start,
+            iced::Alignment::Start,
+            context,
+        )];
+
+        // --- Filter Inputs ---
+        if self.show_filters {
+            let mut filter_children = Vec::new();
+            for &column in &order {
+                let on_filter = on_message.clone();
+                filter_children.push(B::container(
+                    B::text_input(
+                        state.filters.get(&column).cloned().unwrap_or_default(),
+                        "Filter".to_string(),
+                        move |filter| on_filter(DataTableMessage::Filter(column, filter)),
+                        None,
+                        None,
+                        false,
+                        Variant::Ghost,
+                        None,
+                        context,
+                    ),
+                    [4, 4].into(),
+                    self.source_width(state, column),
+                    Length::Shrink,
+                    None,
+                    0.0,
+                    0.0,
+                    None,
+                    None,
+                    iced::Alignment::Start,
+                    iced::Alignment::Center,
+                    context,
+                ));
+                filter_children.push(B::space(
+                    Length::Fixed(HANDLE_WIDTH),
+                    Length::Shrink,
+                    context,
+                ));
+            }
+
+            table_children.push(self.line(Length::Fill, Length::Fixed(1.0), context));
+            table_children.push(B::hstack(
+                filter_children,
+                0.0,
+                Padding::default(),
+                Length::Fill,
+                Length::Shrink,
+                iced::Alignment::Start,
+                iced::Alignment::Center,
+                context,
+            ));
+        }
+
+        table_children.push(self.line(Length::Fill, Length::Fixed(1.0), context));
+
+        // --- Rows in View, between spacers standing in for the rest ---
+        let rows = state.rows();
+        let visible = state.visible_rows(self.row_height, OVERSCAN);
+        let mut rows_children = vec![B::space(
+            Length::Fill,
+            Length::Fixed(visible.start as f32 * self.row_height),
+            context,
+        )];
+
+        for position in visible.clone() {
+            let mut cells: Vec<Option<_>> = source
+                .source
+                .row(rows[position])
+                .into_iter()
+                .map(Some)
+                .collect();
+            let mut row_children = Vec::new();
+
+            for (i, &column) in order.iter().enumerate() {
+                let Some(cell) = cells.get_mut(column).and_then(Option::take) else {
+                    continue;
+                };
+                row_children.push(self.row_cell(
+                    context,
+                    &cell,
+                    self.source_width(state, column),
+                    i == 0,
+                    self.columns[column].alignment,
+                ));
+                row_children.push(if self.show_grid {
+                    B::container(
+                        self.line(Length::Fixed(1.0), Length::Fill, context),
+                        Padding::default(),
+                        Length::Fixed(HANDLE_WIDTH),
+                        Length::Fill,
+                        None,
+                        0.0,
+                        0.0,
+                        None,
+                        None,
+                        iced::Alignment::Center,
+                        iced::Alignment::Start,
+                        context,
+                    )
+                } else {
+                    B::space(Length::Fixed(HANDLE_WIDTH), Length::Shrink, context)
+                });
+            }
+
+            let row = rows[position];
+            rows_children.push(B::mouse_area(
+                B::container(
+                    B::hstack(
+                        row_children,
+                        0.0,
+                        Padding::default(),
+                        Length::Fill,
+                        Length::Fill,
+                        iced::Alignment::Start,
+                        iced::Alignment::Center,
+                        context,
+                    ),
+                    Padding::default(),
+                    Length::Fill,
+                    Length::Fixed(self.row_height),
+                    if state.selected.contains(&row) {
+                        Some(palette.primary.scale_alpha(0.12))
+                    } else if self.alternate_rows && position % 2 != 0 {
+                        Some(palette.surface.scale_alpha(0.1))
+                    } else {
+                        None
+                    },
+                    0.0,
+                    0.0,
+                    None,
+                    None,
+                    iced::Alignment::Start,
+                    iced::Alignment::Center,
+                    context,
+                ),
+                None,
+                Some(on_message(DataTableMessage::RowPressed(row))),
+                None,
+                context,
+            ));
+        }
+
+        rows_children.push(B::space(
+            Length::Fill,
+            Length::Fixed((rows.len() - visible.end) as f32 * self.row_height),
+            context,
+        ));
+
+        let on_scroll = on_message.clone();
+        table_children.push(B::scroll_view_with_offset(
+            B::vstack(
+                rows_children,
+                0.0,
+                Padding::default(),
+                Length::Fill,
+                Length::Shrink,
+                iced::Alignment::Start,
+                iced::Alignment::Start,
+                context,
+            ),
+            Length::Fill,
+            self.height,
+            None,
+            true,
+            Arc::new(move |offset, height| on_scroll(DataTableMessage::Scrolled(offset, height))),
+            context,
+        ));
+
+        // --- Footer ---
+        let total = source.source.row_count();
+        let mut footer_children = vec![B::text(
+            if rows.len() == total {
+                format!("{} rows", total)
+            } else {
+                format!("{} of {} rows", rows.len(), total)
+            },
+            12.0,
+            None,
+            false,
+            false,
+            None,
+            None,
+            Length::Fill,
+            iced::Alignment::Start,
+            context,
+        )];
+
+        if !state.selected.is_empty() {
+            footer_children.push(B::text(
+                format!("{} selected", state.selected.len()),
+                12.0,
+                None,
+                false,
+                true,
+                None,
+                None,
+                Length::Shrink,
+                iced::Alignment::Start,
+                context,
+            ));
+            footer_children.push(self.footer_button(
+                "Copy as Markdown",
+                on_message(DataTableMessage::CopySelection),
+                context,
+            ));
+        }
+
+        if self.exportable {
+            for format in [ExportFormat::Csv, ExportFormat::Tsv, ExportFormat::Json] {
+                footer_children.push(self.footer_button(
+                    format.label(),
+                    on_message(DataTableMessage::Export(format)),
+                    context,
+                ));
+            }
+        }
+
+        table_children.push(self.line(Length::Fill, Length::Fixed(1.0), context));
+        table_children.push(B::hstack(
+            footer_children,
+            8.0,
+            [4, 12].into(),
+            Length::Fill,
+            Length::Shrink,
+            iced::Alignment::Start,
+            iced::Alignment::Center,
+            context,
+        ));
+
+        B::container(
+            B::vstack(
+                table_children,
+                0.0,
+                Padding::default(),
+                Length::Fill,
+                Length::Shrink,
+                iced::Alignment::Start,
+                iced::Alignment::Start,
+                context,
+            ),
+            Padding::default(),
+            Length::Fill,
+            Length::Shrink,
+            None,
+            radius,
+            border_width,
+            border_color,
+            None,
+            iced::Alignment::Start,
+            iced::Alignment::Start,
+            context,
+        )
+    }
+
+    fn footer_button(&self, label: &str, message: M, context: &Context) -> B::AnyView<M> {
+        B::button(
+            B::text(
+                label.to_string(),
+                12.0,
+                None,
+                false,
+                false,
+                None,
+                None,
+                Length::Shrink,
+                iced::Alignment::Center,
+                context,
+            ),
+            Some(message),
+            Variant::Ghost,
+            Intent::Secondary,
+            Length::Shrink,
+            Length::Shrink,
+            true,
+            context,
+        )
+    }
+
+    fn describe_source(&self, source: &DataTableSource<M, B>) -> SemanticNode {
+        let state = &source.state;
+        let rows = state.rows();
+        let visible = state.visible_rows(self.row_height, 0);
+        let order = state.column_order(self.columns.len());
+
+        // Only the rows in view, like on screen
+        let children = rows[visible.clone()].iter().map(|&index| {
+            let values = (0..self.columns.len())
+                .map(|column| source.source.value(index, column))
+                .collect();
+            self.describe_row(Some(index.to_string()), values, &order)
+        });
+
+        SemanticNode::new("data_table")
+            .with_label(format!(
+                "Table with {} columns and {} of {} rows, showing {}-{}",
+                self.columns.len(),
+                rows.len(),
+                source.source.row_count(),
+                (visible.start + 1).min(visible.end),
+                visible.end
+            ))
+            .extend_children(children)
+    }
+}
+
+impl<M, B> View<M, B> for DataTable<M, B>
+where
+    M: 'static + Clone + Send + Sync,
+    B: Backend + Send + Sync + 'static,
+{
+    fn view(&self, context: &Context) -> B::AnyView<M> {
+        if let Some(source) = &self.source {
+            return self.source_view(source, context);
+        }
+
+        let theme = context.theme;
+        let palette = theme.colors;
+
+        let (header_bg, border_color, border_width, radius) = self.preset_style(context);
 
         let grid_color = palette.border.scale_alpha(0.15);
 
@@ -796,6 +1470,41 @@ where
             }
         }
 
+        // --- Copy Footer, once rows are selected ---
+        if let Some(on_copy) = &self.on_copy {
+            if !self.selected_ids.is_empty() {
+                table_children.push(self.line(Length::Fill, Length::Fixed(1.0), context));
+                table_children.push(B::hstack(
+                    vec![
+                        B::text(
+                            format!("{} selected", self.selected_ids.len()),
+                            12.0,
+                            None,
+                            false,
+                            true,
+                            None,
+                            None,
+                            Length::Fill,
+                            iced::Alignment::Start,
+                            context,
+                        ),
+                        self.footer_button(
+                            "Copy as Markdown",
+                            on_copy(self.export_selected(ExportFormat::Markdown, context)),
+                            context,
+                        ),
+                    ],
+                    8.0,
+                    [4, 12].into(),
+                    Length::Fill,
+                    Length::Shrink,
+                    iced::Alignment::Start,
+                    iced::Alignment::Center,
+                    context,
+                ));
+            }
+        }
+
         let table_content = B::vstack(
             table_children,
             0.0,
@@ -843,9 +1552,15 @@ where
     }
 
     fn describe(&self, context: &Context) -> SemanticNode {
-        let rows = self.rows.iter().map(|r| {
-            SemanticNode::new("row").extend_children(r.cells.iter().map(|c| c.describe(context)))
-        });
+        if let Some(source) = &self.source {
+            return self.describe_source(source);
+        }
+
+        let order: Vec<usize> = (0..self.columns.len()).collect();
+        let rows = self
+            .rows
+            .iter()
+            .map(|r| self.describe_row(r.id.clone(), self.row_values(r, context), &order));
 
         SemanticNode::new("data_table")
             .with_label(format!(
@@ -906,4 +1621,44 @@ mod tests {
             .on_page_change(|p| TestMessage::PageChanged(p))
             .on_selection_change(|ids| TestMessage::SelectionChanged(ids));
     }
+
+    #[test]
+    fn test_export_uses_values_then_cells() {
+        let table: DataTable<TestMessage, IcedBackend> = DataTable::new()
+            .column("Service", Length::Fill)
+            .column("Latency", Length::Fixed(80.0))
+            .align_end()
+            .row_with_id(
+                "api",
+                vec![
+                    Box::new(Text::<IcedBackend>::new("api")),
+                    Box::new(Text::<IcedBackend>::new("12 ms")),
+                ],
+            )
+            .values(vec!["api".into(), 12.0.into()])
+            .row_with_id(
+                "db",
+                vec![
+                    Box::new(Text::<IcedBackend>::new("db")),
+                    Box::new(Text::<IcedBackend>::new("4 ms")),
+                ],
+            )
+            .selected_ids(HashSet::from(["db".to_string()]));
+        let context = Context::default();
+
+        assert_eq!(
+            table.export(ExportFormat::Csv, &context),
+            "Service,Latency\napi,12\ndb,4 ms\n"
+        );
+        assert_eq!(
+            table.export_selected(ExportFormat::Markdown, &context),
+            "| Service | Latency |\n| :--- | ---: |\n| db | 4 ms |\n"
+        );
+
+        let description = table.describe(&context);
+        let api = &description.children[0];
+        assert_eq!(api.id.as_deref(), Some("api"));
+        assert_eq!(api.children[1].label.as_deref(), Some("Latency"));
+        assert_eq!(api.children[1].content.as_deref(), Some("12"));
+    }
 }
@@ -0,0 +1,184 @@
+//! Getting rows out of a table as text.
+use super::CellValue;
+use iced::Alignment;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ExportFormat {
+    Csv,
+    Tsv,
+    /// An array of objects keyed by column, in column order.
+    Json,
+    /// A GitHub-flavoured Markdown table.
+    Markdown,
+}
+
+impl ExportFormat {
+    pub fn extension(self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "csv",
+            ExportFormat::Tsv => "tsv",
+            ExportFormat::Json => "json",
+            ExportFormat::Markdown => "md",
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "CSV",
+            ExportFormat::Tsv => "TSV",
+            ExportFormat::Json => "JSON",
+            ExportFormat::Markdown => "Markdown",
+        }
+    }
+}
+
+/// Writes `rows` under `columns`, given as label and alignment. Rows with
+/// fewer values than columns get empty cells.
+pub fn export(
+    format: ExportFormat,
+    columns: &[(&str, Alignment)],
+    rows: impl IntoIterator<Item = Vec<CellValue>>,
+) -> String {
+    let rows = rows.into_iter().map(|mut row| {
+        row.resize(columns.len(), CellValue::Empty);
+        row
+    });
+
+    match format {
+        ExportFormat::Csv => delimited(columns, rows, ',', csv_field),
+        ExportFormat::Tsv => delimited(columns, rows, '\t', tsv_field),
+        ExportFormat::Json => json(columns, rows),
+        ExportFormat::Markdown => markdown(columns, rows),
+    }
+}
+
+fn delimited(
+    columns: &[(&str, Alignment)],
+    rows: impl Iterator<Item = Vec<CellValue>>,
+    separator: char,
+    field: fn(&str) -> String,
+) -> String {
+    let line = |fields: Vec<String>| fields.join(&separator.to_string());
+
+    let mut lines = vec![line(
+        columns.iter().map(|(label, _)| field(label)).collect(),
+    )];
+    lines.extend(rows.map(|row| line(row.iter().map(|value| field(&value.to_string())).collect())));
+
+    let mut out = lines.join("\n");
+    out.push('\n');
+    out
+}
+
+/// Quoted when it holds a comma, quote or line break (RFC 4180).
+fn csv_field(text: &str) -> String {
+    if text.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_string()
+    }
+}
+
+/// TSV has no quoting, so tabs and line breaks become spaces.
+fn tsv_field(text: &str) -> String {
+    text.replace(['\t', '\n', '\r'], " ")
+}
+
+fn json(columns: &[(&str, Alignment)], rows: impl Iterator<Item = Vec<CellValue>>) -> String {
+    // Written by hand to keep the keys in column order
+    let objects: Vec<String> = rows
+        .map(|row| {
+            let fields: Vec<String> = columns
+                .iter()
+                .zip(&row)
+                .map(|((label, _), value)| {
+                    format!(
+                        "{}: {}",
+                        serde_json::Value::String(label.to_string()),
+                        value.to_json()
+                    )
+                })
+                .collect();
+            format!("  {{{}}}", fields.join(", "))
+        })
+        .collect();
+
+    if objects.is_empty() {
+        "[]\n".to_string()
+    } else {
+        format!("[\n{}\n]\n", objects.join(",\n"))
+    }
+}
+
+fn markdown(columns: &[(&str, Alignment)], rows: impl Iterator<Item = Vec<CellValue>>) -> String {
+    let cell = |text: &str| text.replace('|', "\\|").replace(['\n', '\r'], " ");
+    let line = |cells: Vec<String>| format!("| {} |", cells.join(" | "));
+
+    let mut lines = vec![
+        line(columns.iter().map(|(label, _)| cell(label)).collect()),
+        line(
+            columns
+                .iter()
+                .map(|(_, alignment)| {
+                    match alignment {
+                        Alignment::Start => ":---",
+                        Alignment::Center => ":---:",
+                        Alignment::End => "---:",
+                    }
+                    .to_string()
+                })
+                .collect(),
+        ),
+    ];
+    lines.extend(rows.map(|row| line(row.iter().map(|value| cell(&value.to_string())).collect())));
+
+    let mut out = lines.join("\n");
+    out.push('\n');
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn columns() -> [(&'static str, Alignment); 3] {
+        [
+            ("Name", Alignment::Start),
+            ("Took (ms)", Alignment::End),
+            ("Note", Alignment::Start),
+        ]
+    }
+
+    fn rows() -> Vec<Vec<CellValue>> {
+        vec![
+            vec!["fetch".into(), 12.0.into(), "said \"hi\", twice".into()],
+            vec!["parse|lex".into(), 0.25.into()],
+        ]
+    }
+
+    #[test]
+    fn test_delimited_exports_escape_fields() {
+        assert_eq!(
+            export(ExportFormat::Csv, &columns(), rows()),
+            "Name,Took (ms),Note\nfetch,12,\"said \"\"hi\"\", twice\"\nparse|lex,0.25,\n"
+        );
+        assert_eq!(
+            export(ExportFormat::Tsv, &columns(), rows()),
+            "Name\tTook (ms)\tNote\nfetch\t12\tsaid \"hi\", twice\nparse|lex\t0.25\t\n"
+        );
+    }
+
+    #[test]
+    fn test_json_and_markdown_exports() {
+        assert_eq!(
+            export(ExportFormat::Json, &columns(), rows()),
+            "[\n  {\"Name\": \"fetch\", \"Took (ms)\": 12.0, \"Note\": \"said \\\"hi\\\", twice\"},\n  \
+             {\"Name\": \"parse|lex\", \"Took (ms)\": 0.25, \"Note\": null}\n]\n"
+        );
+        assert_eq!(
+            export(ExportFormat::Markdown, &columns(), rows()),
+            "| Name | Took (ms) | Note |\n| :--- | ---: | :--- |\n\
+             | fetch | 12 | said \"hi\", twice |\n| parse\\|lex | 0.25 |  |\n"
+        );
+    }
+}
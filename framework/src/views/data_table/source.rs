@@ -0,0 +1,576 @@
+//! Tables backed by a [`TableSource`], which only build the rows in view.
+//!
+//! The source holds the data; a [`DataTableState`] in app state holds how it
+//! is shown (sort order, filters, column widths and order, scroll position)
+//! along with the resulting order of rows, which is only worked out again when
+//! sorting or filtering changes. The table reports what the user does as
+//! [`DataTableMessage`]s, to be handed back to [`DataTableState::update`].
+use super::{export, CellValue, ExportFormat};
+use crate::prelude::*;
+use std::cmp::Ordering;
+use std::collections::{BTreeSet, HashMap};
+use std::ops::Range;
+use std::sync::Arc;
+
+/// Rows a [`DataTable`](super::DataTable) pulls as they scroll into view.
+pub trait TableSource<M, B>: Send + Sync
+where
+    M: 'static + Send + Sync,
+    B: Backend,
+{
+    fn row_count(&self) -> usize;
+
+    /// The cells of row `index`, one per column.
+    fn row(&self, index: usize) -> Vec<Box<dyn View<M, B> + Send + Sync>>;
+
+    /// The raw value of cell `column` of row `index`, which filters match
+    /// against and exports write.
+    fn value(&self, index: usize, column: usize) -> CellValue;
+
+    /// What row `index` sorts by in `column`; by default its value.
+    fn sort_key(&self, index: usize, column: usize) -> CellValue {
+        self.value(index, column)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SortColumn {
+    pub column: usize,
+    pub ascending: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum DataTableMessage {
+    /// Sorts by a column first, keeping the previous sort as tie-breakers;
+    /// sorting by the first sort column again reverses it.
+    Sort(usize),
+    ClearSort,
+    Filter(usize, String),
+    /// The scroll offset and viewport height of the rows.
+    Scrolled(f32, f32),
+    /// The mouse went down on a column header.
+    HeaderPressed(usize),
+    /// The mouse went up on a column header: a click if it went down there
+    /// too, otherwise a column dropped onto it.
+    HeaderReleased(usize),
+    /// The mouse went down on the resize handle after a column.
+    ResizePressed(usize),
+    /// The mouse moved over the header, at this x.
+    PointerMoved(f32),
+    PointerReleased,
+    /// A row was clicked, selecting or deselecting it.
+    RowPressed(usize),
+    ClearSelection,
+    /// Export was asked for. [`DataTableState::update`] leaves this to the
+    /// app, which can write [`DataTableState::export`] wherever it likes.
+    Export(ExportFormat),
+    /// Copy was asked for. Also left to the app, e.g. to put
+    /// [`DataTableState::copy_selection`] on the clipboard.
+    CopySelection,
+}
+
+/// How a [`TableSource`] is shown. Keep it in app state.
+#[derive(Debug, Clone, Default)]
+pub struct DataTableState {
+    /// The sort columns, the first one deciding first.
+    pub sort: Vec<SortColumn>,
+    /// Case-insensitive filters by column.
+    pub filters: HashMap<usize, String>,
+    /// Widths set by resizing, by column.
+    pub widths: HashMap<usize, f32>,
+    /// Columns in display order; empty for their natural order.
+    pub order: Vec<usize>,
+    pub scroll_offset: f32,
+    pub viewport_height: f32,
+    /// Selected rows of the source, which stay selected while filtered out.
+    pub selected: BTreeSet<usize>,
+    /// The source's rows that pass the filters, in display order.
+    rows: Arc<[usize]>,
+    pressed: Option<usize>,
+    resizing: Option<Resize>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Resize {
+    column: usize,
+    /// Where the pointer was when the resize started.
+    anchor: Option<f32>,
+    width: f32,
+}
+
+/// Most sort columns kept.
+const MAX_SORT_COLUMNS: usize = 3;
+
+/// The width resizing starts from for columns that aren't fixed.
+const DEFAULT_WIDTH: f32 = 160.0;
+
+const MIN_WIDTH: f32 = 48.0;
+
+impl DataTableState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Handles a message from the table, sorting and filtering `source`
+    /// again when needed. `columns` are the table's column widths, which
+    /// resizing starts from.
+    pub fn update<M, B>(
+        &mut self,
+        message: DataTableMessage,
+        source: &dyn TableSource<M, B>,
+        columns: &[Length],
+    ) where
+        M: 'static + Send + Sync,
+        B: Backend,
+    {
+        match message {
+            DataTableMessage::Sort(column) => {
+                self.sort_by(column);
+                self.refresh(source);
+            }
+            DataTableMessage::ClearSort => {
+                self.sort.clear();
+                self.refresh(source);
+            }
+            DataTableMessage::Filter(column, filter) => {
+                if filter.is_empty() {
+                    self.filters.remove(&column);
+                } else {
+                    self.filters.insert(column, filter);
+                }
+                self.scroll_offset = 0.0;
+                self.refresh(source);
+            }
+            DataTableMessage::Scrolled(offset, height) => {
+                self.scroll_offset = offset;
+                self.viewport_height = height;
+            }
+            DataTableMessage::HeaderPressed(column) => {
+                self.pressed = Some(column);
+            }
+            DataTableMessage::HeaderReleased(column) => {
+                if self.resizing.take().is_some() {
+                    self.pressed = None;
+                    return;
+                }
+                match self.pressed.take() {
+                    Some(pressed) if pressed == column => {
+                        self.sort_by(column);
+                        self.refresh(source);
+                    }
+                    Some(pressed) => self.move_column(pressed, column, columns.len()),
+                    None => {}
+                }
+            }
+            DataTableMessage::ResizePressed(column) => {
+                let width = self.widths.get(&column).copied().unwrap_or_else(|| {
+                    match columns.get(column) {
+                        Some(Length::Fixed(width)) => *width,
+                        _ => DEFAULT_WIDTH,
+                    }
+                });
+                self.resizing = Some(Resize {
+                    column,
+                    anchor: None,
+                    width,
+                });
+            }
+            DataTableMessage::PointerMoved(x) => {
+                if let Some(resize) = &mut self.resizing {
+                    let anchor = *resize.anchor.get_or_insert(x);
+                    let width = (resize.width + x - anchor).max(MIN_WIDTH);
+                    self.widths.insert(resize.column, width);
+                }
+            }
+            DataTableMessage::PointerReleased => {
+                self.pressed = None;
+                self.resizing = None;
+            }
+            DataTableMessage::RowPressed(row) => {
+                if !self.selected.remove(&row) {
+                    self.selected.insert(row);
+                }
+            }
+            DataTableMessage::ClearSelection => self.selected.clear(),
+            DataTableMessage::Export(_) | DataTableMessage::CopySelection => {}
+        }
+    }
+
+    /// Sorts and filters `source` again, e.g. after its rows changed.
+    pub fn refresh<M, B>(&mut self, source: &dyn TableSource<M, B>)
+    where
+        M: 'static + Send + Sync,
+        B: Backend,
+    {
+        let count = source.row_count();
+        let filters: Vec<(usize, String)> = self
+            .filters
+            .iter()
+            .filter(|(_, filter)| !filter.is_empty())
+            .map(|(column, filter)| (*column, filter.to_lowercase()))
+            .collect();
+
+        let mut rows: Vec<usize> = (0..count)
+            .filter(|&row| {
+                filters.iter().all(|(column, filter)| {
+                    source.value(row, *column).search_text().contains(filter)
+                })
+            })
+            .collect();
+
+        if !self.sort.is_empty() {
+            // Each row's keys are looked up once, not per comparison
+            let keys: HashMap<usize, Vec<CellValue>> = self
+                .sort
+                .iter()
+                .map(|sort| {
+                    let keys = (0..count)
+                        .map(|row| source.sort_key(row, sort.column).normalized())
+                        .collect();
+                    (sort.column, keys)
+                })
+                .collect();
+
+            rows.sort_by(|&a, &b| {
+                self.sort
+                    .iter()
+                    .map(|sort| {
+                        let keys = &keys[&sort.column];
+                        let ordering = keys[a].compare(&keys[b]);
+                        if sort.ascending {
+                            ordering
+                        } else {
+                            ordering.reverse()
+                        }
+                    })
+                    .find(|ordering| ordering.is_ne())
+                    .unwrap_or(Ordering::Equal)
+            });
+        }
+
+        self.rows = rows.into();
+    }
+
+    /// The source's rows to show, in order, as of the last refresh.
+    pub fn rows(&self) -> &Arc<[usize]> {
+        &self.rows
+    }
+
+    /// How column `column` is sorted, and how it ranks among the sort
+    /// columns.
+    pub fn sorted(&self, column: usize) -> Option<(usize, bool)> {
+        self.sort
+            .iter()
+            .position(|sort| sort.column == column)
+            .map(|rank| (rank, self.sort[rank].ascending))
+    }
+
+    /// The table's `count` columns in display order.
+    pub fn column_order(&self, count: usize) -> Vec<usize> {
+        if self.order.len() == count {
+            self.order.clone()
+        } else {
+            (0..count).collect()
+        }
+    }
+
+    /// The positions of `rows().len()` rows of `row_height` in view, with
+    /// `overscan` more either side.
+    pub fn visible_rows(&self, row_height: f32, overscan: usize) -> Range<usize> {
+        // Before the first scroll, assume a tall viewport
+        let height = if self.viewport_height > 0.0 {
+            self.viewport_height
+        } else {
+            1080.0
+        };
+        let row_height = row_height.max(1.0);
+
+        let first = (self.scroll_offset / row_height).floor().max(0.0) as usize;
+        let last = ((self.scroll_offset + height) / row_height).ceil() as usize;
+        first.saturating_sub(overscan).min(self.rows.len())..(last + overscan).min(self.rows.len())
+    }
+
+    /// Writes the rows as shown, filtered and sorted, with `columns` (label
+    /// and alignment, in the source's order) in display order.
+    pub fn export<M, B>(
+        &self,
+        source: &dyn TableSource<M, B>,
+        columns: &[(&str, Alignment)],
+        format: ExportFormat,
+    ) -> String
+    where
+        M: 'static + Send + Sync,
+        B: Backend,
+    {
+        self.export_rows(source, columns, format, self.rows.iter().copied())
+    }
+
+    /// Like [`export`](Self::export), for the selected rows that are shown.
+    pub fn export_selected<M, B>(
+        &self,
+        source: &dyn TableSource<M, B>,
+        columns: &[(&str, Alignment)],
+        format: ExportFormat,
+    ) -> String
+    where
+        M: 'static + Send + Sync,
+        B: Backend,
+    {
+        let rows = self
+            .rows
+            .iter()
+            .copied()
+            .filter(|row| self.selected.contains(row));
+        self.export_rows(source, columns, format, rows)
+    }
+
+    /// The selected rows as a Markdown table, for the clipboard.
+    pub fn copy_selection<M, B>(
+        &self,
+        source: &dyn TableSource<M, B>,
+        columns: &[(&str, Alignment)],
+    ) -> String
+    where
+        M: 'static + Send + Sync,
+        B: Backend,
+    {
+        self.export_selected(source, columns, ExportFormat::Markdown)
+    }
+
+    fn export_rows<M, B>(
+        &self,
+        source: &dyn TableSource<M, B>,
+        columns: &[(&str, Alignment)],
+        format: ExportFormat,
+        rows: impl Iterator<Item = usize>,
+    ) -> String
+    where
+        M: 'static + Send + Sync,
+        B: Backend,
+    {
+        let order = self.column_order(columns.len());
+        let headers: Vec<_> = order.iter().map(|&column| columns[column]).collect();
+        let rows = rows.map(|row| {
+            order
+                .iter()
+                .map(|&column| source.value(row, column))
+                .collect()
+        });
+        export::export(format, &headers, rows)
+    }
+
+    fn sort_by(&mut self, column: usize) {
+        match self.sort.first_mut() {
+            Some(first) if first.column == column => first.ascending = !first.ascending,
+            _ => {
+                self.sort.retain(|sort| sort.column != column);
+                self.sort.insert(
+                    0,
+                    SortColumn {
+                        column,
+                        ascending: true,
+                    },
+                );
+                self.sort.truncate(MAX_SORT_COLUMNS);
+            }
+        }
+    }
+
+    fn move_column(&mut self, column: usize, onto: usize, count: usize) {
+        let mut order = self.column_order(count);
+        let (Some(from), Some(to)) = (
+            order.iter().position(|&c| c == column),
+            order.iter().position(|&c| c == onto),
+        ) else {
+            return;
+        };
+
+        let column = order.remove(from);
+        order.insert(to, column);
+        self.order = order;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::TermBackend;
+    use crate::views::DataTable;
+
+    struct Logs(Vec<(&'static str, f64)>);
+
+    impl TableSource<(), TermBackend> for Logs {
+        fn row_count(&self) -> usize {
+            self.0.len()
+        }
+
+        fn row(&self, index: usize) -> Vec<Box<dyn View<(), TermBackend> + Send + Sync>> {
+            let (level, took) = self.0[index];
+            vec![
+                Box::new(Text::<TermBackend>::new(level)),
+                Box::new(Text::<TermBackend>::new(took.to_string())),
+            ]
+        }
+
+        fn value(&self, index: usize, column: usize) -> CellValue {
+            let (level, took) = self.0[index];
+            match column {
+                0 => level.into(),
+                _ => took.into(),
+            }
+        }
+    }
+
+    fn logs() -> Logs {
+        Logs(vec![
+            ("warn", 3.0),
+            ("INFO", 12.0),
+            ("error", 7.5),
+            ("info", 1.0),
+            ("warn", 40.0),
+        ])
+    }
+
+    const COLUMNS: [Length; 2] = [Length::Fill, Length::Fixed(80.0)];
+
+    #[test]
+    fn test_sorting_keeps_earlier_sorts_as_tie_breakers() {
+        let logs = logs();
+        let mut state = DataTableState::new();
+        state.refresh(&logs);
+        assert_eq!(&state.rows()[..], [0, 1, 2, 3, 4]);
+
+        state.update(DataTableMessage::Sort(1), &logs, &COLUMNS);
+        state.update(DataTableMessage::Sort(1), &logs, &COLUMNS);
+        assert_eq!(&state.rows()[..], [4, 1, 2, 0, 3]);
+
+        // Levels ascending, case-insensitively, then by time descending
+        state.update(DataTableMessage::Sort(0), &logs, &COLUMNS);
+        assert_eq!(&state.rows()[..], [2, 1, 3, 4, 0]);
+        assert_eq!(state.sorted(0), Some((0, true)));
+        assert_eq!(state.sorted(1), Some((1, false)));
+    }
+
+    #[test]
+    fn test_filters_match_text_and_numbers() {
+        let logs = logs();
+        let mut state = DataTableState::new();
+        state.scroll_offset = 400.0;
+
+        state.update(DataTableMessage::Filter(0, "Info".into()), &logs, &COLUMNS);
+        assert_eq!(&state.rows()[..], [1, 3]);
+        assert_eq!(state.scroll_offset, 0.0);
+
+        state.update(DataTableMessage::Filter(1, "12".into()), &logs, &COLUMNS);
+        assert_eq!(&state.rows()[..], [1]);
+
+        state.update(DataTableMessage::Filter(0, String::new()), &logs, &COLUMNS);
+        state.update(DataTableMessage::Filter(1, String::new()), &logs, &COLUMNS);
+        assert_eq!(state.rows().len(), 5);
+    }
+
+    #[test]
+    fn test_headers_resize_and_reorder() {
+        let logs = logs();
+        let mut state = DataTableState::new();
+
+        // Dragging the handle after the second column widens it
+        state.update(DataTableMessage::ResizePressed(1), &logs, &COLUMNS);
+        state.update(DataTableMessage::PointerMoved(300.0), &logs, &COLUMNS);
+        state.update(DataTableMessage::PointerMoved(340.0), &logs, &COLUMNS);
+        state.update(DataTableMessage::PointerReleased, &logs, &COLUMNS);
+        assert_eq!(state.widths.get(&1), Some(&120.0));
+
+        // Dropping a header onto another moves it there
+        state.update(DataTableMessage::HeaderPressed(1), &logs, &COLUMNS);
+        state.update(DataTableMessage::HeaderReleased(0), &logs, &COLUMNS);
+        assert_eq!(state.column_order(2), [1, 0]);
+        assert!(state.sort.is_empty());
+
+        // While a click sorts
+        state.update(DataTableMessage::HeaderPressed(0), &logs, &COLUMNS);
+        state.update(DataTableMessage::HeaderReleased(0), &logs, &COLUMNS);
+        assert_eq!(state.sorted(0), Some((0, true)));
+    }
+
+    #[test]
+    fn test_exports_follow_filters_sort_and_order() {
+        let logs = logs();
+        let mut state = DataTableState::new();
+        let columns = [("Level", Alignment::Start), ("Took", Alignment::End)];
+
+        state.update(DataTableMessage::Filter(0, "warn".into()), &logs, &COLUMNS);
+        state.update(DataTableMessage::Sort(1), &logs, &COLUMNS);
+        state.update(DataTableMessage::Sort(1), &logs, &COLUMNS);
+        assert_eq!(
+            state.export(&logs, &columns, ExportFormat::Csv),
+            "Level,Took\nwarn,40\nwarn,3\n"
+        );
+
+        // Columns export in display order
+        state.order = vec![1, 0];
+        assert_eq!(
+            state.export(&logs, &columns, ExportFormat::Json),
+            "[\n  {\"Took\": 40.0, \"Level\": \"warn\"},\n  {\"Took\": 3.0, \"Level\": \"warn\"}\n]\n"
+        );
+
+        // Selected rows that are filtered out aren't copied
+        state.update(DataTableMessage::RowPressed(0), &logs, &COLUMNS);
+        state.update(DataTableMessage::RowPressed(1), &logs, &COLUMNS);
+        state.update(DataTableMessage::RowPressed(4), &logs, &COLUMNS);
+        state.update(DataTableMessage::RowPressed(4), &logs, &COLUMNS);
+        assert_eq!(state.selected, BTreeSet::from([0, 1]));
+        assert_eq!(
+            state.copy_selection(&logs, &columns),
+            "| Took | Level |\n| ---: | :--- |\n| 3 | warn |\n"
+        );
+
+        state.update(DataTableMessage::ClearSelection, &logs, &COLUMNS);
+        assert!(state.selected.is_empty());
+    }
+
+    #[test]
+    fn test_only_rows_in_view_are_visible() {
+        let logs = Logs((0..100_000).map(|i| ("info", i as f64)).collect());
+        let mut state = DataTableState::new();
+        state.refresh(&logs);
+
+        state.update(DataTableMessage::Scrolled(4000.0, 400.0), &logs, &COLUMNS);
+        assert_eq!(state.visible_rows(40.0, 5), 95..115);
+
+        state.update(
+            DataTableMessage::Scrolled(4_000_000.0, 400.0),
+            &logs,
+            &COLUMNS,
+        );
+        assert_eq!(state.visible_rows(40.0, 5).end, 100_000);
+    }
+
+    #[test]
+    fn test_sourced_tables_build_rows_in_view() {
+        let logs = Logs((0..100_000).map(|i| ("info", i as f64)).collect());
+        let mut state = DataTableState::new();
+        state.refresh(&logs);
+        state.update(DataTableMessage::Scrolled(4000.0, 400.0), &logs, &COLUMNS);
+
+        let table = DataTable::<(), TermBackend>::new()
+            .column("Level", COLUMNS[0])
+            .column("Took", COLUMNS[1])
+            .source(Arc::new(logs), &state, |_| ());
+
+        let description = table.describe(&Context::default());
+        assert_eq!(
+            description.label.as_deref(),
+            Some("Table with 2 columns and 100000 of 100000 rows, showing 101-110")
+        );
+        assert_eq!(description.children.len(), 10);
+        let row = &description.children[0];
+        assert_eq!(row.id.as_deref(), Some("100"));
+        assert_eq!(row.children[1].label.as_deref(), Some("Took"));
+        assert_eq!(row.children[1].content.as_deref(), Some("100"));
+
+        let rendered = table.view(&Context::default());
+        assert!(rendered.contains("100000 rows"));
+        assert!(!rendered.contains("99999"));
+    }
+}
@@ -0,0 +1,101 @@
+//! The raw values behind table cells, for sorting, filtering and export.
+use std::cmp::Ordering;
+use std::fmt;
+
+/// What a cell holds, as opposed to how it is shown. Empty cells sort
+/// first, then numbers, then text.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub enum CellValue {
+    #[default]
+    Empty,
+    Number(f64),
+    Text(String),
+}
+
+impl CellValue {
+    /// Lowercased text, for filters to match against.
+    pub(crate) fn search_text(&self) -> String {
+        match self {
+            CellValue::Text(text) => text.to_lowercase(),
+            value => value.to_string(),
+        }
+    }
+
+    /// Compares text case-insensitively, so it is lowercased up front.
+    pub(crate) fn normalized(self) -> Self {
+        match self {
+            CellValue::Text(text) => CellValue::Text(text.to_lowercase()),
+            value => value,
+        }
+    }
+
+    pub(crate) fn compare(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            (CellValue::Number(a), CellValue::Number(b)) => a.total_cmp(b),
+            (CellValue::Text(a), CellValue::Text(b)) => a.cmp(b),
+            _ => self.rank().cmp(&other.rank()),
+        }
+    }
+
+    fn rank(&self) -> u8 {
+        match self {
+            CellValue::Empty => 0,
+            CellValue::Number(_) => 1,
+            CellValue::Text(_) => 2,
+        }
+    }
+
+    pub(crate) fn to_json(&self) -> serde_json::Value {
+        match self {
+            CellValue::Empty => serde_json::Value::Null,
+            CellValue::Number(number) => serde_json::Number::from_f64(*number)
+                .map(serde_json::Value::Number)
+                .unwrap_or(serde_json::Value::Null),
+            CellValue::Text(text) => serde_json::Value::String(text.clone()),
+        }
+    }
+}
+
+impl fmt::Display for CellValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CellValue::Empty => Ok(()),
+            // Whole numbers without a trailing ".0"
+            CellValue::Number(number) if number.fract() == 0.0 && number.abs() < 1e15 => {
+                write!(f, "{}", *number as i64)
+            }
+            CellValue::Number(number) => write!(f, "{}", number),
+            CellValue::Text(text) => f.write_str(text),
+        }
+    }
+}
+
+impl From<f64> for CellValue {
+    fn from(number: f64) -> Self {
+        CellValue::Number(number)
+    }
+}
+
+impl From<i64> for CellValue {
+    fn from(number: i64) -> Self {
+        CellValue::Number(number as f64)
+    }
+}
+
+impl From<String> for CellValue {
+    fn from(text: String) -> Self {
+        CellValue::Text(text)
+    }
+}
+
+impl From<&str> for CellValue {
+    fn from(text: &str) -> Self {
+        CellValue::Text(text.to_string())
+    }
+}
+
+impl<T: Into<CellValue>> From<Option<T>> for CellValue {
+    fn from(value: Option<T>) -> Self {
+        value.map(Into::into).unwrap_or_default()
+    }
+}
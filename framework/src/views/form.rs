@@ -0,0 +1,491 @@
+use crate::core::{AccessibilityNode, AccessibilityRole};
+use crate::prelude::*;
+use std::sync::Arc;
+
+pub mod schema;
+pub mod state;
+
+pub use state::{AsyncValidator, FieldKind, FormField, FormMessage, FormState, Validator};
+
+/// A form of the fields of its [`FormState`], each with its label, any
+/// description and, once touched, its error, and buttons to submit and
+/// reset. Unlike [`Form`](crate::elements::forms::Form), which lays out
+/// views it's given, it builds and checks its own fields, e.g. ones
+/// generated from a JSON Schema.
+pub struct FormView<M, B>
+where
+    M: 'static + Send + Sync,
+    B: Backend + Send + Sync,
+{
+    pub state: FormState,
+    pub on_message: Arc<dyn Fn(FormMessage) -> M + Send + Sync>,
+    pub submit_label: String,
+    pub show_reset: bool,
+    _phantom: std::marker::PhantomData<B>,
+}
+
+impl<M, B> FormView<M, B>
+where
+    M: 'static + Clone + Send + Sync,
+    B: Backend + Send + Sync + 'static,
+{
+    /// A form of `state`'s fields, which reports what the user does through
+    /// `on_message`, for [`FormState::update`].
+    pub fn new(
+        state: &FormState,
+        on_message: impl Fn(FormMessage) -> M + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            state: state.clone(),
+            on_message: Arc::new(on_message),
+            submit_label: "Submit".to_string(),
+            show_reset: true,
+            _phantom: std::marker::PhantomData,
+        }
+    }
+
+    pub fn submit_label(mut self, label: impl Into<String>) -> Self {
+        self.submit_label = label.into();
+        self
+    }
+
+    pub fn show_reset(mut self, show: bool) -> Self {
+        self.show_reset = show;
+        self
+    }
+
+    fn caption(&self, text: String, color: Option<Color>, context: &Context) -> B::AnyView<M> {
+        B::text(
+            text,
+            12.0,
+            color,
+            false,
+            color.is_none(),
+            None,
+            None,
+            Length::Fill,
+            iced::Alignment::Start,
+            context,
+        )
+    }
+
+    fn input(&self, field: &FormField, input: &str, context: &Context) -> B::AnyView<M> {
+        let key = field.key.clone();
+        let on_message = self.on_message.clone();
+        match &field.kind {
+            FieldKind::Toggle => B::toggle(
+                field.label.clone(),
+                input == "true",
+                move |on| on_message(FormMessage::Toggled(key.clone(), on)),
+                context,
+            ),
+            FieldKind::Choice(options) => {
+                // Optional choices can be cleared
+                let none = (!field.required).then(|| ("None", String::new()));
+                let options = options
+                    .iter()
+                    .map(|option| (option.as_str(), option.clone()))
+                    .chain(none);
+                let buttons = options
+                    .map(|(label, value)| {
+                        let selected = input == value;
+                        B::button(
+                            B::text(
+                                label.to_string(),
+                                13.0,
+                                None,
+                                false,
+                                false,
+                                None,
+                                None,
+                                Length::Shrink,
+                                iced::Alignment::Center,
+                                context,
+                            ),
+                            Some((self.on_message)(FormMessage::Changed(key.clone(), value))),
+                            if selected {
+                                Variant::Soft
+                            } else {
+                                Variant::Outline
+                            },
+                            if selected {
+                                Intent::Primary
+                            } else {
+                                Intent::Neutral
+                            },
+                            Length::Shrink,
+                            Length::Shrink,
+                            true,
+                            context,
+                        )
+                    })
+                    .collect();
+                B::wrap(
+                    buttons,
+                    6.0,
+                    6.0,
+                    Padding::default(),
+                    Length::Fill,
+                    Length::Shrink,
+                    iced::Alignment::Start,
+                    iced::Alignment::Center,
+                    context,
+                )
+            }
+            kind => B::text_input(
+                input.to_string(),
+                if *kind == FieldKind::Json {
+                    "JSON".to_string()
+                } else {
+                    String::new()
+                },
+                move |input| on_message(FormMessage::Changed(key.clone(), input)),
+                Some((self.on_message)(FormMessage::Submit)),
+                (*kind == FieldKind::Json).then_some(iced::Font::MONOSPACE),
+                *kind == FieldKind::Secret,
+                Variant::Outline,
+                None,
+                context,
+            ),
+        }
+    }
+
+    fn field(&self, field: &FormField, context: &Context) -> B::AnyView<M> {
+        let palette = context.theme.colors;
+        let input = self.state.input(&field.key).unwrap_or_default();
+        let mut children = Vec::new();
+
+        // Toggles carry their own label
+        if field.kind != FieldKind::Toggle {
+            let mut label = field.label.clone();
+            if field.required {
+                label.push_str(" *");
+            }
+            children.push(B::text(
+                label,
+                13.0,
+                None,
+                true,
+                false,
+                None,
+                None,
+                Length::Fill,
+                iced::Alignment::Start,
+                context,
+            ));
+        }
+        if let Some(description) = &field.description {
+            children.push(self.caption(description.clone(), None, context));
+        }
+        children.push(self.input(field, input, context));
+
+        if let Some(error) = self.state.error(&field.key) {
+            children.push(self.caption(error.to_string(), Some(palette.danger), context));
+        } else if self.state.is_validating(&field.key) {
+            children.push(self.caption("Checking…".to_string(), None, context));
+        }
+
+        B::vstack(
+            children,
+            4.0,
+            Padding::default(),
+            Length::Fill,
+            Length::Shrink,
+            iced::Alignment::Start,
+            iced::Alignment::Start,
+            context,
+        )
+    }
+
+    fn buttons(&self, context: &Context) -> B::AnyView<M> {
+        let button = |label: String, message, variant, intent, enabled| {
+            B::button(
+                B::text(
+                    label,
+                    13.0,
+                    None,
+                    false,
+                    false,
+                    None,
+                    None,
+                    Length::Shrink,
+                    iced::Alignment::Center,
+                    context,
+                ),
+                Some((self.on_message)(message)),
+                variant,
+                intent,
+                Length::Shrink,
+                Length::Shrink,
+                enabled,
+                context,
+            )
+        };
+
+        let mut children = vec![B::space(Length::Fill, Length::Shrink, context)];
+        if self.show_reset {
+            children.push(button(
+                "Reset".to_string(),
+                FormMessage::Reset,
+                Variant::Ghost,
+                Intent::Neutral,
+                self.state.has_changes(),
+            ));
+        }
+        children.push(button(
+            if self.state.is_submitting() {
+                "Checking…".to_string()
+            } else {
+                self.submit_label.clone()
+            },
+            FormMessage::Submit,
+            Variant::Solid,
+            Intent::Primary,
+            !self.state.is_submitting(),
+        ));
+
+        B::hstack(
+            children,
+            8.0,
+            Padding::default(),
+            Length::Fill,
+            Length::Shrink,
+            iced::Alignment::Start,
+            iced::Alignment::Center,
+            context,
+        )
+    }
+
+    fn describe_field(&self, field: &FormField) -> SemanticNode {
+        let input = self.state.input(&field.key).unwrap_or_default();
+        let (role, accessibility_role) = match field.kind {
+            FieldKind::Toggle => ("toggle", AccessibilityRole::Switch),
+            FieldKind::Integer | FieldKind::Number => {
+                ("number_field", AccessibilityRole::SpinButton)
+            }
+            FieldKind::Choice(_) => ("choice", AccessibilityRole::Group),
+            _ => ("text_field", AccessibilityRole::TextField),
+        };
+        // Secrets stay secret
+        let value = if field.kind == FieldKind::Secret {
+            "•".repeat(input.chars().count())
+        } else {
+            input.to_string()
+        };
+
+        let mut states = Vec::new();
+        if field.required {
+            states.push("required".into());
+        }
+        if self.state.error(&field.key).is_some() {
+            states.push("invalid".into());
+        }
+        if self.state.is_dirty(&field.key) {
+            states.push("dirty".into());
+        }
+        if self.state.is_touched(&field.key) {
+            states.push("touched".into());
+        }
+        if self.state.is_validating(&field.key) {
+            states.push("busy".into());
+        }
+
+        let mut node = SemanticNode::new(role)
+            .with_label(field.label.clone())
+            .with_content(value.clone())
+            .with_accessibility(AccessibilityNode {
+                role: accessibility_role,
+                label: field.label.clone().into(),
+                hint: field.description.clone().map(Into::into),
+                value: Some(value.into()),
+                states,
+                ..Default::default()
+            });
+        node.id = Some(field.key.clone().into());
+        if let FieldKind::Choice(options) = &field.kind {
+            node = node.extend_children(
+                options
+                    .iter()
+                    .map(|option| SemanticNode::new("option").with_label(option.clone())),
+            );
+        }
+        if let Some(error) = self.state.error(&field.key) {
+            node = node.push_child(SemanticNode::new("error").with_content(error.to_string()));
+        }
+        node
+    }
+}
+
+impl<M, B> View<M, B> for FormView<M, B>
+where
+    M: 'static + Clone + Send + Sync,
+    B: Backend + Send + Sync + 'static,
+{
+    fn view(&self, context: &Context) -> B::AnyView<M> {
+        let mut children = Vec::new();
+        if let Some(title) = &self.state.title {
+            children.push(B::text(
+                title.clone(),
+                16.0,
+                None,
+                true,
+                false,
+                None,
+                None,
+                Length::Fill,
+                iced::Alignment::Start,
+                context,
+            ));
+        }
+        if let Some(description) = &self.state.description {
+            children.push(self.caption(description.clone(), None, context));
+        }
+
+        let mut section = None;
+        for field in self.state.fields() {
+            if field.section.is_some() && field.section != section {
+                children.push(B::text(
+                    field.section.clone().unwrap_or_default(),
+                    13.0,
+                    Some(context.theme.colors.text_secondary),
+                    true,
+                    false,
+                    None,
+                    None,
+                    Length::Fill,
+                    iced::Alignment::Start,
+                    context,
+                ));
+            }
+            section = field.section.clone();
+            children.push(self.field(field, context));
+        }
+        children.push(self.buttons(context));
+
+        B::vstack(
+            children,
+            16.0,
+            Padding::default(),
+            Length::Fill,
+            Length::Shrink,
+            iced::Alignment::Start,
+            iced::Alignment::Start,
+            context,
+        )
+    }
+
+    fn describe(&self, _context: &Context) -> SemanticNode {
+        let label = self
+            .state
+            .title
+            .clone()
+            .unwrap_or_else(|| "Form".to_string());
+        let mut states = Vec::new();
+        if self.state.has_changes() {
+            states.push("dirty".into());
+        }
+        if !self.state.is_valid() {
+            states.push("invalid".into());
+        }
+        if self.state.is_submitting() {
+            states.push("busy".into());
+        }
+
+        SemanticNode::new("form")
+            .with_label(label.clone())
+            .with_accessibility(AccessibilityNode {
+                role: AccessibilityRole::Group,
+                label: label.into(),
+                hint: self.state.description.clone().map(Into::into),
+                states,
+                ..Default::default()
+            })
+            .extend_children(
+                self.state
+                    .fields()
+                    .iter()
+                    .map(|field| self.describe_field(field)),
+            )
+            .push_child(SemanticNode::new("button").with_label(self.submit_label.clone()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::TermBackend;
+    use serde_json::json;
+
+    fn state() -> FormState {
+        FormState::from_schema(&json!({
+            "title": "Connection",
+            "type": "object",
+            "properties": {
+                "host": { "type": "string", "description": "Where to connect" },
+                "api_key": { "type": "string" },
+                "tls": { "type": "boolean", "default": true },
+            },
+            "required": ["host"]
+        }))
+    }
+
+    #[test]
+    fn test_forms_describe_fields_and_errors() {
+        let mut state = state();
+        let _ = state.update(FormMessage::Changed("api_key".into(), "sk-123".into()));
+        let _ = state.update(FormMessage::Submit);
+        let description =
+            FormView::<(), TermBackend>::new(&state, |_| ()).describe(&Context::default());
+
+        assert_eq!(description.label.as_deref(), Some("Connection"));
+        let field = |key: &str| {
+            description
+                .children
+                .iter()
+                .find(|child| child.id.as_deref() == Some(key))
+                .unwrap()
+        };
+
+        let host = field("host");
+        let a11y = host.accessibility.as_ref().unwrap();
+        assert_eq!(a11y.role, AccessibilityRole::TextField);
+        assert_eq!(a11y.hint.as_deref(), Some("Where to connect"));
+        assert_eq!(a11y.states, ["required", "invalid"]);
+        assert_eq!(host.children[0].content.as_deref(), Some("Required"));
+
+        let api_key = field("api_key");
+        assert_eq!(api_key.content.as_deref(), Some("••••••"));
+        assert_eq!(
+            api_key.accessibility.as_ref().unwrap().states,
+            ["dirty", "touched"]
+        );
+        assert_eq!(
+            field("tls").accessibility.as_ref().unwrap().role,
+            AccessibilityRole::Switch
+        );
+    }
+
+    #[test]
+    fn test_forms_render_labels_and_errors() {
+        let mut state = state();
+        let _ = state.update(FormMessage::Submit);
+        let rendered = FormView::<(), TermBackend>::new(&state, |_| ())
+            .submit_label("Connect")
+            .view(&Context::default());
+        for text in [
+            "Connection",
+            "Host *",
+            "Where to connect",
+            "Required",
+            "Tls [ON]",
+            "Connect",
+        ] {
+            assert!(
+                rendered.contains(text),
+                "{} missing from {}",
+                text,
+                rendered
+            );
+        }
+    }
+}
@@ -0,0 +1,332 @@
+//! Generating form fields from a JSON Schema, as `schemars` writes them for
+//! a `#[derive(JsonSchema)]` type or as a tool declares its input.
+use super::{FieldKind, FormField};
+use serde_json::Value;
+
+/// A field for every property of the object `schema` describes, in the
+/// order `serde_json` keeps them, which is by name. Nested objects become
+/// fields with dotted keys, in a section of their own; what has no field of
+/// its own, like a list, is edited as JSON. So is an object nested in
+/// itself, which would otherwise expand forever.
+pub fn fields(schema: &Value) -> Vec<FormField> {
+    // A recursive root type is written out inline and again in the
+    // definitions, under its title, for its references to point at
+    let definition = schema["title"].as_str().and_then(|title| {
+        ["definitions", "$defs"]
+            .into_iter()
+            .find_map(|defs| schema[defs].get(title))
+    });
+    let mut fields = Vec::new();
+    let mut expanding = definition.into_iter().collect();
+    object_fields(schema, schema, "", None, &mut expanding, &mut fields);
+    fields
+}
+
+fn object_fields<'a>(
+    root: &'a Value,
+    object: &'a Value,
+    prefix: &str,
+    section: Option<&str>,
+    expanding: &mut Vec<&'a Value>,
+    fields: &mut Vec<FormField>,
+) {
+    let object = resolve(root, object);
+    let Some(properties) = object["properties"].as_object() else {
+        return;
+    };
+    expanding.push(object);
+    let required: Vec<&str> = object["required"]
+        .as_array()
+        .map(|required| required.iter().filter_map(Value::as_str).collect())
+        .unwrap_or_default();
+
+    for (name, property) in properties {
+        let key = format!("{}{}", prefix, name);
+        let (schema, nullable) = non_null(root, property);
+        // What the property says of itself comes before what its type says
+        let text = |field: &str| {
+            property[field]
+                .as_str()
+                .or_else(|| schema[field].as_str())
+                .map(str::to_string)
+        };
+        let label = text("title").unwrap_or_else(|| humanize(name));
+
+        let recursive = expanding.iter().any(|outer| std::ptr::eq(*outer, schema));
+        if schema["properties"].is_object() && !recursive {
+            let prefix = format!("{}.", key);
+            object_fields(root, schema, &prefix, Some(&label), expanding, fields);
+            continue;
+        }
+
+        let mut field = FormField::new(key, label, kind(name, schema));
+        field.description = text("description");
+        field.section = section.map(str::to_string);
+        field.required = required.contains(&name.as_str())
+            && !nullable
+            && !matches!(field.kind, FieldKind::Toggle);
+        field.default = property
+            .get("default")
+            .or_else(|| schema.get("default"))
+            .cloned();
+        field.min = schema["minimum"]
+            .as_f64()
+            .or_else(|| schema["exclusiveMinimum"].as_f64());
+        field.max = schema["maximum"]
+            .as_f64()
+            .or_else(|| schema["exclusiveMaximum"].as_f64())
+            .or_else(|| format_max(schema["format"].as_str()?));
+        field.min_length = schema["minLength"].as_u64().map(|length| length as usize);
+        field.max_length = schema["maxLength"].as_u64().map(|length| length as usize);
+        fields.push(field);
+    }
+    expanding.pop();
+}
+
+/// What's at a `$ref` into the schema's definitions, or a lone `allOf`,
+/// which is how references get a description of their own.
+fn resolve<'a>(root: &'a Value, schema: &'a Value) -> &'a Value {
+    if let Some(reference) = schema["$ref"].as_str() {
+        let target = reference
+            .strip_prefix('#')
+            .and_then(|pointer| root.pointer(pointer));
+        if let Some(target) = target {
+            return resolve(root, target);
+        }
+    }
+    match schema["allOf"].as_array().map(Vec::as_slice) {
+        Some([only]) => resolve(root, only),
+        _ => schema,
+    }
+}
+
+/// The schema with `null` taken out of it, and whether it was there, as
+/// `schemars` writes an `Option`.
+fn non_null<'a>(root: &'a Value, schema: &'a Value) -> (&'a Value, bool) {
+    let schema = resolve(root, schema);
+    for union in ["anyOf", "oneOf"] {
+        if let Some(variants) = schema[union].as_array() {
+            let (null, rest): (Vec<&Value>, Vec<&Value>) = variants
+                .iter()
+                .partition(|variant| variant["type"] == "null");
+            if let ([_], [only]) = (null.as_slice(), rest.as_slice()) {
+                return (resolve(root, only), true);
+            }
+        }
+    }
+    let nullable = schema["type"]
+        .as_array()
+        .is_some_and(|types| types.iter().any(|t| t == "null"));
+    (schema, nullable)
+}
+
+fn kind(name: &str, schema: &Value) -> FieldKind {
+    if let Some(options) = options(schema) {
+        return FieldKind::Choice(options);
+    }
+
+    let types: Vec<&str> = match &schema["type"] {
+        Value::String(t) => vec![t.as_str()],
+        Value::Array(types) => types
+            .iter()
+            .filter_map(Value::as_str)
+            .filter(|t| *t != "null")
+            .collect(),
+        _ => Vec::new(),
+    };
+    match types.as_slice() {
+        ["string"] if is_secret(name, schema) => FieldKind::Secret,
+        ["string"] => FieldKind::Text,
+        ["integer"] => FieldKind::Integer,
+        ["number"] => FieldKind::Number,
+        ["boolean"] => FieldKind::Toggle,
+        _ => FieldKind::Json,
+    }
+}
+
+/// The strings an enum can be, written as an `enum` or, with docs on the
+/// variants, as a `oneOf` of one-value enums.
+fn options(schema: &Value) -> Option<Vec<String>> {
+    let strings = |values: &Vec<Value>| -> Option<Vec<String>> {
+        values
+            .iter()
+            .map(|v| v.as_str().map(str::to_string))
+            .collect()
+    };
+    if let Some(values) = schema["enum"].as_array() {
+        return strings(values);
+    }
+    let variants = schema["oneOf"].as_array()?;
+    variants
+        .iter()
+        .map(|variant| match variant["enum"].as_array() {
+            Some(values) if values.len() == 1 => strings(values).map(|mut v| v.remove(0)),
+            _ => variant["const"].as_str().map(str::to_string),
+        })
+        .collect()
+}
+
+fn is_secret(name: &str, schema: &Value) -> bool {
+    let name = name.to_lowercase();
+    schema["format"] == "password"
+        || schema["writeOnly"] == true
+        || ["password", "secret", "token", "api_key"]
+            .iter()
+            .any(|word| name.contains(word))
+}
+
+/// The largest value of a `schemars` integer format.
+fn format_max(format: &str) -> Option<f64> {
+    Some(match format {
+        "uint8" => u8::MAX as f64,
+        "uint16" => u16::MAX as f64,
+        "uint32" => u32::MAX as f64,
+        "int8" => i8::MAX as f64,
+        "int16" => i16::MAX as f64,
+        "int32" => i32::MAX as f64,
+        _ => return None,
+    })
+}
+
+/// "max_tokens" as "Max tokens".
+fn humanize(name: &str) -> String {
+    let words = name.replace(['_', '-'], " ");
+    let mut chars = words.trim().chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[derive(schemars::JsonSchema)]
+    #[allow(dead_code)]
+    enum Mode {
+        Fast,
+        Safe,
+    }
+
+    #[derive(schemars::JsonSchema)]
+    #[allow(dead_code)]
+    struct Limits {
+        retries: u8,
+    }
+
+    /// Server settings
+    #[derive(schemars::JsonSchema)]
+    #[allow(dead_code)]
+    struct Settings {
+        /// The host name
+        host: String,
+        port: u16,
+        api_key: Option<String>,
+        mode: Mode,
+        fallback: Option<Mode>,
+        limits: Limits,
+        tags: Vec<String>,
+        verbose: bool,
+    }
+
+    #[test]
+    fn test_fields_follow_derived_schemas() {
+        let schema = serde_json::to_value(schemars::schema_for!(Settings)).unwrap();
+        let fields = fields(&schema);
+        let field = |key: &str| fields.iter().find(|field| field.key == key).unwrap();
+
+        let host = field("host");
+        assert_eq!(host.kind, FieldKind::Text);
+        assert_eq!(host.description.as_deref(), Some("The host name"));
+        assert!(host.required);
+
+        let port = field("port");
+        assert_eq!((port.min, port.max), (Some(0.0), Some(65535.0)));
+
+        let api_key = field("api_key");
+        assert_eq!(
+            (&api_key.kind, api_key.required),
+            (&FieldKind::Secret, false)
+        );
+        assert_eq!(api_key.label, "Api key");
+
+        let options = FieldKind::Choice(vec!["Fast".into(), "Safe".into()]);
+        assert_eq!(
+            (&field("mode").kind, field("mode").required),
+            (&options, true)
+        );
+        assert_eq!(
+            (&field("fallback").kind, field("fallback").required),
+            (&options, false)
+        );
+
+        let retries = field("limits.retries");
+        assert_eq!(retries.section.as_deref(), Some("Limits"));
+        assert_eq!(retries.max, Some(255.0));
+
+        assert_eq!(field("tags").kind, FieldKind::Json);
+        assert_eq!(
+            (&field("verbose").kind, field("verbose").required),
+            (&FieldKind::Toggle, false)
+        );
+    }
+
+    #[derive(schemars::JsonSchema)]
+    #[allow(dead_code)]
+    struct Tree {
+        label: String,
+        child: Option<Box<Tree>>,
+    }
+
+    #[derive(schemars::JsonSchema)]
+    #[allow(dead_code)]
+    struct Forest {
+        tree: Tree,
+    }
+
+    #[test]
+    fn test_recursive_types_are_edited_as_json() {
+        let schema = serde_json::to_value(schemars::schema_for!(Tree)).unwrap();
+        let keys = |fields: Vec<FormField>| -> Vec<(String, FieldKind)> {
+            fields.into_iter().map(|f| (f.key, f.kind)).collect()
+        };
+        assert_eq!(
+            keys(fields(&schema)),
+            [
+                ("child".to_string(), FieldKind::Json),
+                ("label".to_string(), FieldKind::Text),
+            ]
+        );
+
+        let schema = serde_json::to_value(schemars::schema_for!(Forest)).unwrap();
+        assert_eq!(
+            keys(fields(&schema)),
+            [
+                ("tree.child".to_string(), FieldKind::Json),
+                ("tree.label".to_string(), FieldKind::Text),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_fields_follow_tool_schemas() {
+        let fields = fields(&json!({
+            "type": "object",
+            "properties": {
+                "path": { "type": "string", "description": "Absolute path to file" },
+                "content": { "type": "string", "default": "", "maxLength": 10 },
+                "mode": { "type": "string", "enum": ["append", "overwrite"] }
+            },
+            "required": ["path"]
+        }));
+        let field = |key: &str| fields.iter().find(|field| field.key == key).unwrap();
+
+        assert_eq!(fields.len(), 3);
+        assert_eq!(field("content").default, Some(json!("")));
+        assert_eq!(field("content").max_length, Some(10));
+        assert!(matches!(field("mode").kind, FieldKind::Choice(_)));
+        assert!(field("path").required && !field("content").required);
+    }
+}
@@ -0,0 +1,739 @@
+//! The fields, inputs and validation of a [`FormView`](super::FormView).
+//!
+//! A [`FormState`] in app state holds what has been typed into each
+//! [`FormField`], which fields were changed or touched, and what their
+//! validators said. The form reports what the user does as [`FormMessage`]s,
+//! to be handed back to [`FormState::update`], which answers a valid
+//! [`FormMessage::Submit`] with [`FormMessage::Submitted`].
+use iced::futures::future::BoxFuture;
+use iced::Task;
+use serde_json::{Map, Value};
+use std::collections::BTreeSet;
+use std::sync::Arc;
+
+/// Checks a field's value, parsed for its kind, and says what's wrong.
+pub type Validator = Arc<dyn Fn(&Value) -> Result<(), String> + Send + Sync>;
+
+/// Checks a field's value in the background, e.g. against a server.
+pub type AsyncValidator =
+    Arc<dyn Fn(Value) -> BoxFuture<'static, Result<(), String>> + Send + Sync>;
+
+/// What a field holds, and so how it's edited and parsed.
+#[derive(Debug, Clone, PartialEq)]
+pub enum FieldKind {
+    Text,
+    /// Text shown as dots, e.g. a password or API key.
+    Secret,
+    Integer,
+    Number,
+    Toggle,
+    /// One of the options.
+    Choice(Vec<String>),
+    /// Any JSON, e.g. a list, typed as text.
+    Json,
+}
+
+/// One field of a form, built like
+/// `FormField::integer("port", "Port").required().min(1.0)`.
+#[derive(Clone)]
+pub struct FormField {
+    /// Where the value goes in [`FormState::values`], with dots between the
+    /// keys of nested objects.
+    pub key: String,
+    pub label: String,
+    pub description: Option<String>,
+    pub kind: FieldKind,
+    pub required: bool,
+    /// Shown above the field and the ones after it with the same section.
+    pub section: Option<String>,
+    pub default: Option<Value>,
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+    pub min_length: Option<usize>,
+    pub max_length: Option<usize>,
+    validators: Vec<Validator>,
+    async_validators: Vec<AsyncValidator>,
+}
+
+impl std::fmt::Debug for FormField {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FormField")
+            .field("key", &self.key)
+            .field("kind", &self.kind)
+            .field("required", &self.required)
+            .finish_non_exhaustive()
+    }
+}
+
+impl FormField {
+    pub fn new(key: impl Into<String>, label: impl Into<String>, kind: FieldKind) -> Self {
+        Self {
+            key: key.into(),
+            label: label.into(),
+            description: None,
+            kind,
+            required: false,
+            section: None,
+            default: None,
+            min: None,
+            max: None,
+            min_length: None,
+            max_length: None,
+            validators: Vec::new(),
+            async_validators: Vec::new(),
+        }
+    }
+
+    pub fn text(key: impl Into<String>, label: impl Into<String>) -> Self {
+        Self::new(key, label, FieldKind::Text)
+    }
+
+    pub fn secret(key: impl Into<String>, label: impl Into<String>) -> Self {
+        Self::new(key, label, FieldKind::Secret)
+    }
+
+    pub fn integer(key: impl Into<String>, label: impl Into<String>) -> Self {
+        Self::new(key, label, FieldKind::Integer)
+    }
+
+    pub fn number(key: impl Into<String>, label: impl Into<String>) -> Self {
+        Self::new(key, label, FieldKind::Number)
+    }
+
+    pub fn toggle(key: impl Into<String>, label: impl Into<String>) -> Self {
+        Self::new(key, label, FieldKind::Toggle)
+    }
+
+    pub fn choice(
+        key: impl Into<String>,
+        label: impl Into<String>,
+        options: impl IntoIterator<Item = impl Into<String>>,
+    ) -> Self {
+        Self::new(
+            key,
+            label,
+            FieldKind::Choice(options.into_iter().map(Into::into).collect()),
+        )
+    }
+
+    pub fn json(key: impl Into<String>, label: impl Into<String>) -> Self {
+        Self::new(key, label, FieldKind::Json)
+    }
+
+    pub fn description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+
+    /// The field can't be left empty.
+    pub fn required(mut self) -> Self {
+        self.required = true;
+        self
+    }
+
+    pub fn section(mut self, section: impl Into<String>) -> Self {
+        self.section = Some(section.into());
+        self
+    }
+
+    /// The value the field starts with.
+    pub fn default(mut self, value: impl Into<Value>) -> Self {
+        self.default = Some(value.into());
+        self
+    }
+
+    /// The smallest number allowed.
+    pub fn min(mut self, min: f64) -> Self {
+        self.min = Some(min);
+        self
+    }
+
+    /// The largest number allowed.
+    pub fn max(mut self, max: f64) -> Self {
+        self.max = Some(max);
+        self
+    }
+
+    /// The fewest characters allowed.
+    pub fn min_length(mut self, length: usize) -> Self {
+        self.min_length = Some(length);
+        self
+    }
+
+    /// The most characters allowed.
+    pub fn max_length(mut self, length: usize) -> Self {
+        self.max_length = Some(length);
+        self
+    }
+
+    /// Checks the value whenever it changes. Not called for empty fields.
+    pub fn validate(
+        mut self,
+        validator: impl Fn(&Value) -> Result<(), String> + Send + Sync + 'static,
+    ) -> Self {
+        self.validators.push(Arc::new(validator));
+        self
+    }
+
+    /// Checks the value in the background once the other checks pass, e.g.
+    /// whether a name is taken. The answer is ignored if the value changed
+    /// in the meantime.
+    pub fn validate_async(
+        mut self,
+        validator: impl Fn(Value) -> BoxFuture<'static, Result<(), String>> + Send + Sync + 'static,
+    ) -> Self {
+        self.async_validators.push(Arc::new(validator));
+        self
+    }
+
+    /// The text a value is edited as.
+    fn input(&self, value: &Value) -> String {
+        match value {
+            Value::Null => String::new(),
+            Value::String(text) => text.clone(),
+            Value::Bool(on) => on.to_string(),
+            Value::Number(number) => number.to_string(),
+            other => other.to_string(),
+        }
+    }
+
+    /// The value of `input`, or what's wrong with it.
+    pub fn parse(&self, input: &str) -> Result<Value, String> {
+        let trimmed = input.trim();
+        if trimmed.is_empty() && self.kind != FieldKind::Toggle {
+            return if self.required {
+                Err("Required".to_string())
+            } else {
+                Ok(Value::Null)
+            };
+        }
+
+        let value = match &self.kind {
+            FieldKind::Text | FieldKind::Secret => Value::String(input.to_string()),
+            FieldKind::Integer => trimmed
+                .parse::<i64>()
+                .map(Value::from)
+                .or_else(|_| trimmed.parse::<u64>().map(Value::from))
+                .map_err(|_| "Must be a whole number".to_string())?,
+            FieldKind::Number => trimmed
+                .parse::<f64>()
+                .ok()
+                .and_then(serde_json::Number::from_f64)
+                .map(Value::Number)
+                .ok_or_else(|| "Must be a number".to_string())?,
+            FieldKind::Toggle => Value::Bool(trimmed == "true"),
+            FieldKind::Choice(options) => {
+                if !options.iter().any(|option| option == trimmed) {
+                    return Err(format!("Must be one of {}", options.join(", ")));
+                }
+                Value::String(trimmed.to_string())
+            }
+            FieldKind::Json => {
+                serde_json::from_str(trimmed).map_err(|e| format!("Must be valid JSON: {}", e))?
+            }
+        };
+
+        if let Some(number) = value.as_f64() {
+            if let Some(min) = self.min.filter(|min| number < *min) {
+                return Err(format!("Must be at least {}", min));
+            }
+            if let Some(max) = self.max.filter(|max| number > *max) {
+                return Err(format!("Must be at most {}", max));
+            }
+        }
+        if let Some(text) = value.as_str() {
+            let length = text.chars().count();
+            if let Some(min) = self.min_length.filter(|min| length < *min) {
+                return Err(format!("Must be at least {} characters", min));
+            }
+            if let Some(max) = self.max_length.filter(|max| length > *max) {
+                return Err(format!("Must be at most {} characters", max));
+            }
+        }
+
+        for validator in &self.validators {
+            validator(&value)?;
+        }
+        Ok(value)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum FormMessage {
+    /// The text of the field with this key changed.
+    Changed(String, String),
+    Toggled(String, bool),
+    /// An async validator answered for the field's input at the time.
+    Validated {
+        key: String,
+        input: String,
+        result: Result<(), String>,
+    },
+    /// Validates every field, and submits if they all pass.
+    Submit,
+    /// Puts every field back as it was.
+    Reset,
+    /// The form was submitted with these values. For the app to handle.
+    Submitted(Value),
+}
+
+/// The inputs of a form and how they stand. Keep it in app state.
+#[derive(Debug, Clone)]
+pub struct FormState {
+    pub title: Option<String>,
+    pub description: Option<String>,
+    fields: Arc<Vec<FormField>>,
+    inputs: Vec<String>,
+    /// The inputs as they were loaded or last saved, for dirty tracking.
+    initial: Vec<String>,
+    touched: BTreeSet<usize>,
+    errors: Vec<Option<String>>,
+    /// The fields waiting on an async validator.
+    pending: BTreeSet<usize>,
+    submit_attempted: bool,
+    /// Submitting once the pending validators answer.
+    submitting: bool,
+}
+
+impl FormState {
+    pub fn new(fields: Vec<FormField>) -> Self {
+        let inputs: Vec<String> = fields
+            .iter()
+            .map(|field| match (&field.default, &field.kind) {
+                (Some(value), _) => field.input(value),
+                (None, FieldKind::Toggle) => "false".to_string(),
+                (None, _) => String::new(),
+            })
+            .collect();
+        let mut state = Self {
+            title: None,
+            description: None,
+            errors: vec![None; fields.len()],
+            fields: Arc::new(fields),
+            initial: inputs.clone(),
+            inputs,
+            touched: BTreeSet::new(),
+            pending: BTreeSet::new(),
+            submit_attempted: false,
+            submitting: false,
+        };
+        state.validate_all();
+        state
+    }
+
+    /// A form with a field for every property of a JSON Schema object, e.g.
+    /// an `IntelligenceTool`'s `input_schema()`, as [`schema::fields`]
+    /// generates them.
+    ///
+    /// [`schema::fields`]: super::schema::fields
+    pub fn from_schema(schema: &Value) -> Self {
+        let mut state = Self::new(super::schema::fields(schema));
+        state.title = schema["title"].as_str().map(str::to_string);
+        state.description = schema["description"].as_str().map(str::to_string);
+        state
+    }
+
+    /// A form for `T`, from its JSON Schema.
+    pub fn for_type<T: schemars::JsonSchema>() -> Self {
+        let schema = serde_json::to_value(schemars::schema_for!(T)).unwrap_or_default();
+        Self::from_schema(&schema)
+    }
+
+    /// A form for editing `value`, e.g. a settings struct, filled in with
+    /// it. Read the edited copy back with [`parse`](Self::parse).
+    pub fn for_value<T: schemars::JsonSchema + serde::Serialize>(value: &T) -> Self {
+        let values = serde_json::to_value(value).unwrap_or_default();
+        Self::for_type::<T>().with_values(&values)
+    }
+
+    pub fn title(mut self, title: impl Into<String>) -> Self {
+        self.title = Some(title.into());
+        self
+    }
+
+    /// Fills in the fields from `values`, nested like
+    /// [`values`](Self::values), as the form's unchanged state.
+    pub fn with_values(mut self, values: &Value) -> Self {
+        for (i, field) in self.fields.iter().enumerate() {
+            let value = field
+                .key
+                .split('.')
+                .try_fold(values, |value, key| value.get(key));
+            if let Some(value) = value {
+                self.inputs[i] = field.input(value);
+            }
+        }
+        self.initial = self.inputs.clone();
+        self.validate_all();
+        self
+    }
+
+    pub fn fields(&self) -> &[FormField] {
+        &self.fields
+    }
+
+    pub fn input(&self, key: &str) -> Option<&str> {
+        self.index(key).map(|i| self.inputs[i].as_str())
+    }
+
+    /// The error to show under the field: once it's been touched, or the
+    /// form submitted.
+    pub fn error(&self, key: &str) -> Option<&str> {
+        let i = self.index(key)?;
+        if self.touched.contains(&i) || self.submit_attempted {
+            self.errors[i].as_deref()
+        } else {
+            None
+        }
+    }
+
+    /// Whether the field was changed from how it was loaded.
+    pub fn is_dirty(&self, key: &str) -> bool {
+        self.index(key)
+            .is_some_and(|i| self.inputs[i] != self.initial[i])
+    }
+
+    /// Whether the user has edited the field, even back to how it was.
+    pub fn is_touched(&self, key: &str) -> bool {
+        self.index(key).is_some_and(|i| self.touched.contains(&i))
+    }
+
+    pub fn is_validating(&self, key: &str) -> bool {
+        self.index(key).is_some_and(|i| self.pending.contains(&i))
+    }
+
+    /// Whether any field was changed.
+    pub fn has_changes(&self) -> bool {
+        self.inputs != self.initial
+    }
+
+    /// Whether every field passes its checks, so far as they've answered.
+    pub fn is_valid(&self) -> bool {
+        self.errors.iter().all(Option::is_none)
+    }
+
+    pub fn is_submitting(&self) -> bool {
+        self.submitting
+    }
+
+    /// The fields' values as an object, with nested objects for dotted
+    /// keys. Fields that don't parse are left out.
+    pub fn values(&self) -> Value {
+        let mut values = Value::Object(Map::new());
+        for (field, input) in self.fields.iter().zip(&self.inputs) {
+            let Ok(value) = field.parse(input) else {
+                continue;
+            };
+            insert(&mut values, &field.key, value);
+        }
+        values
+    }
+
+    /// The values as a `T`, e.g. the settings struct the form was made
+    /// from.
+    pub fn parse<T: serde::de::DeserializeOwned>(&self) -> Result<T, serde_json::Error> {
+        serde_json::from_value(self.values())
+    }
+
+    /// Makes the current inputs the unchanged state, e.g. once saved.
+    pub fn mark_saved(&mut self) {
+        self.initial = self.inputs.clone();
+        self.touched.clear();
+        self.submit_attempted = false;
+    }
+
+    /// Handles a message from the form. Async validators run in the returned
+    /// task.
+    pub fn update(&mut self, message: FormMessage) -> Task<FormMessage> {
+        match message {
+            FormMessage::Changed(key, input) => {
+                if let Some(i) = self.index(&key) {
+                    return self.set_input(i, input);
+                }
+            }
+            FormMessage::Toggled(key, on) => {
+                if let Some(i) = self.index(&key) {
+                    return self.set_input(i, on.to_string());
+                }
+            }
+            FormMessage::Validated { key, input, result } => {
+                let Some(i) = self.index(&key) else {
+                    return Task::none();
+                };
+                // Answers for an earlier input are stale
+                if self.inputs[i] != input || !self.pending.remove(&i) {
+                    return Task::none();
+                }
+                self.errors[i] = result.err();
+                if self.submitting && self.pending.is_empty() {
+                    return self.finish_submit();
+                }
+            }
+            FormMessage::Submit => {
+                self.submit_attempted = true;
+                self.submitting = true;
+                self.validate_all();
+                let valid: Vec<usize> = (0..self.fields.len())
+                    .filter(|i| self.errors[*i].is_none())
+                    .collect();
+                let tasks: Vec<Task<FormMessage>> =
+                    valid.into_iter().map(|i| self.validate_async(i)).collect();
+                if self.pending.is_empty() {
+                    return self.finish_submit();
+                }
+                return Task::batch(tasks);
+            }
+            FormMessage::Reset => {
+                self.inputs = self.initial.clone();
+                self.touched.clear();
+                self.pending.clear();
+                self.submit_attempted = false;
+                self.submitting = false;
+                self.validate_all();
+            }
+            FormMessage::Submitted(_) => {}
+        }
+        Task::none()
+    }
+
+    fn index(&self, key: &str) -> Option<usize> {
+        self.fields.iter().position(|field| field.key == key)
+    }
+
+    fn set_input(&mut self, i: usize, input: String) -> Task<FormMessage> {
+        self.inputs[i] = input;
+        self.touched.insert(i);
+        self.submitting = false;
+        self.pending.remove(&i);
+        self.errors[i] = self.fields[i].parse(&self.inputs[i]).err();
+        if self.errors[i].is_none() {
+            self.validate_async(i)
+        } else {
+            Task::none()
+        }
+    }
+
+    fn validate_all(&mut self) {
+        for (i, field) in self.fields.iter().enumerate() {
+            self.errors[i] = field.parse(&self.inputs[i]).err();
+        }
+    }
+
+    /// Runs the field's async validators one after another, if it has any.
+    fn validate_async(&mut self, i: usize) -> Task<FormMessage> {
+        let field = &self.fields[i];
+        let Ok(value) = field.parse(&self.inputs[i]) else {
+            return Task::none();
+        };
+        if field.async_validators.is_empty() || value.is_null() {
+            return Task::none();
+        }
+
+        self.pending.insert(i);
+        let validators = field.async_validators.clone();
+        let key = field.key.clone();
+        let input = self.inputs[i].clone();
+        Task::perform(
+            async move {
+                for validator in validators {
+                    validator(value.clone()).await?;
+                }
+                Ok(())
+            },
+            move |result| FormMessage::Validated {
+                key: key.clone(),
+                input: input.clone(),
+                result,
+            },
+        )
+    }
+
+    fn finish_submit(&mut self) -> Task<FormMessage> {
+        self.submitting = false;
+        if self.is_valid() {
+            Task::done(FormMessage::Submitted(self.values()))
+        } else {
+            Task::none()
+        }
+    }
+}
+
+/// Puts `value` at the dotted `key` of `object`, making the objects on the
+/// way. Values in the way are left as they are.
+fn insert(object: &mut Value, key: &str, value: Value) {
+    let (first, rest) = match key.split_once('.') {
+        Some((first, rest)) => (first, Some(rest)),
+        None => (key, None),
+    };
+    let Some(map) = object.as_object_mut() else {
+        return;
+    };
+    match rest {
+        Some(rest) => insert(
+            map.entry(first)
+                .or_insert_with(|| Value::Object(Map::new())),
+            rest,
+            value,
+        ),
+        None => {
+            map.insert(first.to_string(), value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn state() -> FormState {
+        FormState::new(vec![
+            FormField::text("name", "Name").required().min_length(2),
+            FormField::integer("server.port", "Port")
+                .min(1.0)
+                .max(65535.0)
+                .default(8080),
+            FormField::toggle("server.tls", "TLS"),
+            FormField::choice("mode", "Mode", ["fast", "safe"]).validate(|value| {
+                if *value == "fast" {
+                    Err("Fast mode is off for now".to_string())
+                } else {
+                    Ok(())
+                }
+            }),
+        ])
+    }
+
+    #[test]
+    fn test_fields_parse_and_check_their_values() {
+        let port = FormField::integer("port", "Port").min(1.0).max(65535.0);
+        assert_eq!(port.parse(" 443 "), Ok(json!(443)));
+        assert_eq!(port.parse(""), Ok(Value::Null));
+        assert_eq!(port.parse("4.5"), Err("Must be a whole number".to_string()));
+        assert_eq!(
+            port.parse("70000"),
+            Err("Must be at most 65535".to_string())
+        );
+
+        let tags = FormField::json("tags", "Tags").required();
+        assert_eq!(tags.parse("[\"a\"]"), Ok(json!(["a"])));
+        assert_eq!(tags.parse(" "), Err("Required".to_string()));
+        assert!(tags
+            .parse("[")
+            .unwrap_err()
+            .starts_with("Must be valid JSON"));
+    }
+
+    #[test]
+    fn test_errors_show_once_touched_or_submitted() {
+        let mut state = state();
+        assert!(!state.is_valid());
+        assert_eq!(state.error("name"), None);
+
+        let _ = state.update(FormMessage::Changed("name".into(), "a".into()));
+        assert_eq!(state.error("name"), Some("Must be at least 2 characters"));
+        assert!(state.is_touched("name") && state.is_dirty("name"));
+
+        let _ = state.update(FormMessage::Changed("mode".into(), "fast".into()));
+        assert_eq!(state.error("mode"), Some("Fast mode is off for now"));
+
+        // Submitting shows the errors of untouched fields too
+        let _ = state.update(FormMessage::Changed("server.port".into(), String::new()));
+        let _ = state.update(FormMessage::Submit);
+        assert!(!state.is_submitting());
+        assert_eq!(state.error("server.port"), None);
+
+        let _ = state.update(FormMessage::Reset);
+        assert!(!state.has_changes());
+        assert_eq!(state.input("server.port"), Some("8080"));
+        assert_eq!(state.error("name"), None);
+    }
+
+    #[test]
+    fn test_values_nest_dotted_keys() {
+        let mut state = state().with_values(&json!({
+            "name": "api",
+            "server": { "port": 443, "tls": true },
+        }));
+        assert!(!state.has_changes());
+        let _ = state.update(FormMessage::Toggled("server.tls".into(), false));
+        let _ = state.update(FormMessage::Changed("mode".into(), "safe".into()));
+
+        assert!(state.is_valid());
+        assert_eq!(
+            state.values(),
+            json!({
+                "name": "api",
+                "server": { "port": 443, "tls": false },
+                "mode": "safe",
+            })
+        );
+        state.mark_saved();
+        assert!(!state.has_changes() && !state.is_touched("mode"));
+    }
+
+    #[test]
+    fn test_settings_round_trip_through_their_schema() {
+        #[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
+        struct Proxy {
+            host: String,
+            port: u16,
+            auth: Option<String>,
+        }
+
+        let mut state = FormState::for_value(&Proxy {
+            host: "localhost".into(),
+            port: 80,
+            auth: None,
+        });
+        assert_eq!(state.input("port"), Some("80"));
+        let _ = state.update(FormMessage::Changed("port".into(), "70000".into()));
+        assert_eq!(state.error("port"), Some("Must be at most 65535"));
+
+        let _ = state.update(FormMessage::Changed("port".into(), "8080".into()));
+        assert_eq!(
+            state.parse::<Proxy>().unwrap(),
+            Proxy {
+                host: "localhost".into(),
+                port: 8080,
+                auth: None,
+            }
+        );
+    }
+
+    #[test]
+    fn test_stale_async_answers_are_ignored() {
+        let mut state = FormState::new(vec![FormField::text("user", "User").validate_async(
+            |value| {
+                Box::pin(async move {
+                    if value == "root" {
+                        Err("Taken".to_string())
+                    } else {
+                        Ok(())
+                    }
+                })
+            },
+        )]);
+
+        let _ = state.update(FormMessage::Changed("user".into(), "root".into()));
+        assert!(state.is_validating("user"));
+        let _ = state.update(FormMessage::Changed("user".into(), "rooted".into()));
+        let _ = state.update(FormMessage::Validated {
+            key: "user".into(),
+            input: "root".into(),
+            result: Err("Taken".into()),
+        });
+        assert_eq!(state.error("user"), None);
+        assert!(state.is_validating("user"));
+
+        // Submitting waits for the answer
+        let _ = state.update(FormMessage::Submit);
+        assert!(state.is_submitting());
+        let _ = state.update(FormMessage::Validated {
+            key: "user".into(),
+            input: "rooted".into(),
+            result: Ok(()),
+        });
+        assert!(!state.is_submitting() && state.is_valid());
+    }
+}
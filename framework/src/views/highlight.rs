@@ -0,0 +1,281 @@
+//! Grammar-based syntax highlighting for [`CodeBlock`](super::CodeBlock).
+//!
+//! Languages are parsed with the Sublime Text grammars bundled with
+//! `syntect`, which are only loaded the first time something is highlighted.
+//! Colours come from a theme derived from the app's [`PeakColors`], and the
+//! result is plain [`TextSpan`]s, so every backend renders it: the terminal
+//! backend as ANSI colours.
+use crate::core::TextSpan;
+use iced::{font, Color};
+use peak_theme::PeakColors;
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::{Arc, LazyLock, Mutex};
+use syntect::easy::HighlightLines;
+use syntect::highlighting::{
+    FontStyle, ScopeSelectors, Style, StyleModifier, Theme, ThemeItem, ThemeSettings,
+};
+use syntect::parsing::{SyntaxReference, SyntaxSet};
+
+static SYNTAXES: LazyLock<SyntaxSet> = LazyLock::new(SyntaxSet::load_defaults_newlines);
+
+/// Themes already built, by the palette they were built from.
+static THEMES: LazyLock<Mutex<HashMap<Palette, Arc<Theme>>>> = LazyLock::new(Default::default);
+
+/// The colours of a [`PeakColors`] that highlighting uses, to tell palettes
+/// apart by.
+pub type Palette = [[u8; 4]; 9];
+
+/// The colours of `colors` that [`theme`] uses.
+pub fn palette(colors: &PeakColors) -> Palette {
+    [
+        colors.text_primary,
+        colors.text_secondary,
+        colors.text_tertiary,
+        colors.primary,
+        colors.accent,
+        colors.success,
+        colors.warning,
+        colors.danger,
+        colors.info,
+    ]
+    .map(Color::into_rgba8)
+}
+
+/// Whether `language`, a name like "Rust" or an extension like "rs", has a
+/// grammar.
+pub fn is_supported(language: &str) -> bool {
+    syntax(language).is_some()
+}
+
+/// Highlights `lines` as one document in `language`, one list of spans per
+/// line. Unknown languages come back as plain text.
+pub fn highlight(lines: &[&str], language: &str, colors: &PeakColors) -> Vec<Vec<TextSpan>> {
+    let syntax = syntax(language).unwrap_or_else(|| SYNTAXES.find_syntax_plain_text());
+    let theme = cached_theme(colors);
+    let mut highlighter = HighlightLines::new(syntax, &theme);
+
+    lines
+        .iter()
+        .map(|line| {
+            // The grammars expect each line to end with a newline
+            let line = format!("{}\n", line);
+            match highlighter.highlight_line(&line, &SYNTAXES) {
+                Ok(regions) => {
+                    let mut spans: Vec<TextSpan> = Vec::new();
+                    for (style, text) in regions {
+                        let text = text.trim_end_matches('\n');
+                        match spans.last_mut() {
+                            Some(last)
+                                if last.color == Some(color(style))
+                                    && last.font == Some(font(style)) =>
+                            {
+                                last.content.push_str(text)
+                            }
+                            _ if text.is_empty() => {}
+                            _ => spans.push(span(style, text)),
+                        }
+                    }
+                    spans
+                }
+                Err(e) => {
+                    log::warn!("Failed to highlight {} code: {}", language, e);
+                    vec![TextSpan::new(line.trim_end_matches('\n'))
+                        .color(colors.text_primary)
+                        .font(iced::Font::MONOSPACE)]
+                }
+            }
+        })
+        .collect()
+}
+
+/// The highlighting theme for a palette.
+pub fn theme(colors: &PeakColors) -> Theme {
+    let rules: [(&str, Color, FontStyle); 12] = [
+        (
+            "comment, punctuation.definition.comment",
+            colors.text_tertiary,
+            FontStyle::ITALIC,
+        ),
+        (
+            "string, constant.character, markup.raw",
+            colors.success,
+            FontStyle::empty(),
+        ),
+        (
+            "constant.numeric, constant.language, constant.other",
+            colors.warning,
+            FontStyle::empty(),
+        ),
+        (
+            "keyword, storage, keyword.control",
+            colors.accent,
+            FontStyle::empty(),
+        ),
+        (
+            "keyword.operator, punctuation",
+            colors.text_secondary,
+            FontStyle::empty(),
+        ),
+        (
+            "entity.name.function, support.function, meta.function-call variable.function",
+            colors.info,
+            FontStyle::empty(),
+        ),
+        (
+            "entity.name.type, entity.name.class, entity.name.struct, entity.name.enum, \
+             entity.name.trait, support.type, support.class, storage.type.primitive",
+            colors.primary,
+            FontStyle::empty(),
+        ),
+        (
+            "entity.name.tag, markup.heading",
+            colors.primary,
+            FontStyle::BOLD,
+        ),
+        (
+            "entity.other.attribute-name, variable.parameter",
+            colors.warning,
+            FontStyle::empty(),
+        ),
+        ("markup.inserted", colors.success, FontStyle::empty()),
+        ("markup.deleted, invalid", colors.danger, FontStyle::empty()),
+        (
+            "meta.diff.header, meta.diff.range",
+            colors.info,
+            FontStyle::BOLD,
+        ),
+    ];
+
+    Theme {
+        name: Some("Peak".into()),
+        author: None,
+        settings: ThemeSettings {
+            foreground: Some(syntect_color(colors.text_primary)),
+            ..ThemeSettings::default()
+        },
+        scopes: rules
+            .into_iter()
+            .map(|(scope, color, font_style)| ThemeItem {
+                scope: ScopeSelectors::from_str(scope).expect("valid scope selector"),
+                style: StyleModifier {
+                    foreground: Some(syntect_color(color)),
+                    background: None,
+                    font_style: Some(font_style),
+                },
+            })
+            .collect(),
+    }
+}
+
+/// The theme for a palette, built only the first time it is asked for.
+fn cached_theme(colors: &PeakColors) -> Arc<Theme> {
+    let mut themes = THEMES.lock().unwrap_or_else(|e| e.into_inner());
+    themes
+        .entry(palette(colors))
+        .or_insert_with(|| Arc::new(theme(colors)))
+        .clone()
+}
+
+fn syntax(language: &str) -> Option<&'static SyntaxReference> {
+    let language = language.trim();
+    if language.is_empty() {
+        return None;
+    }
+
+    SYNTAXES
+        .find_syntax_by_token(language)
+        .or_else(|| SYNTAXES.find_syntax_by_name(language))
+}
+
+fn span(style: Style, text: &str) -> TextSpan {
+    let mut span = TextSpan::new(text).color(color(style)).font(font(style));
+    if style.font_style.contains(FontStyle::BOLD) {
+        span = span.bold();
+    }
+    span
+}
+
+fn color(style: Style) -> Color {
+    let syntect::highlighting::Color { r, g, b, a } = style.foreground;
+    Color::from_rgba8(r, g, b, a as f32 / 255.0)
+}
+
+fn font(style: Style) -> iced::Font {
+    iced::Font {
+        weight: if style.font_style.contains(FontStyle::BOLD) {
+            font::Weight::Bold
+        } else {
+            font::Weight::Normal
+        },
+        style: if style.font_style.contains(FontStyle::ITALIC) {
+            font::Style::Italic
+        } else {
+            font::Style::Normal
+        },
+        ..iced::Font::MONOSPACE
+    }
+}
+
+fn syntect_color(color: Color) -> syntect::highlighting::Color {
+    let [r, g, b, a] = color.into_rgba8();
+    syntect::highlighting::Color { r, g, b, a }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn colors() -> PeakColors {
+        peak_theme::PeakTheme::Cupertino.colors(peak_theme::ThemeTone::Dark)
+    }
+
+    fn rgba(color: Option<Color>) -> [u8; 4] {
+        color.unwrap().into_rgba8()
+    }
+
+    #[test]
+    fn test_highlights_known_languages() {
+        let colors = colors();
+
+        let lines = highlight(&["# comment", "x = \"text\""], "python", &colors);
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0][0].content, "# comment");
+        assert_eq!(rgba(lines[0][0].color), colors.text_tertiary.into_rgba8());
+
+        let string = lines[1]
+            .iter()
+            .find(|span| span.content.contains("text"))
+            .unwrap();
+        assert_eq!(rgba(string.color), colors.success.into_rgba8());
+        assert_eq!(
+            lines[1]
+                .iter()
+                .map(|s| s.content.as_str())
+                .collect::<String>(),
+            "x = \"text\""
+        );
+    }
+
+    #[test]
+    fn test_unknown_languages_are_plain() {
+        let colors = colors();
+
+        assert!(is_supported("rs"));
+        assert!(is_supported("Rust"));
+        assert!(!is_supported("no-such-language"));
+
+        let lines = highlight(&["fn main() {}"], "no-such-language", &colors);
+        assert_eq!(lines[0].len(), 1);
+        assert_eq!(rgba(lines[0][0].color), colors.text_primary.into_rgba8());
+    }
+
+    #[test]
+    fn test_themes_are_built_once_per_palette() {
+        let dark = colors();
+        let light = peak_theme::PeakTheme::Cupertino.colors(peak_theme::ThemeTone::Light);
+
+        assert!(Arc::ptr_eq(&cached_theme(&dark), &cached_theme(&dark)));
+        assert!(!Arc::ptr_eq(&cached_theme(&dark), &cached_theme(&light)));
+    }
+}
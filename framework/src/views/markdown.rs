@@ -1,13 +1,19 @@
 use crate::core::{Backend, Context, ScrollDirection, TextSpan, View};
 use crate::views::CodeBlock;
-use iced::{font, Length};
+use iced::{font, Color, Length};
+use pulldown_cmark::{CodeBlockKind, Event, HeadingLevel, Options, Tag, TagEnd};
 use std::sync::Arc;
 
+/// Renders CommonMark with the GitHub extensions (tables, task lists and
+/// strikethrough). Fenced code becomes a [`CodeBlock`], images are loaded
+/// with `B::image` and clicking a link sends the message from
+/// [`MarkdownView::on_link`].
 pub struct MarkdownView<Message: 'static + Send + Sync> {
     content: String,
     size: f32,
     padding: iced::Padding,
     on_copy: Option<Arc<dyn Fn(String) -> Message + Send + Sync>>,
+    on_link: Option<Arc<dyn Fn(String) -> Message + Send + Sync>>,
 }
 
 impl<Message: 'static + Send + Sync> MarkdownView<Message> {
@@ -22,6 +28,7 @@ impl<Message: 'static + Send + Sync> MarkdownView<Message> {
                 left: 0.0,
             },
             on_copy: None,
+            on_link: None,
         }
     }
 
@@ -42,6 +49,15 @@ impl<Message: 'static + Send + Sync> MarkdownView<Message> {
         self.on_copy = Some(Arc::new(f));
         self
     }
+
+    /// Called with the destination of a clicked link.
+    pub fn on_link<F>(mut self, f: F) -> Self
+    where
+        F: Fn(String) -> Message + Send + Sync + 'static,
+    {
+        self.on_link = Some(Arc::new(f));
+        self
+    }
 }
 
 impl<Message, B: Backend> View<Message, B> for MarkdownView<Message>
@@ -49,597 +65,365 @@ where
     Message: 'static + Clone + Send + Sync,
 {
     fn view(&self, context: &Context) -> B::AnyView<Message> {
-        let mut children: Vec<B::AnyView<Message>> = Vec::new();
+        let children = parse(&self.content)
+            .iter()
+            .map(|block| self.render_block::<B>(block, context))
+            .collect();
 
-        let mut in_code_block = false;
-        let mut current_language: Option<String> = None;
-        let mut code_buffer = String::new();
+        B::vstack(
+            children,
+            16.0,
+            self.padding,
+            Length::Fill,
+            Length::Shrink,
+            iced::Alignment::Start,
+            iced::Alignment::Start,
+            context,
+        )
+    }
 
-        let mut table_buffer: Vec<String> = Vec::new();
+    fn describe(&self, _context: &Context) -> crate::core::SemanticNode {
+        crate::core::SemanticNode::new("article")
+            .with_label("Markdown Content")
+            .with_content(self.content.chars().take(100).collect::<String>())
+    }
+}
 
-        for line in self.content.lines() {
-            let trimmed = line.trim();
+impl<Message: 'static + Clone + Send + Sync> MarkdownView<Message> {
+    fn render_block<B: Backend>(&self, block: &Block, context: &Context) -> B::AnyView<Message> {
+        let colors = &context.theme.colors;
 
-            // Table detection
-            if trimmed.starts_with('|') {
-                table_buffer.push(line.to_string());
-                continue;
-            } else if !table_buffer.is_empty() {
-                // Table ended, flush it
-                if table_buffer.len() >= 2 && is_separator_line(&table_buffer[1]) {
-                    children.push(render_table::<Message, B>(
-                        table_buffer.clone(),
-                        self.size,
-                        context,
-                    ));
-                } else {
-                    for l in &table_buffer {
-                        children.push(B::container(
-                            render_rich_text::<Message, B>(l.trim(), self.size, context),
+        match block {
+            Block::Paragraph(inlines) => {
+                self.render_inlines::<B>(inlines, self.size, colors.text_secondary, false, context)
+            }
+            Block::Heading(level, inlines) => {
+                let scale = match level {
+                    1 => 2.0,
+                    2 => 1.5,
+                    3 => 1.25,
+                    _ => 1.1,
+                };
+                self.render_inlines::<B>(
+                    inlines,
+                    self.size * scale,
+                    colors.text_primary,
+                    true,
+                    context,
+                )
+            }
+            Block::List { start, items } => {
+                let rows = items
+                    .iter()
+                    .enumerate()
+                    .map(|(i, item)| {
+                        let marker = match (item.checked, start) {
+                            (Some(checked), _) => B::icon(
+                                if checked { "check-square" } else { "square" }.to_string(),
+                                self.size,
+                                Some(if checked {
+                                    colors.success
+                                } else {
+                                    colors.text_secondary
+                                }),
+                                context,
+                            ),
+                            (None, Some(start)) => B::text(
+                                format!("{}.", start + i as u64),
+                                self.size * 0.875,
+                                Some(colors.text_secondary),
+                                false,
+                                false,
+                                None,
+                                Some(iced::Font::MONOSPACE),
+                                Length::Shrink,
+                                iced::Alignment::Start,
+                                context,
+                            ),
+                            (None, None) => B::text(
+                                "•".to_string(),
+                                self.size * 0.875,
+                                Some(colors.text_secondary),
+                                false,
+                                false,
+                                None,
+                                None,
+                                Length::Shrink,
+                                iced::Alignment::Start,
+                                context,
+                            ),
+                        };
+
+                        let content = B::vstack(
+                            item.blocks
+                                .iter()
+                                .map(|block| self.render_block::<B>(block, context))
+                                .collect(),
+                            8.0,
                             iced::Padding::ZERO,
                             Length::Fill,
                             Length::Shrink,
-                            None,
-                            0.0,
-                            0.0,
-                            None,
-                            None,
                             iced::Alignment::Start,
                             iced::Alignment::Start,
                             context,
-                        ));
-                    }
-                }
-                table_buffer.clear();
-            }
-
-            if trimmed.starts_with("```") {
-                if in_code_block {
-                    // Render code block
-                    let mut block = CodeBlock::new(&code_buffer);
-                    if let Some(lang) = &current_language {
-                        block = block.language(lang);
-                    }
-
-                    if let Some(on_copy) = &self.on_copy {
-                        let f = on_copy.clone();
-                        block = block.on_copy(move |s| f(s));
-                    }
-
-                    children.push(View::<Message, B>::view(&block, context));
-
-                    code_buffer.clear();
-                    in_code_block = false;
-                    current_language = None;
-                } else {
-                    in_code_block = true;
-                    let lang = trimmed.trim_start_matches("```").trim();
-                    if !lang.is_empty() {
-                        current_language = Some(lang.to_string());
-                    }
-                }
-                continue;
-            }
+                        );
 
-            if in_code_block {
-                code_buffer.push_str(line);
-                code_buffer.push('\n');
-                continue;
-            }
-
-            if trimmed.is_empty() {
-                continue;
-            }
-
-            // Headers
-            if trimmed.starts_with("# ") {
-                children.push(B::text(
-                    trimmed.trim_start_matches("# ").trim().to_string(),
-                    self.size * 2.0,
-                    Some(context.theme.colors.text_primary),
-                    true,
-                    false,
-                    None,
-                    None,
-                    Length::Fill,
-                    iced::Alignment::Start,
-                    context,
-                ));
-            } else if trimmed.starts_with("## ") {
-                children.push(B::text(
-                    trimmed.trim_start_matches("## ").trim().to_string(),
-                    self.size * 1.5,
-                    Some(context.theme.colors.text_primary),
-                    true,
-                    false,
-                    None,
-                    None,
-                    Length::Fill,
-                    iced::Alignment::Start,
-                    context,
-                ));
-            } else if trimmed.starts_with("### ") {
-                children.push(B::text(
-                    trimmed.trim_start_matches("### ").trim().to_string(),
-                    self.size * 1.25,
-                    Some(context.theme.colors.text_primary),
-                    true,
-                    false,
-                    None,
-                    None,
-                    Length::Fill,
-                    iced::Alignment::Start,
-                    context,
-                ));
-            }
-            // Checkboxes
-            else if trimmed.starts_with("- [ ] ") || trimmed.starts_with("- [x] ") {
-                let is_checked = trimmed.starts_with("- [x] ");
-                let content = if is_checked {
-                    trimmed.trim_start_matches("- [x] ").trim().to_string()
-                } else {
-                    trimmed.trim_start_matches("- [ ] ").trim().to_string()
-                };
-
-                let icon_text = if is_checked {
-                    B::text(
-                        "[x]".to_string(),
-                        16.0,
-                        Some(context.theme.colors.success),
-                        false,
-                        false,
-                        None,
-                        None,
-                        Length::Shrink,
-                        iced::Alignment::Start,
-                        context,
-                    )
-                } else {
-                    B::text(
-                        "[ ]".to_string(),
-                        16.0,
-                        Some(context.theme.colors.text_secondary),
-                        false,
-                        false,
-                        None,
-                        None,
-                        Length::Shrink,
-                        iced::Alignment::Start,
-                        context,
-                    )
-                };
+                        B::hstack(
+                            vec![marker, content],
+                            if item.checked.is_some() { 12.0 } else { 8.0 },
+                            iced::Padding::ZERO,
+                            Length::Fill,
+                            Length::Shrink,
+                            iced::Alignment::Start,
+                            iced::Alignment::Start,
+                            context,
+                        )
+                    })
+                    .collect();
 
-                children.push(B::hstack(
-                    vec![
-                        icon_text,
-                        render_rich_text::<Message, B>(&content, self.size, context),
-                    ],
-                    12.0,
+                B::vstack(
+                    rows,
+                    8.0,
                     iced::Padding::ZERO,
                     Length::Fill,
                     Length::Shrink,
                     iced::Alignment::Start,
                     iced::Alignment::Start,
                     context,
-                ));
+                )
             }
-            // Bullet points
-            else if trimmed.starts_with("- ") {
-                let content = trimmed.trim_start_matches("- ").trim().to_string();
-                children.push(B::hstack(
-                    vec![
-                        B::text(
-                            "•".to_string(),
-                            self.size * 0.875,
-                            Some(context.theme.colors.text_secondary),
-                            false,
-                            false,
-                            None,
-                            None,
-                            Length::Shrink,
-                            iced::Alignment::Start,
-                            context,
-                        ),
-                        render_rich_text::<Message, B>(&content, self.size, context),
-                    ],
-                    8.0,
+            Block::Quote(blocks) => {
+                let bar = B::container(
+                    B::space(Length::Fixed(3.0), Length::Fill, context),
                     iced::Padding::ZERO,
+                    Length::Fixed(3.0),
                     Length::Fill,
-                    Length::Shrink,
+                    Some(colors.divider),
+                    1.5,
+                    0.0,
+                    None,
+                    None,
                     iced::Alignment::Start,
                     iced::Alignment::Start,
                     context,
-                ));
-            }
-            // Numbered list (Simple detection)
-            else if let Some(rest) = parse_numbered_list(trimmed) {
-                let content = rest.to_string();
-                children.push(B::hstack(
-                    vec![
-                        B::text(
-                            "1.".to_string(),
-                            self.size * 0.875,
-                            Some(context.theme.colors.text_secondary),
-                            false,
-                            false,
-                            None,
-                            Some(iced::Font::MONOSPACE),
-                            Length::Shrink,
-                            iced::Alignment::Start,
-                            context,
-                        ),
-                        render_rich_text::<Message, B>(&content, self.size, context),
-                    ],
-                    8.0,
+                );
+                let content = B::vstack(
+                    blocks
+                        .iter()
+                        .map(|block| self.render_block::<B>(block, context))
+                        .collect(),
+                    12.0,
                     iced::Padding::ZERO,
                     Length::Fill,
                     Length::Shrink,
                     iced::Alignment::Start,
                     iced::Alignment::Start,
                     context,
-                ));
-            }
-            // Paragraph
-            else {
-                children.push(B::container(
-                    render_rich_text::<Message, B>(trimmed, self.size, context),
+                );
+
+                B::hstack(
+                    vec![bar, content],
+                    12.0,
                     iced::Padding::ZERO,
                     Length::Fill,
                     Length::Shrink,
-                    None,
-                    0.0,
-                    0.0,
-                    None,
-                    None,
                     iced::Alignment::Start,
                     iced::Alignment::Start,
                     context,
-                ));
+                )
             }
-        }
-
-        // Final flushes
-        if !table_buffer.is_empty() {
-            if table_buffer.len() >= 2 && is_separator_line(&table_buffer[1]) {
-                children.push(render_table::<Message, B>(table_buffer, self.size, context));
-            } else {
-                for l in table_buffer {
-                    children.push(B::container(
-                        render_rich_text::<Message, B>(l.trim(), self.size, context),
-                        iced::Padding::ZERO,
-                        Length::Fill,
-                        Length::Shrink,
-                        None,
-                        0.0,
-                        0.0,
-                        None,
-                        None,
-                        iced::Alignment::Start,
-                        iced::Alignment::Start,
-                        context,
-                    ));
+            Block::Code { language, code } => {
+                let mut block = CodeBlock::new(code);
+                if let Some(language) = language {
+                    block = block.language(language);
                 }
+                if let Some(on_copy) = &self.on_copy {
+                    let f = on_copy.clone();
+                    block = block.on_copy(move |s| f(s));
+                }
+                View::<Message, B>::view(&block, context)
             }
-        }
-
-        if !code_buffer.is_empty() {
-            let mut block = CodeBlock::new(&code_buffer);
-            if let Some(on_copy) = &self.on_copy {
-                let f = on_copy.clone();
-                block = block.on_copy(move |s| f(s));
-            }
-            children.push(View::<Message, B>::view(&block, context));
-        }
-
-        B::vstack(
-            children,
-            16.0,
-            self.padding,
-            Length::Fill,
-            Length::Shrink,
-            iced::Alignment::Start,
-            iced::Alignment::Start,
-            context,
-        )
-    }
-
-    fn describe(&self, _context: &Context) -> crate::core::SemanticNode {
-        crate::core::SemanticNode::new("article")
-            .with_label("Markdown Content")
-            .with_content(self.content.chars().take(100).collect::<String>())
-    }
-}
-
-fn parse_numbered_list(line: &str) -> Option<&str> {
-    let chars: Vec<char> = line.chars().take(10).collect();
-    if let Some(dot_idx) = chars.iter().position(|&c| c == '.') {
-        if dot_idx > 0 && dot_idx < chars.len() - 1 && chars[dot_idx + 1] == ' ' {
-            if chars[0..dot_idx].iter().all(|c| c.is_numeric()) {
-                return Some(line[dot_idx + 2..].trim());
-            }
-        }
-    }
-    None
-}
-
-fn render_rich_text<Message: Clone + Send + Sync + 'static, B: Backend>(
-    content: &str,
-    size: f32,
-    context: &Context,
-) -> B::AnyView<Message> {
-    let mut spans: Vec<TextSpan> = Vec::new();
-    let theme = context.theme;
-
-    // Split strictly by ** for bold and ` for code
-    let mut remaining = content;
-
-    while !remaining.is_empty() {
-        if let Some(start) = remaining.find("**") {
-            // Text before **
-            if start > 0 {
-                spans.push(TextSpan {
-                    content: remaining[..start].into(),
-                    color: Some(theme.colors.text_secondary),
-                    font: None,
-                    size: None,
-                    is_bold: false,
-                    is_dim: false,
-                });
-            }
-            if let Some(end) = remaining[start + 2..].find("**") {
-                let bold_text = &remaining[start + 2..start + 2 + end];
-                spans.push(TextSpan {
-                    content: bold_text.into(),
-                    color: Some(theme.colors.text_primary),
-                    font: Some(font::Font {
-                        weight: font::Weight::Bold,
-                        ..Default::default()
-                    }),
-                    size: None,
-                    is_bold: true,
-                    is_dim: false,
-                });
-                remaining = &remaining[start + 2 + end + 2..];
-            } else {
-                spans.push(TextSpan {
-                    content: remaining[..].into(),
-                    color: Some(theme.colors.text_secondary),
-                    font: None,
-                    size: None,
-                    is_bold: false,
-                    is_dim: false,
-                });
-                break;
-            }
-        } else if let Some(start) = remaining.find('`') {
-            if start > 0 {
-                spans.push(TextSpan {
-                    content: remaining[..start].into(),
-                    color: Some(theme.colors.text_secondary),
-                    font: None,
-                    size: None,
-                    is_bold: false,
-                    is_dim: false,
-                });
+            Block::Table {
+                alignments,
+                header,
+                rows,
+            } => self.render_table::<B>(alignments, header, rows, context),
+            Block::Image { src, .. } => {
+                B::image(src.clone(), Length::Fill, Length::Shrink, 8.0, context)
             }
-            if let Some(end) = remaining[start + 1..].find('`') {
-                let code_text = &remaining[start + 1..start + 1 + end];
-                spans.push(TextSpan {
-                    content: format!(" {} ", code_text).into(),
-                    color: Some(theme.colors.primary),
-                    font: Some(font::Font::MONOSPACE),
-                    size: None,
-                    is_bold: false,
-                    is_dim: false,
-                });
-                remaining = &remaining[start + 1 + end + 1..];
-            } else {
-                spans.push(TextSpan {
-                    content: remaining[..].into(),
-                    color: Some(theme.colors.text_secondary),
-                    font: None,
-                    size: None,
-                    is_bold: false,
-                    is_dim: false,
-                });
-                break;
-            }
-        } else {
-            spans.push(TextSpan {
-                content: remaining.into(),
-                color: Some(theme.colors.text_secondary),
-                font: None,
-                size: None,
-                is_bold: false,
-                is_dim: false,
-            });
-            break;
+            Block::Rule => B::divider(context),
         }
     }
 
-    B::rich_text(spans, size, Length::Fill, iced::Alignment::Start, context)
-}
-
-fn is_separator_line(line: &str) -> bool {
-    let trimmed = line.trim();
-    if !trimmed.starts_with('|') {
-        return false;
-    }
-    // Check if it's a separator line (contains only |, -, :, whitespace)
-    // and at least one dash
-    let mut has_dash = false;
-    for c in trimmed.chars() {
-        match c {
-            '|' | ':' | ' ' | '\t' | '\r' | '\n' => {}
-            '-' => has_dash = true,
-            _ => return false,
+    fn render_inlines<B: Backend>(
+        &self,
+        inlines: &[Inline],
+        size: f32,
+        color: Color,
+        bold: bool,
+        context: &Context,
+    ) -> B::AnyView<Message> {
+        let spans = inlines
+            .iter()
+            .map(|inline| span(inline, color, bold, context))
+            .collect();
+
+        match &self.on_link {
+            Some(on_link) => B::rich_text_with_links(
+                spans,
+                size,
+                Length::Fill,
+                iced::Alignment::Start,
+                on_link.clone(),
+                context,
+            ),
+            None => B::rich_text(spans, size, Length::Fill, iced::Alignment::Start, context),
         }
     }
-    has_dash
-}
-
-fn render_table<Message: Clone + Send + Sync + 'static, B: Backend>(
-    lines: Vec<String>,
-    size: f32,
-    context: &Context,
-) -> B::AnyView<Message> {
-    // 1. Parse rows
-    let mut rows: Vec<Vec<String>> = lines
-        .into_iter()
-        .map(|line| {
-            let line = line.trim();
-            let line = if line.starts_with('|') {
-                &line[1..]
-            } else {
-                line
-            };
-            let line = if line.ends_with('|') {
-                &line[..line.len() - 1]
-            } else {
-                line
-            };
-            line.split('|').map(|s| s.trim().to_string()).collect()
-        })
-        .collect();
 
-    if rows.len() < 2 {
-        return B::vstack(
-            vec![],
-            0.0,
-            iced::Padding::ZERO,
-            Length::Shrink,
-            Length::Shrink,
-            iced::Alignment::Start,
-            iced::Alignment::Start,
-            context,
-        );
-    }
+    fn render_table<B: Backend>(
+        &self,
+        alignments: &[iced::Alignment],
+        header: &[Vec<Inline>],
+        rows: &[Vec<Vec<Inline>>],
+        context: &Context,
+    ) -> B::AnyView<Message> {
+        // Column weighting heuristic
+        let column_weights: Vec<u16> = header
+            .iter()
+            .map(|h| {
+                let h = plain(h).to_lowercase();
+                if h.contains("description") || h.contains("summary") {
+                    3
+                } else {
+                    1
+                }
+            })
+            .collect();
 
-    let header = rows.remove(0);
-    let separator = rows.remove(0);
-    let data_rows = rows;
-
-    // 2. Parse alignments
-    let alignments: Vec<iced::Alignment> = separator
-        .iter()
-        .map(|s| {
-            let s = s.trim();
-            let starts = s.starts_with(':');
-            let ends = s.ends_with(':');
-            if starts && ends {
-                iced::Alignment::Center
-            } else if ends {
-                iced::Alignment::End
-            } else {
-                iced::Alignment::Start
-            }
-        })
-        .collect();
-
-    // Column weighting heuristic
-    let column_weights: Vec<u16> = header
-        .iter()
-        .map(|h| {
-            let h = h.to_lowercase();
-            if h.contains("description") || h.contains("summary") {
-                3
-            } else if h.contains("modifier") || h.contains("prop") || h.contains("name") {
-                1
-            } else {
-                1
-            }
-        })
-        .collect();
-
-    // 3. Build the UI
-    let tokens = context.theme;
-
-    // Header Row
-    let mut header_cells = Vec::new();
-    for (i, cell) in header.iter().enumerate() {
-        let align = alignments.get(i).cloned().unwrap_or(iced::Alignment::Start);
-        let weight = *column_weights.get(i).unwrap_or(&1);
-
-        header_cells.push(B::container(
-            B::text(
-                cell.clone(),
-                16.0,
-                Some(tokens.colors.text_primary),
-                true,
-                false,
+        let tokens = context.theme;
+        let cell = |i: usize, content: B::AnyView<Message>, align_y| {
+            B::container(
+                content,
+                iced::Padding::ZERO,
+                Length::FillPortion(*column_weights.get(i).unwrap_or(&1)),
+                Length::Shrink,
+                None,
+                0.0,
+                0.0,
                 None,
                 None,
+                alignments.get(i).cloned().unwrap_or(iced::Alignment::Start),
+                align_y,
+                context,
+            )
+        };
+        let row = |cells: Vec<B::AnyView<Message>>| {
+            B::hstack(
+                cells,
+                12.0,
+                iced::Padding::ZERO,
                 Length::Fill,
+                Length::Shrink,
+                iced::Alignment::Start,
                 iced::Alignment::Start,
                 context,
-            ),
-            iced::Padding::ZERO,
-            Length::FillPortion(weight),
+            )
+        };
+
+        // Header Row
+        let header_cells = header
+            .iter()
+            .enumerate()
+            .map(|(i, inlines)| {
+                let content = self.render_inlines::<B>(
+                    inlines,
+                    16.0,
+                    tokens.colors.text_primary,
+                    true,
+                    context,
+                );
+                cell(i, content, iced::Alignment::Center)
+            })
+            .collect();
+
+        let mut table_children = Vec::new();
+        table_children.push(B::container(
+            row(header_cells),
+            iced::Padding::from([12, 16]),
+            Length::Fill,
             Length::Shrink,
-            None,
+            Some(tokens.colors.surface_variant.scale_alpha(0.2)),
             0.0,
             0.0,
             None,
             None,
-            align,
-            iced::Alignment::Center,
+            iced::Alignment::Start,
+            iced::Alignment::Start,
             context,
         ));
-    }
 
-    let header_row = B::hstack(
-        header_cells,
-        12.0,
-        iced::Padding::ZERO,
-        Length::Fill,
-        Length::Shrink,
-        iced::Alignment::Start,
-        iced::Alignment::Start,
-        context,
-    );
-
-    let mut table_children = Vec::new();
-    table_children.push(B::container(
-        header_row,
-        iced::Padding::from([12, 16]),
-        Length::Fill,
-        Length::Shrink,
-        Some(tokens.colors.surface_variant.scale_alpha(0.2)),
-        0.0,
-        0.0,
-        None,
-        None,
-        iced::Alignment::Start,
-        iced::Alignment::Start,
-        context,
-    ));
-
-    // Data Rows
-    for (r_idx, row_data) in data_rows.iter().enumerate() {
-        let mut row_cells = Vec::new();
-        for (i, cell) in row_data.iter().enumerate() {
-            let align = alignments.get(i).cloned().unwrap_or(iced::Alignment::Start);
-            let weight = *column_weights.get(i).unwrap_or(&1);
-
-            row_cells.push(B::container(
-                render_rich_text::<Message, B>(cell, size, context),
-                iced::Padding::ZERO,
-                Length::FillPortion(weight),
+        // Data Rows
+        for (r_idx, row_data) in rows.iter().enumerate() {
+            let row_cells = row_data
+                .iter()
+                .enumerate()
+                .map(|(i, inlines)| {
+                    let content = self.render_inlines::<B>(
+                        inlines,
+                        self.size,
+                        tokens.colors.text_secondary,
+                        false,
+                        context,
+                    );
+                    cell(i, content, iced::Alignment::Start)
+                })
+                .collect();
+
+            let row_bg = if r_idx % 2 == 1 {
+                Some(tokens.colors.surface_variant.scale_alpha(0.05))
+            } else {
+                None
+            };
+
+            table_children.push(B::container(
+                row(row_cells),
+                iced::Padding::from([8, 16]),
+                Length::Fill,
                 Length::Shrink,
-                None,
+                row_bg,
                 0.0,
                 0.0,
                 None,
                 None,
-                align,
+                iced::Alignment::Start,
                 iced::Alignment::Start,
                 context,
             ));
+
+            // Divider
+            if r_idx < rows.len() - 1 {
+                table_children.push(B::container(
+                    B::space(Length::Fill, Length::Fixed(1.0), context),
+                    iced::Padding::ZERO,
+                    Length::Fill,
+                    Length::Shrink,
+                    Some(tokens.colors.divider.scale_alpha(0.5)),
+                    0.0,
+                    0.0,
+                    None,
+                    None,
+                    iced::Alignment::Start,
+                    iced::Alignment::Start,
+                    context,
+                ));
+            }
         }
 
-        let row = B::hstack(
-            row_cells,
-            12.0,
+        let min_table_width = (header.len() as f32 * 160.0).max(600.0);
+        let table_col = B::vstack(
+            table_children,
+            0.0,
             iced::Padding::ZERO,
             Length::Fill,
             Length::Shrink,
@@ -647,80 +431,513 @@ fn render_table<Message: Clone + Send + Sync + 'static, B: Backend>(
             iced::Alignment::Start,
             context,
         );
-        let row_bg = if r_idx % 2 == 1 {
-            Some(tokens.colors.surface_variant.scale_alpha(0.05))
-        } else {
-            None
-        };
 
-        table_children.push(B::container(
-            row,
-            iced::Padding::from([8, 16]),
-            Length::Fill,
+        let table_container = B::container(
+            table_col,
+            iced::Padding::ZERO,
+            Length::Fixed(min_table_width),
             Length::Shrink,
-            row_bg,
-            0.0,
-            0.0,
             None,
+            8.0,
+            1.0,
+            Some(tokens.colors.divider.scale_alpha(0.3)),
             None,
             iced::Alignment::Start,
             iced::Alignment::Start,
             context,
-        ));
+        );
 
-        // Divider
-        if r_idx < data_rows.len() - 1 {
-            table_children.push(B::container(
-                B::space(Length::Fill, Length::Fixed(1.0), context),
-                iced::Padding::ZERO,
-                Length::Fill,
-                Length::Shrink,
-                Some(tokens.colors.divider.scale_alpha(0.5)),
-                0.0,
-                0.0,
-                None,
-                None,
-                iced::Alignment::Start,
-                iced::Alignment::Start,
-                context,
-            ));
+        B::scroll_view(
+            table_container,
+            Length::Fill,
+            Length::Shrink,
+            None,
+            true,
+            ScrollDirection::Horizontal,
+            context,
+        )
+    }
+}
+
+fn span(inline: &Inline, color: Color, bold: bool, context: &Context) -> TextSpan {
+    let colors = &context.theme.colors;
+
+    if inline.style.code {
+        let mut span = TextSpan::new(format!(" {} ", inline.text))
+            .color(colors.primary)
+            .font(iced::Font::MONOSPACE);
+        if let Some(link) = &inline.link {
+            span = span.link(link.clone());
         }
+        return span;
     }
 
-    let min_table_width = (header.len() as f32 * 160.0).max(600.0);
-    let table_col = B::vstack(
-        table_children,
-        0.0,
-        iced::Padding::ZERO,
-        Length::Fill,
-        Length::Shrink,
-        iced::Alignment::Start,
-        iced::Alignment::Start,
-        context,
-    );
-
-    let table_container = B::container(
-        table_col,
-        iced::Padding::ZERO,
-        Length::Fixed(min_table_width),
-        Length::Shrink,
-        None,
-        8.0,
-        1.0,
-        Some(tokens.colors.divider.scale_alpha(0.3)),
-        None,
-        iced::Alignment::Start,
-        iced::Alignment::Start,
-        context,
-    );
-
-    B::scroll_view(
-        table_container,
-        Length::Fill,
-        Length::Shrink,
-        None,
-        true,
-        ScrollDirection::Horizontal,
-        context,
+    let bold = bold || inline.style.bold;
+    let mut span = TextSpan::new(inline.text.clone()).color(if inline.link.is_some() {
+        colors.primary
+    } else if inline.style.bold {
+        colors.text_primary
+    } else {
+        color
+    });
+
+    if bold || inline.style.italic {
+        span = span.font(font::Font {
+            weight: if bold {
+                font::Weight::Bold
+            } else {
+                font::Weight::Normal
+            },
+            style: if inline.style.italic {
+                font::Style::Italic
+            } else {
+                font::Style::Normal
+            },
+            ..Default::default()
+        });
+    }
+    if bold {
+        span = span.bold();
+    }
+    if inline.style.strikethrough {
+        span = span.strikethrough();
+    }
+    if let Some(link) = &inline.link {
+        span = span.link(link.clone());
+    }
+    span
+}
+
+fn plain(inlines: &[Inline]) -> String {
+    inlines.iter().map(|inline| inline.text.as_str()).collect()
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Block {
+    Paragraph(Vec<Inline>),
+    Heading(u8, Vec<Inline>),
+    List {
+        /// The number of the first item of an ordered list.
+        start: Option<u64>,
+        items: Vec<Item>,
+    },
+    Quote(Vec<Block>),
+    Code {
+        language: Option<String>,
+        code: String,
+    },
+    Table {
+        alignments: Vec<iced::Alignment>,
+        header: Vec<Vec<Inline>>,
+        rows: Vec<Vec<Vec<Inline>>>,
+    },
+    Image {
+        src: String,
+        alt: String,
+    },
+    Rule,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Item {
+    /// Whether a task list item is done; `None` for plain items.
+    checked: Option<bool>,
+    blocks: Vec<Block>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Inline {
+    text: String,
+    style: Style,
+    link: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+struct Style {
+    bold: bool,
+    italic: bool,
+    strikethrough: bool,
+    code: bool,
+}
+
+fn parse(markdown: &str) -> Vec<Block> {
+    let options =
+        Options::ENABLE_TABLES | Options::ENABLE_TASKLISTS | Options::ENABLE_STRIKETHROUGH;
+
+    Parser {
+        events: pulldown_cmark::Parser::new_ext(markdown, options),
+        bold: 0,
+        italic: 0,
+        strikethrough: 0,
+        links: Vec::new(),
+    }
+    .blocks(&mut None)
+}
+
+/// Folds the event stream into [`Block`]s. Each container reads events up
+/// to and including its own end tag.
+struct Parser<'a> {
+    events: pulldown_cmark::Parser<'a>,
+    bold: usize,
+    italic: usize,
+    strikethrough: usize,
+    links: Vec<String>,
+}
+
+impl Parser<'_> {
+    /// Blocks until the end of the enclosing container. Task markers set
+    /// `checked`.
+    fn blocks(&mut self, checked: &mut Option<bool>) -> Vec<Block> {
+        let mut blocks = Vec::new();
+        // Text directly inside a tight list item, outside any paragraph
+        let mut loose = Vec::new();
+
+        while let Some(event) = self.events.next() {
+            if is_inline(&event) {
+                self.inline(event, &mut loose, Some(&mut blocks));
+                continue;
+            }
+            if matches!(event, Event::Start(_) | Event::End(_) | Event::Rule) {
+                flush(&mut loose, &mut blocks);
+            }
+
+            match event {
+                Event::Start(Tag::Paragraph) => {
+                    let inlines = self.inlines(Some(&mut blocks));
+                    flush(&mut { inlines }, &mut blocks);
+                }
+                Event::Start(Tag::Heading { level, .. }) => {
+                    let level = match level {
+                        HeadingLevel::H1 => 1,
+                        HeadingLevel::H2 => 2,
+                        HeadingLevel::H3 => 3,
+                        HeadingLevel::H4 => 4,
+                        HeadingLevel::H5 => 5,
+                        HeadingLevel::H6 => 6,
+                    };
+                    blocks.push(Block::Heading(level, self.inlines(None)));
+                }
+                Event::Start(Tag::BlockQuote(_)) => {
+                    blocks.push(Block::Quote(self.blocks(&mut None)));
+                }
+                Event::Start(Tag::CodeBlock(kind)) => {
+                    let language = match kind {
+                        CodeBlockKind::Fenced(info) => info
+                            .split_whitespace()
+                            .next()
+                            .map(|language| language.to_string()),
+                        CodeBlockKind::Indented => None,
+                    };
+                    let mut code = String::new();
+                    for event in self.events.by_ref() {
+                        match event {
+                            Event::Text(text) => code.push_str(&text),
+                            _ => break,
+                        }
+                    }
+                    blocks.push(Block::Code { language, code });
+                }
+                Event::Start(Tag::List(start)) => {
+                    let mut items = Vec::new();
+                    while let Some(Event::Start(Tag::Item)) = self.events.next() {
+                        let mut checked = None;
+                        let blocks = self.blocks(&mut checked);
+                        items.push(Item { checked, blocks });
+                    }
+                    blocks.push(Block::List { start, items });
+                }
+                Event::Start(Tag::Table(alignments)) => {
+                    let alignments = alignments
+                        .into_iter()
+                        .map(|alignment| match alignment {
+                            pulldown_cmark::Alignment::Center => iced::Alignment::Center,
+                            pulldown_cmark::Alignment::Right => iced::Alignment::End,
+                            _ => iced::Alignment::Start,
+                        })
+                        .collect();
+                    let mut header = Vec::new();
+                    let mut rows = Vec::new();
+                    loop {
+                        match self.events.next() {
+                            Some(Event::Start(Tag::TableHead)) => header = self.row(),
+                            Some(Event::Start(Tag::TableRow)) => rows.push(self.row()),
+                            _ => break,
+                        }
+                    }
+                    blocks.push(Block::Table {
+                        alignments,
+                        header,
+                        rows,
+                    });
+                }
+                // Raw HTML and other containers are skipped with their contents
+                Event::Start(_) => {
+                    self.blocks(&mut None);
+                }
+                Event::End(_) => break,
+                Event::Rule => blocks.push(Block::Rule),
+                Event::TaskListMarker(done) => *checked = Some(done),
+                event => self.inline(event, &mut loose, Some(&mut blocks)),
+            }
+        }
+
+        flush(&mut loose, &mut blocks);
+        blocks
+    }
+
+    fn row(&mut self) -> Vec<Vec<Inline>> {
+        let mut cells = Vec::new();
+        while let Some(Event::Start(Tag::TableCell)) = self.events.next() {
+            cells.push(self.inlines(None));
+        }
+        cells
+    }
+
+    /// Inline content until the end of the enclosing block. Images become
+    /// blocks of their own when `blocks` is given, and alt text otherwise.
+    fn inlines(&mut self, mut blocks: Option<&mut Vec<Block>>) -> Vec<Inline> {
+        let mut inlines = Vec::new();
+
+        while let Some(event) = self.events.next() {
+            if matches!(event, Event::End(_)) && !is_inline(&event) {
+                break;
+            }
+            self.inline(event, &mut inlines, blocks.as_deref_mut());
+        }
+
+        inlines
+    }
+
+    fn inline(&mut self, event: Event, inlines: &mut Vec<Inline>, blocks: Option<&mut Vec<Block>>) {
+        match event {
+            Event::Text(text) => self.push(inlines, &text, false),
+            Event::Code(text) => self.push(inlines, &text, true),
+            Event::SoftBreak => self.push(inlines, " ", false),
+            Event::HardBreak => self.push(inlines, "\n", false),
+            Event::Start(Tag::Strong) => self.bold += 1,
+            Event::End(TagEnd::Strong) => self.bold = self.bold.saturating_sub(1),
+            Event::Start(Tag::Emphasis) => self.italic += 1,
+            Event::End(TagEnd::Emphasis) => self.italic = self.italic.saturating_sub(1),
+            Event::Start(Tag::Strikethrough) => self.strikethrough += 1,
+            Event::End(TagEnd::Strikethrough) => {
+                self.strikethrough = self.strikethrough.saturating_sub(1)
+            }
+            Event::Start(Tag::Link { dest_url, .. }) => self.links.push(dest_url.to_string()),
+            Event::End(TagEnd::Link) => {
+                self.links.pop();
+            }
+            Event::Start(Tag::Image { dest_url, .. }) => {
+                let alt = plain(&self.inlines(None));
+                match blocks {
+                    Some(blocks) => {
+                        flush(inlines, blocks);
+                        blocks.push(Block::Image {
+                            src: dest_url.to_string(),
+                            alt,
+                        });
+                    }
+                    None => self.push(inlines, &alt, false),
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Appends text, merging it into the previous inline when both look
+    /// the same.
+    fn push(&self, inlines: &mut Vec<Inline>, text: &str, code: bool) {
+        let style = Style {
+            bold: self.bold > 0,
+            italic: self.italic > 0,
+            strikethrough: self.strikethrough > 0,
+            code,
+        };
+        let link = self.links.last().cloned();
+
+        match inlines.last_mut() {
+            Some(last) if !code && last.style == style && last.link == link => {
+                last.text.push_str(text)
+            }
+            _ => inlines.push(Inline {
+                text: text.to_string(),
+                style,
+                link,
+            }),
+        }
+    }
+}
+
+/// Styles and links, which open and close within a block. An image's end
+/// is not among them: it closes the alt text read by [`Parser::inline`].
+fn is_inline(event: &Event) -> bool {
+    matches!(
+        event,
+        Event::Start(
+            Tag::Strong | Tag::Emphasis | Tag::Strikethrough | Tag::Link { .. } | Tag::Image { .. }
+        ) | Event::End(TagEnd::Strong | TagEnd::Emphasis | TagEnd::Strikethrough | TagEnd::Link)
     )
 }
+
+/// Moves pending inline content into a paragraph.
+fn flush(inlines: &mut Vec<Inline>, blocks: &mut Vec<Block>) {
+    if inlines.iter().any(|inline| !inline.text.trim().is_empty()) {
+        blocks.push(Block::Paragraph(std::mem::take(inlines)));
+    } else {
+        inlines.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(text: &str) -> Inline {
+        Inline {
+            text: text.to_string(),
+            style: Style::default(),
+            link: None,
+        }
+    }
+
+    fn styled(text: &str, style: Style) -> Inline {
+        Inline {
+            style,
+            ..self::text(text)
+        }
+    }
+
+    #[test]
+    fn test_parses_inline_styles_and_links() {
+        let blocks = parse("Plain **bold** *it* ~~old~~ `code` [site](https://example.com)\nnext");
+
+        assert_eq!(
+            blocks,
+            vec![Block::Paragraph(vec![
+                text("Plain "),
+                styled(
+                    "bold",
+                    Style {
+                        bold: true,
+                        ..Style::default()
+                    }
+                ),
+                text(" "),
+                styled(
+                    "it",
+                    Style {
+                        italic: true,
+                        ..Style::default()
+                    }
+                ),
+                text(" "),
+                styled(
+                    "old",
+                    Style {
+                        strikethrough: true,
+                        ..Style::default()
+                    }
+                ),
+                text(" "),
+                styled(
+                    "code",
+                    Style {
+                        code: true,
+                        ..Style::default()
+                    }
+                ),
+                text(" "),
+                Inline {
+                    link: Some("https://example.com".to_string()),
+                    ..text("site")
+                },
+                text(" next"),
+            ])]
+        );
+    }
+
+    #[test]
+    fn test_parses_nested_and_task_lists() {
+        let blocks = parse("3. first\n4. second\n   - [x] done\n   - [ ] todo **now**\n");
+
+        assert_eq!(
+            blocks,
+            vec![Block::List {
+                start: Some(3),
+                items: vec![
+                    Item {
+                        checked: None,
+                        blocks: vec![Block::Paragraph(vec![text("first")])],
+                    },
+                    Item {
+                        checked: None,
+                        blocks: vec![
+                            Block::Paragraph(vec![text("second")]),
+                            Block::List {
+                                start: None,
+                                items: vec![
+                                    Item {
+                                        checked: Some(true),
+                                        blocks: vec![Block::Paragraph(vec![text("done")])],
+                                    },
+                                    Item {
+                                        checked: Some(false),
+                                        blocks: vec![Block::Paragraph(vec![
+                                            text("todo "),
+                                            styled(
+                                                "now",
+                                                Style {
+                                                    bold: true,
+                                                    ..Style::default()
+                                                }
+                                            ),
+                                        ])],
+                                    },
+                                ],
+                            },
+                        ],
+                    },
+                ],
+            }]
+        );
+    }
+
+    #[test]
+    fn test_parses_blocks() {
+        let markdown = "Title\n=====\n\n> quoted\n\n```rust extra\nfn main() {}\n```\n\n\
+                        | Name | Size |\n|:-----|-----:|\n| *a* | 1 |\n\n\
+                        See ![diagram](img.png \"t\") here\n\n---\n";
+
+        assert_eq!(
+            parse(markdown),
+            vec![
+                Block::Heading(1, vec![text("Title")]),
+                Block::Quote(vec![Block::Paragraph(vec![text("quoted")])]),
+                Block::Code {
+                    language: Some("rust".to_string()),
+                    code: "fn main() {}\n".to_string(),
+                },
+                Block::Table {
+                    alignments: vec![iced::Alignment::Start, iced::Alignment::End],
+                    header: vec![vec![text("Name")], vec![text("Size")]],
+                    rows: vec![vec![
+                        vec![styled(
+                            "a",
+                            Style {
+                                italic: true,
+                                ..Style::default()
+                            }
+                        )],
+                        vec![text("1")],
+                    ]],
+                },
+                Block::Paragraph(vec![text("See ")]),
+                Block::Image {
+                    src: "img.png".to_string(),
+                    alt: "diagram".to_string(),
+                },
+                Block::Paragraph(vec![text(" here")]),
+                Block::Rule,
+            ]
+        );
+    }
+}
@@ -1,15 +1,35 @@
+#[cfg(feature = "intelligence")]
+pub mod approval_queue;
 pub mod chart;
 pub mod chat;
 pub mod code_block;
+pub mod code_editor;
+pub mod command_palette;
 pub mod context_menu;
 pub mod data_table;
+pub mod form;
+pub mod highlight;
 pub mod markdown;
+pub mod tree_view;
 
-pub use chart::{Chart, ChartDataPoint, ChartType};
+#[cfg(feature = "intelligence")]
+pub use approval_queue::ApprovalQueue;
+pub use chart::{
+    Chart, ChartDataPoint, ChartType, Downsample, Series, SeriesSummary, StreamingChart,
+    TimeSeries, Trend, XAxis,
+};
 #[cfg(feature = "intelligence")]
 pub use chat::AIChatView;
-pub use chat::{ChatMessage, ChatRole, ChatViewMessage};
+pub use chat::{ChatCitation, ChatHistory, ChatMessage, ChatRole, ChatSummary, ChatViewMessage};
 pub use code_block::CodeBlock;
+pub use code_editor::{CodeEditor, EditorMessage, EditorState};
+pub use command_palette::{
+    CommandPalette, CommandPaletteState, CommandRegistry, PaletteCommand, PaletteMessage,
+};
 pub use context_menu::{ContextMenu, ContextMenuItem};
-pub use data_table::DataTable;
+pub use data_table::{
+    CellValue, DataTable, DataTableMessage, DataTableState, ExportFormat, TableSource,
+};
+pub use form::{FieldKind, FormField, FormMessage, FormState, FormView};
 pub use markdown::MarkdownView;
+pub use tree_view::{TreeMessage, TreeMove, TreeNode, TreeState, TreeView};
@@ -0,0 +1,354 @@
+use crate::core::{AccessibilityNode, AccessibilityRole};
+use crate::prelude::*;
+use std::sync::Arc;
+
+pub mod state;
+
+pub use state::{TreeChildren, TreeMessage, TreeMove, TreeNode, TreeState};
+
+/// The width of the disclosure before each node, and of the gap where
+/// leaves have none.
+const DISCLOSURE_WIDTH: f32 = 16.0;
+
+/// A hierarchical list, e.g. of files or the keys of a JSON document, shown
+/// as its [`TreeState`] says.
+pub struct TreeView<M, B>
+where
+    M: 'static + Send + Sync,
+    B: Backend + Send + Sync,
+{
+    pub state: TreeState,
+    pub on_message: Arc<dyn Fn(TreeMessage) -> M + Send + Sync>,
+    pub label: Option<String>,
+    pub indent: f32,
+    pub row_height: f32,
+    _phantom: std::marker::PhantomData<B>,
+}
+
+impl<M, B> TreeView<M, B>
+where
+    M: 'static + Clone + Send + Sync,
+    B: Backend + Send + Sync + 'static,
+{
+    /// A tree of `state`'s nodes, which reports what the user does through
+    /// `on_message`, for [`TreeState::update`].
+    pub fn new(
+        state: &TreeState,
+        on_message: impl Fn(TreeMessage) -> M + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            state: state.clone(),
+            on_message: Arc::new(on_message),
+            label: None,
+            indent: 16.0,
+            row_height: 28.0,
+            _phantom: std::marker::PhantomData,
+        }
+    }
+
+    pub fn label(mut self, label: impl Into<String>) -> Self {
+        self.label = Some(label.into());
+        self
+    }
+
+    /// How far each level is indented.
+    pub fn indent(mut self, indent: f32) -> Self {
+        self.indent = indent;
+        self
+    }
+
+    pub fn row_height(mut self, height: f32) -> Self {
+        self.row_height = height;
+        self
+    }
+
+    fn row(&self, depth: usize, node: &TreeNode, context: &Context) -> B::AnyView<M> {
+        let palette = context.theme.colors;
+        let expanded = self.state.expanded.contains(&node.id);
+        let selected = self.state.selected.contains(&node.id);
+        let focused = self.state.focused.as_ref() == Some(&node.id);
+
+        let mut children = vec![B::space(
+            Length::Fixed(self.indent * depth as f32),
+            Length::Shrink,
+            context,
+        )];
+
+        children.push(if node.has_children() {
+            B::button(
+                B::icon(
+                    if expanded {
+                        "chevron-down".to_string()
+                    } else {
+                        "chevron-right".to_string()
+                    },
+                    12.0,
+                    Some(palette.text_secondary),
+                    context,
+                ),
+                Some((self.on_message)(TreeMessage::Toggle(node.id.clone()))),
+                Variant::Plain,
+                Intent::Neutral,
+                Length::Fixed(DISCLOSURE_WIDTH),
+                Length::Shrink,
+                true,
+                context,
+            )
+        } else {
+            B::space(Length::Fixed(DISCLOSURE_WIDTH), Length::Shrink, context)
+        });
+
+        if let Some(icon) = &node.icon {
+            children.push(B::icon(
+                icon.clone(),
+                14.0,
+                Some(if selected {
+                    palette.primary
+                } else {
+                    palette.text_secondary
+                }),
+                context,
+            ));
+        }
+
+        children.push(B::text(
+            node.label.clone(),
+            13.0,
+            None,
+            false,
+            false,
+            None,
+            None,
+            Length::Shrink,
+            iced::Alignment::Start,
+            context,
+        ));
+
+        if let Some(detail) = &node.detail {
+            children.push(B::text(
+                detail.clone(),
+                12.0,
+                None,
+                false,
+                true,
+                None,
+                None,
+                Length::Fill,
+                iced::Alignment::Start,
+                context,
+            ));
+        }
+
+        let row = B::container(
+            B::hstack(
+                children,
+                6.0,
+                [0, 8].into(),
+                Length::Fill,
+                Length::Fill,
+                iced::Alignment::Start,
+                iced::Alignment::Center,
+                context,
+            ),
+            Padding::default(),
+            Length::Fill,
+            Length::Fixed(self.row_height),
+            selected.then(|| palette.primary.scale_alpha(0.15)),
+            6.0,
+            if focused { 1.0 } else { 0.0 },
+            focused.then(|| palette.primary.scale_alpha(0.5)),
+            None,
+            iced::Alignment::Start,
+            iced::Alignment::Center,
+            context,
+        );
+
+        // Letting go on another node drops the pressed one onto it
+        let on_release = match self.state.pressed() {
+            Some(pressed) if pressed != node.id => TreeMessage::Moved(TreeMove {
+                node: pressed.to_string(),
+                onto: node.id.clone(),
+            }),
+            _ => TreeMessage::Released(node.id.clone()),
+        };
+
+        B::mouse_area(
+            row,
+            None,
+            Some((self.on_message)(TreeMessage::Pressed(node.id.clone()))),
+            Some((self.on_message)(on_release)),
+            context,
+        )
+    }
+
+    fn loading_row(&self, depth: usize, context: &Context) -> B::AnyView<M> {
+        B::hstack(
+            vec![
+                B::space(
+                    Length::Fixed(self.indent * depth as f32 + DISCLOSURE_WIDTH),
+                    Length::Shrink,
+                    context,
+                ),
+                B::text(
+                    "Loading…".to_string(),
+                    12.0,
+                    None,
+                    false,
+                    true,
+                    None,
+                    None,
+                    Length::Fill,
+                    iced::Alignment::Start,
+                    context,
+                ),
+            ],
+            6.0,
+            [0, 8].into(),
+            Length::Fill,
+            Length::Fixed(self.row_height),
+            iced::Alignment::Start,
+            iced::Alignment::Center,
+            context,
+        )
+    }
+
+    fn describe_node(&self, depth: usize, node: &TreeNode) -> SemanticNode {
+        let expanded = self.state.expanded.contains(&node.id);
+        let mut states = Vec::new();
+        if self.state.selected.contains(&node.id) {
+            states.push("selected".into());
+        }
+        if self.state.focused.as_ref() == Some(&node.id) {
+            states.push("focused".into());
+        }
+        if node.children == TreeChildren::Loading {
+            states.push("busy".into());
+        }
+
+        let mut item = SemanticNode::new("treeitem")
+            .with_label(node.label.clone())
+            .with_accessibility(AccessibilityNode {
+                role: AccessibilityRole::TreeItem,
+                label: node.label.clone().into(),
+                value: node.detail.clone().map(Into::into),
+                states,
+                level: Some(depth as u32 + 1),
+                expanded: node.has_children().then_some(expanded),
+                ..Default::default()
+            });
+        item.id = Some(node.id.clone().into());
+        if let Some(detail) = &node.detail {
+            item = item.with_content(detail.clone());
+        }
+
+        match &node.children {
+            TreeChildren::Loaded(children) if expanded => item.extend_children(
+                children
+                    .iter()
+                    .map(|child| self.describe_node(depth + 1, child)),
+            ),
+            _ => item,
+        }
+    }
+}
+
+impl<M, B> View<M, B> for TreeView<M, B>
+where
+    M: 'static + Clone + Send + Sync,
+    B: Backend + Send + Sync + 'static,
+{
+    fn view(&self, context: &Context) -> B::AnyView<M> {
+        let mut rows = Vec::new();
+        for (depth, node) in self.state.visible() {
+            rows.push(self.row(depth, node, context));
+            if node.children == TreeChildren::Loading && self.state.expanded.contains(&node.id) {
+                rows.push(self.loading_row(depth + 1, context));
+            }
+        }
+
+        B::vstack(
+            rows,
+            2.0,
+            Padding::default(),
+            Length::Fill,
+            Length::Shrink,
+            iced::Alignment::Start,
+            iced::Alignment::Start,
+            context,
+        )
+    }
+
+    fn describe(&self, _context: &Context) -> SemanticNode {
+        let label = self.label.clone().unwrap_or_else(|| "Tree".to_string());
+        SemanticNode::new("tree")
+            .with_label(label.clone())
+            .with_accessibility(AccessibilityNode {
+                role: AccessibilityRole::Tree,
+                label: label.into(),
+                states: vec!["multiselectable".into()],
+                ..Default::default()
+            })
+            .extend_children(
+                self.state
+                    .roots()
+                    .iter()
+                    .map(|node| self.describe_node(0, node)),
+            )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::TermBackend;
+
+    fn state() -> TreeState {
+        let mut state = TreeState::new(vec![
+            TreeNode::new("config", "config")
+                .icon("folder")
+                .children(vec![TreeNode::new("config.port", "port").detail("8080")]),
+            TreeNode::new("users", "users").lazy(),
+        ]);
+        let _ = state.update(TreeMessage::Toggle("config".into()), |_| Task::none());
+        let _ = state.update(TreeMessage::Toggle("users".into()), |_| Task::none());
+        let _ = state.update(TreeMessage::Pressed("config.port".into()), |_| Task::none());
+        state
+    }
+
+    #[test]
+    fn test_trees_describe_levels_and_expansion() {
+        let tree = TreeView::<(), TermBackend>::new(&state(), |_| ()).label("settings.json");
+        let description = tree.describe(&Context::default());
+        assert_eq!(
+            description.accessibility.as_ref().map(|a| a.role),
+            Some(AccessibilityRole::Tree)
+        );
+
+        let config = &description.children[0];
+        let a11y = config.accessibility.as_ref().unwrap();
+        assert_eq!((a11y.level, a11y.expanded), (Some(1), Some(true)));
+
+        let port = &config.children[0];
+        let a11y = port.accessibility.as_ref().unwrap();
+        assert_eq!(port.id.as_deref(), Some("config.port"));
+        assert_eq!(port.content.as_deref(), Some("8080"));
+        assert_eq!((a11y.level, a11y.expanded), (Some(2), None));
+        assert_eq!(a11y.states, ["selected", "focused"]);
+
+        let users = description.children[1].accessibility.as_ref().unwrap();
+        assert_eq!(users.states, ["busy"]);
+    }
+
+    #[test]
+    fn test_trees_render_visible_nodes() {
+        let rendered = TreeView::<(), TermBackend>::new(&state(), |_| ()).view(&Context::default());
+        for text in ["config", "port", "8080", "users", "Loading…"] {
+            assert!(
+                rendered.contains(text),
+                "{} missing from {}",
+                text,
+                rendered
+            );
+        }
+    }
+}
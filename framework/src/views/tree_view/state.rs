@@ -0,0 +1,598 @@
+//! The nodes of a [`TreeView`](super::TreeView) and how they are shown.
+//!
+//! A [`TreeState`] in app state holds the nodes along with what is expanded,
+//! selected and focused. The tree reports what the user does as
+//! [`TreeMessage`]s, to be handed back to [`TreeState::update`], which also
+//! takes keys from [`TreeState::subscription`].
+use crate::prelude::*;
+use std::collections::{BTreeSet, HashSet};
+use std::sync::Arc;
+
+/// A node of a tree, whose children may be loaded once it is first expanded.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TreeNode {
+    /// Unique within the tree.
+    pub id: String,
+    pub label: String,
+    pub icon: Option<String>,
+    /// Secondary text after the label, e.g. a value in a JSON inspector.
+    pub detail: Option<String>,
+    pub children: TreeChildren,
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub enum TreeChildren {
+    #[default]
+    Leaf,
+    /// Children to be loaded when the node is expanded.
+    Unloaded,
+    Loading,
+    Loaded(Vec<TreeNode>),
+}
+
+impl TreeNode {
+    pub fn new(id: impl Into<String>, label: impl Into<String>) -> Self {
+        Self {
+            id: id.into(),
+            label: label.into(),
+            icon: None,
+            detail: None,
+            children: TreeChildren::Leaf,
+        }
+    }
+
+    pub fn icon(mut self, icon: impl Into<String>) -> Self {
+        self.icon = Some(icon.into());
+        self
+    }
+
+    pub fn detail(mut self, detail: impl Into<String>) -> Self {
+        self.detail = Some(detail.into());
+        self
+    }
+
+    pub fn children(mut self, children: Vec<TreeNode>) -> Self {
+        self.children = TreeChildren::Loaded(children);
+        self
+    }
+
+    /// Has children, loaded by [`TreeState::update`] when first expanded.
+    pub fn lazy(mut self) -> Self {
+        self.children = TreeChildren::Unloaded;
+        self
+    }
+
+    /// Whether the node can be expanded.
+    pub fn has_children(&self) -> bool {
+        match &self.children {
+            TreeChildren::Leaf => false,
+            TreeChildren::Loaded(children) => !children.is_empty(),
+            TreeChildren::Unloaded | TreeChildren::Loading => true,
+        }
+    }
+}
+
+/// A node dropped onto another.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TreeMove {
+    pub node: String,
+    pub onto: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TreeMessage {
+    /// The disclosure of a node was clicked.
+    Toggle(String),
+    /// The mouse went down on a node, focusing and selecting it.
+    Pressed(String),
+    /// The mouse went up on the node it went down on.
+    Released(String),
+    /// A node was dragged onto another. [`TreeState::update`] leaves the
+    /// tree as it is, so the app decides; [`TreeState::apply_move`] does
+    /// the usual thing.
+    Moved(TreeMove),
+    /// The children of a node, as loaded.
+    Loaded(String, Vec<TreeNode>),
+    KeyPressed(keyboard::Key, keyboard::Modifiers),
+    ModifiersChanged(keyboard::Modifiers),
+    /// Enter was pressed on a node. Left to the app, e.g. to open a file.
+    Activated(String),
+}
+
+/// How a tree is shown. Keep it in app state.
+#[derive(Debug, Clone, Default)]
+pub struct TreeState {
+    pub expanded: HashSet<String>,
+    pub selected: BTreeSet<String>,
+    /// The node keys act on.
+    pub focused: Option<String>,
+    roots: Arc<Vec<TreeNode>>,
+    /// Where a range selection starts from.
+    anchor: Option<String>,
+    pressed: Option<String>,
+    modifiers: keyboard::Modifiers,
+}
+
+impl TreeState {
+    pub fn new(roots: Vec<TreeNode>) -> Self {
+        Self {
+            roots: Arc::new(roots),
+            ..Self::default()
+        }
+    }
+
+    pub fn roots(&self) -> &[TreeNode] {
+        &self.roots
+    }
+
+    pub fn set_roots(&mut self, roots: Vec<TreeNode>) {
+        self.roots = Arc::new(roots);
+    }
+
+    /// The node being dragged, if any.
+    pub fn pressed(&self) -> Option<&str> {
+        self.pressed.as_deref()
+    }
+
+    pub fn find(&self, id: &str) -> Option<&TreeNode> {
+        find(&self.roots, id)
+    }
+
+    /// The nodes shown, depth-first through the expanded ones, with their
+    /// depth from 0 for the roots.
+    pub fn visible(&self) -> Vec<(usize, &TreeNode)> {
+        let mut visible = Vec::new();
+        self.push_visible(&self.roots, 0, &mut visible);
+        visible
+    }
+
+    fn push_visible<'a>(
+        &self,
+        nodes: &'a [TreeNode],
+        depth: usize,
+        visible: &mut Vec<(usize, &'a TreeNode)>,
+    ) {
+        for node in nodes {
+            visible.push((depth, node));
+            if let TreeChildren::Loaded(children) = &node.children {
+                if self.expanded.contains(&node.id) {
+                    self.push_visible(children, depth + 1, visible);
+                }
+            }
+        }
+    }
+
+    /// Handles a message from the tree. Expanding a node whose children
+    /// aren't loaded yet calls `load`, whose task's children come back as
+    /// [`TreeMessage::Loaded`].
+    pub fn update(
+        &mut self,
+        message: TreeMessage,
+        load: impl Fn(&TreeNode) -> Task<Vec<TreeNode>>,
+    ) -> Task<TreeMessage> {
+        match message {
+            TreeMessage::Toggle(id) => {
+                if self.expanded.contains(&id) {
+                    self.collapse(&id);
+                } else {
+                    return self.expand(&id, load);
+                }
+            }
+            TreeMessage::Pressed(id) => {
+                self.pressed = Some(id.clone());
+                self.focus(id, self.modifiers, true);
+            }
+            TreeMessage::Released(_) | TreeMessage::Moved(_) => self.pressed = None,
+            TreeMessage::Loaded(id, children) => {
+                if let Some(node) = find_mut(Arc::make_mut(&mut self.roots).as_mut_slice(), &id) {
+                    node.children = TreeChildren::Loaded(children);
+                }
+            }
+            TreeMessage::KeyPressed(key, modifiers) => {
+                self.modifiers = modifiers;
+                return self.key_pressed(key, modifiers, load);
+            }
+            TreeMessage::ModifiersChanged(modifiers) => self.modifiers = modifiers,
+            TreeMessage::Activated(_) => {}
+        }
+        Task::none()
+    }
+
+    /// Keys and modifiers not taken by a widget, e.g. a text input.
+    /// Subscribe while the tree is the thing keys are meant for.
+    pub fn subscription() -> Subscription<TreeMessage> {
+        event::listen_with(|event, status, _window| match (event, status) {
+            (
+                Event::Keyboard(keyboard::Event::KeyPressed { key, modifiers, .. }),
+                event::Status::Ignored,
+            ) => Some(TreeMessage::KeyPressed(key, modifiers)),
+            (Event::Keyboard(keyboard::Event::ModifiersChanged(modifiers)), _) => {
+                Some(TreeMessage::ModifiersChanged(modifiers))
+            }
+            _ => None,
+        })
+    }
+
+    /// Expands node `id`, starting to load its children if they aren't.
+    pub fn expand(
+        &mut self,
+        id: &str,
+        load: impl Fn(&TreeNode) -> Task<Vec<TreeNode>>,
+    ) -> Task<TreeMessage> {
+        let Some(node) = find_mut(Arc::make_mut(&mut self.roots).as_mut_slice(), id) else {
+            return Task::none();
+        };
+        if !node.has_children() {
+            return Task::none();
+        }
+        self.expanded.insert(id.to_string());
+
+        if node.children != TreeChildren::Unloaded {
+            return Task::none();
+        }
+        node.children = TreeChildren::Loading;
+        let id = id.to_string();
+        load(node).map(move |children| TreeMessage::Loaded(id.clone(), children))
+    }
+
+    /// Collapses node `id`, taking focus from any node it hides.
+    pub fn collapse(&mut self, id: &str) {
+        let hides_focus = self.focused.as_deref().is_some_and(|focused| {
+            focused != id
+                && self
+                    .find(id)
+                    .is_some_and(|node| find(std::slice::from_ref(node), focused).is_some())
+        });
+        if hides_focus {
+            self.focused = Some(id.to_string());
+        }
+        self.expanded.remove(id);
+    }
+
+    /// Moves `node` into the node it was dropped onto if that has loaded
+    /// children, otherwise just before it. Returns whether it moved: nodes
+    /// can't go inside themselves, or into children still to be loaded.
+    pub fn apply_move(&mut self, movement: &TreeMove) -> bool {
+        let Some(node) = self.find(&movement.node) else {
+            return false;
+        };
+        if find(std::slice::from_ref(node), &movement.onto).is_some() {
+            return false;
+        }
+        let into = match self.find(&movement.onto).map(|onto| &onto.children) {
+            Some(TreeChildren::Loaded(_)) => true,
+            Some(TreeChildren::Leaf) => false,
+            _ => return false,
+        };
+
+        let roots = Arc::make_mut(&mut self.roots);
+        let Some(node) = detach(roots, &movement.node) else {
+            return false;
+        };
+        if into {
+            if let Some(onto) = find_mut(roots, &movement.onto) {
+                onto.children = match std::mem::take(&mut onto.children) {
+                    TreeChildren::Loaded(mut children) => {
+                        children.push(node);
+                        TreeChildren::Loaded(children)
+                    }
+                    _ => TreeChildren::Loaded(vec![node]),
+                };
+            }
+            self.expanded.insert(movement.onto.clone());
+        } else {
+            insert_before(roots, &movement.onto, node);
+        }
+        true
+    }
+
+    fn key_pressed(
+        &mut self,
+        key: keyboard::Key,
+        modifiers: keyboard::Modifiers,
+        load: impl Fn(&TreeNode) -> Task<Vec<TreeNode>>,
+    ) -> Task<TreeMessage> {
+        use keyboard::key::Named;
+
+        let visible: Vec<(usize, String, bool)> = self
+            .visible()
+            .into_iter()
+            .map(|(depth, node)| (depth, node.id.clone(), node.has_children()))
+            .collect();
+        let Some(last) = visible.len().checked_sub(1) else {
+            return Task::none();
+        };
+        let position = self
+            .focused
+            .as_ref()
+            .and_then(|focused| visible.iter().position(|(_, id, _)| id == focused));
+
+        let target = match key.as_ref() {
+            keyboard::Key::Named(Named::ArrowDown) => {
+                Some(position.map_or(0, |position| (position + 1).min(last)))
+            }
+            keyboard::Key::Named(Named::ArrowUp) => {
+                Some(position.map_or(last, |position| position.saturating_sub(1)))
+            }
+            keyboard::Key::Named(Named::Home) => Some(0),
+            keyboard::Key::Named(Named::End) => Some(last),
+            keyboard::Key::Named(Named::ArrowRight) => {
+                let Some(position) = position else {
+                    return Task::none();
+                };
+                let (depth, id, has_children) = &visible[position];
+                if !has_children {
+                    None
+                } else if !self.expanded.contains(id) {
+                    return self.expand(id, load);
+                } else {
+                    // Onto the first child
+                    visible
+                        .get(position + 1)
+                        .filter(|(child_depth, _, _)| child_depth > depth)
+                        .map(|_| position + 1)
+                }
+            }
+            keyboard::Key::Named(Named::ArrowLeft) => {
+                let Some(position) = position else {
+                    return Task::none();
+                };
+                let (depth, id, _) = &visible[position];
+                if self.expanded.contains(id) {
+                    self.collapse(id);
+                    None
+                } else {
+                    // Onto the parent
+                    visible[..position]
+                        .iter()
+                        .rposition(|(parent_depth, _, _)| parent_depth < depth)
+                }
+            }
+            keyboard::Key::Named(Named::Enter) => {
+                return match &self.focused {
+                    Some(id) => Task::done(TreeMessage::Activated(id.clone())),
+                    None => Task::none(),
+                };
+            }
+            keyboard::Key::Named(Named::Space) => {
+                if let Some(id) = self.focused.clone() {
+                    if !self.selected.remove(&id) {
+                        self.selected.insert(id.clone());
+                    }
+                    self.anchor = Some(id);
+                }
+                None
+            }
+            keyboard::Key::Character("a") if modifiers.command() => {
+                self.selected = visible.iter().map(|(_, id, _)| id.clone()).collect();
+                None
+            }
+            _ => None,
+        };
+
+        if let Some(target) = target {
+            self.focus(visible[target].1.clone(), modifiers, false);
+        }
+        Task::none()
+    }
+
+    /// Focuses node `id`. With shift, selects from the anchor to it; with
+    /// command, a click adds or removes it and a key only moves focus;
+    /// otherwise it becomes the only selected node.
+    fn focus(&mut self, id: String, modifiers: keyboard::Modifiers, click: bool) {
+        if modifiers.shift() {
+            let anchor = self.anchor.clone().or_else(|| self.focused.clone());
+            self.selected = self.range(anchor.as_deref().unwrap_or(&id), &id);
+        } else if modifiers.command() {
+            if click && !self.selected.remove(&id) {
+                self.selected.insert(id.clone());
+            }
+            self.anchor = Some(id.clone());
+        } else {
+            self.selected = BTreeSet::from([id.clone()]);
+            self.anchor = Some(id.clone());
+        }
+        self.focused = Some(id);
+    }
+
+    /// The visible nodes from `from` to `to`, either way round.
+    fn range(&self, from: &str, to: &str) -> BTreeSet<String> {
+        let visible = self.visible();
+        let position = |id: &str| visible.iter().position(|(_, node)| node.id == id);
+        let (Some(from), Some(to)) = (position(from), position(to)) else {
+            return BTreeSet::from([to.to_string()]);
+        };
+        visible[from.min(to)..=from.max(to)]
+            .iter()
+            .map(|(_, node)| node.id.clone())
+            .collect()
+    }
+}
+
+fn find<'a>(nodes: &'a [TreeNode], id: &str) -> Option<&'a TreeNode> {
+    nodes.iter().find_map(|node| {
+        if node.id == id {
+            return Some(node);
+        }
+        match &node.children {
+            TreeChildren::Loaded(children) => find(children, id),
+            _ => None,
+        }
+    })
+}
+
+fn find_mut<'a>(nodes: &'a mut [TreeNode], id: &str) -> Option<&'a mut TreeNode> {
+    nodes.iter_mut().find_map(|node| {
+        if node.id == id {
+            return Some(node);
+        }
+        match &mut node.children {
+            TreeChildren::Loaded(children) => find_mut(children, id),
+            _ => None,
+        }
+    })
+}
+
+/// Takes node `id` out of the tree.
+fn detach(nodes: &mut Vec<TreeNode>, id: &str) -> Option<TreeNode> {
+    if let Some(position) = nodes.iter().position(|node| node.id == id) {
+        return Some(nodes.remove(position));
+    }
+    nodes.iter_mut().find_map(|node| match &mut node.children {
+        TreeChildren::Loaded(children) => detach(children, id),
+        _ => None,
+    })
+}
+
+/// Puts `node` just before node `target`, among its siblings.
+fn insert_before(nodes: &mut Vec<TreeNode>, target: &str, node: TreeNode) -> Option<TreeNode> {
+    if let Some(position) = nodes.iter().position(|sibling| sibling.id == target) {
+        nodes.insert(position, node);
+        return None;
+    }
+    let mut node = Some(node);
+    for sibling in nodes.iter_mut() {
+        if let TreeChildren::Loaded(children) = &mut sibling.children {
+            node = insert_before(children, target, node?);
+        }
+    }
+    node
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+
+    fn files() -> TreeState {
+        TreeState::new(vec![
+            TreeNode::new("src", "src").children(vec![
+                TreeNode::new("main.rs", "main.rs"),
+                TreeNode::new("views", "views").lazy(),
+            ]),
+            TreeNode::new("Cargo.toml", "Cargo.toml"),
+        ])
+    }
+
+    fn key(named: keyboard::key::Named) -> TreeMessage {
+        TreeMessage::KeyPressed(keyboard::Key::Named(named), keyboard::Modifiers::default())
+    }
+
+    fn ids(state: &TreeState) -> Vec<&str> {
+        state
+            .visible()
+            .into_iter()
+            .map(|(_, node)| node.id.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn test_children_load_when_first_expanded() {
+        let mut state = files();
+        let loaded = RefCell::new(Vec::new());
+        let load = |node: &TreeNode| {
+            loaded.borrow_mut().push(node.id.clone());
+            Task::none()
+        };
+
+        let _ = state.update(TreeMessage::Toggle("src".into()), load);
+        let _ = state.update(TreeMessage::Toggle("views".into()), load);
+        assert_eq!(*loaded.borrow(), ["views"]);
+        assert_eq!(state.find("views").unwrap().children, TreeChildren::Loading);
+
+        let children = vec![TreeNode::new("chart.rs", "chart.rs")];
+        let _ = state.update(TreeMessage::Loaded("views".into(), children), load);
+        assert_eq!(
+            ids(&state),
+            ["src", "main.rs", "views", "chart.rs", "Cargo.toml"]
+        );
+
+        // Already loaded, so not again
+        let _ = state.update(TreeMessage::Toggle("views".into()), load);
+        let _ = state.update(TreeMessage::Toggle("views".into()), load);
+        assert_eq!(loaded.borrow().len(), 1);
+    }
+
+    #[test]
+    fn test_arrow_keys_walk_the_tree() {
+        use keyboard::key::Named;
+
+        let mut state = files();
+        let load = |_: &TreeNode| Task::none();
+
+        let _ = state.update(key(Named::ArrowDown), load);
+        assert_eq!(state.focused.as_deref(), Some("src"));
+
+        // Right expands, then goes to the first child
+        let _ = state.update(key(Named::ArrowRight), load);
+        let _ = state.update(key(Named::ArrowRight), load);
+        assert_eq!(state.focused.as_deref(), Some("main.rs"));
+        assert_eq!(state.selected, BTreeSet::from(["main.rs".to_string()]));
+
+        let _ = state.update(key(Named::End), load);
+        assert_eq!(state.focused.as_deref(), Some("Cargo.toml"));
+
+        // Left from a child goes to its parent, then collapses it
+        let _ = state.update(key(Named::ArrowUp), load);
+        let _ = state.update(key(Named::ArrowLeft), load);
+        assert_eq!(state.focused.as_deref(), Some("src"));
+        let _ = state.update(key(Named::ArrowLeft), load);
+        assert_eq!(ids(&state), ["src", "Cargo.toml"]);
+    }
+
+    #[test]
+    fn test_modifiers_select_ranges_and_more() {
+        let mut state = files();
+        let load = |_: &TreeNode| Task::none();
+        let _ = state.update(TreeMessage::Toggle("src".into()), load);
+
+        let _ = state.update(TreeMessage::Pressed("main.rs".into()), load);
+        let _ = state.update(
+            TreeMessage::ModifiersChanged(keyboard::Modifiers::SHIFT),
+            load,
+        );
+        let _ = state.update(TreeMessage::Pressed("Cargo.toml".into()), load);
+        assert_eq!(state.selected.len(), 3);
+
+        let _ = state.update(
+            TreeMessage::ModifiersChanged(keyboard::Modifiers::COMMAND),
+            load,
+        );
+        let _ = state.update(TreeMessage::Pressed("views".into()), load);
+        let _ = state.update(TreeMessage::Pressed("src".into()), load);
+        assert_eq!(
+            state.selected,
+            BTreeSet::from(["Cargo.toml".to_string(), "main.rs".into(), "src".into()])
+        );
+    }
+
+    #[test]
+    fn test_moves_go_inside_or_before() {
+        let mut state = files();
+
+        // Onto a folder goes inside it; onto a file goes before it
+        assert!(state.apply_move(&TreeMove {
+            node: "Cargo.toml".into(),
+            onto: "src".into(),
+        }));
+        assert_eq!(ids(&state), ["src", "main.rs", "views", "Cargo.toml"]);
+        assert!(state.apply_move(&TreeMove {
+            node: "Cargo.toml".into(),
+            onto: "main.rs".into(),
+        }));
+        assert_eq!(ids(&state), ["src", "Cargo.toml", "main.rs", "views"]);
+
+        // Not into itself, nor into children that aren't loaded
+        for onto in ["main.rs", "views"] {
+            assert!(!state.apply_move(&TreeMove {
+                node: "src".into(),
+                onto: onto.into(),
+            }));
+        }
+        assert!(!state.apply_move(&TreeMove {
+            node: "main.rs".into(),
+            onto: "views".into(),
+        }));
+        assert_eq!(ids(&state), ["src", "Cargo.toml", "main.rs", "views"]);
+    }
+}